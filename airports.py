@@ -0,0 +1,96 @@
+# ABOUTME: ICAO/IATA airport code lookups for /airport/<code> routes
+# ABOUTME: Bundled major airports, or an OurAirports airports.csv for everything
+
+import csv
+import json
+import re
+from pathlib import Path
+from typing import Any
+
+
+AIRPORTS_PATH = Path(__file__).with_name('airports.json')
+
+AIRPORT_CODE_PATTERN = re.compile(r'^[A-Z0-9]{3,4}$')
+IATA_CODE_LENGTH = 3
+
+# OurAirports types worth routing to (skips heliports, closed fields, etc.)
+OURAIRPORTS_TYPES = {'large_airport', 'medium_airport', 'small_airport'}
+
+
+def airport_record(
+    icao: str, iata: str, name: str, lat: float, lon: float
+) -> dict[str, Any]:
+    """An airport in the shape the index stores and the API returns"""
+    return {
+        'icao': icao.upper() or None,
+        'iata': iata.upper() or None,
+        'name': name,
+        'lat': lat,
+        'lon': lon,
+    }
+
+
+def load_bundled_airports(path: Path) -> list[dict[str, Any]]:
+    """Airports from the bundled JSON list"""
+    with open(path, encoding='utf-8') as f:
+        entries = json.load(f)['airports']
+    return [
+        airport_record(
+            entry.get('icao', ''),
+            entry.get('iata', ''),
+            entry['name'],
+            float(entry['lat']),
+            float(entry['lon']),
+        )
+        for entry in entries
+    ]
+
+
+def load_ourairports_csv(path: Path) -> list[dict[str, Any]]:
+    """Airports from an OurAirports airports.csv export"""
+    airports = []
+    with open(path, encoding='utf-8', newline='') as f:
+        for row in csv.DictReader(f):
+            if row.get('type') not in OURAIRPORTS_TYPES:
+                continue
+            icao = row.get('icao_code') or row.get('gps_code') or ''
+            try:
+                lat, lon = float(row['latitude_deg']), float(row['longitude_deg'])
+            except (KeyError, ValueError):
+                continue
+            airports.append(
+                airport_record(icao, row.get('iata_code', ''), row['name'], lat, lon)
+            )
+    return airports
+
+
+class AirportIndex:
+    """Airports by ICAO (4 characters) and IATA (3 characters) code"""
+
+    def __init__(self, path: str | Path = AIRPORTS_PATH) -> None:
+        path = Path(path)
+        self.by_icao: dict[str, dict[str, Any]] = {}
+        self.by_iata: dict[str, dict[str, Any]] = {}
+        try:
+            if path.suffix == '.csv':
+                airports = load_ourairports_csv(path)
+            else:
+                airports = load_bundled_airports(path)
+        except (OSError, ValueError, KeyError) as e:
+            print(f'❌ Could not load airports from {path}: {str(e)}')
+            return
+        for airport in airports:
+            # The first airport listed for a code wins
+            if airport['icao']:
+                self.by_icao.setdefault(airport['icao'], airport)
+            if airport['iata']:
+                self.by_iata.setdefault(airport['iata'], airport)
+
+    def lookup(self, code: str) -> dict[str, Any] | None:
+        """The airport for an ICAO or IATA code, case-insensitive"""
+        code = code.strip().upper()
+        if not AIRPORT_CODE_PATTERN.match(code):
+            return None
+        if len(code) == IATA_CODE_LENGTH:
+            return self.by_iata.get(code)
+        return self.by_icao.get(code)
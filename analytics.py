@@ -0,0 +1,120 @@
+# ABOUTME: Opt-in aggregate usage counters (per endpoint, location and device class)
+# ABOUTME: Only counts and last-seen dates are kept - never IPs or raw user agents
+
+import json
+import os
+import threading
+import time
+from datetime import date, datetime, timezone
+from typing import Any
+
+
+_DEVICE_MARKERS = (
+    ('bot', ('bot', 'crawler', 'spider', 'curl', 'wget', 'python-requests')),
+    ('tablet', ('ipad', 'tablet', 'kindle')),
+    ('mobile', ('mobile', 'iphone', 'android')),
+    ('desktop', ('windows', 'macintosh', 'x11', 'linux', 'cros')),
+)
+
+
+def device_class(user_agent: str | None) -> str:
+    """Reduce a User-Agent to a coarse device class"""
+    agent = (user_agent or '').lower()
+    for name, markers in _DEVICE_MARKERS:
+        if any(marker in agent for marker in markers):
+            return name
+    return 'other'
+
+
+class UsageAnalytics:
+    """Aggregate request counters, flushed to JSON at most every flush_interval"""
+
+    def __init__(self, path: str, flush_interval: float = 60) -> None:
+        self.path = path
+        self.flush_interval = flush_interval
+        self._lock = threading.Lock()
+        self._last_flush = time.monotonic()
+        self._dirty = False
+        self.reset()
+        self.load()
+
+    def reset(self) -> None:
+        """Start counting from scratch"""
+        self.stats: dict[str, Any] = {
+            'since': datetime.now(timezone.utc).date().isoformat(),
+            'endpoints': {},
+            'devices': {},
+            'locations': {},
+        }
+        self._dirty = True
+
+    def load(self) -> None:
+        """Load saved counters, starting empty if the file is missing or unreadable"""
+        try:
+            with open(self.path, encoding='utf-8') as f:
+                stats = json.load(f)
+        except FileNotFoundError:
+            return
+        except (OSError, ValueError) as e:
+            print(f'❌ Could not read analytics {self.path}: {str(e)}')
+            return
+        with self._lock:
+            self.stats.update(stats)
+            self._dirty = False
+
+    def save(self) -> None:
+        """Write counters atomically (lock held)"""
+        directory = os.path.dirname(self.path)
+        if directory:
+            os.makedirs(directory, exist_ok=True)
+        temp_path = f'{self.path}.tmp'
+        with open(temp_path, 'w', encoding='utf-8') as f:
+            json.dump(self.stats, f, indent=2)
+        os.replace(temp_path, self.path)
+        self._dirty = False
+        self._last_flush = time.monotonic()
+
+    def record(self, endpoint: str, device: str, location: str | None = None) -> None:
+        """Count one request, flushing to disk once the interval has passed"""
+        today = datetime.now(timezone.utc).date().isoformat()
+        with self._lock:
+            endpoints = self.stats['endpoints']
+            endpoints[endpoint] = endpoints.get(endpoint, 0) + 1
+            devices = self.stats['devices']
+            devices[device] = devices.get(device, 0) + 1
+            if location:
+                entry = self.stats['locations'].setdefault(
+                    location, {'count': 0, 'last_seen': today}
+                )
+                entry['count'] += 1
+                entry['last_seen'] = today
+            self._dirty = True
+            if time.monotonic() - self._last_flush >= self.flush_interval:
+                self.flush_locked()
+
+    def flush_locked(self) -> None:
+        """Save pending counts (lock held), logging instead of failing requests"""
+        try:
+            self.save()
+        except OSError as e:
+            print(f'❌ Could not write analytics {self.path}: {str(e)}')
+            self._last_flush = time.monotonic()
+
+    def flush(self) -> None:
+        """Save pending counts now"""
+        with self._lock:
+            if self._dirty:
+                self.flush_locked()
+
+    def snapshot(self, stale_days: int) -> dict[str, Any]:
+        """Copy the counters, listing locations unseen for stale_days or more"""
+        today = datetime.now(timezone.utc).date()
+        with self._lock:
+            stats = json.loads(json.dumps(self.stats))
+        stats['stale_locations'] = sorted(
+            name
+            for name, entry in stats['locations'].items()
+            if (today - date.fromisoformat(entry['last_seen'])).days
+            >= stale_days
+        )
+        return stats
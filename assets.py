@@ -0,0 +1,50 @@
+# ABOUTME: Content hashes for static assets so pages can link cache-busting URLs
+# ABOUTME: Versioned URLs are cached for a year; unversioned ones always revalidate
+
+import hashlib
+from pathlib import Path
+
+
+ASSET_HASH_LENGTH = 12
+IMMUTABLE_CACHE_CONTROL = 'public, max-age=31536000, immutable'
+REVALIDATE_CACHE_CONTROL = 'no-cache'
+
+
+class AssetManifest:
+    """Content hashes of every file under the static directory, by relative path"""
+
+    def __init__(self, root: str | Path) -> None:
+        self.root = Path(root)
+        self.hashes: dict[str, str] = {}
+        if not self.root.is_dir():
+            print(f'⚠️  No static directory at {self.root}')
+            return
+        for path in sorted(self.root.rglob('*')):
+            if path.is_file():
+                digest = hashlib.sha256(path.read_bytes()).hexdigest()
+                relative = path.relative_to(self.root).as_posix()
+                self.hashes[relative] = digest[:ASSET_HASH_LENGTH]
+
+    def version(self, path: str) -> str | None:
+        """The content hash for an asset, or None if it isn't in the manifest"""
+        return self.hashes.get(path)
+
+    def fingerprint(self) -> str:
+        """A hash over every asset, changing whenever any of them does"""
+        combined = ''.join(f'{path}:{digest}' for path, digest in self.hashes.items())
+        return hashlib.sha256(combined.encode()).hexdigest()[:ASSET_HASH_LENGTH]
+
+    def url(self, path: str) -> str:
+        """The asset's URL, versioned with its content hash when known"""
+        version = self.version(path)
+        if version is None:
+            return f'/static/{path}'
+        return f'/static/{path}?v={version}'
+
+    def cache_control(self, path: str, requested_version: str | None) -> str:
+        """Long-lived caching only when the URL names the current content"""
+        version = self.version(path)
+        if version is not None and requested_version == version:
+            return IMMUTABLE_CACHE_CONTROL
+        # Unversioned or stale links still get the current file, just revalidated
+        return REVALIDATE_CACHE_CONTROL
@@ -0,0 +1,123 @@
+# ABOUTME: Bundled astronomical events (meteor showers, eclipses, supermoons)
+# ABOUTME: Rates each event's local visibility from sky darkness, moonlight and clouds
+
+import json
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
+from typing import Any
+
+
+ASTRO_EVENTS_PATH = Path(__file__).with_name('astro_events.json')
+
+# Meteor rates peak before dawn, when the observer faces into Earth's orbit
+METEOR_VIEWING_HOUR = 4  # Local solar time
+CIVIL_TWILIGHT = -6  # Sun elevation (degrees) below which the sky is dark enough
+CLOUDY_PERCENT = 70
+PARTLY_CLOUDY_PERCENT = 30
+BRIGHT_MOON_PERCENT = 75
+HALF_MOON_PERCENT = 40
+VIEWING_RATINGS = ('good', 'fair', 'poor')
+
+
+def load_astro_events(path: Path = ASTRO_EVENTS_PATH) -> list[dict[str, Any]]:
+    """Load the bundled event list, empty if the file is missing or invalid"""
+    try:
+        with open(path, encoding='utf-8') as f:
+            return list(json.load(f)['events'])
+    except (OSError, ValueError, KeyError) as e:
+        print(f'❌ Could not load astronomical events from {path}: {str(e)}')
+        return []
+
+
+def event_time(event: dict[str, Any], lon: float) -> datetime:
+    """When to look: pre-dawn on a shower's peak night, else the event itself"""
+    if event['type'] == 'meteor_shower':
+        peak = datetime.fromisoformat(event['peak_date']).replace(tzinfo=timezone.utc)
+        # Local solar time runs lon/15 hours ahead of UTC
+        return peak + timedelta(hours=METEOR_VIEWING_HOUR - lon / 15)
+    return datetime.fromisoformat(event['time'].replace('Z', '+00:00'))
+
+
+def radiant_max_altitude(lat: float, declination: float) -> float:
+    """Highest altitude (degrees) a shower's radiant reaches from a latitude"""
+    return 90 - abs(lat - declination)
+
+
+def in_regions(lat: float, lon: float, regions: list[list[float]]) -> bool:
+    """Check a point falls in any [south, west, north, east] box"""
+    return any(
+        south <= lat <= north and west <= lon <= east
+        for south, west, north, east in regions
+    )
+
+
+def rate_clouds(cloud_cover: float | None) -> tuple[str | None, str]:
+    """Viewing rating and reason from forecast cloud cover"""
+    if cloud_cover is None:
+        return None, 'Cloud forecast not available yet'
+    if cloud_cover >= CLOUDY_PERCENT:
+        return 'poor', f'{cloud_cover:.0f}% cloud cover forecast'
+    if cloud_cover >= PARTLY_CLOUDY_PERCENT:
+        return 'fair', f'{cloud_cover:.0f}% cloud cover forecast'
+    return 'good', f'Mostly clear skies forecast ({cloud_cover:.0f}% cloud)'
+
+
+def rate_moonlight(moon_illumination: float) -> tuple[str, str]:
+    """Viewing rating and reason from how much moonlight washes out meteors"""
+    if moon_illumination >= BRIGHT_MOON_PERCENT:
+        return 'poor', f'Bright moon ({moon_illumination:.0f}% lit) hides faint meteors'
+    if moon_illumination >= HALF_MOON_PERCENT:
+        return 'fair', f'Moon {moon_illumination:.0f}% lit - some interference'
+    return 'good', f'Dark skies - moon only {moon_illumination:.0f}% lit'
+
+
+def assess_visibility(
+    event: dict[str, Any],
+    lat: float,
+    lon: float,
+    sun_elevation: float,
+    moon_illumination: float,
+    cloud_cover: float | None,
+) -> dict[str, Any]:
+    """Rate an event's local visibility: geometry first, then moonlight and clouds"""
+    event_type = event['type']
+    reasons: list[str] = []
+    blocked = False
+
+    if event_type == 'meteor_shower':
+        if radiant_max_altitude(lat, event['radiant_declination']) <= 0:
+            blocked = True
+            reasons.append('Radiant never rises at this latitude')
+        if sun_elevation > CIVIL_TWILIGHT:
+            blocked = True
+            reasons.append('Sky too bright at the viewing time')
+    elif event_type in ('lunar_eclipse', 'supermoon'):
+        # A full moon sits opposite the sun, so it's up when the sun is down
+        if sun_elevation > 0:
+            blocked = True
+            reasons.append('Moon below the horizon')
+    elif event_type == 'solar_eclipse':
+        if not in_regions(lat, lon, event.get('regions', [])):
+            blocked = True
+            visible_from = event.get('visible_from', 'elsewhere')
+            reasons.append(f'Only visible from {visible_from}')
+        elif sun_elevation <= 0:
+            blocked = True
+            reasons.append('Sun below the horizon')
+
+    if blocked:
+        return {'visible': False, 'rating': 'not_visible', 'reasons': reasons}
+
+    ratings = []
+    if event_type == 'meteor_shower':
+        moon_rating, moon_reason = rate_moonlight(moon_illumination)
+        ratings.append(moon_rating)
+        reasons.append(moon_reason)
+    cloud_rating, cloud_reason = rate_clouds(cloud_cover)
+    if cloud_rating:
+        ratings.append(cloud_rating)
+    reasons.append(cloud_reason)
+
+    # The worst factor decides, e.g. a dark sky doesn't help under full overcast
+    rating = max(ratings, key=VIEWING_RATINGS.index, default='good')
+    return {'visible': True, 'rating': rating, 'reasons': reasons}
@@ -0,0 +1,274 @@
+# ABOUTME: Backup and restore of the server's state - the SQLite history and favorites
+# ABOUTME: and the JSON token, rule, webhook, city and analytics stores - as one tar
+
+import hashlib
+import io
+import json
+import os
+import shutil
+import sqlite3
+import subprocess
+import tarfile
+import tempfile
+import time
+from collections.abc import Mapping
+from typing import Any
+
+
+BACKUP_VERSION = 1
+MANIFEST_NAME = 'manifest.json'
+
+# Archive member: the setting naming where it lives, its default and its kind.
+# Chat webhook URLs and everything else configured in the environment or config
+# file belong with that configuration, not here
+STATE_FILES = {
+    'history.db': ('HISTORY_DB_PATH', 'data/history.db', 'sqlite'),
+    'favorites.db': ('FAVORITES_DB_PATH', 'data/favorites.db', 'sqlite'),
+    'notification_rules.json': (
+        'NOTIFICATION_RULES_PATH',
+        'data/notification_rules.json',
+        'json',
+    ),
+    'webhooks.json': ('WEBHOOKS_PATH', 'data/webhooks.json', 'json'),
+    'tokens.json': ('TOKEN_STORE_PATH', 'data/tokens.json', 'json'),
+    'cities.json': ('CITY_REGISTRY_PATH', 'data/cities.json', 'json'),
+    'analytics.json': ('ANALYTICS_PATH', 'data/analytics.json', 'json'),
+}
+
+# Archive suffix: tarfile's compression, or 'zst' for the zstd command, which
+# Python's standard library can't do before 3.14
+ARCHIVE_SUFFIXES = {
+    '.tar': '',
+    '.tar.gz': 'gz',
+    '.tgz': 'gz',
+    '.tar.xz': 'xz',
+    '.tar.bz2': 'bz2',
+    '.tar.zst': 'zst',
+}
+ZSTD_TIMEOUT = 600
+
+
+def state_paths(environ: Mapping[str, str]) -> dict[str, str]:
+    """Where each state file lives with these settings"""
+    return {
+        name: environ.get(setting) or default
+        for name, (setting, default, _) in STATE_FILES.items()
+    }
+
+
+def archive_compression(path: str) -> str:
+    """The compression an archive's name asks for"""
+    for suffix, compression in ARCHIVE_SUFFIXES.items():
+        if path.endswith(suffix):
+            if compression == 'zst' and shutil.which('zstd') is None:
+                msg = '.tar.zst archives need the zstd command; use .tar.gz instead'
+                raise ValueError(msg)
+            return compression
+    msg = f'Backups must be named {", ".join(ARCHIVE_SUFFIXES)}, not {path!r}'
+    raise ValueError(msg)
+
+
+def run_zstd(args: list[str]) -> None:
+    """Run the zstd command, raising OSError if it fails"""
+    result = subprocess.run(  # noqa: S603
+        ['zstd', '-q', '-f', *args],  # noqa: S607
+        capture_output=True,
+        text=True,
+        timeout=ZSTD_TIMEOUT,
+        check=False,
+    )
+    if result.returncode:
+        msg = f'zstd failed: {result.stderr.strip() or result.returncode}'
+        raise OSError(msg)
+
+
+def snapshot_sqlite(path: str, target: str) -> None:
+    """A consistent copy of a database, even while the server is writing to it"""
+    source = sqlite3.connect(f'file:{path}?mode=ro', uri=True)
+    copy = sqlite3.connect(target)
+    try:
+        source.backup(copy)
+    finally:
+        copy.close()
+        source.close()
+
+
+def file_sha256(path: str) -> str:
+    """A file's SHA-256 as hex"""
+    digest = hashlib.sha256()
+    with open(path, 'rb') as f:
+        for block in iter(lambda: f.read(1 << 20), b''):
+            digest.update(block)
+    return digest.hexdigest()
+
+
+def create_backup(
+    out_path: str, paths: Mapping[str, str], now: float | None = None
+) -> dict[str, Any]:
+    """Write the state files that exist to an archive, returning its manifest
+
+    The archive is written beside out_path and moved into place when
+    complete, so a failed backup never leaves a truncated one behind.
+    """
+    compression = archive_compression(out_path)
+    directory = os.path.dirname(out_path)
+    if directory:
+        os.makedirs(directory, exist_ok=True)
+    manifest: dict[str, Any] = {
+        'version': BACKUP_VERSION,
+        'created_at': time.time() if now is None else now,
+        'files': [],
+    }
+    with tempfile.TemporaryDirectory() as staging:
+        members = []
+        for name, path in paths.items():
+            if not os.path.exists(path):
+                continue
+            staged = os.path.join(staging, name)
+            if STATE_FILES[name][2] == 'sqlite':
+                snapshot_sqlite(path, staged)
+            else:
+                shutil.copyfile(path, staged)
+            members.append((name, staged))
+            manifest['files'].append(
+                {
+                    'name': name,
+                    'size': os.path.getsize(staged),
+                    'sha256': file_sha256(staged),
+                }
+            )
+
+        temp_path = f'{out_path}.tmp'
+        tar_path = os.path.join(staging, 'state.tar') if compression == 'zst' else ''
+        mode = 'w' if compression in ('', 'zst') else f'w:{compression}'
+        try:
+            with tarfile.open(tar_path or temp_path, mode) as archive:
+                encoded = json.dumps(manifest, indent=2).encode()
+                info = tarfile.TarInfo(MANIFEST_NAME)
+                info.size = len(encoded)
+                info.mtime = int(manifest['created_at'])
+                archive.addfile(info, io.BytesIO(encoded))
+                for name, staged in members:
+                    archive.add(staged, arcname=name)
+            if tar_path:
+                run_zstd([tar_path, '-o', temp_path])
+            os.replace(temp_path, out_path)
+        finally:
+            if os.path.exists(temp_path):
+                os.remove(temp_path)
+    return manifest
+
+
+def check_sqlite(path: str) -> None:
+    """Raise ValueError unless a file is an intact SQLite database"""
+    db = sqlite3.connect(f'file:{path}?mode=ro', uri=True)
+    try:
+        result = db.execute('PRAGMA integrity_check').fetchone()
+    except sqlite3.DatabaseError as e:
+        msg = f'not a usable database ({e})'
+        raise ValueError(msg) from None
+    finally:
+        db.close()
+    if result is None or result[0] != 'ok':
+        msg = 'the database failed its integrity check'
+        raise ValueError(msg)
+
+
+def read_manifest(archive: tarfile.TarFile) -> dict[str, Any]:
+    """An archive's manifest, raising ValueError if it isn't one of ours"""
+    try:
+        member = archive.extractfile(MANIFEST_NAME)
+    except KeyError:
+        member = None
+    if member is None:
+        msg = f'The archive has no {MANIFEST_NAME} - is it a weather-server backup?'
+        raise ValueError(msg)
+    try:
+        manifest = json.loads(member.read())
+    except ValueError:
+        manifest = None
+    if (
+        not isinstance(manifest, dict)
+        or not isinstance(manifest.get('files'), list)
+        or not all(
+            isinstance(entry, dict)
+            and entry.get('name') in STATE_FILES
+            and isinstance(entry.get('sha256'), str)
+            for entry in manifest['files']
+        )
+    ):
+        msg = f'The archive has an unreadable {MANIFEST_NAME}'
+        raise ValueError(msg)
+    if manifest.get('version') != BACKUP_VERSION:
+        msg = f'Backup version {manifest.get("version")!r} is not supported'
+        raise ValueError(msg)
+    return manifest
+
+
+def restore_backup(
+    archive_path: str, paths: Mapping[str, str], force: bool = False
+) -> list[str]:
+    """Put an archive's state files back in place, returning their names
+
+    Every file is checked against the manifest (and databases for integrity)
+    before any is replaced. Existing files are only overwritten with force.
+    Stop the server first - it keeps its databases open.
+    """
+    compression = archive_compression(archive_path)
+    with tempfile.TemporaryDirectory() as staging:
+        tar_path = archive_path
+        if compression == 'zst':
+            tar_path = os.path.join(staging, 'state.tar')
+            run_zstd(['-d', archive_path, '-o', tar_path])
+        try:
+            archive = tarfile.open(tar_path, 'r')
+        except tarfile.TarError as e:
+            msg = f'{archive_path} is not a backup archive ({e})'
+            raise ValueError(msg) from None
+
+        # A truncated archive reads fine until the cut, so this can fail midway
+        try:
+            with archive:
+                manifest = read_manifest(archive)
+                staged = {}
+                # Only members the manifest names are read, and each to a fixed
+                # name, so nothing in the archive chooses where files are written
+                for entry in manifest['files']:
+                    name = entry['name']
+                    try:
+                        member = archive.extractfile(name) if name in paths else None
+                    except KeyError:
+                        member = None
+                    if member is None:
+                        msg = f'The backup has no usable {name}'
+                        raise ValueError(msg)
+                    staged[name] = os.path.join(staging, name)
+                    with member, open(staged[name], 'wb') as f:
+                        shutil.copyfileobj(member, f)
+                    if file_sha256(staged[name]) != entry['sha256']:
+                        msg = f'{name} in the backup is corrupt (checksum mismatch)'
+                        raise ValueError(msg)
+                    if STATE_FILES[name][2] == 'sqlite':
+                        try:
+                            check_sqlite(staged[name])
+                        except ValueError as e:
+                            msg = f'{name} in the backup is {e}'
+                            raise ValueError(msg) from None
+        except (tarfile.TarError, EOFError) as e:
+            msg = f'{archive_path} is truncated or damaged ({e})'
+            raise ValueError(msg) from None
+
+        existing = [paths[name] for name in staged if os.path.exists(paths[name])]
+        if existing and not force:
+            msg = f'Would overwrite {", ".join(existing)}; restore with --force'
+            raise FileExistsError(msg)
+        for name, source in staged.items():
+            target = paths[name]
+            directory = os.path.dirname(target)
+            if directory:
+                os.makedirs(directory, exist_ok=True)
+            temp_path = f'{target}.tmp'
+            shutil.copyfile(source, temp_path)
+            os.replace(temp_path, target)
+    return list(staged)
+
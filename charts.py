@@ -0,0 +1,128 @@
+# ABOUTME: Chart-ready hourly forecast series so every client plots the same lines
+# ABOUTME: Shaped for Chart.js datasets and uPlot column arrays, with axis ranges
+
+import math
+from datetime import datetime, timezone
+from typing import Any
+
+
+TEMPERATURE_AXIS_PADDING = 2  # Degrees of headroom above and below the lines
+PROBABILITY_AXIS_MAX = 100
+# Keep a dry forecast's rain axis from collapsing to zero height
+MIN_PRECIPITATION_AXIS: dict[str, float] = {'in': 0.1, 'mm': 2.5}
+
+# (series key, label, axis)
+CHART_SERIES = [
+    ('temperature', 'Temperature', 'temperature'),
+    ('feels_like', 'Feels like', 'temperature'),
+    ('precipitation_probability', 'Chance of precipitation', 'probability'),
+    ('accumulated_precipitation', 'Accumulated precipitation', 'precipitation'),
+]
+
+
+def hour_timestamp(value: Any) -> int | None:
+    """Unix seconds for an hour's ISO time (UTC when it has no offset)"""
+    if not isinstance(value, str):
+        return None
+    try:
+        moment = datetime.fromisoformat(value.replace('Z', '+00:00'))
+    except ValueError:
+        return None
+    if moment.tzinfo is None:
+        moment = moment.replace(tzinfo=timezone.utc)
+    return int(moment.timestamp())
+
+
+def accumulate(amounts: list[Any]) -> list[float]:
+    """Running total of hourly precipitation amounts"""
+    total = 0.0
+    totals = []
+    for amount in amounts:
+        total += amount if isinstance(amount, int | float) else 0
+        totals.append(round(total, 2))
+    return totals
+
+
+def now_index(timestamps: list[int | None], now: datetime) -> int | None:
+    """Index of the hour containing now, or None without usable times"""
+    current = now.timestamp()
+    started = [
+        index
+        for index, stamp in enumerate(timestamps)
+        if stamp is not None and stamp <= current
+    ]
+    if started:
+        return started[-1]
+    return 0 if any(stamp is not None for stamp in timestamps) else None
+
+
+def temperature_axis(values: list[Any]) -> dict[str, int | None]:
+    """Whole-degree bounds around every temperature line, padded"""
+    numbers = [value for value in values if isinstance(value, int | float)]
+    if not numbers:
+        return {'min': None, 'max': None}
+    return {
+        'min': math.floor(min(numbers)) - TEMPERATURE_AXIS_PADDING,
+        'max': math.ceil(max(numbers)) + TEMPERATURE_AXIS_PADDING,
+    }
+
+
+def build_charts(
+    hourly: list[dict[str, Any]], units: dict[str, str], now: datetime
+) -> dict[str, Any]:
+    """Hourly temperature, feels-like and precipitation series ready to plot"""
+    timestamps = [hour_timestamp(hour.get('time')) for hour in hourly]
+    series = {
+        'temperature': [hour.get('temp') for hour in hourly],
+        'feels_like': [hour.get('feels_like', hour.get('temp')) for hour in hourly],
+        'precipitation_probability': [hour.get('rain', 0) for hour in hourly],
+        'accumulated_precipitation': accumulate(
+            [hour.get('precipitation', 0) for hour in hourly]
+        ),
+    }
+    precipitation_unit = units['precipitation']
+    axes = {
+        'temperature': {
+            **temperature_axis(series['temperature'] + series['feels_like']),
+            'unit': units['temperature'],
+        },
+        'probability': {'min': 0, 'max': PROBABILITY_AXIS_MAX, 'unit': '%'},
+        'precipitation': {
+            'min': 0,
+            'max': max(
+                [
+                    MIN_PRECIPITATION_AXIS.get(precipitation_unit, 0),
+                    *series['accumulated_precipitation'],
+                ]
+            ),
+            'unit': precipitation_unit,
+        },
+    }
+    labels = [hour.get('t') for hour in hourly]
+
+    return {
+        'labels': labels,
+        'timestamps': timestamps,
+        'now_index': now_index(timestamps, now),
+        'series': series,
+        'axes': axes,
+        # new Chart(ctx, {data: chartjs}) - scale ids match the axes above
+        'chartjs': {
+            'labels': labels,
+            'datasets': [
+                {'label': label, 'data': series[key], 'yAxisID': axis}
+                for key, label, axis in CHART_SERIES
+            ],
+        },
+        # new uPlot({series, ...}, data) - x values are Unix seconds
+        'uplot': {
+            'data': [timestamps, *(series[key] for key, _, _ in CHART_SERIES)],
+            'series': [
+                {'label': 'Time'},
+                *(
+                    {'label': label, 'scale': axis}
+                    for _key, label, axis in CHART_SERIES
+                ),
+            ],
+        },
+    }
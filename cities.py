@@ -0,0 +1,283 @@
+# ABOUTME: Registry of city shortcuts for /<city> routes: coordinates and aliases
+# ABOUTME: Built-in defaults can be replaced by a JSON file and edited at runtime
+
+import json
+import os
+import threading
+import unicodedata
+from typing import Any
+from urllib.parse import unquote
+
+
+MAX_LATITUDE = 90
+MAX_LONGITUDE = 180
+
+# Fuzzy matching: prefixes need this many characters, and a typo allows one
+# edit per this many characters of the query
+MIN_PREFIX_LENGTH = 3
+CHARS_PER_TYPO = 4
+MAX_SUGGESTIONS = 5
+MAX_SUGGEST_LENGTH = 64  # Longer segments aren't typos of a city name
+
+# Built-in cities (timezone auto-detected by OpenMeteo API). Aliases cover
+# local spellings, translations and long forms and are matched normalized
+DEFAULT_CITIES: dict[str, dict[str, Any]] = {
+    'chicago': {'lat': 41.8781, 'lon': -87.6298, 'name': 'Chicago'},
+    'nyc': {
+        'lat': 40.7128,
+        'lon': -74.0060,
+        'name': 'New York City',
+        'aliases': ['new-york', 'new-york-city', 'nueva-york'],
+    },
+    'sf': {
+        'lat': 37.7749,
+        'lon': -122.4194,
+        'name': 'San Francisco',
+        'aliases': ['san-francisco'],
+    },
+    'london': {
+        'lat': 51.5074,
+        'lon': -0.1278,
+        'name': 'London',
+        'aliases': ['londres', 'londra', 'ロンドン'],
+    },
+    'paris': {
+        'lat': 48.8566,
+        'lon': 2.3522,
+        'name': 'Paris',
+        'aliases': ['parigi'],
+    },
+    'tokyo': {
+        'lat': 35.6762,
+        'lon': 139.6503,
+        'name': 'Tokyo',
+        'aliases': ['東京', 'とうきょう', 'tokio'],
+    },
+    'sydney': {'lat': -33.8688, 'lon': 151.2093, 'name': 'Sydney'},
+    'berlin': {'lat': 52.5200, 'lon': 13.4050, 'name': 'Berlin'},
+    'rome': {'lat': 41.9028, 'lon': 12.4964, 'name': 'Rome', 'aliases': ['roma']},
+    'madrid': {'lat': 40.4168, 'lon': -3.7038, 'name': 'Madrid'},
+    'munich': {
+        'lat': 48.1351,
+        'lon': 11.5820,
+        'name': 'Munich',
+        'aliases': ['münchen', 'muenchen'],
+    },
+}
+
+
+def normalize_city_name(city: str) -> str:
+    """Percent-decode and unicode-normalize a city route segment for lookup"""
+    decoded = unquote(city)
+    normalized = unicodedata.normalize('NFKC', decoded).casefold()
+    return '-'.join(normalized.replace('_', ' ').split())
+
+
+def fold_accents(name: str) -> str:
+    """Strip diacritics so 'zürich' and 'zurich' match the same entry"""
+    decomposed = unicodedata.normalize('NFKD', name)
+    return ''.join(char for char in decomposed if not unicodedata.combining(char))
+
+
+def edit_distance(first: str, second: str) -> int:
+    """Levenshtein distance: insertions, deletions and substitutions"""
+    if len(first) < len(second):
+        first, second = second, first
+    previous = list(range(len(second) + 1))
+    for row, first_char in enumerate(first, start=1):
+        current = [row]
+        for column, second_char in enumerate(second, start=1):
+            current.append(
+                min(
+                    previous[column] + 1,
+                    current[column - 1] + 1,
+                    previous[column - 1] + (first_char != second_char),
+                )
+            )
+        previous = current
+    return previous[-1]
+
+
+def validate_city(key: Any, raw: Any) -> tuple[str, dict[str, Any]]:
+    """Check a city entry, returning its normalized key and clean fields"""
+    if not isinstance(key, str) or not normalize_city_name(key):
+        msg = 'City key must be a non-empty string'
+        raise ValueError(msg)
+    key = normalize_city_name(key)
+    if '/' in key or ',' in key:
+        msg = f'City key {key!r} cannot contain "/" or ","'
+        raise ValueError(msg)
+    if not isinstance(raw, dict):
+        msg = f'City {key!r} must be an object'
+        raise ValueError(msg)
+
+    lat, lon = raw.get('lat'), raw.get('lon')
+    if not isinstance(lat, int | float) or not isinstance(lon, int | float):
+        msg = f'City {key!r} needs numeric lat and lon'
+        raise ValueError(msg)
+    if not (-MAX_LATITUDE <= lat <= MAX_LATITUDE) or not (
+        -MAX_LONGITUDE <= lon <= MAX_LONGITUDE
+    ):
+        msg = f'City {key!r} has coordinates out of range'
+        raise ValueError(msg)
+
+    name = raw.get('name', key.replace('-', ' ').title())
+    aliases = raw.get('aliases', [])
+    if not isinstance(name, str) or not name.strip():
+        msg = f'City {key!r} needs a display name'
+        raise ValueError(msg)
+    if not isinstance(aliases, list) or not all(
+        isinstance(alias, str) and normalize_city_name(alias) for alias in aliases
+    ):
+        msg = f'City {key!r} aliases must be a list of non-empty strings'
+        raise ValueError(msg)
+
+    clean_aliases = sorted(
+        {normalize_city_name(alias) for alias in aliases} - {key}
+    )
+    return key, {
+        'lat': float(lat),
+        'lon': float(lon),
+        'name': name.strip(),
+        'aliases': clean_aliases,
+    }
+
+
+class CityRegistry:
+    """City keys -> (lat, lon, name) plus aliases, optionally saved as JSON
+
+    coords and aliases are updated in place so modules holding a reference to
+    them see runtime edits.
+    """
+
+    def __init__(self, path: str, defaults: dict[str, dict[str, Any]]) -> None:
+        self.path = path
+        self.coords: dict[str, tuple[float, float, str]] = {}
+        self.aliases: dict[str, str] = {}
+        self._lock = threading.Lock()
+        self.replace(defaults)
+        self.load()
+
+    def load(self) -> None:
+        """Replace the table with the file's cities, if there is a usable file"""
+        try:
+            with open(self.path, encoding='utf-8') as f:
+                cities = json.load(f)
+            self.replace(cities)
+        except FileNotFoundError:
+            return
+        except (OSError, ValueError) as e:
+            print(f'❌ Could not read city registry {self.path}: {str(e)}')
+            return
+        print(f'🏙️  Loaded {len(self.coords)} cities from {self.path}')
+
+    def replace(self, cities: Any) -> None:
+        """Validate a whole {key: entry} table and swap it in"""
+        if not isinstance(cities, dict):
+            msg = 'City registry must be an object of key -> city'
+            raise ValueError(msg)
+        coords: dict[str, tuple[float, float, str]] = {}
+        aliases: dict[str, str] = {}
+        for raw_key, raw in cities.items():
+            key, city = validate_city(raw_key, raw)
+            coords[key] = (city['lat'], city['lon'], city['name'])
+            for alias in city['aliases']:
+                aliases[alias] = key
+        clashes = sorted(set(coords) & set(aliases))
+        if clashes:
+            msg = f'Aliases clash with city keys: {", ".join(clashes)}'
+            raise ValueError(msg)
+        with self._lock:
+            self.coords.clear()
+            self.coords.update(coords)
+            self.aliases.clear()
+            self.aliases.update(aliases)
+
+    def save(self) -> None:
+        """Write the table atomically (lock held)"""
+        directory = os.path.dirname(self.path)
+        if directory:
+            os.makedirs(directory, exist_ok=True)
+        temp_path = f'{self.path}.tmp'
+        with open(temp_path, 'w', encoding='utf-8') as f:
+            json.dump(self.entries(), f, indent=2, ensure_ascii=False)
+        os.replace(temp_path, self.path)
+
+    def entries(self) -> dict[str, dict[str, Any]]:
+        """The table in its config-file shape, aliases grouped under each city"""
+        grouped: dict[str, list[str]] = {key: [] for key in self.coords}
+        for alias, key in self.aliases.items():
+            grouped.setdefault(key, []).append(alias)
+        return {
+            key: {'lat': lat, 'lon': lon, 'name': name, 'aliases': sorted(grouped[key])}
+            for key, (lat, lon, name) in self.coords.items()
+        }
+
+    def add(self, key: Any, raw: Any) -> tuple[str, dict[str, Any]]:
+        """Add or replace a city and its aliases, saving the table"""
+        key, city = validate_city(key, raw)
+        with self._lock:
+            if self.aliases.get(key, key) != key:
+                msg = f'{key!r} is already an alias of {self.aliases[key]!r}'
+                raise ValueError(msg)
+            for alias in city['aliases']:
+                if alias in self.coords or self.aliases.get(alias, key) != key:
+                    msg = f'Alias {alias!r} is already used by another city'
+                    raise ValueError(msg)
+            for alias in [a for a, target in self.aliases.items() if target == key]:
+                del self.aliases[alias]
+            self.coords[key] = (city['lat'], city['lon'], city['name'])
+            for alias in city['aliases']:
+                self.aliases[alias] = key
+            self.save()
+        return key, city
+
+    def remove(self, key: str) -> bool:
+        """Remove a city and its aliases, returning False if it doesn't exist"""
+        key = normalize_city_name(key)
+        with self._lock:
+            if self.coords.pop(key, None) is None:
+                return False
+            for alias in [a for a, target in self.aliases.items() if target == key]:
+                del self.aliases[alias]
+            self.save()
+        return True
+
+    def resolve(self, city: str) -> str | None:
+        """Resolve a city route segment to its registry key, following aliases"""
+        normalized = normalize_city_name(city)
+        for candidate in (normalized, fold_accents(normalized)):
+            if candidate in self.coords:
+                return candidate
+            if candidate in self.aliases:
+                return self.aliases[candidate]
+        return None
+
+    def suggest(self, city: str) -> list[tuple[str, int]]:
+        """Registry keys close to a missed segment, as (key, edits), best first
+
+        A segment matches a key, alias or display name it's a prefix of (0 edits)
+        or that is within one edit per CHARS_PER_TYPO characters of it.
+        """
+        query = fold_accents(normalize_city_name(city))
+        if not query or len(query) > MAX_SUGGEST_LENGTH:
+            return []
+        max_edits = max(1, len(query) // CHARS_PER_TYPO)
+        names = [(key, key) for key in self.coords]
+        names += [(alias, key) for alias, key in self.aliases.items()]
+        names += [
+            (normalize_city_name(name), key)
+            for key, (_, _, name) in self.coords.items()
+        ]
+
+        best: dict[str, int] = {}
+        for name, key in names:
+            candidate = fold_accents(name)
+            if len(query) >= MIN_PREFIX_LENGTH and candidate.startswith(query):
+                edits = 0
+            else:
+                edits = edit_distance(query, candidate)
+            if edits <= max_edits and edits < best.get(key, max_edits + 1):
+                best[key] = edits
+        ranked = sorted(best.items(), key=lambda item: (item[1], item[0]))
+        return ranked[:MAX_SUGGESTIONS]
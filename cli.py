@@ -0,0 +1,344 @@
+# ABOUTME: Command line entry point: serve the dashboard, check the configuration,
+# ABOUTME: fetch one forecast as JSON for cron jobs and scripts, or back up its state
+
+import argparse
+import contextlib
+import json
+import os
+import sqlite3
+import ssl
+import sys
+from collections.abc import MutableMapping, Sequence
+
+from dotenv import load_dotenv
+
+from backup import ARCHIVE_SUFFIXES, create_backup, restore_backup, state_paths
+from cities import MAX_LATITUDE, MAX_LONGITUDE, validate_city
+from config import DEFAULT_CONFIG_PATH, ConfigFile, load_config_file, load_tls_context
+from cron import CronSchedule
+from proxies import DEFAULT_TRUSTED_PROXIES, TrustedProxies
+from weather_core.providers import (
+    COMPOSITE_PROVIDER_TYPES,
+    WeatherProvider,
+    WeatherProviderManager,
+    build_provider,
+)
+from weather_core.settings import ConfigError, ProviderSettings, load_provider_settings
+
+
+# Settings that must parse as whole numbers for the server to start
+INTEGER_SETTINGS = (
+    'PORT',
+    'CACHE_TTL',
+    'CACHE_MAXSIZE',
+    'REQUEST_TIMEOUT',
+    'MAX_CONCURRENT_REQUESTS',
+    'MAX_BODY_BYTES',
+    'HISTORY_RETENTION_DAYS',
+    'HISTORY_DAILY_RETENTION_DAYS',
+)
+
+# Settings that must be cron expressions when set
+CRON_SETTINGS = ('DIGEST_CRON', 'HISTORY_COMPACT_CRON')
+
+EXIT_OK = 0
+EXIT_FAILED = 1
+EXIT_USAGE = 2
+
+
+def latitude(value: str) -> float:
+    """An argparse type for -90..90"""
+    lat = float(value)
+    if abs(lat) > MAX_LATITUDE:
+        msg = f'latitude must be between -{MAX_LATITUDE} and {MAX_LATITUDE}'
+        raise argparse.ArgumentTypeError(msg)
+    return lat
+
+
+def longitude(value: str) -> float:
+    """An argparse type for -180..180"""
+    lon = float(value)
+    if abs(lon) > MAX_LONGITUDE:
+        msg = f'longitude must be between -{MAX_LONGITUDE} and {MAX_LONGITUDE}'
+        raise argparse.ArgumentTypeError(msg)
+    return lon
+
+
+def build_parser() -> argparse.ArgumentParser:
+    """The command line and its subcommands"""
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument(
+        '--config',
+        metavar='PATH',
+        help=f'TOML config file (default: $WEATHER_CONFIG or {DEFAULT_CONFIG_PATH})',
+    )
+
+    parser = argparse.ArgumentParser(description='Weather dashboard server and tools')
+    commands = parser.add_subparsers(dest='command', required=True)
+
+    serve = commands.add_parser(
+        'serve', parents=[common], help='run the web server (the default)'
+    )
+    serve.add_argument('--host', help='address to bind (default: $HOST or 127.0.0.1)')
+    serve.add_argument('--port', type=int, help='port to bind (default: $PORT or 5001)')
+    serve.add_argument(
+        '--unix-socket', metavar='PATH', help='listen on a unix socket instead'
+    )
+    serve.add_argument(
+        '--tls-cert', metavar='PATH', help='PEM certificate chain to serve HTTPS'
+    )
+    serve.add_argument('--tls-key', metavar='PATH', help='PEM private key for it')
+
+    commands.add_parser(
+        'check-config',
+        parents=[common],
+        help='validate the config file, providers, cities and TLS files, then exit',
+    )
+
+    fetch = commands.add_parser(
+        'fetch', parents=[common], help='print one forecast as JSON and exit'
+    )
+    fetch.add_argument('--lat', type=latitude, required=True)
+    fetch.add_argument('--lon', type=longitude, required=True)
+    fetch.add_argument(
+        '--provider', help='ask this provider only (default: primary with fallbacks)'
+    )
+    fetch.add_argument('--location', help='name to label the forecast with')
+    fetch.add_argument(
+        '--tz', help='IANA timezone for times, if the provider gives none'
+    )
+
+    backup = commands.add_parser(
+        'backup',
+        parents=[common],
+        help='archive the history, favorites, tokens and notification rules',
+    )
+    backup.add_argument(
+        '--out',
+        metavar='PATH',
+        required=True,
+        help=f'archive to write, named {", ".join(ARCHIVE_SUFFIXES)}',
+    )
+
+    restore = commands.add_parser(
+        'restore',
+        parents=[common],
+        help='put a backup back in place (stop the server first)',
+    )
+    restore.add_argument('archive', help='archive written by backup')
+    restore.add_argument(
+        '--force', action='store_true', help='overwrite the current state files'
+    )
+    return parser
+
+
+def load_settings(
+    config_path: str | None, environ: MutableMapping[str, str]
+) -> ConfigFile:
+    """Layer .env and the config file under the environment
+
+    --config is recorded as WEATHER_CONFIG so the server, which reads its
+    settings at import, loads the same file.
+    """
+    if environ is os.environ:
+        load_dotenv()
+    if config_path:
+        if not os.path.exists(config_path):
+            msg = f'Config file {config_path} does not exist'
+            raise ConfigError(msg)
+        environ['WEATHER_CONFIG'] = config_path
+    config_file = load_config_file(environ.get('WEATHER_CONFIG', DEFAULT_CONFIG_PATH))
+    config_file.apply(environ)
+    return config_file
+
+
+def provider_settings(
+    config_file: ConfigFile, environ: MutableMapping[str, str]
+) -> list[ProviderSettings]:
+    """The provider list the server would use with these settings"""
+    return load_provider_settings(
+        environ.get('PROVIDERS_CONFIG_PATH', 'data/providers.json'),
+        environ,
+        config_file.providers,
+    )
+
+
+def serve(args: argparse.Namespace, environ: MutableMapping[str, str]) -> int:
+    """Run the server, command line flags winning over the environment and file"""
+    try:
+        load_settings(args.config, environ)
+    except (OSError, ConfigError) as e:
+        print(f'❌ {e}', file=sys.stderr)
+        return EXIT_FAILED
+    if args.host:
+        environ['HOST'] = args.host
+    if args.port is not None:
+        environ['PORT'] = str(args.port)
+    if args.unix_socket:
+        environ['UNIX_SOCKET'] = args.unix_socket
+    if args.tls_cert:
+        environ['TLS_CERT_FILE'] = args.tls_cert
+    if args.tls_key:
+        environ['TLS_KEY_FILE'] = args.tls_key
+    try:
+        load_tls_context(environ)
+    except (OSError, ssl.SSLError, ConfigError) as e:
+        print(f'❌ TLS: {e}', file=sys.stderr)
+        return EXIT_FAILED
+
+    # main reads its settings at import, so only once they're in place
+    import main
+
+    main.run_server()
+    return EXIT_OK
+
+
+def check_config(args: argparse.Namespace, environ: MutableMapping[str, str]) -> int:
+    """Report every problem in the configuration, failing if there were any"""
+    problems = []
+    try:
+        config_file = load_settings(args.config, environ)
+    except (OSError, ConfigError) as e:
+        print(f'❌ {e}')
+        return EXIT_FAILED
+    print(f'✅ Config file: {config_file.path or "none"}')
+
+    for name in INTEGER_SETTINGS:
+        value = environ.get(name)
+        if value is not None and not value.strip().isdigit():
+            problems.append(f'{name} must be a whole number, not {value!r}')
+    for name in CRON_SETTINGS:
+        value = environ.get(name, '').strip()
+        if value:
+            try:
+                CronSchedule(value)
+            except ValueError as e:
+                problems.append(f'{name}: {e}')
+
+    try:
+        settings = provider_settings(config_file, environ)
+    except (OSError, ValueError) as e:
+        problems.append(f'Providers: {e}')
+        settings = []
+    built: dict[str, WeatherProvider] = {}
+    enabled = [entry for entry in settings if entry.enabled]
+    for entry in sorted(
+        enabled, key=lambda entry: entry.kind in COMPOSITE_PROVIDER_TYPES
+    ):
+        try:
+            built[entry.name] = build_provider(entry, built)
+        except ValueError as e:
+            problems.append(f'Provider {entry.name}: {e}')
+    chain = [entry.name for entry in enabled if entry.name in built]
+    if chain:
+        print(f'✅ Providers: {" → ".join(chain)}')
+    else:
+        problems.append('No weather providers would be enabled')
+
+    try:
+        if load_tls_context(environ) is not None:
+            print(f'✅ TLS: {environ["TLS_CERT_FILE"]}')
+    except (OSError, ssl.SSLError, ConfigError) as e:
+        problems.append(f'TLS: {e}')
+
+    try:
+        TrustedProxies.parse(
+            environ.get('TRUSTED_PROXIES', DEFAULT_TRUSTED_PROXIES),
+            environ.get('PROXY_HEADER', 'x-forwarded-for'),
+        )
+    except ValueError as e:
+        problems.append(str(e))
+
+    for key, city in (config_file.cities or {}).items():
+        try:
+            validate_city(key, city)
+        except ValueError as e:
+            problems.append(f'City {key!r}: {e}')
+    if config_file.cities:
+        print(f'✅ Cities: {len(config_file.cities)} from the config file')
+
+    for problem in problems:
+        print(f'❌ {problem}')
+    return EXIT_FAILED if problems else EXIT_OK
+
+
+def fetch(args: argparse.Namespace, environ: MutableMapping[str, str]) -> int:
+    """Print one forecast as JSON; progress goes to stderr to keep stdout clean"""
+    with contextlib.redirect_stdout(sys.stderr):
+        try:
+            config_file = load_settings(args.config, environ)
+            settings = provider_settings(config_file, environ)
+        except (OSError, ValueError) as e:
+            print(f'❌ {e}')
+            return EXIT_FAILED
+        manager = WeatherProviderManager()
+        manager.configure(settings)
+
+        if args.provider:
+            provider = manager.providers.get(args.provider)
+            if provider is None:
+                available = ', '.join(manager.providers) or 'none'
+                print(f'❌ Unknown provider {args.provider!r} (have: {available})')
+                return EXIT_USAGE
+            result = provider.get_weather(args.lat, args.lon, args.location, args.tz)
+        else:
+            result = manager.get_weather(args.lat, args.lon, args.location, args.tz)
+        if not result:
+            print('❌ No weather data from any provider')
+            return EXIT_FAILED
+
+    print(json.dumps(result, indent=2, default=str))
+    return EXIT_OK
+
+
+def backup(args: argparse.Namespace, environ: MutableMapping[str, str]) -> int:
+    """Archive the state files where these settings put them"""
+    try:
+        load_settings(args.config, environ)
+        manifest = create_backup(args.out, state_paths(environ))
+    except (OSError, ValueError, sqlite3.Error) as e:
+        print(f'❌ Backup failed: {e}', file=sys.stderr)
+        return EXIT_FAILED
+    names = ', '.join(entry['name'] for entry in manifest['files']) or 'nothing'
+    print(f'✅ Backed up {names} to {args.out}')
+    return EXIT_OK
+
+
+def restore(args: argparse.Namespace, environ: MutableMapping[str, str]) -> int:
+    """Restore the state files from an archive where these settings put them"""
+    try:
+        load_settings(args.config, environ)
+        restored = restore_backup(args.archive, state_paths(environ), args.force)
+    except FileExistsError as e:
+        print(f'❌ {e}', file=sys.stderr)
+        return EXIT_USAGE
+    except (OSError, ValueError, sqlite3.Error) as e:
+        print(f'❌ Restore failed: {e}', file=sys.stderr)
+        return EXIT_FAILED
+    print(f'✅ Restored {", ".join(restored) or "nothing"} from {args.archive}')
+    return EXIT_OK
+
+
+COMMANDS = {
+    'serve': serve,
+    'check-config': check_config,
+    'fetch': fetch,
+    'backup': backup,
+    'restore': restore,
+}
+
+
+def main(
+    argv: Sequence[str] | None = None,
+    environ: MutableMapping[str, str] | None = None,
+) -> int:
+    """Run a subcommand, serving when none is given"""
+    argv = sys.argv[1:] if argv is None else list(argv)
+    if not argv or (argv[0].startswith('-') and argv[0] not in ('-h', '--help')):
+        argv = ['serve', *argv]
+    args = build_parser().parse_args(argv)
+    return COMMANDS[args.command](args, os.environ if environ is None else environ)
+
+
+if __name__ == '__main__':
+    sys.exit(main())
@@ -0,0 +1,154 @@
+# ABOUTME: Startup configuration: weather.toml layered under the environment, the
+# ABOUTME: structured sections it keeps (providers, cities) and the TLS certificate
+
+import json
+import os
+import ssl
+from collections.abc import Mapping, MutableMapping
+from typing import Any
+
+
+try:
+    import tomllib
+except ImportError:  # Python 3.10
+    import tomli as tomllib  # type: ignore[no-redef]
+
+from weather_core.settings import ConfigError
+
+
+# Where the TOML config is read from unless WEATHER_CONFIG (or --config) says
+DEFAULT_CONFIG_PATH = 'weather.toml'
+
+# TOML tables whose keys map to unprefixed variables ([server] port -> PORT)
+UNPREFIXED_TABLES = frozenset({'server'})
+
+# Sections kept as structured data instead of becoming environment variables
+STRUCTURED_SECTIONS = frozenset({'providers', 'cities'})
+
+# Variables whose lists are ';'-separated (their entries contain commas)
+SEMICOLON_LISTS = frozenset(
+    {'PREFETCH_LOCATIONS', 'DIGEST_LOCATIONS', 'PROVIDER_QUOTAS'}
+)
+
+
+def env_value(name: str, value: Any) -> str:
+    """A TOML value written the way the environment variable expects it"""
+    if isinstance(value, bool):
+        return '1' if value else '0'
+    if isinstance(value, list):
+        if any(isinstance(item, dict) for item in value):
+            return json.dumps(value)
+        separator = ';' if name in SEMICOLON_LISTS else ','
+        return separator.join(env_value(name, item) for item in value)
+    if isinstance(value, dict):
+        return json.dumps(value)
+    return str(value)
+
+
+def flatten_settings(table: dict[str, Any], prefix: str = '') -> dict[str, str]:
+    """TOML tables as environment variables: [mqtt] host -> MQTT_HOST"""
+    settings = {}
+    for key, value in table.items():
+        if not prefix and key in STRUCTURED_SECTIONS:
+            continue
+        name = f'{prefix}{key}'.upper()
+        if isinstance(value, dict):
+            inner = '' if not prefix and key in UNPREFIXED_TABLES else f'{name}_'
+            settings.update(flatten_settings(value, inner))
+        else:
+            settings[name] = env_value(name, value)
+    return settings
+
+
+class ConfigFile:
+    """A parsed weather.toml
+
+    Plain settings become defaults for the environment variables of the same
+    name, so anything set in the environment (or .env) wins over the file. The
+    provider list and city table are kept as structured data.
+    """
+
+    def __init__(self, path: str | None = None, data: dict[str, Any] | None = None):
+        data = data or {}
+        self.path = path
+        self.settings = flatten_settings(data)
+        providers = data.get('providers')
+        if providers is not None and not isinstance(providers, list):
+            msg = 'providers must be an array of tables ([[providers]])'
+            raise ConfigError(msg)
+        self.providers: list[Any] | None = providers
+        cities = data.get('cities')
+        if cities is not None and not isinstance(cities, dict):
+            msg = 'cities must be a table of city keys ([cities.<key>])'
+            raise ConfigError(msg)
+        self.cities: dict[str, Any] | None = cities
+        # Variables this file set, which a reload may take back
+        self.applied: list[str] = []
+
+    def apply(self, environ: MutableMapping[str, str] | None = None) -> list[str]:
+        """Set each setting the environment leaves unset, returning their names"""
+        environ = os.environ if environ is None else environ
+        applied = []
+        for name, value in self.settings.items():
+            if name not in environ:
+                environ[name] = value
+                applied.append(name)
+        self.applied = applied
+        return applied
+
+
+def load_config_file(path: str | None) -> ConfigFile:
+    """The TOML config at path, or an empty one when there's no file there"""
+    if not path or not os.path.exists(path):
+        return ConfigFile()
+    try:
+        with open(path, 'rb') as f:
+            data = tomllib.load(f)
+    except tomllib.TOMLDecodeError as e:
+        msg = f'{path} is not valid TOML: {e}'
+        raise ConfigError(msg) from e
+    config = ConfigFile(path, data)
+    print(f'⚙️  Loaded {len(config.settings)} settings from {path}')
+    return config
+
+
+def reload_config_file(
+    previous: ConfigFile,
+    path: str | None,
+    environ: MutableMapping[str, str] | None = None,
+) -> ConfigFile:
+    """Re-read the config file, replacing the variables the previous one set
+
+    Variables changed since the previous file set them (by the command line,
+    say) are left alone. If the file can't be read, ConfigError is raised and
+    the environment is untouched.
+    """
+    environ = os.environ if environ is None else environ
+    config = load_config_file(path)
+    for name in previous.applied:
+        if environ.get(name) == previous.settings.get(name):
+            environ.pop(name, None)
+    config.apply(environ)
+    return config
+
+
+def load_tls_context(
+    environ: Mapping[str, str] | None = None,
+) -> ssl.SSLContext | None:
+    """A server TLS context from TLS_CERT_FILE and TLS_KEY_FILE, None without them
+
+    The certificate file holds the full PEM chain (certbot's fullchain.pem).
+    Missing or unreadable files raise OSError or ssl.SSLError.
+    """
+    environ = os.environ if environ is None else environ
+    cert_file = environ.get('TLS_CERT_FILE', '').strip()
+    key_file = environ.get('TLS_KEY_FILE', '').strip()
+    if not cert_file and not key_file:
+        return None
+    if not cert_file or not key_file:
+        msg = 'TLS_CERT_FILE and TLS_KEY_FILE must be set together'
+        raise ConfigError(msg)
+    context = ssl.SSLContext(ssl.PROTOCOL_TLS_SERVER)
+    context.minimum_version = ssl.TLSVersion.TLSv1_2
+    context.load_cert_chain(cert_file, key_file)
+    return context
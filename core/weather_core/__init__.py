@@ -0,0 +1,48 @@
+# ABOUTME: weather-core: the weather provider abstraction without the web server -
+# ABOUTME: providers and their manager, settings, cache backends, units and geocoding
+
+from weather_core.cache import CacheBackend, CacheKey, create_cache_backend
+from weather_core.geocoding import geocode_place, geocode_postal_code
+from weather_core.providers import (
+    PROVIDER_TYPES,
+    WEATHER_SECTIONS,
+    MockProvider,
+    WeatherProvider,
+    WeatherProviderManager,
+    build_provider,
+    build_providers,
+    extract_weather_section,
+)
+from weather_core.settings import (
+    ConfigError,
+    ProviderSettings,
+    default_provider_settings,
+    load_provider_settings,
+)
+from weather_core.transport import Transport, set_transport
+from weather_core.units import UNIT_LABELS, convert_to_metric
+
+
+__all__ = [
+    'PROVIDER_TYPES',
+    'UNIT_LABELS',
+    'WEATHER_SECTIONS',
+    'CacheBackend',
+    'CacheKey',
+    'ConfigError',
+    'MockProvider',
+    'ProviderSettings',
+    'Transport',
+    'WeatherProvider',
+    'WeatherProviderManager',
+    'build_provider',
+    'build_providers',
+    'convert_to_metric',
+    'create_cache_backend',
+    'default_provider_settings',
+    'extract_weather_section',
+    'geocode_place',
+    'geocode_postal_code',
+    'load_provider_settings',
+    'set_transport',
+]
@@ -0,0 +1,389 @@
+# ABOUTME: Weather cache backends: in-process TTLCache or a Redis shared by replicas
+# ABOUTME: Entries are keyed by place, provider, units and language (CacheKey)
+
+import json
+import queue
+import socket
+import ssl
+import threading
+import time
+from abc import abstractmethod
+from collections.abc import Iterator, MutableMapping
+from typing import Any
+from urllib.parse import SplitResult, unquote, urlsplit
+
+from cachetools import TTLCache
+
+
+REDIS_DEFAULT_PORT = 6379
+REDIS_TIMEOUT_SECONDS = 2
+REDIS_POOL_SIZE = 10
+REDIS_KEY_PREFIX = 'weather-dashboard'
+REDIS_SCAN_COUNT = 100
+CACHE_KEY_PARTS = 4
+
+
+class RedisError(Exception):
+    """Redis returned an error reply or an unreadable response"""
+
+
+class RedisProtocolError(RedisError):
+    """The reply stream ended or desynced; the connection can't be reused"""
+
+
+class CacheKey:
+    """What a cached document is: a place as fetched from one provider
+
+    Units and language are the ones the document is stored in, so data fetched
+    another way can never be served in their place. Backends store the key as
+    its string form, 'lat,lon:provider:units:lang'.
+    """
+
+    def __init__(
+        self,
+        lat: float,
+        lon: float,
+        provider: str,
+        units: str = 'imperial',
+        lang: str = 'en',
+    ) -> None:
+        self.lat = round(lat, 4)
+        self.lon = round(lon, 4)
+        self.provider = provider
+        self.units = units
+        self.lang = lang
+
+    @property
+    def location(self) -> str:
+        """The 'lat,lon' part, shared by every provider's entry for the place"""
+        return f'{self.lat:.4f},{self.lon:.4f}'
+
+    @classmethod
+    def parse(cls, text: str) -> 'CacheKey | None':
+        """Rebuild a key from its string form, or None if it isn't one"""
+        parts = text.split(':')
+        if len(parts) != CACHE_KEY_PARTS:
+            return None
+        location, provider, units, lang = parts
+        try:
+            lat, lon = (float(value) for value in location.split(','))
+        except ValueError:
+            return None
+        return cls(lat, lon, provider, units, lang)
+
+    def __str__(self) -> str:
+        return f'{self.location}:{self.provider}:{self.units}:{self.lang}'
+
+    def __repr__(self) -> str:
+        return f'CacheKey({str(self)!r})'
+
+    def __eq__(self, other: object) -> bool:
+        return isinstance(other, CacheKey) and str(self) == str(other)
+
+    def __hash__(self) -> int:
+        return hash(str(self))
+
+
+class CacheBackend(MutableMapping[str | CacheKey, Any]):
+    """A TTL cache of JSON-able entries, read and written like a dict
+
+    Assignment uses the default ttl; set() gives an entry its own lifetime,
+    capped at max_ttl.
+    """
+
+    kind = 'abstract'
+    ttl: float
+    max_ttl: float
+    maxsize: int | None
+
+    @abstractmethod
+    def set(self, key: str | CacheKey, value: Any, ttl: float | None = None) -> None:
+        """Store an entry for ttl seconds (the cache's default when None)"""
+
+    def lifetime(self, ttl: float | None) -> float:
+        """An entry's TTL: the one asked for, else the default, within max_ttl"""
+        return min(ttl or self.ttl, self.max_ttl)
+
+    def __setitem__(self, key: str | CacheKey, value: Any) -> None:
+        self.set(key, value)
+
+
+class MemoryCacheBackend(CacheBackend):
+    """Per-process cache; each replica keeps its own entries"""
+
+    kind = 'memory'
+
+    def __init__(self, maxsize: int, ttl: float, max_ttl: float | None = None) -> None:
+        # The TTLCache evicts at max_ttl; each entry carries its own deadline
+        self.max_ttl = max(ttl, max_ttl or ttl)
+        self.entries: TTLCache[str, tuple[float, Any]] = TTLCache(
+            maxsize=maxsize, ttl=self.max_ttl
+        )
+        self.maxsize = maxsize
+        self.ttl = ttl
+
+    def set(self, key: str | CacheKey, value: Any, ttl: float | None = None) -> None:
+        self.entries[str(key)] = (time.monotonic() + self.lifetime(ttl), value)
+
+    def live_entry(self, key: str) -> tuple[float, Any] | None:
+        """An entry's (deadline, value), dropping it if its own TTL has passed"""
+        entry = self.entries.get(key)
+        if entry is None:
+            return None
+        if entry[0] <= time.monotonic():
+            self.entries.pop(key, None)
+            return None
+        return entry
+
+    def __getitem__(self, key: str | CacheKey) -> Any:
+        entry = self.live_entry(str(key))
+        if entry is None:
+            raise KeyError(key)
+        return entry[1]
+
+    def __delitem__(self, key: str | CacheKey) -> None:
+        del self.entries[str(key)]
+
+    def __contains__(self, key: object) -> bool:
+        return self.live_entry(str(key)) is not None
+
+    def __iter__(self) -> Iterator[str]:
+        return iter([key for key in list(self.entries) if self.live_entry(key)])
+
+    def __len__(self) -> int:
+        return sum(1 for _ in self)
+
+    def clear(self) -> None:
+        self.entries.clear()
+
+
+def encode_command(*args: Any) -> bytes:
+    """A command as a RESP array of bulk strings"""
+    parts = [b'*%d\r\n' % len(args)]
+    for arg in args:
+        data = arg if isinstance(arg, bytes) else str(arg).encode('utf-8')
+        parts.append(b'$%d\r\n%s\r\n' % (len(data), data))
+    return b''.join(parts)
+
+
+def read_reply(stream: Any) -> Any:
+    """Parse one RESP2 reply from a buffered binary stream"""
+    line = stream.readline()
+    if not line.endswith(b'\r\n'):
+        msg = 'Connection closed mid-reply'
+        raise RedisProtocolError(msg)
+    kind, rest = line[:1], line[1:-2]
+    if kind == b'+':
+        return rest.decode('utf-8')
+    if kind == b'-':
+        raise RedisError(rest.decode('utf-8'))
+    if kind == b':':
+        return int(rest)
+    if kind == b'$':
+        length = int(rest)
+        if length < 0:
+            return None
+        data = stream.read(length + 2)
+        if len(data) != length + 2:
+            msg = 'Connection closed mid-reply'
+            raise RedisProtocolError(msg)
+        return data[:-2]
+    if kind == b'*':
+        count = int(rest)
+        if count < 0:
+            return None
+        return [read_reply(stream) for _ in range(count)]
+    msg = f'Unexpected reply {line!r}'
+    raise RedisProtocolError(msg)
+
+
+def parse_redis_url(url: str) -> SplitResult:
+    """Split a redis:// or rediss:// URL, raising ValueError for anything unusable"""
+    parts = urlsplit(url)
+    if parts.scheme not in ('redis', 'rediss'):
+        msg = f'Unsupported Redis URL scheme: {parts.scheme}'
+        raise ValueError(msg)
+    # urlsplit only checks the port when it's read
+    try:
+        parts.port  # noqa: B018
+    except ValueError as e:
+        msg = f'Invalid Redis URL port: {e}'
+        raise ValueError(msg) from e
+    database = parts.path.strip('/')
+    if database and not database.isdigit():
+        msg = f'Redis URL database must be a number, not {database!r}'
+        raise ValueError(msg)
+    return parts
+
+
+class RedisConnection:
+    """One socket to Redis, authenticated and on the URL's database"""
+
+    def __init__(self, url: str, timeout: float = REDIS_TIMEOUT_SECONDS) -> None:
+        parts = parse_redis_url(url)
+        host = parts.hostname or 'localhost'
+        sock = socket.create_connection(
+            (host, parts.port or REDIS_DEFAULT_PORT), timeout=timeout
+        )
+        if parts.scheme == 'rediss':
+            sock = ssl.create_default_context().wrap_socket(sock, server_hostname=host)
+        self.sock = sock
+        self.stream = sock.makefile('rb')
+
+        if parts.password is not None:
+            password = unquote(parts.password)
+            if parts.username:
+                self.command('AUTH', unquote(parts.username), password)
+            else:
+                self.command('AUTH', password)
+        database = parts.path.strip('/')
+        if database and database != '0':
+            self.command('SELECT', int(database))
+
+    def command(self, *args: Any) -> Any:
+        """Send a command and wait for its reply"""
+        self.sock.sendall(encode_command(*args))
+        return read_reply(self.stream)
+
+    def close(self) -> None:
+        try:
+            self.stream.close()
+            self.sock.close()
+        except OSError:
+            pass
+
+
+class RedisPool:
+    """Reuses up to size connections; callers beyond that wait for one"""
+
+    def __init__(
+        self,
+        url: str,
+        size: int = REDIS_POOL_SIZE,
+        timeout: float = REDIS_TIMEOUT_SECONDS,
+    ) -> None:
+        self.url = url
+        self.timeout = timeout
+        self.slots = threading.BoundedSemaphore(size)
+        self.idle: queue.LifoQueue[RedisConnection] = queue.LifoQueue()
+
+    def command(self, *args: Any) -> Any:
+        """Run a command on a pooled connection"""
+        if not self.slots.acquire(timeout=self.timeout):
+            msg = 'Timed out waiting for a Redis connection'
+            raise RedisError(msg)
+        try:
+            try:
+                connection = self.idle.get_nowait()
+            except queue.Empty:
+                connection = RedisConnection(self.url, self.timeout)
+            try:
+                reply = connection.command(*args)
+            except (OSError, RedisProtocolError):
+                connection.close()
+                raise
+            except RedisError:
+                # An error reply leaves the connection in step for the next command
+                self.idle.put(connection)
+                raise
+            self.idle.put(connection)
+            return reply
+        finally:
+            self.slots.release()
+
+    def close(self) -> None:
+        while True:
+            try:
+                self.idle.get_nowait().close()
+            except queue.Empty:
+                return
+
+
+class RedisCacheBackend(CacheBackend):
+    """Entries shared by every replica pointing at the same Redis
+
+    Redis expires keys itself (SET ... EX ttl) and evicts under its own
+    maxmemory policy, so there's no entry limit here. An unreachable Redis
+    reads as a miss and drops writes rather than failing the request.
+    """
+
+    kind = 'redis'
+
+    def __init__(
+        self, pool: Any, namespace: str, ttl: float, max_ttl: float | None = None
+    ) -> None:
+        self.pool = pool
+        self.prefix = f'{REDIS_KEY_PREFIX}:{namespace}:'
+        self.ttl = ttl
+        self.max_ttl = max(ttl, max_ttl or ttl)
+        self.maxsize = None
+
+    def call(self, *args: Any, default: Any = None) -> Any:
+        """Run a command, logging and returning default when Redis fails"""
+        try:
+            return self.pool.command(*args)
+        except (OSError, RedisError) as e:
+            print(f'⚠️  Redis {args[0]} failed: {str(e)}')
+            return default
+
+    def __getitem__(self, key: str | CacheKey) -> Any:
+        raw = self.call('GET', f'{self.prefix}{key}')
+        if raw is None:
+            raise KeyError(key)
+        return json.loads(raw)
+
+    def set(self, key: str | CacheKey, value: Any, ttl: float | None = None) -> None:
+        expiry = int(self.lifetime(ttl))
+        self.call('SET', f'{self.prefix}{key}', json.dumps(value), 'EX', expiry)
+
+    def __delitem__(self, key: str | CacheKey) -> None:
+        if not self.call('DEL', f'{self.prefix}{key}', default=0):
+            raise KeyError(key)
+
+    def __contains__(self, key: object) -> bool:
+        return bool(self.call('EXISTS', f'{self.prefix}{key}', default=0))
+
+    def __iter__(self) -> Iterator[str]:
+        cursor = b'0'
+        while True:
+            reply = self.call(
+                'SCAN',
+                cursor,
+                'MATCH',
+                f'{self.prefix}*',
+                'COUNT',
+                REDIS_SCAN_COUNT,
+            )
+            if not reply:
+                return
+            cursor, keys = reply
+            for key in keys:
+                yield key.decode('utf-8')[len(self.prefix) :]
+            if cursor in (b'0', '0'):
+                return
+
+    def __len__(self) -> int:
+        return sum(1 for _ in self)
+
+    def clear(self) -> None:
+        keys = [self.prefix + key for key in self]
+        for start in range(0, len(keys), REDIS_SCAN_COUNT):
+            self.call('DEL', *keys[start : start + REDIS_SCAN_COUNT])
+
+
+def create_cache_backend(
+    redis_url: str | None,
+    namespace: str,
+    maxsize: int,
+    ttl: float,
+    max_ttl: float | None = None,
+) -> CacheBackend:
+    """Redis when a URL is configured, otherwise an in-process TTLCache
+
+    The URL is checked here, so a bad REDIS_URL stops the app at startup
+    instead of failing every cached request once it's serving.
+    """
+    if redis_url:
+        parse_redis_url(redis_url)
+        return RedisCacheBackend(RedisPool(redis_url), namespace, ttl, max_ttl)
+    return MemoryCacheBackend(maxsize=maxsize, ttl=ttl, max_ttl=max_ttl)
@@ -0,0 +1,244 @@
+# ABOUTME: The weather command: current conditions and the forecast in the terminal,
+# ABOUTME: as tables or JSON, straight from the providers without running a server
+
+import argparse
+import contextlib
+import json
+import os
+import sys
+from collections.abc import Callable, Mapping, Sequence
+from typing import Any
+
+from weather_core.derived import COMPASS_POINTS, compass_index
+from weather_core.geocoding import geocode_place
+from weather_core.providers import WeatherProviderManager
+from weather_core.settings import load_provider_settings
+from weather_core.units import UNIT_LABELS, convert_to_metric
+
+
+MAX_LATITUDE = 90
+MAX_LONGITUDE = 180
+DEFAULT_HOURS = 12
+MAX_HOURS = 24
+DEFAULT_PROVIDERS_PATH = 'data/providers.json'
+
+EXIT_OK = 0
+EXIT_FAILED = 1
+EXIT_USAGE = 2
+
+
+def coordinate(limit: int) -> Callable[[str], float]:
+    """An argparse type for -limit..limit"""
+
+    def parse(value: str) -> float:
+        number = float(value)
+        if abs(number) > limit:
+            msg = f'must be between -{limit} and {limit}'
+            raise argparse.ArgumentTypeError(msg)
+        return number
+
+    return parse
+
+
+def hour_count(value: str) -> int:
+    """An argparse type for how many forecast hours to show"""
+    hours = int(value)
+    if not 0 <= hours <= MAX_HOURS:
+        msg = f'must be between 0 and {MAX_HOURS}'
+        raise argparse.ArgumentTypeError(msg)
+    return hours
+
+
+def build_parser() -> argparse.ArgumentParser:
+    """The weather command's options"""
+    parser = argparse.ArgumentParser(
+        prog='weather',
+        description='Current conditions and the forecast, without the server',
+    )
+    place = parser.add_mutually_exclusive_group(required=True)
+    place.add_argument('--city', help='place name to look up, e.g. "Chicago"')
+    place.add_argument(
+        '--lat', type=coordinate(MAX_LATITUDE), help='latitude (with --lon)'
+    )
+    parser.add_argument('--lon', type=coordinate(MAX_LONGITUDE), help='longitude')
+    parser.add_argument(
+        '--units', choices=sorted(UNIT_LABELS), default='imperial', help='units'
+    )
+    parser.add_argument(
+        '--provider', help='ask this provider only (default: primary with fallbacks)'
+    )
+    parser.add_argument(
+        '--providers-config',
+        metavar='PATH',
+        help=(
+            'providers JSON file '
+            f'(default: $PROVIDERS_CONFIG_PATH or {DEFAULT_PROVIDERS_PATH})'
+        ),
+    )
+    parser.add_argument(
+        '--format', choices=('table', 'json'), default='table', help='output format'
+    )
+    parser.add_argument(
+        '--hours',
+        type=hour_count,
+        default=DEFAULT_HOURS,
+        help=f'hours of forecast in the table (default: {DEFAULT_HOURS})',
+    )
+    parser.add_argument(
+        '--tz', help='IANA timezone for times, if the provider gives none'
+    )
+    parser.add_argument(
+        '--verbose', action='store_true', help='show provider progress on stderr'
+    )
+    return parser
+
+
+def reading(value: Any, unit: str = '') -> str:
+    """A value for the table, or a dash when the provider didn't give one"""
+    if value is None or value == '':
+        return '-'
+    return f'{value}{unit}'
+
+
+def table(rows: list[list[str]]) -> list[str]:
+    """Rows as left-aligned columns, the first row a header"""
+    widths = [max(len(row[i]) for row in rows) for i in range(len(rows[0]))]
+    return [
+        '  '.join(cell.ljust(width) for cell, width in zip(row, widths)).rstrip()
+        for row in rows
+    ]
+
+
+def condition(icon: str | None) -> str:
+    """An icon code as words, e.g. partly-cloudy-day as partly cloudy"""
+    if not icon:
+        return '-'
+    words = icon.split('-')
+    if words[-1] in ('day', 'night'):
+        words = words[:-1]
+    return ' '.join(words)
+
+
+def render_table(weather: dict[str, Any], units: str, hours: int) -> str:
+    """A weather document as a summary line and hourly and daily tables"""
+    labels = UNIT_LABELS[units]
+    degrees = labels['temperature']
+    current = weather.get('current') or {}
+    lines = [f'{weather.get("location", "")} ({weather.get("provider", "unknown")})']
+
+    if current:
+        summary = current.get('summary') or condition(current.get('icon'))
+        lines.append(
+            f'Now: {reading(current.get("temperature"), degrees)}, {summary}, '
+            f'feels like {reading(current.get("feels_like"), degrees)}'
+        )
+        wind = reading(current.get('wind_speed'), f' {labels["wind_speed"]}')
+        if isinstance(current.get('wind_direction'), int | float):
+            wind += f' {COMPASS_POINTS[compass_index(current["wind_direction"])]}'
+        lines.append(
+            f'Humidity {reading(current.get("humidity"), "%")}  Wind {wind}  '
+            f'UV {reading(current.get("uv_index"))}  '
+            f'Pressure {reading(current.get("pressure"), " hPa")}'
+        )
+
+    hourly = (weather.get('hourly') or [])[:hours]
+    if hourly:
+        rows = [['Hour', 'Temp', 'Rain', 'Conditions']]
+        rows += [
+            [
+                reading(hour.get('t')),
+                reading(hour.get('temp'), degrees),
+                reading(hour.get('rain'), '%'),
+                hour.get('desc') or condition(hour.get('icon')),
+            ]
+            for hour in hourly
+        ]
+        lines += ['', *table(rows)]
+
+    daily = weather.get('daily') or []
+    if daily:
+        rows = [['Day', 'High', 'Low', 'Rain', 'Conditions']]
+        rows += [
+            [
+                reading(day.get('d')),
+                reading(day.get('h'), degrees),
+                reading(day.get('l'), degrees),
+                reading(day.get('rain'), '%'),
+                condition(day.get('icon')),
+            ]
+            for day in daily
+        ]
+        lines += ['', *table(rows)]
+    return '\n'.join(lines)
+
+
+def main(
+    argv: Sequence[str] | None = None, environ: Mapping[str, str] | None = None
+) -> int:
+    """Look up the place, fetch its weather and print it"""
+    environ = os.environ if environ is None else environ
+    parser = build_parser()
+    args = parser.parse_args(argv)
+    if (args.lat is None) != (args.lon is None):
+        parser.error('give either --city or both --lat and --lon')
+    if args.providers_config and not os.path.exists(args.providers_config):
+        parser.error(f'{args.providers_config} does not exist')
+
+    # Providers report progress on stdout, which belongs to the output here
+    with contextlib.ExitStack() as stack:
+        progress = (
+            sys.stderr
+            if args.verbose
+            else stack.enter_context(open(os.devnull, 'w', encoding='utf-8'))
+        )
+        stack.enter_context(contextlib.redirect_stdout(progress))
+        if args.city:
+            match = geocode_place(args.city)
+            if match is None:
+                print(f'❌ No place called {args.city!r} found', file=sys.stderr)
+                return EXIT_FAILED
+            lat, lon, location = match
+        else:
+            lat, lon = args.lat, args.lon
+            location = f'{lat:.4f},{lon:.4f}'
+
+        try:
+            settings = load_provider_settings(
+                args.providers_config
+                or environ.get('PROVIDERS_CONFIG_PATH', DEFAULT_PROVIDERS_PATH),
+                environ,
+            )
+        except (OSError, ValueError) as e:
+            print(f'❌ {e}', file=sys.stderr)
+            return EXIT_FAILED
+        manager = WeatherProviderManager()
+        manager.configure(settings)
+
+        if args.provider:
+            provider = manager.providers.get(args.provider)
+            if provider is None:
+                available = ', '.join(manager.providers) or 'none'
+                print(
+                    f'❌ Unknown provider {args.provider!r} (have: {available})',
+                    file=sys.stderr,
+                )
+                return EXIT_USAGE
+            weather = provider.get_weather(lat, lon, location, args.tz)
+        else:
+            weather = manager.get_weather(lat, lon, location, args.tz)
+        if not weather:
+            print('❌ No weather data from any provider', file=sys.stderr)
+            return EXIT_FAILED
+
+    if args.units == 'metric':
+        weather = convert_to_metric(weather)
+    if args.format == 'json':
+        body = {**weather, 'units': UNIT_LABELS[args.units]}
+        print(json.dumps(body, indent=2, default=str))
+    else:
+        print(render_table(weather, args.units, args.hours))
+    return EXIT_OK
+
+
+if __name__ == '__main__':
+    sys.exit(main())
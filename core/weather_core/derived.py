@@ -0,0 +1,149 @@
+# ABOUTME: Derived weather metrics computed from provider data, language-neutral
+# ABOUTME: Beaufort force, compass sectors, precipitation start/end and pressure trends
+
+import bisect
+from datetime import datetime, timedelta
+
+
+MPH_TO_MS = 0.44704
+
+# Upper bounds (m/s, exclusive) of Beaufort forces 0-11; anything faster is 12
+BEAUFORT_LIMITS_MS = (0.3, 1.5, 3.3, 5.5, 7.9, 10.7, 13.8, 17.1, 20.7, 24.4, 28.4, 32.6)
+
+COMPASS_POINTS = (
+    'N', 'NNE', 'NE', 'ENE', 'E', 'ESE', 'SE', 'SSE',
+    'S', 'SSW', 'SW', 'WSW', 'W', 'WNW', 'NW', 'NNW',
+)  # fmt: skip
+
+
+def beaufort_force(wind_speed_mph: float) -> int:
+    """Classify a wind speed on the Beaufort scale (0-12)"""
+    return bisect.bisect_right(BEAUFORT_LIMITS_MS, wind_speed_mph * MPH_TO_MS)
+
+
+def compass_index(degrees: float) -> int:
+    """Get the 16-point compass sector (0 = N, clockwise) a direction falls in"""
+    return round((degrees % 360) / 22.5) % len(COMPASS_POINTS)
+
+
+# A forecast slot: (start, end, wet) with aware datetimes
+PrecipitationSlot = tuple[datetime, datetime, bool]
+
+PRECIP_PROBABILITY_THRESHOLD = 50  # % chance before an hourly slot counts as wet
+PRECIP_HORIZON = timedelta(hours=24)
+
+
+def minutes_between(start: datetime, end: datetime) -> int:
+    """Whole minutes from start to end, never negative"""
+    return max(0, int((end - start).total_seconds() // 60))
+
+
+def precipitation_timing(
+    slots: list[PrecipitationSlot], now: datetime, raining_now: bool = False
+) -> tuple[int | None, int | None]:
+    """Estimate minutes until precipitation starts and until it ends
+
+    Scans the forecast slots from now: while it's wet the start is 0 and the
+    end is the first dry slot; otherwise both describe the next wet spell.
+    Either is None when it falls outside the forecast or the 24 hour horizon.
+    """
+    horizon = now + PRECIP_HORIZON
+    upcoming = sorted(
+        (slot for slot in slots if slot[1] > now and slot[0] < horizon),
+        key=lambda slot: slot[0],
+    )
+    if upcoming and upcoming[0][0] <= now and upcoming[0][2]:
+        raining_now = True
+
+    starts_in = 0 if raining_now else None
+    for start, _, forecast_wet in upcoming:
+        # What's falling now overrides the forecast for the slot we're in
+        wet = forecast_wet or (raining_now and start <= now)
+        if starts_in is None:
+            if wet:
+                starts_in = minutes_between(now, start)
+        elif not wet:
+            return starts_in, minutes_between(now, start)
+    return starts_in, None
+
+
+# Pressure trend analysis constants
+MIN_PRESSURE_HISTORY_POINTS = 3
+PRESSURE_TREND_STEADY_THRESHOLD = 0.1  # hPa/hour
+PRESSURE_TREND_SIGNIFICANT_THRESHOLD = 0.5  # hPa/hour
+PRESSURE_HIGH_THRESHOLD = 1020  # hPa
+PRESSURE_NORMAL_THRESHOLD = 1000  # hPa
+
+
+def calculate_pressure_trend(pressure_history: list[dict]) -> dict:
+    """Calculate pressure trend indicators from historical data"""
+    if len(pressure_history) < MIN_PRESSURE_HISTORY_POINTS:
+        return {
+            'trend': 'steady',
+            'rate': 0.0,
+            'prediction': 'Unable to determine trend - insufficient data',
+        }
+
+    # Get current pressure and 3-hour ago pressure for trend calculation
+    current_pressure = pressure_history[0]['pressure'] if pressure_history else 0
+    three_hours_ago = pressure_history[min(3, len(pressure_history) - 1)]['pressure']
+
+    # Calculate rate of change in hPa per hour
+    pressure_change = current_pressure - three_hours_ago
+    rate_per_hour = pressure_change / 3.0  # 3-hour change divided by 3
+
+    # Determine trend direction
+    if abs(rate_per_hour) < PRESSURE_TREND_STEADY_THRESHOLD:
+        trend = 'steady'
+    elif rate_per_hour > PRESSURE_TREND_STEADY_THRESHOLD:
+        trend = 'rising'
+    else:
+        trend = 'falling'
+
+    # Generate weather prediction based on pressure trend
+    prediction = get_pressure_prediction(trend, rate_per_hour, current_pressure)
+
+    return {
+        'trend': trend,
+        'rate': round(rate_per_hour, 2),
+        'prediction': prediction,
+        'current_pressure': current_pressure,
+        'history': pressure_history[:12],  # Last 12 hours for mini-chart
+    }
+
+
+def get_pressure_prediction(trend: str, rate: float, current_pressure: float) -> str:
+    """Generate weather prediction based on pressure trends"""
+    predictions = {
+        'rising_fast': 'Improving weather expected - clearing skies likely',
+        'rising_slow': 'Weather gradually improving',
+        'steady_high': 'Continued fair weather',
+        'steady_normal': 'Current weather conditions expected to persist',
+        'steady_low': 'Unsettled weather may continue',
+        'falling_slow': 'Weather may deteriorate gradually',
+        'falling_fast': 'Stormy weather approaching - expect precipitation',
+    }
+
+    # Categorize pressure levels (typical sea level pressure ranges)
+    if current_pressure > PRESSURE_HIGH_THRESHOLD:
+        pressure_level = 'high'
+    elif current_pressure > PRESSURE_NORMAL_THRESHOLD:
+        pressure_level = 'normal'
+    else:
+        pressure_level = 'low'
+
+    # Categorize rate of change
+    if abs(rate) < PRESSURE_TREND_STEADY_THRESHOLD:
+        rate_category = 'steady'
+    elif abs(rate) > PRESSURE_TREND_SIGNIFICANT_THRESHOLD:
+        rate_category = f'{trend}_fast'
+    else:
+        rate_category = f'{trend}_slow'
+
+    # Combine trend and pressure level for prediction
+    if rate_category == 'steady':
+        prediction_key = f'steady_{pressure_level}'
+    else:
+        prediction_key = rate_category
+
+    return predictions.get(prediction_key, 'Weather pattern uncertain')
@@ -0,0 +1,47 @@
+# ABOUTME: Hour labels for forecasts ('2pm', '14:00', '14 Uhr') on a 12h or 24h clock
+# ABOUTME: in each supported language, shared by the providers and the server
+
+from datetime import datetime
+
+
+DEFAULT_CLOCK = '12h'
+DEFAULT_LANGUAGE = 'en'
+
+# Morning and afternoon patterns for 12-hour labels, per language
+TWELVE_HOUR_PATTERNS = {
+    'en': ('{hour}am', '{hour}pm'),
+    'es': ('{hour} a. m.', '{hour} p. m.'),
+    'fr': ('{hour} AM', '{hour} PM'),
+    'de': ('{hour} AM', '{hour} PM'),
+}
+# English keeps the zero-padded '09:00'; the others read '9 h', '9 Uhr'
+TWENTY_FOUR_HOUR_PATTERNS = {
+    'en': '{hour:02d}:00',
+    'es': '{hour}:00',
+    'fr': '{hour} h',
+    'de': '{hour} Uhr',
+}
+
+
+def hour_label(
+    hour: int | datetime,
+    clock: str = DEFAULT_CLOCK,
+    language: str | None = DEFAULT_LANGUAGE,
+) -> str:
+    """An hour of the day (0-23, or a datetime's wall-clock hour) as a label
+
+    12-hour labels run 12am, 1am ... 11am, 12pm, 1pm ... 11pm. Unknown
+    clocks read as 12h and unknown languages as English.
+    """
+    if isinstance(hour, datetime):
+        hour = hour.hour
+    if not 0 <= hour <= 23:  # noqa: PLR2004
+        msg = f'Hour {hour} is not between 0 and 23'
+        raise ValueError(msg)
+    if language not in TWELVE_HOUR_PATTERNS:
+        language = DEFAULT_LANGUAGE
+    if clock == '24h':
+        return TWENTY_FOUR_HOUR_PATTERNS[language].format(hour=hour)
+    morning, afternoon = TWELVE_HOUR_PATTERNS[language]
+    pattern = morning if hour < 12 else afternoon  # noqa: PLR2004
+    return pattern.format(hour=hour % 12 or 12)
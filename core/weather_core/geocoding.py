@@ -0,0 +1,71 @@
+# ABOUTME: Place names and postal codes to coordinates via Open-Meteo geocoding
+# ABOUTME: Answers, misses included, are cached for a day since places don't move
+
+from typing import Any
+
+from cachetools import TTLCache
+
+from weather_core.transport import get_transport
+
+
+# Geocoding API: Open-Meteo (free, multilingual place names)
+GEOCODING_BASE_URL = 'https://geocoding-api.open-meteo.com/v1/search'
+GEOCODING_TIMEOUT = 10
+
+# Cache for geocoded place names (24 hours TTL - places don't move)
+geocode_cache: TTLCache[str, Any] = TTLCache(maxsize=200, ttl=86400)
+
+
+def geocode_place(query: str) -> tuple[float, float, str] | None:
+    """Resolve a place name to coordinates and the geocoder's name for it"""
+    if query in geocode_cache:
+        return geocode_cache[query]  # type: ignore[no-any-return]
+
+    try:
+        response = get_transport().get(
+            GEOCODING_BASE_URL,
+            params={'name': query, 'count': 1, 'format': 'json'},
+            timeout=GEOCODING_TIMEOUT,
+        )
+        response.raise_for_status()
+        results = response.json().get('results') or []
+    except Exception as e:
+        print(f'❌ Geocoding error for {query}: {str(e)}')
+        return None
+
+    match = None
+    if results:
+        top = results[0]
+        match = (top['latitude'], top['longitude'], top.get('name', query))
+        print(f'🗺️  Geocoded {query} to {match[0]:.4f},{match[1]:.4f}')
+
+    geocode_cache[query] = match
+    return match
+
+
+def geocode_postal_code(code: str, country: str) -> tuple[float, float, str] | None:
+    """Resolve a postal code, limited to one country"""
+    cache_key = f'postal:{country}:{code}'
+    if cache_key in geocode_cache:
+        return geocode_cache[cache_key]  # type: ignore[no-any-return]
+
+    try:
+        response = get_transport().get(
+            GEOCODING_BASE_URL,
+            params={'name': code, 'countryCode': country, 'count': 1, 'format': 'json'},
+            timeout=GEOCODING_TIMEOUT,
+        )
+        response.raise_for_status()
+        results = response.json().get('results') or []
+    except Exception as e:
+        print(f'❌ Geocoding error for postal code {code} ({country}): {str(e)}')
+        return None
+
+    match = None
+    if results:
+        top = results[0]
+        match = (top['latitude'], top['longitude'], top.get('name', code))
+        print(f'🗺️  Geocoded {country} {code} to {match[0]:.4f},{match[1]:.4f}')
+
+    geocode_cache[cache_key] = match
+    return match
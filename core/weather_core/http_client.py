@@ -0,0 +1,207 @@
+# ABOUTME: Shared HTTP helper for provider requests: retries with exponential backoff
+# ABOUTME: and per-provider call quotas; only timeouts and 5xx replies are retried
+
+import secrets
+import threading
+import time
+from collections import deque
+from datetime import datetime, timedelta, timezone
+from typing import Any
+from urllib.parse import urlsplit
+
+import requests
+
+from weather_core.tracing import SPAN_KIND_CLIENT, tracer
+from weather_core.transport import get_transport
+
+
+SERVER_ERROR_STATUS = 500
+QUOTA_PERIODS = ('minute', 'day')
+
+
+class RetryPolicy:
+    """How many times to try a request and how long to wait in between
+
+    The wait doubles after each attempt, from base_delay up to max_delay, and
+    up to jitter times that again is added at random so replicas retrying the
+    same outage don't all hit the upstream together.
+    """
+
+    def __init__(
+        self,
+        max_attempts: int = 3,
+        base_delay: float = 0.5,
+        max_delay: float = 4.0,
+        jitter: float = 0.5,
+    ) -> None:
+        self.max_attempts = max_attempts
+        self.base_delay = base_delay
+        self.max_delay = max_delay
+        self.jitter = jitter
+
+    def delay(self, attempt: int) -> float:
+        """Seconds to wait after the given (1-based) failed attempt"""
+        backoff = min(self.base_delay * 2 ** (attempt - 1), self.max_delay)
+        return backoff + secrets.SystemRandom().uniform(0, backoff * self.jitter)
+
+    def describe(self) -> dict[str, Any]:
+        """The policy as shown in provider info"""
+        return {
+            'max_attempts': self.max_attempts,
+            'base_delay_seconds': self.base_delay,
+            'max_delay_seconds': self.max_delay,
+            'jitter': self.jitter,
+        }
+
+
+# Shared by every provider unless one is given its own; main.py configures it
+DEFAULT_RETRY_POLICY = RetryPolicy()
+
+
+class QuotaExceeded(Exception):
+    """A request was refused locally because the provider's quota is spent"""
+
+
+class ProviderQuota:
+    """Calls allowed to one upstream per minute and per UTC day
+
+    The minute is a sliding window; the day count resets at UTC midnight, which
+    is when the free tiers this exists for reset theirs. Either limit may be None.
+    """
+
+    def __init__(self, per_minute: int | None = None, per_day: int | None = None):
+        self.per_minute = per_minute
+        self.per_day = per_day
+        # Monotonic times of the calls made in the last minute
+        self.recent: deque[float] = deque()
+        self.day = datetime.now(timezone.utc).date()
+        self.used_today = 0
+        self.refused = 0
+        self._lock = threading.Lock()
+
+    @classmethod
+    def parse(cls, spec: str) -> 'ProviderQuota':
+        """A quota from '<calls>/minute,<calls>/day', either part optional"""
+        limits: dict[str, int] = {}
+        for part in spec.split(','):
+            calls, _, period = part.strip().partition('/')
+            period = period.strip().lower()
+            if period not in QUOTA_PERIODS or not calls.strip().isdigit():
+                msg = f'Unreadable quota {part.strip()!r} (expected e.g. 10/minute)'
+                raise ValueError(msg)
+            limits[period] = int(calls)
+        return cls(limits.get('minute'), limits.get('day'))
+
+    def _roll(self) -> None:
+        """Forget calls that left the minute window, and yesterday's count"""
+        cutoff = time.monotonic() - 60
+        while self.recent and self.recent[0] <= cutoff:
+            self.recent.popleft()
+        today = datetime.now(timezone.utc).date()
+        if today != self.day:
+            self.day = today
+            self.used_today = 0
+
+    def remaining(self) -> dict[str, int | None]:
+        """Calls left this minute and today, None where there's no limit"""
+        with self._lock:
+            self._roll()
+            return {
+                'minute': (
+                    max(self.per_minute - len(self.recent), 0)
+                    if self.per_minute is not None
+                    else None
+                ),
+                'day': (
+                    max(self.per_day - self.used_today, 0)
+                    if self.per_day is not None
+                    else None
+                ),
+            }
+
+    def available(self) -> bool:
+        """Whether another call fits in both limits right now"""
+        return all(left is None or left > 0 for left in self.remaining().values())
+
+    def acquire(self) -> None:
+        """Count one call, or raise QuotaExceeded if it doesn't fit"""
+        with self._lock:
+            self._roll()
+            if (
+                self.per_minute is not None and len(self.recent) >= self.per_minute
+            ) or (self.per_day is not None and self.used_today >= self.per_day):
+                self.refused += 1
+                msg = 'Provider quota exhausted'
+                raise QuotaExceeded(msg)
+            self.recent.append(time.monotonic())
+            self.used_today += 1
+
+    def describe(self) -> dict[str, Any]:
+        """The quota as shown in provider info"""
+        remaining = self.remaining()
+        resets = datetime.combine(
+            self.day + timedelta(days=1), datetime.min.time(), timezone.utc
+        )
+        return {
+            'per_minute': self.per_minute,
+            'per_day': self.per_day,
+            'remaining_minute': remaining['minute'],
+            'remaining_day': remaining['day'],
+            'used_today': self.used_today,
+            'refused': self.refused,
+            'day_resets_at': resets.isoformat(),
+        }
+
+
+def is_retryable(response: requests.Response) -> bool:
+    """Whether a reply is a server-side failure worth trying again"""
+    status = response.status_code
+    return isinstance(status, int) and status >= SERVER_ERROR_STATUS
+
+
+def get(
+    url: str,
+    policy: RetryPolicy | None = None,
+    quota: ProviderQuota | None = None,
+    **kwargs: Any,
+) -> requests.Response:
+    """A GET through the transport, retrying timeouts and 5xx replies per the policy
+
+    The last attempt's reply is returned (or its timeout raised) as is, so
+    callers check status codes exactly as they would without retries. Every
+    attempt counts against the quota; one that doesn't fit raises QuotaExceeded.
+    """
+    policy = policy or DEFAULT_RETRY_POLICY
+    host = urlsplit(url).netloc
+    # Only the host is recorded: some providers put their API key in the path
+    with tracer.span(
+        'HTTP GET',
+        {'http.request.method': 'GET', 'server.address': host},
+        SPAN_KIND_CLIENT,
+    ) as span:
+        attempt = 1
+        while True:
+            span.set_attribute('http.request.resend_count', attempt - 1)
+            if quota is not None:
+                quota.acquire()
+            try:
+                response = get_transport().get(url, **kwargs)
+            except requests.Timeout:
+                if attempt >= policy.max_attempts:
+                    raise
+                reason = 'timed out'
+            else:
+                if attempt >= policy.max_attempts or not is_retryable(response):
+                    span.set_attribute(
+                        'http.response.status_code', response.status_code
+                    )
+                    return response
+                reason = f'returned {response.status_code}'
+
+            delay = policy.delay(attempt)
+            print(
+                f'🔁 {host} {reason} - retrying in {delay:.1f}s '
+                f'(attempt {attempt + 1}/{policy.max_attempts})'
+            )
+            time.sleep(delay)
+            attempt += 1
@@ -0,0 +1,242 @@
+# ABOUTME: Typed views of raw Open-Meteo and PirateWeather responses, checked on parse
+# ABOUTME: Missing readings stay None instead of silently becoming 0
+
+from datetime import datetime, tzinfo
+from typing import Any
+
+from weather_core.timezones import localize, resolve_timezone
+
+
+class ResponseError(ValueError):
+    """A provider response lacks a required field or has the wrong shape"""
+
+
+def reading(block: dict[str, Any], name: str) -> float | None:
+    """A numeric field, or None when it's absent, null or not a number"""
+    value = block.get(name)
+    if isinstance(value, bool) or not isinstance(value, int | float):
+        return None
+    return value
+
+
+def required_reading(block: dict[str, Any], name: str, source: str) -> float:
+    """A numeric field the response is useless without"""
+    value = reading(block, name)
+    if value is None:
+        msg = f'{source} response has no {name}'
+        raise ResponseError(msg)
+    return value
+
+
+def series(
+    block: dict[str, Any], name: str, length: int, required: bool = False
+) -> list[float | None]:
+    """One reading per timestep, padded with None to the block's length"""
+    values = block.get(name)
+    if not isinstance(values, list):
+        if required:
+            msg = f'Response series {name} is missing'
+            raise ResponseError(msg)
+        values = []
+    parsed = [reading({name: value}, name) for value in values[:length]]
+    return parsed + [None] * (length - len(parsed))
+
+
+def section(raw: dict[str, Any], name: str) -> dict[str, Any] | None:
+    """A nested object, None when the response left it out"""
+    value = raw.get(name)
+    if value is None:
+        return None
+    if not isinstance(value, dict):
+        msg = f'Response block {name} is not an object'
+        raise ResponseError(msg)
+    return value
+
+
+def parse_times(block: dict[str, Any]) -> list[str]:
+    """The ISO timestamps a block's series are indexed by"""
+    times = block.get('time')
+    if not isinstance(times, list) or not all(isinstance(t, str) for t in times):
+        msg = 'Response block has no time series'
+        raise ResponseError(msg)
+    return times
+
+
+def parse_moment(time_str: str) -> datetime:
+    """An Open-Meteo timestamp, accepting a trailing Z for UTC"""
+    try:
+        return datetime.fromisoformat(time_str.replace('Z', '+00:00'))
+    except ValueError as e:
+        msg = f'Unreadable timestamp {time_str!r}'
+        raise ResponseError(msg) from e
+
+
+def first_known(*values: Any) -> Any:
+    """The first value that isn't None, for blending several sources"""
+    return next((value for value in values if value is not None), None)
+
+
+def rounded(value: float | None, digits: int | None = None) -> float | None:
+    """round() that passes a missing reading through"""
+    if value is None:
+        return None
+    return round(value, digits) if digits is not None else round(value)
+
+
+class OpenMeteoCurrent:
+    """The 'current' block: conditions right now"""
+
+    def __init__(self, block: dict[str, Any]) -> None:
+        self.temperature = required_reading(block, 'temperature_2m', 'Open-Meteo')
+        self.weather_code = int(required_reading(block, 'weather_code', 'Open-Meteo'))
+        self.feels_like = reading(block, 'apparent_temperature')
+        self.humidity = reading(block, 'relative_humidity_2m')
+        self.wind_speed = reading(block, 'wind_speed_10m')
+        self.wind_direction = reading(block, 'wind_direction_10m')
+        self.wind_gust = reading(block, 'wind_gusts_10m')
+        self.uv_index = reading(block, 'uv_index')
+        self.pressure = reading(block, 'pressure_msl')
+        self.dew_point = reading(block, 'dew_point_2m')
+        self.precipitation = reading(block, 'precipitation')
+        self.rain = reading(block, 'rain')
+        self.showers = reading(block, 'showers')
+        self.snowfall = reading(block, 'snowfall')
+        # Only a reported night turns the icons to night ones
+        self.is_day = reading(block, 'is_day') != 0
+
+
+class OpenMeteoHourly:
+    """The 'hourly' block: parallel series indexed by hour"""
+
+    def __init__(self, block: dict[str, Any], zone: tzinfo) -> None:
+        self.times = parse_times(block)
+        self.moments = [
+            localize(parse_moment(time_str), zone) for time_str in self.times
+        ]
+        length = len(self.times)
+        self.temperature = series(block, 'temperature_2m', length, required=True)
+        self.weather_code = series(block, 'weather_code', length, required=True)
+        # Older responses may lack this; fall back to the air temperature
+        self.feels_like = [
+            first_known(apparent, temp)
+            for apparent, temp in zip(
+                series(block, 'apparent_temperature', length),
+                self.temperature,
+                strict=True,
+            )
+        ]
+        self.precipitation_probability = series(
+            block, 'precipitation_probability', length
+        )
+        self.precipitation = series(block, 'precipitation', length)
+        self.pressure = series(block, 'pressure_msl', length)
+
+
+class OpenMeteoDaily:
+    """The 'daily' block: parallel series indexed by date"""
+
+    def __init__(self, block: dict[str, Any]) -> None:
+        self.times = parse_times(block)
+        length = len(self.times)
+        self.high = series(block, 'temperature_2m_max', length, required=True)
+        self.low = series(block, 'temperature_2m_min', length, required=True)
+        self.weather_code = series(block, 'weather_code', length, required=True)
+        self.precipitation_probability = series(
+            block, 'precipitation_probability_max', length
+        )
+        sunrise = block.get('sunrise')
+        sunset = block.get('sunset')
+        self.sunrise: list[str] | None = sunrise if isinstance(sunrise, list) else None
+        self.sunset: list[str] | None = sunset if isinstance(sunset, list) else None
+
+
+class OpenMeteoMinutely:
+    """The 'minutely_15' block: parallel series in 15-minute steps"""
+
+    def __init__(self, block: dict[str, Any], zone: tzinfo) -> None:
+        self.times = parse_times(block)
+        self.moments = [
+            localize(parse_moment(time_str), zone) for time_str in self.times
+        ]
+        length = len(self.times)
+        self.temperature = series(block, 'temperature_2m', length)
+        self.precipitation = series(block, 'precipitation', length)
+        self.rain = series(block, 'rain', length)
+        self.snowfall = series(block, 'snowfall', length)
+        self.weather_code = series(block, 'weather_code', length)
+
+
+class OpenMeteoResponse:
+    """A forecast response; split fetches leave out the blocks not asked for"""
+
+    def __init__(self, raw: dict[str, Any]) -> None:
+        current = section(raw, 'current')
+        hourly = section(raw, 'hourly')
+        daily = section(raw, 'daily')
+        minutely = section(raw, 'minutely_15')
+        if current is None and hourly is None and daily is None:
+            msg = 'Open-Meteo response has no forecast blocks'
+            raise ResponseError(msg)
+        timezone_name = raw.get('timezone')
+        self.timezone = timezone_name if isinstance(timezone_name, str) else None
+        offset = reading(raw, 'utc_offset_seconds')
+        self.utc_offset_seconds = int(offset) if offset is not None else None
+        # Times are wall-clock times in the response's timezone, GMT unless asked
+        self.zone = resolve_timezone(
+            self.timezone, utc_offset_seconds=self.utc_offset_seconds or 0
+        )
+        self.current = OpenMeteoCurrent(current) if current is not None else None
+        self.hourly = OpenMeteoHourly(hourly, self.zone) if hourly else None
+        self.daily = OpenMeteoDaily(daily) if daily else None
+        self.minutely = OpenMeteoMinutely(minutely, self.zone) if minutely else None
+
+
+class PirateWeatherPoint:
+    """One 'currently' or hourly data point"""
+
+    def __init__(self, block: dict[str, Any], required: tuple[str, ...]) -> None:
+        for name in required:
+            required_reading(block, name, 'PirateWeather')
+        self.time = reading(block, 'time')
+        self.temperature = reading(block, 'temperature')
+        self.feels_like = reading(block, 'apparentTemperature')
+        self.humidity = reading(block, 'humidity')
+        self.wind_speed = reading(block, 'windSpeed')
+        self.uv_index = reading(block, 'uvIndex')
+        self.pressure = reading(block, 'pressure')
+        self.visibility = reading(block, 'visibility')
+        self.precipitation_rate = reading(block, 'precipIntensity')
+        self.precipitation_probability = reading(block, 'precipProbability')
+        precip_type = block.get('precipType')
+        self.precipitation_type = precip_type if isinstance(precip_type, str) else None
+        icon = block.get('icon')
+        self.icon = icon if isinstance(icon, str) else None
+        summary = block.get('summary')
+        self.summary = summary if isinstance(summary, str) else None
+
+
+class PirateWeatherResponse:
+    """A forecast response fetched with minutely, daily and alerts excluded"""
+
+    def __init__(self, raw: dict[str, Any]) -> None:
+        currently = section(raw, 'currently')
+        if currently is None:
+            msg = 'PirateWeather response has no currently block'
+            raise ResponseError(msg)
+        timezone_name = raw.get('timezone')
+        self.timezone = timezone_name if isinstance(timezone_name, str) else None
+        offset_hours = reading(raw, 'offset')
+        self.utc_offset_seconds = (
+            round(offset_hours * 3600) if offset_hours is not None else None
+        )
+        self.currently = PirateWeatherPoint(currently, required=('temperature',))
+        hourly = section(raw, 'hourly') or {}
+        data = hourly.get('data', [])
+        if not isinstance(data, list):
+            msg = 'PirateWeather hourly data is not a list'
+            raise ResponseError(msg)
+        self.hourly = [
+            PirateWeatherPoint(hour, required=('time',))
+            for hour in data
+            if isinstance(hour, dict)
+        ]
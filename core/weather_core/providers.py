@@ -1,10 +1,17 @@
 # ABOUTME: Weather provider classes for OpenMeteo and National Weather Service APIs
 # ABOUTME: Abstraction layer for weather data access with multiple providers
 
+import copy
 import math
+import random
+import statistics
+import threading
 import time
 from abc import ABC, abstractmethod
-from datetime import datetime, timedelta, timezone
+from collections import Counter, deque
+from collections.abc import Callable, Hashable, Sequence
+from concurrent.futures import FIRST_COMPLETED, Future, wait
+from datetime import datetime, timedelta, timezone, tzinfo
 from typing import Any
 
 
@@ -15,13 +22,112 @@ except ImportError:
 
 import requests
 
+from weather_core import http_client
+from weather_core.derived import (
+    PRECIP_PROBABILITY_THRESHOLD,
+    PrecipitationSlot,
+    calculate_pressure_trend,
+    precipitation_timing,
+)
+from weather_core.formatting import hour_label
+from weather_core.provider_responses import (
+    OpenMeteoMinutely,
+    OpenMeteoResponse,
+    PirateWeatherResponse,
+    ResponseError,
+    first_known,
+    rounded,
+)
+from weather_core.runtime import make_executor
+from weather_core.settings import ProviderSettings
+from weather_core.timezones import resolve_timezone
+from weather_core.tracing import tracer
+from weather_core.transport import get_transport
+
+
+# Sections of the weather document that can be fetched on their own, with the
+# companion fields each one needs to render
+WEATHER_SECTIONS: dict[str, tuple[str, ...]] = {
+    'current': ('current', 'minutely'),
+    'hourly': ('hourly', 'pressure_trend'),
+    'daily': ('daily', 'sun'),
+}
+
+
+# What a provider can declare it supplies, and the document fields each covers
+# ('section.field' for a single reading). Missing fields are filled from another
+# provider that declares them; daily_16 is informational only
+CAPABILITY_FIELDS: dict[str, tuple[str, ...]] = {
+    'current': ('current',),
+    'minutely': ('minutely',),
+    'hourly': ('hourly', 'pressure_trend'),
+    'daily': ('daily', 'sun'),
+    'uv': ('current.uv_index',),
+    'alerts': ('alerts',),
+    'daily_16': (),
+}
+
+
+# Hours in an hourly forecast, and in PirateWeather's short real-time strip
+HOURLY_FORECAST_HOURS = 24
+HOURLY_SHORT_HOURS = 6
+
+
+def upcoming_hours(moments: Sequence[datetime], now: datetime, count: int) -> range:
+    """Indexes of the next `count` hourly slots, starting at the hour `now` is in
+
+    Series don't start at the current hour (Open-Meteo's start at local
+    midnight), so earlier slots are skipped. Pass `now` in the location's
+    zone so the hour boundary is its local one.
+    """
+    current_hour = now.replace(minute=0, second=0, microsecond=0)
+    start = next((i for i, moment in enumerate(moments) if moment >= current_hour), 0)
+    return range(start, min(start + count, len(moments)))
+
+
+def document_field(document: dict[str, Any], path: str) -> Any:
+    """A top-level field, or one reading inside a section for 'section.field'"""
+    section, _, name = path.partition('.')
+    value = document.get(section)
+    if not name:
+        return value
+    return value.get(name) if isinstance(value, dict) else None
+
+
+def is_missing(value: Any) -> bool:
+    """Whether a document field is absent or empty"""
+    return value is None or value == [] or value == {}
+
+
+def extract_weather_section(weather_data: dict[str, Any], section: str) -> dict:
+    """Cut one section (and its companion fields) out of a weather document"""
+    extracted = {
+        field: weather_data[field]
+        for field in WEATHER_SECTIONS[section]
+        if field in weather_data
+    }
+    for field in ('location', 'provider'):
+        if field in weather_data:
+            extracted[field] = weather_data[field]
+    return extracted
+
 
 class WeatherProvider(ABC):
     """Abstract base class for weather providers"""
 
+    # Where health probes ask for weather (Chicago, the default location)
+    HEALTH_PROBE_LOCATION = (41.8781, -87.6298)
+
     def __init__(self, name: str):
         self.name = name
         self.timeout = 10
+        # How long this provider's data stays current; None uses the app default
+        self.cache_ttl: int | None = None
+        self.retry_policy = http_client.DEFAULT_RETRY_POLICY
+        # Upstream calls allowed per minute and day; None is unlimited
+        self.quota: http_client.ProviderQuota | None = None
+        # CAPABILITY_FIELDS keys this provider supplies; empty means undeclared
+        self.capabilities: frozenset[str] = frozenset()
 
     @abstractmethod
     def fetch_weather_data(
@@ -48,21 +154,109 @@ class WeatherProvider(ABC):
         tz_name: str | None = None,
     ) -> dict[str, Any] | None:
         """Get processed weather data for coordinates"""
+        attributes = {'weather.provider': self.name}
+        with tracer.span('provider.get_weather', attributes) as span:
+            try:
+                raw_data = self.fetch_weather_data(lat, lon, tz_name)
+            except Exception as e:
+                print(f'❌ {self.name} provider error: {str(e)}')
+                span.record_error(f'{type(e).__name__}: {e}')
+                return None
+            if not raw_data:
+                span.record_error('Upstream returned no data')
+                return None
+            with tracer.span('provider.parse', attributes):
+                return self.process_weather_data(raw_data, location_name, tz_name)
+
+    def get_weather_section(
+        self,
+        lat: float,
+        lon: float,
+        section: str,
+        location_name: str | None = None,
+        tz_name: str | None = None,
+    ) -> dict[str, Any] | None:
+        """Get one section of the weather document - fetches everything by default"""
+        weather_data = self.get_weather(lat, lon, location_name, tz_name)
+        if not weather_data:
+            return None
+        return extract_weather_section(weather_data, section)
+
+    def supports(self, capability: str) -> bool:
+        """Whether the provider declares a capability, assuming so if undeclared"""
+        return not self.capabilities or capability in self.capabilities
+
+    def has_quota(self) -> bool:
+        """Whether the provider's quota allows another upstream call now"""
+        return self.quota is None or self.quota.available()
+
+    def http_get(self, url: str, **kwargs: Any) -> requests.Response:
+        """GET an upstream URL, retried per this provider's policy and quota"""
+        return http_client.get(url, self.retry_policy, self.quota, **kwargs)
+
+    def health_check(self) -> bool:
+        """Cheap probe of whether the provider is answering right now"""
+        lat, lon = self.HEALTH_PROBE_LOCATION
+        try:
+            return bool(self.fetch_weather_data(lat, lon))
+        except Exception as e:
+            print(f'❌ {self.name} health probe failed: {str(e)}')
+            return False
+
+    def dry_run(
+        self,
+        lat: float,
+        lon: float,
+        location_name: str | None = None,
+        tz_name: str | None = None,
+    ) -> dict[str, Any]:
+        """Fetch and process once, keeping the raw payload, the result and timings
+
+        Nothing is cached and no health is recorded; upstream calls still count
+        against the quota.
+        """
+        error = None
+        raw_data = processed = None
+        started = time.monotonic()
         try:
             raw_data = self.fetch_weather_data(lat, lon, tz_name)
         except Exception as e:
-            print(f'❌ {self.name} provider error: {str(e)}')
-            return None
-        else:
-            if raw_data:
-                return self.process_weather_data(raw_data, location_name, tz_name)
-            return None
+            error = f'Fetch failed: {str(e)}'
+        fetched = time.monotonic()
+        if raw_data:
+            try:
+                processed = self.process_weather_data(raw_data, location_name, tz_name)
+            except Exception as e:
+                error = f'Processing failed: {str(e)}'
+            else:
+                if not processed:
+                    error = 'Processing returned no data'
+        elif error is None:
+            error = 'Upstream returned no data'
+        finished = time.monotonic()
+
+        return {
+            'provider': self.name,
+            'ok': error is None,
+            'error': error,
+            'raw': raw_data,
+            'processed': processed,
+            'timing': {
+                'fetch_ms': round((fetched - started) * 1000),
+                'process_ms': round((finished - fetched) * 1000),
+                'total_ms': round((finished - started) * 1000),
+            },
+        }
 
     def get_provider_info(self) -> dict[str, Any]:
         """Get information about this provider"""
         return {
             'name': self.name,
             'timeout': self.timeout,
+            'cache_ttl': self.cache_ttl,
+            'retry_policy': self.retry_policy.describe(),
+            'capabilities': sorted(self.capabilities),
+            'quota': self.quota.describe() if self.quota else None,
             'description': self.__doc__ or f'{self.name} weather provider',
         }
 
@@ -70,17 +264,27 @@ class WeatherProvider(ABC):
 class OpenMeteoProvider(WeatherProvider):
     """Open-Meteo weather provider - free, accurate, European weather service"""
 
+    # Open-Meteo request blocks behind each weather document section
+    SECTION_BLOCKS: dict[str, tuple[str, ...]] = {
+        'current': ('current', 'minutely_15'),
+        'hourly': ('hourly',),
+        'daily': ('daily',),
+    }
+
     def __init__(self) -> None:
         super().__init__('OpenMeteo')
+        self.capabilities = frozenset({'current', 'minutely', 'hourly', 'daily', 'uv'})
         self.base_url = 'https://api.open-meteo.com/v1/forecast'
+        self.cache_ttl = 900  # Current conditions update every 15 minutes
 
     def fetch_weather_data(
         self,
         lat: float,
         lon: float,
         tz_name: str | None = None,  # noqa: ARG002
+        sections: tuple[str, ...] | None = None,
     ) -> dict | None:
-        """Fetch weather data from Open-Meteo API"""
+        """Fetch weather data from Open-Meteo API, optionally only some sections"""
         try:
             # Build comprehensive weather request with real-time features
             params: dict[str, str | float | int] = {
@@ -96,7 +300,8 @@ class OpenMeteoProvider(WeatherProvider):
                     'temperature_2m,precipitation,rain,snowfall,weather_code'
                 ),
                 'hourly': (
-                    'temperature_2m,precipitation_probability,precipitation,'
+                    'temperature_2m,apparent_temperature,'
+                    'precipitation_probability,precipitation,'
                     'rain,showers,snowfall,weather_code,cloud_cover,wind_speed_10m,'
                     'pressure_msl'
                 ),
@@ -114,8 +319,20 @@ class OpenMeteoProvider(WeatherProvider):
                 'forecast_days': 7,
             }
 
+            # Split fetch: leave out the blocks no requested section needs
+            if sections:
+                wanted = {
+                    block
+                    for section in sections
+                    for block in self.SECTION_BLOCKS[section]
+                }
+                for blocks in self.SECTION_BLOCKS.values():
+                    for block in blocks:
+                        if block not in wanted:
+                            params.pop(block, None)
+
             # Build the full URL for debugging
-            response = requests.get(self.base_url, params=params, timeout=self.timeout)
+            response = self.http_get(self.base_url, params=params, timeout=self.timeout)
             print(f'🌤️  Open-Meteo API URL: {response.url}')
             response.raise_for_status()
 
@@ -125,6 +342,28 @@ class OpenMeteoProvider(WeatherProvider):
             print(f'❌ Open-Meteo API error: {str(e)}')
             return None
 
+    def get_weather_section(
+        self,
+        lat: float,
+        lon: float,
+        section: str,
+        location_name: str | None = None,
+        tz_name: str | None = None,
+    ) -> dict[str, Any] | None:
+        """Get one section, requesting only the Open-Meteo blocks it needs"""
+        raw_data = self.fetch_weather_data(lat, lon, tz_name, sections=(section,))
+        if not raw_data:
+            return None
+        weather_data = self.process_weather_data(raw_data, location_name, tz_name)
+        if not weather_data:
+            return None
+        return extract_weather_section(weather_data, section)
+
+    def health_check(self) -> bool:
+        """Probe with a current-conditions-only request"""
+        lat, lon = self.HEALTH_PROBE_LOCATION
+        return bool(self.fetch_weather_data(lat, lon, sections=('current',)))
+
     def process_weather_data(
         self,
         raw_data: dict,
@@ -136,130 +375,109 @@ class OpenMeteoProvider(WeatherProvider):
             return None
 
         try:
-            current = raw_data.get('current', {})
-            hourly = raw_data.get('hourly', {})
-            daily = raw_data.get('daily', {})
-
-            # Debug: Check what wind data we're getting
-            wind_speed = current.get('wind_speed_10m')
-            wind_direction = current.get('wind_direction_10m')
-            wind_msg = f'Wind: speed={wind_speed}, direction={wind_direction}'
-            print(f'🌬️  {wind_msg}')
+            response = OpenMeteoResponse(raw_data)
+        except ResponseError as e:
+            print(f'❌ Unusable Open-Meteo response: {str(e)}')
+            return None
 
-            # Extract timezone from OpenMeteo response (overrides parameter)
-            api_timezone = raw_data.get('timezone')
-            if api_timezone:
-                tz_name = api_timezone
-                print(f'🌍 Using timezone from API: {tz_name}')
+        try:
+            current = response.current
+            hourly = response.hourly
+            daily = response.daily
+
+            # The location's own timezone (from the response) beats the parameter
+            if response.timezone:
+                print(f'🌍 Using timezone from API: {response.timezone}')
+            tz = resolve_timezone(
+                response.timezone,
+                tz_name,
+                utc_offset_seconds=response.utc_offset_seconds,
+            )
 
             # Process current weather with enhanced real-time data
-            current_weather = {
-                'temperature': round(current.get('temperature_2m', 0)),
-                'feels_like': round(current.get('apparent_temperature', 0)),
-                'humidity': current.get('relative_humidity_2m', 0),
-                'wind_speed': round(current.get('wind_speed_10m', 0)),
-                'wind_direction': current.get('wind_direction_10m'),
-                'wind_gust': round(current.get('wind_gusts_10m', 0)),
-                'uv_index': current.get('uv_index', 0),
-                'pressure': round(current.get('pressure_msl', 0), 2),
-                'dew_point': round(current.get('dew_point_2m', 0)),
-                'precipitation_rate': current.get('precipitation', 0),
-                'rain_rate': current.get('rain', 0),
-                'shower_rate': current.get('showers', 0),
-                'snow_rate': current.get('snowfall', 0),
-                'precipitation_prob': 0,  # Current doesn't have probability
-                'precipitation_type': self._determine_precipitation_type(
-                    current.get('rain', 0),
-                    current.get('showers', 0),
-                    current.get('snowfall', 0),
-                ),
-                'is_day': current.get('is_day', 1) == 1,
-                'icon': self._map_weather_code(
-                    current.get('weather_code', 0), current.get('is_day', 1) == 1
-                ),
-                'summary': self._get_weather_description(
-                    current.get('weather_code', 0)
-                ),
-            }
+            current_weather: dict[str, Any] = {}
+            if current:
+                wind_msg = (
+                    f'Wind: speed={current.wind_speed}, '
+                    f'direction={current.wind_direction}'
+                )
+                print(f'🌬️  {wind_msg}')
+                current_weather = {
+                    'temperature': round(current.temperature),
+                    'feels_like': rounded(current.feels_like),
+                    'humidity': current.humidity,
+                    'wind_speed': rounded(current.wind_speed),
+                    'wind_direction': current.wind_direction,
+                    'wind_gust': rounded(current.wind_gust),
+                    'uv_index': current.uv_index,
+                    'pressure': rounded(current.pressure, 2),
+                    'dew_point': rounded(current.dew_point),
+                    'precipitation_rate': current.precipitation,
+                    'rain_rate': current.rain,
+                    'shower_rate': current.showers,
+                    'snow_rate': current.snowfall,
+                    'precipitation_prob': 0,  # Current doesn't have probability
+                    'precipitation_type': self._determine_precipitation_type(
+                        current.rain or 0, current.showers or 0, current.snowfall or 0
+                    ),
+                    'is_day': current.is_day,
+                    'icon': self._map_weather_code(
+                        current.weather_code, current.is_day
+                    ),
+                    'summary': self._get_weather_description(current.weather_code),
+                }
 
             # Process hourly forecast (next 24 hours starting from current hour)
             hourly_forecast = []
-            if hourly.get('time'):
-                tz = (
-                    zoneinfo.ZoneInfo(tz_name)
-                    if tz_name
-                    else zoneinfo.ZoneInfo('America/Chicago')
-                )
-                current_time = datetime.now(tz)
-
-                # Find the starting index (current hour or next hour)
-                start_index = 0
-                for i, time_str in enumerate(hourly['time']):
-                    hour_time = datetime.fromisoformat(
-                        time_str.replace('Z', '+00:00')
-                    ).astimezone(tz)
-                    if hour_time >= current_time.replace(
-                        minute=0, second=0, microsecond=0
-                    ):
-                        start_index = i
-                        break
-
-                # Get next 24 hours starting from current/next hour
-                pressure_history = []  # Store for trend analysis
-                for i in range(start_index, min(start_index + 24, len(hourly['time']))):
-                    pressure_value = hourly.get(
-                        'pressure_msl', [0] * len(hourly['time'])
-                    )[i]
+            pressure_history = []  # Store for trend analysis
+            if hourly:
+                local_times = [moment.astimezone(tz) for moment in hourly.moments]
+                for i in upcoming_hours(
+                    local_times, datetime.now(tz), HOURLY_FORECAST_HOURS
+                ):
+                    pressure = rounded(hourly.pressure[i], 1)
+                    amount = hourly.precipitation[i]
                     hour_data = {
-                        'temp': round(hourly['temperature_2m'][i]),
-                        'icon': self._map_weather_code(hourly['weather_code'][i]),
-                        'rain': hourly['precipitation_probability'][i]
-                        if i < len(hourly.get('precipitation_probability', []))
-                        else 0,
-                        't': datetime.fromisoformat(
-                            hourly['time'][i].replace('Z', '+00:00')
-                        )
-                        .astimezone(tz)
-                        .strftime('%I%p')
-                        .lower()
-                        .replace('0', ''),
-                        'desc': self._get_weather_description(
-                            hourly['weather_code'][i]
-                        ),
-                        'pressure': round(pressure_value, 1),
+                        'temp': rounded(hourly.temperature[i]),
+                        'feels_like': rounded(hourly.feels_like[i]),
+                        'icon': self._map_weather_code(hourly.weather_code[i]),
+                        'rain': hourly.precipitation_probability[i],
+                        'precipitation': rounded(amount, 2),
+                        't': hour_label(local_times[i]),
+                        'desc': self._get_weather_description(hourly.weather_code[i]),
+                        'pressure': pressure,
+                        'time': hourly.times[i],
                     }
                     hourly_forecast.append(hour_data)
-                    pressure_history.append(
-                        {
-                            'time': hourly['time'][i],
-                            'pressure': round(pressure_value, 1),
-                        }
-                    )
+                    # A missing reading would read as a pressure crash
+                    if pressure is not None:
+                        pressure_history.append(
+                            {'time': hourly.times[i], 'pressure': pressure}
+                        )
 
             # Process daily forecast
             daily_forecast = []
-            if daily.get('time'):
-                for i in range(min(7, len(daily['time']))):
+            if daily:
+                for i in range(min(7, len(daily.times))):
                     day_data = {
-                        'h': round(daily['temperature_2m_max'][i]),
-                        'l': round(daily['temperature_2m_min'][i]),
-                        'icon': self._map_weather_code(daily['weather_code'][i]),
-                        'd': (
-                            datetime.fromisoformat(daily['time'][i])
-                            .astimezone(tz)
-                            .strftime('%a')
-                        ),
+                        'h': rounded(daily.high[i]),
+                        'l': rounded(daily.low[i]),
+                        'icon': self._map_weather_code(daily.weather_code[i]),
+                        'rain': daily.precipitation_probability[i],
+                        # Dates are already the location's calendar days
+                        'd': datetime.fromisoformat(daily.times[i]).strftime('%a'),
+                        'date': daily.times[i],
                     }
                     daily_forecast.append(day_data)
 
             # Process sunrise/sunset data
             sun_data = {}
-            if daily.get('time') and daily.get('sunrise') and daily.get('sunset'):
-                for i in range(min(7, len(daily['time']))):
-                    date_str = daily['time'][i]
+            if daily and daily.sunrise and daily.sunset:
+                for i in range(min(7, len(daily.times))):
+                    date_str = daily.times[i]
                     sun_data[date_str] = {
-                        'sunrise': daily['sunrise'][i],
-                        'sunset': daily['sunset'][i],
+                        'sunrise': daily.sunrise[i],
+                        'sunset': daily.sunset[i],
                     }
 
         except Exception as e:
@@ -267,13 +485,19 @@ class OpenMeteoProvider(WeatherProvider):
             return None
         else:
             # Process 15-minute precipitation data for real-time updates
-            minutely_data = self._process_minutely_data(
-                raw_data.get('minutely_15', {}), tz_name
-            )
+            minutely_data = self._process_minutely_data(response.minutely, tz)
+
+            # Estimate when precipitation starts/ends so clients don't rescan
+            if current_weather:
+                starts_in, ends_in = precipitation_timing(
+                    self._precipitation_slots(response),
+                    datetime.now(timezone.utc),
+                    raining_now=bool(current_weather['precipitation_rate']),
+                )
+                current_weather['precip_starts_in_minutes'] = starts_in
+                current_weather['precip_ends_in_minutes'] = ends_in
 
             # Calculate pressure trends
-            from main import calculate_pressure_trend
-
             pressure_trend = calculate_pressure_trend(pressure_history)
 
             return {
@@ -393,32 +617,53 @@ class OpenMeteoProvider(WeatherProvider):
 
         return None
 
-    def _process_minutely_data(self, minutely: dict, tz_name: str | None) -> list[dict]:
+    def _precipitation_slots(
+        self, response: OpenMeteoResponse
+    ) -> list[PrecipitationSlot]:
+        """Wet/dry forecast slots - 15-minute amounts, then hourly probabilities"""
+        slots: list[PrecipitationSlot] = []
+        if response.minutely:
+            minutely = response.minutely
+            for moment, amount in zip(
+                minutely.moments, minutely.precipitation, strict=True
+            ):
+                slots.append(
+                    (moment, moment + timedelta(minutes=15), (amount or 0) > 0)
+                )
+
+        covered_until = slots[-1][1] if slots else None
+        if response.hourly:
+            hourly = response.hourly
+            for moment, chance in zip(
+                hourly.moments, hourly.precipitation_probability, strict=True
+            ):
+                if covered_until is None or moment >= covered_until:
+                    wet = (chance or 0) >= PRECIP_PROBABILITY_THRESHOLD
+                    slots.append((moment, moment + timedelta(hours=1), wet))
+        return slots
+
+    def _process_minutely_data(
+        self, minutely: OpenMeteoMinutely | None, tz: tzinfo
+    ) -> list[dict]:
         """Process 15-minutely data for real-time precipitation tracking"""
         minutely_data: list[dict] = []
 
-        if not minutely.get('time'):
+        if not minutely:
             return minutely_data
 
         try:
-            tz = (
-                zoneinfo.ZoneInfo(tz_name)
-                if tz_name
-                else zoneinfo.ZoneInfo('America/Chicago')
-            )
-
             # Get next 2 hours of 15-minute data (8 intervals)
-            for i in range(min(8, len(minutely['time']))):
-                time_str = minutely['time'][i]
+            for i in range(min(8, len(minutely.times))):
+                weather_code = minutely.weather_code[i]
                 minute_data = {
-                    'time': datetime.fromisoformat(time_str.replace('Z', '+00:00'))
-                    .astimezone(tz)
-                    .strftime('%H:%M'),
-                    'temp': round(minutely.get('temperature_2m', [0])[i]),
-                    'precipitation': minutely.get('precipitation', [0])[i],
-                    'rain': minutely.get('rain', [0])[i],
-                    'snow': minutely.get('snowfall', [0])[i],
-                    'weather_code': minutely.get('weather_code', [0])[i],
+                    'time': minutely.moments[i].astimezone(tz).strftime('%H:%M'),
+                    'temp': rounded(minutely.temperature[i]),
+                    'precipitation': minutely.precipitation[i],
+                    'rain': minutely.rain[i],
+                    'snow': minutely.snowfall[i],
+                    'weather_code': int(weather_code)
+                    if weather_code is not None
+                    else None,
                 }
                 minutely_data.append(minute_data)
 
@@ -433,8 +678,12 @@ class PirateWeatherProvider(WeatherProvider):
 
     def __init__(self, api_key: str):
         super().__init__('PirateWeather')
+        # Fetched with minutely, daily and alerts excluded; hourly is a short
+        # 'hourly_short' strip, not the 24-hour forecast
+        self.capabilities = frozenset({'current', 'uv'})
         self.api_key = api_key
         self.base_url = 'https://api.pirateweather.net/forecast'
+        self.cache_ttl = 300  # Nowcast-driven current conditions move quickly
 
     def fetch_weather_data(
         self,
@@ -455,7 +704,7 @@ class PirateWeatherProvider(WeatherProvider):
                 'exclude': 'minutely,daily,alerts',  # Focus on current + hourly only
             }
 
-            response = requests.get(url, params=params, timeout=self.timeout)
+            response = self.http_get(url, params=params, timeout=self.timeout)
             print(f'🏴‍☠️ PirateWeather API URL: {response.url}')
             response.raise_for_status()
 
@@ -476,53 +725,66 @@ class PirateWeatherProvider(WeatherProvider):
             return None
 
         try:
-            current = raw_data.get('currently', {})
-            hourly = raw_data.get('hourly', {}).get('data', [])
+            response = PirateWeatherResponse(raw_data)
+        except ResponseError as e:
+            print(f'❌ Unusable PirateWeather response: {str(e)}')
+            return None
+
+        try:
+            current = response.currently
+            icon = current.icon or 'clear-day'
+            humidity = current.humidity
+            chance = current.precipitation_probability
 
             # Process enhanced current weather with real-time focus
             current_weather = {
-                'temperature': round(current.get('temperature', 0)),
-                'feels_like': round(current.get('apparentTemperature', 0)),
-                'humidity': round(current.get('humidity', 0) * 100),
-                'wind_speed': round(current.get('windSpeed', 0)),
-                'uv_index': current.get('uvIndex', 0),
-                'pressure': round(current.get('pressure', 0), 2),
-                'visibility': round(current.get('visibility', 0), 1),
-                'precipitation_rate': current.get('precipIntensity', 0),
-                'precipitation_prob': round(current.get('precipProbability', 0) * 100),
-                'precipitation_type': current.get('precipType'),
-                'icon': current.get('icon', 'clear-day'),
-                'summary': current.get('summary', 'Unknown'),
-                'is_day': self._determine_is_day(current.get('icon', 'clear-day')),
+                'temperature': rounded(current.temperature),
+                'feels_like': rounded(current.feels_like),
+                'humidity': round(humidity * 100) if humidity is not None else None,
+                'wind_speed': rounded(current.wind_speed),
+                'uv_index': current.uv_index,
+                'pressure': rounded(current.pressure, 2),
+                'visibility': rounded(current.visibility, 1),
+                'precipitation_rate': current.precipitation_rate,
+                'precipitation_prob': round(chance * 100)
+                if chance is not None
+                else None,
+                'precipitation_type': current.precipitation_type,
+                'icon': icon,
+                'summary': current.summary or 'Unknown',
+                'is_day': self._determine_is_day(icon),
                 # Add timestamp for freshness comparison
-                'timestamp': current.get('time', 0),
-                'data_age': self._calculate_data_age(current.get('time', 0)),
+                'timestamp': current.time,
+                'data_age': self._calculate_data_age(int(current.time))
+                if current.time is not None
+                else None,
             }
 
             # Process limited hourly data for immediate trends (next 6 hours only)
             hourly_forecast = []
-            if hourly:
-                tz = (
-                    zoneinfo.ZoneInfo(tz_name)
-                    if tz_name
-                    else zoneinfo.ZoneInfo('America/Chicago')
+            if response.hourly:
+                tz = resolve_timezone(
+                    response.timezone,
+                    tz_name,
+                    utc_offset_seconds=response.utc_offset_seconds,
                 )
 
+                moments = [
+                    datetime.fromtimestamp(hour.time or 0, tz=timezone.utc)
+                    for hour in response.hourly
+                ]
                 # Only get next 6 hours for real-time trending
-                for hour in hourly[:6]:
+                for i in upcoming_hours(moments, datetime.now(tz), HOURLY_SHORT_HOURS):
+                    hour = response.hourly[i]
+                    moment = moments[i]
+                    chance = hour.precipitation_probability
                     hour_data = {
-                        'temp': round(hour.get('temperature', 0)),
-                        'icon': hour.get('icon', 'clear-day'),
-                        'rain': round(hour.get('precipProbability', 0) * 100),
-                        'precipitation_rate': hour.get('precipIntensity', 0),
-                        't': datetime.fromtimestamp(
-                            hour.get('time', 0), tz=timezone.utc
-                        )
-                        .astimezone(tz)
-                        .strftime('%I%p')
-                        .lower()
-                        .replace('0', ''),
-                        'desc': hour.get('summary', 'Unknown'),
+                        'temp': rounded(hour.temperature),
+                        'icon': hour.icon or 'clear-day',
+                        'rain': round(chance * 100) if chance is not None else None,
+                        'precipitation_rate': hour.precipitation_rate,
+                        't': hour_label(moment.astimezone(tz)),
+                        'desc': hour.summary or 'Unknown',
                     }
                     hourly_forecast.append(hour_data)
 
@@ -559,6 +821,12 @@ class HybridWeatherProvider(WeatherProvider):
         super().__init__('Hybrid')
         self.pirate_weather = pirate_weather
         self.open_meteo = open_meteo
+        self.capabilities = pirate_weather.capabilities | open_meteo.capabilities
+        # Stale as soon as the faster-moving half is
+        self.cache_ttl = min(
+            (ttl for ttl in (pirate_weather.cache_ttl, open_meteo.cache_ttl) if ttl),
+            default=None,
+        )
 
     def fetch_weather_data(
         self,
@@ -576,6 +844,10 @@ class HybridWeatherProvider(WeatherProvider):
             'open_meteo': openmeteo_data,
         }
 
+    def has_quota(self) -> bool:
+        """Whether both halves can still be called"""
+        return self.pirate_weather.has_quota() and self.open_meteo.has_quota()
+
     def process_weather_data(
         self,
         raw_data: dict,
@@ -642,61 +914,70 @@ class HybridWeatherProvider(WeatherProvider):
             # Blend current conditions - prefer PirateWeather for real-time data
             blended_current = {
                 # Real-time conditions from PirateWeather
-                'temperature': pirate_current.get(
-                    'temperature', openmeteo_current.get('temperature', 0)
+                'temperature': first_known(
+                    pirate_current.get('temperature'),
+                    openmeteo_current.get('temperature'),
                 ),
-                'feels_like': pirate_current.get(
-                    'feels_like', openmeteo_current.get('feels_like', 0)
+                'feels_like': first_known(
+                    pirate_current.get('feels_like'),
+                    openmeteo_current.get('feels_like'),
                 ),
-                'precipitation_rate': pirate_current.get(
-                    'precipitation_rate', openmeteo_current.get('precipitation_rate', 0)
+                'precipitation_rate': first_known(
+                    pirate_current.get('precipitation_rate'),
+                    openmeteo_current.get('precipitation_rate'),
                 ),
-                'precipitation_prob': pirate_current.get(
-                    'precipitation_prob', openmeteo_current.get('precipitation_prob', 0)
+                'precipitation_prob': first_known(
+                    pirate_current.get('precipitation_prob'),
+                    openmeteo_current.get('precipitation_prob'),
                 ),
                 'precipitation_type': pirate_current.get('precipitation_type')
                 or openmeteo_current.get('precipitation_type'),
-                'summary': pirate_current.get(
-                    'summary', openmeteo_current.get('summary', 'Unknown')
+                'summary': first_known(
+                    pirate_current.get('summary'),
+                    openmeteo_current.get('summary'),
+                    'Unknown',
                 ),
-                'icon': pirate_current.get(
-                    'icon', openmeteo_current.get('icon', 'clear-day')
+                'icon': first_known(
+                    pirate_current.get('icon'),
+                    openmeteo_current.get('icon'),
+                    'clear-day',
                 ),
-                'visibility': pirate_current.get('visibility', 10.0),
+                'visibility': pirate_current.get('visibility'),
                 # Enhanced data from OpenMeteo (more reliable for these)
-                'pressure': openmeteo_current.get(
-                    'pressure', pirate_current.get('pressure', 0)
+                'pressure': first_known(
+                    openmeteo_current.get('pressure'), pirate_current.get('pressure')
                 ),
-                'dew_point': openmeteo_current.get('dew_point', 0),
-                'uv_index': openmeteo_current.get(
-                    'uv_index', pirate_current.get('uv_index', 0)
+                'dew_point': openmeteo_current.get('dew_point'),
+                'uv_index': first_known(
+                    openmeteo_current.get('uv_index'), pirate_current.get('uv_index')
                 ),
-                'is_day': openmeteo_current.get(
-                    'is_day', pirate_current.get('is_day', True)
+                'is_day': first_known(
+                    openmeteo_current.get('is_day'), pirate_current.get('is_day'), True
                 ),
                 # Blend precipitation detection (use any source that shows rain)
                 'rain_rate': max(
-                    openmeteo_current.get('rain_rate', 0),
-                    pirate_current.get('precipitation_rate', 0)
+                    openmeteo_current.get('rain_rate') or 0,
+                    pirate_current.get('precipitation_rate') or 0
                     if pirate_current.get('precipitation_type') == 'rain'
                     else 0,
                 ),
-                'shower_rate': openmeteo_current.get('shower_rate', 0),
+                'shower_rate': openmeteo_current.get('shower_rate'),
                 'snow_rate': max(
-                    openmeteo_current.get('snow_rate', 0),
-                    pirate_current.get('precipitation_rate', 0)
+                    openmeteo_current.get('snow_rate') or 0,
+                    pirate_current.get('precipitation_rate') or 0
                     if pirate_current.get('precipitation_type') == 'snow'
                     else 0,
                 ),
                 # Metadata
-                'data_age': pirate_current.get('data_age', 0),
-                'timestamp': pirate_current.get('timestamp', 0),
+                'data_age': pirate_current.get('data_age'),
+                'timestamp': pirate_current.get('timestamp'),
                 # Use the rest from either source
-                'humidity': pirate_current.get(
-                    'humidity', openmeteo_current.get('humidity', 0)
+                'humidity': first_known(
+                    pirate_current.get('humidity'), openmeteo_current.get('humidity')
                 ),
-                'wind_speed': pirate_current.get(
-                    'wind_speed', openmeteo_current.get('wind_speed', 0)
+                'wind_speed': first_known(
+                    pirate_current.get('wind_speed'),
+                    openmeteo_current.get('wind_speed'),
                 ),
             }
 
@@ -715,6 +996,272 @@ class HybridWeatherProvider(WeatherProvider):
         return blended
 
 
+BLEND_METHODS = ('median', 'mean')
+# Compass bearings, where 350 and 10 average to 0 rather than 180
+CIRCULAR_FIELDS = ('wind_direction',)
+
+
+def circular_mean(degrees: list[float]) -> int:
+    """The mean bearing of compass directions"""
+    x = sum(math.cos(math.radians(angle)) for angle in degrees)
+    y = sum(math.sin(math.radians(angle)) for angle in degrees)
+    return round(math.degrees(math.atan2(y, x))) % 360
+
+
+def blend_values(
+    values: list[Any], method: str = 'median', circular: bool = False
+) -> Any:
+    """One consensus value: numbers averaged, anything else majority-voted
+
+    Ties in a vote go to the earliest value, i.e. the first listed member.
+    """
+    known = [value for value in values if value is not None]
+    if not known:
+        return None
+    numbers = [
+        value
+        for value in known
+        if isinstance(value, int | float) and not isinstance(value, bool)
+    ]
+    if len(numbers) == len(known):
+        if circular:
+            return circular_mean(numbers)
+        center = (
+            statistics.median(numbers)
+            if method == 'median'
+            else statistics.fmean(numbers)
+        )
+        # Whole-number readings (rounded temperatures, percentages) stay whole
+        if all(isinstance(value, int) for value in numbers):
+            return round(center)
+        return round(center, 2)
+    votes = [value for value in known if isinstance(value, Hashable)]
+    if not votes:
+        return known[0]
+    counts = Counter(votes)
+    return max(votes, key=lambda value: counts[value])
+
+
+def blend_records(records: list[dict[str, Any]], method: str) -> dict[str, Any]:
+    """Blend matching fields across records, keeping the first record's order"""
+    fields = list(dict.fromkeys(field for record in records for field in record))
+    return {
+        field: blend_values(
+            [record.get(field) for record in records],
+            method,
+            circular=field in CIRCULAR_FIELDS,
+        )
+        for field in fields
+    }
+
+
+def blend_series(
+    series: list[list[dict[str, Any]]], key: str, method: str
+) -> list[dict[str, Any]]:
+    """Blend forecast entries that share a key, on the first series' timeline"""
+    by_key: dict[Any, list[dict[str, Any]]] = {}
+    for entries in series:
+        for entry in entries:
+            if key in entry:
+                by_key.setdefault(entry[key], []).append(entry)
+    return [
+        blend_records(by_key[entry[key]], method) if key in entry else entry
+        for entry in series[0]
+    ]
+
+
+class BlendProvider(WeatherProvider):
+    """Consensus forecast across several providers, fetched side by side
+
+    Numbers are the median (or mean) of the members that reported them, icons
+    and other labels are majority-voted, and hourly and daily entries are lined
+    up by their time and date. The first member that answers supplies anything
+    that can't be blended, such as the minutely series and sun times.
+    """
+
+    def __init__(self, members: list[WeatherProvider], method: str = 'median'):
+        super().__init__('Blend')
+        if method not in BLEND_METHODS:
+            msg = f'Unknown blend method {method!r}'
+            raise ValueError(msg)
+        self.members = members
+        self.method = method
+        self.capabilities = frozenset().union(
+            *(member.capabilities for member in members)
+        )
+        self.cache_ttl = min(
+            (member.cache_ttl for member in members if member.cache_ttl),
+            default=None,
+        )
+
+    def fetch_weather_data(
+        self,
+        lat: float,
+        lon: float,
+        tz_name: str | None = None,
+    ) -> dict | None:
+        """Every member's processed document, by member name"""
+        # Members out of quota sit this one out like failed ones
+        members = [member for member in self.members if member.has_quota()]
+        with make_executor(max(1, len(members))) as executor:
+            futures = {
+                member.name: executor.submit(
+                    member.get_weather, lat, lon, None, tz_name
+                )
+                for member in members
+            }
+        documents = {}
+        for name, future in futures.items():
+            try:
+                document = future.result()
+            except Exception as e:
+                print(f'❌ Blend member {name} failed: {str(e)}')
+                continue
+            if document:
+                documents[name] = document
+        return documents or None
+
+    def has_quota(self) -> bool:
+        """Whether any member can still be called"""
+        return any(member.has_quota() for member in self.members)
+
+    def process_weather_data(
+        self,
+        raw_data: dict,
+        location_name: str | None = None,
+        tz_name: str | None = None,  # noqa: ARG002
+    ) -> dict | None:
+        """Blend the members' documents into one"""
+        if not raw_data:
+            return None
+
+        names = list(raw_data)
+        documents = list(raw_data.values())
+        blended = copy.deepcopy(documents[0])
+
+        currents = [doc['current'] for doc in documents if doc.get('current')]
+        if currents:
+            blended['current'] = blend_records(currents, self.method)
+        for section, key in (('hourly', 'time'), ('daily', 'date')):
+            series = [doc[section] for doc in documents if doc.get(section)]
+            if series:
+                blended[section] = blend_series(series, key, self.method)
+
+        print(f'🧮 Blended {len(names)} providers ({self.method}): {", ".join(names)}')
+        blended['provider'] = f'{self.name} ({" + ".join(names)})'
+        blended['blend'] = {'method': self.method, 'members': names}
+        blended['location'] = location_name or 'Unknown Location'
+        return blended
+
+    def get_provider_info(self) -> dict[str, Any]:
+        """Get information about this provider, including its members"""
+        info = super().get_provider_info()
+        info['members'] = [member.name for member in self.members]
+        info['method'] = self.method
+        return info
+
+
+class RemoteDashboardProvider(WeatherProvider):
+    """Upstream weather-dashboard instance - reuses its cache, quotas and keys"""
+
+    def __init__(self, base_url: str) -> None:
+        super().__init__('RemoteDashboard')
+        self.base_url = base_url.rstrip('/')
+
+    def fetch_weather_data(
+        self,
+        lat: float,
+        lon: float,
+        tz_name: str | None = None,
+    ) -> dict | None:
+        """Fetch already-processed weather data from the upstream /api/weather"""
+        # Legacy naming matches the internal document format used downstream
+        params: dict[str, Any] = {'lat': lat, 'lon': lon, 'naming': 'legacy'}
+        if tz_name:
+            params['timezone'] = tz_name
+
+        response = self.http_get(
+            f'{self.base_url}/api/weather', params=params, timeout=self.timeout
+        )
+        if response.status_code != 200:  # noqa: PLR2004
+            print(f'❌ Upstream dashboard returned {response.status_code}')
+            return None
+
+        data: dict = response.json()
+        # Upstream reports failures as an error document rather than weather
+        if 'error' in data or 'current' not in data:
+            print(f'❌ Upstream dashboard error: {data.get("error", "no data")}')
+            return None
+        return data
+
+    def process_weather_data(
+        self,
+        raw_data: dict,
+        location_name: str | None = None,
+        tz_name: str | None = None,  # noqa: ARG002
+    ) -> dict | None:
+        """Pass upstream data through, noting where it came from"""
+        if not raw_data:
+            return None
+
+        processed = raw_data.copy()
+        upstream_provider = raw_data.get('provider', 'unknown')
+        processed['provider'] = f'{self.name} ({upstream_provider})'
+        processed['upstream'] = self.base_url
+        processed['location'] = (
+            location_name or raw_data.get('location') or 'Unknown Location'
+        )
+        return processed
+
+    def get_weather_section(
+        self,
+        lat: float,
+        lon: float,
+        section: str,
+        location_name: str | None = None,
+        tz_name: str | None = None,
+    ) -> dict[str, Any] | None:
+        """Get one section from the upstream's per-section endpoint"""
+        params: dict[str, Any] = {'lat': lat, 'lon': lon, 'naming': 'legacy'}
+        if tz_name:
+            params['timezone'] = tz_name
+
+        try:
+            response = self.http_get(
+                f'{self.base_url}/api/weather/{section}',
+                params=params,
+                timeout=self.timeout,
+            )
+        except Exception as e:
+            print(f'❌ {self.name} provider error: {str(e)}')
+            return None
+        if response.status_code != 200:  # noqa: PLR2004
+            print(f'❌ Upstream dashboard returned {response.status_code}')
+            return None
+
+        data: dict = response.json()
+        if 'error' in data or section not in data:
+            return None
+        return self.process_weather_data(data, location_name, tz_name)
+
+    def health_check(self) -> bool:
+        """Probe the upstream's liveness endpoint rather than fetch weather"""
+        try:
+            response = get_transport().get(
+                f'{self.base_url}/healthz', timeout=self.timeout
+            )
+        except Exception as e:
+            print(f'❌ {self.name} health probe failed: {str(e)}')
+            return False
+        return response.status_code == 200  # noqa: PLR2004
+
+    def get_provider_info(self) -> dict[str, Any]:
+        """Get information about this provider, including the upstream URL"""
+        info = super().get_provider_info()
+        info['upstream'] = self.base_url
+        return info
+
+
 # AQI threshold constants
 AQI_GOOD = 50
 AQI_MODERATE = 100
@@ -749,7 +1296,7 @@ class AirQualityProvider(WeatherProvider):
                 'API_KEY': self.api_key,
             }
 
-            response = requests.get(lat_lon_url, params=params, timeout=self.timeout)
+            response = self.http_get(lat_lon_url, params=params, timeout=self.timeout)
             print(f'🌬️  AirNow API URL: {response.url}')
             response.raise_for_status()
 
@@ -916,7 +1463,9 @@ class RadarProvider(WeatherProvider):
             }
 
             # First get basic weather data to ensure API key works
-            response = requests.get(timestamps_url, params=params, timeout=self.timeout)
+            response = self.http_get(
+                timestamps_url, params=params, timeout=self.timeout
+            )
 
             if response.status_code == 401:  # noqa: PLR2004
                 print('❌ OpenWeatherMap API key invalid for radar')
@@ -1768,6 +2317,10 @@ class SolarDataProvider(WeatherProvider):
 
         return math.degrees(elevation_rad)
 
+    def solar_elevation(self, lat: float, lon: float, when: datetime) -> float:
+        """Solar elevation angle (degrees) at a UTC time"""
+        return self._calculate_solar_elevation(lat, lon, when.astimezone(timezone.utc))
+
     def _get_daylight_duration(self, lat: float, lon: float, date: datetime) -> float:
         """Get daylight duration in hours for a specific date"""
         try:
@@ -1791,6 +2344,7 @@ class NationalWeatherServiceProvider(WeatherProvider):
 
     def __init__(self) -> None:
         super().__init__('NationalWeatherService')
+        self.capabilities = frozenset({'alerts'})
         self.base_url = 'https://api.weather.gov'
         self.user_agent = (
             'WeatherDashboard/1.0 (https://github.com/user/weather-dashboard)'
@@ -1808,7 +2362,7 @@ class NationalWeatherServiceProvider(WeatherProvider):
 
             # First, get the grid point for this location
             points_url = f'{self.base_url}/points/{lat:.4f},{lon:.4f}'
-            points_response = requests.get(
+            points_response = self.http_get(
                 points_url, headers=headers, timeout=self.timeout
             )
 
@@ -1836,7 +2390,7 @@ class NationalWeatherServiceProvider(WeatherProvider):
                 'limit': 20,
             }
 
-            alerts_response = requests.get(
+            alerts_response = self.http_get(
                 alerts_url, params=alerts_params, headers=headers, timeout=self.timeout
             )
 
@@ -1850,7 +2404,7 @@ class NationalWeatherServiceProvider(WeatherProvider):
             forecast_url = (
                 f'{self.base_url}/gridpoints/{grid_office}/{grid_x},{grid_y}/forecast'
             )
-            forecast_response = requests.get(
+            forecast_response = self.http_get(
                 forecast_url, headers=headers, timeout=self.timeout
             )
 
@@ -2417,7 +2971,7 @@ class FreeRadarProvider(WeatherProvider):
             # RainViewer provides free radar data globally
             timestamps_url = 'https://api.rainviewer.com/public/weather-maps.json'
 
-            response = requests.get(timestamps_url, timeout=self.timeout)
+            response = self.http_get(timestamps_url, timeout=self.timeout)
 
             if response.status_code != 200:  # noqa: PLR2004
                 print(f'❌ RainViewer API returned {response.status_code}')
@@ -2520,23 +3074,750 @@ class FreeRadarProvider(WeatherProvider):
         }
 
 
+# Sea-state fields every marine provider returns, in the dashboard's units
+MARINE_UNITS: dict[str, str] = {
+    'wave_height': 'ft',
+    'wave_period': 's',
+    'wave_direction': '°',
+    'swell_wave_height': 'ft',
+    'swell_wave_period': 's',
+    'swell_wave_direction': '°',
+    'sea_surface_temperature': '°F',
+}
+FEET_PER_METER = 3.28084
+MARINE_FORECAST_DAYS = 3
+
+
+class MarineProvider(WeatherProvider):
+    """Base class for sea-state providers (waves, swell, sea temperature)
+
+    process_weather_data returns {'current': {...}, 'hourly': [...], 'units'}
+    with the MARINE_UNITS fields, so another source (e.g. Stormglass) only
+    has to fetch its own data and map it onto those names.
+    """
+
+    def marine_document(
+        self,
+        current: dict[str, Any],
+        hourly: list[dict[str, Any]],
+        location_name: str | None,
+        tz_name: str | None,
+    ) -> dict[str, Any] | None:
+        """Wrap mapped readings, or None when the point has no sea data (inland)"""
+        if all(current.get(field) is None for field in MARINE_UNITS):
+            print(f'🌊 {self.name} has no marine data for {location_name}')
+            return None
+        return {
+            'current': current,
+            'hourly': hourly,
+            'units': MARINE_UNITS,
+            'timezone': tz_name,
+            'location': location_name or 'Unknown Location',
+            'provider': self.name,
+        }
+
+
+class OpenMeteoMarineProvider(MarineProvider):
+    """Open-Meteo Marine API - free wave, swell and sea temperature forecasts"""
+
+    def __init__(self) -> None:
+        super().__init__('OpenMeteoMarine')
+        self.base_url = 'https://marine-api.open-meteo.com/v1/marine'
+
+    def fetch_weather_data(
+        self, lat: float, lon: float, tz_name: str | None = None
+    ) -> dict[str, Any] | None:
+        """Fetch current and hourly sea state from the Marine API"""
+        fields = ','.join(MARINE_UNITS)
+        response = self.http_get(
+            self.base_url,
+            params={
+                'latitude': lat,
+                'longitude': lon,
+                'current': fields,
+                'hourly': fields,
+                'timezone': tz_name or 'auto',
+                'forecast_days': MARINE_FORECAST_DAYS,
+            },
+            timeout=self.timeout,
+        )
+        response.raise_for_status()
+        data: dict[str, Any] = response.json()
+        return data
+
+    def convert(self, field: str, value: Any) -> float | None:
+        """Metres to feet and Celsius to Fahrenheit, rounded for display"""
+        if value is None:
+            return None
+        if MARINE_UNITS[field] == 'ft':
+            return round(value * FEET_PER_METER, 1)
+        if MARINE_UNITS[field] == '°F':
+            return round(value * 9 / 5 + 32, 1)
+        return round(value, 1)
+
+    def process_weather_data(
+        self,
+        raw_data: dict[str, Any],
+        location_name: str | None = None,
+        tz_name: str | None = None,
+    ) -> dict[str, Any] | None:
+        """Map the Marine API response onto the shared marine fields"""
+        current_raw = raw_data.get('current') or {}
+        current: dict[str, Any] = {
+            field: self.convert(field, current_raw.get(field)) for field in MARINE_UNITS
+        }
+        current['time'] = current_raw.get('time')
+
+        hourly_raw = raw_data.get('hourly') or {}
+        hourly = []
+        for index, hour in enumerate(hourly_raw.get('time', [])):
+            entry: dict[str, Any] = {'time': hour}
+            for field in MARINE_UNITS:
+                values = hourly_raw.get(field) or []
+                entry[field] = self.convert(
+                    field, values[index] if index < len(values) else None
+                )
+            hourly.append(entry)
+
+        return self.marine_document(
+            current, hourly, location_name, raw_data.get('timezone', tz_name)
+        )
+
+
+# Simulated weather for MockProvider by scenario: mean temperature (°F) and its
+# daily swing, humidity %, wind (mph) and gust factor, pressure (hPa) and its
+# change per hour, precipitation chance % and rate (in/hour), UV at midday and
+# the conditions hours are drawn from
+MOCK_SCENARIOS: dict[str, dict[str, Any]] = {
+    'normal': {
+        'temperature': 68,
+        'swing': 9,
+        'humidity': 55,
+        'wind': 8,
+        'gust': 1.4,
+        'pressure': 1016,
+        'pressure_change': 0.0,
+        'chance': 10,
+        'rate': 0.0,
+        'uv': 6,
+        'precipitation_type': None,
+        'icons': ('clear', 'partly-cloudy', 'partly-cloudy', 'cloudy'),
+    },
+    'rain': {
+        'temperature': 57,
+        'swing': 4,
+        'humidity': 90,
+        'wind': 14,
+        'gust': 1.6,
+        'pressure': 1006,
+        'pressure_change': -0.3,
+        'chance': 85,
+        'rate': 0.12,
+        'uv': 2,
+        'precipitation_type': 'rain',
+        'icons': ('light-rain', 'rain', 'rain', 'heavy-rain'),
+    },
+    'thunderstorm': {
+        'temperature': 82,
+        'swing': 6,
+        'humidity': 80,
+        'wind': 20,
+        'gust': 2.3,
+        'pressure': 1002,
+        'pressure_change': -0.7,
+        'chance': 90,
+        'rate': 0.6,
+        'uv': 4,
+        'precipitation_type': 'rain',
+        'icons': ('thunderstorm', 'thunderstorm', 'heavy-rain'),
+    },
+    'blizzard': {
+        'temperature': 12,
+        'swing': 4,
+        'humidity': 85,
+        'wind': 38,
+        'gust': 1.6,
+        'pressure': 992,
+        'pressure_change': -0.6,
+        'chance': 95,
+        'rate': 0.3,
+        'uv': 1,
+        'precipitation_type': 'snow',
+        'icons': ('heavy-snow', 'heavy-snow', 'snow'),
+    },
+    'heat_wave': {
+        'temperature': 103,
+        'swing': 9,
+        'humidity': 30,
+        'wind': 5,
+        'gust': 1.3,
+        'pressure': 1012,
+        'pressure_change': 0.0,
+        'chance': 0,
+        'rate': 0.0,
+        'uv': 11,
+        'precipitation_type': None,
+        'icons': ('clear',),
+    },
+}
+MOCK_DESCRIPTIONS = {
+    'clear': 'Clear sky',
+    'partly-cloudy': 'Partly cloudy',
+    'cloudy': 'Overcast',
+    'light-rain': 'Light rain',
+    'rain': 'Rain',
+    'heavy-rain': 'Heavy rain',
+    'snow': 'Snow',
+    'heavy-snow': 'Heavy snow',
+    'thunderstorm': 'Thunderstorm',
+}
+MOCK_SUNRISE_HOUR = 6
+MOCK_SUNSET_HOUR = 19
+WIND_CHILL_MAX_F = 50
+WIND_CHILL_MIN_MPH = 3
+HEAT_INDEX_MIN_F = 80
+
+
+def apparent_temperature(temperature: float, humidity: float, wind: float) -> float:
+    """Wind chill when it's cold and windy, heat index when it's hot (NWS, °F)"""
+    if temperature <= WIND_CHILL_MAX_F and wind > WIND_CHILL_MIN_MPH:
+        factor = wind**0.16
+        return (
+            35.74
+            + 0.6215 * temperature
+            - 35.75 * factor
+            + 0.4275 * temperature * factor
+        )
+    if temperature >= HEAT_INDEX_MIN_F:
+        return 0.5 * (temperature + 61 + (temperature - 68) * 1.2 + humidity * 0.094)
+    return temperature
+
+
+class MockProvider(WeatherProvider):
+    """Simulated weather for development and demos, repeatable from its seed
+
+    No network is involved. The same seed gives the same weather for a place
+    and time of day; a scenario picks ordinary weather or an extreme.
+    """
+
+    FORECAST_HOURS = 7 * 24
+    # Simulated hours before now, for the pressure trend
+    HISTORY_HOURS = 12
+
+    def __init__(self, seed: int = 0, scenario: str = 'normal') -> None:
+        if scenario not in MOCK_SCENARIOS:
+            msg = (
+                f'Unknown scenario {scenario!r} '
+                f'(expected one of: {", ".join(MOCK_SCENARIOS)})'
+            )
+            raise ValueError(msg)
+        super().__init__('Mock')
+        self.seed = seed
+        self.scenario = scenario
+
+    def with_scenario(self, scenario: str) -> 'MockProvider':
+        """The same provider simulating another scenario, e.g. for one request"""
+        provider = MockProvider(self.seed, scenario)
+        provider.name = self.name
+        return provider
+
+    def fetch_weather_data(
+        self, lat: float, lon: float, tz_name: str | None = None
+    ) -> dict[str, Any]:
+        """Simulated hourly readings: the last half day and the week ahead"""
+        profile = MOCK_SCENARIOS[self.scenario]
+        place = f'{lat:.2f}:{lon:.2f}'
+        rng = random.Random(f'{self.seed}:{self.scenario}:{place}')  # noqa: S311
+        # Without a timezone, local time is solar time near enough
+        tz = (
+            zoneinfo.ZoneInfo(tz_name)
+            if tz_name
+            else timezone(timedelta(hours=round(lon / 15)))
+        )
+        start = datetime.now(tz).replace(
+            minute=0, second=0, microsecond=0
+        ) - timedelta(hours=self.HISTORY_HOURS)
+        pressure = float(profile['pressure'])
+        direction = rng.uniform(0, 360)
+        hours = []
+        for i in range(self.HISTORY_HOURS + self.FORECAST_HOURS):
+            moment = start + timedelta(hours=i)
+            # Coolest before dawn, warmest mid-afternoon
+            diurnal = math.sin((moment.hour - 9) / 24 * 2 * math.pi)
+            pressure += profile['pressure_change'] + rng.uniform(-0.2, 0.2)
+            direction = (direction + rng.uniform(-15, 15)) % 360
+            chance = min(100, max(0, round(profile['chance'] + rng.uniform(-15, 15))))
+            wet = chance >= PRECIP_PROBABILITY_THRESHOLD and profile['rate'] > 0
+            hours.append(
+                {
+                    'time': moment.isoformat(),
+                    'temperature': profile['temperature']
+                    + profile['swing'] * diurnal
+                    + rng.uniform(-2, 2),
+                    'humidity': min(
+                        100, max(0, round(profile['humidity'] + rng.uniform(-8, 8)))
+                    ),
+                    'wind_speed': max(0.0, profile['wind'] + rng.uniform(-4, 4)),
+                    'wind_direction': round(direction),
+                    'pressure': pressure,
+                    'chance': chance,
+                    'precipitation': profile['rate'] * rng.uniform(0.5, 1.5)
+                    if wet
+                    else 0.0,
+                    'icon': rng.choice(profile['icons']),
+                }
+            )
+        return {
+            'scenario': self.scenario,
+            'past': hours[: self.HISTORY_HOURS],
+            'hours': hours[self.HISTORY_HOURS :],
+        }
+
+    def process_weather_data(
+        self,
+        raw_data: dict[str, Any],
+        location_name: str | None = None,
+        tz_name: str | None = None,  # noqa: ARG002
+    ) -> dict[str, Any] | None:
+        """Simulated readings as a weather document, like any provider's"""
+        profile = MOCK_SCENARIOS[raw_data['scenario']]
+        hours = raw_data['hours']
+        moments = [datetime.fromisoformat(hour['time']) for hour in hours]
+        snowing = profile['precipitation_type'] == 'snow'
+
+        def is_day(moment: datetime) -> bool:
+            return MOCK_SUNRISE_HOUR <= moment.hour < MOCK_SUNSET_HOUR
+
+        def icon(condition: str, daytime: bool = True) -> str:
+            if condition in ('clear', 'partly-cloudy'):
+                return f'{condition}-{"day" if daytime else "night"}'
+            return condition
+
+        def feels_like(hour: dict[str, Any]) -> int:
+            return round(
+                apparent_temperature(
+                    hour['temperature'], hour['humidity'], hour['wind_speed']
+                )
+            )
+
+        now, moment = hours[0], moments[0]
+        celsius = (now['temperature'] - 32) * 5 / 9
+        # Dew point falls about 1°C for every 5% drop in humidity
+        dew_point = (celsius - (100 - now['humidity']) / 5) * 9 / 5 + 32
+        rate = round(now['precipitation'], 2)
+        current = {
+            'temperature': round(now['temperature']),
+            'feels_like': feels_like(now),
+            'humidity': now['humidity'],
+            'wind_speed': round(now['wind_speed']),
+            'wind_direction': now['wind_direction'],
+            'wind_gust': round(now['wind_speed'] * profile['gust']),
+            'uv_index': profile['uv'] if is_day(moment) else 0,
+            'pressure': round(now['pressure'], 2),
+            'dew_point': round(dew_point),
+            'precipitation_rate': rate,
+            'rain_rate': 0.0 if snowing else rate,
+            'shower_rate': 0.0,
+            'snow_rate': rate if snowing else 0.0,
+            'precipitation_prob': now['chance'],
+            'precipitation_type': profile['precipitation_type'] if rate else None,
+            'is_day': is_day(moment),
+            'icon': icon(now['icon'], is_day(moment)),
+            'summary': MOCK_DESCRIPTIONS[now['icon']],
+        }
+        slots: list[PrecipitationSlot] = [
+            (when, when + timedelta(hours=1), hour['precipitation'] > 0)
+            for hour, when in zip(hours, moments)
+        ]
+        starts_in, ends_in = precipitation_timing(
+            slots, datetime.now(timezone.utc), raining_now=bool(rate)
+        )
+        current['precip_starts_in_minutes'] = starts_in
+        current['precip_ends_in_minutes'] = ends_in
+
+        hourly = [
+            {
+                'temp': round(hour['temperature']),
+                'feels_like': feels_like(hour),
+                'icon': icon(hour['icon'], is_day(when)),
+                'rain': hour['chance'],
+                'precipitation': round(hour['precipitation'], 2),
+                't': hour_label(when),
+                'desc': MOCK_DESCRIPTIONS[hour['icon']],
+                'pressure': round(hour['pressure'], 1),
+                'time': when.strftime('%Y-%m-%dT%H:%M'),
+            }
+            for hour, when in zip(hours[:24], moments[:24])
+        ]
+
+        days: dict[str, list[dict[str, Any]]] = {}
+        for hour, when in zip(hours, moments):
+            days.setdefault(when.date().isoformat(), []).append(hour)
+        daily = []
+        sun = {}
+        for date, day_hours in days.items():
+            conditions = Counter(hour['icon'] for hour in day_hours)
+            daily.append(
+                {
+                    'h': round(max(hour['temperature'] for hour in day_hours)),
+                    'l': round(min(hour['temperature'] for hour in day_hours)),
+                    'icon': icon(conditions.most_common(1)[0][0]),
+                    'rain': max(hour['chance'] for hour in day_hours),
+                    'd': datetime.fromisoformat(date).strftime('%a'),
+                    'date': date,
+                }
+            )
+            sun[date] = {
+                'sunrise': f'{date}T{MOCK_SUNRISE_HOUR:02d}:00',
+                'sunset': f'{date}T{MOCK_SUNSET_HOUR:02d}:00',
+            }
+
+        # Quarter hours of the current hour, for the precipitation timeline
+        minutely = [
+            {
+                'time': (moment + timedelta(minutes=15 * i)).strftime('%H:%M'),
+                'temp': round(now['temperature']),
+                'precipitation': round(now['precipitation'] / 4, 3),
+                'rain': 0.0 if snowing else round(now['precipitation'] / 4, 3),
+                'snow': round(now['precipitation'] / 4, 3) if snowing else 0.0,
+                'weather_code': None,
+            }
+            for i in range(4)
+        ]
+
+        return {
+            'current': current,
+            'hourly': hourly,
+            'daily': daily[:7],
+            'minutely': minutely,
+            'sun': sun,
+            # Newest first, as the trend wants
+            'pressure_trend': calculate_pressure_trend(
+                [
+                    {
+                        'time': hour['time'][:16],
+                        'pressure': round(hour['pressure'], 1),
+                    }
+                    for hour in [now, *reversed(raw_data['past'])]
+                ]
+            ),
+            'location': location_name or 'Unknown Location',
+            'provider': self.name,
+            'scenario': raw_data['scenario'],
+        }
+
+
+def build_open_meteo(
+    settings: ProviderSettings,  # noqa: ARG001
+    built: dict[str, WeatherProvider],  # noqa: ARG001
+) -> OpenMeteoProvider:
+    """Open-Meteo needs no key"""
+    return OpenMeteoProvider()
+
+
+def build_pirate_weather(
+    settings: ProviderSettings,
+    built: dict[str, WeatherProvider],  # noqa: ARG001
+) -> PirateWeatherProvider:
+    """PirateWeather from its API key"""
+    if not settings.api_key:
+        msg = 'PirateWeather needs an api_key'
+        raise ValueError(msg)
+    return PirateWeatherProvider(settings.api_key)
+
+
+def build_remote_dashboard(
+    settings: ProviderSettings,
+    built: dict[str, WeatherProvider],  # noqa: ARG001
+) -> WeatherProvider:
+    """An upstream dashboard from the url option"""
+    url = settings.options.get('url')
+    if not isinstance(url, str) or not url.strip():
+        msg = 'RemoteDashboard needs a url option'
+        raise ValueError(msg)
+    return RemoteDashboardProvider(url.strip())
+
+
+def build_hybrid(
+    settings: ProviderSettings, built: dict[str, WeatherProvider]
+) -> WeatherProvider:
+    """The hybrid, sharing the configured PirateWeather and Open-Meteo providers
+
+    Sharing keeps quotas and health counted once per upstream. Halves that
+    aren't configured on their own are built privately, the PirateWeather one
+    from the hybrid's own api_key.
+    """
+    pirate_weather = built.get(settings.options.get('pirate_weather', 'PirateWeather'))
+    if not isinstance(pirate_weather, PirateWeatherProvider):
+        pirate_weather = build_pirate_weather(settings, built)
+    open_meteo = built.get(settings.options.get('open_meteo', 'OpenMeteo'))
+    if not isinstance(open_meteo, OpenMeteoProvider):
+        open_meteo = OpenMeteoProvider()
+    return HybridWeatherProvider(pirate_weather, open_meteo)
+
+
+def build_blend(
+    settings: ProviderSettings, built: dict[str, WeatherProvider]
+) -> WeatherProvider:
+    """A blend of the configured providers named in the members option"""
+    members = []
+    for name in settings.options.get('members', []):
+        if name in built:
+            members.append(built[name])
+        else:
+            print(f'⚠️  Skipping unknown blend provider {name!r}')
+    if len(members) < 2:  # noqa: PLR2004
+        msg = 'Blend needs two or more configured members'
+        raise ValueError(msg)
+    return BlendProvider(members, settings.options.get('method', 'median'))
+
+
+def build_mock(
+    settings: ProviderSettings,
+    built: dict[str, WeatherProvider],  # noqa: ARG001
+) -> MockProvider:
+    """Simulated weather from the seed and scenario options"""
+    seed = settings.options.get('seed', 0)
+    if isinstance(seed, bool) or not isinstance(seed, int):
+        msg = 'Mock seed must be a whole number'
+        raise ValueError(msg)
+    return MockProvider(seed, str(settings.options.get('scenario', 'normal')))
+
+
+# Provider types that can be named in the provider config, by type name
+PROVIDER_TYPES: dict[
+    str, Callable[[ProviderSettings, dict[str, WeatherProvider]], WeatherProvider]
+] = {
+    'OpenMeteo': build_open_meteo,
+    'PirateWeather': build_pirate_weather,
+    'RemoteDashboard': build_remote_dashboard,
+    'Hybrid': build_hybrid,
+    'Blend': build_blend,
+    'Mock': build_mock,
+}
+
+# Types built from other providers, so built after everything else
+COMPOSITE_PROVIDER_TYPES = frozenset({'Hybrid', 'Blend'})
+
+
+def build_provider(
+    settings: ProviderSettings, built: dict[str, WeatherProvider]
+) -> WeatherProvider:
+    """A provider from its config entry, tuned with its timeout and quota"""
+    builder = PROVIDER_TYPES.get(settings.kind)
+    if builder is None:
+        msg = (
+            f'Unknown provider type {settings.kind!r} '
+            f'(expected one of: {", ".join(PROVIDER_TYPES)})'
+        )
+        raise ValueError(msg)
+    provider = builder(settings, built)
+    provider.name = settings.name
+    if settings.timeout is not None:
+        provider.timeout = settings.timeout
+    if settings.quota:
+        provider.quota = http_client.ProviderQuota.parse(settings.quota)
+    return provider
+
+
+def build_providers(settings: list[ProviderSettings]) -> list[WeatherProvider]:
+    """The enabled providers of a config list in priority order, skipping bad ones"""
+    enabled = [entry for entry in settings if entry.enabled]
+    built: dict[str, WeatherProvider] = {}
+    # Stable sort: composites go last but keep their order among themselves
+    for entry in sorted(
+        enabled, key=lambda entry: entry.kind in COMPOSITE_PROVIDER_TYPES
+    ):
+        try:
+            built[entry.name] = build_provider(entry, built)
+        except ValueError as e:
+            print(f'⚠️  Skipping provider {entry.name}: {e}')
+    return [built[entry.name] for entry in enabled if entry.name in built]
+
+
+class ProviderHealth:
+    """Recent request and probe outcomes for one provider"""
+
+    def __init__(self, window: int) -> None:
+        self.outcomes: deque[bool] = deque(maxlen=window)
+        self.consecutive_failures = 0
+        self.consecutive_successes = 0
+        self.last_success: float | None = None
+        self.last_failure: float | None = None
+        self.last_checked: float | None = None
+
+    def record(self, ok: bool) -> None:
+        """Count one outcome, from a real request or a probe"""
+        now = time.time()
+        self.outcomes.append(ok)
+        self.last_checked = now
+        if ok:
+            self.consecutive_successes += 1
+            self.consecutive_failures = 0
+            self.last_success = now
+        else:
+            self.consecutive_failures += 1
+            self.consecutive_successes = 0
+            self.last_failure = now
+
+    @property
+    def availability(self) -> float | None:
+        """Share of recent outcomes that succeeded, None before any"""
+        if not self.outcomes:
+            return None
+        return round(sum(self.outcomes) / len(self.outcomes), 3)
+
+    def describe(self, healthy: bool) -> dict[str, Any]:
+        """The record as shown in provider info"""
+
+        def stamp(when: float | None) -> str | None:
+            if when is None:
+                return None
+            return datetime.fromtimestamp(when, timezone.utc).isoformat()
+
+        return {
+            'healthy': healthy,
+            'availability': self.availability,
+            'samples': len(self.outcomes),
+            'consecutive_failures': self.consecutive_failures,
+            'last_success': stamp(self.last_success),
+            'last_failure': stamp(self.last_failure),
+            'last_checked': stamp(self.last_checked),
+        }
+
+
 class WeatherProviderManager:
     """Manager class to handle multiple weather providers"""
 
+    # Budget used when get_weather_within_budget is called without one configured
+    DEFAULT_LATENCY_BUDGET = 3.0
+
+    # Outcomes kept per provider for its availability score
+    HEALTH_WINDOW = 20
+    # Consecutive failures before a provider is unhealthy and the primary fails
+    # over; consecutive successful probes before the configured primary returns
+    FAILURE_THRESHOLD = 3
+    RECOVERY_THRESHOLD = 2
+
     def __init__(self) -> None:
         self.providers: dict[str, WeatherProvider] = {}
         self.primary_provider: str | None = None
         self.fallback_providers: list[str] = []
 
+        # The primary as configured; failover hands the role to a fallback until
+        # health probes see this one recover
+        self.preferred_primary: str | None = None
+        self.health: dict[str, ProviderHealth] = {}
+        self.health_interval = 60.0
+        self._health_lock = threading.Lock()
+
+        # Overall time allowed per request (None tries providers one at a time),
+        # and how long to wait on a provider before racing the next one
+        self.latency_budget: float | None = None
+        self.hedge_delay = 1.0
+        self.latency_metrics: dict[str, Any] = {
+            'requests': 0,
+            'outcomes': {
+                'primary': 0,
+                'fallback': 0,
+                'failed': 0,
+                'budget_exceeded': 0,
+            },
+            'last_elapsed_ms': None,
+            'last_outcome': None,
+        }
+        self._metrics_lock = threading.Lock()
+
+        # One upstream fetch per location at a time: concurrent callers for the
+        # same key wait on the fetch already running instead of starting their own
+        self.in_flight: dict[str, Future] = {}
+        self._in_flight_lock = threading.Lock()
+        self.coalesced_requests = 0
+
     def add_provider(self, provider: WeatherProvider, is_primary: bool = False) -> None:
         """Add a weather provider to the manager"""
         self.providers[provider.name] = provider
+        self.health.setdefault(provider.name, ProviderHealth(self.HEALTH_WINDOW))
 
         if is_primary:
             self.primary_provider = provider.name
+            self.preferred_primary = provider.name
         else:
             self.fallback_providers.append(provider.name)
 
+    def configure(self, settings: list[ProviderSettings]) -> None:
+        """Build and register the enabled providers; the first one is primary"""
+        for provider in build_providers(settings):
+            self.add_provider(provider, is_primary=self.primary_provider is None)
+        if self.primary_provider is None:
+            print('❌ No weather providers enabled')
+            return
+        chain = ' → '.join(self.fallback_providers) or 'none'
+        print(f'🌤️  Primary provider: {self.primary_provider} (fallbacks: {chain})')
+
+    def reconfigure(self, settings: list[ProviderSettings]) -> bool:
+        """Swap in a freshly built provider list, as on a config reload
+
+        The tables are replaced rather than edited, so requests already running
+        finish with the providers they started with. Returns False, keeping the
+        current providers, when none of the new ones could be built.
+        """
+        providers = build_providers(settings)
+        if not providers:
+            print('❌ No weather providers enabled - keeping the current ones')
+            return False
+        with self._health_lock:
+            self.providers = {provider.name: provider for provider in providers}
+            self.health = {
+                provider.name: ProviderHealth(self.HEALTH_WINDOW)
+                for provider in providers
+            }
+            self.primary_provider = providers[0].name
+            self.preferred_primary = providers[0].name
+            self.fallback_providers = [provider.name for provider in providers[1:]]
+        chain = ' → '.join(self.fallback_providers) or 'none'
+        print(f'🌤️  Primary provider: {self.primary_provider} (fallbacks: {chain})')
+        return True
+
+    def register(
+        self, settings: ProviderSettings, primary: bool = False
+    ) -> WeatherProvider:
+        """Build a provider and add it while running, as the last fallback
+
+        Composites can combine any provider already registered.
+        """
+        if settings.name in self.providers:
+            msg = f'Provider {settings.name!r} already exists'
+            raise ValueError(msg)
+        provider = build_provider(settings, dict(self.providers))
+        with self._health_lock:
+            self.add_provider(provider, is_primary=self.primary_provider is None)
+            if primary:
+                self.set_primary_provider(provider.name)
+        print(f'➕ Registered provider {provider.name} ({settings.kind})')
+        return provider
+
+    def remove_provider(self, provider_name: str) -> bool:
+        """Drop a provider while running, the first fallback taking over as primary
+
+        Composites that combine it keep their own reference and go on using it.
+        """
+        with self._health_lock:
+            if provider_name not in self.providers:
+                return False
+            if provider_name in self.fallback_providers:
+                self.fallback_providers.remove(provider_name)
+            if self.primary_provider == provider_name:
+                self.primary_provider = (
+                    self.fallback_providers.pop(0) if self.fallback_providers else None
+                )
+            if self.preferred_primary == provider_name:
+                self.preferred_primary = self.primary_provider
+            del self.providers[provider_name]
+            self.health.pop(provider_name, None)
+        print(f'➖ Removed provider {provider_name}')
+        return True
+
     def set_primary_provider(self, provider_name: str) -> None:
         """Set the primary weather provider"""
         if provider_name in self.providers:
@@ -2554,49 +3835,390 @@ class WeatherProviderManager:
             # Remove from fallbacks if it was there
             if provider_name in self.fallback_providers:
                 self.fallback_providers.remove(provider_name)
+            self.preferred_primary = provider_name
         else:
             msg = f"Provider '{provider_name}' not found"
             raise ValueError(msg)
 
+    def is_healthy(self, provider_name: str) -> bool:
+        """Whether a provider is under the consecutive failure threshold"""
+        health = self.health.get(provider_name)
+        return health is None or health.consecutive_failures < self.FAILURE_THRESHOLD
+
+    def provider_chain(self) -> list[str]:
+        """Providers in the order to try them, unhealthy ones last
+
+        Providers out of quota are left out until it frees up, so requests
+        fall back before the upstream would refuse them.
+        """
+        chain = []
+        for name in [self.primary_provider, *self.fallback_providers]:
+            if name not in self.providers:
+                continue
+            if not self.providers[name].has_quota():
+                print(f'🪫 Skipping {name} - quota exhausted')
+                continue
+            chain.append(name)
+        healthy = [name for name in chain if self.is_healthy(name)]
+        return healthy + [name for name in chain if name not in healthy]
+
+    def record_outcome(self, provider_name: str, ok: bool) -> None:
+        """Count a request's outcome, failing over from a primary that's down"""
+        with self._health_lock:
+            health = self.health.setdefault(
+                provider_name, ProviderHealth(self.HEALTH_WINDOW)
+            )
+            health.record(ok)
+            if provider_name == self.primary_provider and not self.is_healthy(
+                provider_name
+            ):
+                self.fail_over()
+
+    def fail_over(self) -> None:
+        """Hand the primary role to the first healthy fallback, if there is one"""
+        replacement = next(
+            (
+                name
+                for name in self.fallback_providers
+                if name in self.providers and self.is_healthy(name)
+            ),
+            None,
+        )
+        if replacement is None or self.primary_provider is None:
+            return
+        print(f'⚠️  {self.primary_provider} is failing - failing over to {replacement}')
+        self.promote(replacement)
+
+    def promote(self, provider_name: str) -> None:
+        """Swap a fallback in as primary, the old primary first in line behind it"""
+        self.fallback_providers.remove(provider_name)
+        if self.primary_provider:
+            self.fallback_providers.insert(0, self.primary_provider)
+        self.primary_provider = provider_name
+
+    def run_health_checks(self) -> None:
+        """Probe failing, idle and displaced providers, restoring a recovered primary"""
+        now = time.time()
+        for provider_name in self.provider_chain():
+            health = self.health.get(provider_name)
+            idle = (
+                health is None
+                or health.last_checked is None
+                or now - health.last_checked >= self.health_interval
+            )
+            # A displaced primary is probed every round until it's back
+            recovering = (
+                provider_name == self.preferred_primary
+                and provider_name != self.primary_provider
+            )
+            if not idle and not recovering and self.is_healthy(provider_name):
+                continue
+            ok = self.providers[provider_name].health_check()
+            print(f'🩺 {provider_name} health probe: {"ok" if ok else "failed"}')
+            self.record_outcome(provider_name, ok)
+
+        with self._health_lock:
+            preferred = self.preferred_primary
+            if (
+                preferred
+                and preferred != self.primary_provider
+                and preferred in self.fallback_providers
+                and self.health[preferred].consecutive_successes
+                >= self.RECOVERY_THRESHOLD
+            ):
+                print(f'✅ {preferred} recovered - promoting back to primary')
+                self.promote(preferred)
+
     def get_weather(
         self,
         lat: float,
         lon: float,
         location_name: str | None = None,
         tz_name: str | None = None,
+    ) -> dict | None:
+        """Get weather data, sharing one upstream fetch among concurrent callers"""
+        key = f'{lat:.4f},{lon:.4f}:{tz_name or ""}'
+        with self._in_flight_lock:
+            shared = self.in_flight.get(key)
+            leader = shared is None
+            if shared is None:
+                shared = Future()
+                self.in_flight[key] = shared
+
+        if not leader:
+            with self._metrics_lock:
+                self.coalesced_requests += 1
+            print(f'🤝 Joining in-flight weather fetch for {key}')
+            result = shared.result()
+            if not result:
+                return None
+            # Each caller gets its own copy, labeled with its own location name
+            result = copy.deepcopy(result)
+            if location_name:
+                result['location'] = location_name
+            return result
+
+        try:
+            result = self.fetch_with_fallbacks(lat, lon, location_name, tz_name)
+            result = self.fill_missing_fields(
+                result, lat, lon, location_name, tz_name
+            )
+        except BaseException as e:
+            shared.set_exception(e)
+            raise
+        else:
+            # Followers copy from a snapshot, so this caller may edit its result
+            shared.set_result(copy.deepcopy(result))
+            return result
+        finally:
+            with self._in_flight_lock:
+                self.in_flight.pop(key, None)
+
+    def fetch_with_fallbacks(
+        self,
+        lat: float,
+        lon: float,
+        location_name: str | None = None,
+        tz_name: str | None = None,
     ) -> dict | None:
         """Get weather data using primary provider with fallbacks"""
-        # Try primary provider first
-        if self.primary_provider and self.primary_provider in self.providers:
-            print(f'🎯 Trying primary provider: {self.primary_provider}')
-            result = self.providers[self.primary_provider].get_weather(
+        if self.latency_budget:
+            return self.get_weather_within_budget(lat, lon, location_name, tz_name)
+
+        # Try the primary first, then fallbacks, healthy providers before others
+        for index, provider_name in enumerate(self.provider_chain()):
+            if index == 0:
+                print(f'🎯 Trying primary provider: {provider_name}')
+            else:
+                print(f'🔄 Trying fallback provider: {provider_name}')
+            result = self.providers[provider_name].get_weather(
                 lat, lon, location_name, tz_name
             )
+            self.record_outcome(provider_name, bool(result))
+            if result:
+                return self.annotate_provenance(result, provider_name)
+
+        print('❌ All weather providers failed')
+        return None
+
+    def annotate_provenance(self, result: dict, provider_name: str) -> dict:
+        """Note which provider supplied each section the answer carries"""
+        provenance = {
+            field: provider_name
+            for fields in CAPABILITY_FIELDS.values()
+            for field in fields
+            if '.' not in field and field in result
+        }
+        if provenance:
+            result['provenance'] = provenance
+        return result
+
+    def fill_missing_fields(
+        self,
+        result: dict | None,
+        lat: float,
+        lon: float,
+        location_name: str | None = None,
+        tz_name: str | None = None,
+    ) -> dict | None:
+        """Fill fields the answer lacks from providers declaring them"""
+        if not result or 'provenance' not in result:
+            return result
+        provenance: dict[str, str] = result['provenance']
+        answered_by = set(provenance.values())
+        donors: dict[str, dict | None] = {}
+
+        def fillable(field: str) -> bool:
+            # A reading can only go into a section the answer already has
+            section, _, name = field.partition('.')
+            return not name or isinstance(result.get(section), dict)
+
+        for capability, fields in CAPABILITY_FIELDS.items():
+            gaps = [
+                field
+                for field in fields
+                if fillable(field) and is_missing(document_field(result, field))
+            ]
+            for provider_name in self.provider_chain():
+                if not gaps:
+                    break
+                provider = self.providers[provider_name]
+                if (
+                    provider_name in answered_by
+                    or capability not in provider.capabilities
+                ):
+                    continue
+                if provider_name not in donors:
+                    print(f'🧩 Filling {capability} from {provider_name}')
+                    donors[provider_name] = provider.get_weather(
+                        lat, lon, location_name, tz_name
+                    )
+                    self.record_outcome(provider_name, bool(donors[provider_name]))
+                donor = donors[provider_name] or {}
+                for field in list(gaps):
+                    value = document_field(donor, field)
+                    if is_missing(value):
+                        continue
+                    section, _, name = field.partition('.')
+                    if name:
+                        result[section][name] = value
+                    else:
+                        result[section] = value
+                    provenance[field] = provider_name
+                    gaps.remove(field)
+        return result
+
+    def get_weather_section(
+        self,
+        lat: float,
+        lon: float,
+        section: str,
+        location_name: str | None = None,
+        tz_name: str | None = None,
+    ) -> dict | None:
+        """Get one weather document section using primary provider with fallbacks"""
+        for provider_name in self.provider_chain():
+            if not self.providers[provider_name].supports(section):
+                continue
+            print(f'🎯 Fetching {section} from {provider_name}')
+            try:
+                result = self.providers[provider_name].get_weather_section(
+                    lat, lon, section, location_name, tz_name
+                )
+            except Exception as e:
+                print(f'❌ {provider_name} {section} fetch failed: {str(e)}')
+                result = None
+            self.record_outcome(provider_name, bool(result))
             if result:
                 return result
 
-        # Try fallback providers
-        for provider_name in self.fallback_providers:
-            if provider_name in self.providers:
-                print(f'🔄 Trying fallback provider: {provider_name}')
-                result = self.providers[provider_name].get_weather(
-                    lat, lon, location_name, tz_name
+        print(f'❌ All weather providers failed for {section}')
+        return None
+
+    def get_weather_within_budget(
+        self,
+        lat: float,
+        lon: float,
+        location_name: str | None = None,
+        tz_name: str | None = None,
+    ) -> dict | None:
+        """Get weather within the latency budget, racing fallbacks when slow"""
+        chain = self.provider_chain()
+        if not chain:
+            return None
+
+        start = time.monotonic()
+        deadline = start + (self.latency_budget or self.DEFAULT_LATENCY_BUDGET)
+        upcoming = list(chain)
+        pending: dict[Future, str] = {}
+        result: dict | None = None
+        winner: str | None = None
+        executor = make_executor(len(chain))
+
+        def launch_next() -> float:
+            """Start the next provider in the chain and return its hedge time"""
+            provider_name = upcoming.pop(0)
+            print(f'🏁 Starting provider within budget: {provider_name}')
+            future = executor.submit(
+                tracer.wrap(self.providers[provider_name].get_weather),
+                lat,
+                lon,
+                location_name,
+                tz_name,
+            )
+            pending[future] = provider_name
+            # Providers that outrun the budget still count once they finish
+            future.add_done_callback(
+                lambda done, name=provider_name: self.record_outcome(
+                    name,
+                    not done.cancelled()
+                    and done.exception() is None
+                    and bool(done.result()),
                 )
-                if result:
-                    return result
+            )
+            return time.monotonic() + self.hedge_delay
 
-        print('❌ All weather providers failed')
+        try:
+            next_hedge = launch_next()
+            while pending:
+                now = time.monotonic()
+                if now >= deadline:
+                    break
+                wake_at = min(deadline, next_hedge) if upcoming else deadline
+                done, _ = wait(
+                    list(pending), timeout=wake_at - now, return_when=FIRST_COMPLETED
+                )
+                for future in done:
+                    provider_name = pending.pop(future)
+                    if future.exception() is None and future.result():
+                        result, winner = future.result(), provider_name
+                        break
+                    print(f'⚠️  {provider_name} returned no usable data')
+                if result:
+                    break
+                # Race the next provider once the current ones are slow or failed
+                if upcoming and (not pending or time.monotonic() >= next_hedge):
+                    next_hedge = launch_next()
+        finally:
+            # Drop queued work; calls already in flight finish in the background
+            executor.shutdown(wait=False, cancel_futures=True)
+
+        if winner and result:
+            result = self.annotate_provenance(result, winner)
+        if winner:
+            outcome = 'primary' if winner == chain[0] else 'fallback'
+        elif pending:
+            outcome = 'budget_exceeded'
+        else:
+            outcome = 'failed'
+        self.record_latency(outcome, time.monotonic() - start)
+        print(f'⏱️  Weather request finished: {outcome} ({winner or "no provider"})')
+        return result
+
+    def record_latency(self, outcome: str, elapsed_seconds: float) -> None:
+        """Record the outcome and duration of a budgeted weather request"""
+        with self._metrics_lock:
+            self.latency_metrics['requests'] += 1
+            self.latency_metrics['outcomes'][outcome] += 1
+            self.latency_metrics['last_elapsed_ms'] = round(elapsed_seconds * 1000)
+            self.latency_metrics['last_outcome'] = outcome
+
+    def cache_ttl(self, *provider_names: str | None) -> int | None:
+        """The first preferred cache TTL among the named providers, if any"""
+        for name in provider_names:
+            provider = self.providers.get(name or '')
+            if provider is not None and provider.cache_ttl:
+                return provider.cache_ttl
         return None
 
+    def get_latency_metrics(self) -> dict[str, Any]:
+        """Get latency budget settings and recorded outcomes"""
+        with self._metrics_lock:
+            return {
+                'budget_seconds': self.latency_budget,
+                'hedge_delay_seconds': self.hedge_delay,
+                **self.latency_metrics,
+                'outcomes': dict(self.latency_metrics['outcomes']),
+            }
+
     def get_provider_info(self) -> dict[str, Any]:
         """Get information about all available providers"""
         return {
             'primary': self.primary_provider,
+            'preferred_primary': self.preferred_primary,
             'fallbacks': self.fallback_providers,
+            'types': list(PROVIDER_TYPES),
             'providers': {
                 name: provider.get_provider_info()
                 for name, provider in self.providers.items()
             },
+            'health': {
+                name: self.health[name].describe(self.is_healthy(name))
+                for name in self.providers
+                if name in self.health
+            },
+            'latency': self.get_latency_metrics(),
+            'coalesced_requests': self.coalesced_requests,
         }
 
     def switch_provider(self, provider_name: str) -> bool:
@@ -2661,6 +4283,11 @@ class LunarDataProvider(WeatherProvider):
             print(f'❌ Error calculating lunar data: {e}')
             return None
 
+    def illumination_at(self, when: datetime) -> float:
+        """Percentage of the moon's disc lit at a given time"""
+        lunar_age = self._calculate_lunar_age(self._to_julian_day(when))
+        return round(self._calculate_illumination(lunar_age) * 100, 1)
+
     def _calculate_lunar_data(self, now_utc: datetime) -> dict:
         """Calculate comprehensive lunar information"""
         # Convert to Julian Day Number for astronomical calculations
@@ -0,0 +1,250 @@
+# ABOUTME: Record provider HTTP traffic to cassette files and play it back offline,
+# ABOUTME: so tests run real upstream responses through the providers without a network
+
+import argparse
+import contextlib
+import json
+import os
+import sys
+from collections.abc import Iterable, Iterator, Mapping, Sequence
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any
+from urllib.parse import parse_qsl, quote, urlencode, urlsplit, urlunsplit
+
+import requests
+
+from weather_core.cli import DEFAULT_PROVIDERS_PATH, EXIT_FAILED, EXIT_OK, EXIT_USAGE
+from weather_core.providers import WeatherProviderManager
+from weather_core.settings import load_provider_settings
+from weather_core.transport import (
+    Transport,
+    build_response,
+    get_transport,
+    set_transport,
+)
+
+
+CASSETTE_VERSION = 1
+# Stands in for API keys, in recorded URLs and in providers replaying them
+REDACTED = 'REDACTED'
+# Response headers worth keeping; the rest (dates, cookies, servers) is noise
+RECORDED_HEADERS = ('Content-Type', 'Retry-After')
+
+
+def request_url(url: str, params: Any = None) -> str:
+    """The URL a GET goes to, with its query sorted so parameter order doesn't matter"""
+    prepared = requests.Request('GET', url, params=params).prepare().url or url
+    parts = urlsplit(prepared)
+    query = urlencode(sorted(parse_qsl(parts.query, keep_blank_values=True)))
+    return urlunsplit(parts._replace(query=query))
+
+
+class Cassette:
+    """Recorded GETs and their replies, in the order they were made
+
+    A request made more than once replays its recordings in order, the last
+    one repeating once they run out.
+    """
+
+    def __init__(
+        self,
+        interactions: list[dict[str, Any]] | None = None,
+        recorded_at: str | None = None,
+        request: dict[str, Any] | None = None,
+    ) -> None:
+        self.interactions = interactions or []
+        self.recorded_at = recorded_at or datetime.now(timezone.utc).isoformat(
+            timespec='seconds'
+        )
+        # What was asked of the provider, for tests to ask again
+        self.request = request or {}
+        self.played: dict[str, int] = {}
+
+    @classmethod
+    def load(cls, path: str | Path) -> 'Cassette':
+        """A cassette file, raising ValueError if it isn't one"""
+        with open(path, encoding='utf-8') as f:
+            data = json.load(f)
+        if not isinstance(data, dict) or data.get('version') != CASSETTE_VERSION:
+            msg = f'{path} is not a version {CASSETTE_VERSION} cassette'
+            raise ValueError(msg)
+        return cls(data['interactions'], data['recorded_at'], data.get('request'))
+
+    def save(self, path: str | Path) -> None:
+        """Write the cassette as indented JSON, creating its directory"""
+        Path(path).parent.mkdir(parents=True, exist_ok=True)
+        document = {
+            'version': CASSETTE_VERSION,
+            'recorded_at': self.recorded_at,
+            'request': self.request,
+            'interactions': self.interactions,
+        }
+        with open(path, 'w', encoding='utf-8') as f:
+            json.dump(document, f, indent=2, ensure_ascii=False)
+            f.write('\n')
+
+    @property
+    def recorded_time(self) -> datetime:
+        """When the recording was made, for tests to freeze the clock at"""
+        return datetime.fromisoformat(self.recorded_at)
+
+    def next_reply(self, url: str) -> dict[str, Any] | None:
+        """The next recording for a URL, or None if it was never requested"""
+        matches = [entry for entry in self.interactions if entry['url'] == url]
+        if not matches:
+            return None
+        played = self.played.get(url, 0)
+        self.played[url] = played + 1
+        return matches[min(played, len(matches) - 1)]
+
+
+def redact(text: str, secrets: Iterable[str]) -> str:
+    """Text with each secret, plain or URL-encoded, replaced by REDACTED"""
+    for secret in secrets:
+        text = text.replace(secret, REDACTED).replace(quote(secret, safe=''), REDACTED)
+    return text
+
+
+class RecordingTransport:
+    """Passes GETs on to another transport and records the replies
+
+    Secrets (API keys) are redacted from recorded URLs and bodies. Failed
+    requests aren't recorded; replaying them fails as a miss instead.
+    """
+
+    def __init__(
+        self, inner: Transport, cassette: Cassette, secrets: Iterable[str] = ()
+    ) -> None:
+        self.inner = inner
+        self.cassette = cassette
+        self.secrets = [secret for secret in secrets if secret]
+
+    def get(self, url: str, **kwargs: Any) -> requests.Response:
+        response = self.inner.get(url, **kwargs)
+        entry: dict[str, Any] = {
+            'url': redact(request_url(url, kwargs.get('params')), self.secrets),
+            'status': response.status_code,
+            'headers': {
+                name: response.headers[name]
+                for name in RECORDED_HEADERS
+                if name in response.headers
+            },
+        }
+        text = redact(response.text, self.secrets)
+        try:
+            entry['json'] = json.loads(text)
+        except ValueError:
+            entry['text'] = text
+        self.cassette.interactions.append(entry)
+        return response
+
+
+class ReplayTransport:
+    """Answers GETs from a cassette, raising ConnectionError for anything else"""
+
+    def __init__(self, cassette: Cassette) -> None:
+        self.cassette = cassette
+
+    def get(self, url: str, **kwargs: Any) -> requests.Response:
+        key = request_url(url, kwargs.get('params'))
+        entry = self.cassette.next_reply(key)
+        if entry is None:
+            msg = f'No recording of GET {key}'
+            raise requests.ConnectionError(msg)
+        body = json.dumps(entry['json']) if 'json' in entry else entry.get('text', '')
+        return build_response(
+            key, entry['status'], entry.get('headers', {}), body.encode('utf-8')
+        )
+
+
+@contextlib.contextmanager
+def replaying(cassette: Cassette) -> Iterator[Cassette]:
+    """Send provider requests to a cassette for the duration"""
+    original = get_transport()
+    set_transport(ReplayTransport(cassette))
+    try:
+        yield cassette
+    finally:
+        set_transport(original)
+
+
+def build_parser() -> argparse.ArgumentParser:
+    """The recorder's options"""
+    parser = argparse.ArgumentParser(
+        prog='python -m weather_core.replay',
+        description='Record a provider fetch to a cassette file for replay in tests',
+    )
+    parser.add_argument('--provider', required=True, help='configured provider name')
+    parser.add_argument('--lat', type=float, required=True, help='latitude')
+    parser.add_argument('--lon', type=float, required=True, help='longitude')
+    parser.add_argument('--location', help='location name passed to the provider')
+    parser.add_argument('--tz', help='IANA timezone passed to the provider')
+    parser.add_argument(
+        '--providers-config',
+        metavar='PATH',
+        help=(
+            'providers JSON file '
+            f'(default: $PROVIDERS_CONFIG_PATH or {DEFAULT_PROVIDERS_PATH})'
+        ),
+    )
+    parser.add_argument('--out', required=True, help='cassette file to write')
+    return parser
+
+
+def main(
+    argv: Sequence[str] | None = None, environ: Mapping[str, str] | None = None
+) -> int:
+    """Fetch once through a recording transport and save the cassette"""
+    environ = os.environ if environ is None else environ
+    args = build_parser().parse_args(argv)
+    try:
+        settings = load_provider_settings(
+            args.providers_config
+            or environ.get('PROVIDERS_CONFIG_PATH', DEFAULT_PROVIDERS_PATH),
+            environ,
+        )
+    except (OSError, ValueError) as e:
+        print(f'❌ {e}', file=sys.stderr)
+        return EXIT_FAILED
+    manager = WeatherProviderManager()
+    manager.configure(settings)
+    provider = manager.providers.get(args.provider)
+    if provider is None:
+        available = ', '.join(manager.providers) or 'none'
+        print(
+            f'❌ Unknown provider {args.provider!r} (have: {available})',
+            file=sys.stderr,
+        )
+        return EXIT_USAGE
+
+    cassette = Cassette(
+        request={
+            'provider': args.provider,
+            'lat': args.lat,
+            'lon': args.lon,
+            'location': args.location,
+            'tz': args.tz,
+        }
+    )
+    original = get_transport()
+    set_transport(
+        RecordingTransport(
+            original, cassette, [entry.api_key for entry in settings if entry.api_key]
+        )
+    )
+    try:
+        weather = provider.get_weather(args.lat, args.lon, args.location, args.tz)
+    finally:
+        set_transport(original)
+    if not weather:
+        print(f'❌ {args.provider} returned no weather; nothing saved', file=sys.stderr)
+        return EXIT_FAILED
+
+    cassette.save(args.out)
+    print(f'📼 Recorded {len(cassette.interactions)} request(s) to {args.out}')
+    return EXIT_OK
+
+
+if __name__ == '__main__':
+    sys.exit(main())
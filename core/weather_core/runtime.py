@@ -0,0 +1,36 @@
+# ABOUTME: What the interpreter underneath can do. WebAssembly builds (Pyodide in the
+# ABOUTME: browser, edge runtimes) can't start threads, so concurrent work runs in turn
+
+import sys
+from collections.abc import Callable
+from concurrent.futures import Executor, Future, ThreadPoolExecutor
+from typing import Any
+
+
+# Pyodide reports emscripten; WASI builds report wasi
+WASM = sys.platform in ('emscripten', 'wasi')
+CAN_THREAD = not WASM
+
+
+class InlineExecutor(Executor):
+    """An executor that runs each call as it's submitted, for runtimes without threads
+
+    The futures it returns are already done, so code written for a thread
+    pool - waiting on them, racing them - still works and simply sees every
+    call finish in submission order.
+    """
+
+    def submit(self, fn: Callable[..., Any], /, *args: Any, **kwargs: Any) -> Future:
+        future: Future = Future()
+        try:
+            future.set_result(fn(*args, **kwargs))
+        except Exception as e:
+            future.set_exception(e)
+        return future
+
+
+def make_executor(max_workers: int) -> Executor:
+    """A thread pool where threads can run, otherwise an inline executor"""
+    if CAN_THREAD:
+        return ThreadPoolExecutor(max_workers=max_workers)
+    return InlineExecutor()
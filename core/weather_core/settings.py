@@ -0,0 +1,187 @@
+# ABOUTME: Weather provider list - which providers run, in what order, with what keys
+# ABOUTME: and quotas - from a JSON file, config file entries or the environment
+
+import json
+import os
+from collections.abc import Mapping
+from typing import Any
+
+
+# What .env.example ships with; treated as no key at all
+PLACEHOLDER_API_KEY = 'YOUR_API_KEY_HERE'
+
+
+class ConfigError(ValueError):
+    """A config file or entry that can't be used as written"""
+
+
+class ProviderSettings:
+    """One entry of the provider list: which provider to build and how to tune it"""
+
+    def __init__(
+        self,
+        kind: str,
+        name: str | None = None,
+        enabled: bool = True,
+        timeout: float | None = None,
+        api_key: str | None = None,
+        quota: str | None = None,
+        options: dict[str, Any] | None = None,
+    ) -> None:
+        self.kind = kind
+        self.name = name or kind
+        self.enabled = enabled
+        self.timeout = timeout
+        self.api_key = api_key
+        self.quota = quota
+        self.options = options or {}
+
+    @classmethod
+    def from_dict(
+        cls, entry: Any, environ: Mapping[str, str] | None = None
+    ) -> 'ProviderSettings':
+        """An entry as written in the config file
+
+        The key can be given directly as api_key or, to keep it out of the
+        file, as api_key_env naming the environment variable holding it.
+        """
+        environ = os.environ if environ is None else environ
+        if not isinstance(entry, dict) or not isinstance(entry.get('type'), str):
+            msg = f'Provider entry {entry!r} has no "type"'
+            raise ConfigError(msg)
+        kind = entry['type']
+
+        timeout = entry.get('timeout')
+        if timeout is not None and (
+            isinstance(timeout, bool)
+            or not isinstance(timeout, int | float)
+            or timeout <= 0
+        ):
+            msg = f'{kind} timeout must be a positive number of seconds'
+            raise ConfigError(msg)
+        enabled = entry.get('enabled', True)
+        if not isinstance(enabled, bool):
+            msg = f'{kind} "enabled" must be true or false'
+            raise ConfigError(msg)
+        options = entry.get('options', {})
+        if not isinstance(options, dict):
+            msg = f'{kind} "options" must be an object'
+            raise ConfigError(msg)
+        for field in ('name', 'api_key', 'api_key_env', 'quota'):
+            if entry.get(field) is not None and not isinstance(entry[field], str):
+                msg = f'{kind} "{field}" must be a string'
+                raise ConfigError(msg)
+
+        api_key = entry.get('api_key')
+        if entry.get('api_key_env'):
+            api_key = environ.get(entry['api_key_env']) or api_key
+        return cls(
+            kind,
+            name=entry.get('name'),
+            enabled=enabled,
+            timeout=timeout,
+            api_key=api_key,
+            quota=entry.get('quota'),
+            options=options,
+        )
+
+
+def parse_provider_quotas(raw: str) -> dict[str, str]:
+    """PROVIDER_QUOTAS ('Name=10/minute,300/day;Other=...') as specs by name"""
+    quotas = {}
+    for entry in raw.split(';'):
+        name, _, spec = entry.partition('=')
+        if name.strip():
+            quotas[name.strip()] = spec.strip()
+    return quotas
+
+
+def default_provider_settings(
+    environ: Mapping[str, str] | None = None,
+) -> list[ProviderSettings]:
+    """The provider list implied by the environment variables alone
+
+    An upstream dashboard comes first, then the PirateWeather + Open-Meteo hybrid
+    with both halves as fallbacks when there's a PirateWeather key, otherwise
+    Open-Meteo alone. A blend of BLEND_PROVIDERS goes last. MOCK_WEATHER=1
+    replaces all of them with simulated weather, seeded by MOCK_WEATHER_SEED.
+    """
+    environ = os.environ if environ is None else environ
+    if environ.get('MOCK_WEATHER', '').strip() == '1':
+        seed = environ.get('MOCK_WEATHER_SEED', '0').strip()
+        try:
+            options = {'seed': int(seed)}
+        except ValueError:
+            print(f'⚠️  MOCK_WEATHER_SEED must be a whole number, not {seed!r}')
+            options = {'seed': 0}
+        return [ProviderSettings('Mock', options=options)]
+
+    settings = []
+
+    remote_url = environ.get('REMOTE_DASHBOARD_URL', '').strip()
+    if remote_url:
+        settings.append(
+            ProviderSettings('RemoteDashboard', options={'url': remote_url})
+        )
+
+    pirate_key = environ.get('PIRATE_WEATHER_API_KEY', '').strip()
+    if pirate_key and pirate_key != PLACEHOLDER_API_KEY:
+        settings += [
+            ProviderSettings('Hybrid'),
+            ProviderSettings('OpenMeteo'),
+            ProviderSettings('PirateWeather', api_key=pirate_key),
+        ]
+    else:
+        settings.append(ProviderSettings('OpenMeteo'))
+
+    members = [
+        name.strip()
+        for name in environ.get('BLEND_PROVIDERS', '').split(',')
+        if name.strip()
+    ]
+    if members:
+        method = environ.get('BLEND_METHOD', 'median').strip().lower()
+        settings.append(
+            ProviderSettings('Blend', options={'members': members, 'method': method})
+        )
+
+    quotas = parse_provider_quotas(environ.get('PROVIDER_QUOTAS', ''))
+    for entry in settings:
+        entry.quota = quotas.pop(entry.name, None)
+    for name in quotas:
+        print(f'⚠️  Skipping quota for unknown provider {name!r}')
+    return settings
+
+
+def load_provider_settings(
+    path: str | None,
+    environ: Mapping[str, str] | None = None,
+    entries: list[Any] | None = None,
+) -> list[ProviderSettings]:
+    """The provider list from a JSON file, the TOML config or the environment
+
+    The JSON file holds {"providers": [...]} in priority order, as do the TOML
+    config's [[providers]] entries; the first enabled provider is primary and
+    the rest are fallbacks. The JSON file wins over the TOML entries, and the
+    environment decides only when neither lists providers.
+    """
+    source = 'the config file'
+    if path and os.path.exists(path):
+        with open(path, encoding='utf-8') as f:
+            raw = json.load(f)
+        entries = raw.get('providers') if isinstance(raw, dict) else None
+        if not isinstance(entries, list):
+            msg = f'{path} has no "providers" list'
+            raise ConfigError(msg)
+        source = path
+    elif entries is None:
+        return default_provider_settings(environ)
+
+    settings = [ProviderSettings.from_dict(entry, environ) for entry in entries]
+    names = [entry.name for entry in settings]
+    duplicates = sorted({name for name in names if names.count(name) > 1})
+    if duplicates:
+        msg = f'Provider names must be unique: {", ".join(duplicates)}'
+        raise ConfigError(msg)
+    print(f'⚙️  Loaded {len(settings)} provider settings from {source}')
+    return settings
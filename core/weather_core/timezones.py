@@ -0,0 +1,44 @@
+# ABOUTME: Which timezone a forecast's times belong to, and reading its timestamps in it
+# ABOUTME: Naive provider times are the location's wall clock, never the server's
+
+import zoneinfo
+from datetime import datetime, timedelta, timezone, tzinfo
+
+
+# Used when neither the caller nor the provider names a zone
+DEFAULT_TIMEZONE = 'America/Chicago'
+
+
+def zone_named(name: str | None) -> tzinfo | None:
+    """An IANA zone, or None when the name is missing or unknown"""
+    if not name:
+        return None
+    try:
+        return zoneinfo.ZoneInfo(name)
+    except (zoneinfo.ZoneInfoNotFoundError, ValueError):
+        print(f'⚠️  Unknown timezone {name!r}')
+        return None
+
+
+def resolve_timezone(
+    *names: str | None, utc_offset_seconds: float | None = None
+) -> tzinfo:
+    """The first usable zone name, else the fixed UTC offset, else the default
+
+    A named zone is preferred over the offset because it knows about DST
+    changes later in the forecast; the offset is only the current one.
+    """
+    for name in names:
+        zone = zone_named(name)
+        if zone is not None:
+            return zone
+    if utc_offset_seconds is not None:
+        return timezone(timedelta(seconds=utc_offset_seconds))
+    return zoneinfo.ZoneInfo(DEFAULT_TIMEZONE)
+
+
+def localize(moment: datetime, zone: tzinfo) -> datetime:
+    """A timestamp in the zone; naive ones are read as its wall-clock time"""
+    if moment.tzinfo is None:
+        return moment.replace(tzinfo=zone)
+    return moment.astimezone(zone)
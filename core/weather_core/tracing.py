@@ -0,0 +1,298 @@
+# ABOUTME: OpenTelemetry-compatible tracing for requests, cache lookups and provider
+# ABOUTME: calls, exported in batches as OTLP/HTTP JSON to a collector (Jaeger, Tempo)
+
+import re
+import secrets
+import threading
+import time
+from collections import deque
+from collections.abc import Callable, Iterator, Mapping
+from contextlib import contextmanager
+from itertools import islice
+from typing import Any
+from urllib.parse import unquote
+
+import requests
+
+
+OTLP_TIMEOUT_SECONDS = 10
+MAX_RETRY_DELAY_SECONDS = 300
+DEFAULT_SERVICE_NAME = 'weather-dashboard'
+
+# OTLP span kinds and status codes
+SPAN_KIND_INTERNAL = 1
+SPAN_KIND_SERVER = 2
+SPAN_KIND_CLIENT = 3
+STATUS_ERROR = 2
+
+# W3C trace context from a caller that is itself traced (version 00)
+TRACEPARENT = re.compile(r'00-([0-9a-f]{32})-([0-9a-f]{16})-[0-9a-f]{2}')
+
+
+def otlp_value(value: Any) -> dict[str, Any]:
+    """An attribute value in OTLP JSON's tagged form"""
+    if isinstance(value, bool):
+        return {'boolValue': value}
+    if isinstance(value, int):
+        return {'intValue': str(value)}  # int64 travels as a string in JSON
+    if isinstance(value, float):
+        return {'doubleValue': value}
+    return {'stringValue': str(value)}
+
+
+def otlp_attributes(attributes: Mapping[str, Any]) -> list[dict[str, Any]]:
+    """Attributes as OTLP key/value pairs"""
+    return [
+        {'key': key, 'value': otlp_value(value)} for key, value in attributes.items()
+    ]
+
+
+class Span:
+    """One timed operation within a trace"""
+
+    def __init__(
+        self,
+        name: str,
+        trace_id: str,
+        parent_id: str | None = None,
+        kind: int = SPAN_KIND_INTERNAL,
+        attributes: Mapping[str, Any] | None = None,
+    ) -> None:
+        self.name = name
+        self.trace_id = trace_id
+        self.span_id = secrets.token_hex(8)
+        self.parent_id = parent_id
+        self.kind = kind
+        self.attributes = {k: v for k, v in (attributes or {}).items() if v is not None}
+        self.start_ns = time.time_ns()
+        self.end_ns: int | None = None
+        self.error: str | None = None
+
+    def set_attribute(self, key: str, value: Any) -> None:
+        """Record an attribute, leaving out unknown (None) values"""
+        if value is not None:
+            self.attributes[key] = value
+
+    def record_error(self, message: str) -> None:
+        """Mark the span failed, for failures that don't raise out of it"""
+        self.error = message
+
+    def to_otlp(self) -> dict[str, Any]:
+        """The span in OTLP JSON form"""
+        span: dict[str, Any] = {
+            'traceId': self.trace_id,
+            'spanId': self.span_id,
+            'name': self.name,
+            'kind': self.kind,
+            'startTimeUnixNano': str(self.start_ns),
+            'endTimeUnixNano': str(self.end_ns or self.start_ns),
+            'attributes': otlp_attributes(self.attributes),
+        }
+        if self.parent_id:
+            span['parentSpanId'] = self.parent_id
+        if self.error is not None:
+            span['status'] = {'code': STATUS_ERROR, 'message': self.error}
+        return span
+
+
+class OtlpExporter:
+    """Batching span exporter for an OTLP/HTTP collector's /v1/traces endpoint"""
+
+    def __init__(
+        self,
+        endpoint: str,
+        headers: dict[str, str] | None = None,
+        service_name: str = DEFAULT_SERVICE_NAME,
+        batch_size: int = 512,
+        flush_interval: float = 5,
+        max_buffer: int = 4096,
+    ) -> None:
+        self.endpoint = endpoint
+        self.headers = headers or {}
+        self.service_name = service_name
+        self.batch_size = batch_size
+        self.flush_interval = flush_interval
+        # Oldest spans are dropped if the collector stays down long enough to fill this
+        self.buffer: deque[Span] = deque(maxlen=max_buffer)
+        self._lock = threading.Lock()
+        self.retry_delay = flush_interval
+
+    def export(self, span: Span) -> None:
+        """Queue a finished span for the next batch"""
+        with self._lock:
+            self.buffer.append(span)
+
+    def payload(self, batch: list[Span]) -> dict[str, Any]:
+        """An ExportTraceServiceRequest for a batch of spans"""
+        resource = {'service.name': self.service_name}
+        return {
+            'resourceSpans': [
+                {
+                    'resource': {'attributes': otlp_attributes(resource)},
+                    'scopeSpans': [
+                        {
+                            'scope': {'name': self.service_name},
+                            'spans': [span.to_otlp() for span in batch],
+                        }
+                    ],
+                }
+            ]
+        }
+
+    def flush(self) -> bool:
+        """Send queued spans in batches, keeping unsent ones for the next attempt"""
+        while True:
+            with self._lock:
+                batch = list(islice(self.buffer, self.batch_size))
+            if not batch:
+                return True
+            if not self.send(batch):
+                return False
+            with self._lock:
+                # Skip spans the full buffer already dropped while we were sending
+                for span in batch:
+                    if self.buffer and self.buffer[0] is span:
+                        self.buffer.popleft()
+
+    def send(self, batch: list[Span]) -> bool:
+        """POST one batch of spans"""
+        try:
+            response = requests.post(
+                self.endpoint,
+                json=self.payload(batch),
+                headers=self.headers,
+                timeout=OTLP_TIMEOUT_SECONDS,
+            )
+        except requests.exceptions.RequestException as e:
+            print(f'❌ Trace export failed: {str(e)}')
+            return False
+        if not response.ok:
+            print(f'❌ Trace export failed: {response.status_code} {response.text}')
+            return False
+        return True
+
+    def run(self, sleep: Callable[[float], Any] = time.sleep) -> None:
+        """Background loop flushing every interval, backing off while exports fail"""
+        while True:
+            sleep(self.retry_delay)
+            if self.flush():
+                self.retry_delay = self.flush_interval
+            else:
+                self.retry_delay = min(self.retry_delay * 2, MAX_RETRY_DELAY_SECONDS)
+                print(f'⏳ Retrying trace export in {self.retry_delay:.0f}s')
+
+
+class Tracer:
+    """Starts spans as children of the thread's current span, exporting finished
+    ones when an exporter is set (until then spans are timed and dropped)
+    """
+
+    def __init__(self, exporter: OtlpExporter | None = None) -> None:
+        self.exporter = exporter
+        self._local = threading.local()
+
+    def _stack(self) -> list[Span]:
+        if not hasattr(self._local, 'stack'):
+            self._local.stack = []
+        return self._local.stack  # type: ignore[no-any-return]
+
+    def current(self) -> Span | None:
+        """The innermost open span on this thread"""
+        stack = self._stack()
+        return stack[-1] if stack else None
+
+    def start(
+        self,
+        name: str,
+        kind: int = SPAN_KIND_INTERNAL,
+        attributes: Mapping[str, Any] | None = None,
+        traceparent: str | None = None,
+    ) -> Span:
+        """Open a span; a root span joins the caller's trace given its traceparent"""
+        parent = self.current()
+        if parent is not None:
+            trace_id, parent_id = parent.trace_id, parent.span_id
+        elif traceparent and (match := TRACEPARENT.fullmatch(traceparent.strip())):
+            trace_id, parent_id = match.group(1), match.group(2)
+        else:
+            trace_id, parent_id = secrets.token_hex(16), None
+        span = Span(name, trace_id, parent_id, kind, attributes)
+        self._stack().append(span)
+        return span
+
+    def finish(self, span: Span, error: str | None = None) -> None:
+        """Close a span (and any children left open inside it) and export it"""
+        span.end_ns = time.time_ns()
+        if error is not None:
+            span.error = error
+        stack = self._stack()
+        if span in stack:
+            del stack[stack.index(span) :]
+        if self.exporter is not None:
+            self.exporter.export(span)
+
+    def wrap(self, func: Callable[..., Any]) -> Callable[..., Any]:
+        """func, run under this thread's current span on whichever thread calls it"""
+        parent = self.current()
+
+        def run(*args: Any, **kwargs: Any) -> Any:
+            stack = self._stack()
+            depth = len(stack)
+            if parent is not None:
+                stack.append(parent)
+            try:
+                return func(*args, **kwargs)
+            finally:
+                del stack[depth:]
+
+        return run
+
+    @contextmanager
+    def span(
+        self,
+        name: str,
+        attributes: Mapping[str, Any] | None = None,
+        kind: int = SPAN_KIND_INTERNAL,
+    ) -> Iterator[Span]:
+        """A span around a block, marked as an error if the block raises"""
+        span = self.start(name, kind, attributes)
+        error = None
+        try:
+            yield span
+        except Exception as e:
+            error = f'{type(e).__name__}: {e}'
+            raise
+        finally:
+            self.finish(span, error)
+
+
+def parse_otlp_headers(raw: str) -> dict[str, str]:
+    """OTEL_EXPORTER_OTLP_HEADERS: comma-separated key=value, values URL-encoded"""
+    headers = {}
+    for pair in raw.split(','):
+        key, _, value = pair.partition('=')
+        if key.strip() and value.strip():
+            headers[key.strip()] = unquote(value.strip())
+    return headers
+
+
+def load_otlp_exporter(environ: Mapping[str, str]) -> OtlpExporter | None:
+    """An exporter from the standard OTEL_* variables, or None when none is set"""
+    if environ.get('OTEL_TRACES_EXPORTER', 'otlp').strip().lower() == 'none':
+        return None
+    endpoint = environ.get('OTEL_EXPORTER_OTLP_TRACES_ENDPOINT', '').strip()
+    if not endpoint:
+        base = environ.get('OTEL_EXPORTER_OTLP_ENDPOINT', '').strip()
+        if not base:
+            return None
+        endpoint = f'{base.rstrip("/")}/v1/traces'
+    headers = parse_otlp_headers(
+        environ.get('OTEL_EXPORTER_OTLP_TRACES_HEADERS')
+        or environ.get('OTEL_EXPORTER_OTLP_HEADERS', '')
+    )
+    service_name = environ.get('OTEL_SERVICE_NAME', '').strip()
+    return OtlpExporter(endpoint, headers, service_name or DEFAULT_SERVICE_NAME)
+
+
+# Shared by the app, providers and the HTTP helper; main.py sets its exporter
+tracer = Tracer()
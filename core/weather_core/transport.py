@@ -0,0 +1,108 @@
+# ABOUTME: How provider HTTP requests leave the process: requests natively, or the
+# ABOUTME: browser's XMLHttpRequest under Pyodide - swappable for other runtimes
+
+from collections.abc import Mapping
+from typing import Any, Protocol
+
+import requests
+from requests.structures import CaseInsensitiveDict
+
+from weather_core.runtime import WASM
+
+
+class Transport(Protocol):
+    """Sends a GET and returns a requests.Response, raising requests' exceptions"""
+
+    def get(self, url: str, **kwargs: Any) -> requests.Response: ...
+
+
+class RequestsTransport:
+    """The requests library, for CPython"""
+
+    def get(self, url: str, **kwargs: Any) -> requests.Response:
+        return requests.get(url, **kwargs)
+
+
+def parse_header_lines(raw: str) -> CaseInsensitiveDict:
+    """XMLHttpRequest.getAllResponseHeaders() as a header mapping"""
+    headers: CaseInsensitiveDict = CaseInsensitiveDict()
+    for line in raw.splitlines():
+        name, separator, value = line.partition(':')
+        if separator and name.strip():
+            headers[name.strip()] = value.strip()
+    return headers
+
+
+def build_response(
+    url: str,
+    status: int,
+    headers: Mapping[str, str],
+    content: bytes,
+    reason: str = '',
+) -> requests.Response:
+    """A requests.Response for a reply that didn't come through requests"""
+    response = requests.Response()
+    response.status_code = status
+    response.reason = reason
+    response.url = url
+    response.headers = CaseInsensitiveDict(headers)
+    response.encoding = 'utf-8'
+    response._content = content
+    return response
+
+
+class XhrTransport:
+    """Synchronous XMLHttpRequest under Pyodide, in a page or a web worker
+
+    Blocking requests can't have a timeout outside workers, so timeout is
+    ignored; the browser's own limits apply. Other requests arguments
+    (params, headers) work as usual.
+    """
+
+    def get(self, url: str, **kwargs: Any) -> requests.Response:
+        from js import XMLHttpRequest  # Only importable under Pyodide
+
+        prepared = requests.Request(
+            'GET', url, params=kwargs.get('params'), headers=kwargs.get('headers')
+        ).prepare()
+        xhr = XMLHttpRequest.new()
+        try:
+            xhr.open('GET', prepared.url, False)  # noqa: FBT003
+            for name, value in prepared.headers.items():
+                xhr.setRequestHeader(name, value)
+            xhr.send()
+        except Exception as e:
+            raise requests.ConnectionError(str(e)) from e
+        # Network failures and CORS refusals show up as status 0
+        if not xhr.status:
+            msg = f'Request to {prepared.url} failed (network error or CORS)'
+            raise requests.ConnectionError(msg)
+
+        response = build_response(
+            prepared.url or url,
+            xhr.status,
+            parse_header_lines(xhr.getAllResponseHeaders()),
+            str(xhr.responseText).encode('utf-8'),
+            xhr.statusText,
+        )
+        response.request = prepared
+        return response
+
+
+def default_transport() -> Transport:
+    """XMLHttpRequest under Pyodide, requests everywhere else"""
+    return XhrTransport() if WASM else RequestsTransport()
+
+
+_transport: Transport = default_transport()
+
+
+def get_transport() -> Transport:
+    """The transport provider requests go through"""
+    return _transport
+
+
+def set_transport(transport: Transport) -> None:
+    """Send provider requests through another transport, e.g. an edge runtime's"""
+    global _transport  # noqa: PLW0603
+    _transport = transport
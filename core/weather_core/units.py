@@ -0,0 +1,84 @@
+# ABOUTME: Unit conversions for weather documents, which providers fill in imperial
+# ABOUTME: units (°F, mph, inches) - metric copies and the labels for either system
+
+from typing import Any
+
+
+# Labels for the units weather documents are served in (pressure is always hPa)
+UNIT_LABELS: dict[str, dict[str, str]] = {
+    'imperial': {
+        'temperature': '°F',
+        'wind_speed': 'mph',
+        'pressure': 'hPa',
+        'precipitation': 'in',
+    },
+    'metric': {
+        'temperature': '°C',
+        'wind_speed': 'km/h',
+        'pressure': 'hPa',
+        'precipitation': 'mm',
+    },
+}
+KMH_PER_MPH = 1.609344
+MM_PER_INCH = 25.4
+
+# Fields converted in each section of the weather document, by quantity
+METRIC_FIELDS: dict[str, dict[str, str]] = {
+    'current': {
+        'temperature': 'temperature',
+        'feels_like': 'temperature',
+        'dew_point': 'temperature',
+        'wind_speed': 'wind_speed',
+        'wind_gust': 'wind_speed',
+        'precipitation_rate': 'precipitation',
+        'rain_rate': 'precipitation',
+        'shower_rate': 'precipitation',
+        'snow_rate': 'precipitation',
+    },
+    'hourly': {
+        'temp': 'temperature',
+        'feels_like': 'temperature',
+        'precipitation': 'precipitation',
+    },
+    'daily': {'h': 'temperature', 'l': 'temperature'},
+    'minutely': {
+        'temp': 'temperature',
+        'precipitation': 'precipitation',
+        'rain': 'precipitation',
+        'snow': 'precipitation',
+    },
+}
+
+
+def convert_value(value: Any, quantity: str) -> Any:
+    """Convert one imperial reading to metric, leaving non-numbers alone"""
+    if not isinstance(value, int | float) or isinstance(value, bool):
+        return value
+    if quantity == 'temperature':
+        return round((value - 32) * 5 / 9)
+    if quantity == 'wind_speed':
+        return round(value * KMH_PER_MPH)
+    return round(value * MM_PER_INCH, 1)
+
+
+def convert_to_metric(weather: dict[str, Any]) -> dict[str, Any]:
+    """A copy of a weather document with readings in °C, km/h and mm"""
+    converted = dict(weather)
+    for section, fields in METRIC_FIELDS.items():
+        value = weather.get(section)
+        if isinstance(value, dict):
+            converted[section] = {
+                key: convert_value(item, fields[key]) if key in fields else item
+                for key, item in value.items()
+            }
+        elif isinstance(value, list):
+            converted[section] = [
+                {
+                    key: convert_value(item, fields[key]) if key in fields else item
+                    for key, item in entry.items()
+                }
+                if isinstance(entry, dict)
+                else entry
+                for entry in value
+            ]
+    return converted
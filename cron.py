@@ -0,0 +1,87 @@
+# ABOUTME: Five-field cron expressions (minute hour day-of-month month day-of-week)
+# ABOUTME: Supports *, lists, ranges and steps - enough to schedule digests
+
+from datetime import datetime, timedelta
+
+
+# (low, high) for each field; day-of-week also accepts 7 for Sunday
+CRON_FIELD_RANGES = ((0, 59), (0, 23), (1, 31), (1, 12), (0, 7))
+CRON_SEARCH_DAYS = 366 * 5  # Covers Feb 29 schedules
+
+
+def parse_cron_field(field: str, low: int, high: int) -> set[int]:
+    """Expand one cron field ('*/15', '1-5', '0,30') into the values it allows"""
+    values: set[int] = set()
+    for part in field.split(','):
+        spec, _, step_text = part.partition('/')
+        step = int(step_text) if step_text else 1
+        if spec == '*':
+            start, end = low, high
+        elif '-' in spec:
+            start_text, end_text = spec.split('-', 1)
+            start, end = int(start_text), int(end_text)
+        else:
+            start = int(spec)
+            end = high if step_text else start
+        if step < 1 or not (low <= start <= end <= high):
+            msg = f'{part!r} is outside {low}-{high}'
+            raise ValueError(msg)
+        values.update(range(start, end + 1, step))
+    return values
+
+
+class CronSchedule:
+    """A parsed cron expression, matched against local wall-clock times"""
+
+    def __init__(self, expression: str) -> None:
+        fields = expression.split()
+        if len(fields) != len(CRON_FIELD_RANGES):
+            msg = f'Cron expression needs 5 fields, got {expression!r}'
+            raise ValueError(msg)
+        try:
+            self.minutes, self.hours, self.days, self.months, weekdays = (
+                parse_cron_field(field, low, high)
+                for field, (low, high) in zip(fields, CRON_FIELD_RANGES, strict=True)
+            )
+        except ValueError as e:
+            msg = f'Invalid cron expression {expression!r}: {e}'
+            raise ValueError(msg) from None
+        self.weekdays = {day % 7 for day in weekdays}  # 0 and 7 are both Sunday
+        self.expression = expression
+        # Like cron, a restricted day-of-month OR day-of-week matches
+        self.any_day = fields[2] == '*'
+        self.any_weekday = fields[4] == '*'
+
+    def matches_day(self, moment: datetime) -> bool:
+        """Check the date part of a moment against the day and month fields"""
+        if moment.month not in self.months:
+            return False
+        day_ok = moment.day in self.days
+        weekday_ok = (moment.weekday() + 1) % 7 in self.weekdays
+        if self.any_day or self.any_weekday:
+            return day_ok and weekday_ok
+        return day_ok or weekday_ok
+
+    def matches(self, moment: datetime) -> bool:
+        """Check whether the schedule fires in a moment's minute"""
+        return (
+            moment.minute in self.minutes
+            and moment.hour in self.hours
+            and self.matches_day(moment)
+        )
+
+    def next_after(self, moment: datetime) -> datetime:
+        """The first matching minute strictly after a moment (same tzinfo)"""
+        candidate = moment.replace(second=0, microsecond=0) + timedelta(minutes=1)
+        limit = candidate + timedelta(days=CRON_SEARCH_DAYS)
+        while candidate < limit:
+            if not self.matches_day(candidate):
+                candidate = candidate.replace(hour=0, minute=0) + timedelta(days=1)
+            elif candidate.hour not in self.hours:
+                candidate = candidate.replace(minute=0) + timedelta(hours=1)
+            elif candidate.minute not in self.minutes:
+                candidate += timedelta(minutes=1)
+            else:
+                return candidate
+        msg = f'Cron expression {self.expression!r} never fires'
+        raise ValueError(msg)
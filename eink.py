@@ -0,0 +1,415 @@
+# ABOUTME: Forecast rendered straight to PNG for e-ink frames (Kindle, TRMNL, ESP32)
+# ABOUTME: that can't run a browser - bitmap font, drawn icons, stdlib-only encoder
+
+import math
+import struct
+import unicodedata
+import zlib
+from typing import Any
+
+
+DEFAULT_WIDTH = 800
+DEFAULT_HEIGHT = 480
+MIN_SIZE = 100
+MAX_SIZE = 2000
+FORECAST_DAYS = 3
+SUMMARY_LINES = 2
+
+BLACK = 0
+WHITE = 255
+GRAY = 150  # Cloud fill on grayscale panels; 1-bit panels draw clouds black
+ONE_BIT_THRESHOLD = 128
+
+GLYPH_WIDTH = 5
+GLYPH_HEIGHT = 7
+# 5x7 glyphs, rows top to bottom. Text is drawn uppercase; anything else is '?'
+FONT = {
+    ' ': '00000 00000 00000 00000 00000 00000 00000',
+    '0': '01110 10001 10011 10101 11001 10001 01110',
+    '1': '00100 01100 00100 00100 00100 00100 01110',
+    '2': '01110 10001 00001 00010 00100 01000 11111',
+    '3': '11111 00010 00100 00010 00001 10001 01110',
+    '4': '00010 00110 01010 10010 11111 00010 00010',
+    '5': '11111 10000 11110 00001 00001 10001 01110',
+    '6': '00110 01000 10000 11110 10001 10001 01110',
+    '7': '11111 00001 00010 00100 01000 01000 01000',
+    '8': '01110 10001 10001 01110 10001 10001 01110',
+    '9': '01110 10001 10001 01111 00001 00010 01100',
+    'A': '01110 10001 10001 11111 10001 10001 10001',
+    'B': '11110 10001 10001 11110 10001 10001 11110',
+    'C': '01110 10001 10000 10000 10000 10001 01110',
+    'D': '11100 10010 10001 10001 10001 10010 11100',
+    'E': '11111 10000 10000 11110 10000 10000 11111',
+    'F': '11111 10000 10000 11110 10000 10000 10000',
+    'G': '01110 10001 10000 10111 10001 10001 01111',
+    'H': '10001 10001 10001 11111 10001 10001 10001',
+    'I': '01110 00100 00100 00100 00100 00100 01110',
+    'J': '00111 00010 00010 00010 00010 10010 01100',
+    'K': '10001 10010 10100 11000 10100 10010 10001',
+    'L': '10000 10000 10000 10000 10000 10000 11111',
+    'M': '10001 11011 10101 10101 10001 10001 10001',
+    'N': '10001 10001 11001 10101 10011 10001 10001',
+    'O': '01110 10001 10001 10001 10001 10001 01110',
+    'P': '11110 10001 10001 11110 10000 10000 10000',
+    'Q': '01110 10001 10001 10001 10101 10010 01101',
+    'R': '11110 10001 10001 11110 10100 10010 10001',
+    'S': '01111 10000 10000 01110 00001 00001 11110',
+    'T': '11111 00100 00100 00100 00100 00100 00100',
+    'U': '10001 10001 10001 10001 10001 10001 01110',
+    'V': '10001 10001 10001 10001 10001 01010 00100',
+    'W': '10001 10001 10001 10101 10101 10101 01010',
+    'X': '10001 10001 01010 00100 01010 10001 10001',
+    'Y': '10001 10001 10001 01010 00100 00100 00100',
+    'Z': '11111 00001 00010 00100 01000 10000 11111',
+    '.': '00000 00000 00000 00000 00000 01100 01100',
+    ',': '00000 00000 00000 00000 01100 00100 01000',
+    ':': '00000 01100 01100 00000 01100 01100 00000',
+    '-': '00000 00000 00000 11111 00000 00000 00000',
+    '+': '00000 00100 00100 11111 00100 00100 00000',
+    '/': '00000 00001 00010 00100 01000 10000 00000',
+    '%': '11000 11001 00010 00100 01000 10011 00011',
+    '°': '01100 10010 10010 01100 00000 00000 00000',
+    "'": '00100 00100 01000 00000 00000 00000 00000',
+    '(': '00010 00100 01000 01000 01000 00100 00010',
+    ')': '01000 00100 00010 00010 00010 00100 01000',
+    '!': '00100 00100 00100 00100 00100 00000 00100',
+    '?': '01110 10001 00001 00010 00100 00000 00100',
+}
+
+RAIN_DROPS = {'light-rain': 2, 'rain': 3, 'heavy-rain': 4}
+SNOW_FLAKES = {'light-snow': 2, 'snow': 3, 'heavy-snow': 4}
+
+
+def printable(text: str) -> str:
+    """Text as the font can draw it: uppercase, accents stripped ('München')"""
+    decomposed = unicodedata.normalize('NFKD', text.upper())
+    stripped = ''.join(char for char in decomposed if not unicodedata.combining(char))
+    return ''.join(char if char in FONT else '?' for char in stripped)
+
+
+def text_width(text: str, scale: int) -> int:
+    """Pixel width of a line of text, one blank column between glyphs"""
+    return max(0, len(text) * (GLYPH_WIDTH + 1) - 1) * scale
+
+
+def fit_text(text: str, scale: int, width: int) -> str:
+    """The text, cut short with '...' if it's wider than the space it has"""
+    text = printable(text)
+    if text_width(text, scale) <= width:
+        return text
+    while text and text_width(f'{text}...', scale) > width:
+        text = text[:-1]
+    return f'{text.rstrip()}...' if text else ''
+
+
+def wrap_text(text: str, scale: int, width: int, max_lines: int) -> list[str]:
+    """Word-wrapped lines, the last one cut short if the text runs over"""
+    lines: list[str] = []
+    for word in printable(text).split():
+        if lines and text_width(f'{lines[-1]} {word}', scale) <= width:
+            lines[-1] = f'{lines[-1]} {word}'
+        else:
+            lines.append(word)
+    if len(lines) > max_lines:
+        lines[max_lines - 1] = ' '.join(lines[max_lines - 1 :])
+        lines = lines[:max_lines]
+    return [fit_text(line, scale, width) for line in lines]
+
+
+class Canvas:
+    """A grayscale pixel buffer with the few shapes the dashboard needs"""
+
+    def __init__(self, width: int, height: int) -> None:
+        self.width = width
+        self.height = height
+        self.pixels = bytearray([WHITE]) * (width * height)
+
+    def rect(self, x: float, y: float, w: float, h: float, shade: int = BLACK) -> None:
+        """Fill a rectangle, clipped to the canvas"""
+        left, top = max(0, round(x)), max(0, round(y))
+        right = min(self.width, round(x + w))
+        bottom = min(self.height, round(y + h))
+        if right <= left:
+            return
+        run = bytes([shade]) * (right - left)
+        for row in range(top, bottom):
+            start = row * self.width + left
+            self.pixels[start : start + len(run)] = run
+
+    def circle(self, cx: float, cy: float, r: float, shade: int = BLACK) -> None:
+        """Fill a disc"""
+        radius = round(r)
+        for dy in range(-radius, radius + 1):
+            half = math.isqrt(radius * radius - dy * dy)
+            self.rect(cx - half, cy + dy, 2 * half + 1, 1, shade)
+
+    def line(
+        self,
+        start: tuple[float, float],
+        end: tuple[float, float],
+        thickness: float,
+        shade: int = BLACK,
+    ) -> None:
+        """A thick line, stamped as squares along its length"""
+        (x0, y0), (x1, y1) = start, end
+        steps = max(1, round(max(abs(x1 - x0), abs(y1 - y0))))
+        size = max(1, round(thickness))
+        for step in range(steps + 1):
+            x = x0 + (x1 - x0) * step / steps
+            y = y0 + (y1 - y0) * step / steps
+            self.rect(x - size / 2, y - size / 2, size, size, shade)
+
+    def text(
+        self, x: float, y: float, text: str, scale: int, shade: int = BLACK
+    ) -> None:
+        """Draw text with its top-left corner at x, y"""
+        for index, char in enumerate(printable(text)):
+            left = x + index * (GLYPH_WIDTH + 1) * scale
+            for row, bits in enumerate(FONT[char].split()):
+                for column, bit in enumerate(bits):
+                    if bit == '1':
+                        self.rect(
+                            left + column * scale, y + row * scale, scale, scale, shade
+                        )
+
+    def png(self, grayscale: bool = True) -> bytes:
+        """Encode as an 8-bit grayscale PNG, or 1-bit black and white"""
+        if grayscale:
+            bit_depth = 8
+            rows = (
+                b'\x00' + self.pixels[row * self.width : (row + 1) * self.width]
+                for row in range(self.height)
+            )
+        else:
+            bit_depth = 1
+            rows = (b'\x00' + self.packed_row(row) for row in range(self.height))
+        header = struct.pack('>IIBBBBB', self.width, self.height, bit_depth, 0, 0, 0, 0)
+        return b''.join(
+            [
+                b'\x89PNG\r\n\x1a\n',
+                png_chunk(b'IHDR', header),
+                png_chunk(b'IDAT', zlib.compress(b''.join(rows), 9)),
+                png_chunk(b'IEND', b''),
+            ]
+        )
+
+    def packed_row(self, row: int) -> bytes:
+        """One row as 1-bit pixels, eight to a byte (1 is white)"""
+        start = row * self.width
+        bits = [
+            pixel >= ONE_BIT_THRESHOLD
+            for pixel in self.pixels[start : start + self.width]
+        ]
+        bits += [True] * (-len(bits) % 8)
+        return bytes(
+            sum(bit << (7 - offset) for offset, bit in enumerate(bits[i : i + 8]))
+            for i in range(0, len(bits), 8)
+        )
+
+
+def png_chunk(kind: bytes, data: bytes) -> bytes:
+    """A length-prefixed, CRC-suffixed PNG chunk"""
+    crc = zlib.crc32(kind + data) & 0xFFFFFFFF
+    return struct.pack('>I', len(data)) + kind + data + struct.pack('>I', crc)
+
+
+def draw_sun(canvas: Canvas, cx: float, cy: float, r: float) -> None:
+    """A disc with eight rays"""
+    for ray in range(8):
+        angle = ray * math.pi / 4
+        canvas.line(
+            (cx + math.cos(angle) * r * 1.4, cy + math.sin(angle) * r * 1.4),
+            (cx + math.cos(angle) * r * 1.9, cy + math.sin(angle) * r * 1.9),
+            max(2, r / 5),
+        )
+    canvas.circle(cx, cy, r)
+
+
+def draw_moon(canvas: Canvas, cx: float, cy: float, r: float) -> None:
+    """A crescent: a disc with an offset bite taken out"""
+    canvas.circle(cx, cy, r)
+    canvas.circle(cx + r * 0.45, cy - r * 0.3, r * 0.85, WHITE)
+
+
+def draw_cloud(canvas: Canvas, cx: float, cy: float, width: float, shade: int) -> None:
+    """Three puffs on a flat base, outlined in white so it reads over a sun"""
+    unit = width / 10
+    puffs = [
+        (cx - 2.2 * unit, cy + 0.5 * unit, 2.2 * unit),
+        (cx + 0.3 * unit, cy - 0.8 * unit, 3 * unit),
+        (cx + 2.8 * unit, cy + 0.7 * unit, 2 * unit),
+    ]
+    outline = max(2, unit / 2)
+    for fill, grow in ((WHITE, outline), (shade, 0)):
+        for x, y, r in puffs:
+            canvas.circle(x, y, r + grow, fill)
+        canvas.rect(
+            cx - 2.2 * unit,
+            cy + 0.5 * unit - grow,
+            5 * unit,
+            2.2 * unit + 2 * grow,
+            fill,
+        )
+
+
+def draw_icon(
+    canvas: Canvas, code: str | None, x: float, y: float, size: float, shade: int
+) -> None:
+    """An icon code drawn into a size x size box"""
+    cx, cy = x + size / 2, y + size / 2
+    stroke = max(2, size / 24)
+    code = code or 'cloudy'
+    if code == 'clear-day':
+        draw_sun(canvas, cx, cy, size * 0.22)
+    elif code == 'clear-night':
+        draw_moon(canvas, cx, cy, size * 0.3)
+    elif code.startswith('partly-cloudy'):
+        draw = draw_sun if code.endswith('day') else draw_moon
+        draw(canvas, x + size * 0.36, y + size * 0.34, size * 0.17)
+        draw_cloud(canvas, x + size * 0.55, y + size * 0.58, size * 0.72, shade)
+    elif code == 'wind':
+        for row, length in enumerate((0.7, 0.5, 0.6)):
+            top = y + size * (0.32 + row * 0.18)
+            right = x + size * (0.15 + length)
+            canvas.line((x + size * 0.15, top), (right, top), stroke)
+    elif code == 'fog':
+        draw_cloud(canvas, cx, y + size * 0.4, size * 0.75, shade)
+        for row in range(3):
+            top = y + size * (0.74 + row * 0.09)
+            canvas.line((x + size * 0.15, top), (x + size * 0.85, top), stroke)
+    else:
+        draw_cloud(canvas, cx, y + size * 0.38, size * 0.8, shade)
+        draw_precipitation(canvas, code, x, y, size, stroke)
+
+
+def draw_precipitation(
+    canvas: Canvas, code: str, x: float, y: float, size: float, stroke: float
+) -> None:
+    """Drops, flakes or a bolt under a cloud; nothing for plain clouds"""
+    top = y + size * 0.72
+
+    def spots(count: int) -> list[float]:
+        return [x + size * (0.5 + (i - (count - 1) / 2) * 0.18) for i in range(count)]
+
+    def drop(left: float) -> None:
+        canvas.line(
+            (left + size * 0.04, top), (left - size * 0.04, top + size * 0.16), stroke
+        )
+
+    if code in RAIN_DROPS:
+        for left in spots(RAIN_DROPS[code]):
+            drop(left)
+    elif code in SNOW_FLAKES:
+        for left in spots(SNOW_FLAKES[code]):
+            canvas.circle(left, top + size * 0.08, stroke)
+    elif code == 'sleet':
+        for index, left in enumerate(spots(3)):
+            if index % 2:
+                canvas.circle(left, top + size * 0.08, stroke)
+            else:
+                drop(left)
+    elif code == 'hail':
+        for left in spots(3):
+            canvas.circle(left, top + size * 0.08, stroke * 1.6)
+    elif code == 'thunderstorm':
+        cx = x + size / 2
+        bolt = [
+            (cx + size * 0.05, top - size * 0.06),
+            (cx - size * 0.08, top + size * 0.1),
+            (cx + size * 0.06, top + size * 0.1),
+            (cx - size * 0.06, top + size * 0.26),
+        ]
+        for start, end in zip(bolt, bolt[1:]):
+            canvas.line(start, end, stroke * 1.5)
+
+
+def degrees(value: Any) -> str:
+    """A temperature as '72°', or '--' when it's missing"""
+    return f'{round(value)}°' if isinstance(value, int | float) else '--'
+
+
+def render_forecast(
+    weather_data: dict[str, Any],
+    location_name: str,
+    summary: str,
+    width: int = DEFAULT_WIDTH,
+    height: int = DEFAULT_HEIGHT,
+    grayscale: bool = True,
+) -> bytes:
+    """Current conditions, a summary line and the next days as a PNG
+
+    Everything scales with the frame, so 800x480 panels and portrait Kindles
+    (600x800) get the same layout. Temperatures are drawn as given.
+    """
+    canvas = Canvas(width, height)
+    shade = GRAY if grayscale else BLACK
+    current = weather_data.get('current', {})
+    daily = weather_data.get('daily', [])
+    margin = max(4, min(width, height) // 30)
+    inner = width - 2 * margin
+    # Text sizes follow the height, unless a narrow (portrait) frame runs out first
+    basis = min(height, width * 3 // 5)
+    title = max(1, basis // 160)
+    big = max(2, basis // 48)
+    small = max(1, basis // 240)
+
+    canvas.text(margin, margin, fit_text(location_name, title, inner), title)
+
+    # Icon beside the temperature, with today's high and low under it
+    top = margin + GLYPH_HEIGHT * title + margin
+    icon_size = min(height // 3, width // 4)
+    draw_icon(canvas, current.get('icon'), margin, top, icon_size, shade)
+    today = daily[0] if daily else {}
+    block = GLYPH_HEIGHT * big + 3 * title + GLYPH_HEIGHT * title
+    left = margin + icon_size + margin
+    block_top = top + max(0, (icon_size - block) // 2)
+    canvas.text(left, block_top, degrees(current.get('temperature')), big)
+    canvas.text(
+        left,
+        block_top + GLYPH_HEIGHT * big + 3 * title,
+        fit_text(
+            f'H {degrees(today.get("h"))}  L {degrees(today.get("l"))}',
+            title,
+            width - left - margin,
+        ),
+        title,
+    )
+
+    line_height = (GLYPH_HEIGHT + 2) * small
+    top += icon_size + margin // 2
+    for index, line in enumerate(wrap_text(summary, small, inner, SUMMARY_LINES)):
+        canvas.text(margin, top + index * line_height, line, small)
+    top += SUMMARY_LINES * line_height + margin // 2
+    canvas.rect(margin, top, inner, max(1, small), shade)
+    top += margin
+
+    days = daily[:FORECAST_DAYS]
+    if not days:
+        return canvas.png(grayscale)
+    column = inner // len(days)
+    labels = GLYPH_HEIGHT * title
+    day_icon = min(column // 2, height - margin - top - 2 * labels - margin)
+    for index, day in enumerate(days):
+        left = margin + index * column
+        center = left + column / 2
+        name = fit_text(str(day.get('d', '')), title, column)
+        canvas.text(center - text_width(name, title) / 2, top, name, title)
+        if day_icon >= GLYPH_HEIGHT * 2:
+            draw_icon(
+                canvas,
+                day.get('icon'),
+                center - day_icon / 2,
+                top + labels + margin // 2,
+                day_icon,
+                shade,
+            )
+        temps = fit_text(
+            f'{degrees(day.get("h"))}/{degrees(day.get("l"))}', title, column
+        )
+        canvas.text(
+            center - text_width(temps, title) / 2,
+            top + labels + margin + max(0, day_icon),
+            temps,
+            title,
+        )
+    return canvas.png(grayscale)
@@ -0,0 +1,137 @@
+# ABOUTME: Typed API errors with machine-readable codes, so clients can tell a bad
+# ABOUTME: request from a failed upstream without parsing the message
+
+from typing import Any
+
+
+class WeatherError(Exception):
+    """An API failure with a stable code, its HTTP status and whether to retry
+
+    Raised from request handlers; the app's error handler turns it into
+    {code, message, provider, retryable} plus any details passed as keywords.
+    """
+
+    code = 'internal_error'
+    status = 500
+    retryable = False
+
+    def __init__(self, message: str, provider: str | None = None, **details: Any):
+        super().__init__(message)
+        self.message = message
+        self.provider = provider
+        self.details = details
+
+    def to_dict(self) -> dict[str, Any]:
+        """The JSON error body"""
+        return {
+            'code': self.code,
+            'message': self.message,
+            'provider': self.provider,
+            'retryable': self.retryable,
+            # Clients written before error codes read the message from here
+            'error': self.message,
+            **self.details,
+        }
+
+
+class InvalidLocation(WeatherError):
+    """Coordinates or a place that can't be used, or none at all"""
+
+    code = 'invalid_location'
+    status = 400
+
+
+class InvalidParameter(WeatherError):
+    """A query parameter outside what the endpoint accepts"""
+
+    code = 'invalid_parameter'
+    status = 400
+
+
+class Unauthorized(WeatherError):
+    """A missing or unknown token or station key"""
+
+    code = 'unauthorized'
+    status = 401
+
+
+class Forbidden(WeatherError):
+    """A caller who may not use the endpoint at all, whatever token it sends"""
+
+    code = 'forbidden'
+    status = 403
+
+
+class LocationNotFound(WeatherError):
+    """A place name no registry or geocoder knows"""
+
+    code = 'location_not_found'
+    status = 404
+
+
+class NotFound(WeatherError):
+    """A saved record (favorite, rule, webhook, provider...) that doesn't exist"""
+
+    code = 'not_found'
+    status = 404
+
+
+class Conflict(WeatherError):
+    """A change that clashes with what's already there"""
+
+    code = 'conflict'
+    status = 409
+
+
+class PayloadTooLarge(WeatherError):
+    """A request body over the configured limit"""
+
+    code = 'payload_too_large'
+    status = 413
+
+
+class RateLimited(WeatherError):
+    """The client is over its rate limit; retry_after says when to come back"""
+
+    code = 'rate_limited'
+    status = 429
+    retryable = True
+
+
+class ProviderNotConfigured(WeatherError):
+    """An optional provider (radar, air quality) with no API key set"""
+
+    code = 'provider_not_configured'
+    status = 503
+
+
+class ProviderFailed(WeatherError):
+    """The one provider asked returned nothing usable"""
+
+    code = 'provider_failed'
+    status = 502
+    retryable = True
+
+
+class UpstreamUnavailable(WeatherError):
+    """Every configured provider failed, so there's no data to give"""
+
+    code = 'upstream_unavailable'
+    status = 503
+    retryable = True
+
+
+class ServerBusy(WeatherError):
+    """Too many requests already running; retry_after says when to come back"""
+
+    code = 'server_busy'
+    status = 503
+    retryable = True
+
+
+class RequestTimedOut(WeatherError):
+    """The request ran past REQUEST_TIMEOUT, usually waiting on a slow upstream"""
+
+    code = 'request_timeout'
+    status = 504
+    retryable = True
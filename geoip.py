@@ -0,0 +1,211 @@
+# ABOUTME: Client IP geolocation from a MaxMind-format database or a lookup service
+# ABOUTME: Includes a small read-only MMDB reader so no extra dependency is needed
+
+import ipaddress
+import struct
+from typing import Any
+
+import requests
+
+
+MMDB_METADATA_MARKER = b'\xab\xcd\xefMaxMind.com'
+MMDB_DATA_SEPARATOR = 16  # Null bytes between the search tree and data section
+GEOIP_TIMEOUT_SECONDS = 5
+
+# Data section field types
+MMDB_POINTER = 1
+MMDB_STRING = 2
+MMDB_DOUBLE = 3
+MMDB_BYTES = 4
+MMDB_UINT16 = 5
+MMDB_UINT32 = 6
+MMDB_MAP = 7
+MMDB_INT32 = 8
+MMDB_UINT64 = 9
+MMDB_UINT128 = 10
+MMDB_ARRAY = 11
+MMDB_BOOLEAN = 14
+MMDB_FLOAT = 15
+
+
+def public_ip(address: str | None) -> str | None:
+    """The address if it's a public one a location can be found for, else None"""
+    try:
+        parsed = ipaddress.ip_address(address or '')
+    except ValueError:
+        return None
+    return str(parsed) if parsed.is_global else None
+
+
+class MaxMindReader:
+    """Read-only lookups in a MaxMind DB file (GeoLite2-City and compatible)"""
+
+    def __init__(self, path: str) -> None:
+        with open(path, 'rb') as f:
+            self.buffer = f.read()
+        marker = self.buffer.rfind(MMDB_METADATA_MARKER)
+        if marker < 0:
+            msg = f'{path} is not a MaxMind DB file'
+            raise ValueError(msg)
+        metadata_start = marker + len(MMDB_METADATA_MARKER)
+        self.metadata, _ = self.decode(metadata_start, metadata_start)
+        self.node_count = self.metadata['node_count']
+        self.record_size = self.metadata['record_size']
+        self.ip_version = self.metadata['ip_version']
+        self.node_bytes = self.record_size * 2 // 8
+        self.data_start = self.node_count * self.node_bytes + MMDB_DATA_SEPARATOR
+
+    def read_node(self, node: int, bit: int) -> int:
+        """One of a search-tree node's two records (24, 28 or 32 bits each)"""
+        offset = node * self.node_bytes
+        raw = self.buffer[offset : offset + self.node_bytes]
+        if self.record_size == 24:  # noqa: PLR2004
+            record = raw[3:6] if bit else raw[0:3]
+            return int.from_bytes(record, 'big')
+        if self.record_size == 28:  # noqa: PLR2004
+            if bit:
+                return ((raw[3] & 0x0F) << 24) | int.from_bytes(raw[4:7], 'big')
+            return ((raw[3] & 0xF0) << 20) | int.from_bytes(raw[0:3], 'big')
+        record = raw[4:8] if bit else raw[0:4]
+        return int.from_bytes(record, 'big')
+
+    def lookup(self, ip: str) -> dict[str, Any] | None:
+        """The record for an address, or None if the database has no entry"""
+        address = ipaddress.ip_address(ip)
+        packed = address.packed
+        if address.version == 4 and self.ip_version == 6:  # noqa: PLR2004
+            packed = bytes(12) + packed  # IPv4 lives in the ::/96 subtree
+        elif address.version == 6 and self.ip_version == 4:  # noqa: PLR2004
+            return None
+
+        node = 0
+        for index in range(len(packed) * 8):
+            if node >= self.node_count:
+                break
+            bit = (packed[index // 8] >> (7 - index % 8)) & 1
+            node = self.read_node(node, bit)
+        if node <= self.node_count:
+            return None  # node_count itself marks "no data"
+        offset = self.data_start + node - self.node_count - MMDB_DATA_SEPARATOR
+        record, _ = self.decode(offset, self.data_start)
+        return record if isinstance(record, dict) else None
+
+    def decode(self, offset: int, base: int) -> tuple[Any, int]:
+        """Decode the value at offset, returning it and the offset after it"""
+        control = self.buffer[offset]
+        offset += 1
+        field_type = control >> 5
+        if field_type == MMDB_POINTER:
+            return self.decode_pointer(control, offset, base)
+        if field_type == 0:  # Extended type
+            field_type = 7 + self.buffer[offset]
+            offset += 1
+
+        size = control & 0x1F
+        if size >= 29:  # noqa: PLR2004
+            extra = size - 28
+            value = int.from_bytes(self.buffer[offset : offset + extra], 'big')
+            size = (29, 285, 65821)[extra - 1] + value
+            offset += extra
+
+        if field_type == MMDB_MAP:
+            result: dict[str, Any] = {}
+            for _ in range(size):
+                key, offset = self.decode(offset, base)
+                result[key], offset = self.decode(offset, base)
+            return result, offset
+        if field_type == MMDB_ARRAY:
+            items = []
+            for _ in range(size):
+                item, offset = self.decode(offset, base)
+                items.append(item)
+            return items, offset
+        if field_type == MMDB_BOOLEAN:
+            return bool(size), offset
+
+        raw = self.buffer[offset : offset + size]
+        offset += size
+        if field_type == MMDB_STRING:
+            return raw.decode('utf-8'), offset
+        if field_type == MMDB_DOUBLE:
+            return struct.unpack('>d', raw)[0], offset
+        if field_type == MMDB_FLOAT:
+            return struct.unpack('>f', raw)[0], offset
+        if field_type == MMDB_INT32:
+            return int.from_bytes(raw, 'big', signed=True), offset
+        if field_type in (MMDB_UINT16, MMDB_UINT32, MMDB_UINT64, MMDB_UINT128):
+            return int.from_bytes(raw, 'big'), offset
+        return raw, offset  # MMDB_BYTES and types we don't interpret
+
+    def decode_pointer(self, control: int, offset: int, base: int) -> tuple[Any, int]:
+        """Follow a pointer into the data section, resuming after the pointer"""
+        size = (control >> 3) & 0x3
+        prefix = control & 0x7
+        raw = self.buffer[offset : offset + size + 1]
+        if size == 3:  # noqa: PLR2004
+            target = int.from_bytes(raw, 'big')
+        else:
+            target = (prefix << (8 * (size + 1))) | int.from_bytes(raw, 'big')
+            target += (0, 2048, 526336)[size]
+        value, _ = self.decode(base + target, base)
+        return value, offset + size + 1
+
+
+def place_from_record(record: dict[str, Any]) -> tuple[float, float, str] | None:
+    """Coordinates and a display name from a GeoLite2-City style record"""
+    location = record.get('location') or {}
+    lat, lon = location.get('latitude'), location.get('longitude')
+    if lat is None or lon is None:
+        return None
+    names = [
+        (record.get(section) or {}).get('names', {}).get('en')
+        for section in ('city', 'country')
+    ]
+    name = next((name for name in names if name), 'Your location')
+    return float(lat), float(lon), name
+
+
+def lookup_service(url_template: str, ip: str) -> tuple[float, float, str] | None:
+    """Ask a JSON lookup service ('https://ipapi.co/{ip}/json/' style)"""
+    try:
+        response = requests.get(
+            url_template.format(ip=ip), timeout=GEOIP_TIMEOUT_SECONDS
+        )
+        response.raise_for_status()
+        data = response.json()
+    except (requests.exceptions.RequestException, ValueError) as e:
+        print(f'❌ IP geolocation service error: {str(e)}')
+        return None
+
+    lat = data.get('latitude', data.get('lat'))
+    lon = data.get('longitude', data.get('lon'))
+    if not isinstance(lat, int | float) or not isinstance(lon, int | float):
+        return None
+    name = data.get('city') or data.get('country_name') or data.get('country')
+    return float(lat), float(lon), name or 'Your location'
+
+
+class IpLocator:
+    """Resolve client IPs with a local database, falling back to a service"""
+
+    def __init__(
+        self, database_path: str | None = None, service_url: str | None = None
+    ) -> None:
+        self.reader = None
+        if database_path:
+            try:
+                self.reader = MaxMindReader(database_path)
+            except (OSError, ValueError, KeyError) as e:
+                print(f'❌ Could not open GeoIP database {database_path}: {str(e)}')
+        self.service_url = service_url
+
+    def locate(self, ip: str) -> tuple[float, float, str] | None:
+        """Coordinates and place name for an address, if either source knows it"""
+        if self.reader is not None:
+            record = self.reader.lookup(ip)
+            place = place_from_record(record) if record else None
+            if place:
+                return place
+        if self.service_url:
+            return lookup_service(self.service_url, ip)
+        return None
@@ -0,0 +1,660 @@
+# ABOUTME: SQLite history of every fetched current-conditions snapshot, per location
+# ABOUTME: and provider, pruned past a retention window; feeds trends and accuracy
+
+import calendar
+import math
+import os
+import sqlite3
+import threading
+import time
+from datetime import date, datetime, timezone
+from typing import Any
+
+
+# Numeric readings kept from the 'current' block, one column each
+HISTORY_FIELDS = (
+    'temperature',
+    'feels_like',
+    'dew_point',
+    'humidity',
+    'wind_speed',
+    'wind_gust',
+    'wind_direction',
+    'pressure',
+    'uv_index',
+    'precipitation_rate',
+    'visibility',
+)
+
+HISTORY_SCHEMA = f"""
+CREATE TABLE IF NOT EXISTS observations (
+    location TEXT NOT NULL,
+    lat REAL NOT NULL,
+    lon REAL NOT NULL,
+    provider TEXT NOT NULL,
+    observed_at REAL NOT NULL,
+    {''.join(f'{field} REAL, ' for field in HISTORY_FIELDS)}
+    icon TEXT,
+    summary TEXT,
+    PRIMARY KEY (location, provider, observed_at)
+)
+"""
+HISTORY_INDEX = """
+CREATE INDEX IF NOT EXISTS observations_by_time ON observations (location, observed_at)
+"""
+# Each provider's latest next-day forecast, kept to score against what happened
+FORECASTS_SCHEMA = """
+CREATE TABLE IF NOT EXISTS forecasts (
+    location TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    target_date TEXT NOT NULL,
+    issued_at REAL NOT NULL,
+    high REAL,
+    low REAL,
+    precipitation_probability REAL,
+    PRIMARY KEY (location, provider, target_date)
+)
+"""
+# One row per local day: highs, lows and totals, e.g. seeded from an archive
+DAYS_SCHEMA = """
+CREATE TABLE IF NOT EXISTS days (
+    location TEXT NOT NULL,
+    date TEXT NOT NULL,
+    temperature_max REAL,
+    temperature_min REAL,
+    precipitation_sum REAL,
+    source TEXT NOT NULL,
+    PRIMARY KEY (location, date)
+)
+"""
+# The daily shape: each field, the hourly field it's from and how it's combined
+DAILY_FIELDS = {
+    'temperature_max': ('temperature', 'max'),
+    'temperature_min': ('temperature', 'min'),
+    'temperature_mean': ('temperature', 'mean'),
+    'humidity_mean': ('humidity', 'mean'),
+    'pressure_mean': ('pressure', 'mean'),
+    'wind_speed_max': ('wind_speed', 'max'),
+    'wind_gust_max': ('wind_gust', 'max'),
+    'precipitation_sum': ('precipitation', 'sum'),
+}
+# Snapshots past their retention, compacted into UTC days of DAILY_FIELDS
+DAILY_SCHEMA = f"""
+CREATE TABLE IF NOT EXISTS daily_observations (
+    location TEXT NOT NULL,
+    lat REAL NOT NULL,
+    lon REAL NOT NULL,
+    date TEXT NOT NULL,
+    {''.join(f'{field} REAL, ' for field in DAILY_FIELDS)}
+    hours INTEGER NOT NULL,
+    PRIMARY KEY (location, date)
+)
+"""
+HISTORY_COLUMNS = (
+    'location',
+    'lat',
+    'lon',
+    'provider',
+    'observed_at',
+    *HISTORY_FIELDS,
+    'icon',
+    'summary',
+)
+DAILY_COLUMNS = ('location', 'lat', 'lon', 'date', *DAILY_FIELDS, 'hours')
+# What can be exported: each table, its columns and the column ranges are on
+EXPORT_TABLES = {
+    'observations': ('observations', HISTORY_COLUMNS, 'observed_at'),
+    'daily': ('daily_observations', DAILY_COLUMNS, 'date'),
+}
+
+SECONDS_PER_HOUR = 3600
+SECONDS_PER_DAY = 86400
+
+# A day verifies a forecast only if this many of its hours have a temperature
+MIN_VERIFIED_HOURS = 20
+
+# Normals for a date average every year's days this close to it
+NORMAL_WINDOW_DAYS = 7
+
+# The usual threshold for a day counting as wet (0.01 in)
+MEASURABLE_PRECIPITATION_INCHES = 0.01
+
+# The hourly shape served from history, whether from snapshots or an archive:
+# each field and the snapshot column it's averaged from
+HOURLY_FIELDS = {
+    'temperature': 'temperature',
+    'feels_like': 'feels_like',
+    'dew_point': 'dew_point',
+    'humidity': 'humidity',
+    'wind_speed': 'wind_speed',
+    'wind_gust': 'wind_gust',
+    'wind_direction': 'wind_direction',
+    'pressure': 'pressure',
+    # An hour's average rate in in/hr is the hour's total in inches
+    'precipitation': 'precipitation_rate',
+}
+
+
+def observation_time(current: dict[str, Any], fetched_at: float) -> float:
+    """When the conditions were observed: the provider's timestamp, else now
+
+    Providers that report one (PirateWeather) hand out the same observation
+    until the station reports again, so refetches don't make duplicate rows.
+    """
+    stamp = current.get('timestamp')
+    if isinstance(stamp, int | float) and not isinstance(stamp, bool) and stamp > 0:
+        return float(stamp)
+    return fetched_at
+
+
+def numeric(value: Any) -> float | None:
+    """A reading as a float, or None when it's missing or not a number"""
+    if isinstance(value, bool) or not isinstance(value, int | float):
+        return None
+    return float(value)
+
+
+class HistoryStore:
+    """Observations in a SQLite database, oldest first
+
+    Snapshots are kept for retention_days, then compacted into daily
+    summaries kept for daily_retention_days. 0 (or None) keeps them forever.
+    """
+
+    def __init__(
+        self,
+        path: str,
+        retention_days: float | None = None,
+        daily_retention_days: float | None = None,
+    ) -> None:
+        self.path = path
+        self.retention_days = retention_days or None
+        self.daily_retention_days = daily_retention_days or None
+        directory = os.path.dirname(path)
+        if directory:
+            os.makedirs(directory, exist_ok=True)
+        # One shared connection; the lock serializes access across threads
+        self._db = sqlite3.connect(path, check_same_thread=False)
+        self._db.row_factory = sqlite3.Row
+        self._lock = threading.Lock()
+        with self._lock, self._db:
+            self._db.execute(HISTORY_SCHEMA)
+            self._db.execute(HISTORY_INDEX)
+            self._db.execute(FORECASTS_SCHEMA)
+            self._db.execute(DAYS_SCHEMA)
+            self._db.execute(DAILY_SCHEMA)
+
+    def record(
+        self,
+        location: str,
+        lat: float,
+        lon: float,
+        provider: str,
+        current: dict[str, Any],
+        fetched_at: float | None = None,
+    ) -> bool:
+        """Save one snapshot, returning False if it was already recorded"""
+        fetched_at = time.time() if fetched_at is None else fetched_at
+        row = {
+            'location': location,
+            'lat': lat,
+            'lon': lon,
+            'provider': provider,
+            'observed_at': observation_time(current, fetched_at),
+            **{field: numeric(current.get(field)) for field in HISTORY_FIELDS},
+            'icon': current.get('icon'),
+            'summary': current.get('summary'),
+        }
+        columns = ', '.join(HISTORY_COLUMNS)
+        placeholders = ', '.join(f':{column}' for column in HISTORY_COLUMNS)
+        with self._lock, self._db:
+            cursor = self._db.execute(
+                # Column names are the constants above, never from the request
+                f'INSERT OR IGNORE INTO observations ({columns}) '  # noqa: S608
+                f'VALUES ({placeholders})',
+                row,
+            )
+        return cursor.rowcount > 0
+
+    def observations(
+        self,
+        location: str,
+        start: float,
+        end: float,
+        provider: str | None = None,
+    ) -> list[dict[str, Any]]:
+        """A location's snapshots observed between start and end, oldest first"""
+        query = (
+            'SELECT * FROM observations '
+            'WHERE location = ? AND observed_at BETWEEN ? AND ?'
+        )
+        params: list[Any] = [location, start, end]
+        if provider is not None:
+            query += ' AND provider = ?'
+            params.append(provider)
+        with self._lock:
+            rows = self._db.execute(f'{query} ORDER BY observed_at', params).fetchall()
+        return [dict(row) for row in rows]
+
+    def locations(self) -> list[dict[str, Any]]:
+        """Every location with history, with its observation count and span"""
+        with self._lock:
+            rows = self._db.execute(
+                'SELECT location, lat, lon, COUNT(*) AS count, '
+                'MIN(observed_at) AS first, MAX(observed_at) AS last '
+                'FROM observations GROUP BY location ORDER BY location'
+            ).fetchall()
+        return [dict(row) for row in rows]
+
+    def record_forecast(
+        self,
+        location: str,
+        provider: str,
+        day: dict[str, Any],
+        issued_at: float | None = None,
+    ) -> None:
+        """Keep a provider's forecast for a day, replacing any issued earlier
+
+        Called with tomorrow's entry on every fetch, this leaves the last
+        forecast issued the day before - the one scored as next-day accuracy.
+        """
+        with self._lock, self._db:
+            self._db.execute(
+                'INSERT OR REPLACE INTO forecasts (location, provider, target_date, '
+                'issued_at, high, low, precipitation_probability) '
+                'VALUES (?, ?, ?, ?, ?, ?, ?)',
+                (
+                    location,
+                    provider,
+                    str(day['date'])[:10],
+                    time.time() if issued_at is None else issued_at,
+                    numeric(day.get('h')),
+                    numeric(day.get('l')),
+                    numeric(day.get('rain')),
+                ),
+            )
+
+    def forecasts(
+        self, location: str, first_date: str, last_date: str
+    ) -> list[dict[str, Any]]:
+        """A location's kept forecasts for days between two ISO dates"""
+        with self._lock:
+            rows = self._db.execute(
+                'SELECT * FROM forecasts WHERE location = ? '
+                'AND target_date BETWEEN ? AND ? ORDER BY target_date, provider',
+                (location, first_date, last_date),
+            ).fetchall()
+        return [dict(row) for row in rows]
+
+    def record_days(
+        self, location: str, days: dict[str, dict[str, Any]], source: str
+    ) -> int:
+        """Save daily summaries keyed by ISO date, replacing any for those dates"""
+        with self._lock, self._db:
+            self._db.executemany(
+                'INSERT OR REPLACE INTO days (location, date, temperature_max, '
+                'temperature_min, precipitation_sum, source) VALUES (?, ?, ?, ?, ?, ?)',
+                [
+                    (
+                        location,
+                        iso_date,
+                        numeric(day.get('temperature_max')),
+                        numeric(day.get('temperature_min')),
+                        numeric(day.get('precipitation_sum')),
+                        source,
+                    )
+                    for iso_date, day in days.items()
+                ],
+            )
+        return len(days)
+
+    def days(self, location: str) -> list[dict[str, Any]]:
+        """Every daily summary kept for a location, oldest first"""
+        with self._lock:
+            rows = self._db.execute(
+                'SELECT * FROM days WHERE location = ? ORDER BY date', (location,)
+            ).fetchall()
+        return [dict(row) for row in rows]
+
+    def last_day(self, location: str) -> str | None:
+        """The latest date with a daily summary for a location"""
+        with self._lock:
+            (last,) = self._db.execute(
+                'SELECT MAX(date) FROM days WHERE location = ?', (location,)
+            ).fetchone()
+        return last  # type: ignore[no-any-return]
+
+    def daily(self, location: str, start: float, end: float) -> dict[int, dict]:
+        """A location's compacted days between two Unix times, keyed by day start"""
+        with self._lock:
+            rows = self._db.execute(
+                'SELECT * FROM daily_observations WHERE location = ? '
+                'AND date BETWEEN ? AND ? ORDER BY date',
+                (location, utc_date(start), utc_date(end)),
+            ).fetchall()
+        return {
+            int(
+                datetime.fromisoformat(row['date'])
+                .replace(tzinfo=timezone.utc)
+                .timestamp()
+            ): {field: row[field] for field in (*DAILY_FIELDS, 'hours')}
+            for row in rows
+        }
+
+    def export(
+        self, table: str, location: str | None, start: float, end: float
+    ) -> list[dict[str, Any]]:
+        """Every row of an EXPORT_TABLES table in a range, for one or all locations"""
+        source, _, range_column = EXPORT_TABLES[table]
+        if range_column == 'date':
+            bounds: tuple[Any, Any] = (utc_date(start), utc_date(end))
+        else:
+            bounds = (start, end)
+        # Table and column names come from EXPORT_TABLES, never from the request
+        query = (
+            f'SELECT * FROM {source} '  # noqa: S608
+            f'WHERE {range_column} BETWEEN ? AND ?'
+        )
+        params: list[Any] = list(bounds)
+        if location is not None:
+            query += ' AND location = ?'
+            params.append(location)
+        with self._lock:
+            rows = self._db.execute(
+                f'{query} ORDER BY location, {range_column}', params
+            ).fetchall()
+        return [dict(row) for row in rows]
+
+    def compact(self, now: float | None = None) -> dict[str, int]:
+        """Roll snapshots past retention into daily summaries, and prune
+
+        Whole UTC days are compacted, each once: snapshots for a day that turn
+        up after it was compacted are dropped. Daily summaries and forecasts go
+        once past daily_retention_days. Returns how many rows each step wrote
+        or deleted.
+        """
+        now = time.time() if now is None else now
+        counts = {'days': 0, 'observations': 0, 'daily': 0, 'forecasts': 0}
+        if self.retention_days is not None:
+            cutoff = utc_day_start(now - self.retention_days * SECONDS_PER_DAY)
+            with self._lock:
+                locations = [
+                    row['location']
+                    for row in self._db.execute(
+                        'SELECT DISTINCT location FROM observations '
+                        'WHERE observed_at < ?',
+                        (cutoff,),
+                    )
+                ]
+            # A location at a time, so a long backlog isn't all in memory at once
+            for location in locations:
+                counts['days'] += self.compact_location(location, cutoff)
+            with self._lock, self._db:
+                counts['observations'] = self._db.execute(
+                    'DELETE FROM observations WHERE observed_at < ?', (cutoff,)
+                ).rowcount
+        if self.daily_retention_days is not None:
+            cutoff = now - self.daily_retention_days * SECONDS_PER_DAY
+            with self._lock, self._db:
+                counts['daily'] = self._db.execute(
+                    'DELETE FROM daily_observations WHERE date < ?', (utc_date(cutoff),)
+                ).rowcount
+                counts['forecasts'] = self._db.execute(
+                    'DELETE FROM forecasts WHERE issued_at < ?', (cutoff,)
+                ).rowcount
+        if counts['observations'] or counts['daily']:
+            with self._lock:
+                self._db.execute('VACUUM')  # Give the space back
+        return counts
+
+    def compact_location(self, location: str, cutoff: float) -> int:
+        """Summarize a location's snapshots before cutoff into days it hasn't got"""
+        with self._lock:
+            rows = [
+                dict(row)
+                for row in self._db.execute(
+                    'SELECT * FROM observations WHERE location = ? '
+                    'AND observed_at < ? ORDER BY observed_at',
+                    (location, cutoff),
+                )
+            ]
+        if not rows:
+            return 0
+        lat, lon = rows[-1]['lat'], rows[-1]['lon']
+        days = daily_from_hourly(hourly_from_observations(rows))
+        columns = ', '.join(DAILY_COLUMNS)
+        placeholders = ', '.join(f':{column}' for column in DAILY_COLUMNS)
+        with self._lock, self._db:
+            cursor = self._db.executemany(
+                # Column names are the constants above, never from the request
+                f'INSERT OR IGNORE INTO daily_observations ({columns}) '  # noqa: S608
+                f'VALUES ({placeholders})',
+                [
+                    {
+                        'location': location,
+                        'lat': lat,
+                        'lon': lon,
+                        'date': utc_date(day),
+                        **summary,
+                    }
+                    for day, summary in days.items()
+                ],
+            )
+        return cursor.rowcount
+
+
+def utc_day_start(seconds: float) -> int:
+    """The Unix start of the UTC day a time falls in"""
+    return int(seconds) // SECONDS_PER_DAY * SECONDS_PER_DAY
+
+
+def utc_date(seconds: float) -> str:
+    """The ISO date of the UTC day a time falls in"""
+    return datetime.fromtimestamp(seconds, tz=timezone.utc).date().isoformat()
+
+
+def mean_direction(degrees: list[float]) -> float:
+    """Average compass bearings, so 350 and 10 make 0 rather than 180"""
+    x = sum(math.cos(math.radians(value)) for value in degrees)
+    y = sum(math.sin(math.radians(value)) for value in degrees)
+    return round(math.degrees(math.atan2(y, x)) % 360, 1)
+
+
+def hourly_from_observations(rows: list[dict[str, Any]]) -> dict[int, dict[str, Any]]:
+    """Average snapshots into hourly points, keyed by the hour's Unix start
+
+    Snapshots from every provider that reported in the hour are averaged.
+    """
+    hours: dict[int, list[dict[str, Any]]] = {}
+    for row in rows:
+        hour = int(row['observed_at']) // SECONDS_PER_HOUR * SECONDS_PER_HOUR
+        hours.setdefault(hour, []).append(row)
+
+    points = {}
+    for hour, snapshots in hours.items():
+        point: dict[str, Any] = {}
+        for field, column in HOURLY_FIELDS.items():
+            values = [row[column] for row in snapshots if row[column] is not None]
+            if not values:
+                point[field] = None
+            elif field == 'wind_direction':
+                point[field] = mean_direction(values)
+            else:
+                point[field] = round(sum(values) / len(values), 2)
+        points[hour] = point
+    return points
+
+
+def summarize(values: list[float], aggregate: str) -> float | None:
+    """The max, min, mean or sum of some readings, or None if there are none"""
+    if not values:
+        return None
+    if aggregate == 'max':
+        result = max(values)
+    elif aggregate == 'min':
+        result = min(values)
+    elif aggregate == 'sum':
+        result = sum(values)
+    else:
+        result = sum(values) / len(values)
+    return round(result, 2)
+
+
+def daily_from_hourly(hours: dict[int, dict[str, Any]]) -> dict[int, dict[str, Any]]:
+    """Roll hourly points up into UTC days, keyed by the day's Unix start"""
+    days: dict[int, list[dict[str, Any]]] = {}
+    for hour, point in hours.items():
+        days.setdefault(hour // SECONDS_PER_DAY * SECONDS_PER_DAY, []).append(point)
+
+    daily = {}
+    for day, points in days.items():
+        summary: dict[str, Any] = {}
+        for field, (source, aggregate) in DAILY_FIELDS.items():
+            values = [point[source] for point in points if point[source] is not None]
+            summary[field] = summarize(values, aggregate)
+        daily[day] = {**summary, 'hours': len(points)}
+    return daily
+
+
+def solar_day(iso_date: str, lon: float) -> tuple[int, int]:
+    """The Unix start and end of a date at a longitude, in solar time
+
+    Forecast days are local days; an offset of one hour per 15 degrees is
+    close enough to the time zone for finding the day's high.
+    """
+    offset = round(lon / 15) * SECONDS_PER_HOUR
+    midnight = datetime.fromisoformat(iso_date).replace(tzinfo=timezone.utc)
+    start = int(midnight.timestamp()) - offset
+    return start, start + SECONDS_PER_DAY - 1
+
+
+def verify_day(hours: list[dict[str, Any]]) -> dict[str, Any] | None:
+    """What happened on a day - its high and whether it rained - from its hours
+
+    None unless nearly every hour is known, since a gap can hide the high.
+    """
+    temperatures = [
+        hour['temperature'] for hour in hours if hour['temperature'] is not None
+    ]
+    if len(temperatures) < MIN_VERIFIED_HOURS:
+        return None
+    precipitation = [
+        hour['precipitation'] for hour in hours if hour['precipitation'] is not None
+    ]
+    return {
+        'high': max(temperatures),
+        'precipitated': sum(precipitation) >= MEASURABLE_PRECIPITATION_INCHES,
+    }
+
+
+def score_forecasts(
+    forecasts: list[dict[str, Any]], outcomes: dict[str, dict[str, Any]]
+) -> list[dict[str, Any]]:
+    """Per-provider next-day error: MAE of the high, Brier score of rain chances
+
+    outcomes maps each verified date to verify_day's result; forecasts for
+    other dates aren't scored. Providers come best (lowest high MAE) first.
+    """
+    errors: dict[str, list[float]] = {}
+    briers: dict[str, list[float]] = {}
+    for forecast in forecasts:
+        outcome = outcomes.get(forecast['target_date'])
+        if outcome is None:
+            continue
+        provider = forecast['provider']
+        errors.setdefault(provider, [])
+        briers.setdefault(provider, [])
+        if forecast['high'] is not None:
+            errors[provider].append(abs(forecast['high'] - outcome['high']))
+        if forecast['precipitation_probability'] is not None:
+            chance = forecast['precipitation_probability'] / 100
+            briers[provider].append((chance - float(outcome['precipitated'])) ** 2)
+
+    scores = [
+        {
+            'provider': provider,
+            'days': max(len(errors[provider]), len(briers[provider])),
+            'high_mae': summarize(errors[provider], 'mean'),
+            'precipitation_brier': (
+                round(sum(briers[provider]) / len(briers[provider]), 3)
+                if briers[provider]
+                else None
+            ),
+        }
+        for provider in errors
+    ]
+    return sorted(
+        scores,
+        key=lambda score: (score['high_mae'] is None, score['high_mae'] or 0),
+    )
+
+
+def day_of_year_distance(first: date, second: date) -> int:
+    """Days between two dates' places in the year, wrapping at New Year"""
+    gap = abs(first.timetuple().tm_yday - second.timetuple().tm_yday)
+    return min(gap, 365 - gap)
+
+
+def mean(values: list[float]) -> float | None:
+    """The rounded average of some readings, or None if there are none"""
+    return summarize(values, 'mean')
+
+
+def known(days: list[dict[str, Any]], field: str) -> list[float]:
+    """A field's values across days, skipping days without one"""
+    return [day[field] for day in days if day[field] is not None]
+
+
+def day_normal(days: list[dict[str, Any]], day: date) -> dict[str, float | None]:
+    """The normal high and low for a date: every year's days within a week of it"""
+    window = [
+        summary
+        for summary in days
+        if day_of_year_distance(date.fromisoformat(summary['date']), day)
+        <= NORMAL_WINDOW_DAYS
+    ]
+    return {
+        'high': mean(known(window, 'temperature_max')),
+        'low': mean(known(window, 'temperature_min')),
+    }
+
+
+def record_of(
+    days: list[dict[str, Any]], field: str, highest: bool
+) -> dict[str, Any] | None:
+    """The day with the highest (or lowest) value of a field, and that value"""
+    candidates = [day for day in days if day[field] is not None]
+    if not candidates:
+        return None
+    pick = max if highest else min
+    day = pick(candidates, key=lambda candidate: candidate[field])
+    return {'value': day[field], 'date': day['date']}
+
+
+def month_climatology(days: list[dict[str, Any]], month: int) -> dict[str, Any]:
+    """Normals and records for a calendar month from daily summaries"""
+    in_month = [day for day in days if int(day['date'][5:7]) == month]
+    daily_precipitation = mean(known(in_month, 'precipitation_sum'))
+    return {
+        'normals': {
+            'high': mean(known(in_month, 'temperature_max')),
+            'low': mean(known(in_month, 'temperature_min')),
+            'precipitation': (
+                round(daily_precipitation * calendar.monthrange(2001, month)[1], 2)
+                if daily_precipitation is not None
+                else None
+            ),
+        },
+        'records': {
+            'high': record_of(in_month, 'temperature_max', highest=True),
+            'low': record_of(in_month, 'temperature_min', highest=False),
+        },
+        'years': len({day['date'][:4] for day in in_month}),
+    }
+
+
+def compare_to_normal(value: float | None, normal: float | None) -> float | None:
+    """How far a reading is above (positive) or below its normal"""
+    if value is None or normal is None:
+        return None
+    return round(value - normal, 1)
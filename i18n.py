@@ -0,0 +1,106 @@
+# ABOUTME: Localized wind wording - Beaufort force names and compass directions
+# ABOUTME: Combines them into phrases like "brisa fresca del noroeste"
+
+from weather_core.derived import beaufort_force, compass_index
+
+
+DEFAULT_LANGUAGE = 'en'
+
+BEAUFORT_NAMES = {
+    'en': (
+        'calm', 'light air', 'light breeze', 'gentle breeze', 'moderate breeze',
+        'fresh breeze', 'strong breeze', 'near gale', 'gale', 'strong gale',
+        'storm', 'violent storm', 'hurricane',
+    ),
+    'es': (
+        'calma', 'ventolina', 'brisa muy débil', 'brisa débil', 'brisa moderada',
+        'brisa fresca', 'brisa fuerte', 'viento fuerte', 'temporal',
+        'temporal fuerte', 'temporal duro', 'temporal muy duro', 'huracán',
+    ),
+    'fr': (
+        'calme', 'très légère brise', 'légère brise', 'petite brise',
+        'jolie brise', 'bonne brise', 'vent frais', 'grand frais',
+        'coup de vent', 'fort coup de vent', 'tempête', 'violente tempête',
+        'ouragan',
+    ),
+    'de': (
+        'Windstille', 'leiser Zug', 'leichte Brise', 'schwache Brise',
+        'mäßige Brise', 'frische Brise', 'starker Wind', 'steifer Wind',
+        'stürmischer Wind', 'Sturm', 'schwerer Sturm', 'orkanartiger Sturm',
+        'Orkan',
+    ),
+}  # fmt: skip
+
+# Compass directions in the same clockwise order as derived.COMPASS_POINTS
+DIRECTION_NAMES = {
+    'en': (
+        'north', 'north-northeast', 'northeast', 'east-northeast',
+        'east', 'east-southeast', 'southeast', 'south-southeast',
+        'south', 'south-southwest', 'southwest', 'west-southwest',
+        'west', 'west-northwest', 'northwest', 'north-northwest',
+    ),
+    'es': (
+        'norte', 'nornordeste', 'nordeste', 'estenordeste',
+        'este', 'estesudeste', 'sudeste', 'sursudeste',
+        'sur', 'sursudoeste', 'sudoeste', 'oestesudoeste',
+        'oeste', 'oestenoroeste', 'noroeste', 'nornoroeste',
+    ),
+    'fr': (
+        'nord', 'nord-nord-est', 'nord-est', 'est-nord-est',
+        'est', 'est-sud-est', 'sud-est', 'sud-sud-est',
+        'sud', 'sud-sud-ouest', 'sud-ouest', 'ouest-sud-ouest',
+        'ouest', 'ouest-nord-ouest', 'nord-ouest', 'nord-nord-ouest',
+    ),
+    'de': (
+        'Nord', 'Nordnordost', 'Nordost', 'Ostnordost',
+        'Ost', 'Ostsüdost', 'Südost', 'Südsüdost',
+        'Süd', 'Südsüdwest', 'Südwest', 'Westsüdwest',
+        'West', 'Westnordwest', 'Nordwest', 'Nordnordwest',
+    ),
+}  # fmt: skip
+
+# How "<force> from <direction>" is phrased in each language
+WIND_FROM = {
+    'en': '{force} from the {direction}',
+    'es': '{force} del {direction}',
+    'fr': '{force} du {direction}',
+    'de': '{force} aus {direction}',
+}
+SUPPORTED_LANGUAGES = tuple(BEAUFORT_NAMES)
+
+
+def resolve_language(requested: str | None) -> str | None:
+    """Map a language tag like 'es-MX' to a supported language, or None"""
+    if not requested:
+        return None
+    language = requested.split('-')[0].split('_')[0].strip().lower()
+    return language if language in SUPPORTED_LANGUAGES else None
+
+
+def beaufort_name(force: int, language: str = DEFAULT_LANGUAGE) -> str:
+    """Localized Beaufort force name"""
+    return BEAUFORT_NAMES.get(language, BEAUFORT_NAMES[DEFAULT_LANGUAGE])[force]
+
+
+def direction_name(degrees: float, language: str = DEFAULT_LANGUAGE) -> str:
+    """Localized compass direction a wind blows from"""
+    names = DIRECTION_NAMES.get(language, DIRECTION_NAMES[DEFAULT_LANGUAGE])
+    return names[compass_index(degrees)]
+
+
+def describe_wind(
+    wind_speed_mph: float, degrees: float | None, language: str = DEFAULT_LANGUAGE
+) -> str:
+    """Describe wind as a localized Beaufort name with its direction"""
+    if language not in SUPPORTED_LANGUAGES:
+        language = DEFAULT_LANGUAGE
+    force = beaufort_force(wind_speed_mph)
+    name = beaufort_name(force, language)
+    # Calm air and missing directions have no "from"
+    if force == 0 or degrees is None:
+        return name
+
+    direction = direction_name(degrees, language)
+    if language == 'fr' and direction[0] in 'eo':
+        return f"{name} de l'{direction}"
+    return WIND_FROM[language].format(force=name, direction=direction)
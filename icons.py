@@ -0,0 +1,223 @@
+# ABOUTME: Icon packs mapping the weather icon codes to images, font glyphs or emoji
+# ABOUTME: Pages render from the same table that JSON responses send as icon_set
+
+from collections.abc import Callable
+from html import escape
+from typing import Any
+
+
+# The condition vocabulary every provider maps its codes into
+WEATHER_ICON_CODES = (
+    'clear-day',
+    'clear-night',
+    'rain',
+    'heavy-rain',
+    'light-rain',
+    'snow',
+    'heavy-snow',
+    'light-snow',
+    'sleet',
+    'wind',
+    'fog',
+    'cloudy',
+    'partly-cloudy-day',
+    'partly-cloudy-night',
+    'thunderstorm',
+    'hail',
+)
+FALLBACK_ICON = 'clear-day'
+ICON_KINDS = ('image', 'font', 'text')
+
+
+class IconSet:
+    """One icon pack: an image, icon-font class or bit of text per icon code"""
+
+    def __init__(
+        self,
+        name: str,
+        label: str,
+        kind: str,
+        icons: dict[str, str],
+        template: str = '{icon}',
+        animated_template: str | None = None,
+        stylesheet: str | None = None,
+    ) -> None:
+        if kind not in ICON_KINDS:
+            msg = f'Icon set {name} has unknown kind {kind}'
+            raise ValueError(msg)
+        self.name = name
+        self.label = label
+        self.kind = kind
+        self.icons = icons
+        self.template = template
+        self.animated_template = animated_template
+        self.stylesheet = stylesheet
+
+    def value(self, code: str, animated: bool = False) -> str:
+        """The pack's path, URL, class or text for an icon code"""
+        icon = self.icons.get(code, self.icons[FALLBACK_ICON])
+        template = (animated and self.animated_template) or self.template
+        return template.format(icon=icon)
+
+    def source(
+        self, code: str, asset_url: Callable[[str], str], animated: bool = False
+    ) -> str:
+        """What a client shows for a code, with local images as versioned URLs"""
+        value = self.value(code, animated)
+        if self.kind == 'image' and '://' not in value:
+            return asset_url(value)
+        return value
+
+    def describe(self, asset_url: Callable[[str], str]) -> dict[str, Any]:
+        """The pack as sent to clients: its kind and the mapping for every code"""
+        block: dict[str, Any] = {
+            'name': self.name,
+            'label': self.label,
+            'kind': self.kind,
+            'stylesheet': self.stylesheet,
+            'icons': {
+                code: self.source(code, asset_url) for code in WEATHER_ICON_CODES
+            },
+        }
+        if self.animated_template:
+            block['animated_icons'] = {
+                code: self.source(code, asset_url, animated=True)
+                for code in WEATHER_ICON_CODES
+            }
+        return block
+
+    def html(self, code: str, asset_url: Callable[[str], str]) -> str:
+        """Markup for an icon that scales with the surrounding font size"""
+        label = escape(code.replace('-', ' '))
+        value = escape(self.source(code, asset_url))
+        if self.kind == 'image':
+            return f'<img class="weather-icon" src="{value}" alt="{label}">'
+        accessible = f'role="img" aria-label="{label}"'
+        if self.kind == 'font':
+            return f'<i class="weather-icon {value}" {accessible}></i>'
+        return f'<span class="weather-icon" {accessible}>{value}</span>'
+
+
+ICON_SETS: dict[str, IconSet] = {
+    'default': IconSet(
+        'default',
+        'Dashboard',
+        'image',
+        {
+            'clear-day': 'clear-day.svg',
+            'clear-night': 'clear-night.svg',
+            'rain': 'rainy-2.svg',
+            'heavy-rain': 'rainy-3.svg',
+            'light-rain': 'rainy-1.svg',
+            'snow': 'snowy-1.svg',
+            'heavy-snow': 'snowy-3.svg',
+            'light-snow': 'snowy-1.svg',
+            'sleet': 'snowy-2.svg',
+            'wind': 'wind.svg',
+            'fog': 'fog.svg',
+            'cloudy': 'cloudy.svg',
+            'partly-cloudy-day': 'cloudy-1-day.svg',
+            'partly-cloudy-night': 'cloudy-1-night.svg',
+            'thunderstorm': 'thunderstorms.svg',
+            'hail': 'hail.svg',
+        },
+        template='icons/weather/static/{icon}',
+        animated_template='icons/weather/animated/{icon}',
+    ),
+    'weather-icons': IconSet(
+        'weather-icons',
+        'Weather Icons',
+        'font',
+        {
+            'clear-day': 'wi-day-sunny',
+            'clear-night': 'wi-night-clear',
+            'rain': 'wi-rain',
+            'heavy-rain': 'wi-rain-wind',
+            'light-rain': 'wi-sprinkle',
+            'snow': 'wi-snow',
+            'heavy-snow': 'wi-snow-wind',
+            'light-snow': 'wi-snow',
+            'sleet': 'wi-sleet',
+            'wind': 'wi-strong-wind',
+            'fog': 'wi-fog',
+            'cloudy': 'wi-cloudy',
+            'partly-cloudy-day': 'wi-day-cloudy',
+            'partly-cloudy-night': 'wi-night-alt-cloudy',
+            'thunderstorm': 'wi-thunderstorm',
+            'hail': 'wi-hail',
+        },
+        template='wi {icon}',
+        stylesheet=(
+            'https://cdnjs.cloudflare.com/ajax/libs/weather-icons/2.0.12/'
+            'css/weather-icons.min.css'
+        ),
+    ),
+    'meteocons': IconSet(
+        'meteocons',
+        'Meteocons',
+        'image',
+        {
+            'clear-day': 'clear-day',
+            'clear-night': 'clear-night',
+            'rain': 'rain',
+            'heavy-rain': 'extreme-rain',
+            'light-rain': 'drizzle',
+            'snow': 'snow',
+            'heavy-snow': 'extreme-snow',
+            'light-snow': 'snow',
+            'sleet': 'sleet',
+            'wind': 'wind',
+            'fog': 'fog',
+            'cloudy': 'overcast',
+            'partly-cloudy-day': 'partly-cloudy-day',
+            'partly-cloudy-night': 'partly-cloudy-night',
+            'thunderstorm': 'thunderstorms-rain',
+            'hail': 'hail',
+        },
+        template=(
+            'https://cdn.jsdelivr.net/gh/basmilius/weather-icons/'
+            'production/fill/all/{icon}.svg'
+        ),
+    ),
+    'emoji': IconSet(
+        'emoji',
+        'Emoji',
+        'text',
+        {
+            'clear-day': '☀️',
+            'clear-night': '🌙',
+            'rain': '🌧',
+            'heavy-rain': '🌧',
+            'light-rain': '🌦',
+            'snow': '❄️',
+            'heavy-snow': '❄️',
+            'light-snow': '🌨',
+            'sleet': '🌨',
+            'wind': '💨',
+            'fog': '🌫',
+            'cloudy': '☁️',
+            'partly-cloudy-day': '⛅',
+            'partly-cloudy-night': '☁️',
+            'thunderstorm': '⛈',
+            'hail': '🌨',
+        },
+    ),
+}
+DEFAULT_ICON_SET = 'default'
+
+# Alternate names accepted in ?icons= and ICON_SET
+ICON_SET_ALIASES = {
+    'svg': 'default',
+    'weathericons': 'weather-icons',
+    'wi': 'weather-icons',
+    'meteocon': 'meteocons',
+}
+
+
+def resolve_icon_set(name: str | None) -> str | None:
+    """The canonical icon set for a name or alias, or None if it isn't one"""
+    if not name:
+        return None
+    name = name.strip().lower()
+    name = ICON_SET_ALIASES.get(name, name)
+    return name if name in ICON_SETS else None
@@ -0,0 +1,131 @@
+# ABOUTME: Optional InfluxDB v2 sink writing weather observations as line protocol
+# ABOUTME: Points are buffered and written in batches, retried with backoff on failure
+
+import threading
+import time
+from collections import deque
+from collections.abc import Callable
+from itertools import islice
+from typing import Any
+
+import requests
+
+
+INFLUX_TIMEOUT_SECONDS = 10
+MAX_RETRY_DELAY_SECONDS = 300
+
+
+def escape_key(value: str) -> str:
+    """Escape a measurement name, tag key/value or field key"""
+    for char in ('\\', ',', ' ', '='):
+        value = value.replace(char, f'\\{char}')
+    return value
+
+
+def format_field_value(value: Any) -> str | None:
+    """Format a field value - numbers are always floats so types never conflict"""
+    if isinstance(value, bool):
+        return 'true' if value else 'false'
+    if isinstance(value, int | float):
+        return repr(float(value))
+    if isinstance(value, str):
+        escaped = value.replace('\\', '\\\\').replace('"', '\\"')
+        return f'"{escaped}"'
+    return None
+
+
+def line_protocol(
+    measurement: str, tags: dict[str, str], fields: dict[str, Any], timestamp: int
+) -> str | None:
+    """Build one line-protocol point (second precision), or None with no fields"""
+    field_parts = []
+    for key, value in fields.items():
+        formatted = format_field_value(value)
+        if formatted is not None:
+            field_parts.append(f'{escape_key(key)}={formatted}')
+    if not field_parts:
+        return None
+
+    tag_parts = ''.join(
+        f',{escape_key(key)}={escape_key(value)}'
+        for key, value in sorted(tags.items())
+        if value
+    )
+    return f'{escape_key(measurement)}{tag_parts} {",".join(field_parts)} {timestamp}'
+
+
+class InfluxWriter:
+    """Batching line-protocol writer for the InfluxDB v2 /api/v2/write endpoint"""
+
+    def __init__(
+        self,
+        url: str,
+        org: str,
+        bucket: str,
+        token: str,
+        batch_size: int = 500,
+        flush_interval: float = 10,
+        max_buffer: int = 10000,
+    ) -> None:
+        self.write_url = f'{url.rstrip("/")}/api/v2/write'
+        self.org = org
+        self.bucket = bucket
+        self.token = token
+        self.batch_size = batch_size
+        self.flush_interval = flush_interval
+        # Oldest points are dropped if InfluxDB stays down long enough to fill this
+        self.buffer: deque[str] = deque(maxlen=max_buffer)
+        self._lock = threading.Lock()
+        self.retry_delay = flush_interval
+
+    def write(self, line: str | None) -> None:
+        """Queue a point for the next batch"""
+        if line:
+            with self._lock:
+                self.buffer.append(line)
+
+    def flush(self) -> bool:
+        """Send queued points in batches, keeping unsent ones for the next attempt"""
+        while True:
+            with self._lock:
+                batch = list(islice(self.buffer, self.batch_size))
+            if not batch:
+                return True
+            if not self.send(batch):
+                return False
+            with self._lock:
+                # Skip points the full buffer already dropped while we were sending
+                for line in batch:
+                    if self.buffer and self.buffer[0] is line:
+                        self.buffer.popleft()
+
+    def send(self, batch: list[str]) -> bool:
+        """POST one batch of points"""
+        try:
+            response = requests.post(
+                self.write_url,
+                params={'org': self.org, 'bucket': self.bucket, 'precision': 's'},
+                data='\n'.join(batch).encode('utf-8'),
+                headers={
+                    'Authorization': f'Token {self.token}',
+                    'Content-Type': 'text/plain; charset=utf-8',
+                },
+                timeout=INFLUX_TIMEOUT_SECONDS,
+            )
+        except requests.exceptions.RequestException as e:
+            print(f'❌ InfluxDB write failed: {str(e)}')
+            return False
+        if response.status_code != 204:  # noqa: PLR2004
+            print(f'❌ InfluxDB write failed: {response.status_code} {response.text}')
+            return False
+        return True
+
+    def run(self, sleep: Callable[[float], Any] = time.sleep) -> None:
+        """Background loop flushing every interval, backing off while writes fail"""
+        while True:
+            sleep(self.retry_delay)
+            if self.flush():
+                self.retry_delay = self.flush_interval
+            else:
+                self.retry_delay = min(self.retry_delay * 2, MAX_RETRY_DELAY_SECONDS)
+                print(f'⏳ Retrying InfluxDB write in {self.retry_delay:.0f}s')
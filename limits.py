@@ -0,0 +1,163 @@
+# ABOUTME: WSGI middleware bounding each request's time and how many run at once, so a
+# ABOUTME: slow upstream or a flood of requests gets quick 504s and 503s, not a pile-up
+
+import json
+import threading
+from collections.abc import Iterable
+from typing import Any
+
+from werkzeug.wrappers import Response
+
+from errors import RequestTimedOut, ServerBusy, WeatherError
+
+
+# Paths that hold their connection open by design (websockets, long-polling) or
+# must answer however busy the server is
+UNLIMITED_PATHS = ('/socket.io', '/healthz')
+
+# How long a refused client should wait before trying again
+BUSY_RETRY_AFTER_SECONDS = 1
+
+
+def error_response(error: WeatherError) -> Response:
+    """A typed error in the app's own JSON, for answers given before it runs"""
+    response = Response(
+        json.dumps(error.to_dict()), error.status, mimetype='application/json'
+    )
+    retry_after = error.details.get('retry_after')
+    if retry_after is not None:
+        response.headers['Retry-After'] = str(retry_after)
+    return response
+
+
+class RequestLimits:
+    """WSGI middleware with a per-request time limit and a concurrency limit
+
+    A request runs on a worker thread; if it hasn't answered within timeout
+    seconds the client gets a 504 while the worker finishes in the background
+    (threads can't be cancelled), still holding its slot. Requests beyond
+    max_concurrent are shed with a 503 straight away. Event streams keep their
+    connection, so they are exempt from both once their headers are sent.
+    Either limit may be None.
+    """
+
+    def __init__(
+        self,
+        wsgi_app: Any,
+        timeout: float | None = None,
+        max_concurrent: int | None = None,
+    ) -> None:
+        self.wsgi_app = wsgi_app
+        self.timeout = timeout
+        self.max_concurrent = max_concurrent
+        self.slots = threading.BoundedSemaphore(max_concurrent or 1)
+        self.in_flight = 0
+        self.shed = 0
+        self.timed_out = 0
+        self._lock = threading.Lock()
+
+    def __call__(
+        self, environ: dict[str, Any], start_response: Any
+    ) -> Iterable[bytes]:
+        path = environ.get('PATH_INFO', '')
+        unlimited = self.timeout is None and self.max_concurrent is None
+        if unlimited or path.startswith(UNLIMITED_PATHS):
+            return self.wsgi_app(environ, start_response)  # type: ignore[no-any-return]
+
+        if self.max_concurrent is not None and not self.slots.acquire(blocking=False):
+            with self._lock:
+                self.shed += 1
+            busy = ServerBusy(
+                'Server busy, try again shortly', retry_after=BUSY_RETRY_AFTER_SECONDS
+            )
+            return error_response(busy)(environ, start_response)
+
+        call = BufferedCall(self.wsgi_app, environ, self.release)
+        with self._lock:
+            self.in_flight += 1
+        threading.Thread(target=call.run, daemon=True).start()
+        if not call.wait(self.timeout):
+            with self._lock:
+                self.timed_out += 1
+            method = environ.get('REQUEST_METHOD', 'GET')
+            print(f'⏱️  {method} {path} timed out after {self.timeout:g}s')
+            timed_out = RequestTimedOut('Request timed out')
+            return error_response(timed_out)(environ, start_response)
+        return call.respond(start_response)
+
+    def release(self) -> None:
+        """Free a finished request's slot"""
+        with self._lock:
+            self.in_flight -= 1
+        if self.max_concurrent is not None:
+            self.slots.release()
+
+
+class BufferedCall:
+    """One WSGI call on a worker thread, its whole response collected there"""
+
+    def __init__(self, wsgi_app: Any, environ: dict[str, Any], on_done: Any) -> None:
+        self.wsgi_app = wsgi_app
+        self.environ = environ
+        self.on_done = on_done
+        self.status = '500 INTERNAL SERVER ERROR'
+        self.headers: list[tuple[str, str]] = []
+        self.chunks: list[bytes] = []
+        self.stream: Iterable[bytes] | None = None
+        self.error: BaseException | None = None
+        self.abandoned = False
+        self._done = threading.Event()
+        self._lock = threading.Lock()
+
+    def start_response(
+        self,
+        status: str,
+        headers: list[tuple[str, str]],
+        exc_info: Any = None,  # noqa: ARG002
+    ) -> Any:
+        """Capture the status and headers for the calling thread to send"""
+        self.status, self.headers = status, headers
+        return self.chunks.append  # The legacy write() callable
+
+    def is_stream(self) -> bool:
+        """Whether the response is an event stream, sent as it's produced"""
+        return any(
+            name.lower() == 'content-type' and value.startswith('text/event-stream')
+            for name, value in self.headers
+        )
+
+    def run(self) -> None:
+        """Call the app and read its response, or keep an event stream for later"""
+        try:
+            iterable = self.wsgi_app(self.environ, self.start_response)
+            if self.is_stream():
+                self.stream = iterable
+            else:
+                try:
+                    self.chunks.extend(iterable)
+                finally:
+                    if hasattr(iterable, 'close'):
+                        iterable.close()
+        except BaseException as e:  # Re-raised on the calling thread
+            self.error = e
+        finally:
+            self.on_done()
+            with self._lock:
+                self._done.set()
+                abandoned = self.abandoned
+            if abandoned and self.stream is not None and hasattr(self.stream, 'close'):
+                self.stream.close()
+
+    def wait(self, timeout: float | None) -> bool:
+        """Wait for the response, giving up (and abandoning it) after timeout"""
+        self._done.wait(timeout)
+        with self._lock:
+            self.abandoned = not self._done.is_set()
+            return not self.abandoned
+
+    def respond(self, start_response: Any) -> Iterable[bytes]:
+        """Send the collected response through the server's start_response"""
+        if self.error is not None:
+            raise self.error
+        start_response(self.status, self.headers)
+        return self.stream if self.stream is not None else self.chunks
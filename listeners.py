@@ -0,0 +1,80 @@
+# ABOUTME: Where the server listens besides host:port - a unix socket path, or a socket
+# ABOUTME: systemd opened and passed in (socket activation, as sd_listen_fds does)
+
+import os
+import socket
+import ssl
+from collections.abc import MutableMapping
+from typing import Any
+
+from werkzeug.serving import BaseWSGIServer, make_server
+
+
+# systemd passes activated sockets as the descriptors from 3 up
+SD_LISTEN_FDS_START = 3
+
+# Who may connect to a unix socket: owner and group, so a proxy like nginx can
+# be let in by group without opening it to everyone
+DEFAULT_SOCKET_MODE = 0o660
+
+
+def systemd_listen_fd(
+    environ: MutableMapping[str, str] | None = None, pid: int | None = None
+) -> int | None:
+    """The first socket systemd passed to this process, or None
+
+    The LISTEN_* variables are removed, as sd_listen_fds does, so child
+    processes don't take the socket for theirs.
+    """
+    environ = os.environ if environ is None else environ
+    pid = os.getpid() if pid is None else pid
+    if environ.get('LISTEN_PID') != str(pid):
+        return None
+    count = environ.get('LISTEN_FDS', '')
+    for name in ('LISTEN_PID', 'LISTEN_FDS', 'LISTEN_FDNAMES'):
+        environ.pop(name, None)
+    if not count.isdigit() or int(count) < 1:
+        return None
+    if int(count) > 1:
+        print(f'⚠️  systemd passed {count} sockets - listening on the first only')
+    return SD_LISTEN_FDS_START
+
+
+def socket_address(fd: int) -> tuple[str, int]:
+    """The werkzeug host and port for an already open listening socket"""
+    sock = socket.socket(fileno=fd)
+    try:
+        if sock.family == socket.AF_UNIX:
+            return f'unix://{sock.getsockname()}', 0
+        host, port = sock.getsockname()[:2]
+        return host, port
+    finally:
+        sock.detach()  # The descriptor stays open for the server
+
+
+def make_socket_server(
+    app: Any,
+    unix_socket: str | None = None,
+    fd: int | None = None,
+    ssl_context: ssl.SSLContext | None = None,
+    socket_mode: int = DEFAULT_SOCKET_MODE,
+) -> BaseWSGIServer:
+    """A threaded server on an inherited socket, or on a fresh unix socket path"""
+    if fd is not None:
+        host, port = socket_address(fd)
+        server = make_server(
+            host, port, app, threaded=True, ssl_context=ssl_context, fd=fd
+        )
+        address = f'{host}:{port}' if port else host.removeprefix('unix://')
+        print(f'🔌 Listening on {address} (socket from systemd)')
+        return server
+    if not unix_socket:
+        msg = 'A unix socket path or an inherited socket is required'
+        raise ValueError(msg)
+    # A stale socket file left by a previous run is replaced
+    server = make_server(
+        f'unix://{unix_socket}', 0, app, threaded=True, ssl_context=ssl_context
+    )
+    os.chmod(unix_socket, socket_mode)
+    print(f'🔌 Listening on {unix_socket} (mode {socket_mode:o})')
+    return server
@@ -1,8 +1,30 @@
+import csv
+import hashlib
+import html
+import io
+import ipaddress
+import json
+import math
 import os
+import queue
+import re
+import secrets
+import signal
+import socket
+import sqlite3
+import ssl
+import struct
 import subprocess  # nosec B404 # Safe subprocess usage for git commands
+import threading
 import time
-from datetime import datetime, timezone
+import unicodedata
+from collections import deque
+from collections.abc import Iterator, Mapping
+from concurrent.futures import ThreadPoolExecutor
+from datetime import date, datetime, timedelta, timezone
+from functools import wraps
 from typing import Any
+from urllib.parse import quote, unquote, urlencode
 
 
 try:
@@ -17,36 +39,184 @@ from flask import (
     Flask,
     Response,
     abort,
+    g,
     jsonify,
+    redirect,
     render_template,
     request,
     send_from_directory,
+    stream_with_context,
 )
 from flask_compress import Compress
-from flask_socketio import SocketIO, emit
-
-from weather_providers import (
+from flask_socketio import SocketIO, emit, join_room, leave_room
+from itsdangerous import BadSignature, URLSafeSerializer
+from markupsafe import Markup
+from werkzeug.exceptions import InternalServerError, RequestEntityTooLarge
+
+from airports import AIRPORTS_PATH, AirportIndex
+from analytics import UsageAnalytics, device_class
+from assets import AssetManifest
+from astro import assess_visibility, event_time, load_astro_events
+from backup import ARCHIVE_SUFFIXES, create_backup, state_paths
+from charts import build_charts
+from cities import DEFAULT_CITIES, CityRegistry, normalize_city_name
+from config import (
+    DEFAULT_CONFIG_PATH,
+    ConfigFile,
+    load_config_file,
+    load_tls_context,
+    reload_config_file,
+)
+from cron import CronSchedule
+from eink import DEFAULT_HEIGHT, DEFAULT_WIDTH, MAX_SIZE, MIN_SIZE, render_forecast
+from errors import (
+    Conflict,
+    Forbidden,
+    InvalidLocation,
+    InvalidParameter,
+    LocationNotFound,
+    NotFound,
+    PayloadTooLarge,
+    ProviderFailed,
+    ProviderNotConfigured,
+    RateLimited,
+    Unauthorized,
+    UpstreamUnavailable,
+    WeatherError,
+)
+from geoip import IpLocator, public_ip
+from history import (
+    EXPORT_TABLES,
+    SECONDS_PER_DAY,
+    SECONDS_PER_HOUR,
+    HistoryStore,
+    compare_to_normal,
+    daily_from_hourly,
+    day_normal,
+    hourly_from_observations,
+    month_climatology,
+    score_forecasts,
+    solar_day,
+    verify_day,
+)
+from i18n import describe_wind, resolve_language
+from icons import (
+    DEFAULT_ICON_SET,
+    ICON_SETS,
+    WEATHER_ICON_CODES,
+    IconSet,
+    resolve_icon_set,
+)
+from influx import InfluxWriter, line_protocol
+from limits import RequestLimits
+from listeners import make_socket_server, systemd_listen_fd
+from mqtt import MqttError, MqttPublisher
+from narrative import DEFAULT_NARRATIVE_STYLE, NARRATIVE_STYLES, build_narrative
+from notifications import (
+    ChannelLimits,
+    DiscordChannel,
+    EmailChannel,
+    NotificationDispatcher,
+    NotificationRules,
+    NtfyChannel,
+    PushoverChannel,
+    SlackChannel,
+    build_alert_notification,
+    build_digest,
+    parse_quiet_hours,
+)
+from parquet import write_parquet
+from postal import PostalIndex, valid_postal_query
+from prefs import PREF_CHOICES, PREF_DEFAULTS, label_hours, validate_prefs
+from proxies import (
+    DEFAULT_TRUSTED_PROXIES,
+    PEER_ADDR_KEY,
+    UNIX_SOCKET_PEER,
+    ProxyHeadersMiddleware,
+    TrustedProxies,
+)
+from pwa import build_manifest, shell_urls, weather_icon_urls
+from ratelimit import RateLimiter
+from realtime import SubscriptionManager, location_key
+from requestlog import REQUEST_ID_HEADER, access_log_line, request_id
+from schema import (
+    API_FIELD_RENAMES,
+    API_NAMING_STYLES,
+    DEFAULT_API_NAMING,
+    WEATHER_SCHEMA,
+)
+from station import (
+    STATION_PROVIDER,
+    UPLOAD_KEY_PARAMS,
+    StationNetwork,
+    overlay_current,
+    parse_stations,
+    reading_from_json,
+    reading_from_upload,
+)
+from storage import FavoritesStore
+from themes import resolve_theme, theme_attribute, theme_stylesheet
+from tides import (
+    TIDE_DATUM,
+    fetch_tide_predictions,
+    fetch_tide_stations,
+    nearest_station,
+    upcoming_tides,
+)
+from tokens import TOKEN_SCOPES, TokenStore, parse_api_keys
+from trends import TREND_WINDOWS, build_trends
+from weather_core.cache import CacheBackend, CacheKey, create_cache_backend
+from weather_core.derived import beaufort_force, calculate_pressure_trend
+from weather_core.formatting import hour_label
+from weather_core.geocoding import geocode_cache, geocode_place, geocode_postal_code
+from weather_core.http_client import DEFAULT_RETRY_POLICY
+from weather_core.providers import (
+    MOCK_SCENARIOS,
     AirQualityProvider,
     ClothingRecommendationProvider,
     EnhancedTemperatureTrendProvider,
     FreeRadarProvider,
-    HybridWeatherProvider,
     LunarDataProvider,
+    MarineProvider,
+    MockProvider,
     NationalWeatherServiceProvider,
-    OpenMeteoProvider,
-    PirateWeatherProvider,
+    OpenMeteoMarineProvider,
     SolarDataProvider,
     WeatherProviderManager,
+    extract_weather_section,
+)
+from weather_core.settings import (
+    ConfigError,
+    ProviderSettings,
+    default_provider_settings,
+    load_provider_settings,
+)
+from weather_core.tracing import SPAN_KIND_SERVER, load_otlp_exporter, tracer
+from weather_core.units import UNIT_LABELS, convert_to_metric, convert_value
+from webhooks import (
+    TEMPLATE_FIELDS,
+    WEBHOOK_EVENTS,
+    WebhookSubscriptions,
+    build_event,
+    deliver,
 )
 
 
 load_dotenv()
 
-app = Flask(__name__)
+# weather.toml (or WEATHER_CONFIG) fills in whatever the environment leaves unset,
+# so settings layer file < environment (.env included) < command line
+try:
+    config_file = load_config_file(os.getenv('WEATHER_CONFIG', DEFAULT_CONFIG_PATH))
+except (OSError, ConfigError) as e:
+    print(f'❌ Ignoring config file: {e}')
+    config_file = ConfigFile()
+config_file.apply()
+
+# Static files go through static_files() below, which sets their caching
+app = Flask(__name__, static_folder=None)
 secret_key = os.getenv('SECRET_KEY')
 if not secret_key:
-    import secrets
-
     secret_key = secrets.token_hex(16)
     print(
         'Warning: No SECRET_KEY environment variable set. '
@@ -54,6 +224,11 @@ if not secret_key:
     )
 app.config['SECRET_KEY'] = secret_key
 
+# Static assets are linked with content-hash versions so browsers can cache them
+asset_manifest = AssetManifest(os.path.join(os.path.dirname(__file__), 'static'))
+app.jinja_env.globals['asset_url'] = asset_manifest.url
+prefs_serializer = URLSafeSerializer(secret_key, salt='weather-prefs')
+
 # Enable gzip compression for all responses
 Compress(app)
 
@@ -63,9 +238,309 @@ cors_origins = os.getenv(
 ).split(',')
 socketio = SocketIO(app, cors_allowed_origins=cors_origins)
 
+
+def load_trusted_proxies() -> TrustedProxies:
+    """The proxies (TRUSTED_PROXIES) whose PROXY_HEADER names the client"""
+    return TrustedProxies.parse(
+        os.getenv('TRUSTED_PROXIES', DEFAULT_TRUSTED_PROXIES),
+        os.getenv('PROXY_HEADER', 'x-forwarded-for'),
+    )
+
+
+# Behind a reverse proxy, REMOTE_ADDR is the client's address rather than the
+# proxy's, for rate limits, IP location and logs. Wrapped outside Socket.IO so
+# websocket connections get it too
+try:
+    trusted_proxies = load_trusted_proxies()
+except ValueError as e:
+    print(f'❌ {e} - ignoring forwarding headers')
+    trusted_proxies = TrustedProxies([])
+proxy_headers = ProxyHeadersMiddleware(app.wsgi_app, trusted_proxies)
+
+# Each request gets REQUEST_TIMEOUT seconds before a 504, and at most
+# MAX_CONCURRENT_REQUESTS run at once with the rest shed as 503s (0 turns either
+# off). Request bodies over MAX_BODY_BYTES are refused with a 413
+REQUEST_TIMEOUT = int(os.getenv('REQUEST_TIMEOUT', '30'))
+MAX_CONCURRENT_REQUESTS = int(os.getenv('MAX_CONCURRENT_REQUESTS', '100'))
+app.config['MAX_CONTENT_LENGTH'] = int(os.getenv('MAX_BODY_BYTES', '65536')) or None
+request_limits = RequestLimits(
+    proxy_headers, REQUEST_TIMEOUT or None, MAX_CONCURRENT_REQUESTS or None
+)
+app.wsgi_app = request_limits  # type: ignore[method-assign]
+
 # Weather API: Open-Meteo (free and accurate)
 OPEN_METEO_BASE_URL = 'https://api.open-meteo.com/v1/forecast'
 
+# Archive API: Open-Meteo (reanalysis back to 1940, a few days behind)
+OPEN_METEO_ARCHIVE_URL = 'https://archive-api.open-meteo.com/v1/archive'
+
+# API tokens: several admin/read tokens can be valid at once so a leaked one is
+# rotated out gradually. ADMIN_TOKEN bootstraps token management; read tokens
+# are only enforced on /api/ when REQUIRE_READ_TOKEN=1
+ADMIN_TOKEN = os.getenv('ADMIN_TOKEN', '').strip()
+REQUIRE_READ_TOKEN = os.getenv('REQUIRE_READ_TOKEN', '0') == '1'
+TOKEN_COOKIE = 'weather_token'
+TOKEN_COOKIE_MAX_AGE = 365 * 86400
+token_store = TokenStore(os.getenv('TOKEN_STORE_PATH', 'data/tokens.json'))
+
+
+def load_api_keys() -> None:
+    """Load the keys set in API_KEYS ([[api_keys]] in the config file)"""
+    try:
+        token_store.configure(parse_api_keys(os.getenv('API_KEYS', '')))
+    except ValueError as e:
+        print(f'❌ Invalid API_KEYS, keys unchanged: {e}')
+
+
+load_api_keys()
+
+# Strict-Transport-Security lifetime when serving HTTPS directly (TLS_CERT_FILE)
+HSTS_MAX_AGE_SECONDS = 180 * 86400
+
+# One JSON line per request on stdout (ACCESS_LOG=0 turns it off); every
+# response carries its X-Request-Id either way
+ACCESS_LOG = os.getenv('ACCESS_LOG', '1') == '1'
+
+# Traces of requests, cache lookups and provider calls go to an OTLP/HTTP
+# collector when OTEL_EXPORTER_OTLP_ENDPOINT (or ..._TRACES_ENDPOINT) is set
+tracer.exporter = load_otlp_exporter(os.environ)
+if tracer.exporter is not None:
+    print(f'🔭 Exporting traces to {tracer.exporter.endpoint}')
+
+# Per-client rate limit on /api/: RATE_LIMIT_PER_MINUTE requests a minute (unset
+# or 0 is off) in bursts of up to RATE_LIMIT_BURST (default the same number)
+RATE_LIMIT_PER_MINUTE = int(os.getenv('RATE_LIMIT_PER_MINUTE', '0'))
+rate_limiter = (
+    RateLimiter(RATE_LIMIT_PER_MINUTE, int(os.getenv('RATE_LIMIT_BURST', '0')))
+    if RATE_LIMIT_PER_MINUTE > 0
+    else None
+)
+
+# Opt-in usage analytics: aggregate counts per endpoint, location and device
+# class (no IPs), stored locally and served at /api/admin/analytics
+ANALYTICS_ENABLED = os.getenv('ANALYTICS_ENABLED', '0') == '1'
+ANALYTICS_STALE_DAYS = 30
+usage_analytics = (
+    UsageAnalytics(os.getenv('ANALYTICS_PATH', 'data/analytics.json'))
+    if ANALYTICS_ENABLED
+    else None
+)
+
+# Optional InfluxDB v2 sink: every fetched observation is written in batches
+# to INFLUXDB_BUCKET as the 'weather' measurement
+INFLUXDB_URL = os.getenv('INFLUXDB_URL', '').strip()
+influx_writer = (
+    InfluxWriter(
+        INFLUXDB_URL,
+        os.getenv('INFLUXDB_ORG', ''),
+        os.getenv('INFLUXDB_BUCKET', 'weather'),
+        os.getenv('INFLUXDB_TOKEN', ''),
+        flush_interval=float(os.getenv('INFLUXDB_FLUSH_SECONDS', '10')),
+    )
+    if INFLUXDB_URL
+    else None
+)
+
+# Optional MQTT publishing with Home Assistant discovery: current conditions
+# and forecasts go to <MQTT_TOPIC_PREFIX>/<location>/current|forecast
+MQTT_HOST = os.getenv('MQTT_HOST', '').strip()
+mqtt_publisher = (
+    MqttPublisher(
+        MQTT_HOST,
+        int(os.getenv('MQTT_PORT', '1883')),
+        os.getenv('MQTT_USERNAME') or None,
+        os.getenv('MQTT_PASSWORD') or None,
+        os.getenv('MQTT_TOPIC_PREFIX', 'weather-dashboard'),
+        os.getenv('MQTT_DISCOVERY_PREFIX', 'homeassistant'),
+    )
+    if MQTT_HOST
+    else None
+)
+
+# Notification rules ("rain chance over 60% tomorrow"), checked by a background
+# loop against fresh weather and sent to the rule's channels when they trip
+NOTIFICATION_CHECK_SECONDS = 300
+notification_rules = NotificationRules(
+    os.getenv('NOTIFICATION_RULES_PATH', 'data/notification_rules.json')
+)
+notification_dispatcher = NotificationDispatcher()
+
+# Webhook subscriptions, checked by the same loop: each POSTs when its location's
+# temperature swings past its delta or precipitation starts or stops
+webhook_subscriptions = WebhookSubscriptions(
+    os.getenv('WEBHOOKS_PATH', 'data/webhooks.json')
+)
+
+# Saved favorite locations (SQLite), kept warm in the cache by a background loop
+# and used for digests when there are any
+favorites_store = FavoritesStore(os.getenv('FAVORITES_DB_PATH', 'data/favorites.db'))
+
+# Every fetched current-conditions snapshot is kept (SQLite) for history, trends
+# and provider scoring. Each night (HISTORY_COMPACT_CRON, UTC) those older than
+# HISTORY_RETENTION_DAYS are compacted into daily summaries, which are kept for
+# HISTORY_DAILY_RETENTION_DAYS (0 keeps either forever)
+HISTORY_COMPACT_CRON = os.getenv('HISTORY_COMPACT_CRON', '30 3 * * *').strip()
+HISTORY_COMPACT_CHECK_SECONDS = 60
+history_store = HistoryStore(
+    os.getenv('HISTORY_DB_PATH', 'data/history.db'),
+    int(os.getenv('HISTORY_RETENTION_DAYS', '90')),
+    int(os.getenv('HISTORY_DAILY_RETENTION_DAYS', '0')),
+)
+
+# POST /api/admin/backup archives the state files above (see backup.py) into
+# BACKUP_DIR, compressed as BACKUP_FORMAT
+BACKUP_DIR = os.getenv('BACKUP_DIR', 'data/backups')
+BACKUP_FORMAT = os.getenv('BACKUP_FORMAT', '.tar.gz').strip()
+backup_lock = threading.Lock()
+
+# Personal weather stations (STATIONS) upload into the history; those set to
+# override_current replace the current conditions for places near them
+STATION_UPLOAD_PATH = '/api/station/observations'
+station_network = StationNetwork()
+
+
+def load_stations() -> None:
+    """Load the stations set in STATIONS ([[stations]] in the config file)"""
+    try:
+        station_network.configure(parse_stations(os.getenv('STATIONS', '')))
+    except ValueError as e:
+        print(f'❌ Invalid STATIONS, stations unchanged: {e}')
+
+
+load_stations()
+
+# Prefetch: favorites plus PREFETCH_LOCATIONS (';'-separated city keys, places or
+# lat,lon) are refetched shortly before their cache entries expire, so visitors
+# always get a hit. Jitter spreads the refreshes so they don't all land together
+PREFETCH_LOCATIONS = [
+    entry.strip()
+    for entry in os.getenv('PREFETCH_LOCATIONS', '').split(';')
+    if entry.strip()
+]
+PREFETCH_LEAD_SECONDS = 30  # Refresh this long before an entry would expire
+PREFETCH_JITTER_SECONDS = 20  # Up to this much earlier, at random
+PREFETCH_MIN_SLEEP_SECONDS = 5
+PREFETCH_MAX_SLEEP_SECONDS = 60  # New favorites get picked up within a minute
+prefetch_status: dict[str, Any] = {'running': False, 'last_run': None, 'next_run': None}
+
+# Push channels share a timezone for their PREFIX_QUIET_HOURS (e.g. 22:00-07:00)
+# and resend the same rule or alert at most every NOTIFICATION_REPEAT_MINUTES,
+# with an optional PREFIX_MAX_PER_HOUR cap per channel
+NOTIFICATION_TIMEZONE = zoneinfo.ZoneInfo(
+    os.getenv('NOTIFICATION_TIMEZONE', 'America/Chicago')
+)
+NOTIFICATION_REPEAT_MINUTES = float(os.getenv('NOTIFICATION_REPEAT_MINUTES', '60'))
+
+
+def channel_limits(prefix: str) -> ChannelLimits:
+    """Quiet hours and rate limits for a channel from its PREFIX_* settings"""
+    try:
+        quiet_hours = parse_quiet_hours(os.getenv(f'{prefix}_QUIET_HOURS'))
+    except ValueError as e:
+        print(f'⚠️  Ignoring {prefix}_QUIET_HOURS: {e}')
+        quiet_hours = None
+    max_per_hour = os.getenv(f'{prefix}_MAX_PER_HOUR')
+    return ChannelLimits(
+        quiet_hours,
+        NOTIFICATION_TIMEZONE,
+        int(max_per_hour) if max_per_hour else None,
+        NOTIFICATION_REPEAT_MINUTES,
+    )
+
+
+# Optional ntfy push channel (ntfy.sh or self-hosted), named 'ntfy' in rules
+NTFY_TOPIC = os.getenv('NTFY_TOPIC', '').strip()
+if NTFY_TOPIC:
+    notification_dispatcher.register(
+        'ntfy',
+        NtfyChannel(
+            NTFY_TOPIC,
+            os.getenv('NTFY_URL', 'https://ntfy.sh'),
+            os.getenv('NTFY_TOKEN') or None,
+            os.getenv('NTFY_USERNAME') or None,
+            os.getenv('NTFY_PASSWORD') or None,
+        ),
+        channel_limits('NTFY'),
+    )
+
+# Optional Slack/Discord incoming webhooks, named 'slack' and 'discord' in rules
+SLACK_WEBHOOK_URL = os.getenv('SLACK_WEBHOOK_URL', '').strip()
+if SLACK_WEBHOOK_URL:
+    notification_dispatcher.register(
+        'slack', SlackChannel(SLACK_WEBHOOK_URL), channel_limits('SLACK')
+    )
+DISCORD_WEBHOOK_URL = os.getenv('DISCORD_WEBHOOK_URL', '').strip()
+if DISCORD_WEBHOOK_URL:
+    notification_dispatcher.register(
+        'discord', DiscordChannel(DISCORD_WEBHOOK_URL), channel_limits('DISCORD')
+    )
+
+# Optional Pushover channel, named 'pushover' in rules
+PUSHOVER_APP_TOKEN = os.getenv('PUSHOVER_APP_TOKEN', '').strip()
+if PUSHOVER_APP_TOKEN:
+    notification_dispatcher.register(
+        'pushover',
+        PushoverChannel(PUSHOVER_APP_TOKEN, os.getenv('PUSHOVER_USER_KEY', '')),
+        channel_limits('PUSHOVER'),
+    )
+
+# Optional email channel over SMTP, named 'email' in rules
+SMTP_HOST = os.getenv('SMTP_HOST', '').strip()
+if SMTP_HOST:
+    notification_dispatcher.register(
+        'email',
+        EmailChannel(
+            SMTP_HOST,
+            int(os.getenv('SMTP_PORT', '587')),
+            os.getenv('SMTP_FROM', 'weather-dashboard@localhost'),
+            [
+                address.strip()
+                for address in os.getenv('NOTIFICATION_EMAIL_TO', '').split(',')
+                if address.strip()
+            ],
+            os.getenv('SMTP_USERNAME') or None,
+            os.getenv('SMTP_PASSWORD') or None,
+        ),
+        channel_limits('EMAIL'),
+    )
+
+# Morning digest: DIGEST_CRON (e.g. '30 6 * * *', in NOTIFICATION_TIMEZONE)
+# sends each DIGEST_LOCATIONS entry (';'-separated city keys or lat,lon) to
+# DIGEST_CHANNELS
+DIGEST_CRON = os.getenv('DIGEST_CRON', '').strip()
+DIGEST_LOCATIONS = [
+    entry.strip()
+    for entry in os.getenv('DIGEST_LOCATIONS', 'chicago').split(';')
+    if entry.strip()
+]
+DIGEST_CHANNELS = [
+    name.strip()
+    for name in os.getenv('DIGEST_CHANNELS', 'log').split(',')
+    if name.strip()
+]
+DIGEST_CHECK_SECONDS = 60
+
+# Channels that newly issued NWS alerts are pushed to as they're first seen
+ALERT_NOTIFICATION_CHANNELS = [
+    name.strip()
+    for name in os.getenv('ALERT_NOTIFICATION_CHANNELS', '').split(',')
+    if name.strip()
+]
+
+# Optional startup clock check against NTP - skewed clocks (common on Pi
+# deployments without an RTC) silently break past-hour filtering and cache TTLs
+NTP_SERVER = os.getenv('NTP_SERVER', '').strip()
+NTP_PORT = 123
+NTP_TIMEOUT_SECONDS = 2
+NTP_EPOCH_OFFSET = 2208988800  # Seconds from 1900-01-01 (NTP) to 1970-01-01 (Unix)
+NTP_PACKET_SIZE = 48
+CLOCK_SKEW_WARNING_SECONDS = float(os.getenv('CLOCK_SKEW_WARNING_SECONDS', '5'))
+clock_status: dict[str, Any] = {
+    'server': None,
+    'skew_seconds': None,
+    'checked_at': None,
+}
+
 # Chicago coordinates
 CHICAGO_LAT = 41.8781
 CHICAGO_LON = -87.6298
@@ -76,8 +551,63 @@ MAX_LATITUDE = 90
 MIN_LONGITUDE = -180
 MAX_LONGITUDE = 180
 
-# Cache for weather API responses (3 minutes TTL for real-time updates, max 100 entries)
-weather_cache: TTLCache[str, Any] = TTLCache(maxsize=100, ttl=180)
+# Where the weather API goes when a request has no usable location: the
+# profile default (a city or lat,lon cookie the dashboard sets), then the
+# instance default, then an explicit 400. An empty DEFAULT_LOCATION disables
+# the instance step.
+DEFAULT_LOCATION = os.getenv('DEFAULT_LOCATION', 'chicago').strip()
+PROFILE_LOCATION_COOKIE = 'weather_default_location'
+
+# Visitor preferences (units, 12/24-hour clock, home location, theme) live in a
+# cookie signed with SECRET_KEY; the home location is the profile default above
+PREFS_COOKIE = 'weather_prefs'
+PREFS_COOKIE_MAX_AGE = 365 * 86400
+
+# Icon pack for pages and the JSON icon_set block; ?icons= overrides per request
+ICON_SET = resolve_icon_set(os.getenv('ICON_SET')) or DEFAULT_ICON_SET
+
+# Cache for weather API responses (CACHE_MAXSIZE entries, default 100). Each entry
+# lives as long as its provider's cache_ttl, capped at an hour; CACHE_TTL seconds
+# (default 3 minutes) for providers without one. In-process by default; set
+# REDIS_URL so replicas behind a load balancer share one
+REDIS_URL = os.getenv('REDIS_URL', '').strip() or None
+WEATHER_CACHE_TTL = int(os.getenv('CACHE_TTL', '180'))
+WEATHER_CACHE_MAX_TTL = 3600
+WEATHER_CACHE_MAXSIZE = int(os.getenv('CACHE_MAXSIZE', '100'))
+weather_cache: CacheBackend = create_cache_backend(
+    REDIS_URL,
+    'weather',
+    maxsize=WEATHER_CACHE_MAXSIZE,
+    ttl=WEATHER_CACHE_TTL,
+    max_ttl=WEATHER_CACHE_MAX_TTL,
+)
+
+# Entries are keyed by CacheKey: location plus the provider that was primary when
+# it was fetched (so a provider switch refreshes entries lazily instead of
+# purging the whole cache) and the units and language the document is stored in.
+# Documents stay in provider units and English; responses convert on the way out
+CACHED_UNITS = 'imperial'
+CACHED_LANGUAGE = 'en'
+
+# Provenance of each weather entry, under the same key and TTL: its provider, when
+# it was fetched and its TTL (for validators and Age/max-age headers)
+weather_cache_metadata: CacheBackend = create_cache_backend(
+    REDIS_URL,
+    'weather-metadata',
+    maxsize=WEATHER_CACHE_MAXSIZE,
+    ttl=WEATHER_CACHE_TTL,
+    max_ttl=WEATHER_CACHE_MAX_TTL,
+)
+weather_refreshes_in_flight: set[str] = set()
+
+# Per-section caches for clients refreshing sections at their own pace - each
+# entry is (fetched_at, section document)
+WEATHER_SECTION_TTLS = {'current': 300, 'hourly': 1800, 'daily': 3600}
+weather_section_caches: dict[str, TTLCache[str, tuple[float, dict]]] = {
+    section: TTLCache(maxsize=WEATHER_CACHE_MAXSIZE, ttl=ttl)
+    for section, ttl in WEATHER_SECTION_TTLS.items()
+}
+weather_refresh_lock = threading.Lock()
 
 # Cache for weather alerts (5 minutes TTL - alerts change less frequently)
 alerts_cache: TTLCache[str, Any] = TTLCache(maxsize=50, ttl=300)
@@ -94,19 +624,100 @@ solar_cache: TTLCache[str, Any] = TTLCache(maxsize=50, ttl=3600)
 # Cache for lunar data (4 hours TTL - moon data changes slowly)
 lunar_cache: TTLCache[str, Any] = TTLCache(maxsize=50, ttl=14400)
 
+# Cache for hourly cloud cover used to rate astronomical events (1 hour TTL)
+cloud_cover_cache: TTLCache[str, Any] = TTLCache(maxsize=50, ttl=3600)
+
 # Cache for temperature trends (15 minutes TTL - trend analysis is computation-heavy)
 temperature_trends_cache: TTLCache[str, Any] = TTLCache(maxsize=50, ttl=900)
 
+# Cache for binned weather history (30 minutes TTL - past hours rarely change)
+history_cache: TTLCache[str, Any] = TTLCache(maxsize=50, ttl=1800)
+
+# Client IP geolocation for ?auto=1 and first visits to /: a MaxMind-format
+# database (GEOIP_DATABASE, e.g. GeoLite2-City.mmdb) and/or a JSON lookup
+# service (GEOIP_SERVICE_URL with an {ip} placeholder)
+GEOIP_DATABASE = os.getenv('GEOIP_DATABASE', '').strip()
+GEOIP_SERVICE_URL = os.getenv('GEOIP_SERVICE_URL', '').strip()
+ip_locator = (
+    IpLocator(GEOIP_DATABASE or None, GEOIP_SERVICE_URL or None)
+    if GEOIP_DATABASE or GEOIP_SERVICE_URL
+    else None
+)
+geoip_cache: TTLCache[str, Any] = TTLCache(maxsize=1000, ttl=86400)
+
+# Airports for /airport/<code>: bundled major airports, or an OurAirports
+# airports.csv at AIRPORTS_PATH for full ICAO/IATA coverage
+airport_index = AirportIndex(os.getenv('AIRPORTS_PATH') or AIRPORTS_PATH)
+
+# Postal codes for /zip/<code> and ?postal=&country=: GeoNames postal code dumps
+# (one file or a directory of country files) at POSTAL_CODES_PATH, with the
+# geocoder covering countries that aren't loaded
+POSTAL_DEFAULT_COUNTRY = os.getenv('POSTAL_DEFAULT_COUNTRY', 'US').strip().upper()
+postal_index = PostalIndex(os.getenv('POSTAL_CODES_PATH', 'data/postal_codes'))
+
+# Air quality changes slowly (30 minutes per cached entry)
+AIR_QUALITY_CACHE_TTL = 1800
+air_quality_cache: CacheBackend = create_cache_backend(
+    REDIS_URL, 'air-quality', maxsize=100, ttl=AIR_QUALITY_CACHE_TTL
+)
+
+# Sea state forecasts for /api/marine (30 minutes - models update a few times a day)
+MARINE_CACHE_TTL = 1800
+marine_cache: TTLCache[str, Any] = TTLCache(maxsize=100, ttl=MARINE_CACHE_TTL)
+
+# Tide predictions are astronomical, so they're cached for hours; the NOAA
+# station list changes even less often
+TIDE_CACHE_TTL = 6 * 3600
+tide_cache: TTLCache[str, Any] = TTLCache(maxsize=200, ttl=TIDE_CACHE_TTL)
+tide_station_cache: TTLCache[str, Any] = TTLCache(maxsize=1, ttl=86400)
+
+# Readiness probe results per provider (1 minute TTL - keeps /readyz cheap to poll)
+PROVIDER_PROBE_TTL = 60
+provider_probe_cache: TTLCache[str, dict[str, Any]] = TTLCache(
+    maxsize=20, ttl=PROVIDER_PROBE_TTL
+)
+
+# Server-Sent Events subscribers, keyed by weather cache key
+WEATHER_STREAM_KEEPALIVE_SECONDS = 15
+weather_stream_subscribers: dict[str, list[queue.Queue]] = {}
+weather_stream_lock = threading.Lock()
+
+# Significant changes per location (alerts, temperature swings, precipitation),
+# kept newest-last for the Atom feeds
+CHANGE_HISTORY_SIZE = 50
+TEMPERATURE_SWING_THRESHOLD = 10  # °F away from the last reported temperature
+weather_change_history: dict[str, deque[dict]] = {}
+weather_change_baselines: dict[str, dict[str, Any]] = {}
+weather_change_lock = threading.Lock()
+
+# WebSocket live-update channel: clients subscribe to locations in this namespace
+REALTIME_NAMESPACE = '/ws'
+realtime_subscriptions = SubscriptionManager()
+
 # Initialize weather provider manager
 weather_manager = WeatherProviderManager()
 
+# Latency budget per weather request, off unless set: providers are then tried
+# one at a time; with it, fallbacks are raced when the primary is slow
+weather_manager.latency_budget = float(os.getenv('WEATHER_LATENCY_BUDGET', '0')) or None
+weather_manager.hedge_delay = float(os.getenv('WEATHER_HEDGE_DELAY', '1'))
+
+# Retries for provider requests that time out or get a 5xx reply
+DEFAULT_RETRY_POLICY.max_attempts = max(1, int(os.getenv('HTTP_RETRY_ATTEMPTS', '3')))
+DEFAULT_RETRY_POLICY.base_delay = float(os.getenv('HTTP_RETRY_BASE_DELAY', '0.5'))
+DEFAULT_RETRY_POLICY.max_delay = float(os.getenv('HTTP_RETRY_MAX_DELAY', '4'))
+DEFAULT_RETRY_POLICY.jitter = float(os.getenv('HTTP_RETRY_JITTER', '0.5'))
+
+# How often idle and failing providers get a health probe
+weather_manager.health_interval = float(os.getenv('PROVIDER_HEALTH_INTERVAL', '60'))
+
 # Initialize individual providers
-open_meteo = OpenMeteoProvider()
 nws_provider = NationalWeatherServiceProvider()
 clothing_provider = ClothingRecommendationProvider()
 solar_provider = SolarDataProvider()
 lunar_provider = LunarDataProvider()
 temperature_trends_provider = EnhancedTemperatureTrendProvider()
+marine_provider: MarineProvider = OpenMeteoMarineProvider()
 
 # Initialize EPA AirNow air quality provider (API key required)
 airnow_api_key = os.getenv('AIRNOW_API_KEY')
@@ -125,24 +736,19 @@ except Exception as e:
     radar_provider = None
     print(f'🌧️ Failed to initialize radar provider: {e}')
 
-# Check for PirateWeather API key and create hybrid provider if available
-pirate_weather_api_key = os.getenv('PIRATE_WEATHER_API_KEY', 'YOUR_API_KEY_HERE')
-
-if pirate_weather_api_key and pirate_weather_api_key != 'YOUR_API_KEY_HERE':
-    print('🏴‍☠️ PirateWeather API key found - creating hybrid provider')
-    pirate_weather = PirateWeatherProvider(pirate_weather_api_key)
-    hybrid_provider = HybridWeatherProvider(pirate_weather, open_meteo)
-
-    # Set up hybrid with fallbacks
-    weather_manager.add_provider(hybrid_provider, is_primary=True)
-    weather_manager.add_provider(open_meteo, is_primary=False)  # Fallback
-    weather_manager.add_provider(pirate_weather, is_primary=False)  # Secondary fallback
-
-    print('✨ Using Hybrid Provider (PirateWeather current + OpenMeteo forecasts)')
-    print('🔄 Fallbacks: OpenMeteo → PirateWeather')
-else:
-    print('🌤️  No PirateWeather API key - using OpenMeteo only')
-    weather_manager.add_provider(open_meteo, is_primary=True)
+# Weather providers in priority order: the first enabled one is primary and the
+# rest are fallbacks. PROVIDERS_CONFIG_PATH lists them with per-provider timeouts,
+# keys and quotas; without that file they follow PIRATE_WEATHER_API_KEY,
+# REMOTE_DASHBOARD_URL, BLEND_PROVIDERS and PROVIDER_QUOTAS
+try:
+    provider_settings = load_provider_settings(
+        os.getenv('PROVIDERS_CONFIG_PATH', 'data/providers.json'),
+        entries=config_file.providers,
+    )
+except (OSError, ValueError) as e:
+    print(f'❌ Invalid provider config ({e}) - using environment settings')
+    provider_settings = default_provider_settings()
+weather_manager.configure(provider_settings)
 
 
 def get_git_hash() -> str:
@@ -163,6 +769,531 @@ def get_git_hash() -> str:
     return 'unknown'
 
 
+def subscribe_weather_stream(cache_key: str) -> queue.Queue:
+    """Register a subscriber queue for weather updates at a location"""
+    subscriber: queue.Queue = queue.Queue(maxsize=10)
+    with weather_stream_lock:
+        weather_stream_subscribers.setdefault(cache_key, []).append(subscriber)
+    return subscriber
+
+
+def unsubscribe_weather_stream(cache_key: str, subscriber: queue.Queue) -> None:
+    """Remove a subscriber queue once its stream closes"""
+    with weather_stream_lock:
+        subscribers = weather_stream_subscribers.get(cache_key, [])
+        if subscriber in subscribers:
+            subscribers.remove(subscriber)
+        if not subscribers:
+            weather_stream_subscribers.pop(cache_key, None)
+
+
+def publish_weather_update(cache_key: str, weather_data: dict) -> None:
+    """Push fresh weather data to every stream subscribed to this location"""
+    with weather_stream_lock:
+        subscribers = list(weather_stream_subscribers.get(cache_key, []))
+
+    for subscriber in subscribers:
+        try:
+            subscriber.put_nowait(weather_data)
+        except queue.Full:
+            # Slow client - drop the oldest update so the newest one gets through
+            try:
+                subscriber.get_nowait()
+                subscriber.put_nowait(weather_data)
+            except (queue.Empty, queue.Full):
+                pass
+
+
+def store_weather_data(
+    cache_key: str, weather_data: dict, provider_name: str | None = None
+) -> None:
+    """Cache fresh weather data and notify any live stream subscribers"""
+    entry_key = weather_cache_key(cache_key, provider_name)
+    # The provider that actually answered decides, in case a fallback did
+    ttl = min(
+        weather_manager.cache_ttl(weather_data.get('provider'), entry_key.provider)
+        or WEATHER_CACHE_TTL,
+        WEATHER_CACHE_MAX_TTL,
+    )
+    with tracer.span('cache.set', {'cache.key': cache_key, 'cache.ttl': ttl}):
+        weather_cache.set(entry_key, weather_data, ttl)
+        weather_cache_metadata.set(
+            entry_key,
+            {'provider': entry_key.provider, 'fetched_at': time.time(), 'ttl': ttl},
+            ttl,
+        )
+    publish_weather_update(cache_key, weather_data)
+    broadcast_realtime_patch(cache_key, 'current', weather_data.get('current', {}))
+    detect_weather_changes(cache_key, weather_data)
+    if influx_writer is not None:
+        influx_writer.write(
+            observation_line(cache_key, weather_data, entry_key.provider)
+        )
+    record_history(cache_key, weather_data, entry_key.provider)
+    if mqtt_publisher is not None:
+        socketio.start_background_task(
+            publish_mqtt_weather,
+            str(weather_data.get('location', cache_key)),
+            serialize_weather(weather_data),
+        )
+
+
+def publish_mqtt_weather(location: str, weather_data: dict) -> None:
+    """Publish weather to MQTT, logging rather than raising on broker trouble"""
+    if mqtt_publisher is None:
+        return
+    try:
+        mqtt_publisher.publish_weather(location, weather_data)
+    except (OSError, MqttError) as e:
+        print(f'❌ MQTT publish failed for {location}: {str(e)}')
+
+
+def record_history(cache_key: str, weather_data: dict, provider: str) -> None:
+    """Keep current conditions and tomorrow's forecast, logging rather than raising"""
+    provider = weather_data.get('provider') or provider
+    current = weather_data.get('current')
+    daily = weather_data.get('daily')
+    lat, lon = (float(value) for value in cache_key.split(','))
+    try:
+        if isinstance(current, dict):
+            history_store.record(cache_key, lat, lon, provider, current)
+        # The first day is today; the next one is scored for next-day accuracy
+        if isinstance(daily, list) and len(daily) > 1 and 'date' in daily[1]:
+            history_store.record_forecast(cache_key, provider, daily[1])
+    except sqlite3.Error as e:
+        print(f'❌ Failed to record history for {cache_key}: {str(e)}')
+
+
+def observation_line(cache_key: str, weather_data: dict, provider: str) -> str | None:
+    """Format current conditions as an InfluxDB line-protocol point"""
+    current = weather_data.get('current', {})
+    lat, lon = cache_key.split(',')
+    return line_protocol(
+        'weather',
+        {
+            'location': str(weather_data.get('location', cache_key)),
+            'lat': lat,
+            'lon': lon,
+            'provider': provider,
+        },
+        {field: current.get(field) for _, _, field in WEATHER_GAUGES},
+        int(time.time()),
+    )
+
+
+def store_alerts_data(lat: float, lon: float, alerts_data: dict) -> None:
+    """Cache fresh alerts and notify live subscribers and change feeds"""
+    cache_key = location_key(lat, lon)
+    alerts_cache[f'alerts_{cache_key}'] = alerts_data
+    broadcast_realtime_patch(cache_key, 'alerts', alerts_by_id(alerts_data))
+    detect_new_alerts(cache_key, alerts_data)
+
+
+def alerts_by_id(alerts_data: dict) -> dict[str, dict]:
+    """Key active alerts by id so new and expired alerts diff cleanly"""
+    alerts = alerts_data.get('alerts', {}).get('alerts', [])
+    return {
+        str(alert.get('id') or index): alert for index, alert in enumerate(alerts)
+    }
+
+
+def broadcast_realtime_patch(cache_key: str, section: str, value: Any) -> None:
+    """Send a JSON patch to /ws clients subscribed to this location"""
+    value = serialize_weather({section: value})[section]
+    patch = realtime_subscriptions.update_snapshot(cache_key, section, value)
+    if patch:
+        socketio.emit(
+            'weather_patch',
+            {'location': cache_key, 'patch': patch},
+            to=cache_key,
+            namespace=REALTIME_NAMESPACE,
+        )
+
+
+def record_weather_change(cache_key: str, kind: str, title: str, summary: str) -> None:
+    """Append a significant change to a location's feed history"""
+    now = datetime.now(timezone.utc)
+    entry = {
+        'id': f'urn:weather-dashboard:{cache_key}:{kind}:{int(now.timestamp() * 1000)}',
+        'kind': kind,
+        'title': title,
+        'summary': summary,
+        'updated': now.isoformat(),
+    }
+    with weather_change_lock:
+        history = weather_change_history.setdefault(
+            cache_key, deque(maxlen=CHANGE_HISTORY_SIZE)
+        )
+        history.append(entry)
+    print(f'📰 {cache_key}: {title}')
+
+
+def detect_weather_changes(cache_key: str, weather_data: dict) -> None:
+    """Record temperature swings and precipitation starting at a location"""
+    current = weather_data.get('current', {})
+    temperature = current.get('temperature')
+    precipitating = bool(current.get('precipitation_rate'))
+
+    with weather_change_lock:
+        baseline = weather_change_baselines.setdefault(cache_key, {})
+        previous_temperature = baseline.get('temperature')
+        was_precipitating = baseline.get('precipitating')
+        baseline['precipitating'] = precipitating
+        # The baseline only moves on a reported swing, so slow drifts add up
+        swing = (
+            temperature is not None
+            and previous_temperature is not None
+            and abs(temperature - previous_temperature) >= TEMPERATURE_SWING_THRESHOLD
+        )
+        if previous_temperature is None or swing:
+            baseline['temperature'] = temperature
+
+    if swing:
+        direction = 'rose' if temperature > previous_temperature else 'fell'
+        record_weather_change(
+            cache_key,
+            'temperature',
+            f'Temperature {direction} to {temperature}°F',
+            f'Temperature {direction} from {previous_temperature}°F '
+            f'to {temperature}°F.',
+        )
+    if precipitating and was_precipitating is False:
+        precipitation_type = current.get('precipitation_type') or 'precipitation'
+        record_weather_change(
+            cache_key,
+            'precipitation',
+            f'{precipitation_type.capitalize()} starting',
+            f'{precipitation_type.capitalize()} started at '
+            f'{current.get("precipitation_rate")} in/hr.',
+        )
+
+
+def detect_new_alerts(cache_key: str, alerts_data: dict) -> None:
+    """Record and push alerts that weren't active the last time alerts were fetched"""
+    active = alerts_by_id(alerts_data)
+    with weather_change_lock:
+        baseline = weather_change_baselines.setdefault(cache_key, {})
+        seen = baseline.get('alert_ids', set())
+        baseline['alert_ids'] = set(active)
+
+    for alert_id, alert in active.items():
+        if alert_id in seen:
+            continue
+        record_weather_change(
+            cache_key,
+            'alert',
+            alert.get('headline') or alert.get('type') or 'Weather alert',
+            alert.get('description') or alert.get('type') or '',
+        )
+        if ALERT_NOTIFICATION_CHANNELS:
+            notification = build_alert_notification(
+                alert, str(alerts_data.get('location_name') or cache_key)
+            )
+            socketio.start_background_task(
+                notification_dispatcher.dispatch,
+                notification,
+                ALERT_NOTIFICATION_CHANNELS,
+            )
+
+
+def weather_cache_key(location: str, provider_name: str | None = None) -> CacheKey:
+    """Key for a 'lat,lon' location's weather from a provider (default: primary)"""
+    lat, lon = (float(value) for value in location.split(','))
+    return CacheKey(
+        lat,
+        lon,
+        provider_name or weather_manager.primary_provider or 'unknown',
+        CACHED_UNITS,
+        CACHED_LANGUAGE,
+    )
+
+
+def cached_weather_key(location: str) -> CacheKey | None:
+    """Key of the entry to serve: the primary provider's, else another provider's"""
+    primary_key = weather_cache_key(location)
+    if primary_key in weather_cache:
+        return primary_key
+    for provider_name in weather_manager.providers:
+        entry_key = weather_cache_key(location, provider_name)
+        if entry_key != primary_key and entry_key in weather_cache:
+            return entry_key
+    return None
+
+
+def refresh_stale_weather(
+    cache_key: str,
+    lat: float,
+    lon: float,
+    location_name: str | None = None,
+    timezone_name: str | None = None,
+) -> None:
+    """Replace a stale cache entry with data from the current provider"""
+    provider_name = weather_manager.primary_provider
+    try:
+        print(f'♻️  Refreshing stale weather for {cache_key} from {provider_name}')
+        weather_data = weather_manager.get_weather(
+            lat, lon, location_name, timezone_name
+        )
+        if weather_data:
+            store_weather_data(cache_key, weather_data, provider_name)
+            # Other providers' entries for the place are superseded now
+            fresh_key = weather_cache_key(cache_key, provider_name)
+            for other_provider in weather_manager.providers:
+                entry_key = weather_cache_key(cache_key, other_provider)
+                if entry_key != fresh_key:
+                    weather_cache.pop(entry_key, None)
+                    weather_cache_metadata.pop(entry_key, None)
+    finally:
+        with weather_refresh_lock:
+            weather_refreshes_in_flight.discard(cache_key)
+
+
+def get_cached_weather(
+    cache_key: str,
+    lat: float,
+    lon: float,
+    location_name: str | None = None,
+    timezone_name: str | None = None,
+) -> dict | None:
+    """Look up cached weather, lazily refreshing entries from an old provider"""
+    with tracer.span('cache.get', {'cache.key': cache_key}) as span:
+        entry_key = cached_weather_key(cache_key)
+        span.set_attribute('cache.hit', entry_key is not None)
+    if entry_key is None:
+        return None
+    cached_data = weather_cache[entry_key]
+
+    if entry_key == weather_cache_key(cache_key):
+        return cached_data  # type: ignore[no-any-return]
+
+    # Cached before a provider switch - serve it flagged as stale while the
+    # new provider's data loads in the background (one refresh per location)
+    with weather_refresh_lock:
+        already_refreshing = cache_key in weather_refreshes_in_flight
+        weather_refreshes_in_flight.add(cache_key)
+    if not already_refreshing:
+        socketio.start_background_task(
+            refresh_stale_weather, cache_key, lat, lon, location_name, timezone_name
+        )
+    return {**cached_data, 'stale': True}
+
+
+def rename_fields(entry: Any, renames: dict[str, str]) -> Any:
+    """Rename the keys of a single section entry, leaving other values alone"""
+    if not isinstance(entry, dict):
+        return entry
+    return {renames.get(key, key): value for key, value in entry.items()}
+
+
+def serialize_weather(
+    weather_data: dict,
+    naming: str = DEFAULT_API_NAMING,
+    language: str | None = None,
+    units: str = 'imperial',
+    time_format: str = '12h',
+) -> dict[str, Any]:
+    """Prepare a weather document for clients: units, naming and server time"""
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    if time_format == '24h' or language:
+        weather_data = label_hours(weather_data, time_format, language)
+    serialized = {
+        **weather_data,
+        'server_time': datetime.now(timezone.utc).isoformat(),
+    }
+    current = weather_data.get('current')
+    if language and current and current.get('wind_speed') is not None:
+        # Localized wind wording is opt-in via ?lang= so the default schema holds
+        weather_data = {
+            **weather_data,
+            'current': {
+                **current,
+                'beaufort': beaufort_force(current['wind_speed']),
+                'wind_description': describe_wind(
+                    current['wind_speed'], current.get('wind_direction'), language
+                ),
+            },
+        }
+        serialized['current'] = weather_data['current']
+    if naming == 'legacy':
+        return serialized
+
+    for section, renames in API_FIELD_RENAMES.items():
+        value = weather_data.get(section)
+        if isinstance(value, list):
+            serialized[section] = [rename_fields(entry, renames) for entry in value]
+        elif value is not None:
+            serialized[section] = rename_fields(value, renames)
+    return serialized
+
+
+def requested_api_naming() -> str:
+    """Get the naming style a request asked for, defaulting to snake_case"""
+    naming = request.args.get('naming', DEFAULT_API_NAMING)
+    return naming if naming in API_NAMING_STYLES else DEFAULT_API_NAMING
+
+
+def requested_language() -> str | None:
+    """Get the ?lang= wind wording was asked for, if it's supported"""
+    return resolve_language(request.args.get('lang'))
+
+
+def saved_prefs() -> tuple[dict[str, Any], int]:
+    """Preferences from the signed cookie and when they were saved (0 if never)"""
+    # The response now depends on the cookie; vary_on_prefs marks it so
+    g.reads_prefs = True
+    cookie = request.cookies.get(PREFS_COOKIE)
+    if not cookie:
+        return {}, 0
+    try:
+        saved = dict(prefs_serializer.loads(cookie))
+        saved_at = saved.pop('saved_at', 0)
+        prefs = validate_prefs(saved)
+    except (BadSignature, TypeError, ValueError):
+        return {}, 0
+    g.prefs_from_cookie = True
+    return prefs, int(saved_at) if isinstance(saved_at, int | float) else 0
+
+
+def visitor_prefs() -> dict[str, Any]:
+    """The visitor's saved preferences over the defaults (unsigned cookies ignored)"""
+    return {**PREF_DEFAULTS, **saved_prefs()[0]}
+
+
+def requested_display() -> tuple[str, str]:
+    """Units and clock for a response: ?units=/?time_format=, else saved prefs"""
+    prefs = visitor_prefs()
+    units = request.args.get('units', prefs['units'])
+    time_format = request.args.get('time_format', prefs['time_format'])
+    if units not in PREF_CHOICES['units']:
+        units = prefs['units']
+    if time_format not in PREF_CHOICES['time_format']:
+        time_format = prefs['time_format']
+    return units, time_format
+
+
+def requested_icon_set() -> IconSet:
+    """The icon pack for a response: ?icons=, else the configured ICON_SET"""
+    return ICON_SETS[resolve_icon_set(request.args.get('icons')) or ICON_SET]
+
+
+def cached_weather_metadata(location: str) -> dict[str, Any] | None:
+    """Provenance of the entry served for a location, if one is cached"""
+    entry_key = cached_weather_key(location)
+    if entry_key is None:
+        return None
+    return weather_cache_metadata.get(entry_key)  # type: ignore[no-any-return]
+
+
+def weather_cache_validators(
+    cache_key: str,
+    location_name: str,
+    naming: str = DEFAULT_API_NAMING,
+    language: str | None = None,
+    fallback: str | None = None,
+    display: str = 'imperial:12h',
+    prefs_saved_at: int = 0,
+) -> tuple[str, datetime] | None:
+    """Build an ETag and Last-Modified time from a cached entry's provenance
+
+    Saving new preferences changes the body without a fetch, so Last-Modified
+    is the later of the fetch and the save.
+    """
+    metadata = cached_weather_metadata(cache_key)
+    if not metadata:
+        return None
+    # The location name, naming style, language, units/clock and any location
+    # fallback shape the body, so they're in the tag
+    fingerprint = (
+        f'{metadata["provider"]}:{metadata["fetched_at"]}:{cache_key}:'
+        f'{location_name}:{naming}:{language}:{fallback}:{display}'
+    )
+    etag = hashlib.sha256(fingerprint.encode()).hexdigest()[:32]
+    modified_at = max(int(metadata['fetched_at']), prefs_saved_at)
+    return etag, datetime.fromtimestamp(modified_at, tz=timezone.utc)
+
+
+def set_weather_cache_headers(response: Response, cache_key: str) -> Response:
+    """Set Cache-Control max-age to the entry's remaining TTL, plus its Age"""
+    metadata = cached_weather_metadata(cache_key)
+    if not metadata:
+        response.headers['Cache-Control'] = f'public, max-age={WEATHER_CACHE_TTL}'
+        return response
+
+    age = max(0, int(time.time() - metadata['fetched_at']))
+    remaining = max(0, metadata.get('ttl', WEATHER_CACHE_TTL) - age)
+    response.headers['Cache-Control'] = f'public, max-age={remaining}'
+    response.headers['Age'] = str(age)
+    return response
+
+
+def conditional_weather_response(
+    weather_data: dict,
+    cache_key: str,
+    location_name: str,
+    location_fallback: dict[str, str | None] | None = None,
+) -> Response:
+    """Respond with weather data, or 304 when the client's copy is current"""
+    g.weather_provider = weather_data.get('provider')
+    overlaid = with_station_current(cache_key, weather_data)
+    # A station's newer reading changes the body without touching the cache
+    station_tag = None
+    if overlaid is not weather_data:
+        weather_data, station_tag = overlaid, overlaid['current']['timestamp']
+    naming = requested_api_naming()
+    language = requested_language()
+    units, time_format = requested_display()
+    icon_set = requested_icon_set()
+    body = serialize_weather(weather_data, naming, language, units, time_format)
+    body['units'] = UNIT_LABELS[units]
+    body['icon_set'] = icon_set.describe(asset_manifest.url)
+    fallback_tag = None
+    if location_fallback:
+        body['location_fallback'] = location_fallback
+        fallback_tag = f'{location_fallback["source"]}:{location_fallback["reason"]}'
+    response = set_weather_cache_headers(
+        with_location_fallback(jsonify(body), location_fallback), cache_key
+    )
+
+    validators = weather_cache_validators(
+        cache_key,
+        location_name,
+        naming,
+        language,
+        fallback_tag,
+        f'{units}:{time_format}:{icon_set.name}:{station_tag}',
+        saved_prefs()[1],
+    )
+    if validators is None:
+        etag_value = hash(cache_key + str(int(time.time() // 300)))
+        response.headers['ETag'] = f'"{etag_value}"'
+        return response
+
+    etag, last_modified = validators
+    # Weak: bodies for the same entry differ only in their server_time stamp
+    response.set_etag(etag, weak=True)
+    response.last_modified = last_modified
+    # Honors If-None-Match and If-Modified-Since, turning matches into a 304
+    return response.make_conditional(request)
+
+
+def with_station_current(cache_key: str, weather_data: dict) -> dict:
+    """Weather with a nearby station's latest reading laid over current, if any"""
+    current = weather_data.get('current')
+    if not isinstance(current, dict):
+        return weather_data
+    lat, lon = (float(value) for value in cache_key.split(','))
+    found = station_network.current_for(lat, lon)
+    if found is None:
+        return weather_data
+    return {**weather_data, 'current': overlay_current(current, *found)}
+
+
+def format_sse_event(event: str, data: dict) -> str:
+    """Format a Server-Sent Events message"""
+    return f'event: {event}\ndata: {json.dumps(data)}\n\n'
+
+
 def get_weather_from_open_meteo(lat: float, lon: float) -> dict | None:
     """Fetch weather data from Open-Meteo API"""
     try:
@@ -232,26 +1363,7 @@ def map_open_meteo_weather_code(code: int) -> str:
 
 def get_weather_icon(icon_code: str) -> str:
     """Return weather icon code for use with weather-icons library"""
-    # Map some common variations to standard icon codes
-    icon_map = {
-        'clear-day': 'clear-day',
-        'clear-night': 'clear-night',
-        'rain': 'rain',
-        'heavy-rain': 'heavy-rain',
-        'light-rain': 'light-rain',
-        'snow': 'snow',
-        'heavy-snow': 'heavy-snow',
-        'light-snow': 'light-snow',
-        'sleet': 'sleet',
-        'wind': 'wind',
-        'fog': 'fog',
-        'cloudy': 'cloudy',
-        'partly-cloudy-day': 'partly-cloudy-day',
-        'partly-cloudy-night': 'partly-cloudy-night',
-        'thunderstorm': 'thunderstorm',
-        'hail': 'hail',
-    }
-    return icon_map.get(icon_code, 'clear-day')
+    return icon_code if icon_code in WEATHER_ICON_CODES else 'clear-day'
 
 
 def process_open_meteo_data(
@@ -302,15 +1414,14 @@ def process_open_meteo_data(
                         if i < len(hourly.get('precipitation_probability', []))
                         else 0
                     ),
-                    't': (
-                        datetime.fromisoformat(hourly['time'][i].replace('Z', '+00:00'))
-                        .astimezone(zoneinfo.ZoneInfo('America/Chicago'))
-                        .strftime('%I%p')
-                        .lower()
-                        .lstrip('0')
+                    't': hour_label(
+                        datetime.fromisoformat(
+                            hourly['time'][i].replace('Z', '+00:00')
+                        ).astimezone(zoneinfo.ZoneInfo('America/Chicago'))
                     ),
                     'desc': get_weather_description(hourly['weather_code'][i]),
                     'pressure': round(pressure_value, 1),
+                    'time': hourly['time'][i],
                 }
                 hourly_forecast.append(hour_data)
                 pressure_history.append(
@@ -326,6 +1437,7 @@ def process_open_meteo_data(
                     'l': round(daily['temperature_2m_min'][i]),
                     'icon': map_open_meteo_weather_code(daily['weather_code'][i]),
                     'd': datetime.fromisoformat(daily['time'][i]).strftime('%a'),
+                    'date': daily['time'][i],
                 }
                 daily_forecast.append(day_data)
 
@@ -345,88 +1457,6 @@ def process_open_meteo_data(
         }
 
 
-# Pressure trend analysis constants
-MIN_PRESSURE_HISTORY_POINTS = 3
-PRESSURE_TREND_STEADY_THRESHOLD = 0.1  # hPa/hour
-PRESSURE_TREND_SIGNIFICANT_THRESHOLD = 0.5  # hPa/hour
-PRESSURE_HIGH_THRESHOLD = 1020  # hPa
-PRESSURE_NORMAL_THRESHOLD = 1000  # hPa
-
-
-def calculate_pressure_trend(pressure_history: list[dict]) -> dict:
-    """Calculate pressure trend indicators from historical data"""
-    if len(pressure_history) < MIN_PRESSURE_HISTORY_POINTS:
-        return {
-            'trend': 'steady',
-            'rate': 0.0,
-            'prediction': 'Unable to determine trend - insufficient data',
-        }
-
-    # Get current pressure and 3-hour ago pressure for trend calculation
-    current_pressure = pressure_history[0]['pressure'] if pressure_history else 0
-    three_hours_ago = pressure_history[min(3, len(pressure_history) - 1)]['pressure']
-
-    # Calculate rate of change in hPa per hour
-    pressure_change = current_pressure - three_hours_ago
-    rate_per_hour = pressure_change / 3.0  # 3-hour change divided by 3
-
-    # Determine trend direction
-    if abs(rate_per_hour) < PRESSURE_TREND_STEADY_THRESHOLD:
-        trend = 'steady'
-    elif rate_per_hour > PRESSURE_TREND_STEADY_THRESHOLD:
-        trend = 'rising'
-    else:
-        trend = 'falling'
-
-    # Generate weather prediction based on pressure trend
-    prediction = get_pressure_prediction(trend, rate_per_hour, current_pressure)
-
-    return {
-        'trend': trend,
-        'rate': round(rate_per_hour, 2),
-        'prediction': prediction,
-        'current_pressure': current_pressure,
-        'history': pressure_history[:12],  # Last 12 hours for mini-chart
-    }
-
-
-def get_pressure_prediction(trend: str, rate: float, current_pressure: float) -> str:
-    """Generate weather prediction based on pressure trends"""
-    predictions = {
-        'rising_fast': 'Improving weather expected - clearing skies likely',
-        'rising_slow': 'Weather gradually improving',
-        'steady_high': 'Continued fair weather',
-        'steady_normal': 'Current weather conditions expected to persist',
-        'steady_low': 'Unsettled weather may continue',
-        'falling_slow': 'Weather may deteriorate gradually',
-        'falling_fast': 'Stormy weather approaching - expect precipitation',
-    }
-
-    # Categorize pressure levels (typical sea level pressure ranges)
-    if current_pressure > PRESSURE_HIGH_THRESHOLD:
-        pressure_level = 'high'
-    elif current_pressure > PRESSURE_NORMAL_THRESHOLD:
-        pressure_level = 'normal'
-    else:
-        pressure_level = 'low'
-
-    # Categorize rate of change
-    if abs(rate) < PRESSURE_TREND_STEADY_THRESHOLD:
-        rate_category = 'steady'
-    elif abs(rate) > PRESSURE_TREND_SIGNIFICANT_THRESHOLD:
-        rate_category = f'{trend}_fast'
-    else:
-        rate_category = f'{trend}_slow'
-
-    # Combine trend and pressure level for prediction
-    if rate_category == 'steady':
-        prediction_key = f'steady_{pressure_level}'
-    else:
-        prediction_key = rate_category
-
-    return predictions.get(prediction_key, 'Weather pattern uncertain')
-
-
 def get_weather_description(weather_code: int) -> str:
     """Get human-readable weather description from WMO code"""
     descriptions = {
@@ -457,80 +1487,644 @@ def get_weather_description(weather_code: int) -> str:
     return descriptions.get(weather_code, 'Unknown')
 
 
-# Common city shortcuts (timezone auto-detected by OpenMeteo API)
-CITY_COORDS = {
-    'chicago': (41.8781, -87.6298, 'Chicago'),
-    'nyc': (40.7128, -74.0060, 'New York City'),
-    'sf': (37.7749, -122.4194, 'San Francisco'),
-    'london': (51.5074, -0.1278, 'London'),
-    'paris': (48.8566, 2.3522, 'Paris'),
-    'tokyo': (35.6762, 139.6503, 'Tokyo'),
-    'sydney': (-33.8688, 151.2093, 'Sydney'),
-    'berlin': (52.5200, 13.4050, 'Berlin'),
-    'rome': (41.9028, 12.4964, 'Rome'),
-    'madrid': (40.4168, -3.7038, 'Madrid'),
-}
+# City shortcuts for /<city> routes. The built-in table (or the config file's
+# [cities.<key>] tables) can be replaced with a JSON file at CITY_REGISTRY_PATH
+# ({"key": {"lat", "lon", "name", "aliases"}}) and edited at runtime through
+# /api/admin/cities, which rewrites that file
+TYPO_REDIRECT_MIN_LENGTH = 4  # Shorter segments are too ambiguous to guess
+CITY_REGISTRY_PATH = os.getenv('CITY_REGISTRY_PATH', 'data/cities.json')
+try:
+    city_registry = CityRegistry(
+        CITY_REGISTRY_PATH, config_file.cities or DEFAULT_CITIES
+    )
+except ValueError as e:
+    print(f'❌ Ignoring cities from {config_file.path}: {e}')
+    city_registry = CityRegistry(CITY_REGISTRY_PATH, DEFAULT_CITIES)
+CITY_COORDS = city_registry.coords
+CITY_ALIASES = city_registry.aliases
 
 
-@app.route('/')
-def index() -> str:
-    """Main weather page"""
-    return str(render_template('weather.html', git_hash=get_git_hash()))
+def resolve_city_key(city: str) -> str | None:
+    """Resolve a city route segment to its CITY_COORDS key, following aliases"""
+    return city_registry.resolve(city)
 
 
-@app.route('/<city>')
-def weather_by_city(city: str) -> str | tuple[str, int]:
-    """Weather page for common cities"""
-    city_lower = city.lower()
-    if city_lower in CITY_COORDS:
-        return str(render_template('weather.html', git_hash=get_git_hash()))
+def is_geocodable_city_name(city: str) -> bool:
+    """Check a route segment looks like a place name worth geocoding"""
+    decoded = unicodedata.normalize('NFKC', unquote(city)).strip()
+    max_length = 100
+    return (
+        0 < len(decoded) <= max_length
+        and any(char.isalpha() for char in decoded)
+        and all(char.isalpha() or char in " -'" for char in decoded)
+    )
 
-    # Check if this might be coordinates (contains comma and numbers)
-    coord_chars = {'.', '-'}
-    if ',' in city and any(char.isdigit() or char in coord_chars for char in city):
-        try:
-            parts = city.split(',')
-            coord_parts_expected = 2
-            if len(parts) == coord_parts_expected:
-                lat = float(parts[0])
-                lon = float(parts[1])
-                # Valid coordinate range check
-                min_lat, max_lat = -90, 90
-                min_lon, max_lon = -180, 180
-                if min_lat <= lat <= max_lat and min_lon <= lon <= max_lon:
-                    return str(render_template('weather.html', git_hash=get_git_hash()))
-        except ValueError:
-            pass
 
-    return (
-        f"City '{city}' not found. Available cities: {', '.join(CITY_COORDS.keys())}"
-    ), 404
+def geocode_city(city: str) -> tuple[float, float, str] | None:
+    """Resolve a free-form city name (a route segment) to coordinates"""
+    return geocode_place(normalize_city_name(city).replace('-', ' '))
 
 
-@app.route('/<coords>', methods=['GET'])
-@app.route('/<coords>/<location>', methods=['GET'])
-def weather_by_coords_route(coords: str, location: str | None = None) -> str:
-    """Weather page for coordinates with optional location name"""
-    # Parse coordinates from string like "41.8781,-87.6298"
-    try:
-        if ',' not in coords:
-            # Not coordinates, fall through to city route
-            abort(404)
+def resolve_postal_code(
+    code: str, country: str | None = None
+) -> tuple[float, float, str] | None:
+    """Coordinates for a postal code: the local dump first, then the geocoder"""
+    code = ' '.join(code.split())
+    country = (country or POSTAL_DEFAULT_COUNTRY).strip().upper()
+    if not valid_postal_query(code, country):
+        return None
+    return postal_index.lookup(code, country) or geocode_postal_code(code, country)
 
-        lat_str, lon_str = coords.split(',', 1)
-        lat = float(lat_str.strip())
-        lon = float(lon_str.strip())
 
-        # Validate coordinate ranges
-        if not (MIN_LATITUDE <= lat <= MAX_LATITUDE) or not (
-            MIN_LONGITUDE <= lon <= MAX_LONGITUDE
-        ):
-            abort(404)
+def request_token() -> str | None:
+    """Get the API token from the Authorization or X-API-Key header, the query
+    (?token= or ?api_key=) or the kiosk cookie
+    """
+    authorization = request.headers.get('Authorization', '')
+    if authorization.startswith('Bearer '):
+        return authorization.removeprefix('Bearer ').strip()
+    return (
+        request.headers.get('X-API-Key')
+        or request.args.get('token')
+        or request.args.get('api_key')
+        or request.cookies.get(TOKEN_COOKIE)
+    )
 
-        # Use location if provided for the page title or meta
-        return str(
-            render_template('weather.html', git_hash=get_git_hash(), location=location)
-        )
+
+def token_grants(secret: str | None, scope: str) -> bool:
+    """Check a token secret grants the scope (admin tokens also grant read)"""
+    if not secret:
+        return False
+    if ADMIN_TOKEN and secrets.compare_digest(secret, ADMIN_TOKEN):
+        return True
+    return token_store.verify(secret, scope) is not None
+
+
+def admin_auth_enabled() -> bool:
+    """Admin endpoints are locked once a bootstrap or stored admin token exists"""
+    return bool(ADMIN_TOKEN) or token_store.has_active('admin')
+
+
+def is_local_request() -> bool:
+    """A direct connection from this machine (loopback or unix socket), no proxy"""
+    if request.headers.get('X-Forwarded-For') or request.headers.get('Forwarded'):
+        return False
+    peer = request.environ.get(PEER_ADDR_KEY, request.remote_addr)
+    if peer == UNIX_SOCKET_PEER:
+        return True
+    try:
+        return ipaddress.ip_address(peer or '').is_loopback
+    except ValueError:
+        return False
+
+
+def require_admin(view: Any) -> Any:
+    """Require an admin token on a view, or a local caller until there is one"""
+
+    @wraps(view)
+    def wrapper(*args: Any, **kwargs: Any) -> Any:
+        if admin_auth_enabled():
+            if not token_grants(request_token(), 'admin'):
+                raise Unauthorized('Admin token required')
+        elif not is_local_request():
+            raise Forbidden(
+                'Admin endpoints only answer local requests until ADMIN_TOKEN '
+                'or an admin API key is set'
+            )
+        return view(*args, **kwargs)
+
+    return wrapper
+
+
+@app.before_request
+def assign_request_id() -> None:
+    """Give the request an id and start its clock, before anything can refuse it"""
+    g.request_id = request_id(request.headers.get(REQUEST_ID_HEADER))
+    g.request_started = time.perf_counter()
+    if request.endpoint != 'static':
+        route = request.url_rule.rule if request.url_rule else None
+        g.trace_span = tracer.start(
+            f'{request.method} {route or request.path}',
+            SPAN_KIND_SERVER,
+            {
+                'http.request.method': request.method,
+                'http.route': route,
+                'url.path': request.path,
+                'request.id': g.request_id,
+            },
+            request.headers.get('traceparent'),
+        )
+
+
+@app.after_request
+def log_request(response: Response) -> Response:
+    """Return the request id and log the request (registered first, so runs last)"""
+    response.headers[REQUEST_ID_HEADER] = g.request_id
+    cache_hit = g.get('cache_hit')
+    if cache_hit is None and 'X-Cache' in response.headers:
+        cache_hit = response.headers['X-Cache'] == 'HIT'
+    span = g.get('trace_span')
+    if span is not None:
+        span.set_attribute('http.response.status_code', response.status_code)
+        span.set_attribute('weather.provider', g.get('weather_provider'))
+        span.set_attribute('weather.cache_hit', cache_hit)
+        if response.status_code >= 500:  # noqa: PLR2004
+            span.record_error(response.status)
+    if ACCESS_LOG:
+        print(
+            access_log_line(
+                g.request_id,
+                request.method,
+                request.path,
+                response.status_code,
+                (time.perf_counter() - g.request_started) * 1000,
+                g.get('weather_provider'),
+                cache_hit,
+            )
+        )
+    return response
+
+
+@app.teardown_request
+def finish_trace(error: BaseException | None) -> None:
+    """End the request's span, even when the request failed before responding"""
+    span = g.pop('trace_span', None)
+    if span is not None:
+        tracer.finish(span, f'{type(error).__name__}: {error}' if error else None)
+
+
+@app.before_request
+def enforce_rate_limit() -> Response | None:
+    """Refuse API requests beyond the client's rate limit with a 429"""
+    if rate_limiter is None or not request.path.startswith('/api/'):
+        return None
+    g.rate_limit = rate_limiter.check(request.remote_addr or 'unknown')
+    if g.rate_limit.allowed:
+        return None
+    raise RateLimited('Too many requests', retry_after=g.rate_limit.retry_after)
+
+
+@app.after_request
+def add_rate_limit_headers(response: Response) -> Response:
+    """Tell API clients how much of their rate limit is left"""
+    decision = g.get('rate_limit')
+    if decision is not None:
+        response.headers.update(decision.headers())
+    return response
+
+
+@app.after_request
+def vary_on_prefs(response: Response) -> Response:
+    """Keep shared caches from handing one visitor's units or clock to another"""
+    if g.get('reads_prefs'):
+        response.vary.add('Cookie')
+        cache_control = response.headers.get('Cache-Control', '')
+        if g.get('prefs_from_cookie') and cache_control.startswith('public'):
+            response.headers['Cache-Control'] = cache_control.replace(
+                'public', 'private', 1
+            )
+    return response
+
+
+@app.before_request
+def check_read_token() -> Response | None:
+    """Require a read token on the API when REQUIRE_READ_TOKEN is set"""
+    if not REQUIRE_READ_TOKEN or not request.path.startswith('/api/'):
+        return None
+    if request.path == STATION_UPLOAD_PATH:  # Stations send their own key
+        return None
+    if token_grants(request_token(), 'read'):
+        return None
+    raise Unauthorized('Read token required')
+
+
+@app.after_request
+def remember_kiosk_token(response: Response) -> Response:
+    """Keep a valid ?token= as a cookie so a kiosk URL works for page API calls"""
+    token = request.args.get('token')
+    if token and token != request.cookies.get(TOKEN_COOKIE):
+        if token_grants(token, 'read'):
+            response.set_cookie(
+                TOKEN_COOKIE,
+                token,
+                max_age=TOKEN_COOKIE_MAX_AGE,
+                httponly=True,
+                samesite='Strict',
+                secure=request.is_secure,
+            )
+    return response
+
+
+def analytics_location() -> str | None:
+    """Location key a request is about, from lat/lon or a known city segment"""
+    try:
+        lat = float(request.args['lat'])
+        lon = float(request.args['lon'])
+    except (KeyError, ValueError):
+        city = (request.view_args or {}).get('city')
+        if isinstance(city, str) and city.lower() in CITY_COORDS:
+            lat, lon, _ = CITY_COORDS[city.lower()]
+            return location_key(lat, lon)
+        return None
+    return location_key(lat, lon)
+
+
+@app.after_request
+def require_https_next_time(response: Response) -> Response:
+    """Tell browsers to stay on HTTPS once the server is serving it directly"""
+    if request.is_secure and app.config.get('TLS_CONTEXT') is not None:
+        response.headers['Strict-Transport-Security'] = (
+            f'max-age={HSTS_MAX_AGE_SECONDS}'
+        )
+    return response
+
+
+@app.after_request
+def record_usage(response: Response) -> Response:
+    """Count the request in the usage analytics when enabled"""
+    if usage_analytics is not None and request.url_rule is not None:
+        if request.endpoint != 'static':
+            usage_analytics.record(
+                request.url_rule.rule,
+                device_class(request.headers.get('User-Agent')),
+                analytics_location(),
+            )
+    return response
+
+
+@app.context_processor
+def inject_city_coords() -> dict[str, Any]:
+    """Give pages the live city registry so /<city> routes match the server"""
+    return {'city_coords': {key: list(coords) for key, coords in CITY_COORDS.items()}}
+
+
+@app.context_processor
+def inject_prefs() -> dict[str, Any]:
+    """Give pages the visitor's preferences (theme, units, clock, home)"""
+    return {'prefs': visitor_prefs()}
+
+
+@app.context_processor
+def inject_schema() -> dict[str, Any]:
+    """Give pages the weather schema, so their unit labels match the API's"""
+    return {'weather_schema': WEATHER_SCHEMA}
+
+
+THEME_STYLESHEET = theme_stylesheet()
+
+
+def requested_theme() -> str:
+    """The page theme: ?theme= (or ?background=), else the saved preference"""
+    for name in (request.args.get('theme'), request.args.get('background')):
+        theme = resolve_theme(name)
+        if theme:
+            return theme
+    return str(visitor_prefs()['theme'])
+
+
+@app.context_processor
+def inject_theme() -> dict[str, Any]:
+    """Give pages the theme tokens and the body's data-theme for this request"""
+    return {
+        'theme_css': THEME_STYLESHEET,
+        'theme_attribute': theme_attribute(requested_theme()),
+    }
+
+
+@app.context_processor
+def inject_icons() -> dict[str, Any]:
+    """Give pages the request's icon pack, as data for scripts and as markup"""
+    icon_set = requested_icon_set()
+    return {
+        'icon_set': icon_set.describe(asset_manifest.url),
+        'weather_icon': lambda code: Markup(icon_set.html(code, asset_manifest.url)),
+    }
+
+
+# Alert banners rendered into the pages, so displays without JS still see them
+ALERT_SEVERITY_ORDER = ('extreme', 'severe', 'moderate', 'minor')
+DEFAULT_ALERT_COLOR = '#1E90FF'
+
+
+def location_alerts(lat: float, lon: float, name: str) -> dict:
+    """Cached NWS alerts for a location, fetching and caching them on a miss"""
+    cache_key = location_key(lat, lon)
+    if f'alerts_{cache_key}' not in alerts_cache:
+        alerts_data = nws_provider.get_weather(lat, lon, name)
+        if alerts_data:
+            store_alerts_data(lat, lon, alerts_data)
+    return alerts_cache.get(f'alerts_{cache_key}') or {}
+
+
+def alert_expiry_label(end_time: str | None) -> str | None:
+    """'Mon 6:00 PM' in the alert's own timezone, or None if it has no expiry"""
+    if not end_time:
+        return None
+    try:
+        expires = datetime.fromisoformat(end_time.replace('Z', '+00:00'))
+    except ValueError:
+        return None
+    return f'{expires:%a} {expires.hour % 12 or 12}:{expires:%M %p}'
+
+
+def alert_banners(alerts_data: dict) -> list[dict[str, Any]]:
+    """Active alerts as page banners (event, severity color, expiry), worst first"""
+    banners = [
+        {
+            'event': alert.get('type') or 'Weather alert',
+            'headline': alert.get('headline'),
+            'severity': alert.get('severity') or 'Unknown',
+            'color': alert.get('color') or DEFAULT_ALERT_COLOR,
+            'expires': alert.get('end_time'),
+            'expires_label': alert_expiry_label(alert.get('end_time')),
+        }
+        for alert in alerts_data.get('alerts', {}).get('alerts', [])
+    ]
+    return sorted(
+        banners,
+        key=lambda banner: (
+            ALERT_SEVERITY_ORDER.index(banner['severity'].lower())
+            if banner['severity'].lower() in ALERT_SEVERITY_ORDER
+            else len(ALERT_SEVERITY_ORDER)
+        ),
+    )
+
+
+def render_weather_page(place: tuple[float, float, str] | None, **context: Any) -> str:
+    """The dashboard page, with alert banners for the place when it's known"""
+    alerts = alert_banners(location_alerts(*place)) if place else []
+    return str(
+        render_template(
+            'weather.html', git_hash=get_git_hash(), alerts=alerts, **context
+        )
+    )
+
+
+@app.route('/')
+def index() -> str | Response:
+    """Main weather page, sending first-time visitors to their IP's location"""
+    has_home = (
+        PROFILE_LOCATION_COOKIE in request.cookies or visitor_prefs()['location']
+    )
+    if not request.args and not has_home:
+        located = locate_client()
+        if located:
+            lat, lon, name = located
+            return redirect(f'/{lat:.4f},{lon:.4f}/{quote(name.replace(" ", "-"))}')
+    location, _fallback = resolve_request_location()
+    return render_weather_page(location)
+
+
+# Multi-location grid: ?cities= (comma-separated), else favorites, else the default
+MULTI_MAX_LOCATIONS = 12
+MULTI_FORECAST_HOURS = 6
+MULTI_FORECAST_DAYS = 3
+
+
+def location_path(lat: float, lon: float, name: str, city_key: str | None) -> str:
+    """The dashboard route for a location: its city key, else lat,lon/name"""
+    if city_key:
+        return f'/{city_key}'
+    return f'/{lat:.4f},{lon:.4f}/{quote(name.replace(" ", "-"))}'
+
+
+def multi_locations(cities: str | None) -> tuple[list[tuple], list[str]]:
+    """Locations for /multi as (lat, lon, name, path), plus names not found"""
+    if not cities:
+        favorites = favorites_store.list_favorites()
+        if favorites:
+            return [
+                (fav['lat'], fav['lon'], fav['name'], None)
+                for fav in favorites[:MULTI_MAX_LOCATIONS]
+            ], []
+        cities = DEFAULT_LOCATION
+
+    locations, unknown = [], []
+    for entry in [part.strip() for part in cities.split(',') if part.strip()]:
+        if len(locations) == MULTI_MAX_LOCATIONS:
+            break
+        resolved = resolve_location_segment(entry)
+        if resolved:
+            locations.append((*resolved, resolve_city_key(entry)))
+        else:
+            unknown.append(entry)
+    return locations, unknown
+
+
+def location_weather(lat: float, lon: float, name: str | None) -> dict | None:
+    """Cached weather for a location, fetching and caching it on a miss"""
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    weather_data = get_cached_weather(cache_key, lat, lon, name)
+    if weather_data is None:
+        weather_data = weather_manager.get_weather(lat, lon, name)
+        if weather_data:
+            store_weather_data(cache_key, weather_data)
+    return weather_data
+
+
+@app.route('/multi')
+def multi_dashboard() -> str:
+    """Grid of current conditions and short forecasts for several locations"""
+    locations, unknown = multi_locations(request.args.get('cities'))
+    units, time_format = requested_display()
+
+    # Fetch every location at once so the page waits for the slowest, not the sum
+    with ThreadPoolExecutor(max_workers=max(1, len(locations))) as executor:
+        fetched = list(
+            executor.map(lambda loc: location_weather(*loc[:3]), locations)
+        )
+
+    tiles = []
+    for (lat, lon, name, city_key), weather_data in zip(
+        locations, fetched, strict=True
+    ):
+        weather = None
+        if weather_data:
+            weather = serialize_weather(
+                {**weather_data, 'location': name},
+                language=requested_language(),
+                units=units,
+                time_format=time_format,
+            )
+            weather['hourly'] = weather.get('hourly', [])[:MULTI_FORECAST_HOURS]
+            weather['daily'] = weather.get('daily', [])[:MULTI_FORECAST_DAYS]
+        tiles.append(
+            {
+                'name': name,
+                'path': location_path(lat, lon, name, city_key),
+                'weather': weather,
+            }
+        )
+    return str(
+        render_template(
+            'multi.html',
+            tiles=tiles,
+            unknown=unknown,
+            units=UNIT_LABELS[units],
+        )
+    )
+
+
+# Kiosk: chrome-less fullscreen view rotating through locations for wall displays
+KIOSK_DEFAULT_INTERVAL_SECONDS = 30
+KIOSK_MIN_INTERVAL_SECONDS = 10
+KIOSK_MAX_INTERVAL_SECONDS = 3600
+
+
+def kiosk_interval(raw: str | None) -> int:
+    """Seconds to show each location, clamped to something a wall display can use"""
+    try:
+        interval = int(raw) if raw else KIOSK_DEFAULT_INTERVAL_SECONDS
+    except ValueError:
+        interval = KIOSK_DEFAULT_INTERVAL_SECONDS
+    return max(KIOSK_MIN_INTERVAL_SECONDS, min(interval, KIOSK_MAX_INTERVAL_SECONDS))
+
+
+@app.route('/kiosk')
+@app.route('/kiosk/<city>')
+def kiosk(city: str | None = None) -> str:
+    """Fullscreen kiosk view, rotating through ?rotate= locations every interval"""
+    entries = [city] if city else []
+    entries += [part for part in request.args.get('rotate', '').split(',') if part]
+    locations, _unknown = multi_locations(
+        ','.join(dict.fromkeys(entries)) or DEFAULT_LOCATION
+    )
+    if not locations:
+        locations, _unknown = multi_locations(DEFAULT_LOCATION)
+    interval = kiosk_interval(request.args.get('interval'))
+    try:
+        position = int(request.args.get('at', '0')) % len(locations)
+    except ValueError:
+        position = 0
+
+    # The first screen is rendered here so displays without JS still work; with
+    # JS the page follows each location's weather stream and rotates itself
+    lat, lon, name, _city_key = locations[position]
+    units, time_format = requested_display()
+    weather_data = location_weather(lat, lon, name)
+    weather = None
+    if weather_data:
+        weather = serialize_weather(
+            {**weather_data, 'location': name},
+            units=units,
+            time_format=time_format,
+        )
+        weather['hourly'] = weather.get('hourly', [])[:MULTI_FORECAST_HOURS]
+    next_args = {**request.args.to_dict(), 'at': str((position + 1) % len(locations))}
+    return str(
+        render_template(
+            'kiosk.html',
+            locations=[
+                {'lat': lat, 'lon': lon, 'name': name}
+                for lat, lon, name, _key in locations
+            ],
+            position=position,
+            weather=weather,
+            alerts=alert_banners(location_alerts(lat, lon, name)),
+            units=UNIT_LABELS[units],
+            interval=interval,
+            next_url=f'{request.path}?{urlencode(next_args)}',
+        )
+    )
+
+
+@app.route('/<city>')
+def weather_by_city(city: str) -> str | tuple[str, int] | Response:
+    """Weather page for common cities"""
+    city_key = resolve_city_key(city)
+    if city_key:
+        if city_key != city.lower():
+            # Aliases and local spellings land on the canonical city route
+            return redirect(f'/{city_key}')
+        return render_weather_page(CITY_COORDS[city_key])
+
+    # Check if this might be coordinates (contains comma and numbers)
+    coord_chars = {'.', '-'}
+    if ',' in city and any(char.isdigit() or char in coord_chars for char in city):
+        try:
+            parts = city.split(',')
+            coord_parts_expected = 2
+            if len(parts) == coord_parts_expected:
+                lat = float(parts[0])
+                lon = float(parts[1])
+                # Valid coordinate range check
+                min_lat, max_lat = -90, 90
+                min_lon, max_lon = -180, 180
+                if min_lat <= lat <= max_lat and min_lon <= lon <= max_lon:
+                    return render_weather_page((lat, lon, 'Location'))
+        except ValueError:
+            pass
+
+    # Fall back to the geocoder for cities outside the registry
+    if is_geocodable_city_name(city):
+        geocoded = geocode_city(city)
+        if geocoded:
+            lat, lon, name = geocoded
+            return redirect(f'/{lat:.4f},{lon:.4f}/{quote(name.replace(" ", "-"))}')
+
+    # Nowhere is called that: a single close registry match ('chicgo', 'sydn')
+    # is taken as meant, several are offered instead of the whole list
+    suggestions = city_registry.suggest(city)
+    if (
+        len(normalize_city_name(city)) >= TYPO_REDIRECT_MIN_LENGTH
+        and len(suggestions) == 1
+        and suggestions[0][1] <= 1
+    ):
+        return redirect(f'/{suggestions[0][0]}')
+    if suggestions:
+        did_you_mean = ', '.join(f'/{key}' for key, _ in suggestions)
+        return f"City '{city}' not found. Did you mean {did_you_mean}?", 404
+    return (
+        f"City '{city}' not found. Try a place name, /<lat>,<lon> "
+        f"or one of: {', '.join(CITY_COORDS.keys())}"
+    ), 404
+
+
+@app.route('/zip/<code>')
+def weather_by_postal_code(code: str) -> tuple[str, int] | Response:
+    """Weather page for a ZIP/postal code (?country= for outside the default)"""
+    resolved = resolve_postal_code(code, request.args.get('country'))
+    if not resolved:
+        country = request.args.get('country', POSTAL_DEFAULT_COUNTRY)
+        return f"Postal code '{code}' not found in {country}", 404
+    lat, lon, name = resolved
+    return redirect(f'/{lat:.4f},{lon:.4f}/{quote(name.replace(" ", "-"))}')
+
+
+@app.route('/airport/<code>')
+def weather_by_airport(code: str) -> tuple[str, int] | Response:
+    """Weather page for an airport by ICAO (EGLL) or IATA (ORD) code"""
+    airport = airport_index.lookup(code)
+    if not airport:
+        return f"Airport '{code}' not found", 404
+    name = quote(airport['name'].replace(' ', '-'))
+    return redirect(f'/{airport["lat"]:.4f},{airport["lon"]:.4f}/{name}')
+
+
+@app.route('/<coords>', methods=['GET'])
+@app.route('/<coords>/<location>', methods=['GET'])
+def weather_by_coords_route(coords: str, location: str | None = None) -> str:
+    """Weather page for coordinates with optional location name"""
+    # Parse coordinates from string like "41.8781,-87.6298"
+    try:
+        if ',' not in coords:
+            # Not coordinates, fall through to city route
+            abort(404)
+
+        lat_str, lon_str = coords.split(',', 1)
+        lat = float(lat_str.strip())
+        lon = float(lon_str.strip())
+
+        # Validate coordinate ranges
+        if not (MIN_LATITUDE <= lat <= MAX_LATITUDE) or not (
+            MIN_LONGITUDE <= lon <= MAX_LONGITUDE
+        ):
+            abort(404)
+
+        # Use location if provided for the page title or meta
+        return render_weather_page(
+            (lat, lon, location or 'Location'), location=location
+        )
 
     except (ValueError, AttributeError):
         # Not valid coordinates, fall through to city route
@@ -543,62 +2137,1624 @@ def weather_by_coords_route(coords: str, location: str | None = None) -> str:
 @app.route('/api/weather')
 def weather_api() -> Response:
     """API endpoint for weather data"""
-    # Get lat/lon from URL parameters
-    lat = request.args.get('lat', type=float)
-    lon = request.args.get('lon', type=float)
-    location_name = request.args.get('location', 'Chicago')
+    # ?lat=&lon= or ?city=, else the profile or instance default
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
     timezone_name = request.args.get('timezone')  # Optional override
-
-    # Default to Chicago if no coordinates provided
-    if not lat or not lon:
-        lat = CHICAGO_LAT
-        lon = CHICAGO_LON
+    scenario = request.args.get('scenario')
+    if scenario is not None:
+        return scenario_weather_response(
+            scenario, lat, lon, location_name, timezone_name
+        )
 
     # Create cache key
     cache_key = f'{lat:.4f},{lon:.4f}'
 
     # Check cache first
-    if cache_key in weather_cache:
+    cached_data = get_cached_weather(
+        cache_key, lat, lon, location_name, timezone_name
+    )
+    g.cache_hit = cached_data is not None
+    if cached_data is not None:
         print(f'📦 Returning cached data for {cache_key}')
-        cached_data = weather_cache[cache_key]
         cached_data['location'] = location_name  # Update location name
-        response = jsonify(cached_data)
-        response.headers['Cache-Control'] = 'public, max-age=180'
-        etag_value = hash(str(lat) + str(lon) + str(int(time.time() // 300)))
-        response.headers['ETag'] = f'"{etag_value}"'
-        return response
+        return conditional_weather_response(
+            cached_data, cache_key, location_name, location_fallback
+        )
 
     # Use weather provider manager to get data
     print(f'🌤️  Fetching weather for {location_name} using provider system')
     processed_data = weather_manager.get_weather(lat, lon, location_name, timezone_name)
 
     if processed_data:
-        # Cache the result
-        weather_cache[cache_key] = processed_data
+        # Cache the result and notify stream subscribers
+        store_weather_data(cache_key, processed_data)
         print(f'💾 Cached weather data for {cache_key}')
+        return conditional_weather_response(
+            processed_data, cache_key, location_name, location_fallback
+        )
+    raise UpstreamUnavailable('Failed to fetch weather data from all sources')
+
+
+def scenario_weather_response(
+    scenario: str,
+    lat: float,
+    lon: float,
+    location_name: str,
+    timezone_name: str | None,
+) -> Response:
+    """Simulated weather for ?scenario=, straight from the mock provider
+
+    It's never cached or recorded, so a demo blizzard can't reach the
+    history, alerts or anyone else's responses.
+    """
+    mock = next(
+        (
+            provider
+            for provider in weather_manager.providers.values()
+            if isinstance(provider, MockProvider)
+        ),
+        None,
+    )
+    if mock is None:
+        raise InvalidParameter('Scenarios need the Mock provider (set MOCK_WEATHER=1)')
+    if scenario not in MOCK_SCENARIOS:
+        raise InvalidParameter(
+            f'Unknown scenario {scenario!r}',
+            mock.name,
+            scenarios=list(MOCK_SCENARIOS),
+        )
 
-        response = jsonify(processed_data)
-        response.headers['Cache-Control'] = 'public, max-age=180'
-        etag_value = hash(str(lat) + str(lon) + str(int(time.time() // 300)))
-        response.headers['ETag'] = f'"{etag_value}"'
+    weather_data = mock.with_scenario(scenario).get_weather(
+        lat, lon, location_name, timezone_name
+    )
+    if not weather_data:
+        raise ProviderFailed('Failed to simulate weather data', mock.name)
+    g.weather_provider = mock.name
+    units, time_format = requested_display()
+    body = serialize_weather(
+        weather_data, requested_api_naming(), requested_language(), units, time_format
+    )
+    body['units'] = UNIT_LABELS[units]
+    body['icon_set'] = requested_icon_set().describe(asset_manifest.url)
+    response = jsonify(body)
+    response.headers['Cache-Control'] = 'no-store'
+    return response
+
+
+@app.route('/api/weather/<any(current, hourly, daily):section>')
+def weather_section_api(section: str) -> Response:
+    """One section of the weather document, cached and fetched on its own"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+    timezone_name = request.args.get('timezone')
+
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    section_cache = weather_section_caches[section]
+    ttl = WEATHER_SECTION_TTLS[section]
+
+    # A full document fetched recently already has this section - no upstream call
+    entry_key = cached_weather_key(cache_key)
+    section_key = str(weather_cache_key(cache_key))
+    if entry_key is not None:
+        section_data = extract_weather_section(weather_cache[entry_key], section)
+        metadata = weather_cache_metadata.get(entry_key, {})
+        fetched_at = metadata.get('fetched_at', time.time())
+        ttl = metadata.get('ttl', WEATHER_CACHE_TTL)
+        cache_status = 'HIT'
+    elif section_key in section_cache:
+        fetched_at, section_data = section_cache[section_key]
+        cache_status = 'HIT'
+    else:
+        print(f'🌤️  Fetching {section} weather for {location_name}')
+        fetched = weather_manager.get_weather_section(
+            lat, lon, section, location_name, timezone_name
+        )
+        if not fetched:
+            raise UpstreamUnavailable(f'Failed to fetch {section} weather data')
+        fetched_at, section_data = time.time(), fetched
+        section_cache[section_key] = (fetched_at, section_data)
+        cache_status = 'MISS'
+
+    g.weather_provider = section_data.get('provider')
+    age = max(0, int(time.time() - fetched_at))
+    units, time_format = requested_display()
+    body = serialize_weather(
+        {**with_station_current(cache_key, section_data), 'location': location_name},
+        requested_api_naming(),
+        requested_language(),
+        units,
+        time_format,
+    )
+    body['units'] = UNIT_LABELS[units]
+    body['icon_set'] = requested_icon_set().describe(asset_manifest.url)
+    if location_fallback:
+        body['location_fallback'] = location_fallback
+    response = with_location_fallback(jsonify(body), location_fallback)
+    response.headers['Cache-Control'] = f'public, max-age={max(0, ttl - age)}'
+    response.headers['Age'] = str(age)
+    response.headers['X-Cache'] = cache_status
+    return response
+
+
+@app.route('/api/schema')
+def schema_api() -> Response:
+    """The weather document's fields, units, icons and enum values as JSON Schema"""
+    response = jsonify(WEATHER_SCHEMA)
+    response.headers['Cache-Control'] = 'public, max-age=3600'
+    return response
+
+
+@app.route('/api/weather/stream')
+def weather_stream_api() -> Response:
+    """Server-Sent Events stream of weather updates for a location"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+    timezone_name = request.args.get('timezone')
+
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    units, time_format = requested_display()
+
+    def serialize(weather_data: dict) -> dict[str, Any]:
+        body = serialize_weather(
+            {**weather_data, 'location': location_name},
+            units=units,
+            time_format=time_format,
+        )
+        return {**body, 'units': UNIT_LABELS[units]}
+
+    def refresh_weather() -> dict | None:
+        """Fetch fresh data when the cached entry has expired"""
+        weather_data = weather_manager.get_weather(
+            lat, lon, location_name, timezone_name
+        )
+        if weather_data:
+            store_weather_data(cache_key, weather_data)
+        return weather_data
+
+    def generate() -> Iterator[str]:
+        # Send the current conditions immediately so clients render right away
+        initial_data = (
+            get_cached_weather(cache_key, lat, lon, location_name, timezone_name)
+            or refresh_weather()
+        )
+        subscriber = subscribe_weather_stream(cache_key)
+        try:
+            if location_fallback:
+                yield format_sse_event('location_fallback', location_fallback)
+            if initial_data:
+                yield format_sse_event('weather', serialize(initial_data))
+            else:
+                yield format_sse_event(
+                    'weather_error', {'error': 'Failed to fetch weather data'}
+                )
+
+            while True:
+                try:
+                    weather_data = subscriber.get(
+                        timeout=WEATHER_STREAM_KEEPALIVE_SECONDS
+                    )
+                except queue.Empty:
+                    # Cache entry expired with nobody refreshing it - refresh here,
+                    # which also fans the update out to every other subscriber
+                    if weather_cache_key(cache_key) not in weather_cache:
+                        refresh_weather()
+                    yield ': keepalive\n\n'
+                    continue
+
+                yield format_sse_event('weather', serialize(weather_data))
+        finally:
+            unsubscribe_weather_stream(cache_key, subscriber)
+
+    print(f'📡 Opening weather stream for {location_name} ({cache_key})')
+    response = with_location_fallback(
+        Response(stream_with_context(generate()), mimetype='text/event-stream'),
+        location_fallback,
+    )
+    response.headers['Cache-Control'] = 'no-cache'
+    response.headers['X-Accel-Buffering'] = 'no'
+    return response
+
+
+# Plain-text (curl) rendering: five-line ASCII art per condition, wttr.in style
+TEXT_ICON_ART = {
+    'sun': [
+        '    \\   /    ',
+        '     .-.     ',
+        '  - (   ) -  ',
+        "     `-'     ",
+        '    /   \\    ',
+    ],
+    'partly': [
+        '   \\  /      ',
+        ' _ /"".-.    ',
+        '   \\_(   ).  ',
+        '   /(___(__) ',
+        '             ',
+    ],
+    'cloud': [
+        '             ',
+        '     .--.    ',
+        '  .-(    ).  ',
+        ' (___.__)__) ',
+        '             ',
+    ],
+    'fog': [
+        '             ',
+        ' _ - _ - _ - ',
+        '  _ - _ - _  ',
+        ' _ - _ - _ - ',
+        '             ',
+    ],
+    'rain': [
+        '     .-.     ',
+        '    (   ).   ',
+        '   (___(__)  ',
+        "    ' ' ' '  ",
+        "   ' ' ' '   ",
+    ],
+    'snow': [
+        '     .-.     ',
+        '    (   ).   ',
+        '   (___(__)  ',
+        '    *  *  *  ',
+        '   *  *  *   ',
+    ],
+    'thunder': [
+        '     .-.     ',
+        '    (   ).   ',
+        '   (___(__)  ',
+        '    /_ /_    ',
+        '     /  /    ',
+    ],
+}
+
+TEXT_ICON_SYMBOLS = {
+    'sun': '☀️',
+    'partly': '⛅',
+    'cloud': '☁️',
+    'fog': '🌫',
+    'rain': '🌧',
+    'snow': '❄️',
+    'thunder': '⛈',
+}
+
+# wttr.in-compatible one-line presets for ?format=1..4
+TEXT_FORMAT_PRESETS = {
+    '1': '%c %t',
+    '2': '%c 🌡️%t 🌬️%w',
+    '3': '%l: %c %t',
+    '4': '%l: %c 🌡️%t 🌬️%w',
+}
+
+# Arrows point where the wind blows to, indexed by the compass octant it comes from
+WIND_ARROWS = ['↓', '↙', '←', '↖', '↑', '↗', '→', '↘']
+
+# ANSI colors for temperatures (°F upper bound, color code); hotter is red
+TEXT_TEMPERATURE_COLORS = [(32, '34'), (50, '36'), (70, '32'), (85, '33')]
+TEXT_HOT_COLOR = '31'
+TEXT_FORECAST_DAYS = 3
+TEXT_COLUMN_WIDTH = 6
+
+
+def text_icon_group(icon_code: str) -> str:
+    """Collapse an icon code into one of the plain-text condition groups"""
+    if icon_code.startswith('partly-cloudy'):
+        return 'partly'
+    if icon_code.startswith('clear'):
+        return 'sun'
+    if icon_code == 'thunderstorm':
+        return 'thunder'
+    if icon_code.endswith('snow') or icon_code in {'sleet', 'hail'}:
+        return 'snow'
+    if icon_code.endswith('rain'):
+        return 'rain'
+    if icon_code == 'fog':
+        return 'fog'
+    return 'cloud'
+
+
+def text_condition_label(icon_code: str) -> str:
+    """Turn an icon code like 'partly-cloudy-day' into 'Partly cloudy'"""
+    label = icon_code.removesuffix('-day').removesuffix('-night')
+    return label.replace('-', ' ').capitalize()
+
+
+def format_text_reading(value: float | None, unit: str = '') -> str:
+    """A reading with its unit, '?' when the provider didn't report it"""
+    return f'{value if value is not None else "?"}{unit}'
+
+
+def format_text_temperature(
+    temp: float | None, unit: str, use_color: bool = False, width: int = 0
+) -> str:
+    """Format a temperature the way wttr.in does, e.g. +72°F"""
+    text = f'{round(temp):+d}{unit}' if temp is not None else f'?{unit}'
+    # Pad before coloring so the ANSI codes don't break column alignment
+    padding = ' ' * max(width - len(text), 0)
+    if not use_color or temp is None:
+        return text + padding
+    color = next(
+        (
+            code
+            for limit, code in TEXT_TEMPERATURE_COLORS
+            if temp <= (limit if unit == '°F' else fahrenheit_to_celsius(limit))
+        ),
+        TEXT_HOT_COLOR,
+    )
+    return f'\033[{color}m{text}\033[0m{padding}'
+
+
+def format_text_wind(current: dict, unit: str) -> str:
+    """Format wind speed with a direction arrow when the direction is known"""
+    direction = current.get('wind_direction')
+    arrow = WIND_ARROWS[round(direction / 45) % 8] if direction is not None else ''
+    return f'{arrow}{format_text_reading(current.get("wind_speed"), unit)}'
+
+
+def render_text_format(
+    template: str, weather_data: dict, location: str, units: str
+) -> str:
+    """Expand a wttr.in-style %-template against current conditions"""
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    labels = UNIT_LABELS[units]
+    current = weather_data.get('current', {})
+    icon_code = current.get('icon', 'clear-day')
+    values = {
+        'c': TEXT_ICON_SYMBOLS[text_icon_group(icon_code)],
+        'C': current.get('summary') or text_condition_label(icon_code),
+        't': format_text_temperature(current.get('temperature'), labels['temperature']),
+        'f': format_text_temperature(current.get('feels_like'), labels['temperature']),
+        'h': format_text_reading(current.get('humidity'), '%'),
+        'w': format_text_wind(current, labels['wind_speed']),
+        'l': location,
+        'u': format_text_reading(current.get('uv_index')),
+        'p': format_text_reading(
+            current.get('precipitation_rate'), labels['precipitation']
+        ),
+        'P': format_text_reading(current.get('pressure'), labels['pressure']),
+        '%': '%',
+    }
+    # Unknown specifiers are left untouched so typos are visible
+    return re.sub(
+        r'%(.)', lambda match: values.get(match.group(1), match.group(0)), template
+    )
+
+
+def render_text_report(
+    weather_data: dict, location: str, use_color: bool, units: str
+) -> str:
+    """Render current conditions and a short forecast for terminals"""
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    labels = UNIT_LABELS[units]
+    unit = labels['temperature']
+    current = weather_data.get('current', {})
+    icon_code = current.get('icon', 'clear-day')
+    art = TEXT_ICON_ART[text_icon_group(icon_code)]
+    temperature = format_text_temperature(current.get('temperature'), unit, use_color)
+    feels_like = format_text_temperature(current.get('feels_like'), unit, use_color)
+    details = [
+        current.get('summary') or text_condition_label(icon_code),
+        f'{temperature} (feels like {feels_like})',
+        format_text_wind(current, labels['wind_speed']),
+        f'Humidity {format_text_reading(current.get("humidity"), "%")}',
+        f'UV index {format_text_reading(current.get("uv_index"))}',
+    ]
+    title = f'\033[1m{location}\033[0m' if use_color else location
+
+    lines = [f'Weather report: {title}', '']
+    lines.extend(f'{art_line} {detail}' for art_line, detail in zip(art, details))
+
+    daily = weather_data.get('daily', [])[:TEXT_FORECAST_DAYS]
+    if daily:
+        lines.extend(['', 'Day   High   Low    Conditions'])
+        for day in daily:
+            high = format_text_temperature(
+                day.get('h'), unit, use_color, TEXT_COLUMN_WIDTH
+            )
+            low = format_text_temperature(
+                day.get('l'), unit, use_color, TEXT_COLUMN_WIDTH
+            )
+            day_icon = day.get('icon', 'clear-day')
+            lines.append(
+                f'{day.get("d", ""):<5} {high} {low} '
+                f'{TEXT_ICON_SYMBOLS[text_icon_group(day_icon)]} '
+                f'{text_condition_label(day_icon)}'
+            )
+
+    return '\n'.join(lines) + '\n'
+
+
+def resolve_location_segment(city: str) -> tuple[float, float, str] | None:
+    """Resolve a city, alias, or lat,lon path segment to coordinates"""
+    city_key = resolve_city_key(city)
+    if city_key:
+        return CITY_COORDS[city_key]
+
+    if ',' in city:
+        try:
+            lat_str, lon_str = city.split(',', 1)
+            lat, lon = float(lat_str), float(lon_str)
+        except ValueError:
+            return None
+        if not (MIN_LATITUDE <= lat <= MAX_LATITUDE):
+            return None
+        if not (MIN_LONGITUDE <= lon <= MAX_LONGITUDE):
+            return None
+        return lat, lon, f'{lat:.4f},{lon:.4f}'
+
+    if is_geocodable_city_name(city):
+        return geocode_city(city)
+    return None
+
+
+def locate_client() -> tuple[float, float, str] | None:
+    """Geolocate the requesting client's IP (behind trusted proxies, the client's)"""
+    if ip_locator is None:
+        return None
+    ip = public_ip(request.remote_addr)
+    if ip is None:
+        return None
+    if ip not in geoip_cache:
+        geoip_cache[ip] = ip_locator.locate(ip)
+    return geoip_cache[ip]  # type: ignore[no-any-return]
+
+
+def requested_location(
+    args: Mapping[str, Any],
+) -> tuple[tuple[float, float, str] | None, str | None]:
+    """Read ?lat=&lon=, ?city=, ?postal= or ?auto=1, and why not when unusable"""
+    lat_arg, lon_arg = args.get('lat'), args.get('lon')
+    city = args.get('city')
+    postal = args.get('postal')
+    if postal and lat_arg is None and lon_arg is None and not city:
+        resolved = resolve_postal_code(postal, args.get('country'))
+        if not resolved:
+            return None, 'postal_code_not_found'
+        lat, lon, name = resolved
+        return (lat, lon, args.get('location', name)), None
+
+    if lat_arg is None and lon_arg is None and not city:
+        if args.get('auto') != '1':
+            return None, 'missing'
+        located = locate_client()
+        if not located:
+            return None, 'ip_lookup_failed'
+        lat, lon, name = located
+        return (lat, lon, args.get('location', name)), None
+
+    if city and lat_arg is None and lon_arg is None:
+        resolved = resolve_location_segment(city)
+        if not resolved:
+            return None, 'geocoding_failed'
+        lat, lon, name = resolved
+        return (lat, lon, args.get('location', name)), None
+
+    resolved = resolve_location_segment(f'{lat_arg},{lon_arg}')
+    if not resolved:
+        return None, 'invalid_coordinates'
+    lat, lon, name = resolved
+    return (lat, lon, args.get('location', name)), None
+
+
+def resolve_request_location(
+    args: Mapping[str, Any] | None = None,
+) -> tuple[tuple[float, float, str] | None, dict[str, str | None] | None]:
+    """Resolve the request's location, falling back profile -> instance default
+
+    Reads the query string, or args (a Socket.IO message) when given. Returns
+    the location (None once the chain is exhausted) and a record of the
+    fallback for the response, None when the request's own location was used.
+    """
+    args = request.args if args is None else args
+    location, reason = requested_location(args)
+    if location:
+        return location, None
+
+    fallback: dict[str, str | None] = {
+        'reason': reason,
+        'requested': args.get('city')
+        or args.get('postal')
+        or ','.join(
+            str(value)
+            for value in (args.get('lat'), args.get('lon'))
+            if value is not None
+        )
+        or None,
+        'source': None,
+    }
+    chain = (
+        (
+            'profile',
+            visitor_prefs()['location']
+            or request.cookies.get(PROFILE_LOCATION_COOKIE),
+        ),
+        ('instance', DEFAULT_LOCATION),
+    )
+    for source, setting in chain:
+        default = resolve_location_segment(setting) if setting else None
+        if default:
+            fallback['source'] = source
+            print(f'📍 No usable location ({reason}), using {source} default')
+            return default, fallback
+    return None, fallback
+
+
+def location_fallback_error(fallback: dict[str, str | None]) -> InvalidLocation:
+    """Explicit 400 once neither the request nor any default gave a location"""
+    return InvalidLocation(
+        'No usable location: pass lat and lon or city', location_fallback=fallback
+    )
+
+
+def jsonify_with_fallback(
+    data: dict[str, Any], fallback: dict[str, str | None] | None
+) -> Response:
+    """JSON response recording any location fallback in the body and a header"""
+    body = {**data, 'location_fallback': fallback} if fallback else data
+    return with_location_fallback(jsonify(body), fallback)
+
+
+def with_location_fallback(
+    response: Response, fallback: dict[str, str | None] | None
+) -> Response:
+    """Tell clients which default stood in for the requested location, and why"""
+    if fallback:
+        response.headers['X-Location-Fallback'] = (
+            f'{fallback["source"]}; reason={fallback["reason"]}'
+        )
+    return response
+
+
+@app.route('/txt')
+@app.route('/txt/<city>')
+def weather_text(city: str = 'chicago') -> Response:
+    """Plain-text weather for curl users, with optional ?format= templates"""
+    resolved = resolve_location_segment(city)
+    if not resolved:
+        response = Response(f"Unknown location '{city}'\n", mimetype='text/plain')
+        response.status_code = 404
         return response
-    response = jsonify({'error': 'Failed to fetch weather data from all sources'})
-    response.status_code = 500
+
+    lat, lon, location_name = resolved
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    weather_data = location_weather(lat, lon, location_name)
+
+    if not weather_data:
+        response = Response('Failed to fetch weather data\n', mimetype='text/plain')
+        response.status_code = 500
+        return response
+
+    units, _ = requested_display()
+    format_arg = request.args.get('format')
+    if format_arg:
+        template = TEXT_FORMAT_PRESETS.get(format_arg, format_arg)
+        text = render_text_format(template, weather_data, location_name, units)
+        text += '\n'
+    else:
+        use_color = request.args.get('ansi', '1') != '0'
+        text = render_text_report(weather_data, location_name, use_color, units)
+
+    response = Response(text, mimetype='text/plain')
+    return set_weather_cache_headers(response, cache_key)
+
+
+@app.route('/render/<city>.png')
+def render_png(city: str) -> Response:
+    """The forecast drawn as a PNG for e-ink frames that can't run a browser"""
+    resolved = resolve_location_segment(city)
+    if not resolved:
+        raise LocationNotFound(f"Unknown location '{city}'")
+
+    width = request.args.get('width', DEFAULT_WIDTH, type=int)
+    height = request.args.get('height', DEFAULT_HEIGHT, type=int)
+    if not (MIN_SIZE <= width <= MAX_SIZE and MIN_SIZE <= height <= MAX_SIZE):
+        raise InvalidParameter(
+            f'width and height must be {MIN_SIZE}-{MAX_SIZE} pixels',
+            min=MIN_SIZE,
+            max=MAX_SIZE,
+        )
+    # 1-bit black and white unless the panel can show shades of gray
+    grayscale = request.args.get('grayscale', '0') == '1'
+
+    lat, lon, location_name = resolved
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    weather_data = location_weather(lat, lon, location_name)
+
+    if not weather_data:
+        raise UpstreamUnavailable('Failed to fetch weather data')
+
+    summary = weather_data.get('current', {}).get('summary') or ''
+    image = render_forecast(
+        weather_data, location_name, summary, width, height, grayscale
+    )
+    response = Response(image, mimetype='image/png')
+    return set_weather_cache_headers(response, cache_key)
+
+
+# iCalendar content lines are limited to 75 octets before folding (RFC 5545)
+ICAL_LINE_OCTETS = 75
+
+
+def escape_ical_text(text: str) -> str:
+    """Escape a TEXT property value for iCalendar"""
+    return (
+        text.replace('\\', '\\\\')
+        .replace(';', '\\;')
+        .replace(',', '\\,')
+        .replace('\n', '\\n')
+    )
+
+
+def fold_ical_line(line: str) -> str:
+    """Fold a content line so no physical line exceeds 75 octets"""
+    chunks = []
+    current = ''
+    limit = ICAL_LINE_OCTETS
+    for char in line:
+        if len((current + char).encode()) > limit:
+            chunks.append(current)
+            current = char
+            limit = ICAL_LINE_OCTETS - 1  # Continuation lines start with a space
+        else:
+            current += char
+    chunks.append(current)
+    return '\r\n '.join(chunks)
+
+
+def render_forecast_ical(
+    weather_data: dict, lat: float, lon: float, location_name: str, units: str
+) -> str:
+    """Render the daily forecast as an iCalendar feed of all-day events"""
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    unit = UNIT_LABELS[units]['temperature']
+    stamp = datetime.now(timezone.utc)
+    lines = [
+        'BEGIN:VCALENDAR',
+        'VERSION:2.0',
+        'PRODID:-//weather-dashboard//forecast//EN',
+        'CALSCALE:GREGORIAN',
+        'METHOD:PUBLISH',
+        f'X-WR-CALNAME:{escape_ical_text(f"Weather - {location_name}")}',
+        'X-PUBLISHED-TTL:PT1H',
+    ]
+
+    for index, day in enumerate(weather_data.get('daily', [])):
+        # Older cached entries have no date - count forward from today instead
+        if day.get('date'):
+            day_date = datetime.fromisoformat(day['date']).date()
+        else:
+            day_date = stamp.date() + timedelta(days=index)
+
+        icon_code = day.get('icon', 'clear-day')
+        condition = text_condition_label(icon_code)
+        symbol = TEXT_ICON_SYMBOLS[text_icon_group(icon_code)]
+        high = format_text_temperature(day.get('h'), unit)
+        low = format_text_temperature(day.get('l'), unit)
+        lines.extend(
+            [
+                'BEGIN:VEVENT',
+                # Stable UIDs let calendar apps update days in place on refresh
+                f'UID:{day_date:%Y%m%d}-{lat:.4f}{lon:+.4f}@weather-dashboard',
+                f'DTSTAMP:{stamp:%Y%m%dT%H%M%SZ}',
+                f'DTSTART;VALUE=DATE:{day_date:%Y%m%d}',
+                f'DTEND;VALUE=DATE:{day_date + timedelta(days=1):%Y%m%d}',
+                f'SUMMARY:{escape_ical_text(f"{symbol} {high} / {low} {condition}")}',
+                'DESCRIPTION:'
+                + escape_ical_text(
+                    f'High {high}, low {low}. {condition} in {location_name}.'
+                ),
+                'TRANSP:TRANSPARENT',
+                'END:VEVENT',
+            ]
+        )
+
+    lines.append('END:VCALENDAR')
+    return '\r\n'.join(fold_ical_line(line) for line in lines) + '\r\n'
+
+
+@app.route('/api/forecast.ics')
+def forecast_ical_api() -> Response:
+    """iCalendar feed of the daily forecast for calendar overlays"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    weather_data = location_weather(lat, lon, location_name)
+
+    if not weather_data:
+        raise UpstreamUnavailable('Failed to fetch weather data')
+
+    units, _ = requested_display()
+    response = Response(
+        render_forecast_ical(weather_data, lat, lon, location_name, units),
+        mimetype='text/calendar',
+    )
+    response.headers['Content-Disposition'] = 'inline; filename="forecast.ics"'
+    response.headers['Cache-Control'] = 'public, max-age=1800'
+    return with_location_fallback(response, location_fallback)
+
+
+def render_change_feed(
+    entries: list[dict], cache_key: str, location_name: str, feed_url: str
+) -> str:
+    """Render significant weather changes as an Atom feed, newest first"""
+    if entries:
+        updated = entries[0]['updated']
+    else:
+        updated = datetime.now(timezone.utc).isoformat()
+    lines = [
+        '<?xml version="1.0" encoding="utf-8"?>',
+        '<feed xmlns="http://www.w3.org/2005/Atom">',
+        f'  <id>urn:weather-dashboard:feed:{cache_key}</id>',
+        f'  <title>{html.escape(f"Weather changes - {location_name}")}</title>',
+        f'  <updated>{updated}</updated>',
+        f'  <link rel="self" href="{html.escape(feed_url)}"/>',
+        '  <author><name>Weather Dashboard</name></author>',
+    ]
+    for entry in entries:
+        lines.extend(
+            [
+                '  <entry>',
+                f'    <id>{html.escape(entry["id"])}</id>',
+                f'    <title>{html.escape(entry["title"])}</title>',
+                f'    <updated>{entry["updated"]}</updated>',
+                f'    <category term="{entry["kind"]}"/>',
+                f'    <summary>{html.escape(entry["summary"])}</summary>',
+                '  </entry>',
+            ]
+        )
+    lines.append('</feed>')
+    return '\n'.join(lines) + '\n'
+
+
+@app.route('/feed/<city>.xml')
+def weather_change_feed(city: str) -> Response:
+    """Atom feed of significant changes (alerts, temperature swings, precipitation)"""
+    resolved = resolve_location_segment(city)
+    if not resolved:
+        raise LocationNotFound(f"Unknown location '{city}'")
+
+    lat, lon, location_name = resolved
+    cache_key = location_key(lat, lon)
+
+    # Make sure recent data has been through change detection before rendering
+    location_weather(lat, lon, location_name)
+    if f'alerts_{cache_key}' not in alerts_cache:
+        alerts_data = nws_provider.get_weather(lat, lon, location_name)
+        if alerts_data:
+            store_alerts_data(lat, lon, alerts_data)
+
+    with weather_change_lock:
+        entries = list(reversed(weather_change_history.get(cache_key, [])))
+
+    response = Response(
+        render_change_feed(entries, cache_key, location_name, request.url),
+        mimetype='application/atom+xml',
+    )
+    response.headers['Cache-Control'] = 'public, max-age=300'
+    return response
+
+
+# Watch faces get compact numeric icon ids instead of icon code strings
+WATCH_ICON_IDS = {
+    'clear-day': 1,
+    'clear-night': 2,
+    'partly-cloudy-day': 3,
+    'partly-cloudy-night': 4,
+    'cloudy': 5,
+    'fog': 6,
+    'light-rain': 7,
+    'rain': 8,
+    'heavy-rain': 9,
+    'light-snow': 10,
+    'snow': 11,
+    'heavy-snow': 12,
+    'sleet': 13,
+    'hail': 14,
+    'thunderstorm': 15,
+    'wind': 16,
+}
+WATCH_PAYLOAD_BUDGET = 500  # bytes - watch HTTP clients truncate larger bodies
+WATCH_PRECIP_PROBABILITY = 50  # % chance before an hour counts as wet
+WATCH_LOCATION_LENGTH = 24
+
+
+def minutes_until_precipitation(weather_data: dict) -> int | None:
+    """Estimate minutes until precipitation from current and hourly data"""
+    current = weather_data.get('current', {})
+    if 'precip_starts_in_minutes' in current:
+        return current['precip_starts_in_minutes']  # type: ignore[no-any-return]
+    if current.get('precipitation_rate'):
+        return 0
+    # Hourly entries start at the current hour, so the index is hours from now
+    for index, hour in enumerate(weather_data.get('hourly', [])):
+        if (hour.get('rain') or 0) >= WATCH_PRECIP_PROBABILITY:
+            return index * 60
+    return None
+
+
+def build_watch_payload(weather_data: dict, location_name: str, units: str) -> str:
+    """Build the compact JSON body for watch faces, within the size budget"""
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    current = weather_data.get('current', {})
+    today = (weather_data.get('daily') or [{}])[0]
+    payload = {
+        'loc': location_name[:WATCH_LOCATION_LENGTH],
+        't': current.get('temperature'),
+        'i': WATCH_ICON_IDS.get(current.get('icon', ''), 0),
+        'p': minutes_until_precipitation(weather_data),
+        'h': today.get('h'),
+        'l': today.get('l'),
+        'u': UNIT_LABELS[units]['temperature'].removeprefix('°'),
+    }
+    body = json.dumps(payload, separators=(',', ':'), ensure_ascii=False)
+    if len(body.encode()) > WATCH_PAYLOAD_BUDGET:
+        # Only the location name can grow - drop it rather than blow the budget
+        payload.pop('loc')
+        body = json.dumps(payload, separators=(',', ':'))
+    return body
+
+
+@app.route('/api/watch')
+def watch_api() -> Response:
+    """Ultra-compact current conditions for watch faces"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    weather_data = location_weather(lat, lon, location_name)
+
+    if not weather_data:
+        raise UpstreamUnavailable('Failed to fetch weather data')
+
+    units, _ = requested_display()
+    body = build_watch_payload(weather_data, location_name, units)
+    response = Response(body, mimetype='application/json')
+    # Watches poll on their own schedule - let them and any proxy reuse data
+    response.headers['Cache-Control'] = (
+        'public, max-age=900, stale-while-revalidate=3600'
+    )
+    response.headers['ETag'] = f'"{hash(body)}"'
+    return with_location_fallback(response, location_fallback)
+
+
+# Hours each home-screen widget size has room for (Scriptable / KWGT layouts)
+WIDGET_HOURS = {'small': 3, 'medium': 6}
+DEFAULT_WIDGET_SIZE = 'medium'
+
+
+def whole_degrees(value: float | None) -> int | None:
+    """A temperature rounded for display, so widgets needn't format numbers"""
+    return round(value) if value is not None else None
+
+
+def build_widget_payload(
+    weather_data: dict, location_name: str, size: str, units: str
+) -> dict[str, Any]:
+    """Flat, pre-rounded conditions for home-screen widgets (imperial input)"""
+    summary = build_narrative(weather_data, 'brief', units)
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    current = weather_data.get('current', {})
+    today = (weather_data.get('daily') or [{}])[0]
+    return {
+        'location': location_name,
+        'temperature': whole_degrees(current.get('temperature')),
+        'icon': current.get('icon'),
+        'high': whole_degrees(today.get('h')),
+        'low': whole_degrees(today.get('l')),
+        'unit': UNIT_LABELS[units]['temperature'],
+        'summary': summary,
+        'hours': [
+            {
+                'label': hour.get('t'),
+                'temperature': whole_degrees(hour.get('temp')),
+                'icon': hour.get('icon'),
+                'precipitation_probability': hour.get('rain'),
+            }
+            for hour in weather_data.get('hourly', [])[: WIDGET_HOURS[size]]
+        ],
+    }
+
+
+@app.route('/api/widget')
+def widget_api() -> Response:
+    """Compact current conditions and next hours for iOS/Android home widgets"""
+    size = request.args.get('size', DEFAULT_WIDGET_SIZE)
+    if size not in WIDGET_HOURS:
+        raise InvalidParameter('Invalid size', sizes=list(WIDGET_HOURS))
+
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    weather_data = location_weather(lat, lon, location_name)
+    if not weather_data:
+        raise UpstreamUnavailable('Failed to fetch weather data')
+
+    units, time_format = requested_display()
+    if time_format == '24h':
+        weather_data = label_hours(weather_data)
+    body = build_widget_payload(weather_data, location_name, size, units)
+    if location_fallback:
+        body['location_fallback'] = location_fallback
+    response = with_location_fallback(jsonify(body), location_fallback)
+    return set_weather_cache_headers(response, f'{lat:.4f},{lon:.4f}')
+
+
+# Home Assistant weather conditions for each dashboard icon
+HA_CONDITIONS = {
+    'clear-day': 'sunny',
+    'clear-night': 'clear-night',
+    'partly-cloudy-day': 'partlycloudy',
+    'partly-cloudy-night': 'partlycloudy',
+    'cloudy': 'cloudy',
+    'fog': 'fog',
+    'light-rain': 'rainy',
+    'rain': 'rainy',
+    'heavy-rain': 'pouring',
+    'light-snow': 'snowy',
+    'snow': 'snowy',
+    'heavy-snow': 'snowy',
+    'sleet': 'snowy-rainy',
+    'hail': 'hail',
+    'thunderstorm': 'lightning-rainy',
+    'wind': 'windy',
+}
+HA_UNKNOWN_CONDITION = 'exceptional'
+
+
+def ha_condition(icon: str | None) -> str:
+    """Translate a dashboard icon into a Home Assistant condition string"""
+    return HA_CONDITIONS.get(icon or '', HA_UNKNOWN_CONDITION)
+
+
+def build_ha_weather(weather_data: dict, units: str) -> dict[str, Any]:
+    """Shape weather as the attributes of a Home Assistant weather entity"""
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    labels = UNIT_LABELS[units]
+    current = weather_data.get('current', {})
+    return {
+        'condition': ha_condition(current.get('icon')),
+        'temperature': current.get('temperature'),
+        'apparent_temperature': current.get('feels_like'),
+        'dew_point': current.get('dew_point'),
+        'humidity': current.get('humidity'),
+        'pressure': current.get('pressure'),
+        'wind_speed': current.get('wind_speed'),
+        'wind_gust_speed': current.get('wind_gust'),
+        'wind_bearing': current.get('wind_direction'),
+        'uv_index': current.get('uv_index'),
+        'temperature_unit': labels['temperature'],
+        'pressure_unit': labels['pressure'],
+        'wind_speed_unit': labels['wind_speed'],
+        'precipitation_unit': labels['precipitation'],
+        'attribution': f'Weather data from {weather_data.get("provider", "unknown")}',
+        'forecast': [
+            {
+                'datetime': day.get('date'),
+                'condition': ha_condition(day.get('icon')),
+                'temperature': day.get('h'),
+                'templow': day.get('l'),
+            }
+            for day in weather_data.get('daily', [])
+        ],
+        'forecast_hourly': [
+            {
+                'datetime': hour.get('time'),
+                'condition': ha_condition(hour.get('icon')),
+                'temperature': hour.get('temp'),
+                'precipitation_probability': hour.get('rain'),
+            }
+            for hour in weather_data.get('hourly', [])
+        ],
+    }
+
+
+@app.route('/api/ha/weather')
+def ha_weather_api() -> Response:
+    """Weather in the attribute schema Home Assistant weather entities expect"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    weather_data = location_weather(lat, lon, location_name)
+
+    if not weather_data:
+        raise UpstreamUnavailable('Weather data unavailable')
+
+    units, _ = requested_display()
+    body = build_ha_weather(weather_data, units)
+    if location_fallback:
+        body['location_fallback'] = location_fallback
+    response = with_location_fallback(jsonify(body), location_fallback)
+    return set_weather_cache_headers(response, cache_key)
+
+
+# Export columns per forecast range: (column name, forecast key, unit), where a
+# unit named in UNIT_LABELS is labelled in the units the export is served in
+EXPORT_COLUMNS: dict[str, list[tuple[str, str, str | None]]] = {
+    'hourly': [
+        ('time', 'time', None),
+        ('label', 't', None),
+        ('temperature', 'temp', 'temperature'),
+        ('precipitation_probability', 'rain', '%'),
+        ('pressure', 'pressure', 'pressure'),
+        ('icon', 'icon', None),
+        ('description', 'desc', None),
+    ],
+    'daily': [
+        ('date', 'date', None),
+        ('day', 'd', None),
+        ('high', 'h', 'temperature'),
+        ('low', 'l', 'temperature'),
+        ('icon', 'icon', None),
+    ],
+}
+EXPORT_FORMATS = {'csv': 'text/csv', 'jsonl': 'application/x-ndjson'}
+
+
+def flatten_forecast_rows(
+    weather_data: dict, range_name: str, location_name: str, units: str
+) -> tuple[list[str], list[dict]]:
+    """Flatten a forecast array into rows, with a unit column after each measure"""
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    labels = UNIT_LABELS[units]
+    header = ['location']
+    for name, _key, unit in EXPORT_COLUMNS[range_name]:
+        header.append(name)
+        if unit:
+            header.append(f'{name}_unit')
+
+    rows = []
+    for entry in weather_data.get(range_name, []):
+        row: dict[str, Any] = {'location': location_name}
+        for name, key, unit in EXPORT_COLUMNS[range_name]:
+            row[name] = entry.get(key)
+            if unit:
+                row[f'{name}_unit'] = labels.get(unit, unit)
+        rows.append(row)
+    return header, rows
+
+
+def sanitize_csv_value(value: Any) -> Any:
+    """Stop spreadsheets from treating text cells as formulas"""
+    if isinstance(value, str) and value.startswith(('=', '+', '-', '@')):
+        return f"'{value}"
+    return value
+
+
+@app.route('/api/charts')
+def charts_api() -> Response:
+    """API endpoint for plot-ready hourly series, so clients don't bin their own"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    weather_data = location_weather(lat, lon, location_name)
+    if not weather_data:
+        raise UpstreamUnavailable('Failed to fetch weather data')
+
+    units, time_format = requested_display()
+    if units == 'metric':
+        weather_data = convert_to_metric(weather_data)
+    if time_format == '24h':
+        weather_data = label_hours(weather_data)
+    body = {
+        'location': location_name,
+        **build_charts(
+            weather_data.get('hourly', []),
+            UNIT_LABELS[units],
+            datetime.now(timezone.utc),
+        ),
+    }
+    if location_fallback:
+        body['location_fallback'] = location_fallback
+    response = with_location_fallback(jsonify(body), location_fallback)
+    return set_weather_cache_headers(response, f'{lat:.4f},{lon:.4f}')
+
+
+@app.route('/api/summary')
+def summary_api() -> Response:
+    """API endpoint for a spoken-word forecast paragraph"""
+    style = request.args.get('style', DEFAULT_NARRATIVE_STYLE)
+    if style not in NARRATIVE_STYLES:
+        raise InvalidParameter('Invalid style', styles=list(NARRATIVE_STYLES))
+
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    weather_data = location_weather(lat, lon, location_name)
+    if not weather_data:
+        raise UpstreamUnavailable('Failed to fetch weather data')
+
+    units, time_format = requested_display()
+    if time_format == '24h':
+        weather_data = label_hours(weather_data)
+    body = {
+        'location': location_name,
+        'style': style,
+        'summary': build_narrative(weather_data, style, units),
+    }
+    if location_fallback:
+        body['location_fallback'] = location_fallback
+    response = with_location_fallback(jsonify(body), location_fallback)
+    return set_weather_cache_headers(response, f'{lat:.4f},{lon:.4f}')
+
+
+@app.route('/api/export')
+def export_api() -> Response:
+    """API endpoint exporting the forecast as CSV or JSON lines"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+    export_format = request.args.get('format', 'csv')
+    range_name = request.args.get('range', 'hourly')
+
+    if export_format not in EXPORT_FORMATS or range_name not in EXPORT_COLUMNS:
+        raise InvalidParameter(
+            'Invalid format or range',
+            formats=list(EXPORT_FORMATS),
+            ranges=list(EXPORT_COLUMNS),
+        )
+
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    weather_data = location_weather(lat, lon, location_name)
+
+    if not weather_data:
+        raise UpstreamUnavailable('Failed to fetch weather data')
+
+    units, _ = requested_display()
+    header, rows = flatten_forecast_rows(
+        weather_data, range_name, location_name, units
+    )
+    if export_format == 'csv':
+        output = io.StringIO()
+        writer = csv.DictWriter(output, fieldnames=header)
+        writer.writeheader()
+        writer.writerows(
+            {key: sanitize_csv_value(value) for key, value in row.items()}
+            for row in rows
+        )
+        body = output.getvalue()
+    else:
+        body = ''.join(json.dumps(row, ensure_ascii=False) + '\n' for row in rows)
+
+    response = Response(body, mimetype=EXPORT_FORMATS[export_format])
+    response.headers['Content-Disposition'] = (
+        f'attachment; filename="forecast-{range_name}.{export_format}"'
+    )
+    response = with_location_fallback(response, location_fallback)
+    return set_weather_cache_headers(response, cache_key)
+
+
+# Commute corridors: ordered points pre-warmed in the cache around departure time
+CORRIDOR_PREWARM_LEAD_MINUTES = 20  # Start warming this long before departure
+CORRIDOR_PREWARM_GRACE_MINUTES = 30  # Keep warming this long after departure
+CORRIDOR_CHECK_SECONDS = 60  # Well inside the weather cache TTL
+CORRIDOR_ROUTE_HOURS = 3
+WEEKDAY_NAMES = ['mon', 'tue', 'wed', 'thu', 'fri', 'sat', 'sun']
+
+
+def load_commute_corridors(raw_config: str | None) -> dict[str, dict]:
+    """Parse COMMUTE_CORRIDORS JSON into corridor configs keyed by name"""
+    if not raw_config:
+        return {}
+    try:
+        entries = json.loads(raw_config)
+    except json.JSONDecodeError as e:
+        print(f'❌ Invalid COMMUTE_CORRIDORS JSON: {e}')
+        return {}
+
+    corridors = {}
+    for entry in entries:
+        try:
+            hour, minute = (int(part) for part in entry['departure'].split(':'))
+            corridor = {
+                'name': entry['name'],
+                'departure': (hour, minute),
+                'timezone': zoneinfo.ZoneInfo(entry.get('timezone', 'America/Chicago')),
+                'days': {
+                    WEEKDAY_NAMES.index(day.lower()[:3])
+                    for day in entry.get('days', WEEKDAY_NAMES)
+                },
+                'points': [
+                    (float(point['lat']), float(point['lon']), point.get('name', ''))
+                    for point in entry['points']
+                ],
+            }
+        except (KeyError, TypeError, ValueError, zoneinfo.ZoneInfoNotFoundError) as e:
+            print(f'⚠️  Skipping invalid commute corridor {entry!r}: {e}')
+            continue
+        corridors[corridor['name']] = corridor
+    return corridors
+
+
+commute_corridors = load_commute_corridors(os.getenv('COMMUTE_CORRIDORS'))
+
+
+def corridor_in_prewarm_window(corridor: dict, now: datetime) -> bool:
+    """Check whether a corridor's departure is close enough to keep it warm"""
+    local_now = now.astimezone(corridor['timezone'])
+    if local_now.weekday() not in corridor['days']:
+        return False
+    hour, minute = corridor['departure']
+    departure = local_now.replace(hour=hour, minute=minute, second=0, microsecond=0)
+    window_start = departure - timedelta(minutes=CORRIDOR_PREWARM_LEAD_MINUTES)
+    window_end = departure + timedelta(minutes=CORRIDOR_PREWARM_GRACE_MINUTES)
+    return window_start <= local_now <= window_end
+
+
+def prewarm_corridor(corridor: dict) -> int:
+    """Fetch any corridor points missing from the cache, returning how many"""
+    warmed = 0
+    for lat, lon, name in corridor['points']:
+        cache_key = f'{lat:.4f},{lon:.4f}'
+        if weather_cache_key(cache_key) in weather_cache:
+            continue
+        weather_data = weather_manager.get_weather(lat, lon, name or None)
+        if weather_data:
+            store_weather_data(cache_key, weather_data)
+            warmed += 1
+    if warmed:
+        print(f'🚗 Pre-warmed {warmed} points for corridor {corridor["name"]}')
+    return warmed
+
+
+def run_corridor_prewarm_scheduler() -> None:
+    """Background loop keeping commute corridors cached around departure times"""
+    while True:
+        now = datetime.now(timezone.utc)
+        for corridor in commute_corridors.values():
+            if corridor_in_prewarm_window(corridor, now):
+                try:
+                    prewarm_corridor(corridor)
+                except Exception as e:
+                    print(f'❌ Corridor pre-warm error for {corridor["name"]}: {e}')
+        socketio.sleep(CORRIDOR_CHECK_SECONDS)
+
+
+def tracked_weather(lat: float, lon: float, location: str | None) -> dict | None:
+    """A watched location's weather, cached or freshly fetched (snake_case)"""
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    entry_key = weather_cache_key(cache_key)
+    if entry_key in weather_cache:
+        weather_data = weather_cache[entry_key]
+    else:
+        weather_data = weather_manager.get_weather(lat, lon, location)
+        if not weather_data:
+            return None
+        store_weather_data(cache_key, weather_data)
+    return serialize_weather(weather_data)
+
+
+def check_notification_rules() -> int:
+    """Evaluate every rule against its location's weather, returning sends"""
+    sent = 0
+    for rule in notification_rules.list_rules():
+        weather_data = tracked_weather(rule['lat'], rule['lon'], rule['location'])
+        if not weather_data:
+            continue
+
+        notification = notification_rules.check(rule['id'], weather_data)
+        if notification:
+            if notification.get('icon'):
+                notification['symbol'] = TEXT_ICON_SYMBOLS[
+                    text_icon_group(notification['icon'])
+                ]
+            notification_dispatcher.dispatch(notification, rule['channels'])
+            sent += 1
+    return sent
+
+
+def deliver_webhook(subscription: dict[str, Any], event: dict[str, Any]) -> bool:
+    """POST one event, recording how it went on the subscription"""
+    try:
+        status = deliver(subscription, event)
+    except requests.RequestException as e:
+        response = getattr(e, 'response', None)
+        status = response.status_code if response is not None else None
+        print(f'❌ Webhook {subscription["name"]} failed: {e}')
+        webhook_subscriptions.record_delivery(subscription['id'], status, str(e))
+        return False
+    print(f'🪝 Webhook {subscription["name"]}: {event["event"]}')
+    webhook_subscriptions.record_delivery(subscription['id'], status)
+    return True
+
+
+def check_webhooks() -> int:
+    """Compare each subscription's location with what it last saw, returning sends"""
+    sent = 0
+    for listed in webhook_subscriptions.list_subscriptions():
+        weather_data = tracked_weather(listed['lat'], listed['lon'], listed['location'])
+        if not weather_data:
+            continue
+        subscription = webhook_subscriptions.get(listed['id'])
+        for event in webhook_subscriptions.check(listed['id'], weather_data):
+            if subscription and deliver_webhook(subscription, event):
+                sent += 1
+    return sent
+
+
+def run_notification_scheduler() -> None:
+    """Background loop evaluating notification rules and webhooks after refreshes"""
+    while True:
+        try:
+            check_notification_rules()
+        except Exception as e:
+            print(f'❌ Notification rule check error: {e}')
+        try:
+            check_webhooks()
+        except Exception as e:
+            print(f'❌ Webhook check error: {e}')
+        socketio.sleep(NOTIFICATION_CHECK_SECONDS)
+
+
+def compact_history() -> None:
+    """Compact and prune the history store, logging rather than raising"""
+    try:
+        counts = history_store.compact()
+    except sqlite3.Error as e:
+        print(f'❌ History compaction error: {e}')
+        return
+    if any(counts.values()):
+        print(
+            f'🧹 Compacted history into {counts["days"]} days, deleting '
+            f'{counts["observations"]} observations, {counts["daily"]} days and '
+            f'{counts["forecasts"]} forecasts'
+        )
+
+
+def run_history_compactor(schedule: CronSchedule) -> None:
+    """Background loop compacting the history at startup and then on schedule"""
+    compact_history()
+    next_run = schedule.next_after(datetime.now(timezone.utc))
+    while True:
+        now = datetime.now(timezone.utc)
+        if now < next_run:
+            socketio.sleep(
+                min(HISTORY_COMPACT_CHECK_SECONDS, (next_run - now).total_seconds())
+            )
+            continue
+        compact_history()
+        next_run = schedule.next_after(now)
+
+
+def iso_timestamp(seconds: float) -> str:
+    """A Unix time as an ISO 8601 UTC string"""
+    return datetime.fromtimestamp(seconds, tz=timezone.utc).isoformat()
+
+
+def prefetch_targets() -> list[tuple[float, float, str]]:
+    """Favorites plus PREFETCH_LOCATIONS, each place once"""
+    targets: dict[str, tuple[float, float, str]] = {}
+    for favorite in favorites_store.list_favorites():
+        lat, lon = favorite['lat'], favorite['lon']
+        targets.setdefault(location_key(lat, lon), (lat, lon, favorite['name']))
+    for entry in PREFETCH_LOCATIONS:
+        resolved = resolve_location_segment(entry)
+        if not resolved:
+            print(f'⚠️  Skipping unknown prefetch location {entry!r}')
+            continue
+        targets.setdefault(location_key(resolved[0], resolved[1]), resolved)
+    return list(targets.values())
+
+
+def prefetch_due_in(cache_key: str) -> float:
+    """Seconds until a location should be refetched - zero or less means now"""
+    entry_key = weather_cache_key(cache_key)
+    metadata = weather_cache_metadata.get(entry_key)
+    if not metadata or entry_key not in weather_cache:
+        return 0
+    expires_at = metadata['fetched_at'] + metadata.get('ttl', WEATHER_CACHE_TTL)
+    return float(expires_at - PREFETCH_LEAD_SECONDS - time.time())
+
+
+def prefetch_locations() -> int:
+    """Refetch every prefetch target that's missing or nearly expired"""
+    started = time.time()
+    refreshed = failed = 0
+    next_due = float(PREFETCH_MAX_SLEEP_SECONDS)
+    for lat, lon, name in prefetch_targets():
+        cache_key = location_key(lat, lon)
+        due_in = prefetch_due_in(cache_key)
+        if due_in > secrets.randbelow(PREFETCH_JITTER_SECONDS + 1):
+            next_due = min(next_due, due_in)
+            continue
+        weather_data = weather_manager.get_weather(lat, lon, name)
+        if weather_data:
+            store_weather_data(cache_key, weather_data)
+            refreshed += 1
+        else:
+            failed += 1
+    if refreshed:
+        print(f'⭐ Pre-fetched weather for {refreshed} locations')
+
+    prefetch_status['last_run'] = {
+        'started_at': iso_timestamp(started),
+        'duration_ms': round((time.time() - started) * 1000),
+        'refreshed': refreshed,
+        'failed': failed,
+    }
+    next_run = time.time() + max(PREFETCH_MIN_SLEEP_SECONDS, next_due)
+    prefetch_status['next_run'] = next_run
+    return refreshed
+
+
+def run_prefetch_scheduler() -> None:
+    """Background loop keeping prefetch targets in the weather cache"""
+    prefetch_status['running'] = True
+    while True:
+        try:
+            prefetch_locations()
+        except Exception as e:
+            print(f'❌ Prefetch error: {e}')
+            prefetch_status['last_run'] = {
+                'started_at': iso_timestamp(time.time()),
+                'error': str(e),
+            }
+            prefetch_status['next_run'] = time.time() + PREFETCH_MAX_SLEEP_SECONDS
+        socketio.sleep(max(0, prefetch_status['next_run'] - time.time()))
+
+
+def run_provider_health_monitor() -> None:
+    """Background loop probing providers and announcing primary changes"""
+    primary = weather_manager.primary_provider
+    while True:
+        try:
+            weather_manager.run_health_checks()
+        except Exception as e:
+            print(f'❌ Provider health check error: {e}')
+        # Failover happens on the request path; either way clients hear of it here
+        if weather_manager.primary_provider != primary:
+            primary = weather_manager.primary_provider
+            socketio.emit(
+                'provider_switched',
+                {
+                    'provider': primary,
+                    'provider_info': weather_manager.get_provider_info(),
+                },
+            )
+        socketio.sleep(weather_manager.health_interval)
+
+
+def digest_locations() -> list[tuple[float, float, str]]:
+    """Where digests go: the saved favorites, or DIGEST_LOCATIONS without any"""
+    favorites = favorites_store.list_favorites()
+    if favorites:
+        return [(fav['lat'], fav['lon'], fav['name']) for fav in favorites]
+
+    locations = []
+    for entry in DIGEST_LOCATIONS:
+        resolved = resolve_location_segment(entry)
+        if not resolved:
+            print(f'⚠️  Skipping unknown digest location {entry!r}')
+            continue
+        locations.append(resolved)
+    return locations
+
+
+def send_morning_digests() -> int:
+    """Compose and send the digest for every digest location, returning sends"""
+    sent = 0
+    for lat, lon, location_name in digest_locations():
+        cache_key = f'{lat:.4f},{lon:.4f}'
+        weather_data = location_weather(lat, lon, location_name)
+        if not weather_data:
+            print(f'❌ No weather for the {location_name} digest')
+            continue
+
+        if f'alerts_{cache_key}' not in alerts_cache:
+            alerts_data = nws_provider.get_weather(lat, lon, location_name)
+            if alerts_data:
+                store_alerts_data(lat, lon, alerts_data)
+        alerts_data = alerts_cache.get(f'alerts_{cache_key}') or {}
+
+        notification = build_digest(
+            location_name,
+            serialize_weather(weather_data),
+            list(alerts_by_id(alerts_data).values()),
+            get_air_quality(lat, lon, location_name),
+        )
+        if notification.get('icon'):
+            notification['symbol'] = TEXT_ICON_SYMBOLS[
+                text_icon_group(notification['icon'])
+            ]
+        if notification_dispatcher.dispatch(notification, DIGEST_CHANNELS):
+            sent += 1
+    return sent
+
+
+def run_digest_scheduler(schedule: CronSchedule) -> None:
+    """Background loop sending the morning digest whenever the schedule fires"""
+    next_run = schedule.next_after(datetime.now(NOTIFICATION_TIMEZONE))
+    print(f'📬 Next forecast digest at {next_run.isoformat()}')
+    while True:
+        now = datetime.now(NOTIFICATION_TIMEZONE)
+        if now < next_run:
+            # Short naps so suspend/resume or clock corrections can't oversleep
+            socketio.sleep(min(DIGEST_CHECK_SECONDS, (next_run - now).total_seconds()))
+            continue
+        try:
+            send_morning_digests()
+        except Exception as e:
+            print(f'❌ Forecast digest error: {e}')
+        next_run = schedule.next_after(now)
+
+
+@app.route('/api/route')
+def route_weather_api() -> Response:
+    """API endpoint for weather along a configured commute corridor"""
+    corridor_name = request.args.get('corridor', '')
+    corridor = commute_corridors.get(corridor_name)
+    if not corridor:
+        raise NotFound(
+            f"Unknown corridor '{corridor_name}'", corridors=list(commute_corridors)
+        )
+
+    points = []
+    all_cached = True
+    for lat, lon, name in corridor['points']:
+        cache_key = f'{lat:.4f},{lon:.4f}'
+        weather_data = get_cached_weather(cache_key, lat, lon, name or None)
+        if weather_data is None:
+            all_cached = False
+            weather_data = weather_manager.get_weather(lat, lon, name or None)
+            if weather_data:
+                store_weather_data(cache_key, weather_data)
+
+        weather_data = serialize_weather(weather_data or {})
+        points.append(
+            {
+                'lat': lat,
+                'lon': lon,
+                'name': name,
+                'current': weather_data.get('current'),
+                'hourly': weather_data.get('hourly', [])[:CORRIDOR_ROUTE_HOURS],
+            }
+        )
+
+    response = jsonify(
+        {
+            'corridor': corridor_name,
+            'points': points,
+            'server_time': datetime.now(timezone.utc).isoformat(),
+        }
+    )
+    response.headers['X-Cache'] = 'HIT' if all_cached else 'MISS'
+    response.headers['Cache-Control'] = 'public, max-age=180'
     return response
 
 
 @app.route('/api/weather/alerts')
 def weather_alerts_api() -> Response:
-    """API endpoint for weather alerts and warnings from National Weather Service"""
-    # Get lat/lon from URL parameters
-    lat = request.args.get('lat', type=float)
-    lon = request.args.get('lon', type=float)
-    location_name = request.args.get('location', 'Chicago')
-
-    # Default to Chicago if no coordinates provided
-    if not lat or not lon:
-        lat = CHICAGO_LAT
-        lon = CHICAGO_LON
+    """API endpoint for weather alerts and warnings from National Weather Service"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
 
     # Create cache key
     cache_key = f'alerts_{lat:.4f},{lon:.4f}'
@@ -607,7 +3763,7 @@ def weather_alerts_api() -> Response:
     if cache_key in alerts_cache:
         print(f'🚨 Returning cached alerts for {lat:.4f},{lon:.4f}')
         cached_data = alerts_cache[cache_key]
-        response = jsonify(cached_data)
+        response = jsonify_with_fallback(cached_data, location_fallback)
         response.headers['Cache-Control'] = 'public, max-age=300'
         etag_value = hash(str(lat) + str(lon) + str(int(time.time() // 600)))
         response.headers['ETag'] = f'"{etag_value}"'
@@ -619,23 +3775,33 @@ def weather_alerts_api() -> Response:
 
     if alerts_data:
         # Cache the result
-        alerts_cache[cache_key] = alerts_data
+        store_alerts_data(lat, lon, alerts_data)
         print(f'💾 Cached alerts data for {cache_key}')
 
-        response = jsonify(alerts_data)
+        response = jsonify_with_fallback(alerts_data, location_fallback)
         response.headers['Cache-Control'] = 'public, max-age=300'
         etag_value = hash(str(lat) + str(lon) + str(int(time.time() // 600)))
         response.headers['ETag'] = f'"{etag_value}"'
         return response
 
-    response = jsonify(
-        {
-            'error': 'Failed to fetch weather alerts',
-            'alerts': {'active_count': 0, 'alerts': [], 'has_warnings': False},
-        }
+    raise ProviderFailed(
+        'Failed to fetch weather alerts',
+        nws_provider.name,
+        alerts={'active_count': 0, 'alerts': [], 'has_warnings': False},
     )
-    response.status_code = 500
-    return response
+
+
+# The radar body clients get back when there are no frames to show
+UNAVAILABLE_RADAR = {
+    'available': False,
+    'frames': [],
+    'animation_metadata': {
+        'total_frames': 0,
+        'historical_frames': 0,
+        'current_frame': 0,
+        'forecast_frames': 0,
+    },
+}
 
 
 @app.route('/api/radar')
@@ -643,33 +3809,15 @@ def radar_api() -> Response:
     """API endpoint for precipitation radar tiles and animation data"""
     # Check if radar provider is available
     if not radar_provider:
-        response = jsonify(
-            {
-                'error': 'Radar service unavailable - OpenWeatherMap API key required',
-                'radar': {
-                    'available': False,
-                    'frames': [],
-                    'animation_metadata': {
-                        'total_frames': 0,
-                        'historical_frames': 0,
-                        'current_frame': 0,
-                        'forecast_frames': 0,
-                    },
-                },
-            }
+        raise ProviderNotConfigured(
+            'Radar service unavailable - OpenWeatherMap API key required',
+            radar=UNAVAILABLE_RADAR,
         )
-        response.status_code = 503
-        return response
-
-    # Get lat/lon from URL parameters
-    lat = request.args.get('lat', type=float)
-    lon = request.args.get('lon', type=float)
-    location_name = request.args.get('location', 'Chicago')
 
-    # Default to Chicago if no coordinates provided
-    if not lat or not lon:
-        lat = CHICAGO_LAT
-        lon = CHICAGO_LON
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
 
     # Create cache key
     cache_key = f'radar_{lat:.4f},{lon:.4f}'
@@ -678,7 +3826,7 @@ def radar_api() -> Response:
     if cache_key in radar_cache:
         print(f'🌧️ Returning cached radar for {lat:.4f},{lon:.4f}')
         cached_data = radar_cache[cache_key]
-        response = jsonify(cached_data)
+        response = jsonify_with_fallback(cached_data, location_fallback)
         response.headers['Cache-Control'] = 'public, max-age=600'
         etag_value = hash(str(lat) + str(lon) + str(int(time.time() // 600)))
         response.headers['ETag'] = f'"{etag_value}"'
@@ -693,43 +3841,24 @@ def radar_api() -> Response:
         radar_cache[cache_key] = radar_data
         print(f'💾 Cached radar data for {cache_key}')
 
-        response = jsonify(radar_data)
+        response = jsonify_with_fallback(radar_data, location_fallback)
         response.headers['Cache-Control'] = 'public, max-age=600'
         etag_value = hash(str(lat) + str(lon) + str(int(time.time() // 600)))
         response.headers['ETag'] = f'"{etag_value}"'
         return response
 
-    response = jsonify(
-        {
-            'error': 'Failed to fetch radar data',
-            'radar': {
-                'available': False,
-                'frames': [],
-                'animation_metadata': {
-                    'total_frames': 0,
-                    'historical_frames': 0,
-                    'current_frame': 0,
-                    'forecast_frames': 0,
-                },
-            },
-        }
+    raise ProviderFailed(
+        'Failed to fetch radar data', radar_provider.name, radar=UNAVAILABLE_RADAR
     )
-    response.status_code = 500
-    return response
 
 
 @app.route('/api/clothing')
 def clothing_recommendations_api() -> Response:
     """API endpoint for smart clothing recommendations based on weather conditions"""
-    # Get lat/lon from URL parameters
-    lat = request.args.get('lat', type=float)
-    lon = request.args.get('lon', type=float)
-    location_name = request.args.get('location', 'Chicago')
-
-    # Default to Chicago if no coordinates provided
-    if not lat or not lon:
-        lat = CHICAGO_LAT
-        lon = CHICAGO_LON
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
 
     # Create cache key
     cache_key = f'clothing_{lat:.4f},{lon:.4f}'
@@ -738,7 +3867,7 @@ def clothing_recommendations_api() -> Response:
     if cache_key in clothing_cache:
         print(f'👔 Returning cached clothing recommendations for {lat:.4f},{lon:.4f}')
         cached_data = clothing_cache[cache_key]
-        response = jsonify(cached_data)
+        response = jsonify_with_fallback(cached_data, location_fallback)
         response.headers['Cache-Control'] = 'public, max-age=1800'
         etag_value = hash(str(lat) + str(lon) + str(int(time.time() // 1800)))
         response.headers['ETag'] = f'"{etag_value}"'
@@ -749,24 +3878,20 @@ def clothing_recommendations_api() -> Response:
     weather_data = weather_manager.get_weather(lat, lon, location_name)
 
     if not weather_data:
-        response = jsonify(
-            {
-                'error': 'Unable to get weather data for clothing recommendations',
-                'clothing': {
-                    'recommendations': {
-                        'primary_suggestion': (
-                            'Weather data unavailable - dress according to season'
-                        ),
-                        'items': [],
-                        'warnings': ['Weather data unavailable'],
-                        'comfort_tips': [],
-                        'activity_specific': {},
-                    }
-                },
-            }
+        raise UpstreamUnavailable(
+            'Unable to get weather data for clothing recommendations',
+            clothing={
+                'recommendations': {
+                    'primary_suggestion': (
+                        'Weather data unavailable - dress according to season'
+                    ),
+                    'items': [],
+                    'warnings': ['Weather data unavailable'],
+                    'comfort_tips': [],
+                    'activity_specific': {},
+                }
+            },
         )
-        response.status_code = 503
-        return response
 
     # Generate clothing recommendations based on weather data
     clothing_data = clothing_provider.process_weather_data(weather_data, location_name)
@@ -776,30 +3901,26 @@ def clothing_recommendations_api() -> Response:
         clothing_cache[cache_key] = clothing_data
         print(f'💾 Cached clothing recommendations for {cache_key}')
 
-        response = jsonify(clothing_data)
+        response = jsonify_with_fallback(clothing_data, location_fallback)
         response.headers['Cache-Control'] = 'public, max-age=1800'
         etag_value = hash(str(lat) + str(lon) + str(int(time.time() // 1800)))
         response.headers['ETag'] = f'"{etag_value}"'
         return response
 
-    response = jsonify(
-        {
-            'error': 'Failed to generate clothing recommendations',
-            'clothing': {
-                'recommendations': {
-                    'primary_suggestion': (
-                        'Unable to generate recommendations - dress comfortably'
-                    ),
-                    'items': [],
-                    'warnings': ['Recommendation system unavailable'],
-                    'comfort_tips': [],
-                    'activity_specific': {},
-                }
-            },
-        }
+    raise WeatherError(
+        'Failed to generate clothing recommendations',
+        clothing={
+            'recommendations': {
+                'primary_suggestion': (
+                    'Unable to generate recommendations - dress comfortably'
+                ),
+                'items': [],
+                'warnings': ['Recommendation system unavailable'],
+                'comfort_tips': [],
+                'activity_specific': {},
+            }
+        },
     )
-    response.status_code = 500
-    return response
 
 
 @app.route('/api/solar')
@@ -812,7 +3933,7 @@ def solar_data_api() -> Response:
         date_str = request.args.get('date')  # Optional specific date
 
         if lat == 0 and lon == 0:
-            return jsonify({'error': 'Valid latitude and longitude required'}), 400
+            raise InvalidLocation('Valid latitude and longitude required')
 
         # Create cache key
         cache_key = f'solar_{lat}_{lon}_{date_str or "today"}'
@@ -832,7 +3953,7 @@ def solar_data_api() -> Response:
         # Get weather data first for timezone
         weather_data = weather_manager.get_weather(lat, lon, location_name)
         if not weather_data:
-            return jsonify({'error': 'Failed to get weather data for timezone'}), 500
+            raise UpstreamUnavailable('Failed to get weather data for timezone')
 
         # Extract timezone from weather data
         tz_name = weather_data.get('timezone', 'UTC')
@@ -862,38 +3983,125 @@ def solar_data_api() -> Response:
             response.headers['ETag'] = f'"{etag_value}"'
             return response
 
+    except WeatherError:
+        raise
     except ValueError:
-        return jsonify({'error': 'Invalid latitude or longitude format'}), 400
+        raise InvalidLocation('Invalid latitude or longitude format') from None
     except Exception as e:
         print(f'❌ Solar data API error: {str(e)}')
 
-    response = jsonify(
-        {
-            'error': 'Failed to generate solar data',
-            'solar': {
-                'times': {
-                    'sunrise': None,
-                    'sunset': None,
-                    'solar_noon': None,
-                    'golden_hour_morning': None,
-                    'golden_hour_evening': None,
-                    'blue_hour_morning': None,
-                    'blue_hour_evening': None,
-                },
-                'progress': {
-                    'daylight_progress': 0,
-                    'solar_elevation': 0,
-                    'is_daytime': False,
-                },
-                'metadata': {
-                    'date': date_str or datetime.now(timezone.utc).strftime('%Y-%m-%d'),
-                    'location': location_name,
-                    'timezone': 'UTC',
-                },
+    raise WeatherError(
+        'Failed to generate solar data',
+        solar={
+            'times': {
+                'sunrise': None,
+                'sunset': None,
+                'solar_noon': None,
+                'golden_hour_morning': None,
+                'golden_hour_evening': None,
+                'blue_hour_morning': None,
+                'blue_hour_evening': None,
             },
-        }
+            'progress': {
+                'daylight_progress': 0,
+                'solar_elevation': 0,
+                'is_daytime': False,
+            },
+            'metadata': {
+                'date': date_str or datetime.now(timezone.utc).strftime('%Y-%m-%d'),
+                'location': location_name,
+                'timezone': 'UTC',
+            },
+        },
     )
-    response.status_code = 500
+
+
+# Astronomical events: bundled dataset, rated for each location's visibility
+ASTRO_EVENTS = load_astro_events()
+ASTRO_DEFAULT_DAYS = 365
+CLOUD_FORECAST_DAYS = 16  # Open-Meteo's longest forecast
+
+
+def get_cloud_cover_forecast(lat: float, lon: float) -> dict[datetime, float]:
+    """Hourly cloud cover (%) keyed by UTC hour, empty if unavailable"""
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    if cache_key in cloud_cover_cache:
+        return cloud_cover_cache[cache_key]  # type: ignore[no-any-return]
+
+    forecast: dict[datetime, float] = {}
+    try:
+        response = requests.get(
+            OPEN_METEO_BASE_URL,
+            params={
+                'latitude': lat,
+                'longitude': lon,
+                'hourly': 'cloud_cover',
+                'forecast_days': CLOUD_FORECAST_DAYS,
+                'timezone': 'UTC',
+            },
+            timeout=10,
+        )
+        response.raise_for_status()
+        hourly = response.json().get('hourly', {})
+        for time_str, cover in zip(
+            hourly.get('time', []), hourly.get('cloud_cover', []), strict=False
+        ):
+            if cover is not None:
+                hour = datetime.fromisoformat(time_str).replace(tzinfo=timezone.utc)
+                forecast[hour] = cover
+    except Exception as e:
+        print(f'❌ Cloud cover forecast error: {str(e)}')
+        return forecast
+
+    cloud_cover_cache[cache_key] = forecast
+    return forecast
+
+
+@app.route('/api/astro/events')
+def astro_events_api() -> Response:
+    """Upcoming meteor showers, eclipses and supermoons rated for local viewing"""
+    try:
+        lat = float(request.args['lat'])
+        lon = float(request.args['lon'])
+        days = int(request.args.get('days', ASTRO_DEFAULT_DAYS))
+    except (KeyError, ValueError):
+        raise InvalidLocation('Valid lat, lon (and optional days) required') from None
+
+    now = datetime.now(timezone.utc)
+    until = now + timedelta(days=days)
+    timed = ((event, event_time(event, lon)) for event in ASTRO_EVENTS)
+    upcoming = [
+        (event, viewing_time)
+        for event, viewing_time in timed
+        if now - timedelta(hours=12) <= viewing_time <= until
+    ]
+    clouds = get_cloud_cover_forecast(lat, lon) if upcoming else {}
+
+    events = []
+    for event, viewing_time in sorted(upcoming, key=lambda item: item[1]):
+        hour = viewing_time.replace(minute=0, second=0, microsecond=0)
+        sun_elevation = solar_provider.solar_elevation(lat, lon, viewing_time)
+        moon_illumination = lunar_provider.illumination_at(viewing_time)
+        cloud_cover = clouds.get(hour)
+        events.append(
+            {
+                'name': event['name'],
+                'type': event['type'],
+                'description': event.get('description'),
+                'viewing_time': viewing_time.isoformat(),
+                'zhr': event.get('zhr'),
+                'visible_from': event.get('visible_from'),
+                'sun_elevation': round(sun_elevation, 1),
+                'moon_illumination_percent': moon_illumination,
+                'cloud_cover_percent': cloud_cover,
+                'visibility': assess_visibility(
+                    event, lat, lon, sun_elevation, moon_illumination, cloud_cover
+                ),
+            }
+        )
+
+    response = jsonify({'lat': lat, 'lon': lon, 'events': events})
+    response.headers['Cache-Control'] = 'public, max-age=3600'
     return response
 
 
@@ -906,7 +4114,7 @@ def lunar_data_api() -> Response:
         location_name = request.args.get('location', 'Unknown')
 
         if lat == 0 and lon == 0:
-            return jsonify({'error': 'Valid latitude and longitude required'}), 400
+            raise InvalidLocation('Valid latitude and longitude required')
 
         # Create cache key (location-independent for lunar calculations)
         current_hour = int(time.time() // 3600)  # Round to nearest hour for caching
@@ -946,69 +4154,68 @@ def lunar_data_api() -> Response:
             response.headers['ETag'] = f'"{etag_value}"'
             return response
 
+    except WeatherError:
+        raise
     except ValueError:
-        return jsonify({'error': 'Invalid latitude or longitude format'}), 400
+        raise InvalidLocation('Invalid latitude or longitude format') from None
     except Exception as e:
         print(f'❌ Lunar data API error: {str(e)}')
 
-    response = jsonify(
-        {
-            'error': 'Failed to calculate lunar data',
-            'lunar_data': {
-                'current_phase': {
-                    'name': 'Unknown',
-                    'illumination_percent': 0,
-                    'lunar_age_days': 0,
-                    'description': 'Unable to calculate moon phase',
-                },
-                'next_phases': {
-                    'new_moon': {
-                        'date': None,
-                        'days_until': None,
-                        'countdown_text': 'Unknown',
-                    },
-                    'full_moon': {
-                        'date': None,
-                        'days_until': None,
-                        'countdown_text': 'Unknown',
-                    },
+    raise WeatherError(
+        'Failed to calculate lunar data',
+        lunar_data={
+            'current_phase': {
+                'name': 'Unknown',
+                'illumination_percent': 0,
+                'lunar_age_days': 0,
+                'description': 'Unable to calculate moon phase',
+            },
+            'next_phases': {
+                'new_moon': {
+                    'date': None,
+                    'days_until': None,
+                    'countdown_text': 'Unknown',
                 },
-                'lunar_cycle': {
-                    'current_cycle_progress': 0,
-                    'synodic_month_days': 29.53,
+                'full_moon': {
+                    'date': None,
+                    'days_until': None,
+                    'countdown_text': 'Unknown',
                 },
-                'astronomical_data': {
-                    'julian_day': None,
-                    'lunar_distance_varies': True,
-                    'best_viewing': {
-                        'visibility': 'Unknown',
-                        'photography': 'Check lunar calculator',
-                        'best_time': 'Varies by phase',
-                        'stargazing': 'Depends on moon brightness',
-                    },
+            },
+            'lunar_cycle': {
+                'current_cycle_progress': 0,
+                'synodic_month_days': 29.53,
+            },
+            'astronomical_data': {
+                'julian_day': None,
+                'lunar_distance_varies': True,
+                'best_viewing': {
+                    'visibility': 'Unknown',
+                    'photography': 'Check lunar calculator',
+                    'best_time': 'Varies by phase',
+                    'stargazing': 'Depends on moon brightness',
                 },
             },
-        }
+        },
     )
-    response.status_code = 500
-    return response
 
 
 @app.route('/api/temperature-trends')
 def temperature_trends_api() -> Response:
     """Temperature trends API endpoint for enhanced temperature analysis"""
-    try:
-        lat = float(request.args.get('lat', CHICAGO_LAT))
-        lon = float(request.args.get('lon', CHICAGO_LON))
-        location_name = request.args.get('location', 'Chicago')
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
 
+    try:
         # Create cache key
         cache_key = f'temp_trends_{round(lat, 4)},{round(lon, 4)}'
 
         # Check cache first
         if cache_key in temperature_trends_cache:
             cached_data = temperature_trends_cache[cache_key]
-            response = jsonify(cached_data)
+            response = jsonify_with_fallback(cached_data, location_fallback)
             response.headers['X-Cache'] = 'HIT'
             response.headers['Cache-Control'] = 'public, max-age=900'
 
@@ -1021,9 +4228,8 @@ def temperature_trends_api() -> Response:
         weather_data = weather_manager.get_weather(lat, lon, location_name)
 
         if not weather_data:
-            return jsonify(
-                {'error': 'Failed to get weather data for temperature trends'}
-            ), 500
+            msg = 'Failed to get weather data for temperature trends'
+            raise UpstreamUnavailable(msg)
 
         # Get enhanced temperature trends
         trends_data = temperature_trends_provider.process_weather_data(
@@ -1034,7 +4240,7 @@ def temperature_trends_api() -> Response:
             # Cache the result
             temperature_trends_cache[cache_key] = trends_data
 
-            response = jsonify(trends_data)
+            response = jsonify_with_fallback(trends_data, location_fallback)
             response.headers['X-Cache'] = 'MISS'
             response.headers['Cache-Control'] = 'public, max-age=900'
 
@@ -1043,57 +4249,977 @@ def temperature_trends_api() -> Response:
             response.headers['ETag'] = f'"{etag_value}"'
             return response
 
-    except ValueError:
-        return jsonify({'error': 'Invalid latitude or longitude format'}), 400
+    except WeatherError:
+        raise
     except Exception as e:
         print(f'❌ Temperature trends API error: {str(e)}')
 
     # Error fallback response
-    response = jsonify(
-        {
-            'error': 'Failed to generate temperature trends',
-            'temperature_trends': {
-                'hourly_data': [],
-                'statistics': {
-                    'temperature': {
-                        'min': 0,
-                        'max': 0,
-                        'mean': 0,
-                        'range': 0,
-                    },
-                    'apparent_temperature': {
-                        'min': 0,
-                        'max': 0,
-                        'mean': 0,
-                        'range': 0,
-                    },
-                },
-                'comfort_analysis': {
-                    'categories': {'comfortable': 0, 'hot': 0, 'cool': 0, 'cold': 0},
-                    'percentages': {'comfortable': 0, 'hot': 0, 'cool': 0, 'cold': 0},
-                    'primary_comfort': 'unknown',
-                },
-                'trend_analysis': {
-                    'trend_direction': 'unknown',
-                    'temperature_change_24h': 0,
-                    'volatility': 0,
-                },
-                'percentile_bands': {
-                    '10th_percentile': 0,
-                    '50th_percentile': 0,
-                    '90th_percentile': 0,
-                    'data_source': 'error',
+    raise WeatherError(
+        'Failed to generate temperature trends',
+        temperature_trends={
+            'hourly_data': [],
+            'statistics': {
+                'temperature': {
+                    'min': 0,
+                    'max': 0,
+                    'mean': 0,
+                    'range': 0,
                 },
-                'current': {
-                    'temperature': 0,
-                    'apparent_temperature': 0,
-                    'comfort_category': 'unknown',
+                'apparent_temperature': {
+                    'min': 0,
+                    'max': 0,
+                    'mean': 0,
+                    'range': 0,
                 },
             },
+            'comfort_analysis': {
+                'categories': {'comfortable': 0, 'hot': 0, 'cool': 0, 'cold': 0},
+                'percentages': {'comfortable': 0, 'hot': 0, 'cool': 0, 'cold': 0},
+                'primary_comfort': 'unknown',
+            },
+            'trend_analysis': {
+                'trend_direction': 'unknown',
+                'temperature_change_24h': 0,
+                'volatility': 0,
+            },
+            'percentile_bands': {
+                '10th_percentile': 0,
+                '50th_percentile': 0,
+                '90th_percentile': 0,
+                'data_source': 'error',
+            },
+            'current': {
+                'temperature': 0,
+                'apparent_temperature': 0,
+                'comfort_category': 'unknown',
+            },
+        },
+    )
+
+
+# Hourly history fields available for binning, keyed by API metric name
+HISTORY_METRICS = {
+    'temperature': 'temperature_2m',
+    'feels_like': 'apparent_temperature',
+    'humidity': 'relative_humidity_2m',
+    'pressure': 'pressure_msl',
+    'wind_speed': 'wind_speed_10m',
+    'precipitation': 'precipitation',
+}
+
+# Supported bucket widths in seconds
+HISTORY_BUCKETS = {'1h': 3600, '6h': 6 * 3600, '1d': 24 * 3600}
+
+DEFAULT_HISTORY_DAYS = 7
+MAX_HISTORY_DAYS = 92  # About a season of hourly buckets
+
+
+def get_history_from_open_meteo(
+    lat: float, lon: float, field: str, days: int, forecast_days: int = 0
+) -> list[tuple[datetime, float]] | None:
+    """Fetch past hourly observations (and optionally forecasts) for one field"""
+    try:
+        url = f'{OPEN_METEO_BASE_URL}?latitude={lat}&longitude={lon}'
+        url += f'&hourly={field}&past_days={days}&forecast_days={forecast_days}'
+        url += '&timezone=UTC'
+        url += (
+            '&temperature_unit=fahrenheit&wind_speed_unit=mph&precipitation_unit=inch'
+        )
+
+        print(f'📚 Fetching Open-Meteo history from: {url}')
+
+        response = requests.get(url, timeout=10)
+        response.raise_for_status()
+        hourly = response.json().get('hourly', {})
+    except Exception as e:
+        print(f'❌ Open-Meteo history error: {str(e)}')
+        return None
+
+    # Timestamps come back in UTC without an offset; skip gaps in the series
+    return [
+        (datetime.fromisoformat(stamp).replace(tzinfo=timezone.utc), value)
+        for stamp, value in zip(hourly.get('time', []), hourly.get(field, []))
+        if value is not None
+    ]
+
+
+# Archive variables for each field of the hourly history shape
+ARCHIVE_HOURLY_FIELDS = {
+    'temperature': 'temperature_2m',
+    'feels_like': 'apparent_temperature',
+    'dew_point': 'dew_point_2m',
+    'humidity': 'relative_humidity_2m',
+    'wind_speed': 'wind_speed_10m',
+    'wind_gust': 'wind_gusts_10m',
+    'wind_direction': 'wind_direction_10m',
+    'pressure': 'pressure_msl',
+    'precipitation': 'precipitation',
+}
+
+
+def get_archive_from_open_meteo(
+    lat: float, lon: float, start_date: str, end_date: str
+) -> dict[int, dict] | None:
+    """Fetch archived hourly weather between two UTC dates, keyed by hour start"""
+    params = {
+        'latitude': lat,
+        'longitude': lon,
+        'start_date': start_date,
+        'end_date': end_date,
+        'hourly': ','.join(ARCHIVE_HOURLY_FIELDS.values()),
+        'timezone': 'UTC',
+        'temperature_unit': 'fahrenheit',
+        'wind_speed_unit': 'mph',
+        'precipitation_unit': 'inch',
+    }
+    try:
+        print(f'📚 Fetching Open-Meteo archive {start_date}..{end_date} for {lat},{lon}')
+        response = requests.get(OPEN_METEO_ARCHIVE_URL, params=params, timeout=10)
+        response.raise_for_status()
+        hourly = response.json().get('hourly', {})
+    except Exception as e:
+        print(f'❌ Open-Meteo archive error: {str(e)}')
+        return None
+
+    points = {}
+    for index, stamp in enumerate(hourly.get('time', [])):
+        point = {}
+        for field, variable in ARCHIVE_HOURLY_FIELDS.items():
+            values = hourly.get(variable) or []
+            point[field] = values[index] if index < len(values) else None
+        # Recent hours the reanalysis hasn't reached yet come back empty
+        if any(value is not None for value in point.values()):
+            hour = datetime.fromisoformat(stamp).replace(tzinfo=timezone.utc)
+            points[int(hour.timestamp())] = point
+    return points
+
+
+def bin_history_points(
+    points: list[tuple[datetime, float]], bucket_seconds: int
+) -> list[dict]:
+    """Aggregate time-stamped values into min/max/avg buckets"""
+    buckets: dict[int, list[float]] = {}
+    for stamp, value in points:
+        bucket_start = int(stamp.timestamp()) // bucket_seconds * bucket_seconds
+        buckets.setdefault(bucket_start, []).append(value)
+
+    return [
+        {
+            'start': datetime.fromtimestamp(start, tz=timezone.utc).isoformat(),
+            'min': round(min(values), 2),
+            'max': round(max(values), 2),
+            'avg': round(sum(values) / len(values), 2),
+            'count': len(values),
+        }
+        for start, values in sorted(buckets.items())
+    ]
+
+
+@app.route('/api/history/binned')
+def history_binned_api() -> Response:
+    """API endpoint for pre-aggregated weather history buckets"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, _ = location
+    metric = request.args.get('metric', 'temperature')
+    bucket = request.args.get('bucket', '1h')
+    days = request.args.get('days', DEFAULT_HISTORY_DAYS, type=int)
+
+    if metric not in HISTORY_METRICS or bucket not in HISTORY_BUCKETS:
+        raise InvalidParameter(
+            'Invalid metric or bucket',
+            metrics=list(HISTORY_METRICS),
+            buckets=list(HISTORY_BUCKETS),
+        )
+    days = max(1, min(days, MAX_HISTORY_DAYS))
+
+    cache_key = f'{lat:.4f},{lon:.4f}:{metric}:{bucket}:{days}'
+    if cache_key in history_cache:
+        response = jsonify_with_fallback(history_cache[cache_key], location_fallback)
+        response.headers['X-Cache'] = 'HIT'
+        response.headers['Cache-Control'] = 'public, max-age=1800'
+        return response
+
+    end = int(time.time())
+    points = history_points(lat, lon, metric, end - days * SECONDS_PER_DAY, end)
+    history_data = {
+        'metric': metric,
+        'bucket': bucket,
+        'days': days,
+        'buckets': bin_history_points(points, HISTORY_BUCKETS[bucket]),
+    }
+    history_cache[cache_key] = history_data
+
+    response = jsonify_with_fallback(history_data, location_fallback)
+    response.headers['X-Cache'] = 'MISS'
+    response.headers['Cache-Control'] = 'public, max-age=1800'
+    return response
+
+
+# /api/history: hourly or daily weather for a range, from the local history
+# store where it has data and Open-Meteo's archive for the hours it doesn't
+HISTORY_GRANULARITIES = ('hour', 'day')
+HISTORY_UNITS = {
+    'temperature': '°F',
+    'humidity': '%',
+    'wind_speed': 'mph',
+    'wind_direction': '°',
+    'pressure': 'hPa',
+    'precipitation': 'in',
+}
+MAX_HISTORY_RANGE_DAYS = 366
+
+
+def history_hours(
+    lat: float, lon: float, start: int, end: int, covered: set[int] | None = None
+) -> dict[int, dict]:
+    """Hourly points between two Unix times, each tagged with its source
+
+    Hours in covered (e.g. those of compacted days) aren't fetched from the
+    archive when missing.
+    """
+    rows = history_store.observations(location_key(lat, lon), start, end)
+    hours = {
+        hour: {**point, 'source': 'local'}
+        for hour, point in hourly_from_observations(rows).items()
+    }
+    first_hour = start // SECONDS_PER_HOUR * SECONDS_PER_HOUR
+    # The archive has nothing for hours that haven't happened yet
+    last_hour = min(end, int(time.time()))
+    missing = [
+        hour
+        for hour in range(first_hour, last_hour + 1, SECONDS_PER_HOUR)
+        if hour not in hours and hour not in (covered or ())
+    ]
+    if not missing:
+        return hours
+
+    start_date = datetime.fromtimestamp(missing[0], tz=timezone.utc).date()
+    end_date = datetime.fromtimestamp(missing[-1], tz=timezone.utc).date()
+    cache_key = f'archive_{lat:.4f},{lon:.4f}_{start_date}_{end_date}'
+    if cache_key in history_cache:
+        archived = history_cache[cache_key]
+    else:
+        archived = get_archive_from_open_meteo(
+            lat, lon, start_date.isoformat(), end_date.isoformat()
+        )
+        if archived is None:
+            return hours
+        history_cache[cache_key] = archived
+    for hour in missing:
+        if hour in archived:
+            hours[hour] = {**archived[hour], 'source': 'archive'}
+    return hours
+
+
+def history_points(
+    lat: float, lon: float, metric: str, start: int, end: int
+) -> list[tuple[datetime, float]]:
+    """One metric's hourly history between two Unix times, oldest first"""
+    hours = history_hours(lat, lon, start, end)
+    return [
+        (datetime.fromtimestamp(hour, tz=timezone.utc), point[metric])
+        for hour, point in sorted(hours.items())
+        if point.get(metric) is not None
+    ]
+
+
+@app.route('/api/history')
+def history_api() -> Response:
+    """Past hourly or daily weather for a location and time range (UTC)"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, _ = location
+    granularity = request.args.get('granularity', 'hour')
+    now = datetime.now(timezone.utc)
+    end = parse_utc_time(request.args['end']) if 'end' in request.args else now
+    start = (
+        parse_utc_time(request.args['start'])
+        if 'start' in request.args
+        else (end or now) - timedelta(days=DEFAULT_HISTORY_DAYS)
+    )
+
+    if granularity not in HISTORY_GRANULARITIES:
+        raise InvalidParameter(
+            'Invalid granularity', granularities=list(HISTORY_GRANULARITIES)
+        )
+    if start is None or end is None or start > end:
+        msg = 'start and end must be ISO 8601 dates or times, in order'
+        raise InvalidParameter(msg)
+    if end - start > timedelta(days=MAX_HISTORY_RANGE_DAYS):
+        raise InvalidParameter(
+            f'The range can be at most {MAX_HISTORY_RANGE_DAYS} days'
+        )
+
+    first, last = int(start.timestamp()), int(end.timestamp())
+    # Days compacted out of the snapshots come from their daily summaries
+    compacted = (
+        history_store.daily(location_key(lat, lon), first, last)
+        if granularity == 'day'
+        else {}
+    )
+    covered = {
+        day + hour * SECONDS_PER_HOUR for day in compacted for hour in range(24)
+    }
+    hours = history_hours(lat, lon, first, last, covered)
+    if granularity == 'day':
+        days = {**daily_from_hourly(hours), **compacted}
+        series = [
+            {'date': iso_timestamp(day)[:10], **point}
+            for day, point in sorted(days.items())
+        ]
+    else:
+        series = [
+            {'time': iso_timestamp(hour), **point}
+            for hour, point in sorted(hours.items())
+        ]
+
+    sources = [point['source'] for point in hours.values()]
+    return jsonify_with_fallback(
+        {
+            'lat': lat,
+            'lon': lon,
+            'granularity': granularity,
+            'start': start.isoformat(),
+            'end': end.isoformat(),
+            'units': HISTORY_UNITS,
+            'sources': {
+                'local': sources.count('local')
+                + sum(day['hours'] for day in compacted.values()),
+                'archive': sources.count('archive'),
+            },
+            'points': series,
+        },
+        location_fallback,
+    )
+
+
+# /api/history/export: the raw snapshots or compacted days, for one location or
+# every one, as CSV or Parquet (admin, since it's the whole store)
+HISTORY_EXPORT_FORMATS = {
+    'csv': 'text/csv',
+    'parquet': 'application/vnd.apache.parquet',
+}
+
+
+def history_export_columns(table: str) -> list[tuple[str, str]]:
+    """A history table's columns as Parquet (name, kind) pairs"""
+    _, columns, _ = EXPORT_TABLES[table]
+    kinds = {'observed_at': 'timestamp', 'hours': 'int64'}
+    text = ('location', 'provider', 'icon', 'summary', 'date')
+    return [
+        (column, 'string' if column in text else kinds.get(column, 'double'))
+        for column in columns
+    ]
+
+
+@app.route('/api/history/export')
+@require_admin
+def history_export_api() -> Response:
+    """Download stored history: every location's, or one with ?lat=&lon="""
+    export_format = request.args.get('format', 'csv')
+    table = request.args.get('table', 'observations')
+    if export_format not in HISTORY_EXPORT_FORMATS or table not in EXPORT_TABLES:
+        raise InvalidParameter(
+            'Invalid export',
+            formats=list(HISTORY_EXPORT_FORMATS),
+            tables=list(EXPORT_TABLES),
+        )
+    start = parse_utc_time(request.args.get('start', '1970-01-01'))
+    end = (
+        parse_utc_time(request.args['end'])
+        if 'end' in request.args
+        else datetime.now(timezone.utc)
+    )
+    if start is None or end is None or start > end:
+        msg = 'start and end must be ISO 8601 dates or times, in order'
+        raise InvalidParameter(msg)
+    lat = request.args.get('lat', type=float)
+    lon = request.args.get('lon', type=float)
+    location = location_key(lat, lon) if lat is not None and lon is not None else None
+
+    rows = history_store.export(table, location, start.timestamp(), end.timestamp())
+    columns = history_export_columns(table)
+    if export_format == 'parquet':
+        body: str | bytes = write_parquet(columns, rows)
+    else:
+        output = io.StringIO()
+        writer = csv.DictWriter(output, fieldnames=[name for name, _ in columns])
+        writer.writeheader()
+        for row in rows:
+            if 'observed_at' in row:
+                row['observed_at'] = iso_timestamp(row['observed_at'])
+            writer.writerow(
+                {key: sanitize_csv_value(value) for key, value in row.items()}
+            )
+        body = output.getvalue()
+
+    response = Response(body, mimetype=HISTORY_EXPORT_FORMATS[export_format])
+    response.headers['Content-Disposition'] = (
+        f'attachment; filename="history-{table}.{export_format}"'
+    )
+    return response
+
+
+# /api/station/observations: readings from the personal weather stations in
+# STATIONS, as JSON (keyed like an API token) or the Ecowitt and Weather
+# Underground upload formats (keyed by PASSKEY or PASSWORD), by GET or POST
+@app.route(STATION_UPLOAD_PATH, methods=['GET', 'POST'])
+def station_observations_api() -> Response:
+    """Store a reading from a personal weather station in the history"""
+    received_at = time.time()
+    params = request.values
+    secret = next(
+        (params[name] for name in UPLOAD_KEY_PARAMS if params.get(name)), None
+    )
+    station = station_network.authenticate(secret or request_token())
+    if station is None:
+        raise Unauthorized('Unknown station key')
+
+    try:
+        if request.is_json:
+            reading = reading_from_json(request.get_json(silent=True), received_at)
+        else:
+            reading = reading_from_upload(params, received_at)
+    except ValueError as e:
+        raise InvalidParameter(str(e)) from e
+
+    cache_key = location_key(station['lat'], station['lon'])
+    try:
+        recorded = history_store.record(
+            cache_key,
+            station['lat'],
+            station['lon'],
+            STATION_PROVIDER,
+            reading,
+            received_at,
+        )
+    except sqlite3.Error as e:
+        print(f'❌ Failed to record station {station["name"]}: {str(e)}')
+        raise WeatherError('Failed to store the observation') from e
+    station_network.update(station, reading)
+    print(f'🏡 Reading from station {station["name"]} at {cache_key}')
+    return jsonify(
+        {
+            'station': station['name'],
+            'location': cache_key,
+            'observed_at': iso_timestamp(reading['timestamp']),
+            'recorded': recorded,
+            'override_current': station['override_current'],
+        }
+    )
+
+
+# /api/trends: rolling statistics over the stored observations of the last
+# day or week, as chart-ready series
+@app.route('/api/trends')
+def trends_api() -> Response:
+    """Ranges, rates of change and anomalies in a location's recent history"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, _ = location
+    window = request.args.get('window', '24h')
+    if window not in TREND_WINDOWS:
+        raise InvalidParameter('Invalid window', windows=list(TREND_WINDOWS))
+
+    length, _ = TREND_WINDOWS[window]
+    end = int(time.time())
+    rows = history_store.observations(location_key(lat, lon), end - length, end)
+    trends = build_trends(hourly_from_observations(rows), window)
+    response = jsonify_with_fallback(
+        {
+            'lat': lat,
+            'lon': lon,
+            'start': iso_timestamp(end - length),
+            'end': iso_timestamp(end),
+            'observations': len(rows),
+            **trends,
+        },
+        location_fallback,
+    )
+    response.headers['Cache-Control'] = 'public, max-age=300'
+    return response
+
+
+# /api/climatology: normals and records from daily summaries the archive seeds
+# into the history store (the last CLIMATE_YEARS years), topped up as it grows
+CLIMATE_YEARS = 30
+CLIMATE_TOPUP_DAYS = 7
+ARCHIVE_DAILY_FIELDS = {
+    'temperature_max': 'temperature_2m_max',
+    'temperature_min': 'temperature_2m_min',
+    'precipitation_sum': 'precipitation_sum',
+}
+
+
+def get_daily_archive_from_open_meteo(
+    lat: float, lon: float, start_date: str, end_date: str
+) -> dict[str, dict] | None:
+    """Fetch archived local-day highs, lows and totals, keyed by ISO date"""
+    params = {
+        'latitude': lat,
+        'longitude': lon,
+        'start_date': start_date,
+        'end_date': end_date,
+        'daily': ','.join(ARCHIVE_DAILY_FIELDS.values()),
+        'timezone': 'auto',
+        'temperature_unit': 'fahrenheit',
+        'precipitation_unit': 'inch',
+    }
+    try:
+        print(f'📚 Fetching Open-Meteo daily archive {start_date}..{end_date}')
+        # Decades of days make a big response; give it longer than usual
+        response = requests.get(OPEN_METEO_ARCHIVE_URL, params=params, timeout=30)
+        response.raise_for_status()
+        daily = response.json().get('daily', {})
+    except Exception as e:
+        print(f'❌ Open-Meteo daily archive error: {str(e)}')
+        return None
+
+    days = {}
+    for index, stamp in enumerate(daily.get('time', [])):
+        day = {}
+        for field, variable in ARCHIVE_DAILY_FIELDS.items():
+            values = daily.get(variable) or []
+            day[field] = values[index] if index < len(values) else None
+        # Days the reanalysis hasn't reached yet come back empty
+        if any(value is not None for value in day.values()):
+            days[stamp] = day
+    return days
+
+
+def seed_climatology(lat: float, lon: float) -> None:
+    """Fill in a location's daily summaries from the archive, once it's behind"""
+    key = location_key(lat, lon)
+    marker = f'climate_seeded_{key}'
+    if marker in history_cache:
+        return  # Checked recently; the archive won't have moved on
+    today = datetime.now(timezone.utc).date()
+    last = history_store.last_day(key)
+    if last is None:
+        first = today.replace(year=today.year - CLIMATE_YEARS, month=1, day=1)
+    elif date.fromisoformat(last) < today - timedelta(days=CLIMATE_TOPUP_DAYS):
+        first = date.fromisoformat(last) + timedelta(days=1)
+    else:
+        history_cache[marker] = True
+        return
+    days = get_daily_archive_from_open_meteo(
+        lat, lon, first.isoformat(), (today - timedelta(days=1)).isoformat()
+    )
+    if days is None:
+        return
+    try:
+        history_store.record_days(key, days, 'archive')
+    except sqlite3.Error as e:
+        print(f'❌ Failed to save climatology for {key}: {str(e)}')
+        return
+    history_cache[marker] = True
+
+
+def fahrenheit_to_celsius(
+    value: float | None, difference: bool = False
+) -> float | None:
+    """°F as °C to a tenth; a difference between two readings is only scaled"""
+    if value is None:
+        return None
+    return round((value if difference else value - 32) * 5 / 9, 1)
+
+
+def climatology_to_metric(
+    climate: dict[str, Any], today: dict[str, Any]
+) -> tuple[dict[str, Any], dict[str, Any]]:
+    """Normals, records and today's comparison in °C and mm"""
+    normals = climate['normals']
+    records = {
+        kind: record and {**record, 'value': fahrenheit_to_celsius(record['value'])}
+        for kind, record in climate['records'].items()
+    }
+    climate = {
+        **climate,
+        'normals': {
+            'high': fahrenheit_to_celsius(normals['high']),
+            'low': fahrenheit_to_celsius(normals['low']),
+            'precipitation': convert_value(normals['precipitation'], 'precipitation'),
+        },
+        'records': records,
+    }
+    today = {
+        **today,
+        **{
+            field: fahrenheit_to_celsius(today[field])
+            for field in ('normal_high', 'normal_low', 'forecast_high', 'forecast_low')
+        },
+        'high_delta': fahrenheit_to_celsius(today['high_delta'], difference=True),
+        'low_delta': fahrenheit_to_celsius(today['low_delta'], difference=True),
+    }
+    return climate, today
+
+
+def describe_anomaly(delta: float | None, units: str) -> str | None:
+    """'5° warmer than average' for a difference from normal in °F"""
+    if delta is None:
+        return None
+    if units == 'metric':
+        delta = delta * 5 / 9
+    degrees = round(abs(delta))
+    if degrees == 0:
+        return 'About average'
+    return f'{degrees}° {"warmer" if delta > 0 else "cooler"} than average'
+
+
+@app.route('/api/climatology')
+def climatology_api() -> Response:
+    """Normals, records and today against normal for a location"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+    today = datetime.now(timezone.utc).date()
+    month = request.args.get('month', today.month, type=int)
+    if not 1 <= month <= 12:  # noqa: PLR2004
+        raise InvalidParameter('month must be 1-12')
+
+    seed_climatology(lat, lon)
+    days = history_store.days(location_key(lat, lon))
+    if not days:
+        raise ProviderFailed('Failed to fetch climate history', 'archive')
+
+    # Today is the location's today, as its forecast has it
+    weather_data = location_weather(lat, lon, location_name)
+    forecast_days = (weather_data or {}).get('daily') or [{}]
+    forecast = forecast_days[0]
+    if 'date' in forecast:
+        today = date.fromisoformat(str(forecast['date'])[:10])
+    normal = day_normal(days, today)
+    high_delta = compare_to_normal(forecast.get('h'), normal['high'])
+    units, _ = requested_display()
+    climate = month_climatology(days, month)
+    comparison = {
+        'date': today.isoformat(),
+        'normal_high': normal['high'],
+        'normal_low': normal['low'],
+        'forecast_high': forecast.get('h'),
+        'forecast_low': forecast.get('l'),
+        'high_delta': high_delta,
+        'low_delta': compare_to_normal(forecast.get('l'), normal['low']),
+        'annotation': describe_anomaly(high_delta, units),
+    }
+    if units == 'metric':
+        climate, comparison = climatology_to_metric(climate, comparison)
+    labels = UNIT_LABELS[units]
+
+    return jsonify_with_fallback(
+        {
+            'lat': lat,
+            'lon': lon,
+            'month': month,
+            'first_date': days[0]['date'],
+            'last_date': days[-1]['date'],
+            **climate,
+            'today': comparison,
+            'units': {
+                'temperature': labels['temperature'],
+                'precipitation': labels['precipitation'],
+            },
+        },
+        location_fallback,
+    )
+
+
+# Grafana Simple JSON datasource at /api/grafana/ - targets are
+# '<city>.<metric>' for past hourly observations and '<city>.forecast.<metric>'
+# for the hourly forecast; change-feed entries double as annotations
+GRAFANA_FORECAST_DAYS = 7
+
+
+def grafana_targets() -> list[str]:
+    """List every queryable Grafana target"""
+    return [
+        f'{city}.{series}{metric}'
+        for city in CITY_COORDS
+        for series in ('', 'forecast.')
+        for metric in HISTORY_METRICS
+    ]
+
+
+def parse_utc_time(value: Any) -> datetime | None:
+    """Parse an ISO 8601 time or date, UTC unless it says otherwise"""
+    if not isinstance(value, str):
+        return None
+    try:
+        parsed = datetime.fromisoformat(value.replace('Z', '+00:00'))
+    except ValueError:
+        return None
+    if parsed.tzinfo is None:
+        parsed = parsed.replace(tzinfo=timezone.utc)
+    return parsed
+
+
+def grafana_series(target: str, start: datetime, end: datetime) -> list[list] | None:
+    """Get [value, epoch_ms] datapoints for a target within the range"""
+    parts = target.split('.')
+    if len(parts) == 2:  # noqa: PLR2004
+        city, metric = parts
+        forecast = False
+    elif len(parts) == 3 and parts[1] == 'forecast':  # noqa: PLR2004
+        city, _, metric = parts
+        forecast = True
+    else:
+        return None
+    if city not in CITY_COORDS or metric not in HISTORY_METRICS:
+        return None
+
+    lat, lon, _ = CITY_COORDS[city]
+    if forecast:
+        cache_key = f'grafana_{lat:.4f},{lon:.4f}_{metric}_forecast'
+        if cache_key in history_cache:
+            points = history_cache[cache_key]
+        else:
+            points = get_history_from_open_meteo(
+                lat, lon, HISTORY_METRICS[metric], 0, GRAFANA_FORECAST_DAYS
+            )
+            if points is None:
+                return None
+            history_cache[cache_key] = points
+    else:
+        # Past hours come from the history store, topped up from the archive
+        first = max(start, end - timedelta(days=MAX_HISTORY_RANGE_DAYS))
+        points = history_points(
+            lat, lon, metric, int(first.timestamp()), int(end.timestamp())
+        )
+
+    return [
+        [value, int(stamp.timestamp() * 1000)]
+        for stamp, value in points
+        if start <= stamp <= end
+    ]
+
+
+def grafana_range(data: dict) -> tuple[datetime, datetime] | None:
+    """Get the (from, to) range of a Grafana request body"""
+    time_range = data.get('range') or {}
+    start = parse_utc_time(time_range.get('from'))
+    end = parse_utc_time(time_range.get('to'))
+    if start is None or end is None or start > end:
+        return None
+    return start, end
+
+
+@app.route('/api/grafana/')
+def grafana_test_connection() -> Response:
+    """Grafana datasource health check"""
+    return jsonify({'status': 'ok'})
+
+
+@app.route('/api/grafana/search', methods=['POST'])
+def grafana_search() -> Response:
+    """List targets matching the metric picker's search text"""
+    data = request.get_json(silent=True) or {}
+    query = str(data.get('target') or '').lower()
+    return jsonify([target for target in grafana_targets() if query in target])
+
+
+@app.route('/api/grafana/query', methods=['POST'])
+def grafana_query() -> Response:
+    """Return time series for the requested targets"""
+    data = request.get_json(silent=True) or {}
+    time_range = grafana_range(data)
+    if time_range is None:
+        raise InvalidParameter('A valid range.from and range.to is required')
+
+    results = []
+    for target_spec in data.get('targets', []):
+        target = str(target_spec.get('target', ''))
+        datapoints = grafana_series(target, *time_range)
+        if datapoints is None:
+            print(f'⚠️  Grafana target unavailable: {target}')
+            datapoints = []
+        results.append({'target': target, 'datapoints': datapoints})
+    return jsonify(results)
+
+
+@app.route('/api/grafana/annotations', methods=['POST'])
+def grafana_annotations() -> Response:
+    """Significant weather changes for a city (annotation query) as annotations"""
+    data = request.get_json(silent=True) or {}
+    time_range = grafana_range(data)
+    annotation = data.get('annotation') or {}
+    city = str(annotation.get('query') or '').strip().lower()
+    if time_range is None or city not in CITY_COORDS:
+        return jsonify([])
+
+    lat, lon, _ = CITY_COORDS[city]
+    with weather_change_lock:
+        entries = list(weather_change_history.get(f'{lat:.4f},{lon:.4f}', []))
+
+    start, end = time_range
+    annotations = []
+    for entry in entries:
+        updated = datetime.fromisoformat(entry['updated'])
+        if start <= updated <= end:
+            annotations.append(
+                {
+                    'annotation': annotation,
+                    'time': int(updated.timestamp() * 1000),
+                    'title': entry['title'],
+                    'text': entry['summary'],
+                    'tags': [entry['kind'], city],
+                }
+            )
+    return jsonify(annotations)
+
+
+# Prometheus weather exporter: (metric, help text, field in current conditions)
+WEATHER_GAUGES = [
+    ('weather_temperature_fahrenheit', 'Air temperature', 'temperature'),
+    ('weather_feels_like_fahrenheit', 'Apparent temperature', 'feels_like'),
+    ('weather_dew_point_fahrenheit', 'Dew point', 'dew_point'),
+    ('weather_humidity_percent', 'Relative humidity', 'humidity'),
+    ('weather_wind_speed_mph', 'Wind speed', 'wind_speed'),
+    ('weather_wind_gust_mph', 'Wind gust speed', 'wind_gust'),
+    ('weather_wind_direction_degrees', 'Wind direction', 'wind_direction'),
+    ('weather_pressure_hpa', 'Sea-level pressure', 'pressure'),
+    ('weather_uv_index', 'UV index', 'uv_index'),
+    (
+        'weather_precipitation_inches_per_hour',
+        'Precipitation rate',
+        'precipitation_rate',
+    ),
+]
+PROMETHEUS_CONTENT_TYPE = 'text/plain; version=0.0.4; charset=utf-8'
+
+
+def escape_prometheus_label(value: str) -> str:
+    """Escape a Prometheus label value (backslash, quote, newline)"""
+    return value.replace('\\', '\\\\').replace('"', '\\"').replace('\n', '\\n')
+
+
+def render_prometheus_gauges(
+    samples: list[tuple[str, str, float]], labels: dict[str, str]
+) -> str:
+    """Render (metric, help, value) samples in the Prometheus text format"""
+    label_text = ','.join(
+        f'{key}="{escape_prometheus_label(value)}"' for key, value in labels.items()
+    )
+    lines = []
+    for metric, help_text, value in samples:
+        lines.append(f'# HELP {metric} {help_text}')
+        lines.append(f'# TYPE {metric} gauge')
+        number = str(int(value)) if value.is_integer() else repr(value)
+        lines.append(f'{metric}{{{label_text}}} {number}')
+    return '\n'.join(lines) + '\n'
+
+
+@app.route('/metrics/weather')
+def weather_metrics() -> Response:
+    """Current observations as Prometheus gauges, labelled by location"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    weather_data = location_weather(lat, lon, location_name)
+    if not weather_data:
+        response = Response('# Failed to fetch weather data\n', mimetype='text/plain')
+        response.status_code = 500
+        return response
+
+    current = weather_data.get('current', {})
+    samples = [
+        (metric, help_text, float(current[field]))
+        for metric, help_text, field in WEATHER_GAUGES
+        if isinstance(current.get(field), int | float)
+        and not isinstance(current.get(field), bool)
+    ]
+
+    air_quality = get_air_quality(lat, lon, location_name)
+    us_aqi = (air_quality or {}).get('aqi', {}).get('us_aqi')
+    if isinstance(us_aqi, int | float):
+        samples.append(('weather_air_quality_index', 'US EPA AQI', float(us_aqi)))
+
+    fetched_at = (cached_weather_metadata(cache_key) or {}).get('fetched_at')
+    if fetched_at:
+        samples.append(
+            (
+                'weather_observation_timestamp_seconds',
+                'When these observations were fetched',
+                float(round(fetched_at)),
+            )
+        )
+
+    labels = {
+        'location': location_name,
+        'lat': f'{lat:.4f}',
+        'lon': f'{lon:.4f}',
+        'provider': str(weather_data.get('provider', 'unknown')),
+    }
+    body = render_prometheus_gauges(samples, labels)
+    response = Response(body, content_type=PROMETHEUS_CONTENT_TYPE)
+    return with_location_fallback(response, location_fallback)
+
+
+@app.route('/api/prefetch/status')
+def prefetch_status_api() -> Response:
+    """Prefetch schedule, the last run's results and when each target expires"""
+    next_run = prefetch_status['next_run']
+    return jsonify(
+        {
+            'running': prefetch_status['running'],
+            'schedule': {
+                'cache_ttl_seconds': WEATHER_CACHE_TTL,
+                'lead_seconds': PREFETCH_LEAD_SECONDS,
+                'jitter_seconds': PREFETCH_JITTER_SECONDS,
+                'max_interval_seconds': PREFETCH_MAX_SLEEP_SECONDS,
+            },
+            'last_run': prefetch_status['last_run'],
+            'next_run_at': iso_timestamp(next_run) if next_run else None,
+            'locations': [
+                {
+                    'location': name,
+                    'key': location_key(lat, lon),
+                    'refresh_in_seconds': max(
+                        0, round(prefetch_due_in(location_key(lat, lon)))
+                    ),
+                }
+                for lat, lon, name in prefetch_targets()
+            ],
         }
     )
-    response.status_code = 500
-    return response
+
+
+def weather_cache_entries() -> list[dict[str, Any]]:
+    """Every live weather entry with its provider and age, newest first
+
+    The metadata cache is the index: it's written alongside each entry under the
+    same key and TTL, so it lists exactly the keys the weather cache holds.
+    """
+    now = time.time()
+    entries = []
+    for key, metadata in weather_cache_metadata.items():
+        entry_key = CacheKey.parse(str(key))
+        if entry_key is None:
+            continue
+        age = max(0, int(now - metadata['fetched_at']))
+        ttl = metadata.get('ttl', WEATHER_CACHE_TTL)
+        entries.append(
+            {
+                'key': str(entry_key),
+                'location': entry_key.location,
+                'provider': entry_key.provider,
+                'units': entry_key.units,
+                'lang': entry_key.lang,
+                'age_seconds': age,
+                'ttl_seconds': ttl,
+                'expires_in_seconds': max(0, ttl - age),
+                'stale': entry_key.provider != weather_manager.primary_provider,
+            }
+        )
+    return sorted(entries, key=lambda entry: entry['age_seconds'])
 
 
 @app.route('/api/cache/stats')
@@ -1102,10 +5228,23 @@ def cache_stats() -> Response:
     return jsonify(
         {
             'weather_cache': {
+                'backend': weather_cache.kind,
                 'cache_size': len(weather_cache),
                 'max_size': weather_cache.maxsize,
                 'ttl_seconds': weather_cache.ttl,
                 'cached_locations': list(weather_cache.keys()),
+                'entries': weather_cache_entries(),
+                'stale_locations': [
+                    key
+                    for key, metadata in weather_cache_metadata.items()
+                    if metadata['provider'] != weather_manager.primary_provider
+                ],
+            },
+            'air_quality_cache': {
+                'backend': air_quality_cache.kind,
+                'cache_size': len(air_quality_cache),
+                'max_size': air_quality_cache.maxsize,
+                'ttl_seconds': air_quality_cache.ttl,
             },
             'alerts_cache': {
                 'cache_size': len(alerts_cache),
@@ -1137,8 +5276,230 @@ def cache_stats() -> Response:
                 'max_size': temperature_trends_cache.maxsize,
                 'ttl_seconds': temperature_trends_cache.ttl,
             },
+            'geocode_cache': {
+                'cache_size': len(geocode_cache),
+                'max_size': geocode_cache.maxsize,
+                'ttl_seconds': geocode_cache.ttl,
+            },
+            'history_cache': {
+                'cache_size': len(history_cache),
+                'max_size': history_cache.maxsize,
+                'ttl_seconds': history_cache.ttl,
+            },
+            'realtime': realtime_subscriptions.get_stats(),
+        }
+    )
+
+
+def probe_provider(name: str) -> dict[str, Any]:
+    """Check a weather provider responds, reusing a recent result if there is one"""
+    if name in provider_probe_cache:
+        return provider_probe_cache[name]
+
+    started = time.time()
+    try:
+        healthy = weather_manager.providers[name].health_check()
+    except Exception as e:
+        print(f'❌ Readiness probe for {name} failed: {str(e)}')
+        healthy = False
+
+    result = {
+        'status': 'up' if healthy else 'down',
+        'latency_ms': round((time.time() - started) * 1000),
+        'checked_at': datetime.now(timezone.utc).isoformat(),
+    }
+    provider_probe_cache[name] = result
+    return result
+
+
+@app.route('/api/cache', methods=['DELETE'])
+@require_admin
+def clear_weather_cache() -> Response:
+    """Drop every cached weather document, so each location refetches on next use"""
+    removed = len(weather_cache)
+    weather_cache.clear()
+    weather_cache_metadata.clear()
+    for section_cache in weather_section_caches.values():
+        section_cache.clear()
+    print(f'🧹 Cleared {removed} weather cache entries')
+    return jsonify({'success': True, 'removed': removed})
+
+
+@app.route('/api/cache/<key>', methods=['DELETE'])
+@require_admin
+def invalidate_weather_cache(key: str) -> Response:
+    """Drop one entry by its full key, or every entry for a 'lat,lon' location"""
+    entry_key = CacheKey.parse(key)
+    if entry_key is not None:
+        location = entry_key.location
+        targets = [entry_key]
+    else:
+        try:
+            lat, lon = (float(value) for value in key.split(','))
+        except ValueError:
+            raise InvalidParameter(
+                'Key must be a cache key or a lat,lon location'
+            ) from None
+        location = location_key(lat, lon)
+        targets = [
+            parsed
+            for parsed in (CacheKey.parse(str(cached)) for cached in weather_cache)
+            if parsed is not None and parsed.location == location
+        ]
+
+    removed = []
+    for target in targets:
+        weather_cache_metadata.pop(target, None)
+        if weather_cache.pop(target, None) is not None:
+            removed.append(str(target))
+    # Sections fetched on their own are the primary provider's view of the place
+    for section_cache in weather_section_caches.values():
+        section_cache.pop(str(weather_cache_key(location)), None)
+
+    if not removed:
+        raise NotFound('Cache entry not found')
+    print(f'🧹 Invalidated {", ".join(removed)}')
+    return jsonify({'success': True, 'removed': removed})
+
+
+def check_cache_usable() -> bool:
+    """Round-trip a sentinel entry through the weather cache"""
+    sentinel_key = '__readyz__'
+    try:
+        weather_cache[sentinel_key] = time.time()
+        usable = sentinel_key in weather_cache
+        del weather_cache[sentinel_key]
+    except Exception as e:
+        print(f'❌ Weather cache check failed: {str(e)}')
+        return False
+    return usable
+
+
+def query_ntp_offset(server: str, timeout: float = NTP_TIMEOUT_SECONDS) -> float | None:
+    """Ask an NTP server how far off the local clock is (positive = running slow)"""
+    request_packet = b'\x1b' + bytes(NTP_PACKET_SIZE - 1)  # Version 3, client mode
+    try:
+        with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as sock:
+            sock.settimeout(timeout)
+            sent_at = time.time()
+            sock.sendto(request_packet, (server, NTP_PORT))
+            reply, _ = sock.recvfrom(NTP_PACKET_SIZE)
+            received_at = time.time()
+    except OSError as e:
+        print(f'⚠️  NTP query to {server} failed: {str(e)}')
+        return None
+
+    if len(reply) < NTP_PACKET_SIZE:
+        return None
+    # Server transmit timestamp: 32-bit seconds plus 32-bit fraction since 1900
+    seconds, fraction = struct.unpack('!II', reply[40:48])
+    server_time = seconds - NTP_EPOCH_OFFSET + fraction / 2**32
+    return server_time - (sent_at + received_at) / 2
+
+
+def check_clock_skew(server: str) -> float | None:
+    """Measure clock skew against NTP and warn loudly when it's past the threshold"""
+    skew = query_ntp_offset(server)
+    clock_status.update(
+        {
+            'server': server,
+            'skew_seconds': round(skew, 3) if skew is not None else None,
+            'checked_at': datetime.now(timezone.utc).isoformat(),
+        }
+    )
+
+    if skew is None:
+        print(f'⚠️  Could not reach {server} - system clock skew unchecked')
+    elif abs(skew) > CLOCK_SKEW_WARNING_SECONDS:
+        print('🚨' * 20)
+        print(
+            f'🚨 System clock is off by {skew:+.1f}s (NTP: {server}) - past-hour '
+            'filtering and cache expiry will be wrong until the clock is fixed'
+        )
+        print('🚨' * 20)
+    else:
+        print(f'🕰️  System clock within {abs(skew):.2f}s of {server}')
+    return skew
+
+
+@app.errorhandler(RequestEntityTooLarge)
+def request_too_large(_error: RequestEntityTooLarge) -> Response:
+    """Refuse bodies over MAX_BODY_BYTES with a JSON 413"""
+    return weather_error_response(
+        PayloadTooLarge(
+            'Request body too large', max_bytes=app.config['MAX_CONTENT_LENGTH']
+        )
+    )
+
+
+@app.errorhandler(WeatherError)
+def weather_error_response(error: WeatherError) -> Response:
+    """A typed API error as its JSON body and status"""
+    response = jsonify(error.to_dict())
+    response.status_code = error.status
+    return response
+
+
+@app.errorhandler(InternalServerError)
+def internal_error(error: InternalServerError) -> Response | InternalServerError:
+    """Unexpected failures in the API as a JSON internal_error, pages as usual"""
+    if not request.path.startswith('/api/'):
+        return error
+    cause = error.original_exception or error
+    print(f'❌ Unhandled error in {request.path}: {type(cause).__name__}: {cause}')
+    return weather_error_response(WeatherError('Internal server error'))
+
+
+@app.route('/healthz')
+def healthz() -> Response:
+    """Liveness probe - the process is up and serving requests"""
+    return jsonify({'status': 'ok'})
+
+
+@app.route('/readyz')
+def readyz() -> Response:
+    """Readiness probe - config valid, a provider responding, cache usable"""
+    primary = weather_manager.primary_provider
+    config_ok = primary is not None and primary in weather_manager.providers
+
+    provider_names = ([primary] if primary else []) + [
+        name for name in weather_manager.fallback_providers if name != primary
+    ]
+    providers = {
+        name: probe_provider(name)
+        for name in provider_names
+        if name in weather_manager.providers
+    }
+    providers_ok = any(probe['status'] == 'up' for probe in providers.values())
+    cache_ok = check_cache_usable()
+
+    ready = config_ok and providers_ok and cache_ok
+    response = jsonify(
+        {
+            'status': 'ready' if ready else 'not_ready',
+            'checks': {
+                'config': {
+                    'status': 'ok' if config_ok else 'error',
+                    'primary': primary,
+                },
+                'providers': providers,
+                'cache': {'status': 'ok' if cache_ok else 'error'},
+                # Informational only - a skewed clock is loud in the logs instead
+                'clock': clock_status,
+                # Informational too: shedding and timeouts are load, not faults
+                'requests': {
+                    'in_flight': request_limits.in_flight,
+                    'max_concurrent': request_limits.max_concurrent,
+                    'shed': request_limits.shed,
+                    'timed_out': request_limits.timed_out,
+                },
+            },
         }
     )
+    response.headers['Cache-Control'] = 'no-cache'
+    if not ready:
+        response.status_code = 503
+    return response
 
 
 @app.route('/api/providers')
@@ -1147,77 +5508,225 @@ def get_providers() -> Response:
     return jsonify(weather_manager.get_provider_info())
 
 
+def get_air_quality(lat: float, lon: float, location_name: str) -> dict | None:
+    """Get air quality for a location, cached for 30 minutes"""
+    if not air_quality_provider:
+        return None
+
+    cache_key = CacheKey(
+        lat, lon, air_quality_provider.name, CACHED_UNITS, CACHED_LANGUAGE
+    )
+    if cache_key in air_quality_cache:
+        print(f'📦 Returning cached air quality data for {cache_key.location}')
+        return air_quality_cache[cache_key]  # type: ignore[no-any-return]
+
+    print(f'🌬️  Fetching air quality for {location_name}')
+    air_quality_data = air_quality_provider.get_weather(lat, lon, location_name)
+    if air_quality_data:
+        air_quality_cache[cache_key] = air_quality_data
+        print(f'💾 Cached air quality data for {cache_key.location}')
+    return air_quality_data
+
+
 @app.route('/api/air-quality')
 def air_quality_api() -> Response:
     """API endpoint for air quality data"""
     # AirNow requires API key - service unavailable without it
     if not air_quality_provider:
-        response = jsonify(
-            {'error': 'Air quality service unavailable - AirNow API key required'}
+        raise ProviderNotConfigured(
+            'Air quality service unavailable - AirNow API key required'
         )
-        response.status_code = 503
+
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    air_quality_data = get_air_quality(lat, lon, location_name)
+
+    if air_quality_data:
+        response = jsonify_with_fallback(air_quality_data, location_fallback)
+        response.headers['Cache-Control'] = f'public, max-age={AIR_QUALITY_CACHE_TTL}'
         return response
 
-    # Get lat/lon from URL parameters
-    lat = request.args.get('lat', type=float)
-    lon = request.args.get('lon', type=float)
-    location_name = request.args.get('location', 'Unknown Location')
+    raise ProviderFailed('Failed to fetch air quality data', air_quality_provider.name)
 
-    # Default to Chicago if no coordinates provided
-    if not lat or not lon:
-        lat = CHICAGO_LAT
-        lon = CHICAGO_LON
 
-    # Create cache key for air quality
-    cache_key = f'air_quality_{lat:.4f},{lon:.4f}'
+@app.route('/api/prefs')
+def get_prefs() -> Response:
+    """The visitor's display preferences, defaults filled in"""
+    return jsonify(visitor_prefs())
 
-    # Check cache first (air quality updates less frequently - 30 min TTL)
-    air_quality_cache_ttl = 1800  # 30 minutes
-    cache_time = int(time.time() // air_quality_cache_ttl)
-    cache_key_with_time = f'{cache_key}_{cache_time}'
 
-    if cache_key_with_time in weather_cache:
-        print(f'📦 Returning cached air quality data for {cache_key}')
-        cached_data = weather_cache[cache_key_with_time]
-        response = jsonify(cached_data)
-        response.headers['Cache-Control'] = f'public, max-age={air_quality_cache_ttl}'
-        return response
+@app.route('/api/prefs', methods=['PUT'])
+def put_prefs() -> Response:
+    """Update some or all preferences, saving them in a signed cookie"""
+    try:
+        update = validate_prefs(request.get_json(silent=True))
+    except ValueError as e:
+        raise InvalidParameter(str(e)) from e
+    if update.get('location') and not resolve_location_segment(update['location']):
+        raise InvalidLocation(f'Unknown location: {update["location"]}')
+
+    prefs = {**visitor_prefs(), **update}
+    response = jsonify(prefs)
+    response.set_cookie(
+        PREFS_COOKIE,
+        # When they changed, so conditional GETs from before don't get a 304
+        prefs_serializer.dumps({**prefs, 'saved_at': int(time.time())}),
+        max_age=PREFS_COOKIE_MAX_AGE,
+        httponly=True,
+        samesite='Lax',
+        secure=request.is_secure,
+    )
+    return response
 
-    # Fetch fresh air quality data
-    print(f'🌬️  Fetching air quality for {location_name}')
-    air_quality_data = air_quality_provider.get_weather(lat, lon, location_name)
 
-    if air_quality_data:
-        # Cache the result
-        weather_cache[cache_key_with_time] = air_quality_data
-        print(f'💾 Cached air quality data for {cache_key}')
+@app.route('/api/marine')
+def marine_api() -> Response:
+    """Wave, swell and sea surface temperature forecast for sailors and surfers"""
+    try:
+        lat = float(request.args['lat'])
+        lon = float(request.args['lon'])
+    except (KeyError, ValueError):
+        lat = lon = math.nan
+    if not (MIN_LATITUDE <= lat <= MAX_LATITUDE) or not (
+        MIN_LONGITUDE <= lon <= MAX_LONGITUDE
+    ):
+        raise InvalidLocation('Valid lat and lon required')
+    location_name = request.args.get('location', 'Unknown Location')
 
-        response = jsonify(air_quality_data)
-        response.headers['Cache-Control'] = f'public, max-age={air_quality_cache_ttl}'
-        return response
+    cache_key = f'{lat:.4f},{lon:.4f}'
+    if cache_key in marine_cache:
+        marine_data = marine_cache[cache_key]
+    else:
+        print(f'🌊 Fetching marine forecast for {location_name}')
+        marine_data = marine_provider.get_weather(lat, lon, location_name)
+        if marine_data:
+            marine_cache[cache_key] = marine_data
+
+    if not marine_data:
+        raise NotFound('No marine forecast here - is it at sea?', marine_provider.name)
+    response = jsonify({**marine_data, 'location': location_name})
+    response.headers['Cache-Control'] = f'public, max-age={MARINE_CACHE_TTL}'
+    return response
 
-    response = jsonify({'error': 'Failed to fetch air quality data'})
-    response.status_code = 500
+
+TIDE_PROVIDER = 'NOAA CO-OPS'
+
+
+@app.route('/api/tides')
+def tides_api() -> Response:
+    """Next 3 days of high/low tides at the nearest NOAA tide station"""
+    try:
+        lat = float(request.args['lat'])
+        lon = float(request.args['lon'])
+    except (KeyError, ValueError):
+        raise InvalidLocation('Valid lat and lon required') from None
+
+    try:
+        if 'stations' not in tide_station_cache:
+            tide_station_cache['stations'] = fetch_tide_stations()
+        station = nearest_station(tide_station_cache['stations'], lat, lon)
+        if station is None:
+            raise NotFound('No NOAA tide station near this location', TIDE_PROVIDER)
+
+        now = datetime.now(timezone.utc)
+        cache_key = f'{station["id"]}_{now:%Y%m%d}'
+        if cache_key not in tide_cache:
+            print(f'🌊 Fetching tide predictions for station {station["id"]}')
+            start = now.replace(hour=0, minute=0, second=0, microsecond=0)
+            tide_cache[cache_key] = fetch_tide_predictions(station['id'], start)
+        predictions = upcoming_tides(tide_cache[cache_key], now)
+    except (requests.exceptions.RequestException, ValueError) as e:
+        print(f'❌ NOAA CO-OPS error: {str(e)}')
+        raise ProviderFailed('Failed to fetch tide predictions', TIDE_PROVIDER) from e
+
+    response = jsonify(
+        {
+            'station': station,
+            'datum': TIDE_DATUM,
+            'units': 'ft',
+            'predictions': predictions,
+            'provider': TIDE_PROVIDER,
+        }
+    )
+    response.headers['Cache-Control'] = 'public, max-age=3600'
     return response
 
 
+# /api/providers/accuracy: each provider's kept next-day forecasts scored
+# against what the history then recorded (or the archive, where it has gaps)
+DEFAULT_ACCURACY_DAYS = 30
+MAX_ACCURACY_DAYS = 365
+
+
+def forecast_outcomes(
+    lat: float, lon: float, dates: list[str]
+) -> dict[str, dict[str, Any]]:
+    """What happened on each past date, for the dates there's enough data for"""
+    windows = {day: solar_day(day, lon) for day in dates}
+    hours = history_hours(
+        lat,
+        lon,
+        min(start for start, _ in windows.values()),
+        max(end for _, end in windows.values()),
+    )
+    outcomes = {}
+    for day, (start, end) in windows.items():
+        outcome = verify_day(
+            [point for hour, point in hours.items() if start <= hour <= end]
+        )
+        if outcome is not None:
+            outcomes[day] = outcome
+    return outcomes
+
+
+@app.route('/api/providers/accuracy')
+def provider_accuracy_api() -> Response:
+    """Score each provider's next-day forecasts for a location"""
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, _ = location
+    days = request.args.get('days', DEFAULT_ACCURACY_DAYS, type=int)
+    days = max(1, min(days, MAX_ACCURACY_DAYS))
+
+    today = datetime.now(timezone.utc).date()
+    # Only days that are over everywhere can be verified
+    first = (today - timedelta(days=days)).isoformat()
+    last = (today - timedelta(days=2)).isoformat()
+    forecasts = history_store.forecasts(location_key(lat, lon), first, last)
+    dates = sorted({forecast['target_date'] for forecast in forecasts})
+    outcomes = forecast_outcomes(lat, lon, dates) if dates else {}
+
+    return jsonify_with_fallback(
+        {
+            'lat': lat,
+            'lon': lon,
+            'days': days,
+            'verified_days': len(outcomes),
+            'providers': score_forecasts(forecasts, outcomes),
+        },
+        location_fallback,
+    )
+
+
 @app.route('/api/providers/switch', methods=['POST'])
+@require_admin
 def switch_provider() -> Response:
     """API endpoint to switch weather provider"""
     data = request.get_json()
     provider_name = data.get('provider')
 
     if not provider_name:
-        response = jsonify({'error': 'Provider name is required'})
-        response.status_code = 400
-        return response
+        raise InvalidParameter('Provider name is required')
 
     success = weather_manager.switch_provider(provider_name)
 
     if success:
-        # Clear cache when switching providers
-        weather_cache.clear()
-
+        # Cached entries are tagged with their provider and refresh lazily on
+        # next use, so there's no burst of upstream fetches right after a switch
         # Notify all connected clients via WebSocket
         provider_info = weather_manager.get_provider_info()
         socketio.emit(
@@ -1232,21 +5741,412 @@ def switch_provider() -> Response:
                 'provider_info': provider_info,
             }
         )
+    raise InvalidParameter(
+        f'Provider {provider_name} not found',
+        provider_name,
+        success=False,
+        available_providers=list(weather_manager.providers.keys()),
+    )
+
+
+@app.route('/api/providers', methods=['POST'])
+@require_admin
+def register_provider() -> Response:
+    """Register a provider instance (type, key and options) without restarting"""
+    data = request.get_json(silent=True)
+    try:
+        settings = ProviderSettings.from_dict(data)
+    except ValueError as e:
+        raise InvalidParameter(str(e)) from e
+    if settings.name in weather_manager.providers:
+        raise Conflict(f'Provider {settings.name} already exists', settings.name)
+
+    previous_primary = weather_manager.primary_provider
+    try:
+        provider = weather_manager.register(
+            settings, primary=data.get('primary') is True
+        )
+    except ValueError as e:
+        raise InvalidParameter(str(e), settings.name) from e
+    provider_info = weather_manager.get_provider_info()
+    if weather_manager.primary_provider != previous_primary:
+        socketio.emit(
+            'provider_switched',
+            {
+                'provider': weather_manager.primary_provider,
+                'provider_info': provider_info,
+            },
+        )
     response = jsonify(
+        {'provider': provider.get_provider_info(), 'provider_info': provider_info}
+    )
+    response.status_code = 201
+    return response
+
+
+@app.route('/api/providers/<name>', methods=['DELETE'])
+@require_admin
+def delete_provider(name: str) -> Response:
+    """Remove a provider; removing the primary hands over to the first fallback"""
+    if name not in weather_manager.providers:
+        raise NotFound('Provider not found', name)
+    if len(weather_manager.providers) == 1:
+        raise Conflict('Cannot remove the only weather provider', name)
+
+    previous_primary = weather_manager.primary_provider
+    weather_manager.remove_provider(name)
+    provider_info = weather_manager.get_provider_info()
+    if weather_manager.primary_provider != previous_primary:
+        socketio.emit(
+            'provider_switched',
+            {
+                'provider': weather_manager.primary_provider,
+                'provider_info': provider_info,
+            },
+        )
+    return jsonify({'success': True, 'name': name, 'provider_info': provider_info})
+
+
+@app.route('/api/providers/<name>/test')
+@require_admin
+def test_provider(name: str) -> Response:
+    """Run one provider end to end, bypassing the cache and fallbacks
+
+    Returns the raw upstream payload next to the processed document, so
+    mapping problems (wrong icons, missing fields) can be traced to their source.
+    """
+    provider = weather_manager.providers.get(name)
+    if provider is None:
+        raise NotFound('Provider not found', name)
+    location, location_fallback = resolve_request_location()
+    if location is None:
+        raise location_fallback_error(location_fallback or {})
+    lat, lon, location_name = location
+
+    print(f'🧪 Dry run of {name} for {lat:.4f},{lon:.4f}')
+    result = provider.dry_run(lat, lon, location_name, request.args.get('tz'))
+    response = jsonify_with_fallback(
+        {**result, 'lat': lat, 'lon': lon}, location_fallback
+    )
+    response.headers['Cache-Control'] = 'no-store'
+    return response
+
+
+@app.route('/api/tokens')
+@require_admin
+def list_tokens() -> Response:
+    """List API tokens (metadata only - secrets are never stored)"""
+    if not admin_auth_enabled():
+        raise Forbidden('Token management is disabled - set ADMIN_TOKEN')
+    return jsonify({'tokens': token_store.list_tokens()})
+
+
+@app.route('/api/tokens', methods=['POST'])
+@require_admin
+def create_token() -> Response:
+    """Create an admin or read token, returning its secret exactly once"""
+    if not admin_auth_enabled():
+        raise Forbidden('Token management is disabled - set ADMIN_TOKEN')
+
+    data = request.get_json(silent=True) or {}
+    name = data.get('name')
+    scope = data.get('scope', 'read')
+    expires_in = data.get('expires_in')
+
+    if not isinstance(name, str) or not name.strip():
+        raise InvalidParameter('Token name is required')
+    if scope not in TOKEN_SCOPES:
+        raise InvalidParameter(f'Scope must be one of: {", ".join(TOKEN_SCOPES)}')
+    if expires_in is not None and (
+        isinstance(expires_in, bool)
+        or not isinstance(expires_in, int | float)
+        or expires_in <= 0
+    ):
+        raise InvalidParameter('expires_in must be a positive number of seconds')
+
+    record, secret = token_store.create(name.strip(), scope, expires_in)
+    print(f'🔑 Created {scope} token {record["id"]} ({record["name"]})')
+    response = jsonify({**record, 'token': secret})
+    response.status_code = 201
+    return response
+
+
+@app.route('/api/tokens/<token_id>', methods=['DELETE'])
+@require_admin
+def revoke_token(token_id: str) -> Response:
+    """Revoke a token - other tokens keep working, so devices can move over"""
+    if not admin_auth_enabled():
+        raise Forbidden('Token management is disabled - set ADMIN_TOKEN')
+    try:
+        revoked = token_store.revoke(token_id)
+    except ValueError as e:
+        raise Conflict(str(e)) from e
+    if not revoked:
+        raise NotFound('Token not found')
+    print(f'🔒 Revoked token {token_id}')
+    return jsonify({'success': True, 'id': token_id})
+
+
+@app.route('/api/admin/analytics')
+@require_admin
+def admin_analytics() -> Response:
+    """Aggregate usage counts, listing locations nobody has looked at lately"""
+    if usage_analytics is None:
+        raise NotFound('Usage analytics are disabled - set ANALYTICS_ENABLED')
+    try:
+        stale_days = int(request.args.get('stale_days', ANALYTICS_STALE_DAYS))
+    except ValueError as e:
+        msg = 'stale_days must be a whole number of days'
+        raise InvalidParameter(msg) from e
+    usage_analytics.flush()
+    return jsonify(usage_analytics.snapshot(stale_days))
+
+
+@app.route('/api/admin/backup', methods=['POST'])
+@require_admin
+def admin_backup() -> Response:
+    """Archive the history, favorites, tokens and rules into BACKUP_DIR
+
+    ?download=1 also sends the archive back.
+    """
+    if BACKUP_FORMAT not in ARCHIVE_SUFFIXES:
+        msg = f'BACKUP_FORMAT must be one of {", ".join(ARCHIVE_SUFFIXES)}'
+        raise WeatherError(msg)
+    now = datetime.now(timezone.utc)
+    filename = f'state-{now.strftime("%Y%m%dT%H%M%SZ")}{BACKUP_FORMAT}'
+    path = os.path.join(BACKUP_DIR, filename)
+    try:
+        with backup_lock:
+            manifest = create_backup(path, state_paths(os.environ), now.timestamp())
+    except (OSError, ValueError, sqlite3.Error) as e:
+        print(f'❌ Backup failed: {str(e)}')
+        raise WeatherError(f'Backup failed: {e}') from e
+    print(f'💾 Backed up state to {path}')
+    if request.args.get('download') in ('1', 'true'):
+        return send_from_directory(
+            os.path.abspath(BACKUP_DIR), filename, as_attachment=True
+        )
+    return jsonify(
+        {
+            'path': path,
+            'size': os.path.getsize(path),
+            'created_at': iso_timestamp(manifest['created_at']),
+            'files': manifest['files'],
+        }
+    )
+
+
+@app.route('/api/admin/notifications/rules')
+@require_admin
+def list_notification_rules() -> Response:
+    """List notification rules and whether each is currently tripped"""
+    return jsonify(
+        {
+            'rules': notification_rules.list_rules(),
+            'channels': sorted(notification_dispatcher.channels),
+        }
+    )
+
+
+@app.route('/api/admin/notifications/rules', methods=['POST'])
+@require_admin
+def create_notification_rule() -> Response:
+    """Add a notification rule"""
+    data = request.get_json(silent=True)
+    try:
+        rule = notification_rules.create(data)
+    except ValueError as e:
+        raise InvalidParameter(str(e)) from e
+    channels = notification_dispatcher.channels
+    unknown = [name for name in rule['channels'] if name not in channels]
+    print(f'🔔 Created notification rule {rule["id"]} ({rule["name"]})')
+    response = jsonify({**rule, 'unknown_channels': unknown})
+    response.status_code = 201
+    return response
+
+
+@app.route('/api/admin/notifications/rules/<rule_id>', methods=['DELETE'])
+@require_admin
+def delete_notification_rule(rule_id: str) -> Response:
+    """Remove a notification rule"""
+    if not notification_rules.delete(rule_id):
+        raise NotFound('Rule not found')
+    print(f'🔕 Deleted notification rule {rule_id}')
+    return jsonify({'success': True, 'id': rule_id})
+
+
+@app.route('/api/admin/webhooks')
+@require_admin
+def list_webhooks() -> Response:
+    """List webhook subscriptions and their latest deliveries"""
+    return jsonify(
         {
-            'success': False,
-            'error': f'Provider {provider_name} not found',
-            'available_providers': list(weather_manager.providers.keys()),
+            'webhooks': webhook_subscriptions.list_subscriptions(),
+            'events': list(WEBHOOK_EVENTS),
+            'template_fields': list(TEMPLATE_FIELDS),
         }
     )
-    response.status_code = 400
+
+
+@app.route('/api/admin/webhooks', methods=['POST'])
+@require_admin
+def create_webhook() -> Response:
+    """Add a webhook subscription; its signing secret is only shown here"""
+    data = request.get_json(silent=True)
+    try:
+        subscription = webhook_subscriptions.create(data)
+    except ValueError as e:
+        raise InvalidParameter(str(e)) from e
+    print(f'🪝 Created webhook {subscription["id"]} ({subscription["name"]})')
+    response = jsonify(subscription)
+    response.status_code = 201
+    return response
+
+
+@app.route('/api/admin/webhooks/<subscription_id>', methods=['DELETE'])
+@require_admin
+def delete_webhook(subscription_id: str) -> Response:
+    """Remove a webhook subscription"""
+    if not webhook_subscriptions.delete(subscription_id):
+        raise NotFound('Webhook not found')
+    print(f'🪝 Deleted webhook {subscription_id}')
+    return jsonify({'success': True, 'id': subscription_id})
+
+
+@app.route('/api/admin/webhooks/<subscription_id>/test', methods=['POST'])
+@require_admin
+def test_webhook(subscription_id: str) -> Response:
+    """Send a sample event with the location's current weather"""
+    subscription = webhook_subscriptions.get(subscription_id)
+    if subscription is None:
+        raise NotFound('Webhook not found')
+    weather_data = tracked_weather(
+        subscription['lat'], subscription['lon'], subscription['location']
+    )
+    event = build_event('test', subscription, weather_data or {})
+    delivered = deliver_webhook(subscription, event)
+    last_delivery = (webhook_subscriptions.get(subscription_id) or {}).get(
+        'last_delivery'
+    )
+    response = jsonify({'delivered': delivered, 'last_delivery': last_delivery})
+    response.status_code = 200 if delivered else 502
+    return response
+
+
+@app.route('/api/admin/cities')
+@require_admin
+def list_cities() -> Response:
+    """List the city registry with each city's aliases"""
+    return jsonify({'cities': city_registry.entries(), 'path': city_registry.path})
+
+
+@app.route('/api/admin/cities/<key>', methods=['PUT'])
+@require_admin
+def put_city(key: str) -> Response:
+    """Add a city to the registry, or replace its coordinates and aliases"""
+    existed = normalize_city_name(key) in city_registry.coords
+    try:
+        key, city = city_registry.add(key, request.get_json(silent=True))
+    except ValueError as e:
+        raise InvalidParameter(str(e)) from e
+    except OSError as e:
+        raise WeatherError(f'Could not save city registry: {str(e)}') from e
+    print(f'🏙️  {"Updated" if existed else "Added"} city {key} ({city["name"]})')
+    response = jsonify({'key': key, **city})
+    response.status_code = 200 if existed else 201
+    return response
+
+
+@app.route('/api/admin/cities/<key>', methods=['DELETE'])
+@require_admin
+def delete_city(key: str) -> Response:
+    """Remove a city and its aliases from the registry"""
+    try:
+        removed = city_registry.remove(key)
+    except OSError as e:
+        raise WeatherError(f'Could not save city registry: {str(e)}') from e
+    if not removed:
+        raise LocationNotFound('City not found')
+    print(f'🏙️  Removed city {key}')
+    return jsonify({'success': True, 'key': normalize_city_name(key)})
+
+
+def favorite_from_request(data: Any) -> Any:
+    """Fill a favorite's name and coordinates in from a 'location' if given"""
+    if not isinstance(data, dict) or 'location' not in data:
+        return data
+    location = data['location']
+    resolved = resolve_location_segment(location) if isinstance(location, str) else None
+    if not resolved:
+        msg = f'Unknown location: {location}'
+        raise ValueError(msg)
+    lat, lon, name = resolved
+    favorite = {key: value for key, value in data.items() if key != 'location'}
+    return {'name': name, **favorite, 'lat': lat, 'lon': lon}
+
+
+@app.route('/api/favorites')
+def list_favorites() -> Response:
+    """List saved favorite locations in display order"""
+    return jsonify({'favorites': favorites_store.list_favorites()})
+
+
+@app.route('/api/favorites', methods=['POST'])
+@require_admin
+def create_favorite() -> Response:
+    """Save a favorite from a name/lat/lon or a city, lat,lon or place name"""
+    try:
+        favorite = favorites_store.add(
+            favorite_from_request(request.get_json(silent=True))
+        )
+    except ValueError as e:
+        raise InvalidParameter(str(e)) from e
+    print(f'⭐ Added favorite {favorite["id"]} ({favorite["name"]})')
+    response = jsonify(favorite)
+    response.status_code = 201
     return response
 
 
+@app.route('/api/favorites/<favorite_id>')
+def get_favorite(favorite_id: str) -> Response:
+    """A single saved favorite"""
+    favorite = favorites_store.get(favorite_id)
+    if favorite is None:
+        raise NotFound('Favorite not found')
+    return jsonify(favorite)
+
+
+@app.route('/api/favorites/<favorite_id>', methods=['PUT'])
+@require_admin
+def update_favorite(favorite_id: str) -> Response:
+    """Rename, move or reorder a favorite"""
+    try:
+        favorite = favorites_store.update(
+            favorite_id, favorite_from_request(request.get_json(silent=True))
+        )
+    except ValueError as e:
+        raise InvalidParameter(str(e)) from e
+    if favorite is None:
+        raise NotFound('Favorite not found')
+    return jsonify(favorite)
+
+
+@app.route('/api/favorites/<favorite_id>', methods=['DELETE'])
+@require_admin
+def delete_favorite(favorite_id: str) -> Response:
+    """Remove a favorite"""
+    if not favorites_store.delete(favorite_id):
+        raise NotFound('Favorite not found')
+    print(f'⭐ Removed favorite {favorite_id}')
+    return jsonify({'success': True, 'id': favorite_id})
+
+
 # WebSocket event handlers
 @socketio.on('connect')
-def handle_connect() -> None:
-    """Handle client connection"""
+def handle_connect() -> bool | None:
+    """Handle client connection, refusing it without a read token if one is required"""
+    if REQUIRE_READ_TOKEN and not token_grants(request_token(), 'read'):
+        return False
     print(f'🔗 Client connected: {request.sid}')
 
     # Send current provider info to the newly connected client
@@ -1263,9 +6163,12 @@ def handle_disconnect() -> None:
 @socketio.on('request_weather_update')
 def handle_weather_update_request(data: dict) -> None:
     """Handle weather update request from client"""
-    lat = data.get('lat', CHICAGO_LAT)
-    lon = data.get('lon', CHICAGO_LON)
-    location = data.get('location', 'Chicago')
+    resolved, location_fallback = resolve_request_location(data or {})
+    if resolved is None:
+        error = location_fallback_error(location_fallback or {})
+        emit('weather_error', error.to_dict())
+        return
+    lat, lon, location = resolved
     timezone_name = data.get('timezone')  # Optional override
 
     print(f'🌤️  Weather update requested for {location}')
@@ -1275,11 +6178,14 @@ def handle_weather_update_request(data: dict) -> None:
 
     if weather_data:
         # Send updated weather data to requesting client
-        emit('weather_update', weather_data)
+        update = serialize_weather(weather_data)
+        if location_fallback:
+            update['location_fallback'] = location_fallback
+        emit('weather_update', update)
 
-        # Update cache
+        # Update cache and notify stream subscribers
         cache_key = f'{lat:.4f},{lon:.4f}'
-        weather_cache[cache_key] = weather_data
+        store_weather_data(cache_key, weather_data)
     else:
         emit('weather_error', {'error': 'Failed to fetch weather data'})
 
@@ -1290,16 +6196,136 @@ def handle_ping() -> None:
     emit('pong', {'timestamp': time.time()})
 
 
-@app.route('/static/<path:filename>')
+def parse_realtime_locations(data: dict | None) -> list[tuple[float, float, str]]:
+    """Pull valid coordinates out of a /ws subscribe or unsubscribe message"""
+    locations = []
+    for entry in (data or {}).get('locations', []):
+        try:
+            lat = float(entry['lat'])
+            lon = float(entry['lon'])
+        except (KeyError, TypeError, ValueError):
+            continue
+        if not (MIN_LATITUDE <= lat <= MAX_LATITUDE):
+            continue
+        if not (MIN_LONGITUDE <= lon <= MAX_LONGITUDE):
+            continue
+        locations.append((lat, lon, str(entry.get('location', location_key(lat, lon)))))
+    return locations
+
+
+@socketio.on('connect', namespace=REALTIME_NAMESPACE)
+def handle_realtime_connect() -> bool | None:
+    """Handle live-update client connection, refused without a required read token"""
+    if REQUIRE_READ_TOKEN and not token_grants(request_token(), 'read'):
+        return False
+    realtime_subscriptions.connect(request.sid)
+    print(f'🔗 Live-update client connected: {request.sid}')
+
+
+@socketio.on('disconnect', namespace=REALTIME_NAMESPACE)
+def handle_realtime_disconnect() -> None:
+    """Handle live-update client disconnection and drop its subscriptions"""
+    locations = realtime_subscriptions.disconnect(request.sid)
+    print(
+        f'📡 Live-update client disconnected: {request.sid} '
+        f'({len(locations)} subscriptions)'
+    )
+
+
+@socketio.on('subscribe', namespace=REALTIME_NAMESPACE)
+def handle_realtime_subscribe(data: dict) -> None:
+    """Subscribe a live-update client to one or more locations"""
+    rejected = []
+    for lat, lon, location in parse_realtime_locations(data):
+        cache_key = location_key(lat, lon)
+
+        # Load data before joining so the client doesn't get its own refresh twice
+        weather_data = location_weather(lat, lon, location)
+
+        if not realtime_subscriptions.subscribe(request.sid, cache_key):
+            rejected.append(cache_key)
+            continue
+        join_room(cache_key)
+
+        if not weather_data:
+            emit(
+                'weather_error',
+                {'location': cache_key, 'error': 'Failed to fetch weather data'},
+            )
+            continue
+
+        realtime_subscriptions.update_snapshot(
+            cache_key, 'current', serialize_weather(weather_data).get('current', {})
+        )
+        alerts_data = alerts_cache.get(f'alerts_{cache_key}')
+        if alerts_data:
+            realtime_subscriptions.update_snapshot(
+                cache_key, 'alerts', alerts_by_id(alerts_data)
+            )
+
+        # New subscribers start from the full document, then receive patches
+        snapshot = realtime_subscriptions.snapshot(cache_key)
+        emit(
+            'weather_patch',
+            {
+                'location': cache_key,
+                'patch': [{'op': 'replace', 'path': '', 'value': snapshot}],
+            },
+        )
+
+    emit(
+        'subscribed',
+        {
+            'locations': realtime_subscriptions.subscriptions(request.sid),
+            'rejected': rejected,
+        },
+    )
+
+
+@socketio.on('unsubscribe', namespace=REALTIME_NAMESPACE)
+def handle_realtime_unsubscribe(data: dict) -> None:
+    """Stop live updates for one or more locations"""
+    for lat, lon, _location in parse_realtime_locations(data):
+        cache_key = location_key(lat, lon)
+        realtime_subscriptions.unsubscribe(request.sid, cache_key)
+        leave_room(cache_key)
+
+    emit(
+        'unsubscribed',
+        {'locations': realtime_subscriptions.subscriptions(request.sid)},
+    )
+
+
+@socketio.on('ping', namespace=REALTIME_NAMESPACE)
+def handle_realtime_ping() -> None:
+    """Keep a live-update connection alive"""
+    realtime_subscriptions.touch(request.sid)
+    emit('pong', {'timestamp': time.time()})
+
+
+@app.route('/static/<path:filename>', endpoint='static')
 def static_files(filename: str) -> Response:
-    """Serve static files"""
-    return send_from_directory('static', filename)
+    """Serve static files, cached for good when the URL carries the content hash"""
+    response = send_from_directory('static', filename)
+    response.headers['Cache-Control'] = asset_manifest.cache_control(
+        filename, request.args.get('v')
+    )
+    return response
 
 
 @app.route('/sw.js')
 def service_worker() -> Response:
-    """Serve service worker from root for security"""
-    response = send_from_directory('static', 'sw.js')
+    """Serve the service worker from root, rendered for the deployed assets"""
+    response = Response(
+        render_template(
+            'sw.js',
+            cache_version=asset_manifest.fingerprint(),
+            static_files=shell_urls(asset_manifest.url),
+            weather_icons=weather_icon_urls(
+                asset_manifest.url, list(asset_manifest.hashes)
+            ),
+        )
+    )
     # Cache control headers
     response.headers['Cache-Control'] = 'no-cache, no-store, must-revalidate'
     response.headers['Pragma'] = 'no-cache'
@@ -1314,13 +6340,138 @@ def service_worker() -> Response:
 
 @app.route('/manifest.json')
 def manifest() -> Response:
-    """Serve manifest file from root"""
-    response = send_from_directory('static', 'manifest.json')
+    """Serve the web app manifest, with shortcuts to the saved favorites"""
+    shortcuts = [
+        {
+            'name': favorite['name'],
+            'url': location_path(
+                favorite['lat'], favorite['lon'], favorite['name'], None
+            ),
+        }
+        for favorite in favorites_store.list_favorites()
+    ]
+    response = jsonify(build_manifest(asset_manifest.url, shortcuts))
     response.headers['Content-Type'] = 'application/manifest+json'
     return response
 
 
-if __name__ == '__main__':
+config_reload_lock = threading.Lock()
+
+
+def reload_configuration() -> bool:
+    """Re-read the config file and apply what can change without a restart
+
+    The provider list and keys, the city table, the default cache TTL, the
+    notification rules, API keys, trusted proxies and the TLS certificate are
+    swapped in; requests already running finish with what they started with.
+    Other settings need a restart.
+    """
+    global config_file, WEATHER_CACHE_TTL  # noqa: PLW0603
+    with config_reload_lock:
+        try:
+            config_file = reload_config_file(
+                config_file, os.getenv('WEATHER_CONFIG', DEFAULT_CONFIG_PATH)
+            )
+        except (OSError, ConfigError) as e:
+            print(f'❌ Config reload failed, keeping the current settings: {e}')
+            return False
+
+        try:
+            WEATHER_CACHE_TTL = int(os.getenv('CACHE_TTL', '180'))
+        except ValueError as e:
+            print(f'❌ Keeping cache TTL {WEATHER_CACHE_TTL}s: {e}')
+        try:
+            settings = load_provider_settings(
+                os.getenv('PROVIDERS_CONFIG_PATH', 'data/providers.json'),
+                entries=config_file.providers,
+            )
+        except (OSError, ValueError) as e:
+            print(f'❌ Invalid provider config ({e}) - keeping the current providers')
+        else:
+            # The health monitor announces a new primary to connected clients
+            weather_manager.reconfigure(settings)
+        # The registry file, when there is one, still wins over the config file
+        if os.path.exists(city_registry.path):
+            city_registry.load()
+        else:
+            try:
+                city_registry.replace(config_file.cities or DEFAULT_CITIES)
+            except ValueError as e:
+                print(f'❌ Keeping the current cities: {e}')
+        notification_rules.load()
+        webhook_subscriptions.load()
+        load_api_keys()
+        load_stations()
+        try:
+            proxy_headers.trusted = load_trusted_proxies()
+        except ValueError as e:
+            print(f'❌ Keeping the current trusted proxies: {e}')
+        # New connections get the renewed certificate; open ones keep theirs
+        tls_context = app.config.get('TLS_CONTEXT')
+        if tls_context is not None:
+            try:
+                tls_context.load_cert_chain(
+                    os.environ['TLS_CERT_FILE'], os.environ['TLS_KEY_FILE']
+                )
+            except (KeyError, OSError, ssl.SSLError) as e:
+                print(f'❌ Keeping the current TLS certificate: {e}')
+    print('🔄 Configuration reloaded')
+    return True
+
+
+def handle_sighup(_signum: int, _frame: Any) -> None:
+    """Reload the configuration off the signal handler, in a background task"""
+    socketio.start_background_task(reload_configuration)
+
+
+def run_server() -> None:
+    """Start the background jobs and serve on HOST:PORT until stopped"""
     port = int(os.getenv('PORT', '5001'))
     host = os.getenv('HOST', '127.0.0.1')  # Default to localhost, allow override
-    socketio.run(app, debug=False, host=host, port=port, allow_unsafe_werkzeug=True)
+    # Optional HTTPS without a reverse proxy, from TLS_CERT_FILE and TLS_KEY_FILE
+    app.config['TLS_CONTEXT'] = load_tls_context()
+    if NTP_SERVER:
+        check_clock_skew(NTP_SERVER)
+    if commute_corridors:
+        socketio.start_background_task(run_corridor_prewarm_scheduler)
+    if influx_writer is not None:
+        socketio.start_background_task(influx_writer.run, socketio.sleep)
+    if tracer.exporter is not None:
+        socketio.start_background_task(tracer.exporter.run, socketio.sleep)
+    socketio.start_background_task(run_notification_scheduler)
+    socketio.start_background_task(run_prefetch_scheduler)
+    socketio.start_background_task(run_provider_health_monitor)
+    socketio.start_background_task(
+        run_history_compactor, CronSchedule(HISTORY_COMPACT_CRON)
+    )
+    if DIGEST_CRON:
+        socketio.start_background_task(run_digest_scheduler, CronSchedule(DIGEST_CRON))
+    if hasattr(signal, 'SIGHUP'):  # Not on Windows
+        signal.signal(signal.SIGHUP, handle_sighup)
+
+    # A socket passed in by systemd, or UNIX_SOCKET for a proxy on the same
+    # machine, replaces HOST:PORT
+    listen_fd = systemd_listen_fd()
+    unix_socket = os.getenv('UNIX_SOCKET', '').strip()
+    if listen_fd is not None or unix_socket:
+        server = make_socket_server(
+            app,
+            unix_socket,
+            listen_fd,
+            app.config['TLS_CONTEXT'],
+            int(os.getenv('UNIX_SOCKET_MODE', '660'), 8),
+        )
+        server.serve_forever()
+        return
+    socketio.run(
+        app,
+        debug=False,
+        host=host,
+        port=port,
+        allow_unsafe_werkzeug=True,
+        ssl_context=app.config['TLS_CONTEXT'],
+    )
+
+
+if __name__ == '__main__':
+    run_server()
@@ -0,0 +1,197 @@
+# ABOUTME: Publishes current conditions and forecasts to MQTT (3.1.1, QoS 0)
+# ABOUTME: Emits Home Assistant discovery configs so the dashboard is an HA source
+
+import json
+import re
+import socket
+import struct
+import threading
+import unicodedata
+from typing import Any
+
+
+MQTT_TIMEOUT_SECONDS = 10
+MQTT_KEEPALIVE_SECONDS = 60
+CONNACK_ACCEPTED = 0
+
+# MQTT control packet types (upper nibble of the fixed header)
+CONNECT = 0x10
+CONNACK = 0x20
+PUBLISH = 0x30
+DISCONNECT = 0xE0
+RETAIN_FLAG = 0x01
+
+# Home Assistant sensors: (field in current conditions, name, unit, device class)
+HA_SENSORS = [
+    ('temperature', 'Temperature', '°F', 'temperature'),
+    ('feels_like', 'Feels like', '°F', 'temperature'),
+    ('dew_point', 'Dew point', '°F', 'temperature'),
+    ('humidity', 'Humidity', '%', 'humidity'),
+    ('wind_speed', 'Wind speed', 'mph', 'wind_speed'),
+    ('wind_gust', 'Wind gust', 'mph', 'wind_speed'),
+    ('wind_direction', 'Wind direction', '°', None),
+    ('pressure', 'Pressure', 'hPa', 'atmospheric_pressure'),
+    ('uv_index', 'UV index', None, None),
+    ('precipitation_rate', 'Precipitation rate', 'in/h', 'precipitation_intensity'),
+    ('precipitation_probability', 'Precipitation probability', '%', None),
+    ('summary', 'Conditions', None, None),
+]
+
+
+class MqttError(Exception):
+    """The broker refused or dropped the connection"""
+
+
+def topic_slug(name: str) -> str:
+    """Turn a location name into an ASCII topic level ('São Paulo' -> 'sao_paulo')"""
+    decomposed = unicodedata.normalize('NFKD', name)
+    ascii_name = decomposed.encode('ascii', 'ignore').decode().lower()
+    return re.sub(r'[^a-z0-9]+', '_', ascii_name).strip('_') or 'location'
+
+
+def encode_string(value: str) -> bytes:
+    """Length-prefixed UTF-8 string as used throughout MQTT packets"""
+    encoded = value.encode('utf-8')
+    return struct.pack('!H', len(encoded)) + encoded
+
+
+def encode_packet(header: int, body: bytes) -> bytes:
+    """Prefix a packet body with its fixed header and variable-length size"""
+    remaining = len(body)
+    size = bytearray()
+    while True:
+        byte, remaining = remaining % 128, remaining // 128
+        size.append(byte | 0x80 if remaining else byte)
+        if not remaining:
+            break
+    return bytes([header]) + bytes(size) + body
+
+
+def connect_packet(client_id: str, username: str | None, password: str | None) -> bytes:
+    """Build a clean-session CONNECT packet"""
+    flags = 0x02  # Clean session
+    payload = encode_string(client_id)
+    if username:
+        flags |= 0x80
+        payload += encode_string(username)
+        if password:
+            flags |= 0x40
+            payload += encode_string(password)
+    variable = encode_string('MQTT') + bytes([4, flags])
+    variable += struct.pack('!H', MQTT_KEEPALIVE_SECONDS)
+    return encode_packet(CONNECT, variable + payload)
+
+
+def publish_packet(topic: str, payload: str, retain: bool) -> bytes:
+    """Build a QoS 0 PUBLISH packet"""
+    header = PUBLISH | (RETAIN_FLAG if retain else 0)
+    return encode_packet(header, encode_string(topic) + payload.encode('utf-8'))
+
+
+class MqttPublisher:
+    """Publishes weather to a broker, one short-lived connection per update"""
+
+    def __init__(
+        self,
+        host: str,
+        port: int = 1883,
+        username: str | None = None,
+        password: str | None = None,
+        topic_prefix: str = 'weather-dashboard',
+        discovery_prefix: str = 'homeassistant',
+    ) -> None:
+        self.host = host
+        self.port = port
+        self.username = username
+        self.password = password
+        self.topic_prefix = topic_prefix.rstrip('/')
+        self.discovery_prefix = discovery_prefix.rstrip('/')
+        self.discovered: set[str] = set()
+        self._lock = threading.Lock()
+
+    def send(self, messages: list[tuple[str, str, bool]]) -> None:
+        """Connect, publish (topic, payload, retain) messages and disconnect"""
+        with socket.create_connection(
+            (self.host, self.port), timeout=MQTT_TIMEOUT_SECONDS
+        ) as sock:
+            client_id = f'{self.topic_prefix}-{threading.get_ident()}'[:23]
+            sock.sendall(connect_packet(client_id, self.username, self.password))
+            connack = sock.recv(4)
+            if len(connack) < 4 or connack[0] != CONNACK:  # noqa: PLR2004
+                msg = 'No CONNACK from broker'
+                raise MqttError(msg)
+            if connack[3] != CONNACK_ACCEPTED:
+                msg = f'Broker refused connection (code {connack[3]})'
+                raise MqttError(msg)
+            for topic, payload, retain in messages:
+                sock.sendall(publish_packet(topic, payload, retain))
+            sock.sendall(encode_packet(DISCONNECT, b''))
+
+    def state_topic(self, slug: str, kind: str) -> str:
+        """Topic carrying a location's current conditions or forecast"""
+        return f'{self.topic_prefix}/{slug}/{kind}'
+
+    def discovery_messages(
+        self, slug: str, location: str
+    ) -> list[tuple[str, str, bool]]:
+        """Retained Home Assistant discovery configs for a location's sensors"""
+        device = {
+            'identifiers': [f'weather_dashboard_{slug}'],
+            'name': f'Weather {location}',
+            'manufacturer': 'weather-dashboard',
+        }
+        messages = []
+        for field, name, unit, device_class in HA_SENSORS:
+            unique_id = f'weather_dashboard_{slug}_{field}'
+            config: dict[str, Any] = {
+                'name': name,
+                'unique_id': unique_id,
+                'state_topic': self.state_topic(slug, 'current'),
+                'value_template': f'{{{{ value_json.{field} }}}}',
+                'device': device,
+            }
+            if unit:
+                config['unit_of_measurement'] = unit
+                config['state_class'] = 'measurement'
+            if device_class:
+                config['device_class'] = device_class
+            if field == 'summary':
+                # The forecast rides along as attributes of the conditions sensor
+                config['json_attributes_topic'] = self.state_topic(slug, 'forecast')
+            messages.append(
+                (
+                    f'{self.discovery_prefix}/sensor/{unique_id}/config',
+                    json.dumps(config),
+                    True,
+                )
+            )
+        return messages
+
+    def publish_weather(self, location: str, weather_data: dict) -> None:
+        """Publish current conditions and forecast, announcing new locations first"""
+        slug = topic_slug(location)
+        messages = []
+        with self._lock:
+            announce = slug not in self.discovered
+        if announce:
+            messages.extend(self.discovery_messages(slug, location))
+
+        forecast = {
+            'hourly': weather_data.get('hourly', []),
+            'daily': weather_data.get('daily', []),
+        }
+        messages.append(
+            (
+                self.state_topic(slug, 'current'),
+                json.dumps(weather_data.get('current', {})),
+                True,
+            )
+        )
+        messages.append(
+            (self.state_topic(slug, 'forecast'), json.dumps(forecast), True)
+        )
+        self.send(messages)
+
+        if announce:
+            with self._lock:
+                self.discovered.add(slug)
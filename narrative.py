@@ -0,0 +1,167 @@
+# ABOUTME: Plain-language forecast paragraphs for screen readers and voice assistants
+# ABOUTME: Rule-based: the conditions now, when precipitation starts or stops, temps
+
+from datetime import datetime
+from typing import Any
+
+from i18n import describe_wind
+from weather_core.units import convert_value
+
+
+NARRATIVE_STYLES = ('brief', 'detailed')
+DEFAULT_NARRATIVE_STYLE = 'brief'
+NARRATIVE_LOOKAHEAD_HOURS = 12
+LIKELY_PRECIPITATION_PERCENT = 50
+
+# How each icon code reads mid-sentence
+CONDITION_PHRASES = {
+    'clear-day': 'sunny',
+    'clear-night': 'clear',
+    'partly-cloudy-day': 'partly cloudy',
+    'partly-cloudy-night': 'partly cloudy',
+    'cloudy': 'cloudy',
+    'fog': 'foggy',
+    'wind': 'windy',
+    'light-rain': 'light rain',
+    'rain': 'rain',
+    'heavy-rain': 'heavy rain',
+    'light-snow': 'light snow',
+    'snow': 'snow',
+    'heavy-snow': 'heavy snow',
+    'sleet': 'sleet',
+    'hail': 'hail',
+    'thunderstorm': 'thunderstorms',
+}
+WET_ICONS = {
+    'light-rain',
+    'rain',
+    'heavy-rain',
+    'light-snow',
+    'snow',
+    'heavy-snow',
+    'sleet',
+    'hail',
+    'thunderstorm',
+}
+
+# (first local hour, phrase) - anything before the first is overnight
+PARTS_OF_DAY = [
+    (5, 'this morning'),
+    (12, 'this afternoon'),
+    (17, 'this evening'),
+    (21, 'tonight'),
+]
+OVERNIGHT = 'overnight'
+EVENING_START_HOUR = 17
+
+
+def condition_phrase(icon_code: str | None) -> str:
+    """Mid-sentence wording for an icon code"""
+    return CONDITION_PHRASES.get(icon_code or '', 'cloudy')
+
+
+def local_hour(hour: dict[str, Any]) -> int | None:
+    """The local clock hour of a forecast entry, if its time parses"""
+    try:
+        return datetime.fromisoformat(hour['time'].replace('Z', '+00:00')).hour
+    except (KeyError, TypeError, ValueError, AttributeError):
+        return None
+
+
+def part_of_day(clock_hour: int) -> str:
+    """'this morning', 'tonight' and so on for a local hour"""
+    phrase = OVERNIGHT
+    for start, name in PARTS_OF_DAY:
+        if clock_hour >= start:
+            phrase = name
+    return phrase
+
+
+def is_wet(hour: dict[str, Any]) -> bool:
+    """Whether precipitation is expected in a forecast hour"""
+    return (
+        hour.get('icon') in WET_ICONS
+        or (hour.get('rain') or 0) >= LIKELY_PRECIPITATION_PERCENT
+    )
+
+
+def precipitation_change(current: dict[str, Any], hourly: list[dict]) -> str | None:
+    """'rain arriving around 4pm', or 'ending around 9am' if it's wet already"""
+    upcoming = hourly[:NARRATIVE_LOOKAHEAD_HOURS]
+    if current.get('icon') in WET_ICONS:
+        dry = next((hour for hour in upcoming if not is_wet(hour)), None)
+        if dry is None:
+            return None
+        return f'ending around {dry.get("t")}'
+
+    wet = next((hour for hour in upcoming if is_wet(hour)), None)
+    if wet is None:
+        return None
+    # Likely but not in the icon: the hour itself may still show clouds
+    icon = wet.get('icon') if wet.get('icon') in WET_ICONS else 'rain'
+    return f'{condition_phrase(icon)} arriving around {wet.get("t")}'
+
+
+def sentence(text: str) -> str:
+    """Capitalize the first letter only, keeping labels like '4pm' intact"""
+    return text[:1].upper() + text[1:]
+
+
+def temperature(value: Any, units: str) -> Any:
+    """A °F reading in the visitor's units"""
+    return convert_value(value, 'temperature') if units == 'metric' else value
+
+
+def build_narrative(
+    weather: dict[str, Any],
+    style: str = DEFAULT_NARRATIVE_STYLE,
+    units: str = 'imperial',
+) -> str:
+    """A paragraph describing a weather document, e.g. 'Cloudy this morning, ...'"""
+    current = weather.get('current', {})
+    hourly = weather.get('hourly', [])
+    daily = weather.get('daily', [])
+
+    clock_hour = local_hour(hourly[0]) if hourly else None
+    opening = condition_phrase(current.get('icon'))
+    if clock_hour is not None:
+        opening = f'{opening} {part_of_day(clock_hour)}'
+    clauses = [opening]
+
+    change = precipitation_change(current, hourly)
+    if change:
+        clauses.append(change)
+    if daily:
+        # Once the day's high is behind us, the low is what's still to come
+        if clock_hour is not None and clock_hour >= EVENING_START_HOUR:
+            clauses.append(f'low of {temperature(daily[0].get("l"), units)}')
+        else:
+            clauses.append(f'high of {temperature(daily[0].get("h"), units)}')
+    sentences = [sentence(', '.join(clauses)) + '.']
+
+    if style == 'detailed':
+        if current.get('temperature') is not None:
+            now = temperature(current['temperature'], units)
+            feels_like = temperature(current.get('feels_like'), units)
+            reading = f'Currently {now} degrees'
+            if feels_like is not None and feels_like != now:
+                reading += f', feeling like {feels_like}'
+            sentences.append(f'{reading}.')
+        if current.get('wind_speed') is not None:
+            wind = describe_wind(current['wind_speed'], current.get('wind_direction'))
+            sentences.append(f'{sentence(wind)}.')
+        upcoming = hourly[:NARRATIVE_LOOKAHEAD_HOURS]
+        chances = [hour.get('rain') or 0 for hour in upcoming]
+        if any(chances):
+            sentences.append(
+                f'Chance of precipitation up to {max(chances)} percent'
+                f' over the next {NARRATIVE_LOOKAHEAD_HOURS} hours.'
+            )
+        if len(daily) > 1:
+            tomorrow = daily[1]
+            sentences.append(
+                f'Tomorrow, {condition_phrase(tomorrow.get("icon"))}'
+                f' with a high of {temperature(tomorrow.get("h"), units)}'
+                f' and a low of {temperature(tomorrow.get("l"), units)}.'
+            )
+    return ' '.join(sentences)
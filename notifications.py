@@ -0,0 +1,754 @@
+# ABOUTME: Threshold notification rules ("rain chance over 60% tomorrow") saved to disk
+# ABOUTME: Rules are checked against fresh weather and sent to channels when they trip
+
+import json
+import operator
+import os
+import secrets
+import smtplib
+import threading
+import time
+from collections import deque
+from collections.abc import Callable
+from datetime import date, datetime, timedelta, timezone, tzinfo
+from email.message import EmailMessage
+from typing import Any, Protocol
+
+import requests
+
+from weather_core.formatting import hour_label as format_hour
+
+
+RULE_METRICS = (
+    'temperature',
+    'feels_like',
+    'precipitation_probability',
+    'wind_speed',
+    'wind_gust',
+    'humidity',
+    'uv_index',
+)
+RULE_WINDOWS = ('now', 'next_24h', 'today', 'tonight', 'tomorrow')
+RULE_OPERATORS: dict[str, Callable[[float, float], bool]] = {
+    '>': operator.gt,
+    '>=': operator.ge,
+    '<': operator.lt,
+    '<=': operator.le,
+}
+OPERATOR_WORDS = {'>': 'above', '>=': 'at or above', '<': 'below', '<=': 'at or below'}
+METRIC_UNITS = {
+    'temperature': '°F',
+    'feels_like': '°F',
+    'precipitation_probability': '%',
+    'wind_speed': ' mph',
+    'wind_gust': ' mph',
+    'humidity': '%',
+    'uv_index': '',
+}
+MAX_LATITUDE = 90
+MAX_LONGITUDE = 180
+WEBHOOK_TIMEOUT_SECONDS = 10
+# NWS severity colors (as on the dashboard's alert cards); rules use the default
+SEVERITY_COLORS = {
+    'extreme': '#8B0000',
+    'severe': '#FF0000',
+    'moderate': '#FF8C00',
+    'minor': '#FFD700',
+}
+DEFAULT_COLOR = '#1E90FF'
+NTFY_TIMEOUT_SECONDS = 10
+# ntfy priorities run 1 (min) to 5 (urgent); NWS severities map onto the top end
+NTFY_PRIORITIES = {'extreme': 5, 'severe': 4, 'moderate': 3, 'minor': 3}
+NTFY_DEFAULT_PRIORITY = 3
+PUSHOVER_URL = 'https://api.pushover.net/1/messages.json'
+SMTP_TIMEOUT_SECONDS = 30
+SMTPS_PORT = 465  # Implicit TLS; other ports upgrade with STARTTLS when offered
+URGENT_SEVERITIES = ('extreme', 'severe')
+REPEAT_MINUTES = 60  # Same rule or alert at most this often per channel
+RAIN_WINDOW_THRESHOLD = 40  # % chance for an hour to count toward a rain window
+SEVERITY_ORDER = ('minor', 'moderate', 'severe', 'extreme')
+TONIGHT_START_HOUR = 18
+TONIGHT_END_HOUR = 6  # Next morning
+
+
+def validate_rule(raw: Any) -> dict[str, Any]:
+    """Check a submitted rule and normalize it, raising ValueError if unusable"""
+    if not isinstance(raw, dict):
+        msg = 'Rule must be a JSON object'
+        raise ValueError(msg)  # noqa: TRY004
+
+    name = raw.get('name')
+    if not isinstance(name, str) or not name.strip():
+        msg = 'Rule name is required'
+        raise ValueError(msg)
+    if raw.get('metric') not in RULE_METRICS:
+        msg = f'metric must be one of: {", ".join(RULE_METRICS)}'
+        raise ValueError(msg)
+    if raw.get('operator') not in RULE_OPERATORS:
+        msg = f'operator must be one of: {", ".join(RULE_OPERATORS)}'
+        raise ValueError(msg)
+    window = raw.get('window', 'now')
+    if window not in RULE_WINDOWS:
+        msg = f'window must be one of: {", ".join(RULE_WINDOWS)}'
+        raise ValueError(msg)
+
+    numbers = {}
+    for field in ('threshold', 'lat', 'lon'):
+        value = raw.get(field)
+        if isinstance(value, bool) or not isinstance(value, int | float):
+            msg = f'{field} must be a number'
+            raise ValueError(msg)
+        numbers[field] = float(value)
+    if abs(numbers['lat']) > MAX_LATITUDE or abs(numbers['lon']) > MAX_LONGITUDE:
+        msg = 'lat/lon are out of range'
+        raise ValueError(msg)
+
+    channels = raw.get('channels', ['log'])
+    if (
+        not isinstance(channels, list)
+        or not channels
+        or not all(isinstance(channel, str) for channel in channels)
+    ):
+        msg = 'channels must be a non-empty list of channel names'
+        raise ValueError(msg)
+
+    location = raw.get('location')
+    return {
+        'name': name.strip(),
+        'lat': numbers['lat'],
+        'lon': numbers['lon'],
+        'location': location if isinstance(location, str) and location else None,
+        'metric': raw['metric'],
+        'operator': raw['operator'],
+        'threshold': numbers['threshold'],
+        'window': window,
+        'channels': channels,
+    }
+
+
+def local_today(weather_data: dict) -> date:
+    """The location's current date, taken from its first hourly timestamp"""
+    hourly = weather_data.get('hourly') or []
+    if hourly and hourly[0].get('time'):
+        return date.fromisoformat(hourly[0]['time'][:10])
+    return datetime.now(timezone.utc).date()
+
+
+def in_window(timestamp: str, window: str, today: date) -> bool:
+    """Check a local hourly timestamp falls inside a rule window"""
+    moment = datetime.fromisoformat(timestamp.replace('Z', '+00:00'))
+    tomorrow = today + timedelta(days=1)
+    if window == 'today':
+        return moment.date() == today
+    if window == 'tomorrow':
+        return moment.date() == tomorrow
+    if window == 'tonight':
+        return (moment.date() == today and moment.hour >= TONIGHT_START_HOUR) or (
+            moment.date() == tomorrow and moment.hour < TONIGHT_END_HOUR
+        )
+    return window == 'next_24h'
+
+
+def window_values(weather_data: dict, metric: str, window: str) -> list[float]:
+    """Every forecast value of a metric within a rule window (snake_case document)"""
+    if window == 'now':
+        value = (weather_data.get('current') or {}).get(metric)
+        return [value] if isinstance(value, int | float) else []
+
+    today = local_today(weather_data)
+    values = [
+        entry[metric]
+        for entry in weather_data.get('hourly') or []
+        if isinstance(entry.get(metric), int | float)
+        and entry.get('time')
+        and in_window(entry['time'], window, today)
+    ]
+    if metric == 'temperature' and window in ('today', 'tomorrow'):
+        # Daily highs and lows cover the hours the 24-hour forecast can't reach
+        wanted = today if window == 'today' else today + timedelta(days=1)
+        for day in weather_data.get('daily') or []:
+            if day.get('date') == wanted.isoformat():
+                values.extend(
+                    day[field]
+                    for field in ('high', 'low')
+                    if isinstance(day.get(field), int | float)
+                )
+    return values
+
+
+def evaluate_rule(rule: dict[str, Any], weather_data: dict) -> float | None:
+    """The value that trips a rule (its most extreme in the window), else None"""
+    values = window_values(weather_data, rule['metric'], rule['window'])
+    if not values:
+        return None
+    extreme = max(values) if rule['operator'] in ('>', '>=') else min(values)
+    if RULE_OPERATORS[rule['operator']](extreme, rule['threshold']):
+        return extreme
+    return None
+
+
+def build_notification(
+    rule: dict[str, Any], value: float, weather_data: dict
+) -> dict[str, Any]:
+    """Message describing a tripped rule, for channels to format"""
+    location = rule['location'] or weather_data.get('location') or 'your location'
+    metric = rule['metric'].replace('_', ' ')
+    unit = METRIC_UNITS.get(rule['metric'], '')
+    window = {'now': 'now', 'next_24h': 'in the next 24 hours'}.get(
+        rule['window'], rule['window']
+    )
+    current = weather_data.get('current') or {}
+    return {
+        'rule_id': rule['id'],
+        'rule': rule['name'],
+        'title': f'{rule["name"]} - {location}',
+        'message': (
+            f'{metric.capitalize()} {OPERATOR_WORDS[rule["operator"]]} '
+            f'{rule["threshold"]:g}{unit} {window}: {value:g}{unit}'
+        ),
+        'location': location,
+        'metric': rule['metric'],
+        'value': value,
+        'threshold': rule['threshold'],
+        'icon': current.get('icon'),
+        'temperature': current.get('temperature'),
+        'triggered_at': datetime.now(timezone.utc).isoformat(),
+    }
+
+
+def build_alert_notification(alert: dict[str, Any], location: str) -> dict[str, Any]:
+    """Message for a newly issued NWS alert"""
+    event = alert.get('type') or 'Weather alert'
+    return {
+        'rule_id': None,
+        'rule': 'alert',
+        'title': f'{event} - {location}',
+        'message': alert.get('headline') or alert.get('description') or event,
+        'location': location,
+        'severity': (alert.get('severity') or '').lower() or None,
+        'symbol': '⚠️',
+        'triggered_at': datetime.now(timezone.utc).isoformat(),
+    }
+
+
+def hour_label(timestamp: str) -> str:
+    """'2026-10-16T14:00' -> '2pm'"""
+    return format_hour(datetime.fromisoformat(timestamp.replace('Z', '+00:00')))
+
+
+def rain_windows(
+    weather_data: dict, threshold: float = RAIN_WINDOW_THRESHOLD
+) -> list[tuple[str, str, float]]:
+    """Today's runs of rainy hours as (first hour, last hour, peak chance)"""
+    today = local_today(weather_data)
+    windows: list[tuple[str, str, float]] = []
+    run: list[dict] = []
+    hours = [
+        hour
+        for hour in weather_data.get('hourly') or []
+        if hour.get('time') and in_window(hour['time'], 'today', today)
+    ]
+    for hour in [*hours, None]:  # The None sentinel closes a run at the end
+        chance = (hour or {}).get('precipitation_probability') or 0
+        if hour is not None and chance >= threshold:
+            run.append(hour)
+            continue
+        if run:
+            windows.append(
+                (
+                    hour_label(run[0]['time']),
+                    hour_label(run[-1]['time']),
+                    max(entry['precipitation_probability'] for entry in run),
+                )
+            )
+            run = []
+    return windows
+
+
+def build_digest(
+    location: str,
+    weather_data: dict,
+    alerts: list[dict[str, Any]],
+    air_quality: dict[str, Any] | None = None,
+) -> dict[str, Any]:
+    """Morning digest: today's high/low, rain windows, air quality and alerts"""
+    lines = []
+    today = local_today(weather_data).isoformat()
+    days = weather_data.get('daily') or []
+    day = next((entry for entry in days if entry.get('date') == today), None)
+    if day is None and days:
+        day = days[0]
+    if day:
+        lines.append(f'High {day["high"]}°F, low {day["low"]}°F')
+
+    windows = rain_windows(weather_data)
+    if windows:
+        spans = ', '.join(
+            f'{start}-{end} ({peak:g}%)' if start != end else f'{start} ({peak:g}%)'
+            for start, end, peak in windows
+        )
+        lines.append(f'Rain likely {spans}')
+    else:
+        lines.append('No rain expected today')
+
+    aqi = (air_quality or {}).get('aqi') or {}
+    if aqi.get('us_aqi') is not None:
+        lines.append(f'Air quality {aqi["us_aqi"]} ({aqi.get("category", "unknown")})')
+
+    if alerts:
+        names = ', '.join(alert.get('type') or 'Weather alert' for alert in alerts)
+        lines.append(f'Active alerts: {names}')
+    severities = [(alert.get('severity') or '').lower() for alert in alerts]
+    worst = max(
+        (severity for severity in severities if severity in SEVERITY_ORDER),
+        key=SEVERITY_ORDER.index,
+        default=None,
+    )
+
+    current = weather_data.get('current') or {}
+    return {
+        'rule_id': f'digest:{location}',
+        'rule': 'digest',
+        'title': f'Morning forecast - {location}',
+        'message': '\n'.join(lines),
+        'location': location,
+        'severity': worst,
+        'icon': current.get('icon'),
+        'temperature': current.get('temperature'),
+        'triggered_at': datetime.now(timezone.utc).isoformat(),
+    }
+
+
+class NotificationChannel(Protocol):
+    """Somewhere a notification can be delivered"""
+
+    def send(self, notification: dict[str, Any]) -> None: ...
+
+
+class LogChannel:
+    """Built-in channel that writes notifications to the server log"""
+
+    def send(self, notification: dict[str, Any]) -> None:
+        print(f'🔔 {notification["title"]}: {notification["message"]}')
+
+
+class NtfyChannel:
+    """Publishes to an ntfy topic on ntfy.sh or a self-hosted server"""
+
+    def __init__(
+        self,
+        topic: str,
+        server: str = 'https://ntfy.sh',
+        token: str | None = None,
+        username: str | None = None,
+        password: str | None = None,
+    ) -> None:
+        self.topic = topic
+        self.server = server.rstrip('/')
+        self.token = token
+        self.username = username
+        self.password = password
+
+    def payload(self, notification: dict[str, Any]) -> dict[str, Any]:
+        """JSON publish body - unlike headers, it carries non-ASCII titles safely"""
+        severity = notification.get('severity')
+        if severity in URGENT_SEVERITIES:
+            tags = ['rotating_light']
+        else:
+            tags = ['warning'] if severity else ['bell']
+        return {
+            'topic': self.topic,
+            'title': notification['title'],
+            'message': notification['message'],
+            'tags': tags,
+            'priority': NTFY_PRIORITIES.get(severity or '', NTFY_DEFAULT_PRIORITY),
+        }
+
+    def send(self, notification: dict[str, Any]) -> None:
+        headers = {}
+        auth = None
+        if self.token:
+            headers['Authorization'] = f'Bearer {self.token}'
+        elif self.username:
+            auth = (self.username, self.password or '')
+        response = requests.post(
+            self.server,
+            json=self.payload(notification),
+            headers=headers,
+            auth=auth,
+            timeout=NTFY_TIMEOUT_SECONDS,
+        )
+        response.raise_for_status()
+
+
+def notification_color(notification: dict[str, Any]) -> str:
+    """Hex color for a notification's severity"""
+    return SEVERITY_COLORS.get(notification.get('severity') or '', DEFAULT_COLOR)
+
+
+def notification_heading(notification: dict[str, Any]) -> str:
+    """Title prefixed with the condition symbol, when there is one"""
+    symbol = notification.get('symbol')
+    return f'{symbol} {notification["title"]}' if symbol else notification['title']
+
+
+def notification_details(notification: dict[str, Any]) -> list[tuple[str, str]]:
+    """Short (label, value) facts shown alongside the message"""
+    details = []
+    if notification.get('temperature') is not None:
+        details.append(('Now', f'{notification["temperature"]:g}°F'))
+    if notification.get('severity'):
+        details.append(('Severity', notification['severity'].capitalize()))
+    return details
+
+
+def post_webhook(url: str, payload: dict[str, Any]) -> None:
+    """POST a JSON payload to a chat webhook, raising on an error status"""
+    response = requests.post(url, json=payload, timeout=WEBHOOK_TIMEOUT_SECONDS)
+    response.raise_for_status()
+
+
+def escape_slack(text: str) -> str:
+    """Escape the characters Slack mrkdwn treats as control sequences"""
+    return text.replace('&', '&amp;').replace('<', '&lt;').replace('>', '&gt;')
+
+
+class SlackChannel:
+    """Posts to a Slack incoming webhook as a severity-colored block message"""
+
+    def __init__(self, webhook_url: str) -> None:
+        self.webhook_url = webhook_url
+
+    def payload(self, notification: dict[str, Any]) -> dict[str, Any]:
+        """Blocks inside a colored attachment, with plain text for previews"""
+        heading = escape_slack(notification_heading(notification))
+        blocks: list[dict[str, Any]] = [
+            {
+                'type': 'section',
+                'text': {
+                    'type': 'mrkdwn',
+                    'text': (
+                        f'*{heading}*\n'
+                        f'{escape_slack(notification["message"])}'
+                    ),
+                },
+            }
+        ]
+        details = notification_details(notification)
+        if details:
+            blocks.append(
+                {
+                    'type': 'context',
+                    'elements': [
+                        {'type': 'mrkdwn', 'text': f'*{label}:* {value}'}
+                        for label, value in details
+                    ],
+                }
+            )
+        return {
+            'text': f'{notification["title"]}: {notification["message"]}',
+            'attachments': [
+                {'color': notification_color(notification), 'blocks': blocks}
+            ],
+        }
+
+    def send(self, notification: dict[str, Any]) -> None:
+        post_webhook(self.webhook_url, self.payload(notification))
+
+
+class DiscordChannel:
+    """Posts to a Discord webhook as a severity-colored embed"""
+
+    def __init__(self, webhook_url: str) -> None:
+        self.webhook_url = webhook_url
+
+    def payload(self, notification: dict[str, Any]) -> dict[str, Any]:
+        """One embed - Discord wants its color as an integer"""
+        embed: dict[str, Any] = {
+            'title': notification_heading(notification),
+            'description': notification['message'],
+            'color': int(notification_color(notification).lstrip('#'), 16),
+            'fields': [
+                {'name': label, 'value': value, 'inline': True}
+                for label, value in notification_details(notification)
+            ],
+        }
+        if notification.get('triggered_at'):
+            embed['timestamp'] = notification['triggered_at']
+        return {'embeds': [embed]}
+
+    def send(self, notification: dict[str, Any]) -> None:
+        post_webhook(self.webhook_url, self.payload(notification))
+
+
+class PushoverChannel:
+    """Sends through the Pushover API to a user or group key"""
+
+    def __init__(self, app_token: str, user_key: str) -> None:
+        self.app_token = app_token
+        self.user_key = user_key
+
+    def payload(self, notification: dict[str, Any]) -> dict[str, Any]:
+        """Form fields - severe alerts are high priority, bypassing Pushover quiet"""
+        return {
+            'token': self.app_token,
+            'user': self.user_key,
+            'title': notification_heading(notification),
+            'message': notification['message'],
+            'priority': 1 if notification.get('severity') in URGENT_SEVERITIES else 0,
+        }
+
+    def send(self, notification: dict[str, Any]) -> None:
+        response = requests.post(
+            PUSHOVER_URL,
+            data=self.payload(notification),
+            timeout=WEBHOOK_TIMEOUT_SECONDS,
+        )
+        response.raise_for_status()
+
+
+class EmailChannel:
+    """Sends plain-text email over SMTP"""
+
+    def __init__(
+        self,
+        host: str,
+        port: int,
+        sender: str,
+        recipients: list[str],
+        username: str | None = None,
+        password: str | None = None,
+    ) -> None:
+        self.host = host
+        self.port = port
+        self.sender = sender
+        self.recipients = recipients
+        self.username = username
+        self.password = password
+
+    def message(self, notification: dict[str, Any]) -> EmailMessage:
+        """The email for a notification, details listed under the message"""
+        message = EmailMessage()
+        message['Subject'] = notification_heading(notification)
+        message['From'] = self.sender
+        message['To'] = ', '.join(self.recipients)
+        lines = [notification['message'], '']
+        lines.extend(
+            f'{label}: {value}' for label, value in notification_details(notification)
+        )
+        message.set_content('\n'.join(lines).strip() + '\n')
+        return message
+
+    def send(self, notification: dict[str, Any]) -> None:
+        smtp_class = smtplib.SMTP_SSL if self.port == SMTPS_PORT else smtplib.SMTP
+        with smtp_class(self.host, self.port, timeout=SMTP_TIMEOUT_SECONDS) as smtp:
+            if smtp_class is smtplib.SMTP and smtp.has_extn('starttls'):
+                smtp.starttls()
+            if self.username:
+                smtp.login(self.username, self.password or '')
+            smtp.send_message(self.message(notification))
+
+
+def parse_quiet_hours(value: str | None) -> tuple[int, int] | None:
+    """Parse 'HH:MM-HH:MM' into start and end minutes past midnight"""
+    if not value:
+        return None
+    try:
+        start, end = (
+            int(hours) * 60 + int(minutes)
+            for hours, minutes in (part.strip().split(':') for part in value.split('-'))
+        )
+    except ValueError:
+        msg = f"Quiet hours must look like '22:00-07:00', not {value!r}"
+        raise ValueError(msg) from None
+    return start, end
+
+
+class ChannelLimits:
+    """Per-channel quiet hours and rate limits
+
+    Quiet hours hold back everything but extreme and severe alerts. Each rule
+    or alert reaches the channel at most once per repeat_minutes, and the
+    channel as a whole at most max_per_hour times, so a flapping condition
+    can't keep waking anyone up.
+    """
+
+    def __init__(
+        self,
+        quiet_hours: tuple[int, int] | None = None,
+        tz: tzinfo = timezone.utc,
+        max_per_hour: int | None = None,
+        repeat_minutes: float = REPEAT_MINUTES,
+    ) -> None:
+        self.quiet_hours = quiet_hours
+        self.tz = tz
+        self.max_per_hour = max_per_hour
+        self.repeat_seconds = repeat_minutes * 60
+        self.sent: deque[float] = deque()
+        self.last_sent: dict[str, float] = {}
+        self._lock = threading.Lock()
+
+    def in_quiet_hours(self, now: datetime) -> bool:
+        """Check a moment falls in quiet hours, which may wrap past midnight"""
+        if self.quiet_hours is None:
+            return False
+        local = now.astimezone(self.tz)
+        minute = local.hour * 60 + local.minute
+        start, end = self.quiet_hours
+        if start <= end:
+            return start <= minute < end
+        return minute >= start or minute < end
+
+    def check(self, notification: dict[str, Any], now: datetime) -> str | None:
+        """Why a notification is held back, or None after recording it as sent"""
+        urgent = notification.get('severity') in URGENT_SEVERITIES
+        if not urgent and self.in_quiet_hours(now):
+            return 'quiet hours'
+
+        timestamp = now.timestamp()
+        key = str(notification.get('rule_id') or notification['title'])
+        with self._lock:
+            while self.sent and self.sent[0] <= timestamp - 3600:  # noqa: PLR2004
+                self.sent.popleft()
+            last = self.last_sent.get(key)
+            if last is not None and timestamp - last < self.repeat_seconds:
+                return 'repeated too soon'
+            if self.max_per_hour is not None and len(self.sent) >= self.max_per_hour:
+                return 'hourly limit reached'
+            self.sent.append(timestamp)
+            self.last_sent[key] = timestamp
+        return None
+
+
+class NotificationDispatcher:
+    """Named channels that rules route their notifications to"""
+
+    def __init__(self) -> None:
+        self.channels: dict[str, NotificationChannel] = {'log': LogChannel()}
+        self.limits: dict[str, ChannelLimits] = {}
+
+    def register(
+        self,
+        name: str,
+        channel: NotificationChannel,
+        limits: ChannelLimits | None = None,
+    ) -> None:
+        """Add or replace a channel, optionally with quiet hours and rate limits"""
+        self.channels[name] = channel
+        if limits is None:
+            self.limits.pop(name, None)
+        else:
+            self.limits[name] = limits
+
+    def dispatch(
+        self,
+        notification: dict[str, Any],
+        channel_names: list[str],
+        now: datetime | None = None,
+    ) -> int:
+        """Send to each named channel, returning how many deliveries succeeded"""
+        now = now or datetime.now(timezone.utc)
+        delivered = 0
+        for name in channel_names:
+            channel = self.channels.get(name)
+            if channel is None:
+                print(f'⚠️  Notification channel {name!r} is not configured')
+                continue
+            limits = self.limits.get(name)
+            held_back = limits.check(notification, now) if limits else None
+            if held_back:
+                print(f'🔕 Held back {notification["title"]!r} on {name}: {held_back}')
+                continue
+            try:
+                channel.send(notification)
+            except Exception as e:
+                print(f'❌ Notification via {name} failed: {str(e)}')
+                continue
+            delivered += 1
+        return delivered
+
+
+class NotificationRules:
+    """User-defined rules plus whether each is currently tripped, saved as JSON"""
+
+    def __init__(self, path: str) -> None:
+        self.path = path
+        self.rules: dict[str, dict[str, Any]] = {}
+        self._lock = threading.Lock()
+        self.load()
+
+    def load(self) -> None:
+        """Load saved rules, starting empty if the file is missing or unreadable"""
+        try:
+            with open(self.path, encoding='utf-8') as f:
+                records = json.load(f)
+        except FileNotFoundError:
+            return
+        except (OSError, ValueError) as e:
+            print(f'❌ Could not read notification rules {self.path}: {str(e)}')
+            return
+        with self._lock:
+            self.rules = {record['id']: record for record in records}
+
+    def save(self) -> None:
+        """Write rules atomically (lock held)"""
+        directory = os.path.dirname(self.path)
+        if directory:
+            os.makedirs(directory, exist_ok=True)
+        temp_path = f'{self.path}.tmp'
+        with open(temp_path, 'w', encoding='utf-8') as f:
+            json.dump(list(self.rules.values()), f, indent=2)
+        os.replace(temp_path, self.path)
+
+    def create(self, raw: Any) -> dict[str, Any]:
+        """Validate and store a new rule"""
+        rule = {
+            'id': secrets.token_hex(4),
+            **validate_rule(raw),
+            'created_at': time.time(),
+            'active': False,
+            'last_triggered_at': None,
+        }
+        with self._lock:
+            self.rules[rule['id']] = rule
+            self.save()
+        return dict(rule)
+
+    def delete(self, rule_id: str) -> bool:
+        """Remove a rule, returning False if it doesn't exist"""
+        with self._lock:
+            if self.rules.pop(rule_id, None) is None:
+                return False
+            self.save()
+            return True
+
+    def list_rules(self) -> list[dict[str, Any]]:
+        """Every rule, oldest first"""
+        with self._lock:
+            return sorted(
+                (dict(rule) for rule in self.rules.values()),
+                key=lambda rule: rule['created_at'],
+            )
+
+    def check(self, rule_id: str, weather_data: dict) -> dict[str, Any] | None:
+        """Evaluate a rule, returning a notification only when it newly trips
+
+        A rule that stays tripped across refreshes notifies once; it can fire
+        again after the condition has cleared.
+        """
+        with self._lock:
+            rule = self.rules.get(rule_id)
+            if rule is None:
+                return None
+            value = evaluate_rule(rule, weather_data)
+            was_active = rule['active']
+            if (value is not None) == was_active:
+                return None
+            rule['active'] = value is not None
+            if value is not None:
+                rule['last_triggered_at'] = time.time()
+            self.save()
+            snapshot = dict(rule)
+        if value is None:
+            return None
+        return build_notification(snapshot, value, weather_data)
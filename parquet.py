@@ -0,0 +1,219 @@
+# ABOUTME: Minimal Apache Parquet writer for flat tables of numbers, text and times -
+# ABOUTME: one row group, plain encoding, uncompressed - enough for history exports
+
+import struct
+from typing import Any
+
+
+MAGIC = b'PAR1'
+
+# Column kinds as Parquet physical types, and the converted types marking text
+# and times
+PHYSICAL_TYPES = {'int64': 2, 'double': 5, 'string': 6, 'timestamp': 2}
+CONVERTED_TYPES = {'string': 0, 'timestamp': 9}  # UTF8, TIMESTAMP_MILLIS
+OPTIONAL = 1  # Every column may hold nulls
+PLAIN = 0
+RLE = 3
+UNCOMPRESSED = 0
+DATA_PAGE = 0
+CREATED_BY = 'weather-dashboard'
+
+# Thrift compact protocol field types
+T_I32 = 5
+T_I64 = 6
+T_BINARY = 8
+T_LIST = 9
+T_STRUCT = 12
+
+
+def varint(value: int) -> bytes:
+    """An unsigned LEB128 varint"""
+    out = bytearray()
+    while True:
+        byte = value & 0x7F
+        value >>= 7
+        if value:
+            out.append(byte | 0x80)
+        else:
+            out.append(byte)
+            return bytes(out)
+
+
+def zigzag(value: int) -> bytes:
+    """A signed integer as a zigzag varint"""
+    return varint((value << 1) ^ (value >> 63))
+
+
+class ThriftStruct:
+    """Serializes one struct in the Thrift compact protocol, fields in id order"""
+
+    def __init__(self) -> None:
+        self.out = bytearray()
+        self.last_id = 0
+
+    def header(self, field_id: int, field_type: int) -> None:
+        """Field header: the id as a delta from the last one where it fits"""
+        delta = field_id - self.last_id
+        if 0 < delta <= 15:  # noqa: PLR2004
+            self.out.append(delta << 4 | field_type)
+        else:
+            self.out.append(field_type)
+            self.out += zigzag(field_id)
+        self.last_id = field_id
+
+    def i32(self, field_id: int, value: int) -> 'ThriftStruct':
+        """Add a 32-bit integer field"""
+        self.header(field_id, T_I32)
+        self.out += zigzag(value)
+        return self
+
+    def i64(self, field_id: int, value: int) -> 'ThriftStruct':
+        """Add a 64-bit integer field"""
+        self.header(field_id, T_I64)
+        self.out += zigzag(value)
+        return self
+
+    def string(self, field_id: int, value: str) -> 'ThriftStruct':
+        """Add a UTF-8 string field"""
+        self.header(field_id, T_BINARY)
+        encoded = value.encode()
+        self.out += varint(len(encoded)) + encoded
+        return self
+
+    def nested(self, field_id: int, value: 'ThriftStruct') -> 'ThriftStruct':
+        """Add a nested struct field"""
+        self.header(field_id, T_STRUCT)
+        self.out += value.finish()
+        return self
+
+    def list_of(
+        self, field_id: int, element_type: int, items: list[Any]
+    ) -> 'ThriftStruct':
+        """Add a list of i32s, strings or structs"""
+        self.header(field_id, T_LIST)
+        if len(items) < 15:  # noqa: PLR2004
+            self.out.append(len(items) << 4 | element_type)
+        else:
+            self.out.append(0xF0 | element_type)
+            self.out += varint(len(items))
+        for item in items:
+            if element_type == T_STRUCT:
+                self.out += item.finish()
+            elif element_type == T_BINARY:
+                encoded = item.encode()
+                self.out += varint(len(encoded)) + encoded
+            else:
+                self.out += zigzag(item)
+        return self
+
+    def finish(self) -> bytes:
+        """The struct's bytes, with its stop field"""
+        return bytes(self.out) + b'\x00'
+
+
+def definition_levels(values: list[Any]) -> bytes:
+    """Which values are present, as length-prefixed RLE runs of bit width 1"""
+    runs = bytearray()
+    index = 0
+    while index < len(values):
+        present = values[index] is not None
+        length = 1
+        while (
+            index + length < len(values)
+            and (values[index + length] is not None) == present
+        ):
+            length += 1
+        runs += varint(length << 1) + bytes([present])
+        index += length
+    return struct.pack('<I', len(runs)) + bytes(runs)
+
+
+def plain_values(kind: str, values: list[Any]) -> bytes:
+    """The present values in PLAIN encoding"""
+    out = bytearray()
+    for value in values:
+        if value is None:
+            continue
+        if kind == 'double':
+            out += struct.pack('<d', float(value))
+        elif kind == 'timestamp':  # Unix seconds in, milliseconds out
+            out += struct.pack('<q', round(float(value) * 1000))
+        elif kind == 'int64':
+            out += struct.pack('<q', int(value))
+        else:
+            encoded = str(value).encode()
+            out += struct.pack('<I', len(encoded)) + encoded
+    return bytes(out)
+
+
+def column_chunk(
+    body: bytearray, name: str, kind: str, values: list[Any]
+) -> tuple[ThriftStruct, int]:
+    """Append a column's single data page to body, returning its chunk and size"""
+    page = definition_levels(values) + plain_values(kind, values)
+    page_header = (
+        ThriftStruct()
+        .i32(1, DATA_PAGE)
+        .i32(2, len(page))
+        .i32(3, len(page))
+        .nested(
+            5,
+            ThriftStruct().i32(1, len(values)).i32(2, PLAIN).i32(3, RLE).i32(4, RLE),
+        )
+        .finish()
+    )
+    offset = len(body)
+    body += page_header + page
+    size = len(page_header) + len(page)
+    metadata = (
+        ThriftStruct()
+        .i32(1, PHYSICAL_TYPES[kind])
+        .list_of(2, T_I32, [PLAIN, RLE])
+        .list_of(3, T_BINARY, [name])
+        .i32(4, UNCOMPRESSED)
+        .i64(5, len(values))
+        .i64(6, size)
+        .i64(7, size)
+        .i64(9, offset)
+    )
+    return ThriftStruct().i64(2, offset).nested(3, metadata), size
+
+
+def write_parquet(columns: list[tuple[str, str]], rows: list[dict[str, Any]]) -> bytes:
+    """A Parquet file of rows, with (name, kind) columns
+
+    Kinds are 'double', 'int64', 'string' and 'timestamp' (Unix seconds,
+    stored as milliseconds). Missing and None values are nulls.
+    """
+    body = bytearray(MAGIC)
+    # No rows is no row group at all, rather than empty pages
+    row_groups = []
+    if rows:
+        chunks = [
+            column_chunk(body, name, kind, [row.get(name) for row in rows])
+            for name, kind in columns
+        ]
+        row_groups.append(
+            ThriftStruct()
+            .list_of(1, T_STRUCT, [chunk for chunk, _ in chunks])
+            .i64(2, sum(size for _, size in chunks))
+            .i64(3, len(rows))
+        )
+
+    schema = [ThriftStruct().string(4, 'schema').i32(5, len(columns))]
+    for name, kind in columns:
+        element = ThriftStruct().i32(1, PHYSICAL_TYPES[kind]).i32(3, OPTIONAL)
+        element.string(4, name)
+        if kind in CONVERTED_TYPES:
+            element.i32(6, CONVERTED_TYPES[kind])
+        schema.append(element)
+    footer = (
+        ThriftStruct()
+        .i32(1, 1)
+        .list_of(2, T_STRUCT, schema)
+        .i64(3, len(rows))
+        .list_of(4, T_STRUCT, row_groups)
+        .string(6, CREATED_BY)
+        .finish()
+    )
+    return bytes(body) + footer + struct.pack('<I', len(footer)) + MAGIC
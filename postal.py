@@ -0,0 +1,98 @@
+# ABOUTME: Postal/ZIP code lookups from GeoNames postal code dumps on disk
+# ABOUTME: Codes are normalized so 'sw1a 1aa', 'SW1A1AA' and ZIP+4 forms all match
+
+import csv
+import os
+import re
+from pathlib import Path
+
+
+# GeoNames postal code columns (tab separated, no header)
+GEONAMES_COUNTRY = 0
+GEONAMES_POSTAL_CODE = 1
+GEONAMES_PLACE = 2
+GEONAMES_ADMIN1 = 3
+GEONAMES_LATITUDE = 9
+GEONAMES_LONGITUDE = 10
+
+POSTAL_CODE_PATTERN = re.compile(r'^[A-Za-z0-9][A-Za-z0-9 -]{1,11}$')
+COUNTRY_PATTERN = re.compile(r'^[A-Za-z]{2}$')
+
+
+def normalize_postal_code(code: str) -> str:
+    """Uppercase a code and drop spaces and dashes ('sw1a 1aa' -> 'SW1A1AA')"""
+    return re.sub(r'[\s-]', '', code).upper()
+
+
+def postal_lookup_keys(code: str) -> list[str]:
+    """Codes to try, most specific first: the full code, then its outward part
+
+    GeoNames only has outward codes for some countries (GB 'SW1A', CA 'K1A'),
+    and ZIP+4 codes are looked up by their five-digit ZIP.
+    """
+    keys = [normalize_postal_code(code)]
+    outward = re.split(r'[\s-]', code.strip(), maxsplit=1)[0]
+    if outward and normalize_postal_code(outward) not in keys:
+        keys.append(normalize_postal_code(outward))
+    return keys
+
+
+def valid_postal_query(code: str, country: str) -> bool:
+    """Check a code and ISO country look like something worth looking up"""
+    return bool(POSTAL_CODE_PATTERN.match(code.strip())) and bool(
+        COUNTRY_PATTERN.match(country)
+    )
+
+
+class PostalIndex:
+    """(country, postal code) -> (lat, lon, place) from GeoNames dump files
+
+    The path can be one file (allCountries.txt, US.txt) or a directory of them.
+    """
+
+    def __init__(self, path: str) -> None:
+        self.codes: dict[tuple[str, str], tuple[float, float, str]] = {}
+        self.countries: set[str] = set()
+        files = sorted(Path(path).glob('*.txt')) if os.path.isdir(path) else [path]
+        for file_path in files:
+            self.load(str(file_path))
+        if self.codes:
+            print(
+                f'📮 Loaded {len(self.codes)} postal codes for '
+                f'{len(self.countries)} countries from {path}'
+            )
+
+    def load(self, path: str) -> None:
+        """Add one GeoNames file; the first place listed for a code wins"""
+        try:
+            with open(path, encoding='utf-8', newline='') as f:
+                for row in csv.reader(f, delimiter='\t', quoting=csv.QUOTE_NONE):
+                    self.add_row(row)
+        except FileNotFoundError:
+            return
+        except (OSError, UnicodeDecodeError) as e:
+            print(f'❌ Could not read postal codes from {path}: {str(e)}')
+
+    def add_row(self, row: list[str]) -> None:
+        """Index a GeoNames row, skipping malformed ones"""
+        try:
+            lat = float(row[GEONAMES_LATITUDE])
+            lon = float(row[GEONAMES_LONGITUDE])
+        except (IndexError, ValueError):
+            return
+        country = row[GEONAMES_COUNTRY].upper()
+        place = row[GEONAMES_PLACE]
+        if row[GEONAMES_ADMIN1]:
+            place = f'{place}, {row[GEONAMES_ADMIN1]}'
+        key = (country, normalize_postal_code(row[GEONAMES_POSTAL_CODE]))
+        self.codes.setdefault(key, (lat, lon, place))
+        self.countries.add(country)
+
+    def lookup(self, code: str, country: str) -> tuple[float, float, str] | None:
+        """Coordinates and place name for a code, or None if it isn't indexed"""
+        country = country.upper()
+        for key in postal_lookup_keys(code):
+            if (country, key) in self.codes:
+                return self.codes[(country, key)]
+        return None
+
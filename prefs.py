@@ -0,0 +1,76 @@
+# ABOUTME: Visitor display preferences (units, clock, home location, theme)
+# ABOUTME: Validation plus the hour-label conversion applied to responses
+
+from datetime import datetime
+from typing import Any
+
+from themes import AUTO_THEME, THEME_NAMES, resolve_theme
+from weather_core.formatting import hour_label
+
+
+PREF_DEFAULTS: dict[str, Any] = {
+    'units': 'imperial',
+    'time_format': '12h',
+    'location': None,
+    'theme': AUTO_THEME,
+}
+PREF_CHOICES: dict[str, tuple[str, ...]] = {
+    'units': ('imperial', 'metric'),
+    'time_format': ('12h', '24h'),
+    'theme': THEME_NAMES,
+}
+MAX_LOCATION_LENGTH = 100
+
+
+def validate_prefs(raw: Any) -> dict[str, Any]:
+    """Check a (partial) preferences update, returning the accepted fields"""
+    if not isinstance(raw, dict):
+        msg = 'Preferences must be a JSON object'
+        raise ValueError(msg)
+    unknown = sorted(set(raw) - set(PREF_DEFAULTS))
+    if unknown:
+        msg = f'Unknown preferences: {", ".join(unknown)}'
+        raise ValueError(msg)
+
+    accepted: dict[str, Any] = {}
+    if isinstance(raw.get('theme'), str):
+        raw = {**raw, 'theme': resolve_theme(raw['theme']) or raw['theme']}
+    for name, choices in PREF_CHOICES.items():
+        if name in raw:
+            if raw[name] not in choices:
+                msg = f'{name} must be one of: {", ".join(choices)}'
+                raise ValueError(msg)
+            accepted[name] = raw[name]
+    if 'location' in raw:
+        location = raw['location']
+        if location is not None and (
+            not isinstance(location, str)
+            or not location.strip()
+            or len(location) > MAX_LOCATION_LENGTH
+        ):
+            msg = 'location must be a city, "lat,lon" or null'
+            raise ValueError(msg)
+        accepted['location'] = location.strip() if location else None
+    return accepted
+
+
+def label_hours(
+    weather: dict[str, Any], time_format: str = '24h', language: str | None = None
+) -> dict[str, Any]:
+    """A copy of a weather document with hourly labels on the given clock
+
+    '14:00' rather than '2pm' for 24h, and in the language's own form
+    ('14 Uhr', '2 p. m.') when one is given.
+    """
+    hourly = weather.get('hourly')
+    if not isinstance(hourly, list):
+        return weather
+    relabeled = []
+    for hour in hourly:
+        try:
+            local = datetime.fromisoformat(hour['time'].replace('Z', '+00:00'))
+        except (KeyError, TypeError, ValueError, AttributeError):
+            relabeled.append(hour)
+            continue
+        relabeled.append({**hour, 't': hour_label(local, time_format, language)})
+    return {**weather, 'hourly': relabeled}
@@ -0,0 +1,139 @@
+# ABOUTME: Finds the real client address behind trusted reverse proxies from the
+# ABOUTME: Forwarded and X-Forwarded-For headers, ignoring hops nobody vouches for
+
+import ipaddress
+from collections.abc import Iterable
+from typing import Any
+
+
+# Loopback and private networks: a proxy on this machine, the LAN or a container
+# network. Clients connecting from a public address can never spoof their own
+DEFAULT_TRUSTED_PROXIES = (
+    '127.0.0.0/8,::1/128,10.0.0.0/8,172.16.0.0/12,192.168.0.0/16,fc00::/7'
+)
+
+# Headers a proxy may report the client in: de facto and RFC 7239
+PROXY_HEADERS = ('x-forwarded-for', 'forwarded')
+
+# REMOTE_ADDR werkzeug gives unix socket clients - always a process on this machine
+UNIX_SOCKET_PEER = '<local>'
+
+# Where the connecting address is kept once REMOTE_ADDR holds the client's
+PEER_ADDR_KEY = 'weather.peer_addr'
+
+IpAddress = ipaddress.IPv4Address | ipaddress.IPv6Address
+
+
+def parse_node(node: str) -> IpAddress | None:
+    """An address from an X-Forwarded-For entry or Forwarded for= node
+
+    Ports ('1.2.3.4:80', '"[2001:db8::1]:443"') are dropped; obfuscated
+    ('_hidden') and 'unknown' nodes aren't addresses.
+    """
+    node = node.strip().strip('"')
+    if node.startswith('['):
+        node = node[1 : node.find(']')] if ']' in node else ''
+    elif node.count(':') == 1:
+        node = node.partition(':')[0]
+    try:
+        return ipaddress.ip_address(node)
+    except ValueError:
+        return None
+
+
+def forwarded_for(header: str) -> list[str]:
+    """The for= nodes of an RFC 7239 Forwarded header, first hop first"""
+    nodes = []
+    for element in header.split(','):
+        for pair in element.split(';'):
+            name, _, value = pair.partition('=')
+            if name.strip().lower() == 'for':
+                nodes.append(value.strip())
+    return nodes
+
+
+class TrustedProxies:
+    """The networks whose forwarding header is believed, and which header that is
+
+    Only the header the proxies actually set is read: a client can send the
+    other one too, and nothing would remove it on the way in.
+    """
+
+    def __init__(
+        self, networks: Iterable[str], header: str = 'x-forwarded-for'
+    ) -> None:
+        self.header = header
+        self.networks = [
+            ipaddress.ip_network(network.strip(), strict=False)
+            for network in networks
+            if network.strip()
+        ]
+
+    @classmethod
+    def parse(cls, spec: str, header: str = 'x-forwarded-for') -> 'TrustedProxies':
+        """Comma-separated addresses and CIDR ranges; empty trusts nobody"""
+        header = header.strip().lower()
+        if header not in PROXY_HEADERS:
+            msg = f'PROXY_HEADER must be one of {", ".join(PROXY_HEADERS)}'
+            raise ValueError(msg)
+        try:
+            return cls(spec.split(','), header)
+        except ValueError as e:
+            msg = f'TRUSTED_PROXIES: {e}'
+            raise ValueError(msg) from e
+
+    def trusts(self, address: IpAddress | None) -> bool:
+        """Whether a hop is one of our proxies"""
+        if address is None:
+            return False
+        if isinstance(address, ipaddress.IPv6Address) and address.ipv4_mapped:
+            address = address.ipv4_mapped
+        return any(address in network for network in self.networks)
+
+    def client_address(
+        self,
+        remote_addr: str | None,
+        forwarded: str | None = None,
+        x_forwarded_for: str | None = None,
+    ) -> str | None:
+        """The client's address: the nearest hop that isn't a trusted proxy
+
+        Hops are walked from the connecting peer back towards the client, so
+        entries a client wrote into the header itself are never reached unless
+        every proxy after it is trusted.
+        """
+        if remote_addr == UNIX_SOCKET_PEER:
+            client = remote_addr
+        else:
+            peer = parse_node(remote_addr or '')
+            if not self.trusts(peer):
+                return remote_addr
+            client = str(peer)
+        if self.header == 'forwarded':
+            hops = forwarded_for(forwarded or '')
+        else:
+            hops = x_forwarded_for.split(',') if x_forwarded_for else []
+        for node in reversed(hops):
+            address = parse_node(node)
+            if address is None:
+                break  # Unreadable hop: the last one we could read is all we know
+            client = str(address)
+            if not self.trusts(address):
+                break
+        return client
+
+
+class ProxyHeadersMiddleware:
+    """WSGI middleware putting the client's address in REMOTE_ADDR"""
+
+    def __init__(self, wsgi_app: Any, trusted: TrustedProxies) -> None:
+        self.wsgi_app = wsgi_app
+        self.trusted = trusted
+
+    def __call__(self, environ: dict[str, Any], start_response: Any) -> Any:
+        peer = environ.get('REMOTE_ADDR')
+        environ[PEER_ADDR_KEY] = peer
+        environ['REMOTE_ADDR'] = self.trusted.client_address(
+            peer, environ.get('HTTP_FORWARDED'), environ.get('HTTP_X_FORWARDED_FOR')
+        )
+        return self.wsgi_app(environ, start_response)
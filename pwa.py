@@ -0,0 +1,76 @@
+# ABOUTME: Web app manifest and service worker settings for installing the dashboard
+# ABOUTME: Both are generated per deploy so icons and precached assets match static/
+
+from collections.abc import Callable
+from typing import Any
+
+
+PWA_NAME = 'Weather Dashboard'
+PWA_SHORT_NAME = 'Weather'
+PWA_DESCRIPTION = 'A real-time weather dashboard with forecasts and current conditions'
+PWA_BACKGROUND_COLOR = '#1e3a8a'
+PWA_THEME_COLOR = '#3b82f6'
+PWA_MAX_SHORTCUTS = 4  # Launchers show at most a handful
+
+# (path under static/, sizes, purpose)
+PWA_ICONS = [
+    ('icons/icon-192x192.png', '192x192', 'any'),
+    ('icons/icon-512x512.png', '512x512', 'any'),
+    ('icons/icon-192x192.png', '192x192', 'maskable'),
+    ('icons/app-icon.svg', 'any', 'any'),
+]
+
+# Assets the service worker caches on install so the page loads offline
+PRECACHE_ASSETS = [
+    'css/weather-components.css',
+    'js/weather-components.js',
+    'js/realtime-weather.js',
+    'icons/icon-192x192.png',
+    'icons/icon-512x512.png',
+]
+# Only the static icon set is precached; animated ones load on demand
+PRECACHE_ICON_DIRECTORY = 'icons/weather/static/'
+
+
+def build_manifest(
+    asset_url: Callable[[str], str], shortcuts: list[dict[str, str]]
+) -> dict[str, Any]:
+    """The web app manifest, with a launcher shortcut per saved location"""
+    return {
+        'name': PWA_NAME,
+        'short_name': PWA_SHORT_NAME,
+        'description': PWA_DESCRIPTION,
+        'id': '/',
+        'start_url': '/',
+        'scope': '/',
+        'display': 'standalone',
+        'background_color': PWA_BACKGROUND_COLOR,
+        'theme_color': PWA_THEME_COLOR,
+        'orientation': 'portrait-primary',
+        'lang': 'en-US',
+        'icons': [
+            {
+                'src': asset_url(path),
+                'sizes': sizes,
+                'type': 'image/svg+xml' if path.endswith('.svg') else 'image/png',
+                'purpose': purpose,
+            }
+            for path, sizes, purpose in PWA_ICONS
+        ],
+        'shortcuts': shortcuts[:PWA_MAX_SHORTCUTS],
+        'categories': ['weather', 'utilities'],
+    }
+
+
+def shell_urls(asset_url: Callable[[str], str]) -> list[str]:
+    """Versioned URLs for the app shell the service worker caches on install"""
+    return ['/', *(asset_url(path) for path in PRECACHE_ASSETS)]
+
+
+def weather_icon_urls(asset_url: Callable[[str], str], assets: list[str]) -> list[str]:
+    """Versioned URLs for every static weather icon"""
+    return [
+        asset_url(path)
+        for path in sorted(assets)
+        if path.startswith(PRECACHE_ICON_DIRECTORY)
+    ]
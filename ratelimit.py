@@ -0,0 +1,82 @@
+# ABOUTME: Per-client token-bucket rate limiting for the API, so one misbehaving
+# ABOUTME: polling script can't crowd out everyone else on a public instance
+
+import math
+import threading
+import time
+
+from cachetools import TTLCache
+
+
+# Buckets tracked at once; the least recently seen are forgotten first
+MAX_TRACKED_CLIENTS = 10000
+
+
+class RateLimitDecision:
+    """Whether a request may go ahead, with what to put in RateLimit-* headers"""
+
+    def __init__(
+        self, allowed: bool, limit: int, remaining: int, reset: int, retry_after: int
+    ) -> None:
+        self.allowed = allowed
+        self.limit = limit
+        self.remaining = remaining
+        # Seconds until the bucket is full again, and until the next request fits
+        self.reset = reset
+        self.retry_after = retry_after
+
+    def headers(self) -> dict[str, str]:
+        """The RateLimit-* headers (and Retry-After when refused)"""
+        headers = {
+            'RateLimit-Limit': str(self.limit),
+            'RateLimit-Remaining': str(self.remaining),
+            'RateLimit-Reset': str(self.reset),
+        }
+        if not self.allowed:
+            headers['Retry-After'] = str(self.retry_after)
+        return headers
+
+
+class RateLimiter:
+    """A token bucket per client: burst requests at once, refilled per_minute
+
+    A client idle long enough to refill its bucket is no different from a new
+    one, so buckets expire after that and memory stays bounded.
+    """
+
+    def __init__(
+        self,
+        per_minute: int,
+        burst: int | None = None,
+        max_clients: int = MAX_TRACKED_CLIENTS,
+    ) -> None:
+        if per_minute < 1:
+            msg = 'Rate limit must allow at least 1 request per minute'
+            raise ValueError(msg)
+        self.per_minute = per_minute
+        self.burst = max(1, burst or per_minute)
+        self.refill_per_second = per_minute / 60
+        # client -> (tokens, monotonic time they were counted)
+        self.buckets: TTLCache[str, tuple[float, float]] = TTLCache(
+            maxsize=max_clients, ttl=self.burst / self.refill_per_second
+        )
+        self._lock = threading.Lock()
+
+    def check(self, client: str, now: float | None = None) -> RateLimitDecision:
+        """Take a token for the client's request if there is one"""
+        now = time.monotonic() if now is None else now
+        with self._lock:
+            tokens, last = self.buckets.get(client, (float(self.burst), now))
+            tokens = min(self.burst, tokens + (now - last) * self.refill_per_second)
+            allowed = tokens >= 1
+            if allowed:
+                tokens -= 1
+            self.buckets[client] = (tokens, now)
+
+        return RateLimitDecision(
+            allowed,
+            self.burst,
+            math.floor(tokens),
+            math.ceil((self.burst - tokens) / self.refill_per_second),
+            math.ceil((1 - tokens) / self.refill_per_second) if not allowed else 0,
+        )
@@ -0,0 +1,140 @@
+# ABOUTME: Per-connection location subscriptions for the /ws live-update channel
+# ABOUTME: Keeps per-location snapshots and diffs them into JSON patches
+
+import threading
+import time
+from typing import Any
+
+
+# Limit how many locations a single connection can follow at once
+MAX_SUBSCRIPTIONS_PER_CONNECTION = 10
+
+
+def location_key(lat: float, lon: float) -> str:
+    """Build the location key shared with the weather cache"""
+    return f'{lat:.4f},{lon:.4f}'
+
+
+def escape_json_pointer(token: str) -> str:
+    """Escape a JSON Pointer reference token (RFC 6901)"""
+    return token.replace('~', '~0').replace('/', '~1')
+
+
+def make_json_patch(old: Any, new: Any, path: str = '') -> list[dict[str, Any]]:
+    """Diff two JSON-compatible values into RFC 6902 patch operations"""
+    if old == new:
+        return []
+
+    # Only objects are diffed key by key - anything else is replaced wholesale
+    if not isinstance(old, dict) or not isinstance(new, dict):
+        return [{'op': 'replace', 'path': path, 'value': new}]
+
+    operations: list[dict[str, Any]] = []
+    for key in old:
+        if key not in new:
+            operations.append(
+                {'op': 'remove', 'path': f'{path}/{escape_json_pointer(str(key))}'}
+            )
+    for key, value in new.items():
+        child_path = f'{path}/{escape_json_pointer(str(key))}'
+        if key not in old:
+            operations.append({'op': 'add', 'path': child_path, 'value': value})
+        else:
+            operations.extend(make_json_patch(old[key], value, child_path))
+    return operations
+
+
+class SubscriptionManager:
+    """Track which connections follow which locations, plus their last snapshots"""
+
+    def __init__(
+        self, max_subscriptions: int = MAX_SUBSCRIPTIONS_PER_CONNECTION
+    ) -> None:
+        self.max_subscriptions = max_subscriptions
+        self.connections: dict[str, set[str]] = {}
+        self.last_seen: dict[str, float] = {}
+        self.snapshots: dict[str, dict[str, Any]] = {}
+        self._lock = threading.Lock()
+
+    def connect(self, sid: str) -> None:
+        """Register a new connection"""
+        with self._lock:
+            self.connections.setdefault(sid, set())
+            self.last_seen[sid] = time.time()
+
+    def disconnect(self, sid: str) -> list[str]:
+        """Drop a connection and return the locations it was following"""
+        with self._lock:
+            locations = sorted(self.connections.pop(sid, set()))
+            self.last_seen.pop(sid, None)
+            for key in locations:
+                self._forget_unwatched(key)
+            return locations
+
+    def touch(self, sid: str) -> None:
+        """Record keepalive activity for a connection"""
+        with self._lock:
+            if sid in self.connections:
+                self.last_seen[sid] = time.time()
+
+    def subscribe(self, sid: str, key: str) -> bool:
+        """Subscribe a connection to a location, respecting the per-connection cap"""
+        with self._lock:
+            subscriptions = self.connections.setdefault(sid, set())
+            if key in subscriptions:
+                return True
+            if len(subscriptions) >= self.max_subscriptions:
+                return False
+            subscriptions.add(key)
+            return True
+
+    def unsubscribe(self, sid: str, key: str) -> None:
+        """Stop a connection following a location"""
+        with self._lock:
+            self.connections.get(sid, set()).discard(key)
+            self._forget_unwatched(key)
+
+    def subscriptions(self, sid: str) -> list[str]:
+        """List the locations a connection follows"""
+        with self._lock:
+            return sorted(self.connections.get(sid, set()))
+
+    def subscribers(self, key: str) -> list[str]:
+        """List the connections following a location"""
+        with self._lock:
+            return sorted(
+                sid for sid, locations in self.connections.items() if key in locations
+            )
+
+    def snapshot(self, key: str) -> dict[str, Any]:
+        """Get the last known document for a location"""
+        with self._lock:
+            return dict(self.snapshots.get(key, {}))
+
+    def update_snapshot(self, key: str, section: str, value: Any) -> list[dict]:
+        """Store a new section value and return the patch from the previous one"""
+        with self._lock:
+            if not any(key in locations for locations in self.connections.values()):
+                # Nobody is watching - don't keep snapshots for unwatched locations
+                return []
+            document = self.snapshots.setdefault(key, {})
+            if section not in document:
+                document[section] = value
+                return [{'op': 'add', 'path': f'/{section}', 'value': value}]
+            patch = make_json_patch(document[section], value, f'/{section}')
+            document[section] = value
+            return patch
+
+    def get_stats(self) -> dict[str, Any]:
+        """Summarize live connections and subscriptions"""
+        with self._lock:
+            return {
+                'connections': len(self.connections),
+                'subscriptions': sum(len(keys) for keys in self.connections.values()),
+                'watched_locations': len(self.snapshots),
+            }
+
+    def _forget_unwatched(self, key: str) -> None:
+        """Drop the snapshot for a location once nobody follows it (lock held)"""
+        if not any(key in locations for locations in self.connections.values()):
+            self.snapshots.pop(key, None)
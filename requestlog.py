@@ -0,0 +1,48 @@
+# ABOUTME: Request IDs and one JSON log line per request, so a user's report of
+# ABOUTME: a failure (with the X-Request-Id it got) can be matched to server logs
+
+import json
+import re
+import secrets
+from datetime import datetime, timezone
+
+
+REQUEST_ID_HEADER = 'X-Request-Id'
+
+# Ids a proxy in front may pass along; anything else gets a fresh id so log
+# lines can't be forged or bloated through the header
+INBOUND_REQUEST_ID = re.compile(r'[A-Za-z0-9._:-]{8,128}')
+
+
+def request_id(inbound: str | None = None) -> str:
+    """The id for a request: the proxy's well-formed one, else a new random one"""
+    if inbound and INBOUND_REQUEST_ID.fullmatch(inbound):
+        return inbound
+    return secrets.token_hex(16)
+
+
+def access_log_line(
+    request_id: str,
+    method: str,
+    path: str,
+    status: int,
+    latency_ms: float,
+    provider: str | None = None,
+    cache_hit: bool | None = None,
+) -> str:
+    """One request as a single-line JSON object; unknown fields are null"""
+    return json.dumps(
+        {
+            'ts': datetime.now(timezone.utc).isoformat(timespec='milliseconds'),
+            'level': 'info',
+            'msg': 'request',
+            'request_id': request_id,
+            'method': method,
+            'path': path,
+            'status': status,
+            'latency_ms': round(latency_ms, 1),
+            'provider': provider,
+            'cache_hit': cache_hit,
+        },
+        separators=(',', ':'),
+    )
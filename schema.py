@@ -0,0 +1,273 @@
+# ABOUTME: The weather document's published shape - fields, types, units, icons and
+# ABOUTME: enum values - as a JSON Schema for /api/schema, pages and the API renames
+
+from typing import Any
+
+from i18n import SUPPORTED_LANGUAGES
+from icons import WEATHER_ICON_CODES
+from prefs import PREF_CHOICES
+from weather_core.units import UNIT_LABELS
+
+
+JSON_SCHEMA_DIALECT = 'https://json-schema.org/draft/2020-12/schema'
+
+# API naming policy: weather documents go out with snake_case field names. The
+# abbreviated names used internally are still served to clients that ask for
+# ?naming=legacy while they migrate
+API_FIELD_RENAMES: dict[str, dict[str, str]] = {
+    'current': {'precipitation_prob': 'precipitation_probability'},
+    'hourly': {
+        't': 'label',
+        'temp': 'temperature',
+        'rain': 'precipitation_probability',
+        'desc': 'description',
+    },
+    'daily': {
+        'd': 'day',
+        'h': 'high',
+        'l': 'low',
+        'rain': 'precipitation_probability',
+    },
+}
+DEFAULT_API_NAMING = 'snake_case'
+API_NAMING_STYLES = (DEFAULT_API_NAMING, 'legacy')
+
+PRECIPITATION_TYPES = ('rain', 'showers', 'snow', 'sleet')
+PRESSURE_TRENDS = ('rising', 'falling', 'steady')
+
+
+def field(
+    kind: str | dict[str, Any],
+    description: str,
+    unit: str | None = None,
+    *,
+    nullable: bool = True,
+    **keywords: Any,
+) -> dict[str, Any]:
+    """One property's schema; unit is a UNIT_LABELS quantity or a fixed unit
+
+    Readings are nullable because providers report a missing value as null
+    rather than inventing one.
+    """
+    schema = dict(kind) if isinstance(kind, dict) else {'type': kind}
+    if nullable:
+        schema = {'anyOf': [schema, {'type': 'null'}]}
+    schema['description'] = description
+    if unit in UNIT_LABELS['imperial']:
+        schema['x-quantity'] = unit
+    elif unit:
+        schema['x-unit'] = unit
+    return {**schema, **keywords}
+
+
+def icon(description: str) -> dict[str, Any]:
+    """An icon code from the shared vocabulary"""
+    return {'$ref': '#/$defs/Icon', 'description': description}
+
+
+# Each section's fields by their snake_case API names
+CURRENT_PROPERTIES = {
+    'temperature': field('number', 'Air temperature', 'temperature'),
+    'feels_like': field('number', 'Apparent temperature', 'temperature'),
+    'humidity': field('number', 'Relative humidity', '%'),
+    'wind_speed': field('number', 'Sustained wind speed', 'wind_speed'),
+    'wind_direction': field('number', 'Direction the wind blows from', '°'),
+    'wind_gust': field('number', 'Wind gust speed', 'wind_speed'),
+    'uv_index': field('number', 'UV index'),
+    'pressure': field('number', 'Sea-level pressure', 'pressure'),
+    'dew_point': field('number', 'Dew point', 'temperature'),
+    'precipitation_rate': field(
+        'number', 'Precipitation in the last hour', 'precipitation'
+    ),
+    'rain_rate': field('number', 'Rain in the last hour', 'precipitation'),
+    'shower_rate': field('number', 'Showers in the last hour', 'precipitation'),
+    'snow_rate': field('number', 'Snowfall in the last hour', 'precipitation'),
+    'precipitation_probability': field('number', 'Chance of precipitation', '%'),
+    'precipitation_type': field(
+        {'enum': list(PRECIPITATION_TYPES)}, 'Kind of precipitation falling'
+    ),
+    'precip_starts_in_minutes': field(
+        'integer', 'When precipitation starts within 24 hours', 'min'
+    ),
+    'precip_ends_in_minutes': field(
+        'integer', 'When falling precipitation stops within 24 hours', 'min'
+    ),
+    'is_day': field('boolean', 'Whether the sun is up'),
+    'icon': icon('Icon for the current conditions'),
+    'summary': field('string', 'Short description of the conditions'),
+    'beaufort': field(
+        'integer', 'Beaufort force of the wind (with ?lang=)', minimum=0, maximum=12
+    ),
+    'wind_description': field('string', 'Localized wind wording (with ?lang=)'),
+}
+HOURLY_PROPERTIES = {
+    'label': field(
+        'string', "Hour label on the visitor's clock ('2pm', '14:00')", nullable=False
+    ),
+    'temperature': field('number', 'Air temperature', 'temperature'),
+    'feels_like': field('number', 'Apparent temperature', 'temperature'),
+    'icon': icon('Icon for the hour'),
+    'precipitation_probability': field('number', 'Chance of precipitation', '%'),
+    'precipitation': field('number', 'Precipitation amount', 'precipitation'),
+    'description': field('string', 'Short description of the conditions'),
+    'pressure': field('number', 'Sea-level pressure', 'pressure'),
+    'time': field('string', "Start of the hour in the location's time", nullable=False),
+}
+DAILY_PROPERTIES = {
+    'day': field('string', "Weekday abbreviation ('Mon')", nullable=False),
+    'date': field({'type': 'string', 'format': 'date'}, 'Local date', nullable=False),
+    'high': field('number', 'High temperature', 'temperature'),
+    'low': field('number', 'Low temperature', 'temperature'),
+    'icon': icon('Icon for the day'),
+    'precipitation_probability': field('number', 'Chance of precipitation', '%'),
+}
+MINUTELY_PROPERTIES = {
+    'time': field('string', "Start of the 15 minutes ('14:15')", nullable=False),
+    'temp': field('number', 'Air temperature', 'temperature'),
+    'precipitation': field('number', 'Precipitation amount', 'precipitation'),
+    'rain': field('number', 'Rain amount', 'precipitation'),
+    'snow': field('number', 'Snowfall amount', 'precipitation'),
+    'weather_code': field('integer', 'WMO weather code'),
+}
+PRESSURE_TREND_PROPERTIES = {
+    'trend': field({'enum': list(PRESSURE_TRENDS)}, 'Direction', nullable=False),
+    'rate': field('number', 'Change per hour over the last 3 hours', 'hPa/h'),
+    'prediction': field('string', 'What the trend suggests', nullable=False),
+    'current_pressure': field('number', 'Latest pressure', 'pressure'),
+    'history': field(
+        {
+            'type': 'array',
+            'items': {
+                'type': 'object',
+                'properties': {
+                    'time': {'type': 'string'},
+                    'pressure': {'type': 'number'},
+                },
+            },
+        },
+        'Hourly pressures for a mini-chart',
+    ),
+}
+# Only added with ?lang=
+OPTIONAL_CURRENT = ('beaufort', 'wind_description')
+REQUIRED_CURRENT = tuple(
+    name for name in CURRENT_PROPERTIES if name not in OPTIONAL_CURRENT
+)
+REQUIRED_PRESSURE_TREND = ('trend', 'rate', 'prediction')
+
+
+def section(
+    title: str, properties: dict[str, dict[str, Any]], required: tuple[str, ...]
+) -> dict[str, Any]:
+    """An object definition listing its properties and which are always present"""
+    return {
+        'title': title,
+        'type': 'object',
+        'properties': properties,
+        'required': list(required),
+    }
+
+
+def legacy_names() -> dict[str, dict[str, str]]:
+    """Each section's snake_case names and the ?naming=legacy name they replace"""
+    return {
+        name: {api: internal for internal, api in renames.items()}
+        for name, renames in API_FIELD_RENAMES.items()
+    }
+
+
+def build_schema() -> dict[str, Any]:
+    """The weather document as a JSON Schema, with units, icons and parameters"""
+    return {
+        '$schema': JSON_SCHEMA_DIALECT,
+        'title': 'WeatherData',
+        'description': 'The document /api/weather returns',
+        'type': 'object',
+        'properties': {
+            'location': field('string', 'Location name', nullable=False),
+            'provider': field('string', 'Provider the data came from', nullable=False),
+            'current': {'$ref': '#/$defs/CurrentConditions'},
+            'hourly': {'type': 'array', 'items': {'$ref': '#/$defs/HourlyForecast'}},
+            'daily': {'type': 'array', 'items': {'$ref': '#/$defs/DailyForecast'}},
+            'minutely': {
+                'type': 'array',
+                'items': {'$ref': '#/$defs/MinutelyForecast'},
+            },
+            'sun': field(
+                {
+                    'type': 'object',
+                    'additionalProperties': {
+                        'type': 'object',
+                        'properties': {
+                            'sunrise': {'type': 'string'},
+                            'sunset': {'type': 'string'},
+                        },
+                    },
+                },
+                'Sunrise and sunset by local date',
+                nullable=False,
+            ),
+            'pressure_trend': {'$ref': '#/$defs/PressureTrend'},
+            'units': field(
+                {'type': 'object', 'additionalProperties': {'type': 'string'}},
+                'Unit label for each quantity in this response',
+                nullable=False,
+            ),
+            'icon_set': field(
+                'object', 'Icon pack and where each icon is', nullable=False
+            ),
+            'server_time': field(
+                {'type': 'string', 'format': 'date-time'},
+                'When the response was made (UTC)',
+                nullable=False,
+            ),
+            'stale': field(
+                'boolean', 'Served from before a provider switch', nullable=False
+            ),
+            'provenance': field(
+                {'type': 'object', 'additionalProperties': {'type': 'string'}},
+                'Provider behind each section',
+                nullable=False,
+            ),
+            'location_fallback': field(
+                'object', 'Why a different location was used', nullable=False
+            ),
+        },
+        'required': [
+            *('location', 'provider', 'current', 'hourly', 'daily', 'minutely'),
+            *('sun', 'pressure_trend', 'units', 'icon_set', 'server_time'),
+        ],
+        '$defs': {
+            'Icon': {
+                'description': 'Icon codes shared by every icon set',
+                'enum': list(WEATHER_ICON_CODES),
+            },
+            'CurrentConditions': section(
+                'CurrentConditions', CURRENT_PROPERTIES, REQUIRED_CURRENT
+            ),
+            'HourlyForecast': section(
+                'HourlyForecast', HOURLY_PROPERTIES, tuple(HOURLY_PROPERTIES)
+            ),
+            'DailyForecast': section(
+                'DailyForecast', DAILY_PROPERTIES, tuple(DAILY_PROPERTIES)
+            ),
+            'MinutelyForecast': section(
+                'MinutelyForecast', MINUTELY_PROPERTIES, tuple(MINUTELY_PROPERTIES)
+            ),
+            'PressureTrend': section(
+                'PressureTrend', PRESSURE_TREND_PROPERTIES, REQUIRED_PRESSURE_TREND
+            ),
+        },
+        # Labels behind each x-quantity, by unit system (?units=)
+        'x-units': UNIT_LABELS,
+        'x-legacy-names': legacy_names(),
+        'x-parameters': {
+            'units': list(PREF_CHOICES['units']),
+            'time_format': list(PREF_CHOICES['time_format']),
+            'naming': list(API_NAMING_STYLES),
+            'lang': list(SUPPORTED_LANGUAGES),
+        },
+    }
+
+
+WEATHER_SCHEMA = build_schema()
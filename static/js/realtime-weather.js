@@ -13,6 +13,8 @@ class RealTimeWeatherManager {
         this.precipitationPollingInterval = null;
         this.precipitationPollingDelay = 60000; // 1 minute when precipitation detected
         this.usePolling = false;
+        this.eventSource = null;
+        this.useEventStream = typeof EventSource !== 'undefined';
         this.currentLocation = null;
         this.eventHandlers = new Map();
         this.hasPrecipitation = false;
@@ -130,6 +132,12 @@ class RealTimeWeatherManager {
     }
 
     startPolling() {
+        // Prefer a server-pushed event stream over interval polling when available
+        if (this.useEventStream && this.currentLocation) {
+            this.startEventStream(this.currentLocation);
+            return;
+        }
+
         if (this.pollingInterval) {
             clearInterval(this.pollingInterval);
         }
@@ -155,6 +163,50 @@ class RealTimeWeatherManager {
             this.pollingInterval = null;
             console.log('⏹️  Polling stopped');
         }
+        this.stopEventStream();
+    }
+
+    startEventStream(location) {
+        this.stopEventStream();
+
+        const params = new URLSearchParams();
+        if (location.lat) params.append('lat', location.lat);
+        if (location.lon) params.append('lon', location.lon);
+        if (location.location) params.append('location', location.location);
+        if (location.timezone) params.append('timezone', location.timezone);
+
+        console.log('📡 Opening weather event stream');
+        this.eventSource = new EventSource(`/api/weather/stream?${params}`);
+        this.broadcastEvent('connection_status', { connected: true, type: 'stream' });
+
+        this.eventSource.addEventListener('weather', (event) => {
+            const data = JSON.parse(event.data);
+            console.log('🌤️  Received weather update via event stream');
+            this.handleWeatherUpdate(data);
+            this.broadcastEvent('weather_update', data);
+        });
+
+        this.eventSource.addEventListener('weather_error', (event) => {
+            this.broadcastEvent('weather_error', JSON.parse(event.data));
+        });
+
+        this.eventSource.onerror = () => {
+            // EventSource reconnects on its own unless the server refused the stream
+            if (this.eventSource.readyState !== EventSource.CLOSED) return;
+
+            console.log('❌ Event stream failed, falling back to interval polling');
+            this.stopEventStream();
+            this.useEventStream = false;
+            this.startPolling();
+        };
+    }
+
+    stopEventStream() {
+        if (this.eventSource) {
+            this.eventSource.close();
+            this.eventSource = null;
+            console.log('⏹️  Event stream closed');
+        }
     }
 
     async fetchWeatherData(location) {
@@ -262,6 +314,8 @@ class RealTimeWeatherManager {
         if (this.isConnected && this.socket) {
             console.log('📡 Requesting weather update via WebSocket');
             this.socket.emit('request_weather_update', location);
+        } else if (this.usePolling && this.useEventStream) {
+            this.startEventStream(location);
         } else if (this.usePolling) {
             console.log('📡 Requesting weather update via polling');
             this.fetchWeatherData(location);
@@ -334,7 +388,7 @@ class RealTimeWeatherManager {
     getConnectionStatus() {
         return {
             connected: this.isConnected || this.usePolling,
-            type: this.isConnected ? 'websocket' : 'polling',
+            type: this.isConnected ? 'websocket' : (this.eventSource ? 'stream' : 'polling'),
             retryCount: this.retryCount
         };
     }
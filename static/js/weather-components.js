@@ -1,25 +1,8 @@
 // ABOUTME: Web Components for weather app using native Custom Elements API
 // ABOUTME: Modular, reusable widgets with Shadow DOM encapsulation and weather-icons library
 
-// Weather icon mapping using local weather-icons library
-const WEATHER_ICONS = {
-    'clear-day': 'clear-day.svg',
-    'clear-night': 'clear-night.svg',
-    'rain': 'rainy-2.svg',
-    'heavy-rain': 'rainy-3.svg',
-    'light-rain': 'rainy-1.svg',
-    'snow': 'snowy-1.svg',
-    'heavy-snow': 'snowy-3.svg',
-    'light-snow': 'snowy-1.svg',
-    'sleet': 'snowy-2.svg',
-    'wind': 'wind.svg',
-    'fog': 'fog.svg',
-    'cloudy': 'cloudy.svg',
-    'partly-cloudy-day': 'cloudy-1-day.svg',
-    'partly-cloudy-night': 'cloudy-1-night.svg',
-    'thunderstorm': 'thunderstorms.svg',
-    'hail': 'hail.svg'
-};
+// Icon pack from the server (icons.py), shared with the JSON icon_set block
+const ICON_SET = window.ICON_SET || { kind: 'image', icons: {}, animated_icons: {} };
 
 // Weather Icon Web Component
 class WeatherIcon extends HTMLElement {
@@ -48,12 +31,22 @@ class WeatherIcon extends HTMLElement {
         // Check URL parameter for animation preference
         const urlParams = new URLSearchParams(window.location.search);
         const theme = urlParams.get('theme') || urlParams.get('background');
-        const isDashboard = theme === 'dashboard' || theme === 'eink';
+        const bodyTheme = document.getElementById('app-body')?.getAttribute('data-theme');
+        const isDashboard = theme === 'dashboard' || theme === 'eink' || bodyTheme === 'dashboard';
         const useAnimated = urlParams.get('animated') !== 'false' && !isDashboard;
         const iconType = useAnimated ? 'animated' : 'static';
 
-        const iconFile = WEATHER_ICONS[iconCode] || WEATHER_ICONS['clear-day'];
-        const iconUrl = `/static/icons/weather/${iconType}/${iconFile}`;
+        const icons = (iconType === 'animated' && ICON_SET.animated_icons) || ICON_SET.icons;
+        const value = icons[iconCode] || icons['clear-day'] || '';
+
+        // Font packs need their stylesheet inside the shadow root to apply
+        let icon = `<img class="weather-icon" src="${value}" alt="${alt}" />`;
+        if (ICON_SET.kind === 'font') {
+            icon = `<link rel="stylesheet" href="${ICON_SET.stylesheet}">
+                <i class="weather-icon ${value}" role="img" aria-label="${alt}"></i>`;
+        } else if (ICON_SET.kind === 'text') {
+            icon = `<span class="weather-icon" role="img" aria-label="${alt}">${value}</span>`;
+        }
 
         this.shadowRoot.innerHTML = `
             <style>
@@ -67,9 +60,12 @@ class WeatherIcon extends HTMLElement {
                     height: ${size};
                     display: block;
                     object-fit: contain;
+                    font-size: ${size};
+                    line-height: 1;
+                    text-align: center;
                 }
             </style>
-            <img class="weather-icon" src="${iconUrl}" alt="${alt}" />
+            ${icon}
         `;
     }
 }
@@ -138,10 +134,23 @@ function getTimeOfDayColor(timeOfDay) {
     return colors[timeOfDay] || colors.day;
 }
 
+// Unit labels sent with weather data (imperial unless the visitor chose metric);
+// the page provides the table from the server's schema
+const IMPERIAL_UNITS = (window.UNIT_LABELS || {}).imperial
+    || { temperature: '°F', wind_speed: 'mph', pressure: 'hPa', precipitation: 'in' };
+
+function unitLabel(data, quantity) {
+    return ((data && data.units) || IMPERIAL_UNITS)[quantity];
+}
+
+function isMetric(data) {
+    return unitLabel(data, 'temperature') === '°C';
+}
+
 // Helper function to calculate wetbulb temperature
-function calculateWetbulbTemp(tempF, humidity) {
+function calculateWetbulbTemp(temp, humidity, metric = false) {
     // Convert Fahrenheit to Celsius
-    const tempC = (tempF - 32) * 5/9;
+    const tempC = metric ? temp : (temp - 32) * 5/9;
     const rh = humidity;
 
     // Stull approximation for wetbulb temperature
@@ -151,6 +160,8 @@ function calculateWetbulbTemp(tempF, humidity) {
                      0.00391838 * Math.pow(rh, 1.5) * Math.atan(0.023101 * rh) -
                      4.686035;
 
+    if (metric) return Math.round(wetbulbC);
+
     // Convert back to Fahrenheit
     const wetbulbF = wetbulbC * 9/5 + 32;
 
@@ -457,6 +468,7 @@ class CurrentWeatherWidget extends WeatherWidget {
 
                 <div class="feels-like" id="feels-like">LOADING...</div>
                 <div class="summary" id="summary">Loading weather data...</div>
+                <div class="climate-note hidden" id="climate-note"></div>
 
                 <div class="weather-details">
                     <div class="detail-card theme-card">
@@ -487,10 +499,10 @@ class CurrentWeatherWidget extends WeatherWidget {
 
         const current = this.data.current;
 
-        this.shadowRoot.getElementById('temp').textContent = `${current.temperature}°F`;
+        this.shadowRoot.getElementById('temp').textContent = `${current.temperature}${unitLabel(this.data, 'temperature')}`;
         this.shadowRoot.getElementById('icon').innerHTML = getWeatherIcon(current.icon, '6rem');
 
-        const wetbulbTemp = calculateWetbulbTemp(current.temperature, current.humidity);
+        const wetbulbTemp = calculateWetbulbTemp(current.temperature, current.humidity, isMetric(this.data));
         this.shadowRoot.getElementById('feels-like').textContent = `FEELS LIKE ${current.feels_like}° • WETBULB ${wetbulbTemp}°`;
 
         // Enhance summary with precipitation info
@@ -499,28 +511,63 @@ class CurrentWeatherWidget extends WeatherWidget {
             const precipType = current.precipitation_type === 'snow' ? 'snowing' : 'raining';
             summary = `Currently ${precipType} - ${summary}`;
         }
+        // Served from the service worker's cache while offline
+        if (this.data.offline && this.data.server_time) {
+            const fetchedAt = new Date(this.data.server_time).toLocaleString([], {
+                weekday: 'short', hour: 'numeric', minute: '2-digit'
+            });
+            summary = `Offline - forecast from ${fetchedAt}. ${summary}`;
+        }
         this.shadowRoot.getElementById('summary').textContent = summary;
 
-        this.shadowRoot.getElementById('humidity').textContent = `${current.humidity}%`;
-        this.shadowRoot.getElementById('wind').textContent = `${current.wind_speed} mph`;
-        this.shadowRoot.getElementById('uv').textContent = current.uv_index;
+        this.shadowRoot.getElementById('humidity').textContent = `${current.humidity ?? '--'}%`;
+        this.shadowRoot.getElementById('wind').textContent = `${current.wind_speed} ${unitLabel(this.data, 'wind_speed')}`;
+        this.shadowRoot.getElementById('uv').textContent = current.uv_index ?? '--';
 
         // Update precipitation display
         const rainEl = this.shadowRoot.getElementById('rain');
         if (current.precipitation_rate > 0) {
-            rainEl.textContent = `${current.precipitation_rate}" now`;
+            rainEl.textContent = isMetric(this.data)
+                ? `${current.precipitation_rate} mm now`
+                : `${current.precipitation_rate}" now`;
             rainEl.style.color = '#60a5fa';
-        } else if (current.precipitation_prob > 0) {
-            rainEl.textContent = `${current.precipitation_prob}%`;
+        } else if (current.precipitation_probability > 0) {
+            rainEl.textContent = `${current.precipitation_probability}%`;
             rainEl.style.color = 'inherit';
         } else {
             rainEl.textContent = '0%';
             rainEl.style.color = 'inherit';
         }
 
+        this.loadClimateNote();
+
         this.hideError();
         this.hideLoading();
     }
+
+    async loadClimateNote() {
+        // Today against the local normal ("4° warmer than average"), looked up
+        // once per place and day since the normal doesn't move in between
+        const { lat, lon } = weatherApp.parseLocationParams();
+        const today = this.data.daily?.[0]?.date;
+        const key = `${lat},${lon},${today}`;
+        if (!lat || !lon || this.climateKey === key) return;
+        this.climateKey = key;
+
+        const noteEl = this.shadowRoot.getElementById('climate-note');
+        try {
+            const params = new URLSearchParams({ lat, lon });
+            if (isMetric(this.data)) params.append('units', 'metric');
+            const response = await fetch(`/api/climatology?${params}`);
+            const data = await response.json();
+            const annotation = response.ok ? data.today?.annotation : null;
+            noteEl.textContent = annotation || '';
+            noteEl.classList.toggle('hidden', !annotation);
+        } catch (error) {
+            console.error('Climatology fetch error:', error);
+            noteEl.classList.add('hidden');
+        }
+    }
 }
 
 // Hourly Forecast Component
@@ -576,11 +623,11 @@ class HourlyForecastWidget extends WeatherWidget {
             hourDiv.className = 'hour-temp';
 
             // Add time-of-day color coding
-            const timeOfDay = getTimeOfDay(hour.t, this.data.sun);
+            const timeOfDay = getTimeOfDay(hour.label, this.data.sun);
             const backgroundColor = getTimeOfDayColor(timeOfDay);
 
             hourDiv.innerHTML = `
-                <div class="hour-temp-value">${hour.temp}°</div>
+                <div class="hour-temp-value">${hour.temperature}°</div>
                 <div class="hour-icon">${getWeatherIcon(hour.icon, '1.75rem')}</div>
             `;
 
@@ -596,9 +643,9 @@ class HourlyForecastWidget extends WeatherWidget {
             timeSpan.className = 'hour-time';
             // Show every other hour on small screens, all hours on larger screens
             if (index % 2 === 0) {
-                timeSpan.textContent = hour.t;
+                timeSpan.textContent = hour.label;
             } else {
-                timeSpan.innerHTML = `<span class="hidden sm:inline">${hour.t}</span>`;
+                timeSpan.innerHTML = `<span class="hidden sm:inline">${hour.label}</span>`;
             }
             hourlyTimesContainer.appendChild(timeSpan);
         });
@@ -620,7 +667,7 @@ class HourlyForecastWidget extends WeatherWidget {
 
         if (width === 0 || height === 0) return;
 
-        const temps = hourlyData.map(h => h.temp);
+        const temps = hourlyData.map(h => h.temperature);
         const maxTemp = Math.max(...temps);
         const minTemp = Math.min(...temps);
         const tempRange = maxTemp - minTemp || 1;
@@ -715,10 +762,10 @@ class DailyForecastWidget extends WeatherWidget {
             }
 
             dayDiv.innerHTML = `
-                <div class="day-name">${day.d}</div>
+                <div class="day-name">${day.day}</div>
                 <div class="day-icon">${getWeatherIcon(day.icon, '2rem')}</div>
-                <div class="day-high">${day.h}°</div>
-                <div class="day-low">${day.l}°</div>
+                <div class="day-high">${day.high}°</div>
+                <div class="day-low">${day.low}°</div>
             `;
             dailyContainer.appendChild(dayDiv);
         });
@@ -740,8 +787,8 @@ class DailyForecastWidget extends WeatherWidget {
 
         if (width === 0 || height === 0) return;
 
-        const highs = dailyData.map(d => d.h);
-        const lows = dailyData.map(d => d.l);
+        const highs = dailyData.map(d => d.high);
+        const lows = dailyData.map(d => d.low);
         const allTemps = [...highs, ...lows];
         const maxTemp = Math.max(...allTemps);
         const minTemp = Math.min(...allTemps);
@@ -749,8 +796,8 @@ class DailyForecastWidget extends WeatherWidget {
 
         dailyData.forEach((day, index) => {
             const x = (index / (dailyData.length - 1)) * width;
-            const highY = height - ((day.h - minTemp) / tempRange) * height;
-            const lowY = height - ((day.l - minTemp) / tempRange) * height;
+            const highY = height - ((day.high - minTemp) / tempRange) * height;
+            const lowY = height - ((day.low - minTemp) / tempRange) * height;
 
             // Temperature range line
             const line = document.createElementNS('http://www.w3.org/2000/svg', 'line');
@@ -819,18 +866,18 @@ class HourlyTimelineWidget extends WeatherWidget {
             const dotClass = isCurrentHour ? 'current' : 'future';
 
             // Add time-of-day color coding
-            const timeOfDay = getTimeOfDay(hour.t, this.data.sun);
+            const timeOfDay = getTimeOfDay(hour.label, this.data.sun);
             const backgroundColor = getTimeOfDayColor(timeOfDay);
 
             timelineItem.innerHTML = `
                 <div class="timeline-dot ${dotClass}"></div>
                 <div class="timeline-content">
                     <div class="timeline-header">
-                        <div class="timeline-time">${isCurrentHour ? 'NOW' : hour.t}</div>
-                        <div class="timeline-temp">${hour.temp}°</div>
+                        <div class="timeline-time">${isCurrentHour ? 'NOW' : hour.label}</div>
+                        <div class="timeline-temp">${hour.temperature}°</div>
                     </div>
-                    ${hour.desc ? `<div class="timeline-desc">${hour.desc}</div>` : ''}
-                    ${hour.rain > 0 ? `<div class="timeline-rain">${hour.rain}% rain</div>` : ''}
+                    ${hour.description ? `<div class="timeline-desc">${hour.description}</div>` : ''}
+                    ${hour.precipitation_probability > 0 ? `<div class="timeline-rain">${hour.precipitation_probability}% rain</div>` : ''}
                 </div>
             `;
 
@@ -1096,19 +1143,8 @@ class AirQualityWidget extends WeatherWidget {
     }
 
     parseLocationParams() {
-        // Use same city mapping as main weather app
-        const cityCoords = {
-            'chicago': [41.8781, -87.6298, 'Chicago'],
-            'nyc': [40.7128, -74.0060, 'New York City'],
-            'sf': [37.7749, -122.4194, 'San Francisco'],
-            'london': [51.5074, -0.1278, 'London'],
-            'paris': [48.8566, 2.3522, 'Paris'],
-            'tokyo': [35.6762, 139.6503, 'Tokyo'],
-            'sydney': [-33.8688, 151.2093, 'Sydney'],
-            'berlin': [52.5200, 13.4050, 'Berlin'],
-            'rome': [41.9028, 12.4964, 'Rome'],
-            'madrid': [40.4168, -3.7038, 'Madrid'],
-        };
+        // City registry served by the page (see inject_city_coords in main.py)
+        const cityCoords = window.CITY_COORDS || {};
 
         let lat, lon, location;
 
@@ -1119,7 +1155,7 @@ class AirQualityWidget extends WeatherWidget {
             lat = latStr;
             lon = lonStr;
             if (pathParts.length >= 2) {
-                location = pathParts[1].replace(/-/g, ' ');
+                location = decodeURIComponent(pathParts[1]).replace(/-/g, ' ');
             }
         } else if (pathParts.length >= 1 && cityCoords[pathParts[0].toLowerCase()]) {
             // Format: /city (like /london, /nyc, etc.)
@@ -1364,11 +1400,12 @@ class WindDirectionWidget extends WeatherWidget {
         if (!this.data || !this.data.current) return;
 
         const current = this.data.current;
+        const speedUnit = unitLabel(this.data, 'wind_speed');
 
         // Update wind speed
         const windSpeedEl = this.shadowRoot.getElementById('wind-speed');
         if (windSpeedEl && current.wind_speed !== undefined) {
-            windSpeedEl.textContent = `${current.wind_speed} mph`;
+            windSpeedEl.textContent = `${current.wind_speed} ${speedUnit}`;
         }
 
         // Update wind direction
@@ -1408,17 +1445,18 @@ class WindDirectionWidget extends WeatherWidget {
         // Update wind gusts (if available)
         const windGustEl = this.shadowRoot.getElementById('wind-gust');
         if (windGustEl) {
-            if (current.wind_gust !== undefined) {
-                windGustEl.textContent = `${current.wind_gust} mph`;
+            if (current.wind_gust != null) {
+                windGustEl.textContent = `${current.wind_gust} ${speedUnit}`;
             } else {
                 // Estimate gusts as 1.3x sustained wind for display
                 const estimatedGust = Math.round(current.wind_speed * 1.3);
-                windGustEl.textContent = `~${estimatedGust} mph`;
+                windGustEl.textContent = `~${estimatedGust} ${speedUnit}`;
             }
         }
 
-        // Update wind description and Beaufort scale
-        this.updateWindDescription(current.wind_speed);
+        // Update wind description and Beaufort scale (which works in mph)
+        const windSpeedMph = isMetric(this.data) ? current.wind_speed / 1.609344 : current.wind_speed;
+        this.updateWindDescription(windSpeedMph);
 
         this.hideError();
         this.hideLoading();
@@ -1484,18 +1522,7 @@ class WeatherApp {
     constructor() {
         this.activeRequests = new Map();
         this.geolocationRequested = false;
-        this.cityCoords = {
-            'chicago': [41.8781, -87.6298, 'Chicago'],
-            'nyc': [40.7128, -74.0060, 'New York City'],
-            'sf': [37.7749, -122.4194, 'San Francisco'],
-            'london': [51.5074, -0.1278, 'London'],
-            'paris': [48.8566, 2.3522, 'Paris'],
-            'tokyo': [35.6762, 139.6503, 'Tokyo'],
-            'sydney': [-33.8688, 151.2093, 'Sydney'],
-            'berlin': [52.5200, 13.4050, 'Berlin'],
-            'rome': [41.9028, 12.4964, 'Rome'],
-            'madrid': [40.4168, -3.7038, 'Madrid'],
-        };
+        this.cityCoords = window.CITY_COORDS || {};
     }
 
     // Save location to localStorage
@@ -1508,6 +1535,8 @@ class WeatherApp {
                 timestamp: Date.now()
             };
             localStorage.setItem('weather_location', JSON.stringify(locationData));
+            // Profile default the API falls back to when a request's location is unusable
+            document.cookie = `weather_default_location=${lat},${lon}; path=/; max-age=31536000; SameSite=Lax`;
             console.log('📍 Location saved to localStorage:', locationData);
         } catch (error) {
             console.error('Failed to save location to localStorage:', error);
@@ -1758,7 +1787,7 @@ class WeatherApp {
             lat = latStr;
             lon = lonStr;
             if (pathParts.length >= 2) {
-                location = pathParts[1].replace(/-/g, ' ');
+                location = decodeURIComponent(pathParts[1]).replace(/-/g, ' ');
             }
             // Timezone will be auto-detected by OpenMeteo API
         } else if (pathParts.length >= 1 && this.cityCoords[pathParts[0].toLowerCase()]) {
@@ -1790,10 +1819,9 @@ class WeatherApp {
 
         // Default to Chicago if no location provided
         if (!lat && !lon && !location) {
-            const defaultCity = this.cityCoords['chicago'];
-            lat = defaultCity[0];
-            lon = defaultCity[1];
-            location = defaultCity[2];
+            lat = 41.8781;
+            lon = -87.6298;
+            location = 'Chicago';
             // Timezone will be auto-detected by OpenMeteo API
         }
 
@@ -1814,6 +1842,11 @@ class WeatherApp {
         if (timezone) {
             params.append('timezone', timezone); // Optional override only
         }
+        // Simulated weather for demos and screenshots (needs MOCK_WEATHER=1)
+        const scenario = new URLSearchParams(window.location.search).get('scenario');
+        if (scenario) {
+            params.append('scenario', scenario);
+        }
         if (params.toString()) {
             apiUrl += '?' + params.toString();
         }
@@ -2025,8 +2058,8 @@ class HelpSection extends HTMLElement {
                             <span class="param-example">?animated=false</span>
                         </li>
                         <li>
-                            <span class="param-name">theme</span> - Background theme (white/light/dashboard/eink available)
-                            <span class="param-example">?theme=white</span>
+                            <span class="param-name">theme</span> - Page theme (default/light/dark/high-contrast/eink; auto follows your system)
+                            <span class="param-example">?theme=dark</span>
                         </li>
                         <li>
                             <span class="param-name">background</span> - Alias for theme parameter
@@ -2434,6 +2467,8 @@ class WeatherAlertsWidget extends WeatherWidget {
             }
 
             this.alertsData = await response.json();
+            // The widget takes over from the banners the server rendered
+            document.getElementById('alert-banners')?.remove();
             this.render();
 
         } catch (error) {
@@ -0,0 +1,253 @@
+# ABOUTME: Readings uploaded by personal weather stations, as JSON or in the Ecowitt
+# ABOUTME: and Weather Underground upload formats, and the stations allowed to send them
+
+import json
+import math
+import secrets
+import threading
+import time
+from collections.abc import Mapping
+from datetime import datetime, timezone
+from typing import Any
+
+from cities import MAX_LATITUDE, MAX_LONGITUDE
+from history import HISTORY_FIELDS, numeric
+from tides import distance_km
+from tokens import SHA256_HEX, hash_token
+
+
+STATION_PROVIDER = 'local-station'
+
+# A station stands in for the provider's current conditions this far around it,
+# while its latest reading is this fresh
+DEFAULT_STATION_RADIUS_KM = 2.0
+STATION_FRESH_SECONDS = 15 * 60
+
+# History field: the upload parameters carrying it, first found wins. Ecowitt and
+# Weather Underground uploads are in imperial units, like the history, except
+# pressure: they send inHg and the history keeps hPa
+UPLOAD_FIELDS = {
+    'temperature': ('tempf',),
+    'feels_like': ('feelslikef',),
+    'dew_point': ('dewptf',),
+    'humidity': ('humidity',),
+    'wind_speed': ('windspeedmph',),
+    'wind_gust': ('windgustmph',),
+    'wind_direction': ('winddir',),
+    'pressure': ('baromrelin', 'baromin'),
+    'uv_index': ('uv', 'UV'),
+    'precipitation_rate': ('rainratein',),
+}
+HPA_PER_INHG = 33.8639
+
+# Ecowitt identifies the station by PASSKEY, Weather Underground by PASSWORD
+UPLOAD_KEY_PARAMS = ('PASSKEY', 'PASSWORD')
+UPLOAD_TIME_FORMAT = '%Y-%m-%d %H:%M:%S'
+
+
+def parse_stations(raw: str) -> list[dict[str, Any]]:
+    """STATIONS (a JSON list of {name, lat, lon, key or key_sha256}) as station records
+
+    Optional radius_km and override_current (default false) say whether, and
+    how far around it, the station's readings replace the current conditions.
+    """
+    if not raw.strip():
+        return []
+    entries = json.loads(raw)
+    if not isinstance(entries, list):
+        msg = 'STATIONS must be a JSON list'
+        raise ValueError(msg)
+    stations: dict[str, dict[str, Any]] = {}
+    for entry in entries:
+        name = entry.get('name') if isinstance(entry, dict) else None
+        if not isinstance(name, str) or not name.strip():
+            msg = 'Every station needs a name'
+            raise ValueError(msg)
+        name = name.strip()
+        if name in stations:
+            msg = f'Station names must be unique: {name!r}'
+            raise ValueError(msg)
+        lat, lon = numeric(entry.get('lat')), numeric(entry.get('lon'))
+        if lat is None or lon is None:
+            msg = f'Station {name!r} needs a lat and lon'
+            raise ValueError(msg)
+        if abs(lat) > MAX_LATITUDE or abs(lon) > MAX_LONGITUDE:
+            msg = f'Station {name!r} has a lat or lon out of range'
+            raise ValueError(msg)
+        key, key_hash = entry.get('key'), entry.get('key_sha256')
+        if isinstance(key, str) and key:
+            station_hash = hash_token(key)
+        elif isinstance(key_hash, str) and SHA256_HEX.fullmatch(key_hash.lower()):
+            station_hash = key_hash.lower()
+        else:
+            msg = f'Station {name!r} needs a key or a 64-digit hex key_sha256'
+            raise ValueError(msg)
+        radius = numeric(entry.get('radius_km', DEFAULT_STATION_RADIUS_KM))
+        if radius is None or radius < 0:
+            msg = f'Station {name!r} has an invalid radius_km'
+            raise ValueError(msg)
+        stations[name] = {
+            'name': name,
+            'lat': lat,
+            'lon': lon,
+            'hash': station_hash,
+            'radius_km': radius,
+            'override_current': entry.get('override_current') is True,
+        }
+    return list(stations.values())
+
+
+def upload_time(value: str | None, received_at: float) -> float:
+    """An upload's dateutc as a Unix time, received_at for "now" or none given"""
+    if not value or value.strip().lower() == 'now':
+        return received_at
+    try:
+        stamp = datetime.strptime(value.strip(), UPLOAD_TIME_FORMAT)
+    except ValueError:
+        msg = f'dateutc must look like 2024-01-31 14:05:00, not {value!r}'
+        raise ValueError(msg) from None
+    return stamp.replace(tzinfo=timezone.utc).timestamp()
+
+
+def reading_value(name: str, value: Any) -> float:
+    """One uploaded reading as a number"""
+    reading = numeric(value)
+    if reading is None and isinstance(value, str):
+        try:
+            reading = float(value)
+        except ValueError:
+            reading = None
+    if reading is None or not math.isfinite(reading):
+        msg = f'{name} must be a number, not {value!r}'
+        raise ValueError(msg)
+    return reading
+
+
+def reading_from_upload(
+    params: Mapping[str, str], received_at: float
+) -> dict[str, Any]:
+    """An Ecowitt or Weather Underground upload as a current-conditions reading"""
+    reading: dict[str, Any] = {}
+    for field, names in UPLOAD_FIELDS.items():
+        name = next((name for name in names if params.get(name)), None)
+        if name is not None:
+            reading[field] = reading_value(name, params[name])
+    if 'pressure' in reading:
+        reading['pressure'] = round(reading['pressure'] * HPA_PER_INHG, 1)
+    if not reading:
+        msg = 'The upload has no readings'
+        raise ValueError(msg)
+    reading['timestamp'] = upload_time(params.get('dateutc'), received_at)
+    return reading
+
+
+def reading_from_json(body: Any, received_at: float) -> dict[str, Any]:
+    """A JSON body of history fields (in the history's units) as a reading
+
+    observed_at is optional, as an ISO 8601 time or Unix seconds; without it
+    the reading is from when it was received.
+    """
+    if not isinstance(body, dict):
+        msg = 'Send a JSON object of readings'
+        raise ValueError(msg)
+    reading: dict[str, Any] = {
+        field: reading_value(field, body[field])
+        for field in HISTORY_FIELDS
+        if body.get(field) is not None
+    }
+    if not reading:
+        msg = f'Send at least one of {", ".join(HISTORY_FIELDS)}'
+        raise ValueError(msg)
+    observed_at = body.get('observed_at')
+    if isinstance(observed_at, str):
+        try:
+            stamp = datetime.fromisoformat(observed_at.replace('Z', '+00:00'))
+        except ValueError:
+            msg = f'observed_at must be an ISO 8601 time, not {observed_at!r}'
+            raise ValueError(msg) from None
+        if stamp.tzinfo is None:
+            stamp = stamp.replace(tzinfo=timezone.utc)
+        reading['timestamp'] = stamp.timestamp()
+    elif observed_at is not None:
+        reading['timestamp'] = reading_value('observed_at', observed_at)
+    else:
+        reading['timestamp'] = received_at
+    return reading
+
+
+class StationNetwork:
+    """The configured stations and the latest reading each has sent"""
+
+    def __init__(self) -> None:
+        self.stations: list[dict[str, Any]] = []
+        self.latest: dict[str, dict[str, Any]] = {}
+        self._lock = threading.Lock()
+
+    def configure(self, stations: list[dict[str, Any]]) -> None:
+        """Replace the stations (see parse_stations), keeping known ones' readings"""
+        with self._lock:
+            self.stations = stations
+            names = {station['name'] for station in stations}
+            self.latest = {
+                name: reading for name, reading in self.latest.items() if name in names
+            }
+
+    def authenticate(self, secret: str | None) -> dict[str, Any] | None:
+        """The station a key belongs to, if any"""
+        if not secret:
+            return None
+        secret_hash = hash_token(secret)
+        with self._lock:
+            stations = list(self.stations)
+        for station in stations:
+            if secrets.compare_digest(station['hash'], secret_hash):
+                return station
+        return None
+
+    def update(self, station: dict[str, Any], reading: dict[str, Any]) -> bool:
+        """Keep a station's reading, returning False if a newer one is already kept"""
+        with self._lock:
+            latest = self.latest.get(station['name'])
+            if latest is not None and latest['timestamp'] > reading['timestamp']:
+                return False
+            self.latest[station['name']] = reading
+            return True
+
+    def current_for(
+        self, lat: float, lon: float, now: float | None = None
+    ) -> tuple[dict[str, Any], dict[str, Any]] | None:
+        """The nearest overriding station with a fresh reading, and that reading"""
+        now = time.time() if now is None else now
+        with self._lock:
+            candidates = [
+                (distance_km(lat, lon, station['lat'], station['lon']), station)
+                for station in self.stations
+                if station['override_current'] and station['name'] in self.latest
+            ]
+            latest = dict(self.latest)
+        nearby = sorted(
+            (distance, station['name'], station)
+            for distance, station in candidates
+            if distance <= station['radius_km']
+            and now - latest[station['name']]['timestamp'] <= STATION_FRESH_SECONDS
+        )
+        if not nearby:
+            return None
+        _, name, station = nearby[0]
+        return station, latest[name]
+
+
+def overlay_current(
+    current: dict[str, Any], station: dict[str, Any], reading: dict[str, Any]
+) -> dict[str, Any]:
+    """A provider's current conditions with a station's readings laid over them
+
+    The provider's icon and summary stay - a station can't see the sky.
+    """
+    return {
+        **current,
+        **{field: reading[field] for field in HISTORY_FIELDS if field in reading},
+        'timestamp': reading['timestamp'],
+        'provider': STATION_PROVIDER,
+        'station': station['name'],
+    }
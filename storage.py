@@ -0,0 +1,139 @@
+# ABOUTME: SQLite persistence for saved favorite locations
+# ABOUTME: Favorites feed the background prefetcher, multi-location view and digests
+
+import os
+import secrets
+import sqlite3
+import threading
+import time
+from typing import Any
+
+
+MAX_LATITUDE = 90
+MAX_LONGITUDE = 180
+MAX_FAVORITE_NAME_LENGTH = 100
+
+FAVORITES_SCHEMA = """
+CREATE TABLE IF NOT EXISTS favorites (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    lat REAL NOT NULL,
+    lon REAL NOT NULL,
+    position INTEGER NOT NULL,
+    created_at REAL NOT NULL
+)
+"""
+FAVORITE_COLUMNS = ('id', 'name', 'lat', 'lon', 'position', 'created_at')
+
+
+def validate_favorite(raw: Any, partial: bool = False) -> dict[str, Any]:
+    """Check a submitted favorite, raising ValueError if unusable"""
+    if not isinstance(raw, dict):
+        msg = 'Favorite must be a JSON object'
+        raise ValueError(msg)  # noqa: TRY004
+
+    favorite: dict[str, Any] = {}
+    if 'name' in raw or not partial:
+        name = raw.get('name')
+        if (
+            not isinstance(name, str)
+            or not name.strip()
+            or len(name) > MAX_FAVORITE_NAME_LENGTH
+        ):
+            msg = 'name is required'
+            raise ValueError(msg)
+        favorite['name'] = name.strip()
+    for field, limit in (('lat', MAX_LATITUDE), ('lon', MAX_LONGITUDE)):
+        if field not in raw and partial:
+            continue
+        value = raw.get(field)
+        if isinstance(value, bool) or not isinstance(value, int | float):
+            msg = f'{field} must be a number'
+            raise ValueError(msg)
+        if abs(value) > limit:
+            msg = 'lat/lon are out of range'
+            raise ValueError(msg)
+        favorite[field] = float(value)
+    if 'position' in raw:
+        position = raw['position']
+        if isinstance(position, bool) or not isinstance(position, int):
+            msg = 'position must be a whole number'
+            raise ValueError(msg)
+        favorite['position'] = position
+    return favorite
+
+
+class FavoritesStore:
+    """Saved locations in a SQLite database, listed in display order"""
+
+    def __init__(self, path: str) -> None:
+        self.path = path
+        directory = os.path.dirname(path)
+        if directory:
+            os.makedirs(directory, exist_ok=True)
+        # One shared connection; the lock serializes access across threads
+        self._db = sqlite3.connect(path, check_same_thread=False)
+        self._db.row_factory = sqlite3.Row
+        self._lock = threading.Lock()
+        with self._lock, self._db:
+            self._db.execute(FAVORITES_SCHEMA)
+
+    def list_favorites(self) -> list[dict[str, Any]]:
+        """Every favorite, in display order"""
+        with self._lock:
+            rows = self._db.execute(
+                'SELECT * FROM favorites ORDER BY position, created_at'
+            ).fetchall()
+        return [dict(row) for row in rows]
+
+    def get(self, favorite_id: str) -> dict[str, Any] | None:
+        """A single favorite, or None if it doesn't exist"""
+        with self._lock:
+            row = self._db.execute(
+                'SELECT * FROM favorites WHERE id = ?', (favorite_id,)
+            ).fetchone()
+        return dict(row) if row else None
+
+    def add(self, raw: Any) -> dict[str, Any]:
+        """Validate and save a new favorite, at the end unless a position is given"""
+        favorite = validate_favorite(raw)
+        with self._lock, self._db:
+            if 'position' not in favorite:
+                (last,) = self._db.execute(
+                    'SELECT MAX(position) FROM favorites'
+                ).fetchone()
+                favorite['position'] = 0 if last is None else last + 1
+            record = {
+                'id': secrets.token_hex(4),
+                **favorite,
+                'created_at': time.time(),
+            }
+            self._db.execute(
+                'INSERT INTO favorites (id, name, lat, lon, position, created_at) '
+                'VALUES (:id, :name, :lat, :lon, :position, :created_at)',
+                record,
+            )
+        return {column: record[column] for column in FAVORITE_COLUMNS}
+
+    def update(self, favorite_id: str, raw: Any) -> dict[str, Any] | None:
+        """Change some of a favorite's fields, returning None if it doesn't exist"""
+        changes = validate_favorite(raw, partial=True)
+        with self._lock, self._db:
+            for column, value in changes.items():
+                # Column names come from validate_favorite, never from the request
+                self._db.execute(
+                    f'UPDATE favorites SET {column} = ? WHERE id = ?',  # noqa: S608
+                    (value, favorite_id),
+                )
+            row = self._db.execute(
+                'SELECT * FROM favorites WHERE id = ?', (favorite_id,)
+            ).fetchone()
+        return dict(row) if row else None
+
+    def delete(self, favorite_id: str) -> bool:
+        """Remove a favorite, returning False if it doesn't exist"""
+        with self._lock, self._db:
+            cursor = self._db.execute(
+                'DELETE FROM favorites WHERE id = ?', (favorite_id,)
+            )
+        return cursor.rowcount > 0
@@ -1,30 +1,19 @@
 // Service Worker for Weather Dashboard PWA
-const CACHE_NAME = 'weather-dashboard-v1';
-const STATIC_CACHE_NAME = 'weather-dashboard-static-v1';
-
-// Files to cache for offline functionality
-const STATIC_FILES = [
-  '/',
-  '/static/css/weather-components.css',
-  '/static/js/weather-components.js',
-  '/static/js/realtime-weather.js',
-  '/static/manifest.json',
-  '/static/icons/icon-192x192.png',
-  '/static/icons/icon-512x512.png'
-];
+// Rendered by the server: cache names follow the deployed assets, so each
+// deploy drops the previous caches
+const CACHE_NAME = 'weather-dashboard-{{ cache_version }}';
+const STATIC_CACHE_NAME = 'weather-dashboard-static-{{ cache_version }}';
+
+// The last successful forecast, served when offline and nothing closer is
+// cached. Its cache outlives deploys
+const FORECAST_CACHE_NAME = 'weather-dashboard-forecast';
+const LAST_FORECAST_URL = '/offline/last-forecast';
+
+// Content-hashed app shell files to cache for offline functionality
+const STATIC_FILES = {{ static_files | tojson }};
 
 // Weather icons that should be cached
-const WEATHER_ICONS = [
-  '/static/icons/weather/static/clear-day.svg',
-  '/static/icons/weather/static/clear-night.svg',
-  '/static/icons/weather/static/cloudy.svg',
-  '/static/icons/weather/static/rainy-1.svg',
-  '/static/icons/weather/static/cloudy-1-day.svg',
-  '/static/icons/weather/static/cloudy-1-night.svg',
-  '/static/icons/weather/static/fog.svg',
-  '/static/icons/weather/static/wind.svg',
-  '/static/icons/weather/static/thunderstorms.svg'
-];
+const WEATHER_ICONS = {{ weather_icons | tojson }};
 
 // Install event - cache static files
 self.addEventListener('install', (event) => {
@@ -75,7 +64,7 @@ self.addEventListener('activate', (event) => {
     caches.keys().then((cacheNames) => {
       return Promise.all(
         cacheNames.map((cacheName) => {
-          if (cacheName !== CACHE_NAME && cacheName !== STATIC_CACHE_NAME) {
+          if (![CACHE_NAME, STATIC_CACHE_NAME, FORECAST_CACHE_NAME].includes(cacheName)) {
             console.log('Service Worker: Deleting old cache:', cacheName);
             return caches.delete(cacheName);
           }
@@ -106,9 +95,15 @@ self.addEventListener('fetch', (event) => {
           // If successful, cache the response for short-term offline use
           if (response.ok) {
             const responseClone = response.clone();
+            const forecastClone = url.pathname === '/api/weather' ? response.clone() : null;
             caches.open(CACHE_NAME).then((cache) => {
               cache.put(request, responseClone);
             });
+            if (forecastClone) {
+              caches.open(FORECAST_CACHE_NAME).then((cache) => {
+                cache.put(LAST_FORECAST_URL, forecastClone);
+              });
+            }
           }
           return response;
         })
@@ -116,7 +111,15 @@ self.addEventListener('fetch', (event) => {
           // If network fails, try to serve from cache
           return caches.match(request).then((cachedResponse) => {
             if (cachedResponse) {
-              return cachedResponse;
+              return markOffline(cachedResponse);
+            }
+            if (url.pathname !== '/api/weather') {
+              return null;
+            }
+            return caches.match(LAST_FORECAST_URL).then((lastForecast) => lastForecast && markOffline(lastForecast));
+          }).then((offlineResponse) => {
+            if (offlineResponse) {
+              return offlineResponse;
             }
 
             // Return a fallback response for weather API
@@ -151,6 +154,25 @@ self.addEventListener('fetch', (event) => {
     return;
   }
 
+  // Pages go to the network first so they pick up new asset versions, falling
+  // back to the cached page (or the cached dashboard shell) when offline
+  if (request.mode === 'navigate') {
+    event.respondWith(
+      fetch(request)
+        .then((response) => {
+          if (response.ok) {
+            const responseClone = response.clone();
+            caches.open(CACHE_NAME).then((cache) => {
+              cache.put(request, responseClone);
+            });
+          }
+          return response;
+        })
+        .catch(() => caches.match(request).then((cachedPage) => cachedPage || caches.match('/')))
+    );
+    return;
+  }
+
   // Handle static files with cache-first strategy
   event.respondWith(
     caches.match(request).then((cachedResponse) => {
@@ -178,6 +200,16 @@ self.addEventListener('fetch', (event) => {
   );
 });
 
+// Flag a cached weather document so the page can say it's showing old data
+function markOffline(response) {
+  return response.clone().json()
+    .then((data) => new Response(JSON.stringify({ ...data, offline: true }), {
+      headers: { 'Content-Type': 'application/json' },
+      status: 200
+    }))
+    .catch(() => response);
+}
+
 // Handle background sync for weather updates
 self.addEventListener('sync', (event) => {
   if (event.tag === 'weather-update') {
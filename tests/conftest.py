@@ -9,11 +9,13 @@ from flask import Flask
 from flask.testing import FlaskClient
 
 
-# Add the project root to the path
-sys.path.insert(0, os.path.abspath(os.path.join(os.path.dirname(__file__), '..')))
+# Add the project root and the weather-core package to the path
+ROOT = os.path.abspath(os.path.join(os.path.dirname(__file__), '..'))
+sys.path.insert(0, ROOT)
+sys.path.insert(0, os.path.join(ROOT, 'core'))
 
 from main import app
-from weather_providers import OpenMeteoProvider, WeatherProviderManager
+from weather_core.providers import OpenMeteoProvider, WeatherProviderManager
 
 
 @pytest.fixture  # type: ignore[misc]
@@ -13,6 +13,7 @@ from main import app, get_weather_from_open_meteo, weather_cache
 HTTP_OK = 200
 HTTP_NOT_FOUND = 404
 HTTP_INTERNAL_SERVER_ERROR = 500
+HTTP_BAD_GATEWAY = 502
 HTTP_SERVICE_UNAVAILABLE = 503
 MOCK_TEMP = 72
 MOCK_FEELS_LIKE = 75
@@ -142,8 +143,11 @@ class TestWeatherAPIIntegration:
             assert response2.status_code == HTTP_OK
             assert mock_get_weather.call_count == 1  # Should not increase
 
-            # Both responses should be identical
-            assert response1.data == response2.data
+            # Both responses should carry the same data, apart from the server time
+            data1 = json.loads(response1.data)
+            data2 = json.loads(response2.data)
+            assert data1.pop('server_time') <= data2.pop('server_time')
+            assert data1 == data2
 
     def test_provider_switching_integration(self, client: FlaskClient) -> None:
         """Test provider switching through API"""
@@ -204,7 +208,7 @@ class TestWeatherAPIIntegration:
             mock_get_weather.return_value = None
 
             response = client.get('/api/weather?lat=41.8781&lon=-87.6298')
-            assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+            assert response.status_code == HTTP_SERVICE_UNAVAILABLE
 
             data = json.loads(response.data)
             assert 'error' in data
@@ -424,6 +428,7 @@ class TestAirQualityIntegration:
         mock_cache.__contains__.return_value = False  # No cache hit
 
         mock_provider_instance = MagicMock()
+        mock_provider_instance.name = 'AirNow'
         mock_provider_instance.get_weather.return_value = None
 
         with patch('main.air_quality_provider', mock_provider_instance):
@@ -431,9 +436,11 @@ class TestAirQualityIntegration:
                 '/api/air-quality?lat=42.0&lon=-88.0'
             )  # Use different coordinates
 
-        assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+        assert response.status_code == HTTP_BAD_GATEWAY
         data = response.get_json()
         assert data is not None
+        assert data['code'] == 'provider_failed'
+        assert data['provider'] == 'AirNow'
         assert 'error' in data
         assert 'Failed to fetch air quality data' in data['error']
 
@@ -517,7 +524,7 @@ class TestEndToEndScenarios:
             mock_get_weather.return_value = None
 
             response = client.get('/api/weather?lat=41.8781&lon=-87.6298')
-            assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+            assert response.status_code == HTTP_SERVICE_UNAVAILABLE
 
             data = json.loads(response.data)
             assert 'error' in data
@@ -702,6 +709,7 @@ class TestWeatherAlertsAPIIntegration:
     def test_weather_alerts_api_failure(self, client: FlaskClient) -> None:
         """Test weather alerts API when NWS provider fails"""
         mock_nws_provider = MagicMock()
+        mock_nws_provider.name = 'NationalWeatherService'
         mock_nws_provider.get_weather.return_value = None
 
         with patch('main.alerts_cache') as mock_cache:
@@ -710,10 +718,11 @@ class TestWeatherAlertsAPIIntegration:
             with patch('main.nws_provider', mock_nws_provider):
                 response = client.get('/api/weather/alerts?lat=42.0&lon=-88.0')
 
-        assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+        assert response.status_code == HTTP_BAD_GATEWAY
         data = response.get_json()
 
         assert data is not None
+        assert data['code'] == 'provider_failed'
         assert 'error' in data
         assert 'Failed to fetch weather alerts' in data['error']
         # Should still provide empty alerts structure
@@ -915,6 +924,7 @@ class TestRadarAPIIntegration:
     def test_radar_api_provider_failure(self, client: FlaskClient) -> None:
         """Test radar API when provider fails"""
         mock_radar_provider = MagicMock()
+        mock_radar_provider.name = 'OpenWeatherMapRadar'
         mock_radar_provider.get_weather.return_value = None
 
         with patch('main.radar_cache') as mock_cache:
@@ -923,10 +933,11 @@ class TestRadarAPIIntegration:
             with patch('main.radar_provider', mock_radar_provider):
                 response = client.get('/api/radar?lat=42.0&lon=-88.0')
 
-        assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+        assert response.status_code == HTTP_BAD_GATEWAY
         data = response.get_json()
 
         assert data is not None
+        assert data['code'] == 'provider_failed'
         assert 'error' in data
         assert 'Failed to fetch radar data' in data['error']
         # Should still provide empty radar structure
@@ -10,7 +10,7 @@ from main import weather_cache
 # Test constants
 HTTP_OK = 200
 HTTP_NOT_FOUND = 404
-HTTP_INTERNAL_SERVER_ERROR = 500
+HTTP_SERVICE_UNAVAILABLE = 503
 MAX_RESPONSE_SIZE = 50000
 
 
@@ -240,7 +240,7 @@ class TestFrontendErrorHandling:
             mock_get_weather.return_value = None
 
             response = client.get('/api/weather?lat=41.8781&lon=-87.6298')
-            assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+            assert response.status_code == HTTP_SERVICE_UNAVAILABLE
             assert response.content_type == 'application/json'
 
             data = json.loads(response.data)
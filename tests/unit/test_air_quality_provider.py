@@ -2,7 +2,7 @@ from unittest.mock import MagicMock, patch
 
 import requests
 
-from weather_providers import AirQualityProvider
+from weather_core.providers import AirQualityProvider
 
 
 # Test constants
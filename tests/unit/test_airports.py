@@ -0,0 +1,47 @@
+"""ABOUTME: Test airport code lookups from the bundled list and OurAirports CSVs
+ABOUTME: CSV fixtures are written to temporary files in the OurAirports layout"""
+
+from pathlib import Path
+
+from airports import AirportIndex
+
+
+OURAIRPORTS_CSV = (
+    'id,ident,type,name,latitude_deg,longitude_deg,gps_code,iata_code,icao_code\n'
+    '1,KPWK,small_airport,Chicago Executive,42.1142,-87.9015,KPWK,PWK,KPWK\n'
+    '2,US-1,heliport,Rooftop Helipad,41.88,-87.63,,,\n'
+    '3,KXYZ,medium_airport,Bad Row,north,west,KXYZ,XYZ,KXYZ\n'
+)
+
+
+class TestAirportIndex:
+    """Test resolving ICAO and IATA codes"""
+
+    def test_bundled_airports(self) -> None:
+        """Test both code styles find the bundled airport, case-insensitively"""
+        index = AirportIndex()
+
+        heathrow = index.lookup('egll')
+        assert heathrow is not None
+        assert heathrow['iata'] == 'LHR'
+        assert index.lookup('ORD') == index.lookup('KORD')
+        assert index.lookup('EGL') is None  # Not an IATA code we know
+        assert index.lookup('../x') is None
+
+    def test_ourairports_csv(self, tmp_path: Path) -> None:
+        """Test an OurAirports export loads, skipping heliports and bad rows"""
+        path = tmp_path / 'airports.csv'
+        path.write_text(OURAIRPORTS_CSV, encoding='utf-8')
+        index = AirportIndex(path)
+
+        executive = index.lookup('PWK')
+        assert executive is not None
+        assert executive['name'] == 'Chicago Executive'
+        assert index.lookup('KPWK') == executive
+        assert index.lookup('KXYZ') is None
+        assert len(index.by_icao) == 1
+
+    def test_missing_file(self, tmp_path: Path) -> None:
+        """Test a missing database leaves an empty index instead of failing"""
+        index = AirportIndex(tmp_path / 'airports.csv')
+        assert index.lookup('ORD') is None
@@ -0,0 +1,71 @@
+"""ABOUTME: Test the opt-in aggregate usage analytics counters
+ABOUTME: Covers device classification, persistence and stale-location reporting"""
+
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
+
+import pytest
+
+from analytics import UsageAnalytics, device_class
+
+
+# Test constants
+IPHONE_UA = 'Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Mobile/15E148'
+IPAD_UA = 'Mozilla/5.0 (iPad; CPU OS 17_0 like Mac OS X)'
+DESKTOP_UA = 'Mozilla/5.0 (Macintosh; Intel Mac OS X 14_0) Safari/605.1.15'
+CHICAGO_KEY = '41.8781,-87.6298'
+STALE_DAYS = 30
+CHICAGO_REQUESTS = 2
+
+
+class TestUsageAnalytics:
+    """Test aggregate request counting without identifying data"""
+
+    @pytest.fixture
+    def analytics(self, tmp_path: Path) -> UsageAnalytics:
+        """Create analytics backed by a temporary file that flushes every record"""
+        return UsageAnalytics(str(tmp_path / 'data' / 'analytics.json'), 0)
+
+    def test_device_class(self) -> None:
+        """Test user agents reduce to coarse device classes"""
+        assert device_class(IPHONE_UA) == 'mobile'
+        assert device_class(IPAD_UA) == 'tablet'
+        assert device_class(DESKTOP_UA) == 'desktop'
+        assert device_class('curl/8.4.0') == 'bot'
+        assert device_class(None) == 'other'
+
+    def test_counts_persist(self, analytics: UsageAnalytics) -> None:
+        """Test counts survive a restart and the file holds no raw user agents"""
+        analytics.record('/api/weather', 'mobile', CHICAGO_KEY)
+        analytics.record('/api/weather', 'desktop', CHICAGO_KEY)
+        analytics.record('/healthz', 'bot')
+
+        reloaded = UsageAnalytics(analytics.path)
+        stats = reloaded.snapshot(STALE_DAYS)
+
+        assert stats['endpoints'] == {'/api/weather': 2, '/healthz': 1}
+        assert stats['devices'] == {'mobile': 1, 'desktop': 1, 'bot': 1}
+        assert stats['locations'][CHICAGO_KEY]['count'] == CHICAGO_REQUESTS
+        assert stats['stale_locations'] == []
+        assert 'Mozilla' not in Path(analytics.path).read_text(encoding='utf-8')
+
+    def test_stale_locations(self, analytics: UsageAnalytics) -> None:
+        """Test locations nobody has requested lately are listed for pruning"""
+        analytics.record('/api/weather', 'mobile', CHICAGO_KEY)
+        last_month = datetime.now(timezone.utc).date() - timedelta(days=STALE_DAYS)
+        analytics.stats['locations']['51.5074,-0.1278'] = {
+            'count': 3,
+            'last_seen': last_month.isoformat(),
+        }
+
+        assert analytics.snapshot(STALE_DAYS)['stale_locations'] == ['51.5074,-0.1278']
+
+    def test_unreadable_file_starts_empty(self, tmp_path: Path) -> None:
+        """Test a corrupt analytics file doesn't stop the app from starting"""
+        path = tmp_path / 'analytics.json'
+        path.write_text('{not json', encoding='utf-8')
+
+        stats = UsageAnalytics(str(path)).snapshot(STALE_DAYS)
+
+        assert stats['endpoints'] == {}
+        assert stats['locations'] == {}
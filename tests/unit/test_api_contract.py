@@ -0,0 +1,259 @@
+"""ABOUTME: Contract tests locking the field names of the weather API documents
+ABOUTME: Covers the snake_case naming policy and the legacy-name compatibility shim"""
+
+import json
+import re
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+from flask_socketio import SocketIOTestClient
+
+from main import (
+    API_FIELD_RENAMES,
+    app,
+    socketio,
+    weather_cache,
+    weather_cache_metadata,
+)
+from schema import WEATHER_SCHEMA
+from weather_core.providers import OpenMeteoProvider
+from weather_core.units import METRIC_FIELDS
+
+
+# Test constants
+HTTP_OK = 200
+WEATHER_URL = '/api/weather?lat=41.8781&lon=-87.6298&location=Chicago'
+SNAKE_CASE = re.compile(r'^[a-z][a-z0-9]*(_[a-z0-9]+)*$')
+
+# The published schema - changing these sets is a breaking API change
+TOP_LEVEL_FIELDS = {
+    'current',
+    'hourly',
+    'daily',
+    'minutely',
+    'sun',
+    'pressure_trend',
+    'location',
+    'provider',
+    'units',
+    'icon_set',
+    'server_time',
+}
+CURRENT_FIELDS = {
+    'temperature',
+    'feels_like',
+    'humidity',
+    'wind_speed',
+    'wind_direction',
+    'wind_gust',
+    'uv_index',
+    'pressure',
+    'dew_point',
+    'precipitation_rate',
+    'rain_rate',
+    'shower_rate',
+    'snow_rate',
+    'precipitation_probability',
+    'precipitation_type',
+    'precip_starts_in_minutes',
+    'precip_ends_in_minutes',
+    'is_day',
+    'icon',
+    'summary',
+}
+HOURLY_FIELDS = {
+    'label',
+    'temperature',
+    'feels_like',
+    'icon',
+    'precipitation_probability',
+    'precipitation',
+    'description',
+    'pressure',
+    'time',
+}
+DAILY_FIELDS = {'day', 'date', 'high', 'low', 'icon', 'precipitation_probability'}
+LEGACY_HOURLY_FIELDS = {
+    't',
+    'temp',
+    'feels_like',
+    'icon',
+    'rain',
+    'precipitation',
+    'desc',
+    'pressure',
+    'time',
+}
+LEGACY_DAILY_FIELDS = {'d', 'date', 'h', 'l', 'icon', 'rain'}
+FRESH_BREEZE_FORCE = 5
+LANG_FIELDS = {'beaufort', 'wind_description'}
+SCHEMA_SECTIONS = {
+    'current': 'CurrentConditions',
+    'hourly': 'HourlyForecast',
+    'daily': 'DailyForecast',
+    'minutely': 'MinutelyForecast',
+}
+
+
+@pytest.fixture
+def provider_weather(mock_open_meteo_response: dict[str, Any]) -> dict[str, Any]:
+    """Weather document exactly as the primary provider produces it"""
+    weather = OpenMeteoProvider().process_weather_data(
+        mock_open_meteo_response, 'Chicago', 'America/Chicago'
+    )
+    assert weather is not None
+    return weather
+
+
+class TestWeatherApiContract:
+    """Lock the /api/weather output schema"""
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    def fetch(self, url: str, weather: dict[str, Any]) -> dict[str, Any]:
+        """Request the API with the provider patched to return the given document"""
+        with patch('main.weather_manager.get_weather') as mock_get_weather:
+            mock_get_weather.return_value = weather
+            response = app.test_client().get(url)
+        assert response.status_code == HTTP_OK
+        return json.loads(response.data)
+
+    def test_snake_case_schema(self, provider_weather: dict[str, Any]) -> None:
+        """Test the default response matches the published snake_case schema"""
+        data = self.fetch(WEATHER_URL, provider_weather)
+
+        assert set(data) == TOP_LEVEL_FIELDS
+        assert set(data['current']) == CURRENT_FIELDS
+        assert all(set(hour) == HOURLY_FIELDS for hour in data['hourly'])
+        assert all(set(day) == DAILY_FIELDS for day in data['daily'])
+
+    def test_every_field_is_snake_case(self, provider_weather: dict[str, Any]) -> None:
+        """Test no abbreviated or camelCase names leak into the response"""
+        data = self.fetch(WEATHER_URL, provider_weather)
+        abbreviations = {
+            old for renames in API_FIELD_RENAMES.values() for old in renames
+        }
+
+        fields = set(data) | set(data['current'])
+        for section in ('hourly', 'daily'):
+            for entry in data[section]:
+                fields |= set(entry)
+        assert all(SNAKE_CASE.match(field) for field in fields)
+        assert not fields & abbreviations
+
+    def test_legacy_naming_shim(self, provider_weather: dict[str, Any]) -> None:
+        """Test ?naming=legacy keeps serving the old abbreviated names"""
+        data = self.fetch(f'{WEATHER_URL}&naming=legacy', provider_weather)
+
+        assert 'precipitation_prob' in data['current']
+        assert all(set(hour) == LEGACY_HOURLY_FIELDS for hour in data['hourly'])
+        assert all(set(day) == LEGACY_DAILY_FIELDS for day in data['daily'])
+
+    def test_naming_styles_get_distinct_etags(
+        self, provider_weather: dict[str, Any]
+    ) -> None:
+        """Test a cached legacy body is never revalidated as the snake_case one"""
+        with patch('main.weather_manager.get_weather') as mock_get_weather:
+            mock_get_weather.return_value = provider_weather
+            client = app.test_client()
+            snake = client.get(WEATHER_URL)
+            legacy = client.get(f'{WEATHER_URL}&naming=legacy')
+
+        assert snake.headers['ETag'] != legacy.headers['ETag']
+
+    @patch('main.weather_manager.get_weather')
+    def test_socket_updates_use_snake_case(
+        self, mock_get_weather: MagicMock, provider_weather: dict[str, Any]
+    ) -> None:
+        """Test WebSocket weather updates follow the same naming policy"""
+        mock_get_weather.return_value = provider_weather
+        client = SocketIOTestClient(app, socketio)
+        client.emit('request_weather_update', {'location': 'Chicago'})
+
+        update = next(
+            r for r in client.get_received() if r['name'] == 'weather_update'
+        )
+        assert set(update['args'][0]['current']) == CURRENT_FIELDS
+        assert all(set(hour) == HOURLY_FIELDS for hour in update['args'][0]['hourly'])
+
+    def test_lang_adds_localized_wind(self, provider_weather: dict[str, Any]) -> None:
+        """Test ?lang= adds Beaufort force and wind wording to current conditions"""
+        provider_weather['current'].update(wind_speed=20, wind_direction=315)
+        data = self.fetch(f'{WEATHER_URL}&lang=es', provider_weather)
+
+        assert set(data['current']) == CURRENT_FIELDS | {'beaufort', 'wind_description'}
+        assert data['current']['beaufort'] == FRESH_BREEZE_FORCE
+        assert data['current']['wind_description'] == 'brisa fresca del noroeste'
+
+    def test_lang_localizes_hour_labels(self, provider_weather: dict[str, Any]) -> None:
+        """Test ?lang= labels hours the way that language writes them"""
+        data = self.fetch(f'{WEATHER_URL}&lang=de&time_format=24h', provider_weather)
+
+        assert all(hour['label'].endswith(' Uhr') for hour in data['hourly'])
+
+
+class TestWeatherSchema:
+    """Lock /api/schema to the documents /api/weather actually sends"""
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    def test_schema_endpoint(self) -> None:
+        """Test the schema is served as cacheable JSON Schema"""
+        response = app.test_client().get('/api/schema')
+
+        assert response.status_code == HTTP_OK
+        assert 'max-age' in response.headers['Cache-Control']
+        schema = json.loads(response.data)
+        assert schema['title'] == 'WeatherData'
+        assert schema['$schema'].startswith('https://json-schema.org/')
+        assert 'thunderstorm' in schema['$defs']['Icon']['enum']
+
+    def test_schema_lists_published_fields(self) -> None:
+        """Test the schema names exactly the fields the contract locks"""
+        definitions = WEATHER_SCHEMA['$defs']
+
+        assert set(WEATHER_SCHEMA['required']) == TOP_LEVEL_FIELDS
+        current = definitions['CurrentConditions']
+        assert set(current['properties']) == CURRENT_FIELDS | LANG_FIELDS
+        assert set(current['required']) == CURRENT_FIELDS
+        assert set(definitions['HourlyForecast']['properties']) == HOURLY_FIELDS
+        assert set(definitions['DailyForecast']['properties']) == DAILY_FIELDS
+
+    def test_documents_match_schema(self, provider_weather: dict[str, Any]) -> None:
+        """Test a served document has every required field and known icons"""
+        with patch('main.weather_manager.get_weather') as mock_get_weather:
+            mock_get_weather.return_value = provider_weather
+            data = json.loads(app.test_client().get(WEATHER_URL).data)
+        definitions = WEATHER_SCHEMA['$defs']
+        icons = set(definitions['Icon']['enum'])
+
+        assert set(WEATHER_SCHEMA['required']) <= set(data)
+        for section, name in SCHEMA_SECTIONS.items():
+            required = set(definitions[name]['required'])
+            value = data[section]
+            for entry in value if isinstance(value, list) else [value]:
+                assert required <= set(entry), section
+                assert entry.get('icon', 'clear-day') in icons
+
+    def test_schema_quantities_match_conversions(self) -> None:
+        """Test every field ?units=metric converts is labelled with its quantity"""
+        for section, fields in METRIC_FIELDS.items():
+            properties = WEATHER_SCHEMA['$defs'][SCHEMA_SECTIONS[section]]['properties']
+            renames = API_FIELD_RENAMES.get(section, {})
+            for name, quantity in fields.items():
+                described = properties[renames.get(name, name)]
+                assert described['x-quantity'] == quantity, (section, name)
+
+    def test_schema_legacy_names(self) -> None:
+        """Test the schema maps each snake_case name back to its legacy name"""
+        legacy = WEATHER_SCHEMA['x-legacy-names']
+
+        assert legacy['hourly']['label'] == 't'
+        assert legacy['daily']['high'] == 'h'
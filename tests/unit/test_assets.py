@@ -0,0 +1,32 @@
+"""ABOUTME: Test static asset hashing and the cache policy for versioned URLs
+ABOUTME: Builds a small static directory per test"""
+
+from pathlib import Path
+
+from assets import IMMUTABLE_CACHE_CONTROL, REVALIDATE_CACHE_CONTROL, AssetManifest
+
+
+class TestAssetManifest:
+    """Test content-hash URLs for static files"""
+
+    def test_versioned_urls(self, tmp_path: Path) -> None:
+        """Test URLs carry a hash that changes with the content"""
+        (tmp_path / 'js').mkdir()
+        (tmp_path / 'js' / 'app.js').write_text('one', encoding='utf-8')
+        first = AssetManifest(tmp_path).url('js/app.js')
+        (tmp_path / 'js' / 'app.js').write_text('two', encoding='utf-8')
+        second = AssetManifest(tmp_path).url('js/app.js')
+
+        assert first.startswith('/static/js/app.js?v=')
+        assert first != second
+        assert AssetManifest(tmp_path).url('missing.css') == '/static/missing.css'
+
+    def test_cache_control(self, tmp_path: Path) -> None:
+        """Test only the current version is cached for good"""
+        (tmp_path / 'app.css').write_text('body {}', encoding='utf-8')
+        manifest = AssetManifest(tmp_path)
+        version = manifest.version('app.css')
+
+        assert manifest.cache_control('app.css', version) == IMMUTABLE_CACHE_CONTROL
+        assert manifest.cache_control('app.css', 'stale') == REVALIDATE_CACHE_CONTROL
+        assert manifest.cache_control('app.css', None) == REVALIDATE_CACHE_CONTROL
@@ -0,0 +1,115 @@
+"""ABOUTME: Test the bundled astronomical events and local visibility ratings
+ABOUTME: Covers meteor viewing times, radiant geometry, moonlight and clouds"""
+
+from datetime import datetime, timezone
+from pathlib import Path
+
+from astro import assess_visibility, event_time, load_astro_events
+
+
+# Test constants
+CHICAGO_LAT = 41.8781
+CHICAGO_LON = -87.6298
+SYDNEY_LAT = -33.8688
+SYDNEY_LON = 151.2093
+DARK_SKY = -30
+DAYLIGHT = 40
+NEW_MOON = 2
+FULL_MOON = 98
+CLEAR = 5
+OVERCAST = 90
+CHICAGO_VIEWING_HOUR_UTC = 9  # 04:00 local solar time at -87.6°
+
+GEMINIDS = {
+    'name': 'Geminids',
+    'type': 'meteor_shower',
+    'peak_date': '2026-12-14',
+    'zhr': 150,
+    'radiant_declination': 32,
+}
+URSIDS = {**GEMINIDS, 'name': 'Ursids', 'radiant_declination': 75}
+ECLIPSE = {
+    'name': 'Total solar eclipse',
+    'type': 'solar_eclipse',
+    'time': '2028-07-22T03:00:00Z',
+    'visible_from': 'Australia, New Zealand and Southeast Asia',
+    'regions': [[-90, 90, 20, 180]],
+}
+
+
+class TestAstroEvents:
+    """Test event timing and visibility assessment"""
+
+    def test_bundled_dataset_loads(self) -> None:
+        """Test every bundled event has a type and a time to compute from"""
+        events = load_astro_events()
+
+        assert events
+        for event in events:
+            assert event_time(event, CHICAGO_LON).tzinfo is not None
+
+    def test_missing_dataset_is_empty(self, tmp_path: Path) -> None:
+        """Test a missing file degrades to no events instead of failing startup"""
+        assert load_astro_events(tmp_path / 'missing.json') == []
+
+    def test_meteor_viewing_time_is_local_predawn(self) -> None:
+        """Test showers are viewed at 4am local solar time on the peak date"""
+        viewing = event_time(GEMINIDS, CHICAGO_LON)
+
+        assert viewing.date() == datetime(2026, 12, 14, tzinfo=timezone.utc).date()
+        assert viewing.hour == CHICAGO_VIEWING_HOUR_UTC
+
+    def test_dark_clear_meteor_shower(self) -> None:
+        """Test a dark, moonless, clear night rates good"""
+        result = assess_visibility(
+            GEMINIDS, CHICAGO_LAT, CHICAGO_LON, DARK_SKY, NEW_MOON, CLEAR
+        )
+
+        assert result['visible'] is True
+        assert result['rating'] == 'good'
+
+    def test_moonlight_and_clouds_lower_rating(self) -> None:
+        """Test the worst of moonlight and clouds decides the rating"""
+        bright = assess_visibility(
+            GEMINIDS, CHICAGO_LAT, CHICAGO_LON, DARK_SKY, FULL_MOON, CLEAR
+        )
+        cloudy = assess_visibility(
+            GEMINIDS, CHICAGO_LAT, CHICAGO_LON, DARK_SKY, NEW_MOON, OVERCAST
+        )
+        unknown = assess_visibility(
+            GEMINIDS, CHICAGO_LAT, CHICAGO_LON, DARK_SKY, NEW_MOON, None
+        )
+
+        assert bright['rating'] == 'poor'
+        assert cloudy['rating'] == 'poor'
+        assert unknown['rating'] == 'good'
+        assert 'Cloud forecast not available yet' in unknown['reasons']
+
+    def test_radiant_below_horizon(self) -> None:
+        """Test far-northern radiants never rise for southern observers"""
+        result = assess_visibility(
+            URSIDS, SYDNEY_LAT, SYDNEY_LON, DARK_SKY, NEW_MOON, CLEAR
+        )
+
+        assert result == {
+            'visible': False,
+            'rating': 'not_visible',
+            'reasons': ['Radiant never rises at this latitude'],
+        }
+
+    def test_solar_eclipse_regions(self) -> None:
+        """Test solar eclipses need the location in range and the sun up"""
+        away = assess_visibility(
+            ECLIPSE, CHICAGO_LAT, CHICAGO_LON, DAYLIGHT, FULL_MOON, CLEAR
+        )
+        night = assess_visibility(
+            ECLIPSE, SYDNEY_LAT, SYDNEY_LON, DARK_SKY, NEW_MOON, CLEAR
+        )
+        seen = assess_visibility(
+            ECLIPSE, SYDNEY_LAT, SYDNEY_LON, DAYLIGHT, NEW_MOON, CLEAR
+        )
+
+        assert away['rating'] == 'not_visible'
+        assert 'Australia' in away['reasons'][0]
+        assert night['reasons'] == ['Sun below the horizon']
+        assert seen['rating'] == 'good'
@@ -0,0 +1,185 @@
+"""ABOUTME: Test backing up and restoring the server's state files - SQLite snapshots,
+ABOUTME: the manifest's checksums, compression by name and refusing to clobber state"""
+
+import json
+import shutil
+import sqlite3
+import tarfile
+from pathlib import Path
+
+import pytest
+
+from backup import (
+    MANIFEST_NAME,
+    STATE_FILES,
+    archive_compression,
+    create_backup,
+    restore_backup,
+    state_paths,
+)
+
+
+# Test constants
+NOW = 1_706_710_000.0
+RULES = [{'id': 'r1', 'metric': 'rain_chance', 'threshold': 60}]
+
+
+def state(root: Path) -> dict[str, str]:
+    """State paths under root, with a favorites database and rules file in place"""
+    paths = {name: str(root / name) for name in STATE_FILES}
+    root.mkdir(parents=True, exist_ok=True)
+    db = sqlite3.connect(paths['favorites.db'])
+    with db:
+        db.execute('CREATE TABLE favorites (id TEXT, name TEXT)')
+        db.execute("INSERT INTO favorites VALUES ('f1', 'Home')")
+    db.close()
+    Path(paths['notification_rules.json']).write_text(json.dumps(RULES))
+    return paths
+
+
+def favorite_names(path: str) -> list[str]:
+    """The names in a favorites database"""
+    db = sqlite3.connect(path)
+    try:
+        return [row[0] for row in db.execute('SELECT name FROM favorites')]
+    finally:
+        db.close()
+
+
+class TestStatePaths:
+    """Test where state files are looked for"""
+
+    def test_settings_and_defaults(self) -> None:
+        """Test each file's setting wins over its default"""
+        paths = state_paths({'HISTORY_DB_PATH': '/srv/weather/history.db'})
+
+        assert paths['history.db'] == '/srv/weather/history.db'
+        assert paths['favorites.db'] == 'data/favorites.db'
+        assert set(paths) == set(STATE_FILES)
+
+    def test_compression_by_name(self) -> None:
+        """Test the archive suffix picks the compression"""
+        assert archive_compression('state.tar.gz') == 'gz'
+        assert archive_compression('state.tar') == ''
+        assert archive_compression('/backups/state.tar.xz') == 'xz'
+        with pytest.raises(ValueError, match='must be named'):
+            archive_compression('state.zip')
+
+
+class TestBackupAndRestore:
+    """Test archives round-trip and are checked before anything is replaced"""
+
+    def test_round_trip(self, tmp_path: Path) -> None:
+        """Test existing files are archived and restored where the settings say"""
+        paths = state(tmp_path / 'live')
+        archive = str(tmp_path / 'backups' / 'state.tar.gz')
+
+        manifest = create_backup(archive, paths, NOW)
+
+        assert [entry['name'] for entry in manifest['files']] == [
+            'favorites.db',
+            'notification_rules.json',
+        ]
+        assert manifest['created_at'] == NOW
+        with tarfile.open(archive) as contents:
+            assert MANIFEST_NAME in contents.getnames()
+
+        target = {name: str(tmp_path / 'restored' / name) for name in STATE_FILES}
+        restored = restore_backup(archive, target)
+
+        assert restored == ['favorites.db', 'notification_rules.json']
+        assert favorite_names(target['favorites.db']) == ['Home']
+        assert json.loads(Path(target['notification_rules.json']).read_text()) == RULES
+        assert not Path(target['history.db']).exists()
+
+    def test_zstd_round_trip(self, tmp_path: Path) -> None:
+        """Test .tar.zst goes through the zstd command when it's installed"""
+        if shutil.which('zstd') is None:
+            with pytest.raises(ValueError, match='need the zstd command'):
+                archive_compression('state.tar.zst')
+            return
+        paths = state(tmp_path / 'live')
+        archive = str(tmp_path / 'state.tar.zst')
+
+        create_backup(archive, paths, NOW)
+        Path(paths['favorites.db']).unlink()
+
+        assert 'favorites.db' in restore_backup(archive, paths, force=True)
+        assert favorite_names(paths['favorites.db']) == ['Home']
+
+    def test_refuses_to_overwrite_without_force(self, tmp_path: Path) -> None:
+        """Test existing state is kept unless restoring with force"""
+        paths = state(tmp_path / 'live')
+        archive = str(tmp_path / 'state.tar')
+        create_backup(archive, paths, NOW)
+        Path(paths['notification_rules.json']).write_text('[]')
+
+        with pytest.raises(FileExistsError, match='--force'):
+            restore_backup(archive, paths)
+        assert Path(paths['notification_rules.json']).read_text() == '[]'
+
+        restore_backup(archive, paths, force=True)
+        assert json.loads(Path(paths['notification_rules.json']).read_text()) == RULES
+
+    def test_corrupt_member_refused(self, tmp_path: Path) -> None:
+        """Test a file that doesn't match its checksum stops the whole restore"""
+        paths = state(tmp_path / 'live')
+        archive = tmp_path / 'state.tar'
+        create_backup(str(archive), paths, NOW)
+        tampered = tmp_path / 'tampered.tar'
+        rules = tmp_path / 'rules.json'
+        rules.write_text('[]')
+        with tarfile.open(archive) as original, tarfile.open(tampered, 'w') as out:
+            for member in original.getmembers():
+                if member.name == 'notification_rules.json':
+                    out.add(rules, arcname=member.name)
+                else:
+                    out.addfile(member, original.extractfile(member))
+        target = {name: str(tmp_path / 'restored' / name) for name in STATE_FILES}
+
+        with pytest.raises(ValueError, match='notification_rules.json .* corrupt'):
+            restore_backup(str(tampered), target)
+        assert not Path(target['favorites.db']).exists()
+
+    def test_member_missing_from_archive(self, tmp_path: Path) -> None:
+        """Test a manifest naming a file the archive lacks is refused, not a crash"""
+        paths = state(tmp_path / 'live')
+        archive = tmp_path / 'state.tar'
+        create_backup(str(archive), paths, NOW)
+        partial = tmp_path / 'partial.tar'
+        with tarfile.open(archive) as original, tarfile.open(partial, 'w') as out:
+            for member in original.getmembers():
+                if member.name != 'favorites.db':
+                    out.addfile(member, original.extractfile(member))
+
+        with pytest.raises(ValueError, match='no usable favorites.db'):
+            restore_backup(str(partial), state_paths({}))
+
+    def test_truncated_archive(self, tmp_path: Path) -> None:
+        """Test an archive cut short (a full disk, an interrupted copy) is refused"""
+        paths = state(tmp_path / 'live')
+        archive = tmp_path / 'state.tar.gz'
+        create_backup(str(archive), paths, NOW)
+        data = archive.read_bytes()
+        archive.write_bytes(data[: len(data) // 2])
+        target = {name: str(tmp_path / 'restored' / name) for name in STATE_FILES}
+
+        with pytest.raises(ValueError, match='truncated or damaged'):
+            restore_backup(str(archive), target)
+        assert not Path(target['favorites.db']).exists()
+
+    def test_not_a_backup(self, tmp_path: Path) -> None:
+        """Test archives without our manifest, or not archives at all, are refused"""
+        other = tmp_path / 'other.tar'
+        notes = tmp_path / 'notes.txt'
+        notes.write_text('hello')
+        with tarfile.open(other, 'w') as out:
+            out.add(notes, arcname='notes.txt')
+        garbage = tmp_path / 'garbage.tar.gz'
+        garbage.write_bytes(b'not a tarball')
+        paths = state_paths({})
+
+        with pytest.raises(ValueError, match='no manifest.json'):
+            restore_backup(str(other), paths)
+        with pytest.raises(ValueError, match='is not a backup archive'):
+            restore_backup(str(garbage), paths)
@@ -0,0 +1,73 @@
+"""ABOUTME: Test the chart-ready hourly series, axes and "now" marker
+ABOUTME: Works on plain hourly entries so no provider or app is needed"""
+
+from datetime import datetime, timezone
+
+from charts import CHART_SERIES, accumulate, build_charts, now_index
+from weather_core.units import UNIT_LABELS
+
+
+# Test constants
+HOURLY = [
+    {
+        'temp': 50,
+        'feels_like': 46,
+        'rain': 10,
+        'precipitation': 0.1,
+        't': '2pm',
+        'time': '2024-01-15T14:00:00Z',
+    },
+    {
+        'temp': 53,
+        'feels_like': 49,
+        'rain': 60,
+        'precipitation': 0.25,
+        't': '3pm',
+        'time': '2024-01-15T15:00:00Z',
+    },
+    {'temp': 55, 'rain': 80, 't': '4pm', 'time': '2024-01-15T16:00:00Z'},
+]
+NOW = datetime(2024, 1, 15, 15, 30, tzinfo=timezone.utc)
+
+
+class TestCharts:
+    """Test series shaped for Chart.js and uPlot"""
+
+    def test_series_and_axes(self) -> None:
+        """Test feels-like falls back to temperature and rain accumulates"""
+        charts = build_charts(HOURLY, UNIT_LABELS['imperial'], NOW)
+
+        assert charts['labels'] == ['2pm', '3pm', '4pm']
+        assert charts['series']['feels_like'] == [46, 49, 55]
+        assert charts['series']['accumulated_precipitation'] == [0.1, 0.35, 0.35]
+        assert charts['axes']['temperature'] == {'min': 44, 'max': 57, 'unit': '°F'}
+        assert charts['axes']['precipitation'] == {'min': 0, 'max': 0.35, 'unit': 'in'}
+        assert charts['now_index'] == 1
+
+    def test_library_shapes(self) -> None:
+        """Test Chart.js datasets and uPlot columns carry the same series"""
+        charts = build_charts(HOURLY, UNIT_LABELS['metric'], NOW)
+
+        datasets = charts['chartjs']['datasets']
+        assert [dataset['yAxisID'] for dataset in datasets] == [
+            axis for _key, _label, axis in CHART_SERIES
+        ]
+        assert charts['uplot']['data'][0] == charts['timestamps']
+        assert charts['uplot']['data'][1] == [50, 53, 55]
+        assert len(charts['uplot']['series']) == len(CHART_SERIES) + 1
+
+    def test_dry_and_empty_forecasts(self) -> None:
+        """Test a dry day keeps a visible rain axis and no hours has no marker"""
+        dry = [{'temp': 50, 'rain': 0, 'precipitation': 0, 'time': 'soon'}]
+        charts = build_charts(dry, UNIT_LABELS['metric'], NOW)
+
+        assert charts['axes']['precipitation']['max'] == 2.5  # noqa: PLR2004
+        assert charts['now_index'] is None
+
+        empty = build_charts([], UNIT_LABELS['imperial'], NOW)
+        assert empty['axes']['temperature']['min'] is None
+
+    def test_helpers(self) -> None:
+        """Test running totals skip gaps and a future-only forecast marks hour 0"""
+        assert accumulate([0.5, None, 0.25]) == [0.5, 0.5, 0.75]
+        assert now_index([2_000_000_000, None], NOW) == 0
@@ -0,0 +1,107 @@
+"""ABOUTME: Test the city registry: validation, aliases and the JSON config file
+ABOUTME: Uses temporary files so the real data/cities.json is never touched"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+from cities import DEFAULT_CITIES, CityRegistry, edit_distance
+
+
+# Test constants
+AUSTIN_LAT = 30.2672
+AUSTIN_LON = -97.7431
+
+
+class TestCityRegistry:
+    """Test loading, editing and saving the city table"""
+
+    def test_defaults_without_file(self, tmp_path: Path) -> None:
+        """Test the built-in table is used when there's no config file"""
+        registry = CityRegistry(str(tmp_path / 'cities.json'), DEFAULT_CITIES)
+
+        assert registry.coords['chicago'] == (41.8781, -87.6298, 'Chicago')
+        assert registry.resolve('New York') == 'nyc'
+        assert registry.resolve('Muenchen') == 'munich'
+
+    def test_file_replaces_defaults(self, tmp_path: Path) -> None:
+        """Test a config file is the whole table and aliases are normalized"""
+        path = tmp_path / 'cities.json'
+        path.write_text(
+            json.dumps(
+                {
+                    'atx': {
+                        'lat': AUSTIN_LAT,
+                        'lon': AUSTIN_LON,
+                        'name': 'Austin',
+                        'aliases': ['Austin TX'],
+                    }
+                }
+            )
+        )
+        registry = CityRegistry(str(path), DEFAULT_CITIES)
+
+        assert list(registry.coords) == ['atx']
+        assert registry.resolve('austin_tx') == 'atx'
+        assert registry.resolve('chicago') is None
+
+    def test_invalid_file_keeps_defaults(self, tmp_path: Path) -> None:
+        """Test a broken config file is reported and the defaults stay"""
+        path = tmp_path / 'cities.json'
+        path.write_text(json.dumps({'atx': {'lat': 100, 'lon': 0}}))
+        registry = CityRegistry(str(path), DEFAULT_CITIES)
+
+        assert 'chicago' in registry.coords
+        assert 'atx' not in registry.coords
+
+    def test_add_and_remove_persist(self, tmp_path: Path) -> None:
+        """Test runtime edits are saved and replacing a city drops old aliases"""
+        path = tmp_path / 'cities.json'
+        registry = CityRegistry(str(path), DEFAULT_CITIES)
+
+        registry.add('ATX', {'lat': AUSTIN_LAT, 'lon': AUSTIN_LON, 'aliases': ['a']})
+        registry.add('atx', {'lat': AUSTIN_LAT, 'lon': AUSTIN_LON, 'aliases': ['b']})
+        assert registry.coords['atx'] == (AUSTIN_LAT, AUSTIN_LON, 'Atx')
+        assert registry.resolve('a') is None
+        assert registry.resolve('b') == 'atx'
+
+        assert registry.remove('nyc')
+        assert not registry.remove('nyc')
+        assert registry.resolve('new-york') is None
+
+        reloaded = CityRegistry(str(path), DEFAULT_CITIES)
+        assert reloaded.coords == registry.coords
+        assert reloaded.aliases == registry.aliases
+
+    def test_alias_conflicts_rejected(self, tmp_path: Path) -> None:
+        """Test a city can't claim another city's key or aliases"""
+        registry = CityRegistry(str(tmp_path / 'cities.json'), DEFAULT_CITIES)
+
+        with pytest.raises(ValueError, match='already used'):
+            registry.add('ny', {'lat': 40.7, 'lon': -74.0, 'aliases': ['new-york']})
+        with pytest.raises(ValueError, match='already an alias'):
+            registry.add('roma', {'lat': 41.9, 'lon': 12.5})
+        with pytest.raises(ValueError, match='cannot contain'):
+            registry.add('41.8,-87.6', {'lat': 41.8, 'lon': -87.6})
+        assert not (tmp_path / 'cities.json').exists()
+
+    def test_edit_distance(self) -> None:
+        """Test Levenshtein distance counts each kind of edit"""
+        assert edit_distance('chicago', 'chicago') == 0
+        assert edit_distance('chicgo', 'chicago') == 1  # Insertion
+        assert edit_distance('madird', 'madrid') == 2  # Transposition
+        assert edit_distance('', 'nyc') == len('nyc')
+
+    def test_suggest(self, tmp_path: Path) -> None:
+        """Test typos, prefixes, aliases and display names all suggest a key"""
+        registry = CityRegistry(str(tmp_path / 'cities.json'), DEFAULT_CITIES)
+
+        assert registry.suggest('Chicgo') == [('chicago', 1)]
+        assert registry.suggest('syd') == [('sydney', 0)]
+        assert registry.suggest('San Fran') == [('sf', 0)]
+        assert registry.suggest('muenchn') == [('munich', 1)]
+        assert registry.suggest('atlantis') == []
+
+        registry.add('sydney-mines', {'lat': 46.2367, 'lon': -60.2177})
+        assert registry.suggest('Sydney M') == [('sydney-mines', 0), ('sydney', 2)]
@@ -0,0 +1,223 @@
+"""ABOUTME: Test the command line - serving with flags, checking config, fetching and
+ABOUTME: backups. Environments are plain dicts and providers are patched"""
+
+import json
+from pathlib import Path
+from unittest.mock import MagicMock, patch
+
+import pytest
+
+from backup import STATE_FILES
+from cli import EXIT_FAILED, EXIT_OK, EXIT_USAGE, main
+
+
+# Test constants
+CHICAGO = ['--lat', '41.8781', '--lon', '-87.6298']
+MOCK_TEMP = 72
+
+
+def write_toml(tmp_path: Path, text: str) -> str:
+    """A weather.toml with the given contents"""
+    path = tmp_path / 'weather.toml'
+    path.write_text(text)
+    return str(path)
+
+
+def environment(tmp_path: Path) -> dict[str, str]:
+    """An environment with no providers file, so nothing on disk leaks in"""
+    return {'PROVIDERS_CONFIG_PATH': str(tmp_path / 'absent.json')}
+
+
+class TestServe:
+    """Test the serve subcommand's layering"""
+
+    @patch('main.run_server')
+    def test_flags_win(self, mock_run: MagicMock, tmp_path: Path) -> None:
+        """Test --port beats the environment, which beats the file"""
+        path = write_toml(tmp_path, '[server]\nport = 7000\nhost = "0.0.0.0"\n')
+        environ = {'HOST': '127.0.0.2'}
+
+        code = main(['serve', '--config', path, '--port', '8080'], environ)
+
+        assert code == EXIT_OK
+        assert environ['PORT'] == '8080'
+        assert environ['HOST'] == '127.0.0.2'
+        assert environ['WEATHER_CONFIG'] == path
+        mock_run.assert_called_once()
+
+    @patch('main.run_server')
+    def test_serve_is_default(self, mock_run: MagicMock) -> None:
+        """Test bare flags without a subcommand serve"""
+        environ: dict[str, str] = {}
+
+        assert main(['--port', '9000'], environ) == EXIT_OK
+        assert environ['PORT'] == '9000'
+        mock_run.assert_called_once()
+
+    @patch('main.run_server')
+    def test_unreadable_certificate_refused(
+        self, mock_run: MagicMock, tmp_path: Path
+    ) -> None:
+        """Test --tls-cert and --tls-key are checked before the server starts"""
+        environ: dict[str, str] = {}
+
+        code = main(
+            [
+                'serve',
+                '--tls-cert',
+                str(tmp_path / 'cert.pem'),
+                '--tls-key',
+                str(tmp_path / 'key.pem'),
+            ],
+            environ,
+        )
+
+        assert code == EXIT_FAILED
+        assert environ['TLS_CERT_FILE'] == str(tmp_path / 'cert.pem')
+        mock_run.assert_not_called()
+
+    @patch('main.run_server')
+    def test_missing_config_refused(self, mock_run: MagicMock, tmp_path: Path) -> None:
+        """Test an explicit --config that doesn't exist stops startup"""
+        code = main(['serve', '--config', str(tmp_path / 'nope.toml')], {})
+
+        assert code == EXIT_FAILED
+        mock_run.assert_not_called()
+
+
+class TestCheckConfig:
+    """Test validation without starting the server"""
+
+    def test_valid_config(
+        self, tmp_path: Path, capsys: pytest.CaptureFixture[str]
+    ) -> None:
+        """Test a good file passes and lists the provider chain"""
+        path = write_toml(
+            tmp_path,
+            '[[providers]]\ntype = "OpenMeteo"\n\n'
+            '[cities.austin]\nlat = 30.27\nlon = -97.74\nname = "Austin"\n',
+        )
+
+        code = main(['check-config', '--config', path], environment(tmp_path))
+
+        assert code == EXIT_OK
+        output = capsys.readouterr().out
+        assert 'Providers: OpenMeteo' in output
+        assert 'Cities: 1' in output
+
+    def test_every_problem_reported(
+        self, tmp_path: Path, capsys: pytest.CaptureFixture[str]
+    ) -> None:
+        """Test bad numbers, schedules, providers and cities are all listed"""
+        path = write_toml(
+            tmp_path,
+            '[server]\nport = "http"\ntrusted_proxies = ["10.0.0.0/33"]\n\n'
+            '[history]\ncompact_cron = "nightly"\n\n'
+            '[[providers]]\ntype = "PirateWeather"\n\n'
+            '[[providers]]\ntype = "OpenMeteo"\nquota = "5/hour"\n\n'
+            '[cities.nowhere]\nlat = 200\nlon = 0\nname = "Nowhere"\n',
+        )
+
+        code = main(['check-config', '--config', path], environment(tmp_path))
+
+        assert code == EXIT_FAILED
+        output = capsys.readouterr().out
+        assert 'PORT must be a whole number' in output
+        assert 'HISTORY_COMPACT_CRON' in output
+        assert 'TRUSTED_PROXIES' in output
+        assert 'Provider PirateWeather' in output
+        assert 'Provider OpenMeteo' in output
+        assert "City 'nowhere'" in output
+
+    def test_invalid_toml(
+        self, tmp_path: Path, capsys: pytest.CaptureFixture[str]
+    ) -> None:
+        """Test a file that isn't TOML fails straight away"""
+        path = write_toml(tmp_path, 'port = ')
+
+        assert main(['check-config', '--config', path], {}) == EXIT_FAILED
+        assert 'not valid TOML' in capsys.readouterr().out
+
+
+class TestFetch:
+    """Test printing one forecast as JSON"""
+
+    @patch('weather_core.providers.OpenMeteoProvider.get_weather')
+    def test_prints_json(
+        self,
+        mock_weather: MagicMock,
+        tmp_path: Path,
+        capsys: pytest.CaptureFixture[str],
+    ) -> None:
+        """Test only the forecast goes to stdout, logs going to stderr"""
+        mock_weather.return_value = {'current': {'temperature': MOCK_TEMP}}
+
+        code = main(
+            ['fetch', *CHICAGO, '--provider', 'OpenMeteo'], environment(tmp_path)
+        )
+
+        assert code == EXIT_OK
+        captured = capsys.readouterr()
+        assert json.loads(captured.out)['current']['temperature'] == MOCK_TEMP
+        assert 'OpenMeteo' in captured.err
+        mock_weather.assert_called_once_with(41.8781, -87.6298, None, None)
+
+    def test_unknown_provider(
+        self, tmp_path: Path, capsys: pytest.CaptureFixture[str]
+    ) -> None:
+        """Test asking for a provider that isn't configured is a usage error"""
+        code = main(['fetch', *CHICAGO, '--provider', 'Nope'], environment(tmp_path))
+
+        assert code == EXIT_USAGE
+        captured = capsys.readouterr()
+        assert captured.out == ''
+        assert 'have: OpenMeteo' in captured.err
+
+    @patch('weather_core.providers.OpenMeteoProvider.get_weather', return_value=None)
+    def test_no_data_fails(self, mock_weather: MagicMock, tmp_path: Path) -> None:
+        """Test a failed fetch exits non-zero for cron to notice"""
+        assert main(['fetch', *CHICAGO], environment(tmp_path)) == EXIT_FAILED
+        mock_weather.assert_called_once()
+
+    def test_coordinates_validated(self) -> None:
+        """Test out-of-range coordinates are rejected by the parser"""
+        with pytest.raises(SystemExit):
+            main(['fetch', '--lat', '91', '--lon', '0'], {})
+
+
+class TestBackup:
+    """Test the backup and restore subcommands"""
+
+    def test_backup_and_restore(
+        self, tmp_path: Path, capsys: pytest.CaptureFixture[str]
+    ) -> None:
+        """Test state goes out to an archive and back where the settings say"""
+        rules = tmp_path / 'rules.json'
+        rules.write_text('[{"id": "r1"}]')
+        environ = {
+            **environment(tmp_path),
+            **{
+                setting: str(tmp_path / name)
+                for name, (setting, _, _) in STATE_FILES.items()
+            },
+            'NOTIFICATION_RULES_PATH': str(rules),
+        }
+        archive = str(tmp_path / 'state.tar.gz')
+
+        assert main(['backup', '--out', archive], dict(environ)) == EXIT_OK
+        assert 'Backed up notification_rules.json' in capsys.readouterr().out
+
+        rules.write_text('[]')
+        assert main(['restore', archive], dict(environ)) == EXIT_USAGE
+        assert '--force' in capsys.readouterr().err
+        assert main(['restore', archive, '--force'], dict(environ)) == EXIT_OK
+        assert json.loads(rules.read_text()) == [{'id': 'r1'}]
+
+    def test_bad_archive_name(
+        self, tmp_path: Path, capsys: pytest.CaptureFixture[str]
+    ) -> None:
+        """Test an archive name without a known suffix fails"""
+        code = main(['backup', '--out', str(tmp_path / 'state.zip')], {})
+
+        assert code == EXIT_FAILED
+        assert 'must be named' in capsys.readouterr().err
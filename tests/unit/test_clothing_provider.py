@@ -2,7 +2,7 @@ from typing import Any
 
 import pytest
 
-from weather_providers import ClothingRecommendationProvider
+from weather_core.providers import ClothingRecommendationProvider
 
 
 # Test constants
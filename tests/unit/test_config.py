@@ -0,0 +1,323 @@
+"""ABOUTME: Test startup configuration - weather.toml, the provider JSON file and env
+ABOUTME: Files are written to a temp directory; environments are plain dicts"""
+
+import json
+from pathlib import Path
+from typing import Any
+
+import pytest
+
+from config import (
+    ConfigFile,
+    flatten_settings,
+    load_config_file,
+    load_tls_context,
+    reload_config_file,
+)
+from weather_core.settings import (
+    ConfigError,
+    ProviderSettings,
+    default_provider_settings,
+    load_provider_settings,
+)
+
+
+# Test constants
+PIRATE_KEY = 'pirate-key'
+FAST_TIMEOUT = 4
+SAMPLE_TOML = """
+prefetch_locations = ["41.88,-87.63,Chicago", "30.27,-97.74,Austin"]
+
+[server]
+host = "127.0.0.1"
+port = 8080
+
+[cache]
+maxsize = 250
+
+[mqtt]
+host = "broker.local"
+retain = true
+
+[[providers]]
+type = "OpenMeteo"
+timeout = 4
+
+[cities.austin]
+lat = 30.27
+lon = -97.74
+name = "Austin"
+"""
+
+
+def write_config(tmp_path: Path, providers: Any) -> str:
+    """A providers.json holding the given list"""
+    path = tmp_path / 'providers.json'
+    path.write_text(json.dumps({'providers': providers}))
+    return str(path)
+
+
+def write_toml(tmp_path: Path, text: str = SAMPLE_TOML) -> str:
+    """A weather.toml with the given contents"""
+    path = tmp_path / 'weather.toml'
+    path.write_text(text)
+    return str(path)
+
+
+class TestConfigFile:
+    """Test reading weather.toml and layering it under the environment"""
+
+    def test_tables_become_variables(self, tmp_path: Path) -> None:
+        """Test [server] keys are unprefixed and other tables prefix their keys"""
+        config = load_config_file(write_toml(tmp_path))
+
+        assert config.settings == {
+            'PREFETCH_LOCATIONS': '41.88,-87.63,Chicago;30.27,-97.74,Austin',
+            'HOST': '127.0.0.1',
+            'PORT': '8080',
+            'CACHE_MAXSIZE': '250',
+            'MQTT_HOST': 'broker.local',
+            'MQTT_RETAIN': '1',
+        }
+
+    def test_structured_sections_kept(self, tmp_path: Path) -> None:
+        """Test providers and cities are kept as data, not variables"""
+        config = load_config_file(write_toml(tmp_path))
+
+        assert config.providers == [{'type': 'OpenMeteo', 'timeout': FAST_TIMEOUT}]
+        assert config.cities is not None
+        assert config.cities['austin']['name'] == 'Austin'
+
+    def test_lists_and_tables_as_values(self) -> None:
+        """Test plain lists are comma-joined and nested data becomes JSON"""
+        settings = flatten_settings(
+            {'blend_providers': ['OpenMeteo', 'PirateWeather'], 'x': [{'a': 1}]}
+        )
+
+        assert settings['BLEND_PROVIDERS'] == 'OpenMeteo,PirateWeather'
+        assert json.loads(settings['X']) == [{'a': 1}]
+
+    def test_environment_wins(self, tmp_path: Path) -> None:
+        """Test apply() only fills in variables the environment leaves unset"""
+        config = load_config_file(write_toml(tmp_path))
+        environ = {'PORT': '5001'}
+
+        applied = config.apply(environ)
+
+        assert environ['PORT'] == '5001'
+        assert environ['HOST'] == '127.0.0.1'
+        assert 'PORT' not in applied
+
+    def test_missing_file_is_empty(self, tmp_path: Path) -> None:
+        """Test no file means no settings, providers or cities"""
+        config = load_config_file(str(tmp_path / 'absent.toml'))
+
+        assert config.settings == {}
+        assert config.providers is None
+        assert config.cities is None
+
+    def test_invalid_files_rejected(self, tmp_path: Path) -> None:
+        """Test broken TOML and misshapen sections raise ConfigError"""
+        for text in ('port = ', 'providers = "OpenMeteo"', 'cities = [1, 2]'):
+            with pytest.raises(ConfigError):
+                load_config_file(write_toml(tmp_path, text))
+
+    def test_reload_replaces_file_settings(self, tmp_path: Path) -> None:
+        """Test a reload swaps the file's variables but keeps overridden ones"""
+        path = write_toml(tmp_path, 'port = 7000\ncache_ttl = 60\nicon_set = "emoji"\n')
+        config = load_config_file(path)
+        environ: dict[str, str] = {}
+        config.apply(environ)
+        environ['PORT'] = '8080'  # Set by the command line after startup
+
+        write_toml(tmp_path, 'port = 7001\ncache_ttl = 90\n')
+        reloaded = reload_config_file(config, path, environ)
+
+        assert environ == {'PORT': '8080', 'CACHE_TTL': '90'}
+        assert reloaded.applied == ['CACHE_TTL']
+
+    def test_failed_reload_changes_nothing(self, tmp_path: Path) -> None:
+        """Test a broken file on reload leaves the environment as it was"""
+        path = write_toml(tmp_path, 'cache_ttl = 60\n')
+        config = load_config_file(path)
+        environ: dict[str, str] = {}
+        config.apply(environ)
+
+        write_toml(tmp_path, 'cache_ttl = ')
+        with pytest.raises(ConfigError):
+            reload_config_file(config, path, environ)
+
+        assert environ == {'CACHE_TTL': '60'}
+
+    def test_providers_feed_provider_settings(self, tmp_path: Path) -> None:
+        """Test [[providers]] entries are used when there's no JSON file"""
+        config = ConfigFile(data={'providers': [{'type': 'OpenMeteo'}]})
+
+        settings = load_provider_settings(
+            str(tmp_path / 'absent.json'), {}, config.providers
+        )
+
+        assert [entry.name for entry in settings] == ['OpenMeteo']
+
+    def test_json_file_wins_over_entries(self, tmp_path: Path) -> None:
+        """Test the providers JSON file takes precedence over [[providers]]"""
+        path = write_config(tmp_path, [{'type': 'OpenMeteo', 'name': 'FromJson'}])
+
+        settings = load_provider_settings(path, {}, [{'type': 'PirateWeather'}])
+
+        assert [entry.name for entry in settings] == ['FromJson']
+
+
+class TestTlsContext:
+    """Test the HTTPS settings"""
+
+    def test_off_without_files(self) -> None:
+        """Test no certificate and key means plain HTTP"""
+        assert load_tls_context({}) is None
+
+    def test_both_files_required(self) -> None:
+        """Test a certificate without a key (or the reverse) is an error"""
+        for environ in ({'TLS_CERT_FILE': 'cert.pem'}, {'TLS_KEY_FILE': 'key.pem'}):
+            with pytest.raises(ConfigError, match='together'):
+                load_tls_context(environ)
+
+    def test_missing_files_rejected(self, tmp_path: Path) -> None:
+        """Test paths that don't exist fail instead of serving plain HTTP"""
+        environ = {
+            'TLS_CERT_FILE': str(tmp_path / 'cert.pem'),
+            'TLS_KEY_FILE': str(tmp_path / 'key.pem'),
+        }
+
+        with pytest.raises(OSError):
+            load_tls_context(environ)
+
+
+class TestDefaultProviderSettings:
+    """Test the provider list derived from environment variables"""
+
+    def test_open_meteo_alone_without_key(self) -> None:
+        """Test no key (or the placeholder) means Open-Meteo only"""
+        for environ in ({}, {'PIRATE_WEATHER_API_KEY': 'YOUR_API_KEY_HERE'}):
+            settings = default_provider_settings(environ)
+
+            assert [entry.name for entry in settings] == ['OpenMeteo']
+
+    def test_hybrid_first_with_key(self) -> None:
+        """Test a key puts the hybrid first with both halves behind it"""
+        settings = default_provider_settings(
+            {'PIRATE_WEATHER_API_KEY': PIRATE_KEY, 'REMOTE_DASHBOARD_URL': 'http://up'}
+        )
+
+        assert [entry.name for entry in settings] == [
+            'RemoteDashboard',
+            'Hybrid',
+            'OpenMeteo',
+            'PirateWeather',
+        ]
+        assert settings[0].options == {'url': 'http://up'}
+        assert settings[3].api_key == PIRATE_KEY
+
+    def test_blend_and_quotas(self) -> None:
+        """Test BLEND_PROVIDERS adds a blend and quotas attach by name"""
+        settings = default_provider_settings(
+            {
+                'BLEND_PROVIDERS': 'OpenMeteo, PirateWeather',
+                'BLEND_METHOD': 'Mean',
+                'PROVIDER_QUOTAS': 'OpenMeteo=600/minute;Unknown=1/day',
+            }
+        )
+
+        blend = settings[-1]
+        assert blend.kind == 'Blend'
+        assert blend.options == {
+            'members': ['OpenMeteo', 'PirateWeather'],
+            'method': 'mean',
+        }
+        assert settings[0].quota == '600/minute'
+
+    def test_mock_weather_replaces_the_rest(self) -> None:
+        """Test MOCK_WEATHER=1 means simulated weather only, with its seed"""
+        settings = default_provider_settings(
+            {
+                'MOCK_WEATHER': '1',
+                'MOCK_WEATHER_SEED': '42',
+                'PIRATE_WEATHER_API_KEY': PIRATE_KEY,
+            }
+        )
+
+        assert [(entry.kind, entry.options) for entry in settings] == [
+            ('Mock', {'seed': 42})
+        ]
+
+
+class TestLoadProviderSettings:
+    """Test reading the provider list from a JSON file"""
+
+    def test_missing_file_uses_environment(self, tmp_path: Path) -> None:
+        """Test the environment decides when there's no file"""
+        settings = load_provider_settings(str(tmp_path / 'absent.json'), {})
+
+        assert [entry.name for entry in settings] == ['OpenMeteo']
+
+    def test_entries_in_order(self, tmp_path: Path) -> None:
+        """Test entries keep their order, options and tuning"""
+        path = write_config(
+            tmp_path,
+            [
+                {
+                    'type': 'PirateWeather',
+                    'api_key_env': 'PW_KEY',
+                    'timeout': FAST_TIMEOUT,
+                },
+                {'type': 'OpenMeteo', 'name': 'Backup', 'enabled': False},
+            ],
+        )
+
+        settings = load_provider_settings(path, {'PW_KEY': PIRATE_KEY})
+
+        assert [entry.name for entry in settings] == ['PirateWeather', 'Backup']
+        assert settings[0].api_key == PIRATE_KEY
+        assert settings[0].timeout == FAST_TIMEOUT
+        assert settings[1].kind == 'OpenMeteo'
+        assert settings[1].enabled is False
+
+    def test_duplicate_names_rejected(self, tmp_path: Path) -> None:
+        """Test two entries can't share a name"""
+        path = write_config(tmp_path, [{'type': 'OpenMeteo'}, {'type': 'OpenMeteo'}])
+
+        with pytest.raises(ConfigError, match='unique'):
+            load_provider_settings(path, {})
+
+    def test_missing_list_rejected(self, tmp_path: Path) -> None:
+        """Test a file without a providers list is an error"""
+        path = tmp_path / 'providers.json'
+        path.write_text('{"primary": "OpenMeteo"}')
+
+        with pytest.raises(ConfigError, match='providers'):
+            load_provider_settings(str(path), {})
+
+
+class TestProviderSettings:
+    """Test validation of single entries"""
+
+    def test_invalid_entries(self) -> None:
+        """Test malformed entries are rejected with a ConfigError"""
+        for entry in (
+            {'name': 'NoType'},
+            {'type': 'OpenMeteo', 'timeout': 0},
+            {'type': 'OpenMeteo', 'timeout': True},
+            {'type': 'OpenMeteo', 'enabled': 'yes'},
+            {'type': 'OpenMeteo', 'options': []},
+            {'type': 'PirateWeather', 'api_key': 1234},
+        ):
+            with pytest.raises(ConfigError):
+                ProviderSettings.from_dict(entry, {})
+
+    def test_inline_key_backs_up_env(self) -> None:
+        """Test api_key is used when the api_key_env variable is unset"""
+        settings = ProviderSettings.from_dict(
+            {'type': 'PirateWeather', 'api_key': PIRATE_KEY, 'api_key_env': 'UNSET'},
+            {},
+        )
+
+        assert settings.api_key == PIRATE_KEY
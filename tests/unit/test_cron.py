@@ -0,0 +1,54 @@
+"""ABOUTME: Test the five-field cron expressions used to schedule digests
+ABOUTME: Covers field parsing, day-of-month/day-of-week rules and next-run search"""
+
+import zoneinfo
+from datetime import datetime
+
+import pytest
+
+from cron import CronSchedule, parse_cron_field
+
+
+# Test constants
+QUARTER_HOURS = {0, 15, 30, 45}
+WEEKDAYS = {1, 2, 3, 4, 5}
+CHICAGO = zoneinfo.ZoneInfo('America/Chicago')
+
+
+def at(month: int, day: int, hour: int, minute: int = 0) -> datetime:
+    """A 2026 wall-clock time in Chicago"""
+    return datetime(2026, month, day, hour, minute, tzinfo=CHICAGO)
+
+
+class TestCronSchedule:
+    """Test cron parsing and matching"""
+
+    def test_parse_fields(self) -> None:
+        """Test steps, ranges and lists expand to allowed values"""
+        assert parse_cron_field('*/15', 0, 59) == QUARTER_HOURS
+        assert parse_cron_field('1-5', 0, 7) == WEEKDAYS
+        assert parse_cron_field('0,30', 0, 59) == {0, 30}
+        with pytest.raises(ValueError, match='outside'):
+            parse_cron_field('61', 0, 59)
+
+    def test_invalid_expressions(self) -> None:
+        """Test malformed expressions are rejected with a clear message"""
+        with pytest.raises(ValueError, match='5 fields'):
+            CronSchedule('0 7 * *')
+        with pytest.raises(ValueError, match='Invalid cron expression'):
+            CronSchedule('0 25 * * *')
+
+    def test_next_weekday_morning(self) -> None:
+        """Test a weekday 6:30 schedule skips the weekend"""
+        schedule = CronSchedule('30 6 * * 1-5')
+
+        assert schedule.next_after(at(10, 16, 18)) == at(10, 19, 6, 30)
+        assert schedule.matches(at(10, 19, 6, 30))
+        assert not schedule.matches(at(10, 18, 6, 30))
+
+    def test_day_fields_are_ored(self) -> None:
+        """Test a restricted day-of-month and day-of-week match either, like cron"""
+        schedule = CronSchedule('0 8 1 * 0')  # The 1st, and every Sunday
+        assert schedule.next_after(at(10, 16, 9)) == at(10, 18, 8)
+        assert schedule.matches(at(11, 1, 8))
+        assert CronSchedule('0 8 * * 7').matches(at(10, 18, 8))
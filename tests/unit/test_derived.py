@@ -0,0 +1,90 @@
+"""ABOUTME: Test derived metrics - Beaufort force, compass sectors, precipitation timing
+ABOUTME: Keeps the backend in step with the wind compass widget's thresholds"""
+
+from datetime import datetime, timedelta, timezone
+
+from weather_core.derived import (
+    PrecipitationSlot,
+    beaufort_force,
+    compass_index,
+    precipitation_timing,
+)
+
+
+# Test constants
+NORTHWEST = 315
+NORTHWEST_INDEX = 14
+FRESH_BREEZE_MPH = 20
+FRESH_BREEZE_FORCE = 5
+HURRICANE_MPH = 80
+HURRICANE_FORCE = 12
+NOW = datetime(2024, 1, 1, 12, 5, tzinfo=timezone.utc)
+RAIN_STARTS_IN = 25
+RAIN_ENDS_IN = 55
+ALL_DAY_MINUTES = 24 * 60
+
+
+def quarter_hours(pattern: str) -> list[PrecipitationSlot]:
+    """Build 15-minute slots from 12:00 UTC, 'R' wet and '.' dry"""
+    start = datetime(2024, 1, 1, 12, tzinfo=timezone.utc)
+    step = timedelta(minutes=15)
+    return [
+        (start + index * step, start + (index + 1) * step, char == 'R')
+        for index, char in enumerate(pattern)
+    ]
+
+
+class TestDerivedWind:
+    """Test language-neutral wind metrics"""
+
+    def test_beaufort_force(self) -> None:
+        """Test speeds map to the same forces as the wind widget"""
+        assert beaufort_force(0) == 0
+        assert beaufort_force(0.5) == 0
+        assert beaufort_force(1) == 1
+        assert beaufort_force(5) == 2  # noqa: PLR2004
+        assert beaufort_force(FRESH_BREEZE_MPH) == FRESH_BREEZE_FORCE
+
+    def test_beaufort_tops_out_at_hurricane(self) -> None:
+        """Test speeds past the last threshold are force 12"""
+        assert beaufort_force(HURRICANE_MPH) == HURRICANE_FORCE
+
+    def test_compass_index(self) -> None:
+        """Test directions snap to the nearest of 16 compass points"""
+        assert compass_index(0) == 0
+        assert compass_index(355) == 0
+        assert compass_index(NORTHWEST) == NORTHWEST_INDEX
+        assert compass_index(-45) == NORTHWEST_INDEX
+
+
+class TestPrecipitationTiming:
+    """Test precipitation start/end estimates from forecast slots"""
+
+    def test_rain_coming(self) -> None:
+        """Test the next wet spell's start and end are minutes from now"""
+        starts_in, ends_in = precipitation_timing(quarter_hours('..RR....'), NOW)
+
+        assert starts_in == RAIN_STARTS_IN
+        assert ends_in == RAIN_ENDS_IN
+
+    def test_rain_ending(self) -> None:
+        """Test ongoing rain starts at 0 and ends at the first dry slot"""
+        starts_in, ends_in = precipitation_timing(quarter_hours('RRR.'), NOW)
+
+        assert starts_in == 0
+        assert ends_in == RAIN_ENDS_IN - 15  # noqa: PLR2004
+
+    def test_current_observation_counts_as_wet(self) -> None:
+        """Test observed precipitation overrides a dry forecast for now"""
+        starts_in, ends_in = precipitation_timing(
+            quarter_hours('.R..'), NOW, raining_now=True
+        )
+
+        assert starts_in == 0
+        assert ends_in == RAIN_STARTS_IN
+
+    def test_dry_or_unending(self) -> None:
+        """Test no wet slot gives no start, and rain past the horizon no end"""
+        assert precipitation_timing(quarter_hours('....'), NOW) == (None, None)
+        all_day = quarter_hours('R' * (ALL_DAY_MINUTES // 15 + 8))
+        assert precipitation_timing(all_day, NOW) == (0, None)
@@ -0,0 +1,122 @@
+"""ABOUTME: Test the e-ink PNG renderer - the PNG encoding, text fitting and the
+ABOUTME: forecast layout at landscape and portrait frame sizes"""
+
+import struct
+import zlib
+from typing import Any
+
+from eink import BLACK, WHITE, Canvas, fit_text, printable, render_forecast, wrap_text
+
+
+# Test constants
+PNG_SIGNATURE = b'\x89PNG\r\n\x1a\n'
+GRAYSCALE_DEPTH = 8
+ONE_BIT_DEPTH = 1
+LANDSCAPE = (800, 480)
+PORTRAIT = (600, 800)
+
+
+def png_header(image: bytes) -> tuple[int, int, int]:
+    """Width, height and bit depth from a PNG's IHDR chunk"""
+    width, height, depth = struct.unpack('>IIB', image[16:25])
+    return width, height, depth
+
+
+def png_rows(image: bytes) -> list[bytes]:
+    """Decoded rows of an 8-bit grayscale PNG (filter bytes dropped)"""
+    width, height, _ = png_header(image)
+    data = zlib.decompress(image[41 : image.index(b'IEND') - 8])
+    stride = width + 1
+    return [data[row * stride + 1 : (row + 1) * stride] for row in range(height)]
+
+
+def forecast() -> dict[str, Any]:
+    """Internal (abbreviated) weather document with three days"""
+    return {
+        'current': {'temperature': 72.4, 'icon': 'partly-cloudy-day'},
+        'daily': [
+            {'d': 'Mon', 'h': 77, 'l': 65, 'icon': 'thunderstorm'},
+            {'d': 'Tue', 'h': 75, 'l': 63, 'icon': 'rain'},
+            {'d': 'Wed', 'h': 70, 'l': 60, 'icon': 'snow'},
+        ],
+    }
+
+
+class TestCanvas:
+    """Test drawing and PNG encoding"""
+
+    def test_grayscale_png(self) -> None:
+        """Test an 8-bit PNG holds exactly the pixels drawn"""
+        canvas = Canvas(4, 3)
+        canvas.rect(1, 1, 2, 1)
+
+        image = canvas.png(grayscale=True)
+
+        assert image.startswith(PNG_SIGNATURE)
+        assert png_header(image) == (4, 3, GRAYSCALE_DEPTH)
+        assert png_rows(image)[1] == bytes([WHITE, BLACK, BLACK, WHITE])
+
+    def test_one_bit_png(self) -> None:
+        """Test 1-bit rows pack eight pixels a byte, padded with white"""
+        canvas = Canvas(10, 1)
+        canvas.rect(0, 0, 1, 1)
+        canvas.rect(9, 0, 1, 1, 200)
+
+        assert png_header(canvas.png(grayscale=False))[2] == ONE_BIT_DEPTH
+        assert canvas.packed_row(0) == bytes([0b01111111, 0b11111111])
+
+    def test_clipping(self) -> None:
+        """Test shapes past the edges are clipped rather than wrapping around"""
+        canvas = Canvas(4, 4)
+        canvas.rect(-2, 3, 10, 5)
+        canvas.circle(10, 10, 3)
+
+        assert canvas.pixels[:12] == bytearray([WHITE]) * 12
+        assert canvas.pixels[12:] == bytearray([BLACK]) * 4
+
+
+class TestText:
+    """Test the bitmap font's text handling"""
+
+    def test_printable(self) -> None:
+        """Test text is uppercased, accents stripped and unknown glyphs marked"""
+        assert printable('München 72°') == 'MUNCHEN 72°'
+        assert printable('東京') == '??'
+
+    def test_fit_and_wrap(self) -> None:
+        """Test long text is wrapped to the line limit and cut with an ellipsis"""
+        assert fit_text('Chicago', 1, 100) == 'CHICAGO'
+        assert fit_text('Chicago', 1, 30) == 'CH...'
+
+        lines = wrap_text('rain arriving around 4pm, high of 58.', 1, 78, 2)
+
+        assert lines == ['RAIN ARRIVING', 'AROUND 4PM...']
+
+
+class TestRenderForecast:
+    """Test the whole forecast frame"""
+
+    def test_frame_sizes(self) -> None:
+        """Test landscape and portrait frames come out at their requested sizes"""
+        for width, height in (LANDSCAPE, PORTRAIT):
+            image = render_forecast(forecast(), 'Chicago', 'Cloudy.', width, height)
+
+            assert png_header(image) == (width, height, GRAYSCALE_DEPTH)
+
+    def test_draws_forecast(self) -> None:
+        """Test both halves of the frame get ink, and grayscale=False is 1-bit"""
+        image = render_forecast(forecast(), 'Chicago', 'Cloudy.', *LANDSCAPE)
+        rows = png_rows(image)
+        half = len(rows) // 2
+
+        assert any(BLACK in row for row in rows[:half])
+        assert any(BLACK in row for row in rows[half:])
+        assert png_header(
+            render_forecast(forecast(), 'Chicago', '', *LANDSCAPE, grayscale=False)
+        )[2] == ONE_BIT_DEPTH
+
+    def test_missing_data(self) -> None:
+        """Test an empty document still renders a frame"""
+        image = render_forecast({}, 'Nowhere', '', *LANDSCAPE)
+
+        assert png_header(image) == (*LANDSCAPE, GRAYSCALE_DEPTH)
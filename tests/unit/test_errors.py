@@ -0,0 +1,62 @@
+"""ABOUTME: Test the typed API errors - codes, statuses, retryability and the JSON
+ABOUTME: body clients use to tell a bad request from failed upstreams"""
+
+from errors import (
+    Forbidden,
+    InvalidLocation,
+    ProviderFailed,
+    RateLimited,
+    RequestTimedOut,
+    Unauthorized,
+    UpstreamUnavailable,
+    WeatherError,
+)
+
+
+# Test constants
+HTTP_BAD_REQUEST = 400
+HTTP_UNAUTHORIZED = 401
+HTTP_FORBIDDEN = 403
+HTTP_BAD_GATEWAY = 502
+HTTP_SERVICE_UNAVAILABLE = 503
+
+
+class TestWeatherError:
+    """Test error bodies and how each kind is classified"""
+
+    def test_body(self) -> None:
+        """Test the body has the code, message, provider, retryability and details"""
+        error = ProviderFailed('Failed to simulate weather data', 'Mock', attempt=2)
+
+        assert error.status == HTTP_BAD_GATEWAY
+        assert error.to_dict() == {
+            'code': 'provider_failed',
+            'message': 'Failed to simulate weather data',
+            'provider': 'Mock',
+            'retryable': True,
+            'error': 'Failed to simulate weather data',
+            'attempt': 2,
+        }
+
+    def test_client_errors_not_retryable(self) -> None:
+        """Test a bad request stays bad, while upstream trouble may pass"""
+        assert InvalidLocation('x').status == HTTP_BAD_REQUEST
+        assert not InvalidLocation('x').retryable
+        assert UpstreamUnavailable('x').status == HTTP_SERVICE_UNAVAILABLE
+        assert UpstreamUnavailable('x').retryable
+        assert RateLimited('x').retryable
+        assert RequestTimedOut('x').retryable
+
+    def test_auth_errors(self) -> None:
+        """Test a missing token and a refused caller have their own codes"""
+        assert Unauthorized('x').status == HTTP_UNAUTHORIZED
+        assert Unauthorized('x').code == 'unauthorized'
+        assert Forbidden('x').status == HTTP_FORBIDDEN
+        assert not Forbidden('x').retryable
+
+    def test_is_an_exception(self) -> None:
+        """Test errors carry their message like any exception"""
+        error = WeatherError('Internal server error')
+
+        assert str(error) == 'Internal server error'
+        assert error.code == 'internal_error'
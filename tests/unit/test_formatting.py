@@ -0,0 +1,74 @@
+"""ABOUTME: Test hour labels on both clocks and in each language, over every hour of
+ABOUTME: the day - stripping every zero used to turn 10pm into 1pm"""
+
+from datetime import datetime, timezone
+
+import pytest
+
+from weather_core.formatting import (
+    TWELVE_HOUR_PATTERNS,
+    TWENTY_FOUR_HOUR_PATTERNS,
+    hour_label,
+)
+
+
+# Test constants
+TWELVE_HOUR_LABELS = [
+    *('12am', '1am', '2am', '3am', '4am', '5am'),
+    *('6am', '7am', '8am', '9am', '10am', '11am'),
+    *('12pm', '1pm', '2pm', '3pm', '4pm', '5pm'),
+    *('6pm', '7pm', '8pm', '9pm', '10pm', '11pm'),
+]
+HOURS = range(24)
+
+
+class TestHourLabel:
+    """Test labels for every hour of the day"""
+
+    def test_twelve_hour_every_hour(self) -> None:
+        """Test 12h labels strip leading zeros only, never zeros inside the hour"""
+        assert [hour_label(hour) for hour in HOURS] == TWELVE_HOUR_LABELS
+
+    def test_twenty_four_hour_every_hour(self) -> None:
+        """Test 24h labels are zero-padded hours on the hour"""
+        labels = [hour_label(hour, '24h') for hour in HOURS]
+
+        assert labels == [f'{hour:02d}:00' for hour in HOURS]
+        assert labels[0] == '00:00'
+        assert labels[20] == '20:00'
+
+    def test_every_language_every_hour(self) -> None:
+        """Test each language labels all 24 hours distinctly on both clocks"""
+        for language in TWELVE_HOUR_PATTERNS:
+            for clock in ('12h', '24h'):
+                labels = [hour_label(hour, clock, language) for hour in HOURS]
+
+                assert len(set(labels)) == len(HOURS), (language, clock)
+            twelve_hour = [hour_label(hour, '12h', language) for hour in HOURS]
+            assert not any(label.startswith('0') for label in twelve_hour)
+        assert set(TWENTY_FOUR_HOUR_PATTERNS) == set(TWELVE_HOUR_PATTERNS)
+
+    def test_localized_forms(self) -> None:
+        """Test each language's own way of writing an hour"""
+        assert hour_label(14, '12h', 'es') == '2 p. m.'
+        assert hour_label(0, '12h', 'es') == '12 a. m.'
+        assert hour_label(9, '24h', 'es') == '9:00'
+        assert hour_label(20, '24h', 'fr') == '20 h'
+        assert hour_label(10, '24h', 'de') == '10 Uhr'
+        assert hour_label(10, '12h', 'de') == '10 AM'
+
+    def test_fallbacks(self) -> None:
+        """Test unknown languages read as English and unknown clocks as 12h"""
+        assert hour_label(22, '24h', 'xx') == '22:00'
+        assert hour_label(22, '12h', None) == '10pm'
+        assert hour_label(22, 'sundial') == '10pm'
+
+    def test_datetime_wall_clock(self) -> None:
+        """Test a datetime is labelled by its own wall-clock hour"""
+        assert hour_label(datetime(2024, 6, 1, 22, 0, tzinfo=timezone.utc)) == '10pm'
+        assert hour_label(datetime(2024, 6, 1, 10, 30)) == '10am'
+
+    def test_out_of_range(self) -> None:
+        """Test hours outside the day are refused"""
+        with pytest.raises(ValueError, match='between 0 and 23'):
+            hour_label(24)
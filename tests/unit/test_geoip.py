@@ -0,0 +1,103 @@
+"""ABOUTME: Test client IP geolocation and the built-in MaxMind DB reader
+ABOUTME: Builds a tiny MMDB file in the test so no real database is needed"""
+
+import struct
+from pathlib import Path
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+from geoip import IpLocator, MaxMindReader, lookup_service, public_ip
+
+
+# Test constants
+NETWORK_PREFIX = 24
+MV_LAT = 37.386
+MV_LON = -122.0838
+
+
+def encode(value: Any) -> bytes:
+    """Encode a value in MaxMind DB data-section format (subset for tests)"""
+    if isinstance(value, dict):
+        body = b''.join(encode(key) + encode(item) for key, item in value.items())
+        return bytes([(7 << 5) | len(value)]) + body
+    if isinstance(value, str):
+        raw = value.encode('utf-8')
+        return bytes([(2 << 5) | len(raw)]) + raw
+    if isinstance(value, float):
+        return bytes([(3 << 5) | 8]) + struct.pack('>d', value)
+    raw = value.to_bytes(2, 'big')  # uint16
+    return bytes([(5 << 5) | len(raw)]) + raw
+
+
+def write_mmdb(path: Path, network: str, record: dict[str, Any]) -> None:
+    """Write an IPv4 database with a single /24 network, 24-bit records"""
+    node_count = NETWORK_PREFIX
+    bits = ''.join(f'{int(octet):08b}' for octet in network.split('.'))
+    tree = b''
+    for depth in range(NETWORK_PREFIX):
+        # Last node points at the data (offset 0), everything else is empty
+        follow = depth + 1 if depth < NETWORK_PREFIX - 1 else node_count + 16
+        records = [node_count, node_count]
+        records[int(bits[depth])] = follow
+        tree += b''.join(item.to_bytes(3, 'big') for item in records)
+    metadata = {'node_count': node_count, 'record_size': 24, 'ip_version': 4}
+    path.write_bytes(
+        tree
+        + bytes(16)
+        + encode(record)
+        + b'\xab\xcd\xefMaxMind.com'
+        + encode(metadata)
+    )
+
+
+class TestGeoIp:
+    """Test IP-to-location lookups"""
+
+    record = {
+        'city': {'names': {'en': 'Mountain View'}},
+        'location': {'latitude': MV_LAT, 'longitude': MV_LON},
+    }
+
+    def test_public_ip(self) -> None:
+        """Test only public addresses are worth locating"""
+        assert public_ip('8.8.8.8') == '8.8.8.8'
+        assert public_ip('10.0.0.1') is None
+        assert public_ip('203.0.113.9') is None  # Documentation range
+        assert public_ip('garbage') is None
+        assert public_ip(None) is None
+
+    def test_reader_lookup(self, tmp_path: Path) -> None:
+        """Test addresses inside the network resolve and others don't"""
+        path = tmp_path / 'test.mmdb'
+        write_mmdb(path, '8.8.8.0', self.record)
+        reader = MaxMindReader(str(path))
+
+        assert reader.lookup('8.8.8.8') == self.record
+        assert reader.lookup('8.8.9.8') is None
+        assert reader.lookup('2001:4860::8888') is None
+
+    def test_locator_prefers_database(self, tmp_path: Path) -> None:
+        """Test the database answers first and the service covers its gaps"""
+        path = tmp_path / 'test.mmdb'
+        write_mmdb(path, '8.8.8.0', self.record)
+        locator = IpLocator(str(path), 'https://geo.example.com/{ip}/json')
+
+        assert locator.locate('8.8.8.8') == (MV_LAT, MV_LON, 'Mountain View')
+        with patch('geoip.lookup_service', return_value=None) as mock_service:
+            assert locator.locate('1.1.1.1') is None
+        mock_service.assert_called_once_with(
+            'https://geo.example.com/{ip}/json', '1.1.1.1'
+        )
+
+    @patch('geoip.requests.get')
+    def test_lookup_service(self, mock_get: MagicMock) -> None:
+        """Test short lat/lon keys parse and the IP is filled into the URL"""
+        mock_get.return_value.json.return_value = {
+            'lat': MV_LAT,
+            'lon': MV_LON,
+            'city': 'Mountain View',
+        }
+        place = lookup_service('https://geo.example.com/{ip}', '8.8.8.8')
+
+        assert place == (MV_LAT, MV_LON, 'Mountain View')
+        assert mock_get.call_args[0][0] == 'https://geo.example.com/8.8.8.8'
@@ -0,0 +1,424 @@
+"""ABOUTME: Test the SQLite observation history: recording, querying and compaction
+ABOUTME: Each test uses a fresh database in a temporary directory"""
+
+from datetime import date
+from pathlib import Path
+
+from history import (
+    HOURLY_FIELDS,
+    SECONDS_PER_DAY,
+    SECONDS_PER_HOUR,
+    HistoryStore,
+    compare_to_normal,
+    daily_from_hourly,
+    day_normal,
+    hourly_from_observations,
+    mean_direction,
+    month_climatology,
+    observation_time,
+    score_forecasts,
+    solar_day,
+    utc_day_start,
+    verify_day,
+)
+
+
+# Test constants
+CHICAGO = '41.8781,-87.6298'
+CHICAGO_LAT = 41.8781
+CHICAGO_LON = -87.6298
+NOW = 1_760_000_000.0
+NEW_YEAR_2024 = 1_704_067_200
+CURRENT = {
+    'temperature': 72,
+    'humidity': 55,
+    'pressure': 30.01,
+    'wind_gust': None,
+    'icon': 'clear-day',
+    'summary': 'Clear',
+}
+
+
+def make_store(
+    tmp_path: Path,
+    retention_days: int | None = None,
+    daily_retention_days: int | None = None,
+) -> HistoryStore:
+    """A store in a fresh database"""
+    return HistoryStore(
+        str(tmp_path / 'history.db'), retention_days, daily_retention_days
+    )
+
+
+class TestObservationTime:
+    """Test picking the time a snapshot was observed"""
+
+    def test_provider_timestamp_wins(self) -> None:
+        """Test a provider's epoch timestamp is used over the fetch time"""
+        assert observation_time({'timestamp': NOW - 60}, NOW) == NOW - 60
+
+    def test_fetch_time_otherwise(self) -> None:
+        """Test missing or unusable timestamps fall back to the fetch time"""
+        for current in ({}, {'timestamp': None}, {'timestamp': 'soon'}):
+            assert observation_time(current, NOW) == NOW
+
+
+class TestHistoryStore:
+    """Test saving and reading back observations"""
+
+    def test_record_and_query(self, tmp_path: Path) -> None:
+        """Test snapshots come back oldest first with their readings"""
+        store = make_store(tmp_path)
+        store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', CURRENT, NOW)
+        store.record(
+            CHICAGO,
+            CHICAGO_LAT,
+            CHICAGO_LON,
+            'OpenMeteo',
+            {**CURRENT, 'temperature': 70},
+            NOW - 600,
+        )
+
+        rows = store.observations(CHICAGO, NOW - 3600, NOW)
+
+        assert [row['temperature'] for row in rows] == [70.0, 72.0]
+        assert rows[1]['provider'] == 'OpenMeteo'
+        assert rows[1]['pressure'] == CURRENT['pressure']
+        assert rows[1]['wind_gust'] is None
+        assert rows[1]['summary'] == 'Clear'
+
+    def test_same_observation_recorded_once(self, tmp_path: Path) -> None:
+        """Test a refetch of an unchanged provider observation is ignored"""
+        store = make_store(tmp_path)
+        current = {**CURRENT, 'timestamp': NOW}
+
+        assert store.record(
+            CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'PirateWeather', current, NOW
+        )
+        assert not store.record(
+            CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'PirateWeather', current, NOW + 300
+        )
+        assert len(store.observations(CHICAGO, 0, NOW * 2)) == 1
+
+    def test_filter_by_provider_and_range(self, tmp_path: Path) -> None:
+        """Test queries only return the asked-for provider within the range"""
+        store = make_store(tmp_path)
+        for provider in ('OpenMeteo', 'PirateWeather'):
+            store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, provider, CURRENT, NOW)
+        store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', CURRENT, 0)
+
+        rows = store.observations(CHICAGO, NOW - 60, NOW, provider='PirateWeather')
+
+        assert [row['provider'] for row in rows] == ['PirateWeather']
+
+    def test_locations_summary(self, tmp_path: Path) -> None:
+        """Test each location lists its count and first and last observation"""
+        store = make_store(tmp_path)
+        for offset in (0, 600):
+            store.record(
+                CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', CURRENT, NOW + offset
+            )
+
+        assert store.locations() == [
+            {
+                'location': CHICAGO,
+                'lat': CHICAGO_LAT,
+                'lon': CHICAGO_LON,
+                'count': 2,
+                'first': NOW,
+                'last': NOW + 600,
+            }
+        ]
+
+    def test_compact_past_retention(self, tmp_path: Path) -> None:
+        """Test whole days past the window become daily summaries and newer stay"""
+        store = make_store(tmp_path, retention_days=30)
+        old_day = utc_day_start(NOW) - 31 * SECONDS_PER_DAY
+        for offset, temperature in ((0, 50), (3600, 54), (7200, 52)):
+            reading = {**CURRENT, 'temperature': temperature}
+            stamp = old_day + offset
+            store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', reading, stamp)
+        store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', CURRENT, NOW)
+
+        counts = store.compact(NOW)
+
+        assert counts == {'days': 1, 'observations': 3, 'daily': 0, 'forecasts': 0}
+        assert [row['observed_at'] for row in store.observations(CHICAGO, 0, NOW)] == [
+            NOW
+        ]
+        day = store.daily(CHICAGO, old_day, NOW)[old_day]
+        assert (day['temperature_min'], day['temperature_max']) == (50.0, 54.0)
+        assert day['hours'] == 3  # noqa: PLR2004
+        assert store.compact(NOW)['days'] == 0
+
+    def test_compacted_day_not_rewritten(self, tmp_path: Path) -> None:
+        """Test a late snapshot for a compacted day doesn't replace its summary"""
+        store = make_store(tmp_path, retention_days=30)
+        old_day = utc_day_start(NOW) - 40 * SECONDS_PER_DAY
+        store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', CURRENT, old_day)
+        store.compact(NOW)
+        late = {**CURRENT, 'temperature': 99}
+        store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'Station', late, old_day + 60)
+
+        counts = store.compact(NOW)
+
+        assert (counts['days'], counts['observations']) == (0, 1)
+        assert store.daily(CHICAGO, old_day, old_day)[old_day]['temperature_max'] == 72
+
+    def test_daily_retention(self, tmp_path: Path) -> None:
+        """Test daily summaries go once past their own, longer window"""
+        store = make_store(tmp_path, retention_days=30, daily_retention_days=365)
+        for days_ago in (400, 100):
+            stamp = NOW - days_ago * SECONDS_PER_DAY
+            store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', CURRENT, stamp)
+
+        counts = store.compact(NOW)
+
+        assert (counts['days'], counts['daily']) == (2, 1)
+        assert len(store.daily(CHICAGO, 0, NOW)) == 1
+
+    def test_zero_retention_keeps_everything(self, tmp_path: Path) -> None:
+        """Test a retention of 0 never compacts or deletes"""
+        store = make_store(tmp_path, retention_days=0)
+        store.record(CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', CURRENT, 0)
+
+        assert sum(store.compact(NOW).values()) == 0
+        assert len(store.observations(CHICAGO, 0, NOW)) == 1
+
+    def test_export(self, tmp_path: Path) -> None:
+        """Test exports cover a range for one location or all of them"""
+        store = make_store(tmp_path)
+        for location, stamp in ((CHICAGO, NOW), (CHICAGO, NOW + 60), ('0,0', NOW)):
+            store.record(location, 0, 0, 'OpenMeteo', CURRENT, stamp)
+
+        everything = store.export('observations', None, 0, NOW + 60)
+
+        assert len(everything) == 3  # noqa: PLR2004
+        (row,) = store.export('observations', CHICAGO, NOW + 30, NOW + 90)
+        assert row['observed_at'] == NOW + 60
+        assert row['temperature'] == 72.0  # noqa: PLR2004
+        assert store.export('daily', None, 0, NOW) == []
+
+    def test_persists_across_restarts(self, tmp_path: Path) -> None:
+        """Test a new store on the same file sees earlier observations"""
+        make_store(tmp_path).record(
+            CHICAGO, CHICAGO_LAT, CHICAGO_LON, 'OpenMeteo', CURRENT, NOW
+        )
+
+        assert len(make_store(tmp_path).observations(CHICAGO, 0, NOW)) == 1
+
+
+class TestAggregation:
+    """Test snapshots rolled up into the hourly and daily history shapes"""
+
+    def snapshot(self, observed_at: float, **readings: float | None) -> dict:
+        """A stored row with only the given readings"""
+        return {
+            'observed_at': observed_at,
+            **{column: readings.get(column) for column in HOURLY_FIELDS.values()},
+        }
+
+    def test_hourly_averages(self) -> None:
+        """Test snapshots in the same hour average and gaps stay None"""
+        hour = NOW // SECONDS_PER_HOUR * SECONDS_PER_HOUR
+        rows = [
+            self.snapshot(hour + 60, temperature=70, precipitation_rate=0.1),
+            self.snapshot(hour + 1800, temperature=73, precipitation_rate=0.2),
+            self.snapshot(hour + SECONDS_PER_HOUR, temperature=75),
+        ]
+
+        points = hourly_from_observations(rows)
+
+        assert list(points) == [hour, hour + SECONDS_PER_HOUR]
+        assert points[hour]['temperature'] == 71.5  # noqa: PLR2004
+        assert points[hour]['precipitation'] == 0.15  # noqa: PLR2004
+        assert points[hour]['wind_gust'] is None
+
+    def test_wind_direction_wraps(self) -> None:
+        """Test bearings either side of north average to north, not south"""
+        assert mean_direction([350, 10]) in (0.0, 360.0)
+        assert mean_direction([80, 100]) == 90.0  # noqa: PLR2004
+
+    def test_daily_rollup(self) -> None:
+        """Test hours roll into UTC days with highs, lows and totals"""
+        day = NOW // SECONDS_PER_DAY * SECONDS_PER_DAY
+        hours = {
+            day + index * SECONDS_PER_HOUR: {
+                'temperature': 60.0 + index,
+                'humidity': 50.0,
+                'pressure': None,
+                'wind_speed': 5.0,
+                'wind_gust': None,
+                'precipitation': 0.1,
+            }
+            for index in range(4)
+        }
+
+        (summary,) = daily_from_hourly(hours).values()
+
+        assert summary['temperature_max'] == 63.0  # noqa: PLR2004
+        assert summary['temperature_min'] == 60.0  # noqa: PLR2004
+        assert summary['temperature_mean'] == 61.5  # noqa: PLR2004
+        assert summary['precipitation_sum'] == 0.4  # noqa: PLR2004
+        assert summary['pressure_mean'] is None
+        assert summary['hours'] == 4  # noqa: PLR2004
+
+
+class TestForecastAccuracy:
+    """Test keeping next-day forecasts and scoring them against outcomes"""
+
+    def test_latest_forecast_kept(self, tmp_path: Path) -> None:
+        """Test a later forecast for the same day replaces the earlier one"""
+        store = make_store(tmp_path)
+        for issued_at, high in ((NOW, 70), (NOW + 3600, 72)):
+            store.record_forecast(
+                CHICAGO,
+                'OpenMeteo',
+                {'date': '2025-10-10', 'h': high, 'l': 55, 'rain': 40},
+                issued_at,
+            )
+
+        (forecast,) = store.forecasts(CHICAGO, '2025-10-01', '2025-10-31')
+
+        assert forecast['high'] == 72.0  # noqa: PLR2004
+        assert forecast['precipitation_probability'] == 40.0  # noqa: PLR2004
+        assert forecast['issued_at'] == NOW + 3600
+
+    def test_forecasts_kept_with_daily_summaries(self, tmp_path: Path) -> None:
+        """Test forecasts outlive snapshots, going with the daily summaries"""
+        store = make_store(tmp_path, retention_days=30, daily_retention_days=365)
+        store.record_forecast(CHICAGO, 'OpenMeteo', {'date': '2020-01-01'}, 0)
+        store.record_forecast(CHICAGO, 'OpenMeteo', {'date': '2025-09-01'}, NOW - 3600)
+
+        assert store.compact(NOW)['forecasts'] == 1
+        (kept,) = store.forecasts(CHICAGO, '2000-01-01', '2100-01-01')
+        assert kept['target_date'] == '2025-09-01'
+
+    def test_solar_day(self) -> None:
+        """Test the day window shifts an hour for every 15 degrees of longitude"""
+        start, end = solar_day('2024-01-01', -87.6)
+
+        assert start == NEW_YEAR_2024 + 6 * SECONDS_PER_HOUR
+        assert end - start == SECONDS_PER_DAY - 1
+
+    def test_verify_day(self) -> None:
+        """Test a day needs nearly all its hours and any measurable rain counts"""
+        hours = [
+            {'temperature': 50.0 + index, 'precipitation': 0.0} for index in range(24)
+        ]
+        hours[5]['precipitation'] = 0.02
+
+        assert verify_day(hours) == {'high': 73.0, 'precipitated': True}
+        assert verify_day(hours[:12]) is None
+
+    def test_scores(self) -> None:
+        """Test MAE of highs and Brier scores, best provider first"""
+        outcomes = {
+            '2024-01-01': {'high': 70.0, 'precipitated': True},
+            '2024-01-02': {'high': 60.0, 'precipitated': False},
+        }
+        forecasts = [
+            {
+                'provider': provider,
+                'target_date': date,
+                'high': high,
+                'precipitation_probability': chance,
+            }
+            for provider, date, high, chance in (
+                ('Good', '2024-01-01', 71.0, 80),
+                ('Good', '2024-01-02', 59.0, 20),
+                ('Poor', '2024-01-01', 64.0, 0),
+                ('Poor', '2024-01-02', 66.0, None),
+                ('Poor', '2024-01-03', 99.0, 100),  # Not verified yet
+            )
+        ]
+
+        good, poor = score_forecasts(forecasts, outcomes)
+
+        assert good == {
+            'provider': 'Good',
+            'days': 2,
+            'high_mae': 1.0,
+            'precipitation_brier': 0.04,
+        }
+        assert poor['high_mae'] == 6.0  # noqa: PLR2004
+        assert poor['precipitation_brier'] == 1.0
+
+
+class TestClimatology:
+    """Test daily summaries and the normals and records built from them"""
+
+    def summaries(self) -> list[dict]:
+        """Three Januaries and one July of made-up days, oldest first"""
+        days = []
+        for year, offset in ((2021, 0.0), (2022, 2.0), (2023, 4.0)):
+            for day in (1, 15, 31):
+                days.append(
+                    {
+                        'date': f'{year}-01-{day:02d}',
+                        'temperature_max': 30.0 + offset,
+                        'temperature_min': 15.0 + offset,
+                        'precipitation_sum': 0.1,
+                    }
+                )
+        days.append(
+            {
+                'date': '2023-07-04',
+                'temperature_max': 90.0,
+                'temperature_min': 70.0,
+                'precipitation_sum': None,
+            }
+        )
+        return days
+
+    def test_store_days(self, tmp_path: Path) -> None:
+        """Test summaries are kept per date and the latest date is known"""
+        store = make_store(tmp_path)
+        store.record_days(
+            CHICAGO,
+            {
+                '2024-01-02': {'temperature_max': 31, 'temperature_min': 20},
+                '2024-01-01': {'temperature_max': 30, 'precipitation_sum': 0.2},
+            },
+            'archive',
+        )
+
+        days = store.days(CHICAGO)
+
+        assert [day['date'] for day in days] == ['2024-01-01', '2024-01-02']
+        assert days[0]['temperature_min'] is None
+        assert days[0]['source'] == 'archive'
+        assert store.last_day(CHICAGO) == '2024-01-02'
+        assert store.last_day('0.0000,0.0000') is None
+
+    def test_month_normals_and_records(self) -> None:
+        """Test a month's normals average every year and records name the day"""
+        january = month_climatology(self.summaries(), 1)
+
+        assert january['normals'] == {
+            'high': 32.0,
+            'low': 17.0,
+            'precipitation': 3.1,
+        }
+        assert january['records']['high'] == {'value': 34.0, 'date': '2023-01-01'}
+        assert january['records']['low'] == {'value': 15.0, 'date': '2021-01-01'}
+        assert january['years'] == 3  # noqa: PLR2004
+
+    def test_month_without_data(self) -> None:
+        """Test a month nothing is known about has no normals or records"""
+        march = month_climatology(self.summaries(), 3)
+
+        assert march['normals']['high'] is None
+        assert march['records'] == {'high': None, 'low': None}
+
+    def test_day_normal_wraps_the_year(self) -> None:
+        """Test New Year's Eve is normal against early January, not July"""
+        normal = day_normal(self.summaries(), date(2024, 12, 31))
+
+        assert normal == {'high': 32.0, 'low': 17.0}
+
+    def test_compare_to_normal(self) -> None:
+        """Test differences are signed and missing values give None"""
+        assert compare_to_normal(36, 32.0) == 4.0  # noqa: PLR2004
+        assert compare_to_normal(28, 32.0) == -4.0  # noqa: PLR2004
+        assert compare_to_normal(None, 32.0) is None
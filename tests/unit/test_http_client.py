@@ -0,0 +1,185 @@
+"""ABOUTME: Test the shared provider HTTP helper - which failures retry and how long
+ABOUTME: Requests are mocked and sleeps patched out, so nothing waits or leaves"""
+
+from datetime import timedelta
+from unittest.mock import MagicMock, patch
+
+import pytest
+import requests
+
+from weather_core.http_client import ProviderQuota, QuotaExceeded, RetryPolicy, get
+
+
+# Test constants
+URL = 'https://api.example.com/forecast'
+ATTEMPTS = 3
+BASE_DELAY = 0.5
+MAX_DELAY = 4.0
+
+
+def reply(status: int) -> MagicMock:
+    """A mocked response with just a status code"""
+    return MagicMock(status_code=status)
+
+
+class TestRetryPolicy:
+    """Test backoff timing"""
+
+    def test_delay_doubles_up_to_max(self) -> None:
+        """Test each wait doubles from the base and stops at the cap"""
+        policy = RetryPolicy(base_delay=BASE_DELAY, max_delay=MAX_DELAY, jitter=0)
+
+        delays = [policy.delay(attempt) for attempt in range(1, 6)]
+
+        assert delays == [0.5, 1.0, 2.0, 4.0, 4.0]
+
+    def test_jitter_bounds(self) -> None:
+        """Test jitter only ever adds, by at most its share of the wait"""
+        policy = RetryPolicy(base_delay=1.0, jitter=0.5)
+
+        for _ in range(50):
+            assert 1.0 <= policy.delay(1) <= 1.5  # noqa: PLR2004
+
+
+@patch('weather_core.http_client.time.sleep')
+@patch('weather_core.http_client.requests.get')
+class TestGet:
+    """Test which failures are retried"""
+
+    def test_server_error_retried(
+        self, mock_get: MagicMock, mock_sleep: MagicMock
+    ) -> None:
+        """Test a 5xx reply is retried until the upstream recovers"""
+        mock_get.side_effect = [reply(503), reply(502), reply(200)]
+
+        response = get(URL, RetryPolicy(max_attempts=ATTEMPTS), timeout=10)
+
+        assert response.status_code == 200  # noqa: PLR2004
+        assert mock_get.call_count == ATTEMPTS
+        assert mock_sleep.call_count == ATTEMPTS - 1
+        mock_get.assert_called_with(URL, timeout=10)
+
+    def test_last_server_error_returned(
+        self, mock_get: MagicMock, mock_sleep: MagicMock
+    ) -> None:
+        """Test the final 5xx reply comes back for the caller to handle"""
+        mock_get.return_value = reply(500)
+
+        response = get(URL, RetryPolicy(max_attempts=ATTEMPTS))
+
+        assert response.status_code == 500  # noqa: PLR2004
+        assert mock_get.call_count == ATTEMPTS
+        assert mock_sleep.call_count == ATTEMPTS - 1
+
+    def test_client_error_not_retried(
+        self, mock_get: MagicMock, mock_sleep: MagicMock
+    ) -> None:
+        """Test a 4xx reply (bad key, unknown place) is returned at once"""
+        mock_get.return_value = reply(401)
+
+        response = get(URL, RetryPolicy(max_attempts=ATTEMPTS))
+
+        assert response.status_code == 401  # noqa: PLR2004
+        mock_get.assert_called_once()
+        mock_sleep.assert_not_called()
+
+    def test_timeout_retried_then_raised(
+        self, mock_get: MagicMock, mock_sleep: MagicMock
+    ) -> None:
+        """Test timeouts are retried and the last one raised"""
+        mock_get.side_effect = requests.Timeout('slow')
+
+        with pytest.raises(requests.Timeout):
+            get(URL, RetryPolicy(max_attempts=ATTEMPTS))
+
+        assert mock_get.call_count == ATTEMPTS
+        assert mock_sleep.call_count == ATTEMPTS - 1
+
+    def test_connection_error_not_retried(
+        self, mock_get: MagicMock, mock_sleep: MagicMock
+    ) -> None:
+        """Test errors other than timeouts propagate on the first try"""
+        mock_get.side_effect = requests.ConnectionError('refused')
+
+        with pytest.raises(requests.ConnectionError):
+            get(URL, RetryPolicy(max_attempts=ATTEMPTS))
+
+        mock_get.assert_called_once()
+        mock_sleep.assert_not_called()
+
+    def test_single_attempt_policy(
+        self, mock_get: MagicMock, mock_sleep: MagicMock
+    ) -> None:
+        """Test max_attempts=1 turns retries off"""
+        mock_get.return_value = reply(503)
+
+        get(URL, RetryPolicy(max_attempts=1))
+
+        mock_get.assert_called_once()
+        mock_sleep.assert_not_called()
+
+
+class TestProviderQuota:
+    """Test per-provider call limits"""
+
+    def test_minute_limit(self) -> None:
+        """Test calls beyond the minute limit are refused and counted"""
+        quota = ProviderQuota(per_minute=2)
+        quota.acquire()
+        quota.acquire()
+
+        with pytest.raises(QuotaExceeded):
+            quota.acquire()
+
+        assert not quota.available()
+        assert quota.refused == 1
+        assert quota.remaining() == {'minute': 0, 'day': None}
+
+    def test_minute_window_slides(self) -> None:
+        """Test calls older than a minute stop counting"""
+        quota = ProviderQuota(per_minute=1)
+        quota.acquire()
+        quota.recent[0] -= 61
+
+        quota.acquire()
+
+        assert quota.used_today == 2  # noqa: PLR2004
+
+    def test_day_limit_resets(self) -> None:
+        """Test the day count starts over on a new UTC day"""
+        quota = ProviderQuota(per_day=1)
+        quota.acquire()
+        assert not quota.available()
+
+        quota.day -= timedelta(days=1)
+
+        assert quota.remaining()['day'] == 1
+        assert quota.describe()['used_today'] == 0
+
+    def test_parse(self) -> None:
+        """Test limits are read from '<calls>/<period>' parts"""
+        quota = ProviderQuota.parse('10/minute, 300/DAY')
+
+        assert (quota.per_minute, quota.per_day) == (10, 300)
+        assert ProviderQuota.parse('5/day').per_minute is None
+
+    def test_parse_rejects_bad_spec(self) -> None:
+        """Test an unknown period or count is an error"""
+        for spec in ('10/hour', 'ten/minute', ''):
+            with pytest.raises(ValueError, match='quota'):
+                ProviderQuota.parse(spec)
+
+    @patch('weather_core.http_client.time.sleep')
+    @patch('weather_core.http_client.requests.get')
+    def test_retries_count_against_quota(
+        self, mock_get: MagicMock, mock_sleep: MagicMock
+    ) -> None:
+        """Test each attempt uses quota and a spent quota stops the retries"""
+        mock_get.return_value = reply(503)
+        quota = ProviderQuota(per_minute=2)
+
+        with pytest.raises(QuotaExceeded):
+            get(URL, RetryPolicy(max_attempts=ATTEMPTS), quota)
+
+        assert mock_get.call_count == 2  # noqa: PLR2004
+        assert mock_sleep.call_count == 2  # noqa: PLR2004
@@ -0,0 +1,44 @@
+"""ABOUTME: Test localized Beaufort names and wind direction phrases
+ABOUTME: Covers every supported language and language tag resolution"""
+
+from i18n import SUPPORTED_LANGUAGES, describe_wind, direction_name, resolve_language
+
+
+# Test constants
+NORTHWEST = 315
+FRESH_BREEZE_MPH = 20
+
+
+class TestWindWording:
+    """Test localized Beaufort and direction phrases"""
+
+    def test_describe_wind_in_each_language(self) -> None:
+        """Test force and direction combine into a natural phrase"""
+        assert describe_wind(FRESH_BREEZE_MPH, NORTHWEST, 'es') == (
+            'brisa fresca del noroeste'
+        )
+        assert describe_wind(FRESH_BREEZE_MPH, NORTHWEST, 'en') == (
+            'fresh breeze from the northwest'
+        )
+        assert describe_wind(FRESH_BREEZE_MPH, NORTHWEST, 'de') == (
+            'frische Brise aus Nordwest'
+        )
+        assert describe_wind(FRESH_BREEZE_MPH, 90, 'fr') == "bonne brise de l'est"
+
+    def test_calm_and_unknown_direction(self) -> None:
+        """Test calm air and missing directions leave out the direction"""
+        assert describe_wind(0, NORTHWEST, 'es') == 'calma'
+        assert describe_wind(FRESH_BREEZE_MPH, None, 'fr') == 'bonne brise'
+
+    def test_every_language_has_sixteen_directions(self) -> None:
+        """Test each language translates every compass point"""
+        for language in SUPPORTED_LANGUAGES:
+            names = {direction_name(index * 22.5, language) for index in range(16)}
+            assert len(names) == 16  # noqa: PLR2004
+
+    def test_resolve_language(self) -> None:
+        """Test regional tags fall back to their base language"""
+        assert resolve_language('es-MX') == 'es'
+        assert resolve_language('DE') == 'de'
+        assert resolve_language('tlh') is None
+        assert resolve_language(None) is None
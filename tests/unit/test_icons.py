@@ -0,0 +1,50 @@
+"""ABOUTME: Test the icon packs and the table they send to clients
+ABOUTME: Uses a stand-in asset_url so no static directory is needed"""
+
+from icons import ICON_SETS, WEATHER_ICON_CODES, resolve_icon_set
+
+
+def fake_asset_url(path: str) -> str:
+    """Version every asset with the same fake hash"""
+    return f'/static/{path}?v=abc'
+
+
+class TestIconSets:
+    """Test mapping icon codes to each pack"""
+
+    def test_every_pack_covers_every_code(self) -> None:
+        """Test no pack falls back for a code providers produce"""
+        for icon_set in ICON_SETS.values():
+            assert set(icon_set.icons) == set(WEATHER_ICON_CODES)
+
+    def test_describe(self) -> None:
+        """Test local images are versioned and only the default pack animates"""
+        default = ICON_SETS['default'].describe(fake_asset_url)
+        meteocons = ICON_SETS['meteocons'].describe(fake_asset_url)
+
+        assert default['icons']['rain'] == (
+            '/static/icons/weather/static/rainy-2.svg?v=abc'
+        )
+        assert default['animated_icons']['rain'] == (
+            '/static/icons/weather/animated/rainy-2.svg?v=abc'
+        )
+        assert meteocons['icons']['cloudy'].endswith('/overcast.svg')
+        assert 'animated_icons' not in meteocons
+
+    def test_html(self) -> None:
+        """Test markup per kind, falling back for unknown codes"""
+        assert ICON_SETS['default'].html('tornado', fake_asset_url) == (
+            '<img class="weather-icon" '
+            'src="/static/icons/weather/static/clear-day.svg?v=abc" alt="tornado">'
+        )
+        assert 'class="weather-icon wi wi-fog"' in ICON_SETS['weather-icons'].html(
+            'fog', fake_asset_url
+        )
+        assert '>🌙</span>' in ICON_SETS['emoji'].html('clear-night', fake_asset_url)
+
+    def test_resolve(self) -> None:
+        """Test names and aliases resolve, anything else doesn't"""
+        assert resolve_icon_set(' WI ') == 'weather-icons'
+        assert resolve_icon_set('emoji') == 'emoji'
+        assert resolve_icon_set('clipart') is None
+        assert resolve_icon_set(None) is None
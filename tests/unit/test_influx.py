@@ -0,0 +1,112 @@
+"""ABOUTME: Test the InfluxDB line-protocol sink
+ABOUTME: Covers escaping, batching and keeping points across failed writes"""
+
+from unittest.mock import MagicMock, patch
+
+import requests
+
+from influx import InfluxWriter, line_protocol
+
+
+# Test constants
+HTTP_NO_CONTENT = 204
+HTTP_SERVICE_UNAVAILABLE = 503
+TIMESTAMP = 1704067200
+BATCH_SIZE = 2
+POINT_COUNT = 3
+FLUSH_INTERVAL = 10
+
+
+def make_writer() -> InfluxWriter:
+    """Create a writer with a small batch size"""
+    return InfluxWriter(
+        'http://influx:8086/',
+        'home',
+        'weather',
+        'secret-token',
+        batch_size=BATCH_SIZE,
+        flush_interval=FLUSH_INTERVAL,
+    )
+
+
+class TestLineProtocol:
+    """Test line-protocol formatting"""
+
+    def test_escapes_tags_and_formats_fields(self) -> None:
+        """Test tag values are escaped and numbers are written as floats"""
+        line = line_protocol(
+            'weather',
+            {'location': 'New York, NY', 'provider': 'OpenMeteo', 'empty': ''},
+            {'temperature': 72, 'humidity': 65.5, 'wind_gust': None, 'is_day': True},
+            TIMESTAMP,
+        )
+
+        assert line == (
+            'weather,location=New\\ York\\,\\ NY,provider=OpenMeteo '
+            'temperature=72.0,humidity=65.5,is_day=true 1704067200'
+        )
+
+    def test_no_fields_is_skipped(self) -> None:
+        """Test a point without any values is not written"""
+        assert line_protocol('weather', {}, {'temperature': None}, TIMESTAMP) is None
+
+
+class TestInfluxWriter:
+    """Test batching and retry behavior"""
+
+    @patch('influx.requests.post')
+    def test_flush_writes_batches(self, mock_post: MagicMock) -> None:
+        """Test queued points are sent in batches to the v2 write API"""
+        mock_post.return_value = MagicMock(status_code=HTTP_NO_CONTENT)
+        writer = make_writer()
+        for index in range(POINT_COUNT):
+            writer.write(f'weather temperature={index}.0 {TIMESTAMP}')
+
+        assert writer.flush() is True
+
+        assert mock_post.call_count == BATCH_SIZE
+        first_call = mock_post.call_args_list[0]
+        assert first_call.args[0] == 'http://influx:8086/api/v2/write'
+        assert first_call.kwargs['params'] == {
+            'org': 'home',
+            'bucket': 'weather',
+            'precision': 's',
+        }
+        assert first_call.kwargs['headers']['Authorization'] == 'Token secret-token'
+        assert first_call.kwargs['data'].count(b'\n') == BATCH_SIZE - 1
+        assert not writer.buffer
+
+    @patch('influx.requests.post')
+    def test_failed_write_keeps_points(self, mock_post: MagicMock) -> None:
+        """Test points stay queued when InfluxDB is down or rejects the write"""
+        writer = make_writer()
+        writer.write(f'weather temperature=1.0 {TIMESTAMP}')
+
+        mock_post.side_effect = requests.exceptions.ConnectionError('down')
+        assert writer.flush() is False
+        mock_post.side_effect = None
+        mock_post.return_value = MagicMock(status_code=HTTP_SERVICE_UNAVAILABLE)
+        assert writer.flush() is False
+        assert len(writer.buffer) == 1
+
+        mock_post.return_value = MagicMock(status_code=HTTP_NO_CONTENT)
+        assert writer.flush() is True
+        assert not writer.buffer
+
+    @patch('influx.InfluxWriter.flush')
+    def test_run_backs_off_while_failing(self, mock_flush: MagicMock) -> None:
+        """Test the retry delay doubles on failure and resets after a success"""
+        mock_flush.side_effect = [False, False, True]
+        delays: list[float] = []
+
+        def sleep(seconds: float) -> None:
+            delays.append(seconds)
+            if len(delays) > POINT_COUNT:
+                raise StopIteration
+
+        try:
+            make_writer().run(sleep)
+        except StopIteration:
+            pass
+
+        assert delays == [FLUSH_INTERVAL, 20, 40, FLUSH_INTERVAL]
@@ -0,0 +1,157 @@
+"""ABOUTME: Test the per-request timeout and the concurrency limit with load shedding
+ABOUTME: Slow requests are tiny WSGI apps blocked on events, so nothing really waits"""
+
+import threading
+from collections.abc import Iterator
+from typing import Any
+
+import pytest
+from werkzeug.test import Client
+
+from limits import RequestLimits
+
+
+# Test constants
+HTTP_OK = 200
+HTTP_SERVICE_UNAVAILABLE = 503
+HTTP_GATEWAY_TIMEOUT = 504
+SHORT_TIMEOUT = 0.05
+WAIT_SECONDS = 5
+
+
+class BlockingApp:
+    """A WSGI app whose /slow requests wait until released"""
+
+    def __init__(self) -> None:
+        self.release = threading.Event()
+        self.started = threading.Event()
+        self.finished = threading.Event()
+
+    def __call__(self, environ: dict[str, Any], start_response: Any) -> Any:
+        path = environ['PATH_INFO']
+        if path == '/boom':
+            msg = 'handler crashed'
+            raise RuntimeError(msg)
+        if path == '/stream':
+            start_response('200 OK', [('Content-Type', 'text/event-stream')])
+            return self.events()
+        if path == '/slow':
+            self.started.set()
+            self.release.wait(WAIT_SECONDS)
+            self.finished.set()
+        start_response('200 OK', [('Content-Type', 'text/plain')])
+        return [b'done']
+
+    def events(self) -> Iterator[bytes]:
+        """An event stream that outlives any timeout"""
+        yield b'event: weather\n\n'
+        self.release.wait(WAIT_SECONDS)
+        yield b'event: weather\n\n'
+
+
+def in_background(client: Client, path: str) -> threading.Thread:
+    """Start a request that will block, without blocking the test"""
+    thread = threading.Thread(target=client.get, args=(path,), daemon=True)
+    thread.start()
+    return thread
+
+
+class TestRequestLimits:
+    """Test timeouts and shedding around a WSGI app"""
+
+    def test_fast_request_passes_through(self) -> None:
+        """Test an answer within the limits arrives untouched"""
+        client = Client(RequestLimits(BlockingApp(), 5, 2))
+
+        response = client.get('/fast')
+
+        assert response.status_code == HTTP_OK
+        assert response.get_data() == b'done'
+
+    def test_slow_request_times_out(self) -> None:
+        """Test a request over the time limit gets a 504 but keeps its slot"""
+        app = BlockingApp()
+        limits = RequestLimits(app, SHORT_TIMEOUT, 1)
+        client = Client(limits)
+
+        response = client.get('/slow')
+
+        assert response.status_code == HTTP_GATEWAY_TIMEOUT
+        assert response.get_json()['code'] == 'request_timeout'
+        assert response.get_json()['error'] == 'Request timed out'
+        assert limits.timed_out == 1
+        # The worker can't be stopped, so it still counts until it finishes
+        assert limits.in_flight == 1
+        assert client.get('/fast').status_code == HTTP_SERVICE_UNAVAILABLE
+        app.release.set()
+        assert app.finished.wait(WAIT_SECONDS)
+
+    def test_excess_requests_shed(self) -> None:
+        """Test requests beyond the concurrency limit get a quick 503"""
+        app = BlockingApp()
+        limits = RequestLimits(app, None, 1)
+        client = Client(limits)
+        slow = in_background(client, '/slow')
+        assert app.started.wait(WAIT_SECONDS)
+
+        shed = client.get('/fast')
+        app.release.set()
+        slow.join(WAIT_SECONDS)
+
+        assert shed.status_code == HTTP_SERVICE_UNAVAILABLE
+        assert shed.headers['Retry-After'] == '1'
+        assert shed.get_json()['code'] == 'server_busy'
+        assert shed.get_json()['retry_after'] == 1
+        assert limits.shed == 1
+        assert client.get('/fast').status_code == HTTP_OK
+
+    def test_health_probe_never_shed(self) -> None:
+        """Test liveness answers however busy the server is"""
+        app = BlockingApp()
+        limits = RequestLimits(app, None, 1)
+        client = Client(limits)
+        slow = in_background(client, '/slow')
+        assert app.started.wait(WAIT_SECONDS)
+
+        probe = client.get('/healthz')
+        app.release.set()
+        slow.join(WAIT_SECONDS)
+
+        assert probe.status_code == HTTP_OK
+
+    def test_event_stream_exempt_once_started(self) -> None:
+        """Test a stream isn't cut off by the timeout and frees its slot"""
+        app = BlockingApp()
+        limits = RequestLimits(app, SHORT_TIMEOUT, 1)
+        client = Client(limits)
+
+        response = client.get('/stream', buffered=False)
+        chunks = iter(response.response)
+        first = next(chunks)
+
+        assert response.status_code == HTTP_OK
+        assert first == b'event: weather\n\n'
+        assert limits.in_flight == 0
+        app.release.set()
+        assert next(chunks) == b'event: weather\n\n'
+        response.close()
+
+    def test_errors_reraised(self) -> None:
+        """Test a crash in the app surfaces on the server's thread"""
+        client = Client(RequestLimits(BlockingApp(), 5, 1))
+
+        with pytest.raises(RuntimeError, match='crashed'):
+            client.get('/boom')
+
+    def test_off_by_default(self) -> None:
+        """Test without limits requests run on the calling thread"""
+        seen = []
+
+        def app(environ: dict[str, Any], start_response: Any) -> list[bytes]:
+            seen.append(threading.current_thread())
+            start_response('200 OK', [])
+            return [b'']
+
+        Client(RequestLimits(app)).get('/')
+
+        assert seen == [threading.current_thread()]
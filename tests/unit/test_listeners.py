@@ -0,0 +1,104 @@
+"""ABOUTME: Test listening on unix sockets and on sockets passed in by systemd
+ABOUTME: Sockets are real but local: temp-directory paths and loopback ports"""
+
+import os
+import socket
+import stat
+from pathlib import Path
+
+import pytest
+from flask import Flask
+
+from listeners import (
+    SD_LISTEN_FDS_START,
+    make_socket_server,
+    socket_address,
+    systemd_listen_fd,
+)
+
+
+# Test constants
+PID = 4242
+
+
+class TestSystemdListenFd:
+    """Test the sd_listen_fds handshake"""
+
+    def test_socket_for_this_process(self) -> None:
+        """Test the first passed descriptor is used and the variables cleared"""
+        environ = {'LISTEN_PID': str(PID), 'LISTEN_FDS': '1', 'LISTEN_FDNAMES': 'web'}
+
+        assert systemd_listen_fd(environ, PID) == SD_LISTEN_FDS_START
+        assert environ == {}
+
+    def test_other_process_ignored(self) -> None:
+        """Test sockets meant for another process (a parent) are left alone"""
+        environ = {'LISTEN_PID': '1', 'LISTEN_FDS': '1'}
+
+        assert systemd_listen_fd(environ, PID) is None
+        assert environ == {'LISTEN_PID': '1', 'LISTEN_FDS': '1'}
+
+    def test_no_sockets(self) -> None:
+        """Test nothing passed (or a garbled count) means no socket"""
+        for count in ('0', 'two', ''):
+            environ = {'LISTEN_PID': str(PID), 'LISTEN_FDS': count}
+
+            assert systemd_listen_fd(environ, PID) is None
+
+
+class TestSocketServer:
+    """Test serving on unix sockets and inherited descriptors"""
+
+    def test_unix_socket(self, tmp_path: Path) -> None:
+        """Test a unix socket is created with the requested mode"""
+        path = str(tmp_path / 'weather.sock')
+
+        server = make_socket_server(Flask(__name__), path, socket_mode=0o600)
+        try:
+            assert stat.S_ISSOCK(os.stat(path).st_mode)
+            assert stat.S_IMODE(os.stat(path).st_mode) == 0o600  # noqa: PLR2004
+        finally:
+            server.server_close()
+
+    def test_stale_socket_replaced(self, tmp_path: Path) -> None:
+        """Test a socket file left by a crashed run doesn't stop startup"""
+        path = tmp_path / 'weather.sock'
+        path.write_text('')
+
+        server = make_socket_server(Flask(__name__), str(path))
+        try:
+            assert stat.S_ISSOCK(os.stat(path).st_mode)
+        finally:
+            server.server_close()
+
+    def test_inherited_tcp_socket(self) -> None:
+        """Test an already bound TCP socket is served on as it is"""
+        listener = socket.socket()
+        listener.bind(('127.0.0.1', 0))
+        listener.listen()
+        try:
+            host, port = socket_address(listener.fileno())
+
+            assert host == '127.0.0.1'
+            assert port == listener.getsockname()[1]
+            server = make_socket_server(Flask(__name__), fd=listener.fileno())
+            assert server.server_address[1] == port
+            server.server_close()
+        finally:
+            listener.close()
+
+    def test_inherited_unix_socket(self, tmp_path: Path) -> None:
+        """Test an inherited unix socket is recognized by its family"""
+        path = str(tmp_path / 'activated.sock')
+        listener = socket.socket(socket.AF_UNIX)
+        listener.bind(path)
+        listener.listen()
+        try:
+            assert socket_address(listener.fileno()) == (f'unix://{path}', 0)
+        finally:
+            listener.close()
+
+    def test_nothing_to_listen_on(self) -> None:
+        """Test a path or descriptor is required"""
+        with pytest.raises(ValueError, match='socket'):
+            make_socket_server(Flask(__name__))
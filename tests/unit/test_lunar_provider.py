@@ -3,7 +3,7 @@
 
 from datetime import datetime, timezone
 
-from weather_providers import LunarDataProvider
+from weather_core.providers import LunarDataProvider
 
 
 # Test constants to avoid magic numbers
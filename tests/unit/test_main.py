@@ -1,23 +1,97 @@
 import json
+import os
+import socket
+import sqlite3
+import struct
+import tarfile
+import time
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
 from typing import Any
 from unittest.mock import MagicMock, patch
 
+import requests
+
+import main
+from analytics import UsageAnalytics
+from cities import DEFAULT_CITIES
+from config import ConfigFile
+from eink import MAX_SIZE
+from history import HistoryStore, solar_day
 from main import (
     CHICAGO_LAT,
     CHICAGO_LON,
     CITY_COORDS,
+    PREFETCH_LEAD_SECONDS,
+    alert_banners,
+    alerts_cache,
+    asset_manifest,
+    bin_history_points,
+    check_clock_skew,
+    check_notification_rules,
+    check_webhooks,
+    city_registry,
+    clock_status,
+    cloud_cover_cache,
+    corridor_in_prewarm_window,
+    detect_new_alerts,
+    digest_locations,
+    escape_prometheus_label,
+    fold_ical_line,
+    geocode_cache,
+    geoip_cache,
+    grafana_targets,
+    ha_condition,
+    history_cache,
+    kiosk_interval,
+    load_commute_corridors,
+    tide_cache,
+    tide_station_cache,
+    marine_cache,
+    get_archive_from_open_meteo,
     get_weather_description,
     get_weather_from_open_meteo,
     get_weather_icon,
     map_open_meteo_weather_code,
+    normalize_city_name,
+    prefetch_locations,
+    prewarm_corridor,
     process_open_meteo_data,
+    query_ntp_offset,
+    provider_probe_cache,
+    publish_mqtt_weather,
+    publish_weather_update,
+    refresh_stale_weather,
+    reload_configuration,
+    resolve_city_key,
+    send_morning_digests,
+    store_weather_data,
     weather_cache,
+    weather_cache_key,
+    weather_cache_metadata,
+    weather_manager,
+    weather_change_baselines,
+    record_weather_change,
+    weather_change_history,
+    weather_refreshes_in_flight,
+    weather_section_caches,
+    weather_stream_subscribers,
 )
+from notifications import NotificationRules
+from postal import PostalIndex
+from ratelimit import RateLimiter
+from station import StationNetwork, parse_stations
+from storage import FavoritesStore
+from tokens import TokenStore, parse_api_keys
+from webhooks import WebhookSubscriptions
+from weather_core.providers import MockProvider
+from weather_core.tracing import OtlpExporter
 
 
 # Test constants
 HTTP_OK = 200
 HTTP_BAD_REQUEST = 400
+HTTP_FOUND = 302
 HTTP_NOT_FOUND = 404
 HTTP_INTERNAL_SERVER_ERROR = 500
 MOCK_TEMP = 72
@@ -27,6 +101,7 @@ MOCK_WIND_SPEED = 8
 MOCK_UV_INDEX = 6
 CACHE_MAX_SIZE = 100
 CACHE_TTL_SECONDS = 180
+OPEN_METEO_CACHE_TTL = 900  # OpenMeteo's own cache_ttl, used for mock data
 EXPECTED_COORDS_COUNT = 3
 EXPECTED_KEY_PARTS = 2
 MIN_LAT = -90
@@ -34,6 +109,49 @@ MAX_LAT = 90
 MIN_LON = -180
 MAX_LON = 180
 TEMP_TRENDS_CACHE_TTL = 900  # 15 minutes
+STREAM_UPDATED_TEMP = 50
+HISTORY_HOURS = 12
+SIX_HOURS = 6 * 3600
+EXPECTED_SIX_HOUR_BUCKETS = 2
+ICAL_LINE_OCTETS = 75
+SWING_TEMP = 85
+SMALL_CHANGE_TEMP = 78
+EXPECTED_FEED_ENTRIES = 2
+WATCH_PAYLOAD_BUDGET = 500
+CLEAR_DAY_ICON_ID = 1
+RAIN_IN_TWO_HOURS = 120
+WIDGET_MEDIUM_HOURS = 6
+WIDGET_SMALL_HOURS = 3
+EXPECTED_CSV_LINES = 3  # Header plus two hourly rows
+CORRIDOR_POINTS = 2
+HTTP_NOT_MODIFIED = 304
+CACHED_ENTRY_AGE = 60
+HTTP_SERVICE_UNAVAILABLE = 503
+NTP_EPOCH_OFFSET = 2208988800
+CLOCK_SKEW = 30
+CLOCK_SKEW_TOLERANCE = 0.5
+HTTP_CREATED = 201
+HTTP_UNAUTHORIZED = 401
+HTTP_FORBIDDEN = 403
+BOOTSTRAP_TOKEN = 'bootstrap-admin-token'
+GRAFANA_START_MS = 1704067200000  # 2024-01-01T00:00:00Z
+HOUR_MS = 3600 * 1000
+ANALYTICS_CHICAGO_HITS = 2
+ASTRO_CLOUD_COVER = 45
+GRAFANA_RANGE_HOURS = 3
+MV_LAT = 37.386
+MV_LON = -122.0838
+HTTP_BAD_GATEWAY = 502
+HTTP_CONFLICT = 409
+HTTP_TOO_MANY_REQUESTS = 429
+HTTP_PAYLOAD_TOO_LARGE = 413
+SF_TIDE_STATION = {
+    'id': '9414290',
+    'name': 'San Francisco',
+    'state': 'CA',
+    'lat': 37.8063,
+    'lon': -122.4659,
+}
 
 
 class TestUtilityFunctions:
@@ -319,10 +437,13 @@ class TestWeatherAPIEndpoint:
         mock_get_weather.return_value = None
 
         response = client.get('/api/weather?lat=41.8781&lon=-87.6298')
-        assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+        assert response.status_code == HTTP_SERVICE_UNAVAILABLE
 
         data = json.loads(response.data)
-        assert 'error' in data
+        assert data['code'] == 'upstream_unavailable'
+        assert data['retryable'] is True
+        assert data['provider'] is None
+        assert data['message'] == data['error']
 
     @patch('main.weather_cache')
     @patch('main.weather_manager.get_weather')
@@ -371,246 +492,4448 @@ class TestWeatherAPIEndpoint:
         mock_get_weather.assert_called_once()
 
         # Verify data was cached
-        mock_cache.__setitem__.assert_called_once()
-
-
-class TestCityCoords:
-    """Test city coordinates constant"""
-
-    def test_city_coords_structure(self) -> None:
-        """Test that CITY_COORDS has the expected structure"""
-        assert isinstance(CITY_COORDS, dict)
-        assert 'chicago' in CITY_COORDS
-        assert 'nyc' in CITY_COORDS
-        assert 'sf' in CITY_COORDS
+        mock_cache.set.assert_called_once()
 
-        # Test chicago coordinates
-        chicago_data = CITY_COORDS['chicago']
-        assert len(chicago_data) == EXPECTED_COORDS_COUNT
-        assert chicago_data[0] == CHICAGO_LAT  # lat
-        assert chicago_data[1] == CHICAGO_LON  # lon
-        assert chicago_data[2] == 'Chicago'  # name
+    @patch.dict('main.weather_manager.providers', {'Mock': MockProvider(seed=7)})
+    @patch('main.store_weather_data')
+    def test_scenario_simulated_and_not_cached(
+        self, mock_store: MagicMock, client: Any
+    ) -> None:
+        """Test ?scenario= answers from the mock provider and is never stored"""
+        response = client.get(
+            '/api/weather?lat=41.8781&lon=-87.6298&location=Chicago&scenario=blizzard'
+        )
 
-    def test_all_cities_have_valid_coords(self) -> None:
-        """Test that all cities have valid coordinate data"""
-        for coords in CITY_COORDS.values():
-            assert len(coords) == EXPECTED_COORDS_COUNT
-            assert isinstance(coords[0], int | float)  # latitude
-            assert isinstance(coords[1], int | float)  # longitude
-            assert isinstance(coords[2], str)  # city name
+        assert response.status_code == HTTP_OK
+        data = json.loads(response.data)
+        assert data['scenario'] == 'blizzard'
+        assert data['current']['precipitation_type'] == 'snow'
+        assert data['location'] == 'Chicago'
+        assert response.headers['Cache-Control'] == 'no-store'
+        mock_store.assert_not_called()
 
-            # Check coordinate ranges
-            assert MIN_LAT <= coords[0] <= MAX_LAT  # latitude range
-            assert MIN_LON <= coords[1] <= MAX_LON  # longitude range
+    def test_scenario_needs_mock_provider(self, client: Any) -> None:
+        """Test scenarios are refused without the mock provider, or if unknown"""
+        response = client.get('/api/weather?lat=41.8781&lon=-87.6298&scenario=blizzard')
+        assert response.status_code == HTTP_BAD_REQUEST
+        assert 'MOCK_WEATHER' in json.loads(response.data)['error']
 
+        with patch.dict('main.weather_manager.providers', {'Mock': MockProvider()}):
+            response = client.get('/api/weather?lat=41.8781&lon=-87.6298&scenario=x')
+        assert response.status_code == HTTP_BAD_REQUEST
+        assert 'heat_wave' in json.loads(response.data)['scenarios']
 
-class TestCacheIntegration:
-    """Test cache integration"""
 
-    def test_cache_is_initialized(self) -> None:
-        """Test that weather cache is properly initialized"""
-        assert weather_cache is not None
-        assert weather_cache.maxsize == CACHE_MAX_SIZE
-        assert weather_cache.ttl == CACHE_TTL_SECONDS  # 3 minutes for real-time updates
+def read_sse_event(stream: Any) -> dict[str, Any]:
+    """Read the next non-keepalive Server-Sent Event from a streamed response"""
+    while True:
+        chunk = next(stream)
+        text = chunk.decode() if isinstance(chunk, bytes) else chunk
+        if text.startswith(':'):
+            continue
+        event_line, data_line = text.strip().split('\n', 1)
+        return {
+            'event': event_line.removeprefix('event: '),
+            'data': json.loads(data_line.removeprefix('data: ')),
+        }
 
-    def test_cache_key_format(self) -> None:
-        """Test cache key format"""
-        # Test key format matches expected pattern
-        lat = round(CHICAGO_LAT, 4)
-        lon = round(CHICAGO_LON, 4)
-        expected_key = f'{lat},{lon}'
 
-        assert isinstance(expected_key, str)
-        assert ',' in expected_key
-        assert len(expected_key.split(',')) == EXPECTED_KEY_PARTS
+class TestWeatherStreamAPI:
+    """Test the Server-Sent Events weather stream endpoint"""
 
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
 
-class TestPWARoutes:
-    """Test Progressive Web App routes and functionality"""
+    @patch('main.weather_manager.get_weather')
+    def test_stream_sends_initial_and_pushed_updates(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the stream sends current data, then pushes published updates"""
+        mock_get_weather.return_value = mock_weather_data
 
-    def test_service_worker_route(self, client: Any) -> None:
-        """Test service worker route serves the correct file"""
-        response = client.get('/sw.js')
+        response = client.get(
+            '/api/weather/stream?lat=41.8781&lon=-87.6298&location=Chicago',
+            buffered=False,
+        )
         assert response.status_code == HTTP_OK
-        assert response.content_type == 'text/javascript'
-        assert b'Service Worker' in response.data
-        # Check security headers
-        assert response.headers.get('X-Content-Type-Options') == 'nosniff'
-        assert response.headers.get('X-Frame-Options') == 'DENY'
+        assert response.mimetype == 'text/event-stream'
+        assert response.headers['Cache-Control'] == 'no-cache'
 
-    def test_manifest_route(self, client: Any) -> None:
-        """Test manifest.json route serves the correct file"""
-        response = client.get('/manifest.json')
-        assert response.status_code == HTTP_OK
-        assert response.content_type == 'application/manifest+json'
+        stream = iter(response.response)
+        first = read_sse_event(stream)
+        assert first['event'] == 'weather'
+        assert first['data']['location'] == 'Chicago'
+        assert first['data']['current']['temperature'] == MOCK_TEMP
 
-        # Parse the manifest to ensure it's valid JSON
-        manifest_data = json.loads(response.data)
-        assert 'name' in manifest_data
-        assert 'short_name' in manifest_data
-        assert 'start_url' in manifest_data
+        # The stream should now be subscribed to this location
+        cache_key = '41.8781,-87.6298'
+        assert weather_stream_subscribers.get(cache_key)
 
+        updated = {**mock_weather_data, 'current': {'temperature': STREAM_UPDATED_TEMP}}
+        publish_weather_update(cache_key, updated)
 
-class TestTemperatureTrendsAPI:
-    """Test the temperature trends API endpoint"""
+        second = read_sse_event(stream)
+        assert second['event'] == 'weather'
+        assert second['data']['current']['temperature'] == STREAM_UPDATED_TEMP
+
+        response.close()
+        assert cache_key not in weather_stream_subscribers
 
-    @patch('main.temperature_trends_cache')
-    @patch('main.temperature_trends_provider.process_weather_data')
     @patch('main.weather_manager.get_weather')
-    def test_temperature_trends_api_success(
+    def test_stream_uses_cached_data(
         self,
         mock_get_weather: MagicMock,
-        mock_process_trends: MagicMock,  # noqa: ARG002
-        mock_cache: MagicMock,
         client: Any,
+        mock_weather_data: dict[str, Any],
     ) -> None:
-        """Test successful temperature trends API call"""
-        # Mock cache miss
-        mock_cache.__contains__.return_value = False
+        """Test the stream serves cached data without an upstream fetch"""
+        weather_cache[weather_cache_key('41.8781,-87.6298')] = mock_weather_data
 
-        # Mock weather data
-        mock_weather_data = {
-            'current': {'temperature': 75, 'humidity': 60, 'wind_speed': 8},
-            'hourly': [
-                {'temp': 75, 't': '12pm'},
-                {'temp': 77, 't': '1pm'},
-                {'temp': 79, 't': '2pm'},
-            ],
-            'daily': [{'h': 85, 'l': 70}],
-        }
-        mock_get_weather.return_value = mock_weather_data
+        response = client.get(
+            '/api/weather/stream?lat=41.8781&lon=-87.6298', buffered=False
+        )
+        event = read_sse_event(iter(response.response))
+        response.close()
 
-        # Mock trends data
-        mock_trends_data = {
-            'provider': 'EnhancedTemperatureTrendProvider',
-            'location_name': 'Test Location',
-            'temperature_trends': {
-                'hourly_data': [
-                    {
-                        'temperature': 75,
-                        'apparent_temperature': 76,
-                        'confidence_lower': 74,
-                        'confidence_upper': 76,
-                        'uncertainty': 1.0,
-                    }
-                ],
-                'statistics': {
-                    'temperature': {'min': 75, 'max': 79, 'mean': 77},
-                    'apparent_temperature': {'min': 76, 'max': 80, 'mean': 78},
-                },
-                'comfort_analysis': {
-                    'categories': {'comfortable': 3, 'hot': 0, 'cool': 0, 'cold': 0},
-                    'percentages': {'comfortable': 100, 'hot': 0, 'cool': 0, 'cold': 0},
-                    'primary_comfort': 'comfortable',
-                },
-                'trend_analysis': {
-                    'trend_direction': 'warming',
-                    'temperature_change_24h': 4,
-                    'volatility': 2.0,
-                },
-                'percentile_bands': {
-                    '10th_percentile': 60,
-                    '50th_percentile': 75,
-                    '90th_percentile': 90,
-                    'data_source': 'estimated',
-                },
-                'current': {
-                    'temperature': 75,
-                    'apparent_temperature': 76,
-                    'comfort_category': 'comfortable',
-                },
-            },
-        }
-        mock_process_trends.return_value = mock_trends_data
+        assert event['event'] == 'weather'
+        mock_get_weather.assert_not_called()
+
+    @patch('main.weather_manager.get_weather')
+    def test_stream_reports_fetch_failure(
+        self, mock_get_weather: MagicMock, client: Any
+    ) -> None:
+        """Test the stream emits an error event when no data is available"""
+        mock_get_weather.return_value = None
 
         response = client.get(
-            '/api/temperature-trends?lat=41.8781&lon=-87.6298&location=Test'
+            '/api/weather/stream?lat=41.8781&lon=-87.6298', buffered=False
         )
-        assert response.status_code == HTTP_OK
+        event = read_sse_event(iter(response.response))
+        response.close()
 
-        data = json.loads(response.data)
-        assert data['provider'] == 'EnhancedTemperatureTrendProvider'
-        assert 'temperature_trends' in data
+        assert event['event'] == 'weather_error'
+        assert 'error' in event['data']
 
-        trends = data['temperature_trends']
-        assert 'hourly_data' in trends
-        assert 'statistics' in trends
-        assert 'comfort_analysis' in trends
-        assert 'trend_analysis' in trends
-        assert 'percentile_bands' in trends
-        assert 'current' in trends
 
-        # Check cache headers
-        assert 'Cache-Control' in response.headers
-        assert 'ETag' in response.headers
-        assert response.headers['X-Cache'] == 'MISS'
+class TestTextWeatherRoute:
+    """Test the plain-text /txt endpoint for curl users"""
 
-    @patch('main.temperature_trends_cache')
-    def test_temperature_trends_api_cache_hit(
-        self, mock_cache: MagicMock, client: Any
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_full_report(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
     ) -> None:
-        """Test temperature trends API cache hit"""
-        # Mock cache hit
-        mock_cache.__contains__.return_value = True
-        mock_cached_data = {
-            'provider': 'EnhancedTemperatureTrendProvider',
-            'temperature_trends': {
-                'hourly_data': [],
-                'statistics': {},
-                'comfort_analysis': {},
-                'trend_analysis': {},
-                'percentile_bands': {},
-                'current': {},
-            },
-        }
-        mock_cache.__getitem__.return_value = mock_cached_data
+        """Test the default report shows current conditions and forecast"""
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get('/txt/chicago?ansi=0')
+        text = response.get_data(as_text=True)
 
-        response = client.get('/api/temperature-trends?lat=41.8781&lon=-87.6298')
         assert response.status_code == HTTP_OK
+        assert response.mimetype == 'text/plain'
+        assert text.startswith('Weather report: Chicago\n')
+        assert '+72°F (feels like +75°F)' in text
+        assert 'Mon   +77°F  +65°F  ☀️ Clear' in text
+        assert '\033[' not in text
 
-        data = json.loads(response.data)
-        assert data['provider'] == 'EnhancedTemperatureTrendProvider'
+    @patch('main.weather_manager.get_weather')
+    def test_ansi_colors_by_default(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test terminal colors are on unless ansi=0"""
+        mock_get_weather.return_value = mock_weather_data
 
-        # Should indicate cache hit
-        assert response.headers['X-Cache'] == 'HIT'
+        text = client.get('/txt/chicago').get_data(as_text=True)
 
-    def test_temperature_trends_api_default_params(self, client: Any) -> None:
-        """Test temperature trends API with default parameters"""
-        # Should use Chicago coordinates by default
-        response = client.get('/api/temperature-trends')
-        # May succeed or fail depending on weather data availability
-        assert response.status_code in (HTTP_OK, HTTP_INTERNAL_SERVER_ERROR)
+        assert '\033[33m+72°F\033[0m' in text
 
-    def test_temperature_trends_api_invalid_coordinates(self, client: Any) -> None:
-        """Test temperature trends API with invalid coordinates"""
-        response = client.get('/api/temperature-trends?lat=invalid&lon=invalid')
-        assert response.status_code == HTTP_BAD_REQUEST
+    @patch('main.weather_manager.get_weather')
+    def test_metric_units(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test ?units=metric converts readings and keeps colors by temperature"""
+        mock_get_weather.return_value = mock_weather_data
 
-        data = json.loads(response.data)
-        assert 'error' in data
+        report = client.get('/txt/chicago?units=metric').get_data(as_text=True)
+        line = client.get('/txt/chicago?units=metric&format=%t+%w').get_data(
+            as_text=True
+        )
+
+        assert '\033[33m+22°C\033[0m (feels like' in report
+        assert '+25°C' in report
+        assert '°F' not in report
+        assert line.startswith('+22°C ')
+        assert line.rstrip().endswith('km/h')
 
-    @patch('main.temperature_trends_cache')
-    @patch('main.temperature_trends_provider.process_weather_data')
     @patch('main.weather_manager.get_weather')
-    def test_temperature_trends_api_weather_failure(
+    def test_format_presets_and_templates(
         self,
         mock_get_weather: MagicMock,
-        mock_process_trends: MagicMock,  # noqa: ARG002
-        mock_cache: MagicMock,
         client: Any,
+        mock_weather_data: dict[str, Any],
     ) -> None:
-        """Test temperature trends API when weather data fails"""
-        # Mock cache miss
-        mock_cache.__contains__.return_value = False
+        """Test ?format= presets and custom %-templates"""
+        mock_get_weather.return_value = mock_weather_data
 
-        # Mock weather data failure
-        mock_get_weather.return_value = None
+        preset = client.get('/txt/chicago?format=3').get_data(as_text=True)
+        assert preset == 'Chicago: ☀️ +72°F\n'
 
-        response = client.get('/api/temperature-trends?lat=41.8781&lon=-87.6298')
-        assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+        custom = client.get('/txt/chicago?format=%C+%h+%25%25+%x').get_data(
+            as_text=True
+        )
+        assert custom == 'Clear sky 65% % %x\n'
 
-        data = json.loads(response.data)
-        assert 'error' in data
+    @patch('main.weather_manager.get_weather')
+    def test_coordinates(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test coordinates resolve without the geocoder"""
+        mock_get_weather.return_value = mock_weather_data
+
+        text = client.get('/txt/40.0,-75.0?format=%l').get_data(as_text=True)
+
+        assert text == '40.0000,-75.0000\n'
+
+    @patch('main.geocode_city')
+    def test_unknown_location(self, mock_geocode: MagicMock, client: Any) -> None:
+        """Test unknown places return a plain-text 404"""
+        mock_geocode.return_value = None
+
+        response = client.get('/txt/nowhere')
+
+        assert response.status_code == HTTP_NOT_FOUND
+        assert response.mimetype == 'text/plain'
+
+
+class TestRenderPNG:
+    """Test the e-ink PNG render of a city's forecast"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_png(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the frame is a PNG of the requested size, 1-bit unless grayscale=1"""
+        mock_get_weather.return_value = mock_weather_data
+
+        one_bit = client.get('/render/chicago.png?width=400&height=300')
+        gray = client.get('/render/chicago.png?grayscale=1')
+
+        assert one_bit.status_code == HTTP_OK
+        assert one_bit.mimetype == 'image/png'
+        # IHDR: width and height as 4-byte big-endian ints, then the bit depth
+        assert one_bit.data[16:25] == bytes.fromhex('00000190 0000012c 01')
+        assert gray.data[16:25] == bytes.fromhex('00000320 000001e0 08')
+
+    @patch('main.geocode_city', return_value=None)
+    def test_rejects(self, mock_geocode: MagicMock, client: Any) -> None:
+        """Test unknown cities and unusable sizes are refused before fetching"""
+        unknown = client.get('/render/nowhere.png')
+        too_big = client.get('/render/chicago.png?width=5000')
+
+        assert unknown.status_code == HTTP_NOT_FOUND
+        assert unknown.get_json()['code'] == 'location_not_found'
+        assert too_big.status_code == HTTP_BAD_REQUEST
+        assert too_big.get_json()['code'] == 'invalid_parameter'
+        assert too_big.get_json()['max'] == MAX_SIZE
+
+
+class TestHistoryBinnedAPI:
+    """Test the pre-binned weather history endpoint"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        history_cache.clear()
+
+    def history_points(self) -> list[tuple[datetime, float]]:
+        """Build twelve hourly readings from 60 to 71 starting at midnight UTC"""
+        start = datetime(2024, 1, 1, tzinfo=timezone.utc)
+        return [
+            (start + timedelta(hours=hour), 60.0 + hour)
+            for hour in range(HISTORY_HOURS)
+        ]
+
+    def test_bin_history_points(self) -> None:
+        """Test readings are aggregated into min/max/avg buckets"""
+        buckets = bin_history_points(self.history_points(), SIX_HOURS)
+
+        assert len(buckets) == EXPECTED_SIX_HOUR_BUCKETS
+        assert buckets[0] == {
+            'start': '2024-01-01T00:00:00+00:00',
+            'min': 60.0,
+            'max': 65.0,
+            'avg': 62.5,
+            'count': 6,
+        }
+        assert buckets[1]['start'] == '2024-01-01T06:00:00+00:00'
+
+    def stored(self, tmp_path: Path) -> HistoryStore:
+        """A store with a pressure reading in each of the last twelve hours"""
+        store = HistoryStore(str(tmp_path / 'history.db'))
+        now = time.time()
+        for hour in range(HISTORY_HOURS):
+            store.record(
+                '41.8781,-87.6298',
+                41.8781,
+                -87.6298,
+                'OpenMeteo',
+                {'pressure': 1000.0 + hour},
+                now - hour * 3600 - 60,
+            )
+        return store
+
+    @patch('main.get_archive_from_open_meteo', return_value={})
+    def test_binned_endpoint(
+        self, mock_archive: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test the endpoint bins the stored history and caches the result"""
+        url = '/api/history/binned?lat=41.8781&lon=-87.6298&metric=pressure&bucket=6h'
+
+        with patch('main.history_store', self.stored(tmp_path)):
+            response = client.get(url)
+            data = json.loads(response.data)
+
+            assert response.status_code == HTTP_OK
+            assert response.headers['X-Cache'] == 'MISS'
+            assert data['metric'] == 'pressure'
+            assert sum(bucket['count'] for bucket in data['buckets']) == HISTORY_HOURS
+            assert data['buckets'][-1]['min'] == 1000.0  # noqa: PLR2004
+
+            assert client.get(url).headers['X-Cache'] == 'HIT'
+            mock_archive.assert_called_once()
+
+    def test_invalid_bucket(self, client: Any) -> None:
+        """Test unknown metrics or bucket sizes are rejected"""
+        response = client.get('/api/history/binned?bucket=5m')
+        assert response.status_code == HTTP_BAD_REQUEST
+
+        response = client.get('/api/history/binned?metric=mood')
+        assert response.status_code == HTTP_BAD_REQUEST
+        data = response.get_json()
+        assert data['code'] == 'invalid_parameter'
+        assert 'temperature' in data['metrics']
+
+    @patch('main.get_archive_from_open_meteo', return_value=None)
+    def test_archive_down(
+        self, mock_archive: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test stored hours are still binned when the archive can't be reached"""
+        with patch('main.history_store', self.stored(tmp_path)):
+            response = client.get('/api/history/binned?metric=pressure&bucket=1d')
+
+        assert response.status_code == HTTP_OK
+        assert sum(
+            bucket['count'] for bucket in response.get_json()['buckets']
+        ) == HISTORY_HOURS
+
+
+class TestHistoryAPI:
+    """Test hourly and daily history from the store, topped up from the archive"""
+
+    start = datetime(2024, 1, 1, tzinfo=timezone.utc)
+    url = '/api/history?lat=41.8781&lon=-87.6298&start=2024-01-01&end=2024-01-01T02:59'
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        history_cache.clear()
+
+    def stored(self, tmp_path: Path) -> HistoryStore:
+        """A store with snapshots in the first two hours of the range"""
+        store = HistoryStore(str(tmp_path / 'history.db'))
+        for minutes, temperature in ((10, 60), (40, 62), (70, 65)):
+            store.record(
+                '41.8781,-87.6298',
+                41.8781,
+                -87.6298,
+                'OpenMeteo',
+                {'temperature': temperature},
+                (self.start + timedelta(minutes=minutes)).timestamp(),
+            )
+        return store
+
+    @patch('main.get_archive_from_open_meteo')
+    def test_local_hours_then_archive(
+        self, mock_archive: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test stored hours are averaged and only missing ones are fetched"""
+        third_hour = int((self.start + timedelta(hours=2)).timestamp())
+        mock_archive.return_value = {third_hour: {'temperature': 58.0}}
+
+        with patch('main.history_store', self.stored(tmp_path)):
+            data = client.get(self.url).get_json()
+
+        assert mock_archive.call_args[0][2:] == ('2024-01-01', '2024-01-01')
+        assert data['sources'] == {'local': 2, 'archive': 1}
+        assert [(point['time'], point['temperature']) for point in data['points']] == [
+            ('2024-01-01T00:00:00+00:00', 61.0),
+            ('2024-01-01T01:00:00+00:00', 65.0),
+            ('2024-01-01T02:00:00+00:00', 58.0),
+        ]
+        assert [point['source'] for point in data['points']] == [
+            'local',
+            'local',
+            'archive',
+        ]
+        assert data['units']['pressure'] == 'hPa'
+
+    @patch('main.get_archive_from_open_meteo')
+    def test_daily_granularity(
+        self, mock_archive: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test hours roll up into a day, served locally when the archive is down"""
+        mock_archive.return_value = None
+
+        with patch('main.history_store', self.stored(tmp_path)):
+            data = client.get(f'{self.url}&granularity=day').get_json()
+
+        (day,) = data['points']
+        assert day['date'] == '2024-01-01'
+        assert (day['temperature_min'], day['temperature_max']) == (61.0, 65.0)
+        assert day['hours'] == 2  # noqa: PLR2004
+
+    @patch('main.get_archive_from_open_meteo')
+    def test_compacted_days(
+        self, mock_archive: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test days compacted out of the snapshots are served from summaries"""
+        store = self.stored(tmp_path)
+        store.retention_days = 1
+        store.compact(self.start.timestamp() + 3 * 86400)
+        mock_archive.return_value = {}
+
+        with patch('main.history_store', store):
+            data = client.get(f'{self.url}&granularity=day').get_json()
+
+        mock_archive.assert_not_called()
+        (day,) = data['points']
+        assert (day['temperature_min'], day['temperature_max']) == (61.0, 65.0)
+        assert data['sources'] == {'local': 2, 'archive': 0}
+
+    def test_invalid_queries(self, client: Any) -> None:
+        """Test bad granularities, unreadable or reversed ranges are rejected"""
+        for query in (
+            'granularity=minute',
+            'start=yesterday',
+            'start=2024-02-01&end=2024-01-01',
+            'start=2020-01-01&end=2024-01-01',
+        ):
+            response = client.get(f'/api/history?{query}')
+            assert response.status_code == HTTP_BAD_REQUEST, query
+
+    @patch('main.get_archive_from_open_meteo', return_value=None)
+    def test_location_fallback(
+        self, mock_archive: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test unusable coordinates use the default chain, not a silent Chicago"""
+        query = 'start=2024-01-01&end=2024-01-01T02:59'
+        with patch('main.history_store', self.stored(tmp_path)):
+            with patch('main.DEFAULT_LOCATION', 'chicago'):
+                data = client.get(f'/api/history?lat=91&lon=0&{query}').get_json()
+            with patch('main.DEFAULT_LOCATION', ''):
+                missing = client.get(f'/api/history?{query}')
+
+        assert data['location_fallback']['reason'] == 'invalid_coordinates'
+        assert data['sources']['local'] == 2  # noqa: PLR2004
+        assert missing.status_code == HTTP_BAD_REQUEST
+        assert missing.get_json()['code'] == 'invalid_location'
+
+    @patch('main.requests.get')
+    def test_archive_normalized(self, mock_get: MagicMock) -> None:
+        """Test archive hours keep their units and hours not yet analysed are dropped"""
+        mock_get.return_value.json.return_value = {
+            'hourly': {
+                'time': ['2024-01-01T00:00', '2024-01-01T01:00'],
+                'temperature_2m': [30.5, None],
+                'pressure_msl': [1013.2, None],
+            }
+        }
+
+        points = get_archive_from_open_meteo(41.88, -87.63, '2024-01-01', '2024-01-01')
+
+        assert points == {
+            int(self.start.timestamp()): {
+                'temperature': 30.5,
+                'feels_like': None,
+                'dew_point': None,
+                'humidity': None,
+                'wind_speed': None,
+                'wind_gust': None,
+                'wind_direction': None,
+                'pressure': 1013.2,
+                'precipitation': None,
+            }
+        }
+        assert mock_get.call_args.kwargs['params']['start_date'] == '2024-01-01'
+
+
+class TestProviderAccuracyAPI:
+    """Test providers are scored on their kept next-day forecasts"""
+
+    @patch('main.history_hours')
+    def test_scores_verified_days(
+        self, mock_hours: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test forecasts are checked against the hours of their local day"""
+        target = (datetime.now(timezone.utc) - timedelta(days=3)).date().isoformat()
+        store = HistoryStore(str(tmp_path / 'history.db'))
+        for provider, high in (('PirateWeather', 77), ('OpenMeteo', 71)):
+            store.record_forecast(
+                '41.8781,-87.6298', provider, {'date': target, 'h': high, 'rain': 50}
+            )
+        start, _ = solar_day(target, -87.6298)
+        mock_hours.return_value = {
+            start + hour * 3600: {'temperature': 50.0 + hour, 'precipitation': 0.0}
+            for hour in range(24)
+        }
+
+        with patch('main.history_store', store):
+            response = client.get('/api/providers/accuracy?lat=41.8781&lon=-87.6298')
+        data = response.get_json()
+
+        assert data['verified_days'] == 1
+        assert [score['provider'] for score in data['providers']] == [
+            'OpenMeteo',
+            'PirateWeather',
+        ]
+        assert data['providers'][0]['high_mae'] == 2.0  # noqa: PLR2004
+        assert data['providers'][0]['precipitation_brier'] == 0.25  # noqa: PLR2004
+
+    def test_no_forecasts_yet(self, client: Any, tmp_path: Path) -> None:
+        """Test a location with nothing kept has no scores, without fetching"""
+        with (
+            patch('main.history_store', HistoryStore(str(tmp_path / 'h.db'))),
+            patch('main.history_hours') as mock_hours,
+            patch('main.DEFAULT_LOCATION', 'chicago'),
+        ):
+            data = client.get('/api/providers/accuracy').get_json()
+
+        assert data['providers'] == []
+        assert data['location_fallback']['reason'] == 'missing'
+        mock_hours.assert_not_called()
+
+    def test_exhausted_chain_is_an_error(self, client: Any) -> None:
+        """Test no location and no default is a 400 rather than Chicago's scores"""
+        with patch('main.DEFAULT_LOCATION', ''):
+            response = client.get('/api/providers/accuracy')
+
+        assert response.status_code == HTTP_BAD_REQUEST
+        assert response.get_json()['code'] == 'invalid_location'
+
+
+class TestHistoryExportAPI:
+    """Test downloading the history store as CSV or Parquet"""
+
+    def stored(self, tmp_path: Path) -> HistoryStore:
+        """A store with a snapshot each in Chicago and New York"""
+        store = HistoryStore(str(tmp_path / 'h.db'))
+        for location, lat, lon in (
+            ('41.8781,-87.6298', 41.8781, -87.6298),
+            ('40.7128,-74.0060', 40.7128, -74.006),
+        ):
+            store.record(
+                location, lat, lon, 'OpenMeteo', {'temperature': 60}, 1_704_067_200
+            )
+        return store
+
+    def test_csv(self, client: Any, tmp_path: Path) -> None:
+        """Test one location's snapshots as CSV with readable times"""
+        with patch('main.history_store', self.stored(tmp_path)):
+            response = client.get('/api/history/export?lat=41.8781&lon=-87.6298')
+
+        assert response.status_code == HTTP_OK
+        assert response.mimetype == 'text/csv'
+        assert 'history-observations.csv' in response.headers['Content-Disposition']
+        header, row = response.get_data(as_text=True).splitlines()
+        assert header.startswith('location,lat,lon,provider,observed_at,temperature')
+        assert row.startswith(
+            '"41.8781,-87.6298",41.8781,-87.6298,OpenMeteo,'
+            '2024-01-01T00:00:00+00:00,60.0'
+        )
+
+    def test_parquet(self, client: Any, tmp_path: Path) -> None:
+        """Test every location's snapshots as a Parquet file"""
+        with patch('main.history_store', self.stored(tmp_path)):
+            response = client.get('/api/history/export?format=parquet')
+
+        body = response.get_data()
+        assert response.mimetype == 'application/vnd.apache.parquet'
+        assert body[:4] == body[-4:] == b'PAR1'
+        assert b'40.7128,-74.0060' in body
+        assert b'41.8781,-87.6298' in body
+
+    def test_invalid(self, client: Any) -> None:
+        """Test unknown formats and tables are rejected"""
+        for query in ('format=xlsx', 'table=forecasts', 'start=soon'):
+            response = client.get(f'/api/history/export?{query}')
+            assert response.status_code == HTTP_BAD_REQUEST
+
+
+class TestStationAPI:
+    """Test personal weather station uploads reach the history and current"""
+
+    url = '/api/station/observations'
+    cache_key = '41.8781,-87.6298'
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    def stations(self, override_current: bool = True) -> StationNetwork:
+        """A network with one backyard station in Chicago"""
+        stations = StationNetwork()
+        stations.configure(
+            parse_stations(
+                json.dumps(
+                    [
+                        {
+                            'name': 'backyard',
+                            'lat': 41.8781,
+                            'lon': -87.6298,
+                            'key': 'station-key',
+                            'override_current': override_current,
+                        }
+                    ]
+                )
+            )
+        )
+        return stations
+
+    def test_ecowitt_upload_overrides_current(
+        self, client: Any, tmp_path: Path, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test a form upload is stored and laid over the cached current block"""
+        store = HistoryStore(str(tmp_path / 'h.db'))
+        with (
+            patch('main.history_store', store),
+            patch('main.station_network', self.stations()),
+        ):
+            response = client.post(
+                self.url,
+                data={
+                    'PASSKEY': 'station-key',
+                    'tempf': '68.4',
+                    'humidity': '41',
+                    'baromrelin': '29.92',
+                },
+            )
+            store_weather_data(self.cache_key, mock_weather_data)
+            weather = client.get('/api/weather?lat=41.8781&lon=-87.6298').get_json()
+
+        assert response.status_code == HTTP_OK
+        assert response.get_json()['station'] == 'backyard'
+        assert response.get_json()['recorded'] is True
+        (row,) = store.observations(self.cache_key, 0, time.time() + 1, 'local-station')
+        assert (row['temperature'], row['humidity']) == (68.4, 41.0)
+        # Uploads send inHg; the history and current conditions are in hPa
+        assert row['pressure'] == 1013.2  # noqa: PLR2004
+        current = weather['current']
+        assert current['provider'] == 'local-station'
+        assert (current['temperature'], current['humidity']) == (68.4, 41.0)
+        assert current['pressure'] == 1013.2  # noqa: PLR2004
+        assert current['summary'] == 'Clear sky'
+        assert weather['provider'] == 'OpenMeteo'
+
+    def test_json_upload_without_override(
+        self, client: Any, tmp_path: Path, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test a JSON reading is only recorded when the station doesn't override"""
+        store = HistoryStore(str(tmp_path / 'h.db'))
+        with (
+            patch('main.history_store', store),
+            patch('main.station_network', self.stations(override_current=False)),
+        ):
+            response = client.post(
+                self.url,
+                json={'temperature': 70, 'observed_at': '2024-01-31T14:05:00Z'},
+                headers={'X-API-Key': 'station-key'},
+            )
+            store_weather_data(self.cache_key, mock_weather_data)
+            weather = client.get('/api/weather?lat=41.8781&lon=-87.6298').get_json()
+
+        assert response.get_json()['observed_at'] == '2024-01-31T14:05:00+00:00'
+        assert store.observations(self.cache_key, 0, time.time(), 'local-station')
+        assert weather['current']['temperature'] == 72  # noqa: PLR2004
+        assert 'station' not in weather['current']
+
+    def test_rejected_uploads(self, client: Any) -> None:
+        """Test unknown keys get a 401 and unreadable readings a 400"""
+        with patch('main.station_network', self.stations()):
+            unknown = client.get(f'{self.url}?PASSWORD=wrong&tempf=70')
+            unreadable = client.get(f'{self.url}?PASSWORD=station-key&tempf=hot')
+
+        assert unknown.status_code == HTTP_UNAUTHORIZED
+        assert unreadable.status_code == HTTP_BAD_REQUEST
+        assert 'tempf' in unreadable.get_json()['error']
+
+
+class TestTrendsAPI:
+    """Test rolling statistics over the last day of stored observations"""
+
+    url = '/api/trends?lat=41.8781&lon=-87.6298'
+
+    def test_recent_observations_charted(self, client: Any, tmp_path: Path) -> None:
+        """Test stored hours become series with their statistics"""
+        store = HistoryStore(str(tmp_path / 'h.db'))
+        latest = int(time.time()) // 3600 * 3600
+        for hours_ago, temperature in ((3, 50), (2, 52), (1, 54), (0, 56)):
+            store.record(
+                '41.8781,-87.6298',
+                41.8781,
+                -87.6298,
+                'OpenMeteo',
+                {'temperature': temperature, 'pressure': 30.1},
+                latest - hours_ago * 3600,
+            )
+
+        with patch('main.history_store', store):
+            response = client.get(self.url)
+        data = response.get_json()
+
+        assert response.status_code == HTTP_OK
+        assert data['window'] == '24h'
+        assert data['observations'] == 4  # noqa: PLR2004
+        assert data['series']['temperature'] == [50.0, 52.0, 54.0, 56.0]
+        assert data['stats']['temperature']['rate_per_hour'] == 2.0  # noqa: PLR2004
+        assert data['stats']['pressure']['change'] == 0.0
+        assert len(data['uplot']['data'][0]) == 4  # noqa: PLR2004
+
+    def test_invalid_window(self, client: Any) -> None:
+        """Test only the known windows are accepted"""
+        response = client.get(f'{self.url}&window=1y')
+
+        assert response.status_code == HTTP_BAD_REQUEST
+        assert response.get_json()['windows'] == ['24h', '7d']
+
+    def test_location_fallback(self, client: Any, tmp_path: Path) -> None:
+        """Test missing coordinates use the default chain, or a 400 without one"""
+        with patch('main.history_store', HistoryStore(str(tmp_path / 'h.db'))):
+            with patch('main.DEFAULT_LOCATION', 'nyc'):
+                data = client.get('/api/trends').get_json()
+            with patch('main.DEFAULT_LOCATION', ''):
+                missing = client.get('/api/trends')
+
+        assert (data['lat'], data['lon']) == (40.7128, -74.006)
+        assert data['location_fallback']['source'] == 'instance'
+        assert missing.status_code == HTTP_BAD_REQUEST
+
+
+class TestClimatologyAPI:
+    """Test normals and today-vs-normal from the archive-seeded history"""
+
+    url = '/api/climatology?lat=41.8781&lon=-87.6298&month=1'
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        history_cache.clear()
+
+    def archive_days(self) -> dict[str, dict]:
+        """Two years of a mild first week of January"""
+        return {
+            f'{year}-01-0{day}': {
+                'temperature_max': 30.0 + year - 2022,
+                'temperature_min': 20.0,
+                'precipitation_sum': 0.0,
+            }
+            for year in (2022, 2023)
+            for day in range(1, 8)
+        }
+
+    @patch('main.location_weather')
+    @patch('main.get_daily_archive_from_open_meteo')
+    def test_seeds_once_and_compares_today(
+        self,
+        mock_archive: MagicMock,
+        mock_weather: MagicMock,
+        client: Any,
+        tmp_path: Path,
+    ) -> None:
+        """Test the archive seeds the store once and today's high gets a note"""
+        mock_archive.return_value = self.archive_days()
+        mock_weather.return_value = {
+            'daily': [{'date': '2024-01-04', 'h': 36, 'l': 22}]
+        }
+
+        with patch('main.history_store', HistoryStore(str(tmp_path / 'h.db'))):
+            data = client.get(self.url).get_json()
+            client.get(self.url)
+
+        mock_archive.assert_called_once()
+        assert data['normals']['high'] == 30.5  # noqa: PLR2004
+        assert data['records']['high'] == {'value': 31.0, 'date': '2023-01-01'}
+        assert data['today']['normal_high'] == 30.5  # noqa: PLR2004
+        assert data['today']['high_delta'] == 5.5  # noqa: PLR2004
+        assert data['today']['annotation'] == '6° warmer than average'
+        assert data['units']['temperature'] == '°F'
+
+    @patch('main.location_weather')
+    @patch('main.get_daily_archive_from_open_meteo')
+    def test_metric_annotation(
+        self,
+        mock_archive: MagicMock,
+        mock_weather: MagicMock,
+        client: Any,
+        tmp_path: Path,
+    ) -> None:
+        """Test metric visitors get every temperature, and the note, in °C"""
+        mock_archive.return_value = self.archive_days()
+        mock_weather.return_value = {'daily': [{'date': '2024-01-04', 'h': 21}]}
+
+        with patch('main.history_store', HistoryStore(str(tmp_path / 'h.db'))):
+            data = client.get(f'{self.url}&units=metric').get_json()
+
+        assert data['today']['annotation'] == '5° cooler than average'
+        assert data['units'] == {'temperature': '°C', 'precipitation': 'mm'}
+        assert data['normals']['high'] == -0.8  # noqa: PLR2004
+        assert data['records']['high'] == {'value': -0.6, 'date': '2023-01-01'}
+        assert data['today']['normal_high'] == -0.8  # noqa: PLR2004
+        assert data['today']['forecast_high'] == -6.1  # noqa: PLR2004
+        # A difference is scaled, not offset like a reading
+        assert data['today']['high_delta'] == -5.3  # noqa: PLR2004
+
+    @patch('main.location_weather', return_value=None)
+    @patch('main.get_daily_archive_from_open_meteo')
+    def test_location_fallback(
+        self,
+        mock_archive: MagicMock,
+        mock_weather: MagicMock,
+        client: Any,
+        tmp_path: Path,
+    ) -> None:
+        """Test unusable coordinates use the default chain and say so"""
+        mock_archive.return_value = self.archive_days()
+
+        with (
+            patch('main.history_store', HistoryStore(str(tmp_path / 'h.db'))),
+            patch('main.DEFAULT_LOCATION', 'nyc'),
+        ):
+            data = client.get('/api/climatology?lat=north&lon=0').get_json()
+
+        assert data['location_fallback']['reason'] == 'invalid_coordinates'
+        assert mock_weather.call_args[0] == (40.7128, -74.006, 'New York City')
+
+    @patch('main.get_daily_archive_from_open_meteo')
+    def test_archive_down(
+        self, mock_archive: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test nothing to go on is an error, and a bad month is rejected"""
+        mock_archive.return_value = None
+
+        with patch('main.history_store', HistoryStore(str(tmp_path / 'h.db'))):
+            response = client.get(self.url)
+        assert response.status_code == HTTP_BAD_GATEWAY
+        assert response.get_json()['provider'] == 'archive'
+
+        assert client.get('/api/climatology?month=13').status_code == HTTP_BAD_REQUEST
+
+
+class TestProviderTaggedCache:
+    """Test cache entries are refreshed lazily after a provider switch"""
+
+    cache_key = '41.8781,-87.6298'
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+        weather_refreshes_in_flight.clear()
+
+    def test_switch_keeps_cached_entries(
+        self, client: Any, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test switching providers no longer purges the weather cache"""
+        entry_key = weather_cache_key(self.cache_key)
+        store_weather_data(self.cache_key, mock_weather_data)
+
+        response = client.post(
+            '/api/providers/switch',
+            json={'provider': 'OpenMeteo'},
+            content_type='application/json',
+        )
+
+        assert response.status_code == HTTP_OK
+        assert entry_key in weather_cache
+
+    def test_entries_keyed_by_provider(
+        self, client: Any, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test each provider's data for a place is its own entry"""
+        store_weather_data(self.cache_key, mock_weather_data, 'OldProvider')
+        store_weather_data(self.cache_key, mock_weather_data)
+
+        stats = json.loads(client.get('/api/cache/stats').data)
+        assert sorted(stats['weather_cache']['cached_locations']) == [
+            '41.8781,-87.6298:OldProvider:imperial:en',
+            '41.8781,-87.6298:OpenMeteo:imperial:en',
+        ]
+
+    @patch.dict('main.weather_manager.providers', {'OldProvider': MagicMock()})
+    @patch('main.socketio.start_background_task')
+    def test_old_provider_entries_served_stale(
+        self,
+        mock_start_task: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test entries from a previous provider are flagged and refreshed once"""
+        store_weather_data(self.cache_key, mock_weather_data, 'OldProvider')
+
+        url = '/api/weather?lat=41.8781&lon=-87.6298&location=Chicago'
+        first = json.loads(client.get(url).data)
+        second = json.loads(client.get(url).data)
+
+        assert first['stale'] is True
+        assert second['stale'] is True
+        old_key = weather_cache_key(self.cache_key, 'OldProvider')
+        assert 'stale' not in weather_cache[old_key]
+        assert weather_cache_key(self.cache_key) not in weather_cache
+        mock_start_task.assert_called_once()
+        assert mock_start_task.call_args[0][:2] == (
+            refresh_stale_weather,
+            self.cache_key,
+        )
+
+    @patch.dict('main.weather_manager.providers', {'OldProvider': MagicMock()})
+    @patch('main.weather_manager.get_weather')
+    def test_refresh_replaces_stale_entry(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test a background refresh retags the entry with the current provider"""
+        store_weather_data(self.cache_key, mock_weather_data, 'OldProvider')
+        weather_refreshes_in_flight.add(self.cache_key)
+        mock_get_weather.return_value = {**mock_weather_data, 'provider': 'New'}
+
+        refresh_stale_weather(self.cache_key, CHICAGO_LAT, CHICAGO_LON)
+
+        assert weather_cache[weather_cache_key(self.cache_key)]['provider'] == 'New'
+        assert weather_cache_key(self.cache_key, 'OldProvider') not in weather_cache
+        assert self.cache_key not in weather_refreshes_in_flight
+
+        data = json.loads(client.get('/api/weather').data)
+        assert 'stale' not in data
+        stats = json.loads(client.get('/api/cache/stats').data)
+        assert stats['weather_cache']['stale_locations'] == []
+
+
+class TestForecastICalAPI:
+    """Test the iCalendar forecast export"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_one_all_day_event_per_day(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test each forecast day becomes an all-day event"""
+        mock_weather_data['daily'][0]['date'] = '2024-07-01'
+        mock_weather_data['daily'][1]['date'] = '2024-07-02'
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get(
+            '/api/forecast.ics?lat=48.8566&lon=2.3522&location=Paris, France'
+        )
+        text = response.get_data(as_text=True)
+
+        assert response.status_code == HTTP_OK
+        assert response.mimetype == 'text/calendar'
+        assert text.startswith('BEGIN:VCALENDAR\r\n')
+        assert text.endswith('END:VCALENDAR\r\n')
+        assert text.count('BEGIN:VEVENT') == len(mock_weather_data['daily'])
+        assert 'X-WR-CALNAME:Weather - Paris\\, France' in text
+        assert 'DTSTART;VALUE=DATE:20240701\r\nDTEND;VALUE=DATE:20240702' in text
+        assert 'UID:20240701-48.8566+2.3522@weather-dashboard' in text
+        assert 'SUMMARY:☀️ +77°F / +65°F Clear' in text
+
+    @patch('main.weather_manager.get_weather')
+    def test_metric_units(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the feed follows ?units= like the rest of the API"""
+        mock_get_weather.return_value = mock_weather_data
+
+        text = client.get('/api/forecast.ics?city=chicago&units=metric').get_data(
+            as_text=True
+        )
+
+        assert 'SUMMARY:☀️ +25°C / +18°C Clear' in text
+        assert '°F' not in text
+
+    @patch('main.weather_manager.get_weather')
+    def test_days_without_dates_count_from_today(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test cached forecasts without dates still produce sequential days"""
+        mock_get_weather.return_value = mock_weather_data
+
+        text = client.get('/api/forecast.ics').get_data(as_text=True)
+
+        today = datetime.now(timezone.utc).date()
+        tomorrow = today + timedelta(days=1)
+        assert f'DTSTART;VALUE=DATE:{today:%Y%m%d}' in text
+        assert f'DTSTART;VALUE=DATE:{tomorrow:%Y%m%d}' in text
+
+    def test_long_lines_are_folded(self) -> None:
+        """Test content lines are folded at 75 octets without splitting characters"""
+        folded = fold_ical_line('DESCRIPTION:' + '☀️' * 40)
+
+        physical_lines = folded.split('\r\n')
+        assert all(len(line.encode()) <= ICAL_LINE_OCTETS for line in physical_lines)
+        assert all(line.startswith(' ') for line in physical_lines[1:])
+        assert ''.join(line.removeprefix(' ') for line in physical_lines) == (
+            'DESCRIPTION:' + '☀️' * 40
+        )
+
+
+class TestChangeFeed:
+    """Test the Atom feed of significant weather changes"""
+
+    cache_key = '41.8781,-87.6298'
+
+    def setup_method(self) -> None:
+        """Clear caches and change history before each test"""
+        weather_cache.clear()
+        alerts_cache.clear()
+        weather_change_history.clear()
+        weather_change_baselines.clear()
+
+    def store_current(self, base: dict[str, Any], **current: Any) -> None:
+        """Store weather data with overridden current conditions"""
+        store_weather_data(
+            self.cache_key, {**base, 'current': {**base['current'], **current}}
+        )
+
+    def test_detects_swings_and_precipitation(
+        self, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test only large swings and precipitation onset are recorded"""
+        self.store_current(mock_weather_data)
+        self.store_current(mock_weather_data, temperature=SMALL_CHANGE_TEMP)
+        assert self.cache_key not in weather_change_history
+
+        self.store_current(
+            mock_weather_data,
+            temperature=SWING_TEMP,
+            precipitation_rate=0.1,
+            precipitation_type='rain',
+        )
+
+        titles = [entry['title'] for entry in weather_change_history[self.cache_key]]
+        assert titles == ['Temperature rose to 85°F', 'Rain starting']
+
+    @patch('main.nws_provider.get_weather')
+    @patch('main.weather_manager.get_weather')
+    def test_feed_lists_changes_newest_first(
+        self,
+        mock_get_weather: MagicMock,
+        mock_get_alerts: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the feed renders alerts and changes as Atom entries"""
+        mock_get_weather.return_value = mock_weather_data
+        mock_get_alerts.return_value = {
+            'alerts': {
+                'alerts': [
+                    {
+                        'id': 'urn:alert/1',
+                        'headline': 'Heat Advisory <until 8pm>',
+                        'description': 'Stay cool',
+                    }
+                ]
+            }
+        }
+        self.store_current(mock_weather_data)
+        self.store_current(mock_weather_data, temperature=SWING_TEMP)
+
+        response = client.get('/feed/chicago.xml')
+        text = response.get_data(as_text=True)
+
+        assert response.status_code == HTTP_OK
+        assert response.mimetype == 'application/atom+xml'
+        assert '<feed xmlns="http://www.w3.org/2005/Atom">' in text
+        assert text.count('<entry>') == EXPECTED_FEED_ENTRIES
+        assert text.index('Heat Advisory &lt;until 8pm&gt;') < text.index(
+            'Temperature rose to 85°F'
+        )
+
+        # Alerts that are still active aren't reported twice
+        alerts_cache.clear()
+        text = client.get('/feed/chicago.xml').get_data(as_text=True)
+        assert text.count('<entry>') == EXPECTED_FEED_ENTRIES
+
+    @patch('main.geocode_city')
+    def test_unknown_location(self, mock_geocode: MagicMock, client: Any) -> None:
+        """Test unknown places return a 404"""
+        mock_geocode.return_value = None
+
+        response = client.get('/feed/nowhere.xml')
+
+        assert response.status_code == HTTP_NOT_FOUND
+
+
+class TestWatchAPI:
+    """Test the compact watch-face endpoint"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_compact_payload(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the payload carries temp, icon id, precip timing, and high/low"""
+        mock_weather_data['hourly'] = [
+            {'temp': 72, 'rain': 10},
+            {'temp': 71, 'rain': 30},
+            {'temp': 70, 'rain': 80},
+        ]
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get('/api/watch?lat=41.8781&lon=-87.6298')
+        data = json.loads(response.data)
+
+        assert response.status_code == HTTP_OK
+        assert len(response.data) < WATCH_PAYLOAD_BUDGET
+        assert data == {
+            'loc': '41.8781,-87.6298',
+            't': MOCK_TEMP,
+            'i': CLEAR_DAY_ICON_ID,
+            'p': RAIN_IN_TWO_HOURS,
+            'h': 77,
+            'l': 65,
+            'u': 'F',
+        }
+        assert 'max-age=900' in response.headers['Cache-Control']
+
+    @patch('main.weather_manager.get_weather')
+    def test_metric_units(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test metric visitors get Celsius and the matching unit letter"""
+        mock_get_weather.return_value = mock_weather_data
+
+        data = json.loads(
+            client.get('/api/watch?lat=41.8781&lon=-87.6298&units=metric').data
+        )
+
+        assert (data['t'], data['h'], data['l'], data['u']) == (22, 25, 18, 'C')
+
+    @patch('main.weather_manager.get_weather')
+    def test_size_budget_with_long_location(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test long location names are truncated and precip can be unknown"""
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get(
+            '/api/watch?lat=53.2&lon=-4.2&location=' + 'Llanfair' * 100
+        )
+        data = json.loads(response.data)
+
+        assert len(response.data) < WATCH_PAYLOAD_BUDGET
+        assert data['loc'] == ('Llanfair' * 3)
+        assert data['p'] is None
+
+
+class TestWidgetAPI:
+    """Test the home-screen widget endpoint"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_sizes(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test medium widgets get six hours, small three, and bad sizes a 400"""
+        mock_weather_data['hourly'] = [
+            {'temp': 72.4 - index, 'icon': 'cloudy', 'rain': 10, 't': f'{index}pm'}
+            for index in range(1, 9)
+        ]
+        mock_get_weather.return_value = mock_weather_data
+
+        medium = client.get('/api/widget?lat=41.8781&lon=-87.6298').get_json()
+        small = client.get('/api/widget?lat=41.8781&lon=-87.6298&size=small')
+        invalid = client.get('/api/widget?lat=41.8781&lon=-87.6298&size=huge')
+
+        assert len(medium['hours']) == WIDGET_MEDIUM_HOURS
+        assert medium['hours'][0] == {
+            'label': '1pm',
+            'temperature': 71,
+            'icon': 'cloudy',
+            'precipitation_probability': 10,
+        }
+        assert medium['temperature'] == MOCK_TEMP
+        assert (medium['high'], medium['low'], medium['unit']) == (77, 65, '°F')
+        assert medium['icon'] == 'clear-day'
+        assert medium['summary'].endswith('high of 77.')
+        assert len(small.get_json()['hours']) == WIDGET_SMALL_HOURS
+        assert invalid.status_code == HTTP_BAD_REQUEST
+        assert invalid.get_json()['sizes'] == ['small', 'medium']
+
+    @patch('main.weather_manager.get_weather')
+    def test_metric(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test ?units=metric converts and rounds every temperature"""
+        mock_get_weather.return_value = mock_weather_data
+
+        data = client.get('/api/widget?lat=41.8781&lon=-87.6298&units=metric')
+
+        body = data.get_json()
+        assert (body['temperature'], body['high'], body['low']) == (22, 25, 18)
+        assert body['unit'] == '°C'
+        assert body['summary'].endswith('high of 25.')
+
+
+class TestExportAPI:
+    """Test CSV and JSON-lines forecast exports"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_hourly_csv(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test hourly rows export as CSV with unit columns"""
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get(
+            '/api/export?format=csv&range=hourly&lat=41.8781&lon=-87.6298'
+            '&location==SUM(A1)'
+        )
+        lines = response.get_data(as_text=True).splitlines()
+
+        assert response.status_code == HTTP_OK
+        assert response.mimetype == 'text/csv'
+        assert 'forecast-hourly.csv' in response.headers['Content-Disposition']
+        assert len(lines) == EXPECTED_CSV_LINES
+        assert lines[0] == (
+            'location,time,label,temperature,temperature_unit,'
+            'precipitation_probability,precipitation_probability_unit,'
+            'pressure,pressure_unit,icon,description'
+        )
+        # Formula-looking text is neutralized for spreadsheet users
+        assert lines[1] == "'=SUM(A1),,12p,72,°F,0,%,,hPa,clear-day,Clear"
+
+    @patch('main.weather_manager.get_weather')
+    def test_daily_jsonl(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test daily rows export as JSON lines"""
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get('/api/export?format=jsonl&range=daily')
+        lines = response.get_data(as_text=True).splitlines()
+        rows = [json.loads(line) for line in lines]
+
+        assert response.mimetype == 'application/x-ndjson'
+        assert len(rows) == len(mock_weather_data['daily'])
+        assert rows[0] == {
+            'location': 'Chicago',
+            'date': None,
+            'day': 'Mon',
+            'high': 77,
+            'high_unit': '°F',
+            'low': 65,
+            'low_unit': '°F',
+            'icon': 'clear-day',
+        }
+
+    @patch('main.weather_manager.get_weather')
+    def test_metric_units(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test exported values and their unit columns follow ?units="""
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get(
+            '/api/export?format=jsonl&range=daily&city=chicago&units=metric'
+        )
+        row = json.loads(response.get_data(as_text=True).splitlines()[0])
+
+        assert (row['high'], row['high_unit']) == (25, '°C')
+        assert (row['low'], row['low_unit']) == (18, '°C')
+
+    def test_invalid_format(self, client: Any) -> None:
+        """Test unknown formats and ranges are rejected"""
+        assert client.get('/api/export?format=xlsx').status_code == HTTP_BAD_REQUEST
+        assert client.get('/api/export?range=weekly').status_code == HTTP_BAD_REQUEST
+
+
+COMMUTE_CONFIG = json.dumps(
+    [
+        {
+            'name': 'work',
+            'departure': '07:45',
+            'timezone': 'America/Chicago',
+            'days': ['mon', 'tue', 'wed', 'thu', 'fri'],
+            'points': [
+                {'lat': 41.8781, 'lon': -87.6298, 'name': 'Home'},
+                {'lat': 41.9742, 'lon': -87.9073, 'name': 'Office'},
+            ],
+        },
+        {'name': 'broken', 'departure': 'soon', 'points': []},
+    ]
+)
+
+
+class TestCommuteCorridors:
+    """Test commute corridor pre-warming and the route endpoint"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+
+    def test_load_skips_invalid_corridors(self) -> None:
+        """Test valid corridors load and broken ones are skipped"""
+        corridors = load_commute_corridors(COMMUTE_CONFIG)
+
+        assert list(corridors) == ['work']
+        assert corridors['work']['departure'] == (7, 45)
+        assert len(corridors['work']['points']) == CORRIDOR_POINTS
+        assert load_commute_corridors('not json') == {}
+
+    def test_prewarm_window(self) -> None:
+        """Test the window opens before departure on configured days only"""
+        corridor = load_commute_corridors(COMMUTE_CONFIG)['work']
+        chicago = corridor['timezone']
+
+        # Monday 2024-07-01
+        assert corridor_in_prewarm_window(
+            corridor, datetime(2024, 7, 1, 7, 30, tzinfo=chicago)
+        )
+        assert not corridor_in_prewarm_window(
+            corridor, datetime(2024, 7, 1, 6, 30, tzinfo=chicago)
+        )
+        # Saturday 2024-07-06
+        assert not corridor_in_prewarm_window(
+            corridor, datetime(2024, 7, 6, 7, 30, tzinfo=chicago)
+        )
+
+    @patch('main.weather_manager.get_weather')
+    def test_prewarm_fetches_only_missing_points(
+        self, mock_get_weather: MagicMock, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test pre-warming skips points that are already cached"""
+        mock_get_weather.return_value = mock_weather_data
+        weather_cache[weather_cache_key('41.8781,-87.6298')] = mock_weather_data
+        corridor = load_commute_corridors(COMMUTE_CONFIG)['work']
+
+        assert prewarm_corridor(corridor) == 1
+        assert weather_cache_key('41.9742,-87.9073') in weather_cache
+
+    @patch('main.weather_manager.get_weather')
+    def test_route_endpoint(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the route endpoint serves pre-warmed points from the cache"""
+        mock_get_weather.return_value = mock_weather_data
+        corridors = load_commute_corridors(COMMUTE_CONFIG)
+
+        with patch.dict('main.commute_corridors', corridors, clear=True):
+            prewarm_corridor(corridors['work'])
+            mock_get_weather.reset_mock()
+
+            response = client.get('/api/route?corridor=work')
+            data = json.loads(response.data)
+
+            assert response.status_code == HTTP_OK
+            assert response.headers['X-Cache'] == 'HIT'
+            assert [point['name'] for point in data['points']] == ['Home', 'Office']
+            assert data['points'][0]['current']['temperature'] == MOCK_TEMP
+            mock_get_weather.assert_not_called()
+
+            missing = client.get('/api/route?corridor=gym')
+            assert missing.status_code == HTTP_NOT_FOUND
+            assert json.loads(missing.data)['corridors'] == ['work']
+
+
+class TestConditionalWeatherGet:
+    """Test ETag and Last-Modified handling on /api/weather"""
+
+    url = '/api/weather?lat=41.8781&lon=-87.6298&location=Chicago'
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_if_none_match_returns_304(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test a matching ETag gets an empty 304 until the data changes"""
+        mock_get_weather.return_value = mock_weather_data
+
+        first = client.get(self.url)
+        etag = first.headers['ETag']
+        assert first.status_code == HTTP_OK
+        assert 'Last-Modified' in first.headers
+
+        second = client.get(self.url, headers={'If-None-Match': etag})
+        assert second.status_code == HTTP_NOT_MODIFIED
+        assert second.data == b''
+
+        # A different location name changes the body, so it changes the tag
+        renamed = client.get(
+            '/api/weather?lat=41.8781&lon=-87.6298&location=Home',
+            headers={'If-None-Match': etag},
+        )
+        assert renamed.status_code == HTTP_OK
+
+        # Fresh data from the provider invalidates the old tag
+        chicago_key = weather_cache_key('41.8781,-87.6298')
+        weather_cache_metadata[chicago_key]['fetched_at'] += 60
+        refreshed = client.get(self.url, headers={'If-None-Match': etag})
+        assert refreshed.status_code == HTTP_OK
+        assert refreshed.headers['ETag'] != etag
+
+    @patch('main.weather_manager.get_weather')
+    def test_if_modified_since_returns_304(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test If-Modified-Since at or after the fetch time gets a 304"""
+        mock_get_weather.return_value = mock_weather_data
+
+        last_modified = client.get(self.url).headers['Last-Modified']
+        response = client.get(self.url, headers={'If-Modified-Since': last_modified})
+
+        assert response.status_code == HTTP_NOT_MODIFIED
+
+    @patch('main.weather_manager.get_weather')
+    def test_max_age_counts_down_with_entry_age(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test Cache-Control max-age is the entry's remaining TTL, with Age"""
+        mock_get_weather.return_value = mock_weather_data
+
+        first = client.get(self.url)
+        assert first.headers['Cache-Control'] == (
+            f'public, max-age={OPEN_METEO_CACHE_TTL}'
+        )
+        assert first.headers['Age'] == '0'
+
+        chicago_key = weather_cache_key('41.8781,-87.6298')
+        weather_cache_metadata[chicago_key]['fetched_at'] -= CACHED_ENTRY_AGE
+        second = client.get(self.url)
+        remaining = OPEN_METEO_CACHE_TTL - CACHED_ENTRY_AGE
+        assert second.headers['Cache-Control'] == f'public, max-age={remaining}'
+        assert second.headers['Age'] == str(CACHED_ENTRY_AGE)
+
+
+class TestHealthEndpoints:
+    """Test the liveness and readiness probes"""
+
+    def setup_method(self) -> None:
+        """Clear cached probe results before each test"""
+        provider_probe_cache.clear()
+
+    def with_providers(self, primary: MagicMock, backup: MagicMock) -> Any:
+        """Swap in mock providers for the manager"""
+        return patch.multiple(
+            'main.weather_manager',
+            providers={'Primary': primary, 'Backup': backup},
+            primary_provider='Primary',
+            fallback_providers=['Backup'],
+        )
+
+    def test_healthz(self, client: Any) -> None:
+        """Test the liveness probe answers without touching providers"""
+        response = client.get('/healthz')
+
+        assert response.status_code == HTTP_OK
+        assert json.loads(response.data) == {'status': 'ok'}
+
+    def test_readyz_reports_each_provider(self, client: Any) -> None:
+        """Test one responding provider is enough to be ready"""
+        primary = MagicMock()
+        primary.health_check.side_effect = Exception('quota exceeded')
+        backup = MagicMock()
+        backup.health_check.return_value = True
+
+        with self.with_providers(primary, backup):
+            response = client.get('/readyz')
+
+        data = json.loads(response.data)
+        assert response.status_code == HTTP_OK
+        assert data['status'] == 'ready'
+        assert data['checks']['providers']['Primary']['status'] == 'down'
+        assert data['checks']['providers']['Backup']['status'] == 'up'
+        assert data['checks']['config']['status'] == 'ok'
+        assert data['checks']['cache']['status'] == 'ok'
+        assert '__readyz__' not in weather_cache
+
+    def test_readyz_unavailable_when_no_provider_responds(self, client: Any) -> None:
+        """Test readiness fails with 503 when every provider is down"""
+        primary = MagicMock()
+        primary.health_check.return_value = False
+        backup = MagicMock()
+        backup.health_check.return_value = False
+
+        with self.with_providers(primary, backup):
+            response = client.get('/readyz')
+
+        assert response.status_code == HTTP_SERVICE_UNAVAILABLE
+        assert json.loads(response.data)['status'] == 'not_ready'
+
+    def test_readyz_reuses_recent_probes(self, client: Any) -> None:
+        """Test repeated polling doesn't hit the providers every time"""
+        primary = MagicMock()
+        primary.health_check.return_value = True
+        backup = MagicMock()
+        backup.health_check.return_value = True
+
+        with self.with_providers(primary, backup):
+            client.get('/readyz')
+            client.get('/readyz')
+
+        primary.health_check.assert_called_once()
+        backup.health_check.assert_called_once()
+        primary.get_weather.assert_not_called()
+
+
+def ntp_reply(unix_time: float) -> bytes:
+    """Build an NTP response packet carrying the given transmit time"""
+    ntp_time = unix_time + NTP_EPOCH_OFFSET
+    seconds = int(ntp_time)
+    fraction = int((ntp_time - seconds) * 2**32)
+    return bytes(40) + struct.pack('!II', seconds, fraction)
+
+
+class TestClockSkew:
+    """Test the optional NTP clock sanity check"""
+
+    @patch('main.socket.socket')
+    def test_query_ntp_offset(self, mock_socket: MagicMock) -> None:
+        """Test the offset is the server time minus the local clock"""
+        sock = mock_socket.return_value.__enter__.return_value
+        sock.recvfrom.return_value = (ntp_reply(time.time() + CLOCK_SKEW), None)
+
+        offset = query_ntp_offset('pool.ntp.org')
+
+        assert offset is not None
+        assert abs(offset - CLOCK_SKEW) < CLOCK_SKEW_TOLERANCE
+        sock.sendto.assert_called_once()
+        assert sock.sendto.call_args[0][1] == ('pool.ntp.org', 123)
+
+    @patch('main.socket.socket')
+    def test_large_skew_warns_loudly(
+        self, mock_socket: MagicMock, capsys: Any
+    ) -> None:
+        """Test skew past the threshold is logged and recorded for /readyz"""
+        sock = mock_socket.return_value.__enter__.return_value
+        sock.recvfrom.return_value = (ntp_reply(time.time() - CLOCK_SKEW), None)
+
+        check_clock_skew('pool.ntp.org')
+
+        assert f'System clock is off by -{CLOCK_SKEW}' in capsys.readouterr().out
+        assert abs(clock_status['skew_seconds'] + CLOCK_SKEW) < CLOCK_SKEW_TOLERANCE
+        assert clock_status['server'] == 'pool.ntp.org'
+
+    @patch('main.socket.socket')
+    def test_unreachable_server(self, mock_socket: MagicMock) -> None:
+        """Test an unreachable NTP server leaves the skew unknown"""
+        sock = mock_socket.return_value.__enter__.return_value
+        sock.recvfrom.side_effect = socket.timeout('timed out')
+
+        assert check_clock_skew('pool.ntp.org') is None
+        assert clock_status['skew_seconds'] is None
+
+
+class TestWeatherSectionAPI:
+    """Test the per-section weather endpoints"""
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+        for section_cache in weather_section_caches.values():
+            section_cache.clear()
+
+    @patch('main.weather_manager.get_weather_section')
+    def test_serves_section_from_full_document(
+        self,
+        mock_get_section: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test a cached full document answers without another upstream call"""
+        store_weather_data('41.8781,-87.6298', mock_weather_data)
+
+        response = client.get('/api/weather/daily?lat=41.8781&lon=-87.6298')
+
+        data = json.loads(response.data)
+        assert response.status_code == HTTP_OK
+        assert response.headers['X-Cache'] == 'HIT'
+        assert data['daily'][0]['high'] == mock_weather_data['daily'][0]['h']
+        assert 'current' not in data
+        mock_get_section.assert_not_called()
+
+    @patch('main.weather_manager.get_weather_section')
+    def test_fetches_and_caches_section(
+        self, mock_get_section: MagicMock, client: Any
+    ) -> None:
+        """Test a section is fetched alone and then served from its own cache"""
+        mock_get_section.return_value = {
+            'current': {'temperature': MOCK_TEMP, 'precipitation_prob': 10},
+            'provider': 'OpenMeteo',
+        }
+        url = '/api/weather/current?lat=41.8781&lon=-87.6298'
+
+        first = client.get(url)
+        second = client.get(url)
+
+        assert first.headers['X-Cache'] == 'MISS'
+        assert second.headers['X-Cache'] == 'HIT'
+        assert first.headers['Cache-Control'] == 'public, max-age=300'
+        assert json.loads(second.data)['current']['precipitation_probability'] == 10
+        mock_get_section.assert_called_once_with(
+            41.8781, -87.6298, 'current', '41.8781,-87.6298', None
+        )
+        assert weather_cache_key('41.8781,-87.6298') not in weather_cache
+
+    @patch('main.weather_manager.get_weather_section')
+    def test_section_fetch_failure(
+        self, mock_get_section: MagicMock, client: Any
+    ) -> None:
+        """Test a failed section fetch returns a 503"""
+        mock_get_section.return_value = None
+
+        response = client.get('/api/weather/hourly?lat=41.8781&lon=-87.6298')
+
+        assert response.status_code == HTTP_SERVICE_UNAVAILABLE
+        assert response.get_json()['code'] == 'upstream_unavailable'
+
+
+class TestApiTokens:
+    """Test token creation, rotation and enforcement"""
+
+    def store(self, tmp_path: Path) -> Any:
+        """Swap in an empty token store backed by a temporary file"""
+        return patch('main.token_store', TokenStore(str(tmp_path / 'tokens.json')))
+
+    def auth_headers(self, token: str = BOOTSTRAP_TOKEN) -> dict[str, str]:
+        """Bearer authorization header, using the bootstrap admin token by default"""
+        return {'Authorization': f'Bearer {token}'}
+
+    def test_token_management_disabled_without_admin_token(
+        self, client: Any, tmp_path: Path
+    ) -> None:
+        """Test nobody can mint tokens until ADMIN_TOKEN bootstraps management"""
+        with self.store(tmp_path), patch('main.ADMIN_TOKEN', ''):
+            response = client.post('/api/tokens', json={'name': 'kiosk'})
+
+        assert response.status_code == HTTP_FORBIDDEN
+        assert json.loads(response.data)['code'] == 'forbidden'
+
+    def test_create_list_and_revoke(self, client: Any, tmp_path: Path) -> None:
+        """Test the admin token lifecycle through the API"""
+        with self.store(tmp_path), patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            assert client.get('/api/tokens').status_code == HTTP_UNAUTHORIZED
+
+            created = client.post(
+                '/api/tokens',
+                json={'name': 'kiosk', 'scope': 'read', 'expires_in': 3600},
+                headers=self.auth_headers(),
+            )
+            assert created.status_code == HTTP_CREATED
+            token = json.loads(created.data)
+            assert token['token']
+            assert token['expires_at'] is not None
+
+            listed = json.loads(
+                client.get('/api/tokens', headers=self.auth_headers()).data
+            )
+            assert [t['id'] for t in listed['tokens']] == [token['id']]
+            assert 'token' not in listed['tokens'][0]
+
+            # A read token can't manage tokens
+            forbidden = client.get(
+                '/api/tokens', headers=self.auth_headers(token['token'])
+            )
+            assert forbidden.status_code == HTTP_UNAUTHORIZED
+            assert json.loads(forbidden.data)['code'] == 'unauthorized'
+
+            revoked = client.delete(
+                f'/api/tokens/{token["id"]}', headers=self.auth_headers()
+            )
+            assert revoked.status_code == HTTP_OK
+            missing = client.delete('/api/tokens/nope', headers=self.auth_headers())
+            assert missing.status_code == HTTP_NOT_FOUND
+            assert json.loads(missing.data)['code'] == 'not_found'
+
+    def test_invalid_token_requests(self, client: Any, tmp_path: Path) -> None:
+        """Test missing names, unknown scopes and bad expiries are rejected"""
+        with self.store(tmp_path), patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            for body in (
+                {},
+                {'name': 'kiosk', 'scope': 'root'},
+                {'name': 'kiosk', 'expires_in': -5},
+            ):
+                response = client.post(
+                    '/api/tokens', json=body, headers=self.auth_headers()
+                )
+                assert response.status_code == HTTP_BAD_REQUEST
+                assert json.loads(response.data)['code'] == 'invalid_parameter'
+
+    @patch('main.weather_manager.get_weather')
+    def test_read_tokens_enforced_when_required(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test REQUIRE_READ_TOKEN locks the API and a kiosk URL sets a cookie"""
+        mock_get_weather.return_value = mock_weather_data
+        weather_cache.clear()
+
+        with (
+            self.store(tmp_path) as store,
+            patch('main.REQUIRE_READ_TOKEN', True),
+            patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN),
+        ):
+            _, old_secret = store.create('kiosk', 'read')
+            _, new_secret = store.create('kiosk (rotated)', 'read')
+
+            assert client.get('/api/weather').status_code == HTTP_UNAUTHORIZED
+            assert client.get('/').status_code == HTTP_OK
+
+            # Both tokens work during the rotation window
+            for secret in (old_secret, new_secret):
+                response = client.get(
+                    '/api/weather', headers=self.auth_headers(secret)
+                )
+                assert response.status_code == HTTP_OK
+
+            # A kiosk URL carries the token once, then the cookie covers API calls
+            page = client.get(f'/?token={new_secret}')
+            assert 'weather_token=' in page.headers['Set-Cookie']
+            assert 'HttpOnly' in page.headers['Set-Cookie']
+            assert client.get('/api/weather').status_code == HTTP_OK
+
+    def test_provider_switch_requires_admin_once_enabled(
+        self, client: Any, tmp_path: Path
+    ) -> None:
+        """Test switching providers is an admin action when auth is enabled"""
+        with self.store(tmp_path), patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            response = client.post(
+                '/api/providers/switch', json={'provider': 'OpenMeteo'}
+            )
+
+        assert response.status_code == HTTP_UNAUTHORIZED
+
+    def test_admin_endpoints_local_only_without_admin_token(
+        self, client: Any, tmp_path: Path
+    ) -> None:
+        """Test admin actions from other machines or via a proxy are refused"""
+        with self.store(tmp_path), patch('main.ADMIN_TOKEN', ''):
+            remote = client.delete(
+                '/api/cache', environ_base={'REMOTE_ADDR': '203.0.113.5'}
+            )
+            proxied = client.delete(
+                '/api/cache', headers={'X-Forwarded-For': '203.0.113.5'}
+            )
+            local = client.delete('/api/cache')
+
+        assert remote.status_code == HTTP_FORBIDDEN
+        assert proxied.status_code == HTTP_FORBIDDEN
+        assert local.status_code == HTTP_OK
+
+    def test_api_key_header_and_query(self, client: Any, tmp_path: Path) -> None:
+        """Test X-API-Key and ?api_key= carry tokens like a Bearer header"""
+        with self.store(tmp_path), patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            by_header = client.get(
+                '/api/tokens', headers={'X-API-Key': BOOTSTRAP_TOKEN}
+            )
+            by_query = client.get(f'/api/tokens?api_key={BOOTSTRAP_TOKEN}')
+
+        assert by_header.status_code == HTTP_OK
+        assert by_query.status_code == HTTP_OK
+
+    def test_configured_admin_key(self, client: Any, tmp_path: Path) -> None:
+        """Test an admin key from API_KEYS enables and passes admin auth"""
+        with self.store(tmp_path) as store, patch('main.ADMIN_TOKEN', ''):
+            store.configure(
+                parse_api_keys('[{"name": "deploy", "scope": "admin", "key": "k"}]')
+            )
+            anonymous = client.delete('/api/cache')
+            keyed = client.delete('/api/cache', headers={'X-API-Key': 'k'})
+
+        assert anonymous.status_code == HTTP_UNAUTHORIZED
+        assert keyed.status_code == HTTP_OK
+
+    def test_configured_key_not_revocable(self, client: Any, tmp_path: Path) -> None:
+        """Test revoking a key set in config is a conflict, not a silent no-op"""
+        with self.store(tmp_path) as store, patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            store.configure(parse_api_keys('[{"name": "dashboard", "key": "k"}]'))
+            response = client.delete(
+                '/api/tokens/config:dashboard', headers=self.auth_headers()
+            )
+
+        assert response.status_code == HTTP_CONFLICT
+
+    def test_websocket_needs_read_token_when_required(self, tmp_path: Path) -> None:
+        """Test sockets are refused without a read token under REQUIRE_READ_TOKEN"""
+        with (
+            self.store(tmp_path) as store,
+            patch('main.REQUIRE_READ_TOKEN', True),
+            patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN),
+        ):
+            _, secret = store.create('kiosk', 'read')
+            with main.app.test_request_context('/socket.io/'):
+                assert main.handle_connect() is False
+                assert main.handle_realtime_connect() is False
+            with (
+                main.app.test_request_context(f'/socket.io/?token={secret}'),
+                patch('main.realtime_subscriptions.connect') as mock_connect,
+            ):
+                main.request.sid = 'sid'  # type: ignore[attr-defined]
+                assert main.handle_realtime_connect() is None
+                mock_connect.assert_called_once_with('sid')
+
+
+class TestWeatherMetrics:
+    """Test the Prometheus weather exporter"""
+
+    url = '/metrics/weather?lat=41.8781&lon=-87.6298&location=Chicago'
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    @patch('main.air_quality_provider', None)
+    @patch('main.weather_manager.get_weather')
+    def test_gauges_with_location_labels(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test observations are exported as labelled gauges"""
+        mock_weather_data['current']['pressure'] = 1013.2
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get(self.url)
+        body = response.data.decode()
+
+        assert response.status_code == HTTP_OK
+        assert response.content_type.startswith('text/plain; version=0.0.4')
+        labels = 'location="Chicago",lat="41.8781",lon="-87.6298",provider="OpenMeteo"'
+        assert f'weather_temperature_fahrenheit{{{labels}}} 72\n' in body
+        assert f'weather_humidity_percent{{{labels}}} 65\n' in body
+        assert f'weather_pressure_hpa{{{labels}}} 1013.2\n' in body
+        assert '# TYPE weather_wind_speed_mph gauge' in body
+        assert 'weather_observation_timestamp_seconds{' in body
+        # Missing observations are left out rather than exported as zero
+        assert 'weather_air_quality_index' not in body
+        assert 'weather_wind_gust_mph' not in body
+
+    @patch('main.air_quality_provider')
+    @patch('main.weather_manager.get_weather')
+    def test_air_quality_gauge(
+        self,
+        mock_get_weather: MagicMock,
+        mock_air_quality: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test AQI is exported when an air quality provider is configured"""
+        mock_get_weather.return_value = mock_weather_data
+        mock_air_quality.get_weather.return_value = {'aqi': {'us_aqi': 42}}
+
+        body = client.get(self.url).data.decode()
+
+        aqi_line = next(
+            line
+            for line in body.splitlines()
+            if line.startswith('weather_air_quality_index{')
+        )
+        assert 'location="Chicago"' in aqi_line
+        assert aqi_line.endswith(' 42')
+
+    @patch('main.weather_manager.get_weather')
+    def test_weather_failure(self, mock_get_weather: MagicMock, client: Any) -> None:
+        """Test a failed fetch returns a 500 instead of empty metrics"""
+        mock_get_weather.return_value = None
+
+        assert client.get(self.url).status_code == HTTP_INTERNAL_SERVER_ERROR
+
+    def test_escape_prometheus_label(self) -> None:
+        """Test label values escape backslashes, quotes and newlines"""
+        assert escape_prometheus_label('Bob\'s "cabin"\n\\') == (
+            'Bob\'s \\"cabin\\"\\n\\\\'
+        )
+
+
+class TestGrafanaDatasource:
+    """Test the Grafana Simple JSON datasource endpoints"""
+
+    query_range = {
+        'from': '2024-01-01T00:00:00.000Z',
+        'to': '2024-01-01T02:00:00.000Z',
+    }
+
+    def setup_method(self) -> None:
+        """Clear caches and change history before each test"""
+        history_cache.clear()
+        weather_change_history.clear()
+
+    def history_points(self) -> list[tuple[datetime, float]]:
+        """Build twelve hourly readings from 60 to 71 starting at midnight UTC"""
+        start = datetime(2024, 1, 1, tzinfo=timezone.utc)
+        return [
+            (start + timedelta(hours=hour), 60.0 + hour)
+            for hour in range(HISTORY_HOURS)
+        ]
+
+    def test_connection_test(self, client: Any) -> None:
+        """Test the datasource root answers Grafana's connection test"""
+        response = client.get('/api/grafana/')
+        assert response.status_code == HTTP_OK
+
+    def test_search_filters_targets(self, client: Any) -> None:
+        """Test the metric picker lists observed and forecast series"""
+        response = client.post('/api/grafana/search', json={'target': 'chicago.'})
+        targets = json.loads(response.data)
+
+        assert 'chicago.temperature' in targets
+        assert 'chicago.forecast.temperature' in targets
+        assert all(target.startswith('chicago.') for target in targets)
+        assert len(json.loads(client.post('/api/grafana/search').data)) == len(
+            grafana_targets()
+        )
+
+    def stored(self, tmp_path: Path) -> HistoryStore:
+        """A store with the twelve hourly readings for Chicago"""
+        store = HistoryStore(str(tmp_path / 'history.db'))
+        for stamp, temperature in self.history_points():
+            store.record(
+                '41.8781,-87.6298',
+                41.8781,
+                -87.6298,
+                'OpenMeteo',
+                {'temperature': temperature},
+                stamp.timestamp(),
+            )
+        return store
+
+    @patch('main.get_archive_from_open_meteo', return_value={})
+    @patch('main.get_history_from_open_meteo')
+    def test_query_returns_datapoints_in_range(
+        self,
+        mock_forecast: MagicMock,
+        mock_archive: MagicMock,
+        client: Any,
+        tmp_path: Path,
+    ) -> None:
+        """Test targets come back as [value, epoch_ms] pairs clipped to the range"""
+        mock_forecast.return_value = self.history_points()
+        body = {
+            'range': self.query_range,
+            'targets': [
+                {'target': 'chicago.temperature'},
+                {'target': 'chicago.forecast.temperature'},
+            ],
+        }
+
+        with patch('main.history_store', self.stored(tmp_path)):
+            response = client.post('/api/grafana/query', json=body)
+        series = json.loads(response.data)
+
+        assert response.status_code == HTTP_OK
+        assert [s['target'] for s in series] == [
+            'chicago.temperature',
+            'chicago.forecast.temperature',
+        ]
+        assert len(series[0]['datapoints']) == GRAFANA_RANGE_HOURS
+        assert series[0]['datapoints'][0] == [60.0, GRAFANA_START_MS]
+        assert series[0]['datapoints'][1] == [61.0, GRAFANA_START_MS + HOUR_MS]
+        # Only the forecast is fetched; observations are all in the store
+        mock_archive.assert_not_called()
+        (forecast_call,) = mock_forecast.call_args_list
+        assert forecast_call[0][3] == 0
+        assert forecast_call[0][4] > 0
+
+    def test_query_unknown_target_and_bad_range(self, client: Any) -> None:
+        """Test unknown targets are empty and a missing range is rejected"""
+        body = {'range': self.query_range, 'targets': [{'target': 'atlantis.temp'}]}
+        series = json.loads(client.post('/api/grafana/query', json=body).data)
+        assert series == [{'target': 'atlantis.temp', 'datapoints': []}]
+
+        response = client.post('/api/grafana/query', json={'targets': []})
+        assert response.status_code == HTTP_BAD_REQUEST
+
+    def test_annotations_from_change_feed(self, client: Any) -> None:
+        """Test significant weather changes are served as annotations"""
+        record_weather_change('41.8781,-87.6298', 'rain', 'Rain starting', 'Rain')
+        now = datetime.now(timezone.utc)
+        body = {
+            'range': {
+                'from': (now - timedelta(hours=1)).isoformat(),
+                'to': (now + timedelta(hours=1)).isoformat(),
+            },
+            'annotation': {'name': 'changes', 'query': 'chicago'},
+        }
+
+        response = client.post('/api/grafana/annotations', json=body)
+        annotations = json.loads(response.data)
+
+        assert len(annotations) == 1
+        assert annotations[0]['title'] == 'Rain starting'
+        assert annotations[0]['tags'] == ['rain', 'chicago']
+        assert annotations[0]['annotation'] == body['annotation']
+
+        body['annotation']['query'] = 'nyc'
+        assert json.loads(client.post('/api/grafana/annotations', json=body).data) == []
+
+
+class TestUsageAnalyticsAPI:
+    """Test opt-in usage analytics recording and the admin endpoint"""
+
+    @patch('main.usage_analytics', None)
+    def test_disabled_by_default(self, client: Any) -> None:
+        """Test the endpoint is unavailable unless analytics are enabled"""
+        assert client.get('/api/admin/analytics').status_code == HTTP_NOT_FOUND
+
+    @patch('main.weather_manager.get_weather')
+    def test_counts_requests(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test requests are counted per endpoint, location and device class"""
+        mock_get_weather.return_value = mock_weather_data
+        weather_cache.clear()
+        analytics = UsageAnalytics(str(tmp_path / 'analytics.json'))
+        with patch('main.usage_analytics', analytics):
+            client.get(
+                '/api/weather?lat=41.8781&lon=-87.6298',
+                headers={'User-Agent': 'Mozilla/5.0 (iPhone) Mobile'},
+            )
+            client.get('/chicago', headers={'User-Agent': 'curl/8.4.0'})
+            client.get('/no-such/page/here')
+            stats = json.loads(client.get('/api/admin/analytics').data)
+
+        assert stats['endpoints']['/api/weather'] == 1
+        assert stats['endpoints']['/<city>'] == 1
+        assert stats['locations']['41.8781,-87.6298']['count'] == ANALYTICS_CHICAGO_HITS
+        assert stats['devices']['mobile'] == 1
+        assert stats['devices']['bot'] == 1
+        assert stats['stale_locations'] == []
+
+    def test_requires_admin_token(self, client: Any, tmp_path: Path) -> None:
+        """Test the stats are admin-only once admin auth is enabled"""
+        analytics = UsageAnalytics(str(tmp_path / 'analytics.json'))
+        with (
+            patch('main.usage_analytics', analytics),
+            patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN),
+        ):
+            assert client.get('/api/admin/analytics').status_code == HTTP_UNAUTHORIZED
+            response = client.get(
+                '/api/admin/analytics',
+                headers={'Authorization': f'Bearer {BOOTSTRAP_TOKEN}'},
+            )
+
+        assert response.status_code == HTTP_OK
+
+
+class TestInfluxSink:
+    """Test fetched observations are queued for InfluxDB"""
+
+    def test_store_queues_observation(self, mock_weather_data: dict[str, Any]) -> None:
+        """Test caching fresh weather writes one tagged line-protocol point"""
+        writer = MagicMock()
+        with patch('main.influx_writer', writer):
+            store_weather_data('41.8781,-87.6298', mock_weather_data, 'OpenMeteo')
+
+        line = writer.write.call_args[0][0]
+        assert line.startswith(
+            'weather,lat=41.8781,location=Test\\ Location,lon=-87.6298,'
+            'provider=OpenMeteo '
+        )
+        assert 'temperature=72.0' in line
+        assert 'humidity=65.0' in line
+
+
+class TestHistorySink:
+    """Test fetched observations are kept in the history store"""
+
+    def test_store_records_observation(
+        self, mock_weather_data: dict[str, Any], tmp_path: Path
+    ) -> None:
+        """Test caching fresh weather saves a snapshot under its location"""
+        store = HistoryStore(str(tmp_path / 'history.db'))
+        with patch('main.history_store', store):
+            store_weather_data('41.8781,-87.6298', mock_weather_data, 'OpenMeteo')
+
+        (row,) = store.observations('41.8781,-87.6298', 0, time.time() + 60)
+        assert row['temperature'] == mock_weather_data['current']['temperature']
+        assert (row['lat'], row['lon']) == (41.8781, -87.6298)
+
+    def test_store_records_tomorrows_forecast(
+        self, mock_weather_data: dict[str, Any], tmp_path: Path
+    ) -> None:
+        """Test the second daily entry is kept to score next-day accuracy"""
+        store = HistoryStore(str(tmp_path / 'history.db'))
+        daily = [
+            {**day, 'date': date, 'rain': 30}
+            for day, date in zip(
+                mock_weather_data['daily'], ('2024-01-01', '2024-01-02'), strict=True
+            )
+        ]
+        with patch('main.history_store', store):
+            store_weather_data(
+                '41.8781,-87.6298', {**mock_weather_data, 'daily': daily}, 'OpenMeteo'
+            )
+
+        (forecast,) = store.forecasts('41.8781,-87.6298', '2024-01-01', '2024-01-31')
+        assert forecast['target_date'] == '2024-01-02'
+        assert forecast['high'] == daily[1]['h']
+        assert forecast['provider'] == 'OpenMeteo'
+
+    def test_database_errors_are_logged(
+        self, mock_weather_data: dict[str, Any], capsys: Any
+    ) -> None:
+        """Test a broken database doesn't stop fresh weather being served"""
+        store = MagicMock()
+        store.record.side_effect = sqlite3.OperationalError('disk I/O error')
+        with patch('main.history_store', store):
+            store_weather_data('41.8781,-87.6298', mock_weather_data, 'OpenMeteo')
+
+        assert 'Failed to record history' in capsys.readouterr().out
+
+
+class TestMqttSink:
+    """Test fetched weather is published to MQTT"""
+
+    def test_store_publishes_snake_case_weather(
+        self, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test caching fresh weather publishes the API document in the background"""
+        publisher = MagicMock()
+        with (
+            patch('main.mqtt_publisher', publisher),
+            patch('main.socketio.start_background_task') as mock_task,
+        ):
+            store_weather_data('41.8781,-87.6298', mock_weather_data, 'OpenMeteo')
+            task, *args = mock_task.call_args[0]
+            task(*args)
+
+        location, document = publisher.publish_weather.call_args[0]
+        assert location == 'Test Location'
+        assert 'precipitation_probability' in document['current']
+
+    def test_broker_errors_are_logged(self, capsys: Any) -> None:
+        """Test an unreachable broker doesn't raise out of the background task"""
+        publisher = MagicMock()
+        publisher.publish_weather.side_effect = ConnectionRefusedError('refused')
+        with patch('main.mqtt_publisher', publisher):
+            publish_mqtt_weather('Chicago', {})
+
+        assert 'MQTT publish failed for Chicago' in capsys.readouterr().out
+
+
+class TestAstroEventsAPI:
+    """Test the astronomical events endpoint"""
+
+    url = '/api/astro/events?lat=41.8781&lon=-87.6298'
+
+    def setup_method(self) -> None:
+        """Clear the cloud cover cache before each test"""
+        cloud_cover_cache.clear()
+
+    @patch('main.requests.get')
+    def test_events_rated_with_cloud_forecast(
+        self, mock_get: MagicMock, client: Any
+    ) -> None:
+        """Test upcoming events come back in order with clouds at viewing time"""
+        viewing = datetime.now(timezone.utc) + timedelta(days=2)
+        viewing = viewing.replace(minute=0, second=0, microsecond=0)
+        event = {
+            'name': 'Supermoon',
+            'type': 'supermoon',
+            'time': viewing.isoformat(),
+        }
+        mock_get.return_value.json.return_value = {
+            'hourly': {
+                'time': [viewing.strftime('%Y-%m-%dT%H:%M')],
+                'cloud_cover': [ASTRO_CLOUD_COVER],
+            }
+        }
+        with patch('main.ASTRO_EVENTS', [event]):
+            response = client.get(self.url)
+        data = json.loads(response.data)
+
+        assert response.status_code == HTTP_OK
+        assert [e['name'] for e in data['events']] == ['Supermoon']
+        assert data['events'][0]['cloud_cover_percent'] == ASTRO_CLOUD_COVER
+        assert 'moon_illumination_percent' in data['events'][0]
+        # 45% cloud is fair, unless the moon is still below the horizon then
+        assert data['events'][0]['visibility']['rating'] in ('fair', 'not_visible')
+        assert mock_get.call_args.kwargs['params']['hourly'] == 'cloud_cover'
+
+    def test_past_and_distant_events_skipped(self, client: Any) -> None:
+        """Test events outside the requested window aren't listed"""
+        past = {'name': 'Old', 'type': 'supermoon', 'time': '2000-01-01T00:00:00Z'}
+        with patch('main.ASTRO_EVENTS', [past]):
+            data = json.loads(client.get(f'{self.url}&days=30').data)
+
+        assert data['events'] == []
+
+    def test_requires_coordinates(self, client: Any) -> None:
+        """Test lat and lon are required"""
+        assert client.get('/api/astro/events').status_code == HTTP_BAD_REQUEST
+
+
+class TestHomeAssistantWeather:
+    """Test the Home Assistant weather entity endpoint"""
+
+    url = '/api/ha/weather?lat=41.8781&lon=-87.6298'
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_entity_attributes(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test conditions are translated and forecasts use HA field names"""
+        mock_weather_data['daily'][0]['date'] = '2024-01-01'
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get(self.url)
+        data = json.loads(response.data)
+
+        assert response.status_code == HTTP_OK
+        assert data['condition'] == 'sunny'
+        assert data['temperature'] == MOCK_TEMP
+        assert data['temperature_unit'] == '°F'
+        assert data['pressure_unit'] == 'hPa'
+        assert data['forecast'][0] == {
+            'datetime': '2024-01-01',
+            'condition': 'sunny',
+            'temperature': 77,
+            'templow': 65,
+        }
+        assert data['forecast'][1]['condition'] == 'partlycloudy'
+        assert data['forecast_hourly'][0]['precipitation_probability'] == 0
+
+    @patch('main.weather_manager.get_weather')
+    def test_metric_units(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test ?units=metric converts the values and their unit attributes"""
+        mock_get_weather.return_value = mock_weather_data
+
+        data = json.loads(client.get(f'{self.url}&units=metric').data)
+
+        assert data['temperature'] == 22  # noqa: PLR2004
+        assert data['temperature_unit'] == '°C'
+        assert data['wind_speed_unit'] == 'km/h'
+        assert data['precipitation_unit'] == 'mm'
+        assert data['pressure_unit'] == 'hPa'
+
+    def test_condition_translation(self) -> None:
+        """Test every dashboard icon maps to an HA condition"""
+        assert ha_condition('heavy-rain') == 'pouring'
+        assert ha_condition('thunderstorm') == 'lightning-rainy'
+        assert ha_condition('clear-night') == 'clear-night'
+        assert ha_condition('volcanic-ash') == 'exceptional'
+        assert ha_condition(None) == 'exceptional'
+
+    @patch('main.weather_manager.get_weather')
+    def test_weather_failure(self, mock_get_weather: MagicMock, client: Any) -> None:
+        """Test a failed fetch returns a 503"""
+        mock_get_weather.return_value = None
+
+        assert client.get(self.url).status_code == HTTP_SERVICE_UNAVAILABLE
+
+
+class TestLocationFallback:
+    """Test the profile -> instance default chain for unusable locations"""
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_explicit_location_has_no_fallback(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test usable coordinates are used as-is with no fallback metadata"""
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get('/api/weather?lat=0&lon=0&location=Null Island')
+        data = json.loads(response.data)
+
+        assert 'location_fallback' not in data
+        assert 'X-Location-Fallback' not in response.headers
+        assert mock_get_weather.call_args[0][:3] == (0.0, 0.0, 'Null Island')
+
+    @patch('main.weather_manager.get_weather')
+    def test_invalid_coordinates_use_profile_default(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test out-of-range coordinates fall back to the profile cookie"""
+        mock_get_weather.return_value = mock_weather_data
+        client.set_cookie('weather_default_location', 'nyc')
+
+        response = client.get('/api/weather?lat=91&lon=0')
+        data = json.loads(response.data)
+
+        assert response.status_code == HTTP_OK
+        assert data['location_fallback'] == {
+            'reason': 'invalid_coordinates',
+            'requested': '91,0',
+            'source': 'profile',
+        }
+        assert response.headers['X-Location-Fallback'] == (
+            'profile; reason=invalid_coordinates'
+        )
+        assert mock_get_weather.call_args[0][2] == 'New York City'
+
+    @patch('main.geocode_city', return_value=None)
+    @patch('main.weather_manager.get_weather_section')
+    def test_failed_geocoding_uses_instance_default(
+        self,
+        mock_get_section: MagicMock,
+        mock_geocode: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test an unknown city falls back to DEFAULT_LOCATION"""
+        mock_get_section.return_value = {'current': mock_weather_data['current']}
+
+        with patch('main.DEFAULT_LOCATION', 'sf'):
+            response = client.get('/api/weather/current?city=Atlantis')
+        data = json.loads(response.data)
+
+        assert response.status_code == HTTP_OK
+        assert data['location'] == 'San Francisco'
+        assert data['location_fallback']['source'] == 'instance'
+        assert data['location_fallback']['reason'] == 'geocoding_failed'
+        assert data['location_fallback']['requested'] == 'Atlantis'
+        mock_geocode.assert_called_once()
+
+    @patch('main.weather_manager.get_weather')
+    def test_coordinates_name_themselves(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test bare coordinates aren't labelled with the old Chicago default"""
+        mock_get_weather.return_value = mock_weather_data
+
+        client.get('/api/weather?lat=40.71&lon=-74.0')
+
+        assert mock_get_weather.call_args[0][:3] == (40.71, -74.0, '40.7100,-74.0000')
+
+    @patch('main.nws_provider.get_weather')
+    def test_zero_coordinates_are_not_missing(
+        self, mock_get_alerts: MagicMock, client: Any
+    ) -> None:
+        """Test endpoints beyond /api/weather take 0,0 as a real place"""
+        alerts_cache.clear()
+        mock_get_alerts.return_value = {'alerts': {'active_count': 0}}
+
+        response = client.get('/api/weather/alerts?lat=0&lon=0')
+
+        assert response.status_code == HTTP_OK
+        assert 'location_fallback' not in json.loads(response.data)
+        assert mock_get_alerts.call_args[0][:2] == (0.0, 0.0)
+
+    @patch('main.weather_manager.get_weather')
+    def test_other_endpoints_record_fallback(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test non-JSON and JSON endpoints alike report the default they used"""
+        mock_get_weather.return_value = mock_weather_data
+
+        ha = client.get('/api/ha/weather?lat=91&lon=0')
+        ical = client.get('/api/forecast.ics')
+
+        assert json.loads(ha.data)['location_fallback']['reason'] == (
+            'invalid_coordinates'
+        )
+        assert ha.headers['X-Location-Fallback'] == (
+            'instance; reason=invalid_coordinates'
+        )
+        assert ical.headers['X-Location-Fallback'] == 'instance; reason=missing'
+
+    def test_exhausted_chain_is_an_error(self, client: Any) -> None:
+        """Test no default configured means an explicit 400, not Chicago"""
+        with patch('main.DEFAULT_LOCATION', ''):
+            response = client.get('/api/weather')
+        data = json.loads(response.data)
+
+        assert response.status_code == HTTP_BAD_REQUEST
+        assert data['code'] == 'invalid_location'
+        assert data['location_fallback'] == {
+            'reason': 'missing',
+            'requested': None,
+            'source': None,
+        }
+
+
+class TestIpLocation:
+    """Test ?auto=1 and first-visit redirects from client IP geolocation"""
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+        geoip_cache.clear()
+
+    def locator(self, place: tuple[float, float, str] | None) -> Any:
+        """Swap in a locator that always answers with place"""
+        mock_locator = MagicMock()
+        mock_locator.locate.return_value = place
+        return patch('main.ip_locator', mock_locator)
+
+    @patch('main.weather_manager.get_weather')
+    def test_auto_uses_forwarded_client_ip(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the public X-Forwarded-For address is located and cached"""
+        mock_get_weather.return_value = mock_weather_data
+        headers = {'X-Forwarded-For': '8.8.8.8, 10.0.0.1'}
+
+        with self.locator((MV_LAT, MV_LON, 'Mountain View')) as mock_locator:
+            response = client.get('/api/weather?auto=1', headers=headers)
+            client.get('/api/weather?auto=1&units=metric', headers=headers)
+        data = json.loads(response.data)
+
+        assert response.status_code == HTTP_OK
+        assert 'location_fallback' not in data
+        assert mock_get_weather.call_args[0][:3] == (MV_LAT, MV_LON, 'Mountain View')
+        mock_locator.locate.assert_called_once_with('8.8.8.8')
+
+    @patch('main.weather_manager.get_weather')
+    def test_auto_failure_falls_back(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test an unknown IP falls back through the usual default chain"""
+        mock_get_weather.return_value = mock_weather_data
+
+        with self.locator(None):
+            response = client.get(
+                '/api/weather?auto=1', headers={'X-Forwarded-For': '8.8.8.8'}
+            )
+        data = json.loads(response.data)
+
+        assert data['location_fallback']['reason'] == 'ip_lookup_failed'
+        assert data['location_fallback']['source'] == 'instance'
+
+    def test_index_redirects_first_visit(self, client: Any) -> None:
+        """Test / sends visitors without a saved location to their IP's city"""
+        headers = {'X-Forwarded-For': '8.8.8.8'}
+        with self.locator((MV_LAT, MV_LON, 'Mountain View')):
+            response = client.get('/', headers=headers)
+            client.set_cookie('weather_default_location', 'nyc')
+            returning = client.get('/', headers=headers)
+
+        assert response.status_code == HTTP_FOUND
+        assert response.headers['Location'].endswith(
+            '/37.3860,-122.0838/Mountain-View'
+        )
+        assert returning.status_code == HTTP_OK
+
+
+class TestCityRegistryAPI:
+    """Test editing the city registry at runtime"""
+
+    def teardown_method(self) -> None:
+        """Put the built-in cities back"""
+        city_registry.replace(DEFAULT_CITIES)
+
+    def test_add_use_and_remove_city(self, client: Any, tmp_path: Path) -> None:
+        """Test an added city and its aliases route, then go away on delete"""
+        path = tmp_path / 'cities.json'
+        city = {'lat': 30.2672, 'lon': -97.7431, 'name': 'Austin', 'aliases': ['atx']}
+        with patch.object(city_registry, 'path', str(path)):
+            bad = client.put('/api/admin/cities/austin', json={'lat': 'north'})
+            created = client.put('/api/admin/cities/austin', json=city)
+            updated = client.put('/api/admin/cities/Austin', json=city)
+            alias = client.get('/atx')
+            page = client.get('/austin')
+            listed = json.loads(client.get('/api/admin/cities').data)
+            deleted = client.delete('/api/admin/cities/austin')
+            missing = client.delete('/api/admin/cities/austin')
+
+        assert bad.status_code == HTTP_BAD_REQUEST
+        assert created.status_code == HTTP_CREATED
+        assert updated.status_code == HTTP_OK
+        assert alias.headers['Location'].endswith('/austin')
+        assert b'"austin": [30.2672, -97.7431, "Austin"]' in page.data
+        assert listed['cities']['austin']['aliases'] == ['atx']
+        saved = json.loads(path.read_text())
+        assert 'chicago' in saved
+        assert 'austin' not in saved
+        assert deleted.status_code == HTTP_OK
+        assert missing.status_code == HTTP_NOT_FOUND
+        assert resolve_city_key('atx') is None
+
+
+class TestProviderRegistrationAPI:
+    """Test adding and removing providers at runtime"""
+
+    auth = {'Authorization': f'Bearer {BOOTSTRAP_TOKEN}'}
+
+    def teardown_method(self) -> None:
+        """Drop anything a test registered"""
+        weather_manager.remove_provider('Spare')
+
+    def test_register_and_remove(self, client: Any) -> None:
+        """Test a registered provider joins the fallbacks and leaves on delete"""
+        with patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            anonymous = client.post('/api/providers', json={'type': 'OpenMeteo'})
+            created = client.post(
+                '/api/providers',
+                json={'type': 'OpenMeteo', 'name': 'Spare', 'timeout': 4},
+                headers=self.auth,
+            )
+            duplicate = client.post(
+                '/api/providers',
+                json={'type': 'OpenMeteo', 'name': 'Spare'},
+                headers=self.auth,
+            )
+            listed = json.loads(client.get('/api/providers').data)
+            deleted = client.delete('/api/providers/Spare', headers=self.auth)
+            missing = client.delete('/api/providers/Spare', headers=self.auth)
+
+        assert anonymous.status_code == HTTP_UNAUTHORIZED
+        assert created.status_code == HTTP_CREATED
+        assert json.loads(created.data)['provider']['timeout'] == 4  # noqa: PLR2004
+        assert duplicate.status_code == HTTP_CONFLICT
+        assert listed['fallbacks'][-1] == 'Spare'
+        assert 'PirateWeather' in listed['types']
+        assert deleted.status_code == HTTP_OK
+        assert missing.status_code == HTTP_NOT_FOUND
+        assert 'Spare' not in weather_manager.providers
+
+    def test_register_rejects_bad_entries(self, client: Any) -> None:
+        """Test unknown types and missing keys are refused"""
+        unknown = client.post('/api/providers', json={'type': 'AccuWeather'})
+        keyless = client.post(
+            '/api/providers', json={'type': 'PirateWeather', 'name': 'Spare'}
+        )
+        malformed = client.post('/api/providers', data='nope')
+
+        assert unknown.status_code == HTTP_BAD_REQUEST
+        assert 'expected one of' in json.loads(unknown.data)['error']
+        assert keyless.status_code == HTTP_BAD_REQUEST
+        assert malformed.status_code == HTTP_BAD_REQUEST
+        assert 'Spare' not in weather_manager.providers
+
+    def test_primary_hands_over_on_delete(self, client: Any) -> None:
+        """Test removing the primary promotes the first fallback"""
+        original = weather_manager.primary_provider
+        client.post(
+            '/api/providers',
+            json={'type': 'OpenMeteo', 'name': 'Spare', 'primary': True},
+        )
+        assert weather_manager.primary_provider == 'Spare'
+
+        client.delete('/api/providers/Spare')
+
+        assert weather_manager.primary_provider == original
+        assert weather_manager.preferred_primary == original
+
+    def test_dry_run(self, client: Any) -> None:
+        """Test a single provider runs at the requested spot, uncached
+
+        Unusable coordinates fall back to the default like any other endpoint.
+        """
+        run = {'provider': 'OpenMeteo', 'ok': True, 'raw': {'current': {}}}
+        with patch.object(
+            weather_manager.providers['OpenMeteo'], 'dry_run', return_value=run
+        ) as dry_run:
+            response = client.get('/api/providers/OpenMeteo/test?lat=35.68&lon=139.7')
+            bad = client.get('/api/providers/OpenMeteo/test?lat=135&lon=0')
+            with patch('main.DEFAULT_LOCATION', ''):
+                unplaced = client.get('/api/providers/OpenMeteo/test')
+        missing = client.get('/api/providers/Nowhere/test')
+
+        data = json.loads(response.data)
+        assert data['raw'] == {'current': {}}
+        assert (data['lat'], data['lon']) == (35.68, 139.7)  # noqa: PLR2004
+        assert response.headers['Cache-Control'] == 'no-store'
+        assert dry_run.call_args_list[0][0] == (
+            35.68,
+            139.7,
+            '35.6800,139.7000',
+            None,
+        )
+        assert json.loads(bad.data)['location_fallback']['reason'] == (
+            'invalid_coordinates'
+        )
+        assert dry_run.call_args_list[1][0][2] == 'Chicago'
+        assert dry_run.call_count == 2  # noqa: PLR2004
+        assert unplaced.status_code == HTTP_BAD_REQUEST
+        assert missing.status_code == HTTP_NOT_FOUND
+
+    def test_last_provider_kept(self, client: Any) -> None:
+        """Test the only provider can't be removed"""
+        with patch.object(
+            weather_manager, 'providers', {'OpenMeteo': MagicMock()}
+        ):
+            response = client.delete('/api/providers/OpenMeteo')
+
+        assert response.status_code == HTTP_CONFLICT
+
+
+class TestConfigReload:
+    """Test re-reading the config file while running"""
+
+    def test_reload_applies_changes(self, tmp_path: Path) -> None:
+        """Test providers, cities, the cache TTL and rules pick up the new file"""
+        path = tmp_path / 'weather.toml'
+        path.write_text(
+            'cache_ttl = 90\n\n[[providers]]\ntype = "OpenMeteo"\nname = "Fresh"\n\n'
+            '[cities.fresno]\nlat = 36.74\nlon = -119.79\nname = "Fresno"\n'
+        )
+        environ = {
+            'WEATHER_CONFIG': str(path),
+            'PROVIDERS_CONFIG_PATH': str(tmp_path / 'absent.json'),
+        }
+        with (
+            patch.dict('os.environ', environ),
+            patch('main.config_file', ConfigFile()),
+            patch('main.WEATHER_CACHE_TTL', CACHE_TTL_SECONDS),
+            patch('main.weather_manager') as mock_manager,
+            patch('main.city_registry') as mock_registry,
+            patch('main.notification_rules') as mock_rules,
+        ):
+            mock_registry.path = str(tmp_path / 'cities.json')
+
+            assert reload_configuration()
+
+            assert main.WEATHER_CACHE_TTL == 90  # noqa: PLR2004
+            settings = mock_manager.reconfigure.call_args.args[0]
+            assert [entry.name for entry in settings] == ['Fresh']
+            cities = mock_registry.replace.call_args.args[0]
+            assert cities['fresno']['name'] == 'Fresno'
+            mock_rules.load.assert_called_once()
+
+    def test_broken_file_keeps_settings(self, tmp_path: Path) -> None:
+        """Test a reload of an invalid file changes nothing"""
+        path = tmp_path / 'weather.toml'
+        path.write_text('cache_ttl = ')
+        with (
+            patch.dict('os.environ', {'WEATHER_CONFIG': str(path)}),
+            patch('main.config_file', ConfigFile()),
+            patch('main.weather_manager') as mock_manager,
+        ):
+            assert not reload_configuration()
+
+            mock_manager.reconfigure.assert_not_called()
+
+
+class TestHttps:
+    """Test serving HTTPS directly"""
+
+    def test_hsts_only_when_serving_tls(self, client: Any) -> None:
+        """Test HSTS is sent over HTTPS the app terminates itself"""
+        with patch.dict('main.app.config', {'TLS_CONTEXT': MagicMock()}):
+            secure = client.get('/healthz', base_url='https://localhost')
+            plain = client.get('/healthz')
+        proxied = client.get('/healthz', base_url='https://localhost')
+
+        assert 'max-age=' in secure.headers['Strict-Transport-Security']
+        assert 'Strict-Transport-Security' not in plain.headers
+        assert 'Strict-Transport-Security' not in proxied.headers
+
+    def test_reload_picks_up_renewed_certificate(self, tmp_path: Path) -> None:
+        """Test SIGHUP reloads the certificate into the live TLS context"""
+        context = MagicMock()
+        environ = {
+            'WEATHER_CONFIG': str(tmp_path / 'absent.toml'),
+            'TLS_CERT_FILE': 'fullchain.pem',
+            'TLS_KEY_FILE': 'privkey.pem',
+        }
+        with (
+            patch.dict('os.environ', environ),
+            patch.dict('main.app.config', {'TLS_CONTEXT': context}),
+            patch('main.config_file', ConfigFile()),
+            patch('main.weather_manager'),
+            patch('main.city_registry', MagicMock(path=str(tmp_path / 'cities.json'))),
+            patch('main.notification_rules'),
+        ):
+            assert reload_configuration()
+
+        context.load_cert_chain.assert_called_once_with('fullchain.pem', 'privkey.pem')
+
+
+class TestRateLimiting:
+    """Test the per-client API rate limit"""
+
+    def test_over_limit_refused(self, client: Any) -> None:
+        """Test requests past the burst get a 429 with Retry-After"""
+        with patch('main.rate_limiter', RateLimiter(60, 1)):
+            first = client.get('/api/cache/stats')
+            second = client.get('/api/cache/stats')
+            other = client.get(
+                '/api/cache/stats', environ_base={'REMOTE_ADDR': '198.51.100.1'}
+            )
+
+        assert first.status_code == HTTP_OK
+        assert first.headers['RateLimit-Remaining'] == '0'
+        assert second.status_code == HTTP_TOO_MANY_REQUESTS
+        assert second.headers['Retry-After'] == '1'
+        assert second.get_json()['retry_after'] == 1
+        assert second.get_json()['code'] == 'rate_limited'
+        assert other.status_code == HTTP_OK
+
+    def test_pages_not_limited(self, client: Any) -> None:
+        """Test only the API counts against the limit"""
+        with patch('main.rate_limiter', RateLimiter(60, 1)):
+            for _ in range(3):
+                response = client.get('/healthz')
+
+        assert response.status_code == HTTP_OK
+        assert 'RateLimit-Limit' not in response.headers
+
+    def test_clients_behind_proxy_counted_apart(self, client: Any) -> None:
+        """Test clients behind a trusted proxy don't share the proxy's bucket"""
+        url = '/api/cache/stats'
+        with patch('main.rate_limiter', RateLimiter(60, 1)):
+            first = client.get(url, headers={'X-Forwarded-For': '198.51.100.1'})
+            second = client.get(url, headers={'X-Forwarded-For': '198.51.100.2'})
+            # A direct client can't dodge its limit by making up addresses
+            direct = {'REMOTE_ADDR': '203.0.113.5'}
+            client.get(url, environ_base=direct)
+            spoofed = client.get(
+                url, environ_base=direct, headers={'X-Forwarded-For': '198.51.100.3'}
+            )
+
+        assert first.status_code == HTTP_OK
+        assert second.status_code == HTTP_OK
+        assert spoofed.status_code == HTTP_TOO_MANY_REQUESTS
+
+
+class TestRequestBodies:
+    """Test the request body size limit"""
+
+    def test_oversized_body_refused(self, client: Any) -> None:
+        """Test a body over MAX_BODY_BYTES gets a JSON 413 before parsing"""
+        with patch.dict(main.app.config, {'MAX_CONTENT_LENGTH': 100}):
+            response = client.post(
+                '/api/favorites', json={'name': 'x' * 200, 'location': 'nyc'}
+            )
+
+        assert response.status_code == HTTP_PAYLOAD_TOO_LARGE
+        assert response.get_json() == {
+            'code': 'payload_too_large',
+            'message': 'Request body too large',
+            'provider': None,
+            'retryable': False,
+            'error': 'Request body too large',
+            'max_bytes': 100,
+        }
+
+
+class TestErrorResponses:
+    """Test API failures come back as typed JSON errors"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_unexpected_error_is_json(
+        self, mock_get_weather: MagicMock, client: Any
+    ) -> None:
+        """Test a crash in the API is an internal_error body, not an HTML page"""
+        mock_get_weather.side_effect = RuntimeError('boom')
+
+        with patch.dict(main.app.config, {'PROPAGATE_EXCEPTIONS': False}):
+            response = client.get('/api/weather?lat=41.8781&lon=-87.6298')
+
+        assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+        assert response.get_json() == {
+            'code': 'internal_error',
+            'message': 'Internal server error',
+            'provider': None,
+            'retryable': False,
+            'error': 'Internal server error',
+        }
+
+    def test_unknown_feed_location(self, client: Any) -> None:
+        """Test a place nobody knows is a location_not_found 404"""
+        with patch('main.resolve_location_segment', return_value=None):
+            response = client.get('/feed/atlantis.xml')
+
+        assert response.status_code == HTTP_NOT_FOUND
+        assert response.get_json()['code'] == 'location_not_found'
+
+    def test_bad_coordinates(self, client: Any) -> None:
+        """Test out-of-range coordinates are an invalid_location 400"""
+        response = client.get('/api/marine?lat=91&lon=0')
+
+        assert response.status_code == HTTP_BAD_REQUEST
+        assert response.get_json()['code'] == 'invalid_location'
+        assert response.get_json()['retryable'] is False
+
+
+class TestRequestLogging:
+    """Test request ids and the JSON access log"""
+
+    def log_entries(self, capsys: Any) -> list[dict[str, Any]]:
+        """The JSON access log lines printed so far"""
+        return [
+            json.loads(line)
+            for line in capsys.readouterr().out.splitlines()
+            if line.startswith('{')
+        ]
+
+    @patch('main.weather_manager.get_weather')
+    def test_weather_request_logged(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        capsys: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the log line records the provider and whether the cache answered"""
+        mock_get_weather.return_value = mock_weather_data
+        weather_cache.clear()
+        url = '/api/weather?lat=41.8781&lon=-87.6298'
+
+        miss = client.get(url)
+        hit = client.get(url)
+
+        first, second = self.log_entries(capsys)
+        assert first['request_id'] == miss.headers['X-Request-Id']
+        assert first['method'] == 'GET'
+        assert first['path'] == '/api/weather'
+        assert first['status'] == HTTP_OK
+        assert first['provider'] == 'OpenMeteo'
+        assert first['cache_hit'] is False
+        assert first['latency_ms'] >= 0
+        assert second['request_id'] == hit.headers['X-Request-Id']
+        assert second['cache_hit'] is True
+        assert first['request_id'] != second['request_id']
+
+    def test_proxy_request_id_kept(self, client: Any, capsys: Any) -> None:
+        """Test an id set by a proxy in front is echoed and logged"""
+        response = client.get(
+            '/api/cache/stats', headers={'X-Request-Id': 'edge-7f3a9c21'}
+        )
+
+        assert response.headers['X-Request-Id'] == 'edge-7f3a9c21'
+        assert self.log_entries(capsys)[-1]['request_id'] == 'edge-7f3a9c21'
+
+    def test_refused_requests_get_ids(self, client: Any) -> None:
+        """Test a rate-limited request still carries an id to report"""
+        with patch('main.rate_limiter', RateLimiter(1, 1)):
+            client.get('/api/cache/stats')
+            refused = client.get('/api/cache/stats')
+
+        assert refused.status_code == HTTP_TOO_MANY_REQUESTS
+        assert refused.headers['X-Request-Id']
+
+    def test_access_log_off(self, client: Any, capsys: Any) -> None:
+        """Test ACCESS_LOG=0 stops the log lines but keeps the header"""
+        with patch('main.ACCESS_LOG', False):
+            response = client.get('/api/cache/stats')
+
+        assert response.headers['X-Request-Id']
+        assert self.log_entries(capsys) == []
+
+
+class TestRequestTracing:
+    """Test request spans and their cache and provider children"""
+
+    @patch('main.weather_manager.get_weather')
+    def test_request_span_tree(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test a weather request is a server span joining the caller's trace"""
+        mock_get_weather.return_value = mock_weather_data
+        weather_cache.clear()
+        exporter = OtlpExporter('http://collector:4318/v1/traces')
+        trace_id = '4bf92f3577b34da6a3ce929d0e0e4736'
+
+        with patch.object(main.tracer, 'exporter', exporter):
+            response = client.get(
+                '/api/weather?lat=41.8781&lon=-87.6298',
+                headers={'traceparent': f'00-{trace_id}-00f067aa0ba902b7-01'},
+            )
+
+        spans = {span.name: span for span in exporter.buffer}
+        server = spans['GET /api/weather']
+        assert server.trace_id == trace_id
+        assert server.parent_id == '00f067aa0ba902b7'
+        assert server.attributes['http.response.status_code'] == HTTP_OK
+        assert server.attributes['http.route'] == '/api/weather'
+        assert server.attributes['request.id'] == response.headers['X-Request-Id']
+        assert server.attributes['weather.provider'] == 'OpenMeteo'
+        assert server.attributes['weather.cache_hit'] is False
+        for name in ('cache.get', 'cache.set'):
+            assert spans[name].parent_id == server.span_id
+        assert spans['cache.get'].attributes['cache.hit'] is False
+        assert main.tracer.current() is None
+
+    def test_static_files_not_traced(self, client: Any) -> None:
+        """Test static assets don't flood the collector"""
+        exporter = OtlpExporter('http://collector:4318/v1/traces')
+
+        with patch.object(main.tracer, 'exporter', exporter):
+            client.get('/static/manifest.json')
+
+        assert not exporter.buffer
+
+
+class TestPostalCodes:
+    """Test ZIP/postal code routes and the ?postal= parameter"""
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+        geocode_cache.clear()
+
+    def index(self, tmp_path: Path) -> Any:
+        """Swap in a postal index holding one Chicago ZIP"""
+        (tmp_path / 'US.txt').write_text(
+            'US\t60622\tChicago\tIllinois\tIL\t\t\t\t\t41.9042\t-87.6802\t4\n',
+            encoding='utf-8',
+        )
+        return patch('main.postal_index', PostalIndex(str(tmp_path)))
+
+    @patch('requests.get')
+    def test_zip_route_redirects(
+        self, mock_get: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test indexed ZIPs resolve offline and others use the geocoder"""
+        mock_get.return_value.json.return_value = {
+            'results': [{'latitude': 51.501, 'longitude': -0.1416, 'name': 'London'}]
+        }
+        with self.index(tmp_path):
+            local = client.get('/zip/60622')
+            geocoded = client.get('/zip/SW1A%201AA?country=gb')
+            invalid = client.get('/zip/60622?country=USA')
+
+        assert local.headers['Location'].endswith(
+            '/41.9042,-87.6802/Chicago%2C-Illinois'
+        )
+        assert geocoded.headers['Location'].endswith('/51.5010,-0.1416/London')
+        assert mock_get.call_args[1]['params']['countryCode'] == 'GB'
+        mock_get.assert_called_once()
+        assert invalid.status_code == HTTP_NOT_FOUND
+
+    @patch('main.geocode_postal_code', return_value=None)
+    @patch('main.weather_manager.get_weather')
+    def test_postal_query_parameter(
+        self,
+        mock_get_weather: MagicMock,
+        mock_geocode: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+        tmp_path: Path,
+    ) -> None:
+        """Test ?postal= resolves, and unknown codes take the fallback chain"""
+        mock_get_weather.return_value = mock_weather_data
+        with self.index(tmp_path):
+            found = client.get('/api/weather?postal=60622-4321')
+            args = mock_get_weather.call_args[0][:3]
+            missing = json.loads(client.get('/api/weather?postal=99999').data)
+
+        assert found.status_code == HTTP_OK
+        assert args == (41.9042, -87.6802, 'Chicago, Illinois')
+        assert missing['location_fallback']['reason'] == 'postal_code_not_found'
+        assert missing['location_fallback']['requested'] == '99999'
+        mock_geocode.assert_called_once_with('99999', 'US')
+
+
+class TestAirportRoutes:
+    """Test /airport/<code> routes"""
+
+    def test_airport_codes_redirect(self, client: Any) -> None:
+        """Test ICAO and IATA codes redirect to the airport's coordinates"""
+        icao = client.get('/airport/EGLL')
+        iata = client.get('/airport/ord')
+        unknown = client.get('/airport/ZZZZ')
+
+        assert icao.status_code == HTTP_FOUND
+        assert icao.headers['Location'].endswith('/51.4700,-0.4543/London-Heathrow')
+        assert iata.headers['Location'].endswith(
+            '/41.9786,-87.9048/Chicago-O%27Hare'
+        )
+        assert unknown.status_code == HTTP_NOT_FOUND
+
+
+class TestMarineAPI:
+    """Test the /api/marine endpoint"""
+
+    def setup_method(self) -> None:
+        """Clear the marine cache before each test"""
+        marine_cache.clear()
+
+    @patch('main.marine_provider.get_weather')
+    def test_marine_forecast(self, mock_get_marine: MagicMock, client: Any) -> None:
+        """Test forecasts are served, cached, and inland points 404"""
+        mock_get_marine.return_value = {
+            'current': {'wave_height': 6.6},
+            'hourly': [],
+            'location': 'Mavericks',
+        }
+        first = client.get('/api/marine?lat=37.4636&lon=-122.4286&location=Surf')
+        client.get('/api/marine?lat=37.4636&lon=-122.4286')
+        bad = client.get('/api/marine?lat=north&lon=0')
+
+        assert first.status_code == HTTP_OK
+        assert json.loads(first.data)['location'] == 'Surf'
+        assert 'max-age' in first.headers['Cache-Control']
+        mock_get_marine.assert_called_once()
+        assert bad.status_code == HTTP_BAD_REQUEST
+
+        mock_get_marine.return_value = None
+        inland = client.get('/api/marine?lat=39.7392&lon=-104.9903')
+        assert inland.status_code == HTTP_NOT_FOUND
+        assert inland.get_json()['code'] == 'not_found'
+
+
+class TestTidesAPI:
+    """Test the /api/tides endpoint"""
+
+    def setup_method(self) -> None:
+        """Clear the tide caches before each test"""
+        tide_cache.clear()
+        tide_station_cache.clear()
+
+    @patch('main.fetch_tide_predictions')
+    @patch('main.fetch_tide_stations')
+    def test_nearest_station_predictions(
+        self, mock_stations: MagicMock, mock_predictions: MagicMock, client: Any
+    ) -> None:
+        """Test predictions come from the nearest station and are cached"""
+        mock_stations.return_value = [SF_TIDE_STATION]
+        upcoming = (datetime.now(timezone.utc) + timedelta(hours=2)).isoformat()
+        mock_predictions.return_value = [
+            {'time': '2000-01-01T00:00:00+00:00', 'type': 'high', 'height_ft': 5.1},
+            {'time': upcoming, 'type': 'low', 'height_ft': -0.4},
+        ]
+
+        response = client.get('/api/tides?lat=37.77&lon=-122.42')
+        client.get('/api/tides?lat=37.78&lon=-122.41')
+        far = client.get('/api/tides?lat=41.8781&lon=-87.6298')
+        data = json.loads(response.data)
+
+        assert response.status_code == HTTP_OK
+        assert data['station']['id'] == '9414290'
+        assert data['datum'] == 'MLLW'
+        assert [tide['type'] for tide in data['predictions']] == ['low']
+        mock_stations.assert_called_once()
+        mock_predictions.assert_called_once()
+        assert far.status_code == HTTP_NOT_FOUND
+
+    @patch('main.fetch_tide_stations')
+    def test_noaa_failure(self, mock_stations: MagicMock, client: Any) -> None:
+        """Test NOAA outages are reported as a bad gateway"""
+        mock_stations.side_effect = requests.exceptions.ConnectionError('down')
+
+        response = client.get('/api/tides?lat=37.77&lon=-122.42')
+
+        assert response.status_code == HTTP_BAD_GATEWAY
+        data = response.get_json()
+        assert (data['code'], data['provider']) == ('provider_failed', 'NOAA CO-OPS')
+        assert data['retryable'] is True
+
+
+class TestPrefsAPI:
+    """Test signed-cookie visitor preferences and how responses honor them"""
+
+    def setup_method(self) -> None:
+        """Clear the weather cache before each test"""
+        weather_cache.clear()
+
+    def test_defaults_and_update(self, client: Any) -> None:
+        """Test prefs start at the defaults and PUTs merge into a signed cookie"""
+        assert json.loads(client.get('/api/prefs').data) == {
+            'units': 'imperial',
+            'time_format': '12h',
+            'location': None,
+            'theme': 'auto',
+        }
+
+        response = client.put('/api/prefs', json={'units': 'metric'})
+        client.put('/api/prefs', json={'time_format': '24h'})
+        prefs = json.loads(client.get('/api/prefs').data)
+
+        assert response.status_code == HTTP_OK
+        assert 'HttpOnly' in response.headers['Set-Cookie']
+        assert prefs['units'] == 'metric'
+        assert prefs['time_format'] == '24h'
+
+    @patch('main.geocode_city', return_value=None)
+    def test_rejects_bad_prefs(self, mock_geocode: MagicMock, client: Any) -> None:
+        """Test invalid values and unknown home locations are refused"""
+        bad_units = client.put('/api/prefs', json={'units': 'kelvin'})
+        bad_home = client.put('/api/prefs', json={'location': 'not a real place'})
+
+        assert bad_units.status_code == HTTP_BAD_REQUEST
+        assert bad_home.status_code == HTTP_BAD_REQUEST
+
+    def test_theme_from_query_or_prefs(self, client: Any) -> None:
+        """Test ?theme= beats the saved theme, and auto sets no data-theme"""
+        auto = client.get('/chicago').data.decode()
+        client.put('/api/prefs', json={'theme': 'dark'})
+        saved = client.get('/chicago').data.decode()
+        override = client.get('/chicago?theme=white').data.decode()
+
+        assert '<body id="app-body">' in auto
+        assert '<body id="app-body" data-theme="dark">' in saved
+        assert '<body id="app-body" data-theme="white">' in override
+        assert '@media (prefers-color-scheme: dark)' in auto
+
+    def test_tampered_cookie_ignored(self, client: Any) -> None:
+        """Test an unsigned prefs cookie falls back to the defaults"""
+        client.set_cookie('weather_prefs', '{"units": "metric"}')
+
+        prefs = json.loads(client.get('/api/prefs').data)
+
+        assert prefs['units'] == 'imperial'
+
+    @patch('main.weather_manager.get_weather')
+    def test_weather_in_preferred_units(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test saved units apply to /api/weather unless ?units= overrides them"""
+        mock_get_weather.return_value = mock_weather_data
+        client.put('/api/prefs', json={'units': 'metric'})
+
+        metric = json.loads(
+            client.get('/api/weather?lat=41.8781&lon=-87.6298&location=Chicago').data
+        )
+        imperial = json.loads(
+            client.get(
+                '/api/weather?lat=41.8781&lon=-87.6298&location=Chicago&units=imperial'
+            ).data
+        )
+
+        assert metric['current']['temperature'] == 22  # noqa: PLR2004
+        assert metric['daily'][0]['high'] == 25  # noqa: PLR2004
+        assert metric['units']['wind_speed'] == 'km/h'
+        assert imperial['current']['temperature'] == 72  # noqa: PLR2004
+        assert imperial['units']['temperature'] == '°F'
+
+    @patch('main.weather_manager.get_weather')
+    def test_shared_caches_split_by_prefs(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test pref-dependent responses vary on the cookie, private once saved"""
+        mock_get_weather.return_value = mock_weather_data
+        url = '/api/weather?lat=41.8781&lon=-87.6298&location=Chicago'
+
+        anonymous = client.get(url)
+        client.put('/api/prefs', json={'units': 'metric'})
+        saved = client.get(url)
+
+        assert 'Cookie' in anonymous.headers['Vary']
+        assert anonymous.headers['Cache-Control'].startswith('public, ')
+        assert 'Cookie' in saved.headers['Vary']
+        assert saved.headers['Cache-Control'].startswith('private, ')
+
+    @patch('main.weather_manager.get_weather')
+    def test_saving_prefs_invalidates_last_modified(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test a copy from before prefs were saved isn't answered with a 304"""
+        mock_get_weather.return_value = mock_weather_data
+        url = '/api/weather?lat=41.8781&lon=-87.6298&location=Chicago'
+        last_modified = client.get(url).headers['Last-Modified']
+
+        with patch('main.time.time', return_value=time.time() + 60):
+            client.put('/api/prefs', json={'units': 'metric'})
+        response = client.get(url, headers={'If-Modified-Since': last_modified})
+
+        assert response.status_code == HTTP_OK
+        assert response.headers['Last-Modified'] != last_modified
+
+    @patch('main.weather_manager.get_weather')
+    def test_home_location_default(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the saved home location is used when no location is given"""
+        mock_get_weather.return_value = mock_weather_data
+        client.put('/api/prefs', json={'location': 'nyc'})
+
+        response = client.get('/api/weather?lat=91&lon=0')
+
+        assert response.status_code == HTTP_OK
+        assert mock_get_weather.call_args[0][2] == 'New York City'
+        assert client.get('/').status_code == HTTP_OK
+
+
+class TestFavoritesAPI:
+    """Test saved favorites and the background work they drive"""
+
+    def favorites(self, tmp_path: Path) -> Any:
+        """Swap in an empty favorites store backed by a temporary database"""
+        return patch(
+            'main.favorites_store', FavoritesStore(str(tmp_path / 'favorites.db'))
+        )
+
+    def test_crud(self, client: Any, tmp_path: Path) -> None:
+        """Test favorites are created from coordinates or cities, edited and removed"""
+        with self.favorites(tmp_path):
+            created = client.post(
+                '/api/favorites',
+                json={'name': 'Home', 'lat': 41.8781, 'lon': -87.6298},
+            )
+            tokyo = json.loads(
+                client.post('/api/favorites', json={'location': 'tokyo'}).data
+            )
+            favorite_id = json.loads(created.data)['id']
+            renamed = client.put(
+                f'/api/favorites/{favorite_id}', json={'name': 'House'}
+            )
+            listed = json.loads(client.get('/api/favorites').data)['favorites']
+            deleted = client.delete(f'/api/favorites/{favorite_id}')
+            missing = client.get(f'/api/favorites/{favorite_id}')
+
+        assert created.status_code == HTTP_CREATED
+        assert tokyo['name'] == 'Tokyo'
+        assert json.loads(renamed.data)['name'] == 'House'
+        assert [fav['name'] for fav in listed] == ['House', 'Tokyo']
+        assert deleted.status_code == HTTP_OK
+        assert missing.status_code == HTTP_NOT_FOUND
+
+    @patch('main.geocode_city', return_value=None)
+    def test_rejects_bad_favorites(
+        self, mock_geocode: MagicMock, client: Any, tmp_path: Path
+    ) -> None:
+        """Test unknown locations and invalid coordinates are refused"""
+        with self.favorites(tmp_path):
+            unknown = client.post('/api/favorites', json={'location': 'nowhere'})
+            bad = client.post('/api/favorites', json={'name': 'X', 'lat': 'north'})
+
+        assert unknown.status_code == HTTP_BAD_REQUEST
+        assert bad.status_code == HTTP_BAD_REQUEST
+
+    @patch('main.weather_manager.get_weather')
+    def test_prefetch_and_digest_locations(
+        self,
+        mock_get_weather: MagicMock,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test favorites are kept cached and replace DIGEST_LOCATIONS"""
+        weather_cache.clear()
+        mock_get_weather.return_value = mock_weather_data
+        with self.favorites(tmp_path) as store:
+            assert digest_locations()[0][2] == 'Chicago'
+            store.add({'name': 'Tokyo', 'lat': 35.6762, 'lon': 139.6503})
+
+            assert prefetch_locations() == 1
+            assert prefetch_locations() == 0
+            assert digest_locations() == [(35.6762, 139.6503, 'Tokyo')]
+        assert weather_cache_key('35.6762,139.6503') in weather_cache
+
+
+class TestMultiDashboard:
+    """Test the /multi grid of locations"""
+
+    def setup_method(self) -> None:
+        """Clear the weather cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.geocode_city', return_value=None)
+    @patch('main.weather_manager.get_weather')
+    def test_cities_fetched_into_grid(
+        self,
+        mock_get_weather: MagicMock,
+        mock_geocode: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test each requested city gets a tile, with unknown names listed"""
+        mock_get_weather.return_value = mock_weather_data
+
+        response = client.get('/multi?cities=chicago,tokyo,nowhere-at-all')
+        page = response.data.decode()
+
+        assert response.status_code == HTTP_OK
+        assert mock_get_weather.call_count == 2  # noqa: PLR2004
+        assert 'href="/chicago"' in page
+        assert 'href="/tokyo"' in page
+        assert '72°F' in page
+        assert "Couldn't find: nowhere-at-all" in page
+        assert weather_cache_key('41.8781,-87.6298') in weather_cache
+
+    @patch('main.weather_manager.get_weather')
+    def test_defaults_to_favorites(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test favorites fill the grid when no cities are given"""
+        mock_get_weather.return_value = mock_weather_data
+        store = FavoritesStore(str(tmp_path / 'favorites.db'))
+        store.add({'name': 'Cabin', 'lat': 46.5, 'lon': -87.4})
+        store.add({'name': 'Office', 'lat': 41.88, 'lon': -87.63})
+
+        with patch('main.favorites_store', store):
+            page = client.get('/multi').data.decode()
+
+        assert 'href="/46.5000,-87.4000/Cabin"' in page
+        assert 'Office' in page
+
+
+class TestStaticAssets:
+    """Test content-hashed static asset URLs and their caching"""
+
+    def test_page_links_versioned_assets(self, client: Any) -> None:
+        """Test pages link assets by hash and those URLs are cached for good"""
+        page = client.get('/chicago').data.decode()
+        version = asset_manifest.version('js/weather-components.js')
+
+        versioned = client.get(f'/static/js/weather-components.js?v={version}')
+        unversioned = client.get('/static/js/weather-components.js')
+
+        assert f'/static/js/weather-components.js?v={version}' in page
+        assert 'immutable' in versioned.headers['Cache-Control']
+        assert unversioned.headers['Cache-Control'] == 'no-cache'
+        versioned.close()
+        unversioned.close()
+
+
+class TestKiosk:
+    """Test the fullscreen kiosk view"""
+
+    def setup_method(self) -> None:
+        """Clear the weather cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_rotation(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the page renders one location and links the next for no-JS reloads"""
+        mock_get_weather.return_value = mock_weather_data
+
+        page = client.get('/kiosk/chicago?rotate=nyc,chicago&interval=5').data.decode()
+        second = client.get('/kiosk/chicago?rotate=nyc&at=1').data.decode()
+
+        assert 'content="10;url=/kiosk/chicago?rotate=nyc%2Cchicago' in page
+        assert 'at=1' in page
+        assert '72°F' in page
+        assert page.count('"lat":') == 2  # noqa: PLR2004
+        assert '<div class="kiosk-location" id="location">New York City</div>' in second
+
+    def test_interval_clamped(self) -> None:
+        """Test intervals stay within what a wall display can use"""
+        assert kiosk_interval(None) == 30  # noqa: PLR2004
+        assert kiosk_interval('2') == 10  # noqa: PLR2004
+        assert kiosk_interval('999999') == 3600  # noqa: PLR2004
+        assert kiosk_interval('soon') == 30  # noqa: PLR2004
+
+
+class TestChartsAPI:
+    """Test the chart-ready series endpoint"""
+
+    def setup_method(self) -> None:
+        """Clear the weather cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_series_in_requested_units(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test hourly series come back plot-ready and honor ?units="""
+        mock_get_weather.return_value = mock_weather_data
+
+        data = client.get('/api/charts?lat=41.88&lon=-87.63').get_json()
+        metric = client.get('/api/charts?lat=41.88&lon=-87.63&units=metric').get_json()
+
+        assert data['series']['temperature'] == [72, 75]
+        assert data['axes']['temperature']['unit'] == '°F'
+        assert data['chartjs']['labels'] == ['12p', '1p']
+        assert metric['series']['temperature'] == [22, 24]
+        assert metric['axes']['precipitation']['unit'] == 'mm'
+
+
+class TestSummaryAPI:
+    """Test the spoken-word forecast summary endpoint"""
+
+    def setup_method(self) -> None:
+        """Clear the weather cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_styles(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test both styles describe the forecast and unknown styles are rejected"""
+        mock_get_weather.return_value = mock_weather_data
+
+        brief = client.get('/api/summary?lat=41.88&lon=-87.63').get_json()
+        detailed = client.get('/api/summary?lat=41.88&lon=-87.63&style=detailed')
+        invalid = client.get('/api/summary?lat=41.88&lon=-87.63&style=haiku')
+
+        assert brief['style'] == 'brief'
+        assert brief['summary'].endswith('high of 77.')
+        assert 'Currently 72 degrees' in detailed.get_json()['summary']
+        assert invalid.status_code == HTTP_BAD_REQUEST
+        assert invalid.get_json()['styles'] == ['brief', 'detailed']
+
+
+class TestAlertBanners:
+    """Test severe-weather banners rendered into the pages"""
+
+    def setup_method(self) -> None:
+        """Clear the alerts cache before each test"""
+        alerts_cache.clear()
+
+    @patch('main.nws_provider.get_weather')
+    def test_dashboard_banner(self, mock_get_alerts: MagicMock, client: Any) -> None:
+        """Test the dashboard shows each alert's event, color and expiry"""
+        mock_get_alerts.return_value = {
+            'alerts': {
+                'alerts': [
+                    {
+                        'type': 'Tornado Warning',
+                        'severity': 'Extreme',
+                        'color': '#8B0000',
+                        'end_time': '2024-05-06T18:30:00-05:00',
+                    }
+                ]
+            }
+        }
+
+        page = client.get('/chicago').get_data(as_text=True)
+
+        assert 'Tornado Warning (Extreme)' in page
+        assert '--alert-color: #8B0000' in page
+        assert 'Mon 6:30 PM' in page
+
+    @patch('main.nws_provider.get_weather', return_value=None)
+    def test_no_alerts(self, mock_get_alerts: MagicMock, client: Any) -> None:
+        """Test pages render without a banner when alerts are unavailable"""
+        page = client.get('/chicago').get_data(as_text=True)
+
+        assert 'id="alert-banners"' not in page
+        mock_get_alerts.assert_called_once()
+
+    def test_worst_first(self) -> None:
+        """Test banners are ordered by severity and tolerate missing fields"""
+        banners = alert_banners(
+            {
+                'alerts': {
+                    'alerts': [
+                        {'type': 'Frost Advisory', 'severity': 'Minor'},
+                        {'severity': None},
+                        {'type': 'Flood Warning', 'severity': 'Severe'},
+                    ]
+                }
+            }
+        )
+
+        assert [banner['event'] for banner in banners] == [
+            'Flood Warning',
+            'Frost Advisory',
+            'Weather alert',
+        ]
+        assert banners[-1]['expires_label'] is None
+
+
+class TestIconSetSelection:
+    """Test choosing an icon pack for pages and the JSON API"""
+
+    def setup_method(self) -> None:
+        """Clear the weather cache before each test"""
+        weather_cache.clear()
+
+    @patch('main.weather_manager.get_weather')
+    def test_query_selects_pack(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test ?icons= picks the pack for the JSON table and rendered pages"""
+        mock_get_weather.return_value = mock_weather_data
+
+        default = client.get('/api/weather?lat=41.88&lon=-87.63').get_json()
+        emoji = client.get('/api/weather?lat=41.88&lon=-87.63&icons=emoji').get_json()
+        page = client.get('/multi?cities=chicago&icons=wi').get_data(as_text=True)
+
+        assert default['icon_set']['name'] == 'default'
+        assert emoji['icon_set']['kind'] == 'text'
+        assert emoji['icon_set']['icons'][emoji['current']['icon']] == '☀️'
+        assert 'class="weather-icon wi wi-day-sunny"' in page
+        assert 'weather-icons.min.css' in page
+
+    @patch('main.ICON_SET', 'meteocons')
+    @patch('main.nws_provider.get_weather', return_value=None)
+    @patch('main.weather_manager.get_weather', return_value=None)
+    def test_configured_default(
+        self, mock_get_weather: MagicMock, mock_get_alerts: MagicMock, client: Any
+    ) -> None:
+        """Test ICON_SET sets the pack when the request doesn't pick one"""
+        page = client.get('/kiosk').get_data(as_text=True)
+
+        assert '"name": "meteocons"' in page
+        mock_get_weather.assert_called_once()
+        mock_get_alerts.assert_called_once()
+
+
+class TestCacheAdminAPI:
+    """Test the cache entry index and admin invalidation"""
+
+    chicago = '41.8781,-87.6298'
+    auth = {'Authorization': f'Bearer {BOOTSTRAP_TOKEN}'}
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    def test_stats_list_entries_with_age(
+        self, client: Any, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test stats report each live key with its provider and age"""
+        store_weather_data(self.chicago, mock_weather_data)
+        weather_cache_metadata[weather_cache_key(self.chicago)]['fetched_at'] -= 30
+
+        stats = json.loads(client.get('/api/cache/stats').data)
+        [entry] = stats['weather_cache']['entries']
+
+        assert entry['key'] == '41.8781,-87.6298:OpenMeteo:imperial:en'
+        assert entry['location'] == self.chicago
+        assert entry['provider'] == 'OpenMeteo'
+        assert entry['age_seconds'] == 30  # noqa: PLR2004
+        assert entry['ttl_seconds'] == OPEN_METEO_CACHE_TTL
+        assert entry['expires_in_seconds'] == OPEN_METEO_CACHE_TTL - 30
+        assert entry['stale'] is False
+
+    @patch.dict(
+        'main.weather_manager.providers',
+        {'Fast': MagicMock(cache_ttl=60), 'Plain': MagicMock(cache_ttl=None)},
+    )
+    def test_entries_use_answering_provider_ttl(
+        self, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test a fallback's own TTL applies, and the default without one"""
+        store_weather_data(self.chicago, {**mock_weather_data, 'provider': 'Fast'})
+        store_weather_data(
+            self.chicago, {**mock_weather_data, 'provider': 'Plain'}, 'Plain'
+        )
+
+        fast = weather_cache_metadata[weather_cache_key(self.chicago)]
+        plain = weather_cache_metadata[weather_cache_key(self.chicago, 'Plain')]
+        assert fast['ttl'] == 60  # noqa: PLR2004
+        assert plain['ttl'] == CACHE_TTL_SECONDS
+
+    def test_delete_requires_admin(
+        self, client: Any, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test invalidation is an admin action once admin auth is enabled"""
+        store_weather_data(self.chicago, mock_weather_data)
+
+        with patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            one = client.delete(f'/api/cache/{self.chicago}')
+            every = client.delete('/api/cache')
+
+        assert one.status_code == HTTP_UNAUTHORIZED
+        assert every.status_code == HTTP_UNAUTHORIZED
+        assert weather_cache_key(self.chicago) in weather_cache
+
+    def test_delete_by_key_or_location(
+        self, client: Any, mock_weather_data: dict[str, Any]
+    ) -> None:
+        """Test a full key drops one entry and a location drops all of its own"""
+        store_weather_data(self.chicago, mock_weather_data)
+        store_weather_data(self.chicago, mock_weather_data, 'OldProvider')
+        store_weather_data('35.6762,139.6503', mock_weather_data)
+
+        with patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            old_key = str(weather_cache_key(self.chicago, 'OldProvider'))
+            by_key = client.delete(f'/api/cache/{old_key}', headers=self.auth)
+            by_location = client.delete(
+                '/api/cache/41.87810,-87.62980', headers=self.auth
+            )
+            missing = client.delete(f'/api/cache/{self.chicago}', headers=self.auth)
+            invalid = client.delete('/api/cache/downtown', headers=self.auth)
+
+        assert json.loads(by_key.data)['removed'] == [old_key]
+        assert json.loads(by_location.data)['removed'] == [
+            str(weather_cache_key(self.chicago))
+        ]
+        assert missing.status_code == HTTP_NOT_FOUND
+        assert invalid.status_code == HTTP_BAD_REQUEST
+        assert weather_cache_key('35.6762,139.6503') in weather_cache
+        assert len(weather_cache_metadata) == 1
+
+    def test_delete_all(self, client: Any, mock_weather_data: dict[str, Any]) -> None:
+        """Test clearing the whole cache reports how many entries went"""
+        store_weather_data(self.chicago, mock_weather_data)
+        store_weather_data('35.6762,139.6503', mock_weather_data)
+
+        with patch('main.ADMIN_TOKEN', BOOTSTRAP_TOKEN):
+            response = client.delete('/api/cache', headers=self.auth)
+
+        assert json.loads(response.data) == {'success': True, 'removed': 2}
+        assert len(weather_cache) == 0
+        stats = json.loads(client.get('/api/cache/stats').data)
+        assert stats['weather_cache']['entries'] == []
+
+
+class TestPrefetch:
+    """Test refreshing favorite and configured locations ahead of expiry"""
+
+    def setup_method(self) -> None:
+        """Clear cache before each test"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+
+    def favorites(self, tmp_path: Path) -> Any:
+        """Swap in an empty favorites store backed by a temporary database"""
+        return patch('main.favorites_store', FavoritesStore(str(tmp_path / 'f.db')))
+
+    @patch('main.weather_manager.get_weather')
+    def test_refreshes_entries_about_to_expire(
+        self,
+        mock_get_weather: MagicMock,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test an entry inside the lead window is refetched before it expires"""
+        mock_get_weather.return_value = mock_weather_data
+        with self.favorites(tmp_path) as store:
+            store.add({'name': 'Chicago', 'lat': CHICAGO_LAT, 'lon': CHICAGO_LON})
+            assert prefetch_locations() == 1
+            assert prefetch_locations() == 0
+
+            metadata = weather_cache_metadata[weather_cache_key('41.8781,-87.6298')]
+            metadata['fetched_at'] -= OPEN_METEO_CACHE_TTL - PREFETCH_LEAD_SECONDS
+            assert prefetch_locations() == 1
+
+    @patch('main.weather_manager.get_weather')
+    def test_configured_locations_merge_with_favorites(
+        self,
+        mock_get_weather: MagicMock,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test PREFETCH_LOCATIONS are warmed too, each place only once"""
+        mock_get_weather.return_value = mock_weather_data
+        with (
+            self.favorites(tmp_path) as store,
+            patch('main.PREFETCH_LOCATIONS', ['chicago', '35.6762,139.6503']),
+        ):
+            store.add({'name': 'Home', 'lat': CHICAGO_LAT, 'lon': CHICAGO_LON})
+            assert prefetch_locations() == 2  # noqa: PLR2004
+
+        assert weather_cache_key('35.6762,139.6503') in weather_cache
+
+    @patch('main.weather_manager.get_weather')
+    def test_status_reports_schedule_and_last_run(
+        self,
+        mock_get_weather: MagicMock,
+        client: Any,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the status endpoint shows the last run and each target's timer"""
+        mock_get_weather.return_value = mock_weather_data
+        with self.favorites(tmp_path) as store:
+            store.add({'name': 'Chicago', 'lat': CHICAGO_LAT, 'lon': CHICAGO_LON})
+            prefetch_locations()
+            status = json.loads(client.get('/api/prefetch/status').data)
+
+        assert status['schedule']['lead_seconds'] == PREFETCH_LEAD_SECONDS
+        assert status['last_run']['refreshed'] == 1
+        assert status['next_run_at'] is not None
+        [location] = status['locations']
+        assert location['location'] == 'Chicago'
+        expected = OPEN_METEO_CACHE_TTL - PREFETCH_LEAD_SECONDS
+        assert expected - 1 <= location['refresh_in_seconds'] <= expected
+
+
+class TestBackupAPI:
+    """Test triggering a state backup over HTTP"""
+
+    def state(self, tmp_path: Path) -> Any:
+        """Point the state files at tmp_path, with notification rules saved"""
+        (tmp_path / 'rules.json').write_text('[]')
+        return patch.dict(
+            os.environ,
+            {
+                'NOTIFICATION_RULES_PATH': str(tmp_path / 'rules.json'),
+                'HISTORY_DB_PATH': str(tmp_path / 'absent.db'),
+                'FAVORITES_DB_PATH': str(tmp_path / 'absent-favorites.db'),
+                'TOKEN_STORE_PATH': str(tmp_path / 'absent.json'),
+                'CITY_REGISTRY_PATH': str(tmp_path / 'absent-cities.json'),
+                'ANALYTICS_PATH': str(tmp_path / 'absent-analytics.json'),
+            },
+        )
+
+    def test_backup_written(self, client: Any, tmp_path: Path) -> None:
+        """Test the archive lands in BACKUP_DIR with the files that exist"""
+        with (
+            self.state(tmp_path),
+            patch('main.BACKUP_DIR', str(tmp_path / 'backups')),
+        ):
+            response = client.post('/api/admin/backup')
+
+        assert response.status_code == HTTP_OK
+        data = json.loads(response.data)
+        assert data['path'].startswith(str(tmp_path / 'backups' / 'state-'))
+        assert data['path'].endswith('.tar.gz')
+        assert [entry['name'] for entry in data['files']] == [
+            'notification_rules.json'
+        ]
+        with tarfile.open(data['path']) as archive:
+            assert 'notification_rules.json' in archive.getnames()
+
+    def test_download(self, client: Any, tmp_path: Path) -> None:
+        """Test ?download=1 sends the archive back as an attachment"""
+        with (
+            self.state(tmp_path),
+            patch('main.BACKUP_DIR', str(tmp_path / 'backups')),
+            patch('main.BACKUP_FORMAT', '.tar'),
+        ):
+            response = client.post('/api/admin/backup?download=1')
+            body = response.get_data()
+
+        assert response.status_code == HTTP_OK
+        assert 'attachment' in response.headers['Content-Disposition']
+        assert '.tar' in response.headers['Content-Disposition']
+        assert b'manifest.json' in body
+
+    def test_bad_format(self, client: Any, tmp_path: Path) -> None:
+        """Test a BACKUP_FORMAT that isn't an archive suffix is reported"""
+        with (
+            patch('main.BACKUP_DIR', str(tmp_path)),
+            patch('main.BACKUP_FORMAT', '.zip'),
+        ):
+            response = client.post('/api/admin/backup')
+
+        assert response.status_code == HTTP_INTERNAL_SERVER_ERROR
+        assert 'BACKUP_FORMAT' in json.loads(response.data)['error']
+
+
+class TestNotificationRulesAPI:
+    """Test managing notification rules and the background check"""
+
+    def rules(self, tmp_path: Path) -> Any:
+        """Swap in an empty rule store backed by a temporary file"""
+        return patch(
+            'main.notification_rules', NotificationRules(str(tmp_path / 'rules.json'))
+        )
+
+    def test_create_list_and_delete(self, client: Any, tmp_path: Path) -> None:
+        """Test rules are validated, listed and removed"""
+        rule = {
+            'name': 'Freeze warning',
+            'lat': 41.8781,
+            'lon': -87.6298,
+            'metric': 'temperature',
+            'operator': '<',
+            'threshold': 32,
+            'window': 'tonight',
+            'channels': ['log', 'pager'],
+        }
+        with self.rules(tmp_path):
+            bad = client.post('/api/admin/notifications/rules', json={'name': 'x'})
+            created = client.post('/api/admin/notifications/rules', json=rule)
+            listed = json.loads(client.get('/api/admin/notifications/rules').data)
+            rule_id = json.loads(created.data)['id']
+            deleted = client.delete(f'/api/admin/notifications/rules/{rule_id}')
+            missing = client.delete(f'/api/admin/notifications/rules/{rule_id}')
+
+        assert bad.status_code == HTTP_BAD_REQUEST
+        assert created.status_code == HTTP_CREATED
+        assert json.loads(created.data)['unknown_channels'] == ['pager']
+        assert [saved['name'] for saved in listed['rules']] == ['Freeze warning']
+        assert 'log' in listed['channels']
+        assert deleted.status_code == HTTP_OK
+        assert missing.status_code == HTTP_NOT_FOUND
+
+    def test_new_alerts_are_pushed(self) -> None:
+        """Test alerts are pushed to ALERT_NOTIFICATION_CHANNELS once each"""
+        weather_change_baselines.clear()
+        alerts_data = {
+            'location_name': 'Chicago',
+            'alerts': {
+                'alerts': [
+                    {'id': 'a1', 'type': 'Flood Watch', 'severity': 'Moderate'}
+                ]
+            },
+        }
+        with (
+            patch('main.ALERT_NOTIFICATION_CHANNELS', ['ntfy']),
+            patch('main.socketio.start_background_task') as mock_task,
+        ):
+            detect_new_alerts('41.8781,-87.6298', alerts_data)
+            detect_new_alerts('41.8781,-87.6298', alerts_data)
+
+        mock_task.assert_called_once()
+        _, notification, channels = mock_task.call_args[0]
+        assert notification['title'] == 'Flood Watch - Chicago'
+        assert notification['severity'] == 'moderate'
+        assert channels == ['ntfy']
+
+    @patch('main.get_air_quality', return_value=None)
+    @patch('main.nws_provider.get_weather', return_value=None)
+    @patch('main.notification_dispatcher.dispatch', return_value=1)
+    @patch('main.weather_manager.get_weather')
+    def test_morning_digest(
+        self,
+        mock_get_weather: MagicMock,
+        mock_dispatch: MagicMock,
+        mock_alerts: MagicMock,
+        mock_air_quality: MagicMock,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test each digest location is fetched and sent to the digest channels"""
+        weather_cache.clear()
+        weather_cache_metadata.clear()
+        mock_get_weather.return_value = mock_weather_data
+        with (
+            patch('main.DIGEST_LOCATIONS', ['chicago', 'atlantis-nowhere-1']),
+            patch('main.DIGEST_CHANNELS', ['email']),
+        ):
+            assert send_morning_digests() == 1
+
+        notification, channels = mock_dispatch.call_args[0]
+        assert notification['title'] == 'Morning forecast - Chicago'
+        assert notification['message'].startswith('High 77°F, low 65°F')
+        assert channels == ['email']
+        mock_alerts.assert_called_once()
+
+    @patch('main.notification_dispatcher.dispatch')
+    @patch('main.weather_manager.get_weather')
+    def test_check_fetches_and_dispatches(
+        self,
+        mock_get_weather: MagicMock,
+        mock_dispatch: MagicMock,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the background check fetches missing weather and notifies once"""
+        weather_cache.clear()
+        mock_get_weather.return_value = mock_weather_data
+        store = NotificationRules(str(tmp_path / 'rules.json'))
+        store.create(
+            {
+                'name': 'Warm',
+                'lat': 41.8781,
+                'lon': -87.6298,
+                'metric': 'temperature',
+                'operator': '>',
+                'threshold': 60,
+            }
+        )
+        with patch('main.notification_rules', store):
+            assert check_notification_rules() == 1
+            assert check_notification_rules() == 0
+
+        mock_get_weather.assert_called_once()
+        notification, channels = mock_dispatch.call_args[0]
+        assert notification['location'] == 'Test Location'
+        assert notification['symbol'] == '☀️'
+        assert channels == ['log']
+
+
+class TestWebhooksAPI:
+    """Test managing webhook subscriptions and the background check"""
+
+    subscription = {
+        'name': 'Porch lights',
+        'url': 'https://automation.example.com/hooks/weather',
+        'lat': 41.8781,
+        'lon': -87.6298,
+        'temperature_delta': 5,
+    }
+
+    def test_create_list_and_delete(self, client: Any, tmp_path: Path) -> None:
+        """Test webhooks are validated, listed without secrets and removed"""
+        store = WebhookSubscriptions(str(tmp_path / 'webhooks.json'))
+        with patch('main.webhook_subscriptions', store):
+            bad = client.post('/api/admin/webhooks', json={'name': 'x'})
+            created = client.post('/api/admin/webhooks', json=self.subscription)
+            listed = json.loads(client.get('/api/admin/webhooks').data)
+            webhook_id = json.loads(created.data)['id']
+            deleted = client.delete(f'/api/admin/webhooks/{webhook_id}')
+            missing = client.delete(f'/api/admin/webhooks/{webhook_id}')
+
+        assert bad.status_code == HTTP_BAD_REQUEST
+        assert created.status_code == HTTP_CREATED
+        assert json.loads(created.data)['secret']
+        assert [saved['name'] for saved in listed['webhooks']] == ['Porch lights']
+        assert 'secret' not in listed['webhooks'][0]
+        assert 'temperature_swing' in listed['events']
+        assert deleted.status_code == HTTP_OK
+        assert missing.status_code == HTTP_NOT_FOUND
+
+    @patch('main.deliver', return_value=204)
+    @patch('main.weather_manager.get_weather')
+    def test_check_delivers_changes(
+        self,
+        mock_get_weather: MagicMock,
+        mock_deliver: MagicMock,
+        tmp_path: Path,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test the first check records conditions and a swing is delivered once"""
+        weather_cache.clear()
+        mock_get_weather.return_value = mock_weather_data
+        store = WebhookSubscriptions(str(tmp_path / 'webhooks.json'))
+        created = store.create(self.subscription)
+        warmer = {
+            **mock_weather_data,
+            'current': {**mock_weather_data['current'], 'temperature': MOCK_TEMP + 8},
+        }
+        with patch('main.webhook_subscriptions', store):
+            assert check_webhooks() == 0
+            weather_cache.clear()
+            mock_get_weather.return_value = warmer
+            assert check_webhooks() == 1
+            assert check_webhooks() == 0
+
+        subscription, event = mock_deliver.call_args[0]
+        assert subscription['secret'] == created['secret']
+        assert event['event'] == 'temperature_swing'
+        assert event['previous_temperature'] == MOCK_TEMP
+        last_delivery = store.list_subscriptions()[0]['last_delivery']
+        assert last_delivery['status'] == 204  # noqa: PLR2004
+
+    @patch('main.deliver', side_effect=requests.ConnectionError('refused'))
+    @patch('main.tracked_weather', return_value=None)
+    def test_test_delivery_failure(
+        self,
+        mock_weather: MagicMock,
+        mock_deliver: MagicMock,
+        client: Any,
+        tmp_path: Path,
+    ) -> None:
+        """Test a failed test delivery is reported and recorded"""
+        store = WebhookSubscriptions(str(tmp_path / 'webhooks.json'))
+        created = store.create(self.subscription)
+        with patch('main.webhook_subscriptions', store):
+            response = client.post(f'/api/admin/webhooks/{created["id"]}/test')
+
+        body = json.loads(response.data)
+        assert response.status_code == HTTP_BAD_GATEWAY
+        assert body['delivered'] is False
+        assert body['last_delivery']['error'] == 'refused'
+        assert mock_deliver.call_args[0][1]['event'] == 'test'
+
+
+class TestCityCoords:
+    """Test city coordinates constant"""
+
+    def test_city_coords_structure(self) -> None:
+        """Test that CITY_COORDS has the expected structure"""
+        assert isinstance(CITY_COORDS, dict)
+        assert 'chicago' in CITY_COORDS
+        assert 'nyc' in CITY_COORDS
+        assert 'sf' in CITY_COORDS
+
+        # Test chicago coordinates
+        chicago_data = CITY_COORDS['chicago']
+        assert len(chicago_data) == EXPECTED_COORDS_COUNT
+        assert chicago_data[0] == CHICAGO_LAT  # lat
+        assert chicago_data[1] == CHICAGO_LON  # lon
+        assert chicago_data[2] == 'Chicago'  # name
+
+    def test_all_cities_have_valid_coords(self) -> None:
+        """Test that all cities have valid coordinate data"""
+        for coords in CITY_COORDS.values():
+            assert len(coords) == EXPECTED_COORDS_COUNT
+            assert isinstance(coords[0], int | float)  # latitude
+            assert isinstance(coords[1], int | float)  # longitude
+            assert isinstance(coords[2], str)  # city name
+
+            # Check coordinate ranges
+            assert MIN_LAT <= coords[0] <= MAX_LAT  # latitude range
+            assert MIN_LON <= coords[1] <= MAX_LON  # longitude range
+
+
+class TestCityResolution:
+    """Test unicode city route normalization, aliases, and geocoder fallback"""
+
+    def setup_method(self) -> None:
+        """Clear geocode cache before each test"""
+        geocode_cache.clear()
+
+    def test_normalize_city_name(self) -> None:
+        """Test percent-decoding, case folding, and separator normalization"""
+        assert normalize_city_name('M%C3%BCnchen') == 'münchen'
+        assert normalize_city_name('New_York  City') == 'new-york-city'
+        assert normalize_city_name('ＴＯＫＹＯ') == 'tokyo'  # Full-width letters
+
+    def test_resolve_city_key(self) -> None:
+        """Test registry and alias resolution"""
+        assert resolve_city_key('Chicago') == 'chicago'
+        assert resolve_city_key('東京') == 'tokyo'
+        assert resolve_city_key('München') == 'munich'
+        assert resolve_city_key('Londres') == 'london'
+        assert resolve_city_key('Mádrid') == 'madrid'  # Accent folding
+        assert resolve_city_key('atlantis') is None
+
+    def test_alias_route_redirects_to_canonical_city(self, client: Any) -> None:
+        """Test alias and non-ASCII routes redirect to the canonical city"""
+        response = client.get('/m%C3%BCnchen')
+        assert response.status_code == HTTP_FOUND
+        assert response.headers['Location'].endswith('/munich')
+
+        response = client.get('/%E6%9D%B1%E4%BA%AC')
+        assert response.status_code == HTTP_FOUND
+        assert response.headers['Location'].endswith('/tokyo')
+
+        response = client.get('/munich')
+        assert response.status_code == HTTP_OK
+
+    @patch('requests.get')
+    def test_unknown_city_falls_back_to_geocoder(
+        self, mock_get: MagicMock, client: Any
+    ) -> None:
+        """Test cities outside the registry resolve through the geocoder"""
+        mock_response = MagicMock()
+        mock_response.json.return_value = {
+            'results': [{'latitude': 47.3769, 'longitude': 8.5417, 'name': 'Zürich'}]
+        }
+        mock_response.raise_for_status.return_value = None
+        mock_get.return_value = mock_response
+
+        response = client.get('/z%C3%BCrich')
+        assert response.status_code == HTTP_FOUND
+        assert response.headers['Location'].endswith('/47.3769,8.5417/Z%C3%BCrich')
+
+        # Second lookup is served from the geocode cache
+        client.get('/z%C3%BCrich')
+        mock_get.assert_called_once()
+
+    @patch('requests.get')
+    def test_geocoder_miss_returns_not_found(
+        self, mock_get: MagicMock, client: Any
+    ) -> None:
+        """Test unknown cities 404 when the geocoder has no match"""
+        mock_response = MagicMock()
+        mock_response.json.return_value = {}
+        mock_response.raise_for_status.return_value = None
+        mock_get.return_value = mock_response
+
+        response = client.get('/atlantis')
+        assert response.status_code == HTTP_NOT_FOUND
+
+    @patch('main.geocode_city', return_value=None)
+    def test_city_typos_redirect_or_suggest(
+        self, mock_geocode: MagicMock, client: Any
+    ) -> None:
+        """Test a close registry match redirects and short misses suggest"""
+        response = client.get('/chicgo')
+        assert response.status_code == HTTP_FOUND
+        assert response.headers['Location'].endswith('/chicago')
+
+        response = client.get('/sa')
+        assert response.status_code == HTTP_NOT_FOUND
+        assert b'Did you mean /sf?' in response.data
+
+        response = client.get('/atlantis')
+        assert b'Did you mean' not in response.data
+        mock_geocode.assert_any_call('chicgo')  # Real places win over typos
+
+    @patch('requests.get')
+    def test_non_place_names_skip_geocoder(
+        self, mock_get: MagicMock, client: Any
+    ) -> None:
+        """Test file-like and underscored segments never hit the geocoder"""
+        assert client.get('/favicon.ico').status_code == HTTP_NOT_FOUND
+        assert client.get('/invalid_city').status_code == HTTP_NOT_FOUND
+        mock_get.assert_not_called()
+
+
+class TestCacheIntegration:
+    """Test cache integration"""
+
+    def test_cache_is_initialized(self) -> None:
+        """Test that weather cache is properly initialized"""
+        assert weather_cache is not None
+        assert weather_cache.maxsize == CACHE_MAX_SIZE
+        assert weather_cache.ttl == CACHE_TTL_SECONDS  # 3 minutes for real-time updates
+
+    def test_cache_key_format(self) -> None:
+        """Test cache key format"""
+        # Test key format matches expected pattern
+        lat = round(CHICAGO_LAT, 4)
+        lon = round(CHICAGO_LON, 4)
+        expected_key = f'{lat},{lon}'
+
+        assert isinstance(expected_key, str)
+        assert ',' in expected_key
+        assert len(expected_key.split(',')) == EXPECTED_KEY_PARTS
+
+
+class TestPWARoutes:
+    """Test Progressive Web App routes and functionality"""
+
+    def test_service_worker_route(self, client: Any) -> None:
+        """Test service worker route serves the correct file"""
+        response = client.get('/sw.js')
+        assert response.status_code == HTTP_OK
+        assert response.content_type == 'text/javascript'
+        assert b'Service Worker' in response.data
+        # Check security headers
+        assert response.headers.get('X-Content-Type-Options') == 'nosniff'
+        assert response.headers.get('X-Frame-Options') == 'DENY'
+
+    def test_manifest_route(self, client: Any) -> None:
+        """Test manifest.json route serves the correct file"""
+        response = client.get('/manifest.json')
+        assert response.status_code == HTTP_OK
+        assert response.content_type == 'application/manifest+json'
+
+        # Parse the manifest to ensure it's valid JSON
+        manifest_data = json.loads(response.data)
+        assert 'name' in manifest_data
+        assert 'short_name' in manifest_data
+        assert 'start_url' in manifest_data
+
+    def test_service_worker_precaches_versioned_assets(self, client: Any) -> None:
+        """Test the worker is rendered with this deploy's asset URLs"""
+        response = client.get('/sw.js')
+        script = response.data.decode()
+
+        assert asset_manifest.url('js/weather-components.js') in script
+        assert f"'weather-dashboard-{asset_manifest.fingerprint()}'" in script
+        assert '/offline/last-forecast' in script
+        assert response.headers['Cache-Control'].startswith('no-cache')
+
+    def test_manifest_shortcuts_from_favorites(
+        self, client: Any, tmp_path: Path
+    ) -> None:
+        """Test each favorite becomes a launcher shortcut"""
+        store = FavoritesStore(str(tmp_path / 'favorites.db'))
+        store.add({'name': 'Cabin', 'lat': 46.5, 'lon': -87.4})
+
+        with patch('main.favorites_store', store):
+            manifest_data = json.loads(client.get('/manifest.json').data)
+
+        assert manifest_data['shortcuts'] == [
+            {'name': 'Cabin', 'url': '/46.5000,-87.4000/Cabin'}
+        ]
+        assert manifest_data['icons'][0]['src'].startswith(
+            '/static/icons/icon-192x192.png?v='
+        )
+
+
+class TestTemperatureTrendsAPI:
+    """Test the temperature trends API endpoint"""
+
+    @patch('main.temperature_trends_cache')
+    @patch('main.temperature_trends_provider.process_weather_data')
+    @patch('main.weather_manager.get_weather')
+    def test_temperature_trends_api_success(
+        self,
+        mock_get_weather: MagicMock,
+        mock_process_trends: MagicMock,  # noqa: ARG002
+        mock_cache: MagicMock,
+        client: Any,
+    ) -> None:
+        """Test successful temperature trends API call"""
+        # Mock cache miss
+        mock_cache.__contains__.return_value = False
+
+        # Mock weather data
+        mock_weather_data = {
+            'current': {'temperature': 75, 'humidity': 60, 'wind_speed': 8},
+            'hourly': [
+                {'temp': 75, 't': '12pm'},
+                {'temp': 77, 't': '1pm'},
+                {'temp': 79, 't': '2pm'},
+            ],
+            'daily': [{'h': 85, 'l': 70}],
+        }
+        mock_get_weather.return_value = mock_weather_data
+
+        # Mock trends data
+        mock_trends_data = {
+            'provider': 'EnhancedTemperatureTrendProvider',
+            'location_name': 'Test Location',
+            'temperature_trends': {
+                'hourly_data': [
+                    {
+                        'temperature': 75,
+                        'apparent_temperature': 76,
+                        'confidence_lower': 74,
+                        'confidence_upper': 76,
+                        'uncertainty': 1.0,
+                    }
+                ],
+                'statistics': {
+                    'temperature': {'min': 75, 'max': 79, 'mean': 77},
+                    'apparent_temperature': {'min': 76, 'max': 80, 'mean': 78},
+                },
+                'comfort_analysis': {
+                    'categories': {'comfortable': 3, 'hot': 0, 'cool': 0, 'cold': 0},
+                    'percentages': {'comfortable': 100, 'hot': 0, 'cool': 0, 'cold': 0},
+                    'primary_comfort': 'comfortable',
+                },
+                'trend_analysis': {
+                    'trend_direction': 'warming',
+                    'temperature_change_24h': 4,
+                    'volatility': 2.0,
+                },
+                'percentile_bands': {
+                    '10th_percentile': 60,
+                    '50th_percentile': 75,
+                    '90th_percentile': 90,
+                    'data_source': 'estimated',
+                },
+                'current': {
+                    'temperature': 75,
+                    'apparent_temperature': 76,
+                    'comfort_category': 'comfortable',
+                },
+            },
+        }
+        mock_process_trends.return_value = mock_trends_data
+
+        response = client.get(
+            '/api/temperature-trends?lat=41.8781&lon=-87.6298&location=Test'
+        )
+        assert response.status_code == HTTP_OK
+
+        data = json.loads(response.data)
+        assert data['provider'] == 'EnhancedTemperatureTrendProvider'
+        assert 'temperature_trends' in data
+
+        trends = data['temperature_trends']
+        assert 'hourly_data' in trends
+        assert 'statistics' in trends
+        assert 'comfort_analysis' in trends
+        assert 'trend_analysis' in trends
+        assert 'percentile_bands' in trends
+        assert 'current' in trends
+
+        # Check cache headers
+        assert 'Cache-Control' in response.headers
+        assert 'ETag' in response.headers
+        assert response.headers['X-Cache'] == 'MISS'
+
+    @patch('main.temperature_trends_cache')
+    def test_temperature_trends_api_cache_hit(
+        self, mock_cache: MagicMock, client: Any
+    ) -> None:
+        """Test temperature trends API cache hit"""
+        # Mock cache hit
+        mock_cache.__contains__.return_value = True
+        mock_cached_data = {
+            'provider': 'EnhancedTemperatureTrendProvider',
+            'temperature_trends': {
+                'hourly_data': [],
+                'statistics': {},
+                'comfort_analysis': {},
+                'trend_analysis': {},
+                'percentile_bands': {},
+                'current': {},
+            },
+        }
+        mock_cache.__getitem__.return_value = mock_cached_data
+
+        response = client.get('/api/temperature-trends?lat=41.8781&lon=-87.6298')
+        assert response.status_code == HTTP_OK
+
+        data = json.loads(response.data)
+        assert data['provider'] == 'EnhancedTemperatureTrendProvider'
+
+        # Should indicate cache hit
+        assert response.headers['X-Cache'] == 'HIT'
+
+    def test_temperature_trends_api_default_params(self, client: Any) -> None:
+        """Test temperature trends API with default parameters"""
+        # Should use Chicago coordinates by default
+        response = client.get('/api/temperature-trends')
+        # May succeed or fail depending on weather data availability
+        assert response.status_code in (
+            HTTP_OK,
+            HTTP_INTERNAL_SERVER_ERROR,
+            HTTP_SERVICE_UNAVAILABLE,
+        )
+
+    @patch('main.temperature_trends_cache')
+    def test_temperature_trends_api_invalid_coordinates(
+        self, mock_cache: MagicMock, client: Any
+    ) -> None:
+        """Test invalid coordinates fall back to the default and say so"""
+        mock_cache.__contains__.return_value = True
+        mock_cache.__getitem__.return_value = {'temperature_trends': {}}
+
+        response = client.get('/api/temperature-trends?lat=invalid&lon=invalid')
+        assert response.status_code == HTTP_OK
+
+        data = json.loads(response.data)
+        assert data['location_fallback'] == {
+            'reason': 'invalid_coordinates',
+            'requested': 'invalid,invalid',
+            'source': 'instance',
+        }
+        assert response.headers['X-Location-Fallback'] == (
+            'instance; reason=invalid_coordinates'
+        )
+
+    @patch('main.temperature_trends_cache')
+    @patch('main.temperature_trends_provider.process_weather_data')
+    @patch('main.weather_manager.get_weather')
+    def test_temperature_trends_api_weather_failure(
+        self,
+        mock_get_weather: MagicMock,
+        mock_process_trends: MagicMock,  # noqa: ARG002
+        mock_cache: MagicMock,
+        client: Any,
+    ) -> None:
+        """Test temperature trends API when weather data fails"""
+        # Mock cache miss
+        mock_cache.__contains__.return_value = False
+
+        # Mock weather data failure
+        mock_get_weather.return_value = None
+
+        response = client.get('/api/temperature-trends?lat=41.8781&lon=-87.6298')
+        assert response.status_code == HTTP_SERVICE_UNAVAILABLE
+
+        data = json.loads(response.data)
+        assert data['code'] == 'upstream_unavailable'
         assert 'Failed to get weather data' in data['error']
 
     @patch('main.temperature_trends_cache')
@@ -0,0 +1,67 @@
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+
+from weather_core.providers import MARINE_UNITS, OpenMeteoMarineProvider
+
+
+# Test constants
+HALF_MOON_BAY_LAT = 37.4636
+HALF_MOON_BAY_LON = -122.4286
+WAVE_HEIGHT_FT = 6.6  # 2.0 m
+SEA_TEMP_F = 57.2  # 14.0 °C
+HOURLY_COUNT = 2
+
+
+class TestOpenMeteoMarineProvider:
+    """Test the Open-Meteo Marine API provider"""
+
+    @pytest.fixture
+    def raw_marine(self) -> dict[str, Any]:
+        """Marine API response in metric units"""
+        readings = {
+            'wave_height': 2.0,
+            'wave_period': 11.5,
+            'wave_direction': 285,
+            'swell_wave_height': 1.6,
+            'swell_wave_period': 13.2,
+            'swell_wave_direction': 290,
+            'sea_surface_temperature': 14.0,
+        }
+        return {
+            'timezone': 'America/Los_Angeles',
+            'current': {'time': '2026-10-16T09:00', **readings},
+            'hourly': {
+                'time': ['2026-10-16T09:00', '2026-10-16T10:00'],
+                **{field: [value, None] for field, value in readings.items()},
+            },
+        }
+
+    @patch('weather_core.providers.requests.get')
+    def test_fetch_and_process(
+        self, mock_get: MagicMock, raw_marine: dict[str, Any]
+    ) -> None:
+        """Test readings are requested for every field and converted to feet/°F"""
+        mock_get.return_value.json.return_value = raw_marine
+        provider = OpenMeteoMarineProvider()
+
+        marine = provider.get_weather(HALF_MOON_BAY_LAT, HALF_MOON_BAY_LON, 'Mavericks')
+
+        assert marine is not None
+        params = mock_get.call_args[1]['params']
+        assert params['hourly'].split(',') == list(MARINE_UNITS)
+        assert marine['current']['wave_height'] == WAVE_HEIGHT_FT
+        assert marine['current']['sea_surface_temperature'] == SEA_TEMP_F
+        assert marine['current']['swell_wave_direction'] == 290  # noqa: PLR2004
+        assert len(marine['hourly']) == HOURLY_COUNT
+        assert marine['hourly'][1]['wave_height'] is None
+        assert marine['units']['wave_height'] == 'ft'
+        assert marine['timezone'] == 'America/Los_Angeles'
+
+    def test_inland_point_has_no_forecast(self) -> None:
+        """Test a response with no sea readings (inland) is treated as missing"""
+        provider = OpenMeteoMarineProvider()
+        raw = {'current': {'time': '2026-10-16T09:00'}, 'hourly': {}}
+
+        assert provider.process_weather_data(raw, 'Denver') is None
@@ -0,0 +1,126 @@
+"""ABOUTME: Test MQTT publishing and Home Assistant discovery messages
+ABOUTME: Uses a fake broker socket to check the packets that go on the wire"""
+
+import json
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+
+from mqtt import (
+    HA_SENSORS,
+    MqttError,
+    MqttPublisher,
+    encode_packet,
+    publish_packet,
+    topic_slug,
+)
+
+
+# Test constants
+CONNACK_OK = bytes([0x20, 0x02, 0x00, 0x00])
+CONNACK_BAD_AUTH = bytes([0x20, 0x02, 0x00, 0x05])
+LONG_BODY_SIZE = 200
+PUBLISH_RETAIN = 0x31
+
+
+def sent_publishes(sock: MagicMock) -> dict[str, Any]:
+    """Decode the topics and JSON payloads of PUBLISH packets sent to a fake socket"""
+    published = {}
+    for call in sock.sendall.call_args_list:
+        packet = call.args[0]
+        if packet[0] & 0xF0 != 0x30:  # noqa: PLR2004
+            continue
+        # Skip the variable-length size bytes
+        index = 1
+        while packet[index] & 0x80:
+            index += 1
+        index += 1
+        topic_length = int.from_bytes(packet[index : index + 2], 'big')
+        topic = packet[index + 2 : index + 2 + topic_length].decode()
+        published[topic] = json.loads(packet[index + 2 + topic_length :])
+    return published
+
+
+@pytest.fixture
+def broker() -> MagicMock:
+    """Fake broker connection that accepts the CONNECT"""
+    sock = MagicMock()
+    sock.recv.return_value = CONNACK_OK
+    sock.__enter__.return_value = sock
+    return sock
+
+
+class TestMqttPackets:
+    """Test MQTT packet encoding"""
+
+    def test_variable_length_size(self) -> None:
+        """Test bodies over 127 bytes use a two-byte remaining length"""
+        packet = encode_packet(0x30, b'x' * LONG_BODY_SIZE)
+        assert packet[:3] == bytes([0x30, 0xC8, 0x01])
+
+    def test_retained_publish(self) -> None:
+        """Test retained publishes set the retain flag and prefix the topic"""
+        packet = publish_packet('a/b', '{}', retain=True)
+        assert packet == bytes([PUBLISH_RETAIN, 7, 0, 3]) + b'a/b{}'
+
+    def test_topic_slug(self) -> None:
+        """Test location names become ASCII topic levels"""
+        assert topic_slug('São Paulo') == 'sao_paulo'
+        assert topic_slug('New York City') == 'new_york_city'
+        assert topic_slug('東京') == 'location'
+
+
+class TestMqttPublisher:
+    """Test publishing weather and discovery configs"""
+
+    @patch('mqtt.socket.create_connection')
+    def test_publishes_discovery_then_state(
+        self,
+        mock_connect: MagicMock,
+        broker: MagicMock,
+        mock_weather_data: dict[str, Any],
+    ) -> None:
+        """Test a new location is announced to Home Assistant once"""
+        mock_connect.return_value = broker
+        publisher = MqttPublisher('broker.local', username='ha', password='secret')
+
+        publisher.publish_weather('Chicago', mock_weather_data)
+        published = sent_publishes(broker)
+
+        assert published['weather-dashboard/chicago/current'] == (
+            mock_weather_data['current']
+        )
+        assert set(published['weather-dashboard/chicago/forecast']) == {
+            'hourly',
+            'daily',
+        }
+        config = published[
+            'homeassistant/sensor/weather_dashboard_chicago_temperature/config'
+        ]
+        assert config['state_topic'] == 'weather-dashboard/chicago/current'
+        assert config['value_template'] == '{{ value_json.temperature }}'
+        assert config['unit_of_measurement'] == '°F'
+        pressure = published[
+            'homeassistant/sensor/weather_dashboard_chicago_pressure/config'
+        ]
+        assert pressure['unit_of_measurement'] == 'hPa'
+        assert config['device']['identifiers'] == ['weather_dashboard_chicago']
+        assert len(published) == len(HA_SENSORS) + 2
+
+        broker.reset_mock()
+        publisher.publish_weather('Chicago', mock_weather_data)
+        assert len(sent_publishes(broker)) == 2  # noqa: PLR2004
+
+    @patch('mqtt.socket.create_connection')
+    def test_refused_connection(
+        self, mock_connect: MagicMock, broker: MagicMock
+    ) -> None:
+        """Test a refused CONNECT raises and the location is announced next time"""
+        broker.recv.return_value = CONNACK_BAD_AUTH
+        mock_connect.return_value = broker
+        publisher = MqttPublisher('broker.local')
+
+        with pytest.raises(MqttError):
+            publisher.publish_weather('Chicago', {'current': {}})
+        assert not publisher.discovered
@@ -0,0 +1,69 @@
+"""ABOUTME: Test the rule-based spoken forecast paragraphs
+ABOUTME: Builds small weather documents by hand to hit each wording rule"""
+
+from typing import Any
+
+from narrative import build_narrative, part_of_day, precipitation_change
+
+
+# Test constants
+MORNING_WEATHER: dict[str, Any] = {
+    'current': {
+        'icon': 'cloudy',
+        'temperature': 52,
+        'feels_like': 48,
+        'wind_speed': 20,
+        'wind_direction': 315,
+    },
+    'hourly': [
+        {'icon': 'cloudy', 'rain': 10, 't': '9am', 'time': '2024-01-15T09:00'},
+        {'icon': 'cloudy', 'rain': 40, 't': '3pm', 'time': '2024-01-15T15:00'},
+        {'icon': 'rain', 'rain': 80, 't': '4pm', 'time': '2024-01-15T16:00'},
+    ],
+    'daily': [
+        {'h': 58, 'l': 45, 'icon': 'rain', 'd': 'Mon'},
+        {'h': 50, 'l': 41, 'icon': 'clear-day', 'd': 'Tue'},
+    ],
+}
+
+
+class TestNarrative:
+    """Test forecast paragraphs for screen readers and voice assistants"""
+
+    def test_brief(self) -> None:
+        """Test the brief style names the conditions, the rain and the high"""
+        assert build_narrative(MORNING_WEATHER) == (
+            'Cloudy this morning, rain arriving around 4pm, high of 58.'
+        )
+
+    def test_detailed_metric(self) -> None:
+        """Test the detailed style adds readings, wind, chances and tomorrow"""
+        text = build_narrative(MORNING_WEATHER, 'detailed', 'metric')
+
+        assert text.startswith('Cloudy this morning, rain arriving around 4pm, high of')
+        assert ' high of 14. ' in text
+        assert 'Currently 11 degrees, feeling like 9.' in text
+        assert 'Fresh breeze from the northwest.' in text
+        assert 'Chance of precipitation up to 80 percent' in text
+        assert text.endswith('Tomorrow, sunny with a high of 10 and a low of 5.')
+
+    def test_evening_and_ending_rain(self) -> None:
+        """Test rain already falling gets an end time and evenings give the low"""
+        weather = {
+            'current': {'icon': 'light-rain'},
+            'hourly': [
+                {'icon': 'rain', 'rain': 70, 't': '7pm', 'time': '2024-01-15T19:00'},
+                {'icon': 'cloudy', 'rain': 20, 't': '8pm', 'time': '2024-01-15T20:00'},
+            ],
+            'daily': [{'h': 58, 'l': 45}],
+        }
+
+        assert build_narrative(weather) == (
+            'Light rain this evening, ending around 8pm, low of 45.'
+        )
+
+    def test_helpers(self) -> None:
+        """Test day parts wrap past midnight and dry forecasts have no change"""
+        assert part_of_day(2) == 'overnight'
+        assert part_of_day(22) == 'tonight'
+        assert precipitation_change({'icon': 'clear-day'}, []) is None
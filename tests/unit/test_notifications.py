@@ -0,0 +1,352 @@
+"""ABOUTME: Test threshold notification rules and channel dispatch
+ABOUTME: Covers validation, forecast windows, edge-triggering and persistence"""
+
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+
+from notifications import (
+    ChannelLimits,
+    DiscordChannel,
+    EmailChannel,
+    NotificationDispatcher,
+    NotificationRules,
+    NtfyChannel,
+    PushoverChannel,
+    SlackChannel,
+    build_alert_notification,
+    build_digest,
+    evaluate_rule,
+    parse_quiet_hours,
+    rain_windows,
+    validate_rule,
+)
+
+
+# Test constants
+RAIN_THRESHOLD = 60
+FREEZING = 32
+TONIGHT_LOW = 30
+TOMORROW_RAIN = 80
+NTFY_URGENT = 5
+NTFY_DEFAULT = 3
+SEVERE_RED = 0xFF0000
+QUIET_START = 22 * 60
+QUIET_END = 7 * 60
+NIGHT = datetime(2026, 10, 16, 3, 0, tzinfo=timezone.utc)
+NOON = datetime(2026, 10, 16, 12, 0, tzinfo=timezone.utc)
+
+
+def make_rule(**overrides: Any) -> dict[str, Any]:
+    """A valid rule submission with optional overrides"""
+    return {
+        'name': 'Rain tomorrow',
+        'lat': 41.8781,
+        'lon': -87.6298,
+        'metric': 'precipitation_probability',
+        'operator': '>',
+        'threshold': RAIN_THRESHOLD,
+        'window': 'tomorrow',
+        **overrides,
+    }
+
+
+def forecast(rain_tomorrow: int = TOMORROW_RAIN) -> dict[str, Any]:
+    """A snake_case weather document starting at 4pm local time"""
+    hours = [
+        ('2026-10-16T16:00', 40, 10),
+        ('2026-10-16T22:00', TONIGHT_LOW, 20),
+        ('2026-10-17T03:00', 31, 30),
+        ('2026-10-17T12:00', 45, rain_tomorrow),
+    ]
+    return {
+        'location': 'Chicago',
+        'current': {'temperature': 41, 'precipitation_probability': 10},
+        'hourly': [
+            {'time': time, 'temperature': temp, 'precipitation_probability': rain}
+            for time, temp, rain in hours
+        ],
+        'daily': [
+            {'date': '2026-10-16', 'high': 48, 'low': 29},
+            {'date': '2026-10-17', 'high': 52, 'low': 33},
+        ],
+    }
+
+
+class TestRuleEvaluation:
+    """Test rules are validated and evaluated over the right forecast window"""
+
+    def test_validation(self) -> None:
+        """Test incomplete or out-of-range rules are rejected"""
+        assert validate_rule(make_rule())['channels'] == ['log']
+        with pytest.raises(ValueError, match='metric'):
+            validate_rule(make_rule(metric='vibes'))
+        with pytest.raises(ValueError, match='window'):
+            validate_rule(make_rule(window='someday'))
+        with pytest.raises(ValueError, match='out of range'):
+            validate_rule(make_rule(lat=91))
+        with pytest.raises(ValueError, match='threshold'):
+            validate_rule(make_rule(threshold='lots'))
+
+    def test_windows(self) -> None:
+        """Test tomorrow, tonight and now only look at their own hours"""
+        data = forecast()
+        assert evaluate_rule(validate_rule(make_rule()), data) == TOMORROW_RAIN
+        assert evaluate_rule(validate_rule(make_rule(window='now')), data) is None
+
+        freeze = make_rule(
+            metric='temperature', operator='<', threshold=FREEZING, window='tonight'
+        )
+        assert evaluate_rule(validate_rule(freeze), data) == TONIGHT_LOW
+
+    def test_daily_temperatures_count(self) -> None:
+        """Test a day's forecast low trips a rule the hourly data doesn't reach"""
+        rule = make_rule(
+            metric='temperature', operator='<=', threshold=FREEZING, window='today'
+        )
+        assert evaluate_rule(validate_rule(rule), forecast()) == 29  # noqa: PLR2004
+
+
+class TestNotificationRules:
+    """Test rule storage and one notification per trip"""
+
+    def test_notifies_once_per_trip(self, tmp_path: Path) -> None:
+        """Test a rule fires when it trips, stays quiet, then re-arms when clear"""
+        rules = NotificationRules(str(tmp_path / 'rules.json'))
+        rule = rules.create(make_rule())
+
+        notification = rules.check(rule['id'], forecast())
+        assert notification is not None
+        assert notification['location'] == 'Chicago'
+        assert 'above 60%' in notification['message']
+        assert rules.check(rule['id'], forecast()) is None
+
+        assert rules.check(rule['id'], forecast(rain_tomorrow=20)) is None
+        assert rules.check(rule['id'], forecast()) is not None
+
+    def test_persists(self, tmp_path: Path) -> None:
+        """Test rules and their tripped state survive a restart"""
+        path = str(tmp_path / 'data' / 'rules.json')
+        rules = NotificationRules(path)
+        rule = rules.create(make_rule())
+        rules.check(rule['id'], forecast())
+
+        reloaded = NotificationRules(path).list_rules()
+        assert [saved['id'] for saved in reloaded] == [rule['id']]
+        assert reloaded[0]['active'] is True
+        assert rules.delete(rule['id']) is True
+        assert rules.delete(rule['id']) is False
+
+    def test_dispatch_skips_failing_channels(self) -> None:
+        """Test one broken or unknown channel doesn't stop the others"""
+        delivered: list[dict[str, Any]] = []
+
+        class Broken:
+            def send(self, notification: dict[str, Any]) -> None:
+                msg = 'offline'
+                raise OSError(msg)
+
+        class Recorder:
+            def send(self, notification: dict[str, Any]) -> None:
+                delivered.append(notification)
+
+        dispatcher = NotificationDispatcher()
+        dispatcher.register('broken', Broken())
+        dispatcher.register('recorder', Recorder())
+        notification = {'title': 'Test', 'message': 'Hello'}
+
+        sent = dispatcher.dispatch(notification, ['broken', 'missing', 'recorder'])
+        assert sent == 1
+        assert delivered == [notification]
+
+
+class TestNtfyChannel:
+    """Test publishing notifications to ntfy"""
+
+    @patch('notifications.requests.post')
+    def test_publishes_json_with_token(self, mock_post: MagicMock) -> None:
+        """Test a rule notification is posted as JSON with bearer auth"""
+        channel = NtfyChannel('weather', 'https://ntfy.example.com/', token='tk_1')
+        channel.send({'title': 'Freeze - Zürich', 'message': 'Below 32°F tonight'})
+
+        url = mock_post.call_args[0][0]
+        kwargs = mock_post.call_args[1]
+        assert url == 'https://ntfy.example.com'
+        assert kwargs['json'] == {
+            'topic': 'weather',
+            'title': 'Freeze - Zürich',
+            'message': 'Below 32°F tonight',
+            'tags': ['bell'],
+            'priority': NTFY_DEFAULT,
+        }
+        assert kwargs['headers'] == {'Authorization': 'Bearer tk_1'}
+        assert kwargs['auth'] is None
+        mock_post.return_value.raise_for_status.assert_called_once()
+
+    @patch('notifications.requests.post')
+    def test_alert_priority_and_basic_auth(self, mock_post: MagicMock) -> None:
+        """Test severe alerts are urgent and username/password use basic auth"""
+        channel = NtfyChannel('weather', username='me', password='secret')
+        alert = {'type': 'Tornado Warning', 'severity': 'Extreme'}
+        channel.send(build_alert_notification(alert, 'Chicago'))
+
+        payload = mock_post.call_args[1]['json']
+        assert payload['title'] == 'Tornado Warning - Chicago'
+        assert payload['priority'] == NTFY_URGENT
+        assert payload['tags'] == ['rotating_light']
+        assert mock_post.call_args[1]['auth'] == ('me', 'secret')
+
+
+class TestChatWebhooks:
+    """Test Slack blocks and Discord embeds"""
+
+    notification = {
+        'title': 'Heat <advisory> - Chicago',
+        'message': 'Temperature above 90°F today: 95°F',
+        'symbol': '☀️',
+        'temperature': 91,
+        'triggered_at': '2026-07-01T15:00:00+00:00',
+    }
+
+    def test_slack_blocks(self) -> None:
+        """Test Slack gets escaped mrkdwn blocks in a colored attachment"""
+        payload = SlackChannel('https://hooks.slack.com/x').payload(self.notification)
+
+        attachment = payload['attachments'][0]
+        assert attachment['color'] == '#1E90FF'
+        heading = attachment['blocks'][0]['text']['text']
+        assert heading.startswith('*☀️ Heat &lt;advisory&gt; - Chicago*')
+        assert attachment['blocks'][1]['elements'][0]['text'] == '*Now:* 91°F'
+        assert payload['text'].startswith('Heat <advisory> - Chicago')
+
+    @patch('notifications.requests.post')
+    def test_discord_alert_embed(self, mock_post: MagicMock) -> None:
+        """Test Discord gets an embed colored by alert severity"""
+        alert = {'type': 'Severe Thunderstorm Warning', 'severity': 'Severe'}
+        DiscordChannel('https://discord.com/api/webhooks/x').send(
+            build_alert_notification(alert, 'Chicago')
+        )
+
+        embed = mock_post.call_args[1]['json']['embeds'][0]
+        assert embed['title'] == '⚠️ Severe Thunderstorm Warning - Chicago'
+        assert embed['color'] == SEVERE_RED
+        assert embed['fields'] == [
+            {'name': 'Severity', 'value': 'Severe', 'inline': True}
+        ]
+        mock_post.return_value.raise_for_status.assert_called_once()
+
+
+class TestPushoverAndEmail:
+    """Test the Pushover and SMTP channels"""
+
+    @patch('notifications.requests.post')
+    def test_pushover_priority(self, mock_post: MagicMock) -> None:
+        """Test severe alerts go out as high priority"""
+        alert = {'type': 'Tornado Warning', 'severity': 'Extreme'}
+        PushoverChannel('app', 'user').send(build_alert_notification(alert, 'Chicago'))
+
+        form = mock_post.call_args[1]['data']
+        assert form['token'] == 'app'
+        assert form['user'] == 'user'
+        assert form['title'] == '⚠️ Tornado Warning - Chicago'
+        assert form['priority'] == 1
+
+    @patch('notifications.smtplib.SMTP')
+    def test_email_uses_starttls_and_login(self, mock_smtp: MagicMock) -> None:
+        """Test mail is sent after STARTTLS and login when credentials are set"""
+        smtp = mock_smtp.return_value.__enter__.return_value
+        smtp.has_extn.return_value = True
+        channel = EmailChannel(
+            'smtp.example.com', 587, 'wx@example.com', ['a@example.com'], 'u', 'p'
+        )
+        channel.send({'title': 'Freeze', 'message': 'Below 32°F', 'temperature': 30})
+
+        smtp.starttls.assert_called_once()
+        smtp.login.assert_called_once_with('u', 'p')
+        message = smtp.send_message.call_args[0][0]
+        assert message['Subject'] == 'Freeze'
+        assert message['To'] == 'a@example.com'
+        assert 'Now: 30°F' in message.get_content()
+
+
+class TestChannelLimits:
+    """Test quiet hours and rate limiting per channel"""
+
+    def test_parse_quiet_hours(self) -> None:
+        """Test quiet hours parse to minutes past midnight"""
+        assert parse_quiet_hours('22:00-07:00') == (QUIET_START, QUIET_END)
+        assert parse_quiet_hours('') is None
+        with pytest.raises(ValueError, match='22:00-07:00'):
+            parse_quiet_hours('late')
+
+    def test_quiet_hours_wrap_midnight(self) -> None:
+        """Test quiet hours hold back rules overnight but not severe alerts"""
+        limits = ChannelLimits((QUIET_START, QUIET_END))
+        rule = {'title': 'Rain tomorrow', 'rule_id': 'r1'}
+        alert = {'title': 'Tornado Warning', 'severity': 'extreme'}
+
+        assert limits.check(rule, NIGHT) == 'quiet hours'
+        assert limits.check(alert, NIGHT) is None
+        assert limits.check(rule, NOON) is None
+
+    def test_repeat_and_hourly_limits(self) -> None:
+        """Test a flapping rule repeats only after the interval, under the cap"""
+        limits = ChannelLimits(max_per_hour=2, repeat_minutes=30)
+        flapping = {'title': 'Windy', 'rule_id': 'r1'}
+
+        assert limits.check(flapping, NOON) is None
+        later = NOON + timedelta(minutes=10)
+        assert limits.check(flapping, later) == 'repeated too soon'
+        assert limits.check({'title': 'Other', 'rule_id': 'r2'}, later) is None
+        assert limits.check({'title': 'Third', 'rule_id': 'r3'}, later) == (
+            'hourly limit reached'
+        )
+        assert limits.check(flapping, NOON + timedelta(minutes=61)) is None
+
+    def test_dispatcher_applies_limits(self) -> None:
+        """Test held-back notifications aren't sent or counted as delivered"""
+        channel = MagicMock()
+        dispatcher = NotificationDispatcher()
+        dispatcher.register('pager', channel, ChannelLimits((QUIET_START, QUIET_END)))
+        notification = {'title': 'Rain', 'message': 'Soon', 'rule_id': 'r1'}
+
+        assert dispatcher.dispatch(notification, ['pager'], NIGHT) == 0
+        assert dispatcher.dispatch(notification, ['pager'], NOON) == 1
+        channel.send.assert_called_once_with(notification)
+
+
+class TestMorningDigest:
+    """Test composing the morning forecast digest"""
+
+    def test_rain_windows(self) -> None:
+        """Test rainy hours group into windows, today only"""
+        data = forecast()
+        data['hourly'][0]['precipitation_probability'] = 50
+        data['hourly'].insert(
+            1, {'time': '2026-10-16T17:00', 'precipitation_probability': 70}
+        )
+
+        assert rain_windows(data) == [('4pm', '5pm', 70)]
+
+    def test_digest_message(self) -> None:
+        """Test the digest lists high/low, rain, air quality and alerts"""
+        alerts = [
+            {'type': 'Wind Advisory', 'severity': 'Moderate'},
+            {'type': 'Flood Warning', 'severity': 'Severe'},
+        ]
+        air_quality = {'aqi': {'us_aqi': 42, 'category': 'Good'}}
+
+        digest = build_digest('Chicago', forecast(), alerts, air_quality)
+
+        assert digest['title'] == 'Morning forecast - Chicago'
+        assert digest['message'].split('\n') == [
+            'High 48°F, low 29°F',
+            'No rain expected today',
+            'Air quality 42 (Good)',
+            'Active alerts: Wind Advisory, Flood Warning',
+        ]
+        assert digest['severity'] == 'severe'
+        assert digest['rule_id'] == 'digest:Chicago'
@@ -4,7 +4,8 @@ from unittest.mock import MagicMock, patch
 import pytest
 import requests
 
-from weather_providers import NationalWeatherServiceProvider
+from weather_core.http_client import DEFAULT_RETRY_POLICY
+from weather_core.providers import NationalWeatherServiceProvider
 
 
 # Test constants
@@ -114,7 +115,7 @@ class TestNationalWeatherServiceProvider:
         assert nws_provider.timeout == PROVIDER_TIMEOUT
         assert 'WeatherDashboard' in nws_provider.user_agent
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_success(
         self,
         mock_get: MagicMock,
@@ -169,7 +170,7 @@ class TestNationalWeatherServiceProvider:
         forecast_call = mock_get.call_args_list[2]
         assert '/gridpoints/LOT/75,73/forecast' in forecast_call[0][0]
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_points_failure(
         self,
         mock_get: MagicMock,
@@ -184,7 +185,7 @@ class TestNationalWeatherServiceProvider:
         assert result is None
         assert mock_get.call_count == 1
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_missing_grid_info(
         self,
         mock_get: MagicMock,
@@ -208,25 +209,24 @@ class TestNationalWeatherServiceProvider:
 
         assert result is None
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.http_client.time.sleep')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_partial_failure(
         self,
         mock_get: MagicMock,
+        mock_sleep: MagicMock,
         nws_provider: NationalWeatherServiceProvider,
         mock_points_response: dict[str, Any],
     ) -> None:
         """Test fetch weather data when alerts/forecast APIs fail"""
+        points = MagicMock(
+            status_code=200, json=MagicMock(return_value=mock_points_response)
+        )
 
-        # Points succeeds, alerts and forecast fail
-        mock_responses = [
-            MagicMock(
-                status_code=200, json=MagicMock(return_value=mock_points_response)
-            ),
-            MagicMock(status_code=500),  # alerts fail
-            MagicMock(status_code=500),  # forecast fail
-        ]
-
-        mock_get.side_effect = mock_responses
+        # Points succeeds, alerts and forecast keep failing through every retry
+        mock_get.side_effect = lambda url, **_: (
+            points if '/points/' in url else MagicMock(status_code=500)
+        )
 
         result = nws_provider.fetch_weather_data(CHICAGO_LAT, CHICAGO_LON)
 
@@ -235,8 +235,10 @@ class TestNationalWeatherServiceProvider:
         assert result['alerts'] is None
         assert result['forecast'] is None
         assert result['grid_info']['office'] == 'LOT'
+        # The failing endpoints were each retried; points answered first time
+        assert mock_get.call_count == 1 + 2 * DEFAULT_RETRY_POLICY.max_attempts
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_network_error(
         self,
         mock_get: MagicMock,
@@ -379,7 +381,7 @@ class TestNationalWeatherServiceProvider:
             alert = result['alerts']['alerts'][0]
             assert alert['color'] == expected_color
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_get_weather_integration(
         self,
         mock_get: MagicMock,
@@ -412,7 +414,7 @@ class TestNationalWeatherServiceProvider:
         assert result['alerts']['active_count'] == EXPECTED_ALERT_COUNT
         assert result['alerts']['has_warnings'] is True
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_get_weather_api_failure(
         self,
         mock_get: MagicMock,
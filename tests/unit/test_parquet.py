@@ -0,0 +1,224 @@
+"""ABOUTME: Test the Parquet writer by reading files back with a small Thrift compact
+ABOUTME: protocol decoder - footer, schema, page headers and every column's values"""
+
+import io
+import struct
+from typing import Any
+
+import pytest
+
+from parquet import definition_levels, varint, write_parquet, zigzag
+
+
+# Test constants
+COLUMNS = [
+    ('location', 'string'),
+    ('observed_at', 'timestamp'),
+    ('temperature', 'double'),
+    ('hours', 'int64'),
+]
+ROWS: list[dict[str, Any]] = [
+    {'location': '41.8781,-87.6298', 'observed_at': 1_700_000_000, 'temperature': 61.5},
+    {'location': 'Zürich', 'observed_at': 1_700_003_600.5, 'hours': 24},
+    {'location': None, 'observed_at': 1_700_007_200, 'temperature': -3.25},
+]
+BOOLEAN_TRUE = 1
+BOOLEAN_FALSE = 2
+T_LIST = 9
+T_STRUCT = 12
+LONG_LIST = 15
+
+
+def read_varint(data: bytes, pos: int) -> tuple[int, int]:
+    """An unsigned varint and the position after it"""
+    value = shift = 0
+    while True:
+        byte = data[pos]
+        pos += 1
+        value |= (byte & 0x7F) << shift
+        shift += 7
+        if not byte & 0x80:
+            return value, pos
+
+
+def read_zigzag(data: bytes, pos: int) -> tuple[int, int]:
+    """A zigzag varint and the position after it"""
+    value, pos = read_varint(data, pos)
+    return (value >> 1) ^ -(value & 1), pos
+
+
+def read_value(data: bytes, pos: int, field_type: int) -> tuple[Any, int]:
+    """One compact-protocol value of a type"""
+    if field_type in (BOOLEAN_TRUE, BOOLEAN_FALSE):
+        return field_type == BOOLEAN_TRUE, pos
+    if field_type == T_STRUCT:
+        return read_struct(data, pos)
+    if field_type == T_LIST:
+        header = data[pos]
+        pos += 1
+        size, element_type = header >> 4, header & 0x0F
+        if size == LONG_LIST:
+            size, pos = read_varint(data, pos)
+        items = []
+        for _ in range(size):
+            item, pos = read_value(data, pos, element_type)
+            items.append(item)
+        return items, pos
+    if field_type == 8:  # noqa: PLR2004 - binary
+        length, pos = read_varint(data, pos)
+        return data[pos : pos + length], pos + length
+    return read_zigzag(data, pos)
+
+
+def read_struct(data: bytes, pos: int) -> tuple[dict[int, Any], int]:
+    """A compact-protocol struct as {field id: value}"""
+    fields: dict[int, Any] = {}
+    last_id = 0
+    while True:
+        header = data[pos]
+        pos += 1
+        if header == 0:
+            return fields, pos
+        delta, field_type = header >> 4, header & 0x0F
+        if delta:
+            field_id = last_id + delta
+        else:
+            field_id, pos = read_zigzag(data, pos)
+        fields[field_id], pos = read_value(data, pos, field_type)
+        last_id = field_id
+
+
+def read_levels(data: bytes, pos: int, count: int) -> tuple[list[bool], int]:
+    """Length-prefixed RLE definition levels of bit width 1"""
+    (length,) = struct.unpack_from('<I', data, pos)
+    pos += 4
+    end = pos + length
+    levels: list[bool] = []
+    while pos < end:
+        header, pos = read_varint(data, pos)
+        assert not header & 1, 'only RLE runs are written'
+        levels += [bool(data[pos])] * (header >> 1)
+        pos += 1
+    assert len(levels) == count
+    return levels, end
+
+
+def read_parquet(data: bytes) -> tuple[dict[int, Any], dict[str, list[Any]]]:
+    """The footer and each column's values, with None for nulls"""
+    assert data[:4] == data[-4:] == b'PAR1'
+    (footer_length,) = struct.unpack('<I', data[-8:-4])
+    footer, end = read_struct(data, len(data) - 8 - footer_length)
+    assert end == len(data) - 8
+
+    columns: dict[str, list[Any]] = {}
+    for row_group in footer[4]:
+        for chunk in row_group[1]:
+            metadata = chunk[3]
+            name = metadata[3][0].decode()
+            page_header, pos = read_struct(data, metadata[9])
+            count = page_header[5][1]
+            levels, pos = read_levels(data, pos, count)
+            values: list[Any] = []
+            for present in levels:
+                if not present:
+                    values.append(None)
+                elif metadata[1] == 5:  # noqa: PLR2004 - double
+                    values.append(struct.unpack_from('<d', data, pos)[0])
+                    pos += 8
+                elif metadata[1] == 2:  # noqa: PLR2004 - int64
+                    values.append(struct.unpack_from('<q', data, pos)[0])
+                    pos += 8
+                else:
+                    (length,) = struct.unpack_from('<I', data, pos)
+                    values.append(data[pos + 4 : pos + 4 + length].decode())
+                    pos += 4 + length
+            assert pos == metadata[9] + metadata[6]
+            columns[name] = values
+    return footer, columns
+
+
+class TestEncoding:
+    """Test the building blocks"""
+
+    def test_varints(self) -> None:
+        """Test varints and zigzag round-trip through the reader"""
+        for value in (0, 1, 127, 128, 300, 2**40):
+            assert read_varint(varint(value), 0) == (value, len(varint(value)))
+        for value in (0, -1, 1, -64, 2**62, -(2**62)):
+            assert read_zigzag(zigzag(value), 0)[0] == value
+
+    def test_definition_levels(self) -> None:
+        """Test present and null values become runs"""
+        values = [1.0, 2.0, None, None, None, 3.0]
+
+        levels, _ = read_levels(definition_levels(values), 0, len(values))
+
+        assert levels == [True, True, False, False, False, True]
+
+
+class TestWriteParquet:
+    """Test whole files"""
+
+    def test_round_trip(self) -> None:
+        """Test the schema and values come back, with nulls where missing"""
+        footer, columns = read_parquet(write_parquet(COLUMNS, ROWS))
+
+        root, *schema = footer[2]
+        assert root[5] == len(COLUMNS)
+        assert [(element[4].decode(), element[1]) for element in schema] == [
+            ('location', 6),
+            ('observed_at', 2),
+            ('temperature', 5),
+            ('hours', 2),
+        ]
+        assert [element.get(6) for element in schema] == [0, 9, None, None]
+        assert footer[3] == len(ROWS)
+        assert footer[4][0][3] == len(ROWS)
+        assert columns == {
+            'location': ['41.8781,-87.6298', 'Zürich', None],
+            'observed_at': [1_700_000_000_000, 1_700_003_600_500, 1_700_007_200_000],
+            'temperature': [61.5, None, -3.25],
+            'hours': [None, 24, None],
+        }
+
+    def test_many_columns(self) -> None:
+        """Test lists of 15 or more use the long list header"""
+        columns = [(f'c{index}', 'double') for index in range(20)]
+
+        footer, values = read_parquet(write_parquet(columns, [{'c19': 1.0}]))
+
+        assert len(footer[2]) == len(columns) + 1
+        assert values['c19'] == [1.0]
+        assert values['c0'] == [None]
+
+    def test_no_rows(self) -> None:
+        """Test an empty table still has its schema but no row groups"""
+        footer, columns = read_parquet(write_parquet(COLUMNS, []))
+
+        assert footer[3] == 0
+        assert footer[4] == []
+        assert columns == {}
+        assert len(footer[2]) == len(COLUMNS) + 1
+
+    def test_pyarrow_reads_file(self) -> None:
+        """Test a real Parquet reader agrees with the hand-rolled decoder"""
+        pyarrow = pytest.importorskip('pyarrow')
+        pq = pytest.importorskip('pyarrow.parquet')
+
+        table = pq.read_table(io.BytesIO(write_parquet(COLUMNS, ROWS)))
+
+        assert table.column_names == [name for name, _kind in COLUMNS]
+        assert table.num_rows == len(ROWS)
+        assert pyarrow.types.is_timestamp(table.schema.field('observed_at').type)
+        assert table.column('observed_at').cast(pyarrow.int64()).to_pylist() == [
+            1_700_000_000_000,
+            1_700_003_600_500,
+            1_700_007_200_000,
+        ]
+        assert table.column('location').to_pylist() == [
+            '41.8781,-87.6298',
+            'Zürich',
+            None,
+        ]
+        assert table.column('temperature').to_pylist() == [61.5, None, -3.25]
+        assert table.column('hours').to_pylist() == [None, 24, None]
@@ -0,0 +1,52 @@
+"""ABOUTME: Test postal code normalization and GeoNames dump loading
+ABOUTME: Writes small GeoNames-format files so no real dump is needed"""
+
+from pathlib import Path
+
+from postal import PostalIndex, postal_lookup_keys, valid_postal_query
+
+
+# Test constants
+WICKER_PARK = (41.9042, -87.6802, 'Chicago, Illinois')
+WESTMINSTER = (51.5010, -0.1416, 'London, England')
+
+US_ROWS = (
+    'US\t60622\tChicago\tIllinois\tIL\tCook\t031\t\t\t41.9042\t-87.6802\t4\n'
+    'US\t60622\tChicago Duplicate\tIllinois\tIL\t\t\t\t\t0\t0\t4\n'
+    'US\tbroken row\n'
+)
+GB_ROWS = 'GB\tSW1A\tLondon\tEngland\tENG\t\t\t\t\t51.5010\t-0.1416\t4\n'
+
+
+class TestPostalIndex:
+    """Test indexing and looking up postal codes"""
+
+    def test_lookup_keys(self) -> None:
+        """Test full codes are tried before their outward part"""
+        assert postal_lookup_keys('sw1a 1aa') == ['SW1A1AA', 'SW1A']
+        assert postal_lookup_keys('60622-4321') == ['606224321', '60622']
+        assert postal_lookup_keys('60622') == ['60622']
+
+    def test_valid_postal_query(self) -> None:
+        """Test codes and country codes are sanity checked before lookups"""
+        assert valid_postal_query('SW1A 1AA', 'GB')
+        assert not valid_postal_query('60622', 'USA')
+        assert not valid_postal_query('../etc', 'US')
+        assert not valid_postal_query('1', 'US')
+
+    def test_directory_of_country_files(self, tmp_path: Path) -> None:
+        """Test a directory loads every country file, first place winning"""
+        (tmp_path / 'US.txt').write_text(US_ROWS, encoding='utf-8')
+        (tmp_path / 'GB.txt').write_text(GB_ROWS, encoding='utf-8')
+        index = PostalIndex(str(tmp_path))
+
+        assert index.countries == {'US', 'GB'}
+        assert index.lookup('60622', 'us') == WICKER_PARK
+        assert index.lookup('60622-4321', 'US') == WICKER_PARK
+        assert index.lookup('SW1A 1AA', 'GB') == WESTMINSTER
+        assert index.lookup('60622', 'GB') is None
+
+    def test_missing_path_is_empty(self, tmp_path: Path) -> None:
+        """Test a missing dump just means every lookup goes to the geocoder"""
+        index = PostalIndex(str(tmp_path / 'allCountries.txt'))
+        assert index.codes == {}
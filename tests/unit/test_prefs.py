@@ -0,0 +1,81 @@
+"""ABOUTME: Test visitor preference validation and the unit/clock conversions
+ABOUTME: Conversions work on internal weather documents, before API renaming"""
+
+import pytest
+
+from prefs import label_hours, validate_prefs
+from weather_core.units import convert_to_metric
+
+
+# Test constants
+IMPERIAL_WEATHER = {
+    'current': {
+        'temperature': 50,
+        'wind_speed': 10,
+        'pressure': 1013.2,
+        'precipitation_rate': 0.1,
+        'humidity': 65,
+        'precipitation_type': None,
+    },
+    'hourly': [{'temp': 32, 't': '12am', 'time': '2024-01-15T14:00:00-06:00'}],
+    'daily': [{'h': 212, 'l': -40, 'd': 'Mon'}],
+    'location': 'Chicago',
+}
+
+
+class TestValidatePrefs:
+    """Test checking preference updates"""
+
+    def test_partial_update(self) -> None:
+        """Test only the fields sent come back, normalized (trims, theme aliases)"""
+        assert validate_prefs({'units': 'metric'}) == {'units': 'metric'}
+        assert validate_prefs({'location': ' nyc ', 'theme': 'white'}) == {
+            'location': 'nyc',
+            'theme': 'light',
+        }
+        assert validate_prefs({'location': None}) == {'location': None}
+
+    def test_rejects_bad_values(self) -> None:
+        """Test unknown names, bad choices and non-objects are errors"""
+        with pytest.raises(ValueError, match='Unknown preferences: color'):
+            validate_prefs({'color': 'red'})
+        with pytest.raises(ValueError, match='units must be one of'):
+            validate_prefs({'units': 'kelvin'})
+        with pytest.raises(ValueError, match='location'):
+            validate_prefs({'location': 42})
+        with pytest.raises(ValueError, match='JSON object'):
+            validate_prefs(['metric'])
+
+
+class TestConversions:
+    """Test serving documents in metric units and 24-hour time"""
+
+    def test_convert_to_metric(self) -> None:
+        """Test readings convert while other fields and the input are untouched"""
+        metric = convert_to_metric(IMPERIAL_WEATHER)
+
+        assert metric['current'] == {
+            'temperature': 10,
+            'wind_speed': 16,
+            'pressure': 1013.2,
+            'precipitation_rate': 2.5,
+            'humidity': 65,
+            'precipitation_type': None,
+        }
+        assert metric['hourly'][0]['temp'] == 0
+        assert metric['daily'][0] == {'h': 100, 'l': -40, 'd': 'Mon'}
+        assert IMPERIAL_WEATHER['current']['temperature'] == 50  # noqa: PLR2004
+
+    def test_label_hours_24h(self) -> None:
+        """Test hour labels come from each hour's local time"""
+        relabeled = label_hours(IMPERIAL_WEATHER)
+        assert relabeled['hourly'][0]['t'] == '14:00'
+
+        no_time = {'hourly': [{'temp': 32, 't': '12am'}]}
+        assert label_hours(no_time) == no_time
+
+    def test_label_hours_localized(self) -> None:
+        """Test a language relabels hours in its own form on either clock"""
+        assert label_hours(IMPERIAL_WEATHER, '24h', 'de')['hourly'][0]['t'] == '14 Uhr'
+        relabeled = label_hours(IMPERIAL_WEATHER, '12h', 'es')
+        assert relabeled['hourly'][0]['t'] == '2 p. m.'
@@ -1,4 +1,4 @@
-from main import calculate_pressure_trend, get_pressure_prediction
+from weather_core.derived import calculate_pressure_trend, get_pressure_prediction
 
 
 # Test constants for pressure trend calculations
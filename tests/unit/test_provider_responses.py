@@ -0,0 +1,242 @@
+"""ABOUTME: Test typed provider responses - required fields, nulls and short series
+ABOUTME: A missing reading must come through as None, never as a made-up 0"""
+
+from datetime import datetime, timedelta, timezone
+from typing import Any
+
+import pytest
+
+from weather_core.formatting import hour_label
+from weather_core.provider_responses import (
+    OpenMeteoResponse,
+    PirateWeatherResponse,
+    ResponseError,
+    first_known,
+    rounded,
+)
+from weather_core.providers import OpenMeteoProvider, PirateWeatherProvider
+
+
+# Test constants
+MOCK_TEMP = 72
+HOURS = 2
+PIRATE_HUMIDITY = 65
+TOKYO_OFFSET_SECONDS = 9 * 3600
+SHORT_HOURS = 6
+
+
+class TestOpenMeteoResponse:
+    """Test parsing of Open-Meteo forecast responses"""
+
+    def test_parses_blocks(self, mock_open_meteo_response: dict[str, Any]) -> None:
+        """Test each block is parsed into typed series"""
+        response = OpenMeteoResponse(mock_open_meteo_response)
+
+        assert response.current is not None
+        assert response.current.temperature == MOCK_TEMP
+        assert response.current.weather_code == 0
+        assert response.current.is_day is True
+        assert response.hourly is not None
+        assert len(response.hourly.temperature) == HOURS
+        assert response.daily is not None
+        assert response.minutely is None
+
+    def test_missing_readings_are_none(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test absent and null readings stay None instead of 0"""
+        mock_open_meteo_response['current']['uv_index'] = None
+        response = OpenMeteoResponse(mock_open_meteo_response)
+
+        assert response.current is not None
+        assert response.current.uv_index is None
+        assert response.current.pressure is None
+        assert response.hourly is not None
+        assert response.hourly.pressure == [None, None]
+
+    def test_short_series_padded(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test series shorter than the time axis are padded with None"""
+        mock_open_meteo_response['hourly']['precipitation_probability'] = [40]
+        response = OpenMeteoResponse(mock_open_meteo_response)
+
+        assert response.hourly is not None
+        assert response.hourly.precipitation_probability == [40, None]
+
+    def test_apparent_temperature_falls_back(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test hourly feels-like falls back to air temperature per hour"""
+        mock_open_meteo_response['hourly']['apparent_temperature'] = [70.0, None]
+        response = OpenMeteoResponse(mock_open_meteo_response)
+
+        assert response.hourly is not None
+        assert response.hourly.feels_like == [70.0, 75.0]  # noqa: PLR2004
+
+    def test_missing_current_temperature_rejected(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test a current block without a temperature is an error, not 0°"""
+        del mock_open_meteo_response['current']['temperature_2m']
+
+        with pytest.raises(ResponseError):
+            OpenMeteoResponse(mock_open_meteo_response)
+
+    def test_wrong_shape_rejected(self) -> None:
+        """Test a block that isn't an object is an error"""
+        with pytest.raises(ResponseError):
+            OpenMeteoResponse({'current': [1, 2, 3]})
+
+    def test_split_fetch_without_current(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test a hourly-only response parses without a current block"""
+        del mock_open_meteo_response['current']
+        response = OpenMeteoResponse(mock_open_meteo_response)
+
+        assert response.current is None
+        assert response.hourly is not None
+
+
+    def test_times_in_response_timezone(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test naive times are the location's clock: named zone, offset, then GMT"""
+        mock_open_meteo_response['hourly']['time'] = ['2024-06-01T09:00'] * 2
+        utc_midnight = datetime(2024, 6, 1, 0, 0, tzinfo=timezone.utc)
+
+        named = OpenMeteoResponse(
+            {**mock_open_meteo_response, 'timezone': 'Asia/Tokyo'}
+        )
+        offset = OpenMeteoResponse(
+            {**mock_open_meteo_response, 'utc_offset_seconds': TOKYO_OFFSET_SECONDS}
+        )
+        gmt = OpenMeteoResponse(mock_open_meteo_response)
+
+        assert named.hourly is not None and offset.hourly is not None
+        assert gmt.hourly is not None
+        assert named.hourly.moments[0] == utc_midnight
+        assert offset.hourly.moments[0] == utc_midnight
+        assert gmt.hourly.moments[0] == utc_midnight + timedelta(hours=9)
+
+
+class TestPirateWeatherResponse:
+    """Test parsing of PirateWeather forecast responses"""
+
+    def test_parses_points(self) -> None:
+        """Test the currently block and hourly points are parsed"""
+        response = PirateWeatherResponse(
+            {
+                'currently': {'temperature': 72.4, 'humidity': 0.65},
+                'hourly': {'data': [{'time': 1704110400, 'temperature': 70.0}]},
+            }
+        )
+
+        assert response.currently.temperature == 72.4  # noqa: PLR2004
+        assert response.currently.uv_index is None
+        assert len(response.hourly) == 1
+
+    def test_missing_temperature_rejected(self) -> None:
+        """Test conditions without a temperature are an error"""
+        with pytest.raises(ResponseError):
+            PirateWeatherResponse({'currently': {'humidity': 0.5}})
+
+
+class TestProcessedDocuments:
+    """Test providers surface missing readings instead of zeros"""
+
+    def test_open_meteo_missing_readings(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test unreported current readings come out as None"""
+        result = OpenMeteoProvider().process_weather_data(
+            mock_open_meteo_response, 'Chicago'
+        )
+
+        assert result is not None
+        assert result['current']['temperature'] == MOCK_TEMP
+        assert result['current']['pressure'] is None
+        assert result['current']['dew_point'] is None
+        # Hours without a pressure reading stay out of the trend
+        assert 'insufficient data' in result['pressure_trend']['prediction']
+
+    def test_open_meteo_unusable_response(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test a response missing its temperature is rejected for fallback"""
+        del mock_open_meteo_response['current']['temperature_2m']
+
+        assert (
+            OpenMeteoProvider().process_weather_data(mock_open_meteo_response) is None
+        )
+
+    def test_pirate_weather_missing_readings(self) -> None:
+        """Test unreported PirateWeather readings come out as None"""
+        result = PirateWeatherProvider('test-key').process_weather_data(
+            {'currently': {'temperature': 72.4, 'humidity': 0.65}}, 'Chicago'
+        )
+
+        assert result is not None
+        current = result['current']
+        assert current['temperature'] == MOCK_TEMP
+        assert current['humidity'] == PIRATE_HUMIDITY
+        assert current['visibility'] is None
+        assert current['data_age'] is None
+
+
+    def test_pirate_weather_labels_in_location_timezone(self) -> None:
+        """Test hour labels use the response's timezone, not the default"""
+        noon_in_tokyo = datetime(2024, 6, 1, 3, 0, tzinfo=timezone.utc).timestamp()
+
+        result = PirateWeatherProvider('test-key').process_weather_data(
+            {
+                'timezone': 'Asia/Tokyo',
+                'offset': 9,
+                'currently': {'temperature': 72.0},
+                'hourly': {'data': [{'time': noon_in_tokyo, 'temperature': 70.0}]},
+            },
+            'Tokyo',
+        )
+
+        assert result is not None
+        assert result['hourly_short'][0]['t'] == '12pm'
+
+    def test_pirate_weather_hourly_starts_now(self) -> None:
+        """Test hours already past are skipped and the strip is the next six"""
+        this_hour = datetime.now(timezone.utc).replace(
+            minute=0, second=0, microsecond=0
+        )
+        hours = [
+            {'time': (this_hour + timedelta(hours=offset)).timestamp()}
+            for offset in range(-3, 10)
+        ]
+
+        result = PirateWeatherProvider('test-key').process_weather_data(
+            {
+                'timezone': 'UTC',
+                'currently': {'temperature': 72.0},
+                'hourly': {'data': hours},
+            },
+            'Here',
+        )
+
+        assert result is not None
+        strip = result['hourly_short']
+        assert len(strip) == SHORT_HOURS
+        assert strip[0]['t'] == hour_label(this_hour)
+
+
+class TestHelpers:
+    """Test the small parsing helpers"""
+
+    def test_first_known(self) -> None:
+        """Test None is skipped but falsy readings are kept"""
+        assert first_known(None, 0, 5) == 0
+        assert first_known(None, None) is None
+
+    def test_rounded(self) -> None:
+        """Test rounding passes None through"""
+        assert rounded(None) is None
+        assert rounded(71.6) == MOCK_TEMP
+        assert rounded(29.921, 2) == 29.92  # noqa: PLR2004
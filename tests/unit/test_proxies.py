@@ -0,0 +1,120 @@
+"""ABOUTME: Test finding the client address behind trusted reverse proxies
+ABOUTME: Covers X-Forwarded-For and RFC 7239 Forwarded, spoofing and odd hop formats"""
+
+import pytest
+
+from proxies import (
+    DEFAULT_TRUSTED_PROXIES,
+    PEER_ADDR_KEY,
+    UNIX_SOCKET_PEER,
+    ProxyHeadersMiddleware,
+    TrustedProxies,
+    forwarded_for,
+    parse_node,
+)
+
+
+# Test constants
+CLIENT = '198.51.100.7'
+PROXY = '10.0.0.2'
+PUBLIC_PEER = '203.0.113.50'
+
+
+class TestParsing:
+    """Test reading hops out of the headers"""
+
+    def test_nodes(self) -> None:
+        """Test ports, brackets and quotes are stripped and junk is rejected"""
+        assert str(parse_node(' 198.51.100.7 ')) == CLIENT
+        assert str(parse_node('198.51.100.7:4711')) == CLIENT
+        assert str(parse_node('"[2001:db8::1]:443"')) == '2001:db8::1'
+        assert str(parse_node('2001:db8::1')) == '2001:db8::1'
+        for junk in ('unknown', '_hidden', '', '[2001:db8::1'):
+            assert parse_node(junk) is None
+
+    def test_forwarded_header(self) -> None:
+        """Test for= is picked out of each element, whatever else it carries"""
+        header = 'for=198.51.100.7;proto=https, For="[2001:db8::1]";by=10.0.0.2'
+
+        assert forwarded_for(header) == ['198.51.100.7', '"[2001:db8::1]"']
+
+
+class TestClientAddress:
+    """Test walking the hops back to the client"""
+
+    trusted = TrustedProxies.parse(DEFAULT_TRUSTED_PROXIES)
+
+    def test_behind_trusted_proxy(self) -> None:
+        """Test the nearest untrusted hop is the client"""
+        assert self.trusted.client_address(PROXY, None, CLIENT) == CLIENT
+        # Proxies chained on the LAN are all skipped
+        chain = f'{CLIENT}, 192.168.1.1'
+        assert self.trusted.client_address('127.0.0.1', None, chain) == CLIENT
+
+    def test_spoofed_entries_ignored(self) -> None:
+        """Test a fake address a client put first in the header isn't believed"""
+        chain = f'8.8.8.8, {CLIENT}'
+
+        assert self.trusted.client_address(PROXY, None, chain) == CLIENT
+
+    def test_untrusted_peer_headers_ignored(self) -> None:
+        """Test a direct public client can't claim another address"""
+        assert (
+            self.trusted.client_address(PUBLIC_PEER, 'for=1.1.1.1', '1.1.1.1')
+            == PUBLIC_PEER
+        )
+
+    def test_unreadable_hop_stops_the_walk(self) -> None:
+        """Test a garbled hop leaves the last readable one as the client"""
+        assert self.trusted.client_address(PROXY, None, f'{CLIENT}, unknown') == PROXY
+
+    def test_only_configured_header_read(self) -> None:
+        """Test X-Forwarded-For is ignored when the proxies set Forwarded"""
+        forwarded = TrustedProxies.parse(DEFAULT_TRUSTED_PROXIES, 'Forwarded')
+        header = f'for={CLIENT};proto=https'
+
+        assert forwarded.client_address(PROXY, header, '8.8.8.8') == CLIENT
+        assert self.trusted.client_address(PROXY, 'for=8.8.8.8', CLIENT) == CLIENT
+
+    def test_unix_socket_peer_trusted(self) -> None:
+        """Test a proxy on the unix socket is believed, and kept without a header"""
+        assert self.trusted.client_address(UNIX_SOCKET_PEER, None, CLIENT) == CLIENT
+        assert (
+            self.trusted.client_address(UNIX_SOCKET_PEER, None, None)
+            == UNIX_SOCKET_PEER
+        )
+
+    def test_ipv4_mapped_peer(self) -> None:
+        """Test a dual-stack socket's ::ffff:10.0.0.2 counts as 10.0.0.2"""
+        assert self.trusted.client_address('::ffff:10.0.0.2', None, CLIENT) == CLIENT
+
+    def test_trust_nobody(self) -> None:
+        """Test an empty list turns forwarding headers off"""
+        nobody = TrustedProxies.parse('')
+
+        assert nobody.client_address('127.0.0.1', None, CLIENT) == '127.0.0.1'
+
+    def test_invalid_configuration(self) -> None:
+        """Test bad ranges and unknown headers are refused"""
+        with pytest.raises(ValueError, match='TRUSTED_PROXIES'):
+            TrustedProxies.parse('10.0.0.0/33')
+        with pytest.raises(ValueError, match='PROXY_HEADER'):
+            TrustedProxies.parse(PROXY, 'x-real-ip')
+
+
+class TestMiddleware:
+    """Test REMOTE_ADDR is rewritten for the app"""
+
+    def test_remote_addr_rewritten(self) -> None:
+        """Test the app sees the client and the peer is kept aside"""
+        seen = {}
+
+        def app(environ: dict, start_response: object) -> list[bytes]:
+            seen.update(environ)
+            return []
+
+        middleware = ProxyHeadersMiddleware(app, TrustedProxies.parse(PROXY))
+        middleware({'REMOTE_ADDR': PROXY, 'HTTP_X_FORWARDED_FOR': CLIENT}, None)
+
+        assert seen['REMOTE_ADDR'] == CLIENT
+        assert seen[PEER_ADDR_KEY] == PROXY
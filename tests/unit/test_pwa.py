@@ -0,0 +1,37 @@
+"""ABOUTME: Test the generated web app manifest and service worker asset lists
+ABOUTME: Uses a stand-in asset_url so no static directory is needed"""
+
+from pwa import PWA_MAX_SHORTCUTS, build_manifest, shell_urls, weather_icon_urls
+
+
+def fake_asset_url(path: str) -> str:
+    """Version every asset with the same fake hash"""
+    return f'/static/{path}?v=abc'
+
+
+class TestPwa:
+    """Test what installed apps and the service worker are given"""
+
+    def test_manifest(self) -> None:
+        """Test icons are versioned and shortcuts are capped"""
+        shortcuts = [{'name': f'Place {i}', 'url': f'/place-{i}'} for i in range(6)]
+        manifest = build_manifest(fake_asset_url, shortcuts)
+
+        assert manifest['start_url'] == '/'
+        assert manifest['icons'][0]['src'] == '/static/icons/icon-192x192.png?v=abc'
+        assert manifest['icons'][-1]['type'] == 'image/svg+xml'
+        assert len(manifest['shortcuts']) == PWA_MAX_SHORTCUTS
+
+    def test_precache_lists(self) -> None:
+        """Test the shell includes the page and only static icons are listed"""
+        assets = [
+            'icons/weather/static/fog.svg',
+            'icons/weather/animated/fog.svg',
+            'icons/weather/static/clear-day.svg',
+        ]
+
+        assert shell_urls(fake_asset_url)[0] == '/'
+        assert weather_icon_urls(fake_asset_url, assets) == [
+            '/static/icons/weather/static/clear-day.svg?v=abc',
+            '/static/icons/weather/static/fog.svg?v=abc',
+        ]
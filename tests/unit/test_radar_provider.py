@@ -5,7 +5,7 @@ from unittest.mock import MagicMock, patch
 import pytest
 import requests
 
-from weather_providers import RadarProvider
+from weather_core.providers import RadarProvider
 
 
 # Test constants
@@ -85,7 +85,7 @@ class TestRadarProvider:
             assert abs(tile_x - expected_x) <= TOLERANCE_PIXELS
             assert abs(tile_y - expected_y) <= TOLERANCE_PIXELS
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_success(
         self,
         mock_get: MagicMock,
@@ -140,7 +140,7 @@ class TestRadarProvider:
         assert str(CHICAGO_LAT) in str(call_args)
         assert str(CHICAGO_LON) in str(call_args)
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_invalid_api_key(
         self,
         mock_get: MagicMock,
@@ -156,10 +156,12 @@ class TestRadarProvider:
 
         assert result is None
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.http_client.time.sleep')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_api_error(
         self,
         mock_get: MagicMock,
+        mock_sleep: MagicMock,
         radar_provider: RadarProvider,
     ) -> None:
         """Test fetch weather data with API error"""
@@ -172,7 +174,7 @@ class TestRadarProvider:
 
         assert result is None
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_fetch_weather_data_network_error(
         self,
         mock_get: MagicMock,
@@ -305,7 +307,7 @@ class TestRadarProvider:
         assert animation['historical_frames'] == 0
         assert animation['forecast_frames'] == 0
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_get_weather_integration(
         self,
         mock_get: MagicMock,
@@ -334,7 +336,7 @@ class TestRadarProvider:
         assert 'animation_metadata' in radar
         assert 'map_bounds' in radar
 
-    @patch('weather_providers.requests.get')
+    @patch('weather_core.providers.requests.get')
     def test_get_weather_api_failure(
         self,
         mock_get: MagicMock,
@@ -0,0 +1,66 @@
+"""ABOUTME: Test the per-client token buckets behind API rate limiting
+ABOUTME: Time is passed in explicitly, so refills are exact and nothing sleeps"""
+
+import pytest
+
+from ratelimit import RateLimiter
+
+
+# Test constants
+PER_MINUTE = 60
+BURST = 3
+CLIENT = '203.0.113.7'
+
+
+class TestRateLimiter:
+    """Test token bucket accounting"""
+
+    def test_burst_then_refused(self) -> None:
+        """Test a client gets its burst at once and is then refused"""
+        limiter = RateLimiter(PER_MINUTE, BURST)
+
+        decisions = [limiter.check(CLIENT, now=0) for _ in range(BURST + 1)]
+
+        assert [decision.allowed for decision in decisions] == [True, True, True, False]
+        assert [decision.remaining for decision in decisions] == [2, 1, 0, 0]
+        refused = decisions[-1]
+        assert refused.retry_after == 1
+        assert refused.headers()['Retry-After'] == '1'
+        assert refused.headers()['RateLimit-Reset'] == str(BURST)
+
+    def test_refills_over_time(self) -> None:
+        """Test tokens come back at the per-minute rate, up to the burst"""
+        limiter = RateLimiter(PER_MINUTE, BURST)
+        for _ in range(BURST):
+            limiter.check(CLIENT, now=0)
+
+        assert limiter.check(CLIENT, now=1).allowed
+        assert not limiter.check(CLIENT, now=1).allowed
+        assert limiter.check(CLIENT, now=100).remaining == BURST - 1
+
+    def test_clients_are_separate(self) -> None:
+        """Test one client's burst doesn't use up another's"""
+        limiter = RateLimiter(PER_MINUTE, 1)
+        limiter.check(CLIENT, now=0)
+
+        assert not limiter.check(CLIENT, now=0).allowed
+        assert limiter.check('198.51.100.1', now=0).allowed
+
+    def test_allowed_headers(self) -> None:
+        """Test allowed requests carry RateLimit-* headers but no Retry-After"""
+        headers = RateLimiter(PER_MINUTE, BURST).check(CLIENT, now=0).headers()
+
+        assert headers == {
+            'RateLimit-Limit': str(BURST),
+            'RateLimit-Remaining': '2',
+            'RateLimit-Reset': '1',
+        }
+
+    def test_burst_defaults_to_rate(self) -> None:
+        """Test the burst is a minute's worth when not given"""
+        assert RateLimiter(PER_MINUTE).burst == PER_MINUTE
+
+    def test_zero_rate_rejected(self) -> None:
+        """Test a limit that allows nothing is a configuration error"""
+        with pytest.raises(ValueError, match='at least'):
+            RateLimiter(0)
@@ -0,0 +1,78 @@
+"""ABOUTME: Test live-update subscription management and JSON patch diffing
+ABOUTME: Covers the realtime module used by the /ws WebSocket channel"""
+
+from realtime import SubscriptionManager, location_key, make_json_patch
+
+
+# Test constants
+MAX_SUBSCRIPTIONS = 2
+
+
+class TestMakeJsonPatch:
+    """Test JSON patch generation"""
+
+    def test_identical_values_produce_no_patch(self) -> None:
+        """Test unchanged documents diff to nothing"""
+        assert make_json_patch({'a': 1}, {'a': 1}) == []
+
+    def test_nested_changes(self) -> None:
+        """Test replace, add, and remove operations on nested objects"""
+        old = {'current': {'temperature': 70, 'humidity': 40}}
+        new = {'current': {'temperature': 72, 'wind_speed': 5}}
+
+        patch = make_json_patch(old, new)
+
+        assert {'op': 'remove', 'path': '/current/humidity'} in patch
+        assert {
+            'op': 'replace',
+            'path': '/current/temperature',
+            'value': 72,
+        } in patch
+        assert {'op': 'add', 'path': '/current/wind_speed', 'value': 5} in patch
+
+    def test_pointer_tokens_are_escaped(self) -> None:
+        """Test keys containing / and ~ are escaped per RFC 6901"""
+        patch = make_json_patch({}, {'urn:a/b~c': 1})
+        assert patch == [{'op': 'add', 'path': '/urn:a~1b~0c', 'value': 1}]
+
+    def test_lists_are_replaced(self) -> None:
+        """Test lists are replaced wholesale rather than diffed"""
+        patch = make_json_patch({'hourly': [1, 2]}, {'hourly': [2, 3]})
+        assert patch == [{'op': 'replace', 'path': '/hourly', 'value': [2, 3]}]
+
+
+class TestSubscriptionManager:
+    """Test per-connection subscription bookkeeping"""
+
+    def test_location_key_matches_cache_key(self) -> None:
+        """Test location keys use the weather cache format"""
+        assert location_key(41.8781, -87.6298) == '41.8781,-87.6298'
+
+    def test_subscription_cap(self) -> None:
+        """Test a connection cannot exceed its subscription limit"""
+        manager = SubscriptionManager(max_subscriptions=MAX_SUBSCRIPTIONS)
+        manager.connect('a')
+
+        assert manager.subscribe('a', 'one')
+        assert manager.subscribe('a', 'two')
+        assert manager.subscribe('a', 'one')  # Re-subscribing is a no-op
+        assert not manager.subscribe('a', 'three')
+        assert manager.subscriptions('a') == ['one', 'two']
+
+    def test_snapshots_only_kept_while_watched(self) -> None:
+        """Test snapshots are skipped for unwatched locations and dropped later"""
+        manager = SubscriptionManager()
+        assert manager.update_snapshot('loc', 'current', {'temperature': 70}) == []
+
+        manager.connect('a')
+        manager.subscribe('a', 'loc')
+        assert manager.update_snapshot('loc', 'current', {'temperature': 70}) == [
+            {'op': 'add', 'path': '/current', 'value': {'temperature': 70}}
+        ]
+        assert manager.update_snapshot('loc', 'current', {'temperature': 71}) == [
+            {'op': 'replace', 'path': '/current/temperature', 'value': 71}
+        ]
+
+        assert manager.disconnect('a') == ['loc']
+        assert manager.snapshot('loc') == {}
+        assert manager.get_stats()['connections'] == 0
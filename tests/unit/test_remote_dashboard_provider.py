@@ -0,0 +1,119 @@
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+import requests
+
+from weather_core.providers import RemoteDashboardProvider, WeatherProviderManager
+
+
+# Test constants
+UPSTREAM_URL = 'https://weather.example.com'
+CHICAGO_LAT = 41.8781
+CHICAGO_LON = -87.6298
+PROVIDER_TIMEOUT = 10
+UPSTREAM_TEMP = 68
+
+
+class TestRemoteDashboardProvider:
+    """Test using an upstream weather-dashboard instance as a provider"""
+
+    @pytest.fixture
+    def remote_provider(self) -> RemoteDashboardProvider:
+        """Create a remote dashboard provider pointed at the test upstream"""
+        return RemoteDashboardProvider(f'{UPSTREAM_URL}/')
+
+    @pytest.fixture
+    def upstream_weather(self) -> dict[str, Any]:
+        """Processed weather document as served by the upstream /api/weather"""
+        return {
+            'current': {'temperature': UPSTREAM_TEMP, 'icon': 'clear-day'},
+            'hourly': [],
+            'daily': [],
+            'location': 'Chicago',
+            'provider': 'OpenMeteo',
+        }
+
+    def test_provider_initialization(
+        self, remote_provider: RemoteDashboardProvider
+    ) -> None:
+        """Test the upstream URL is normalized without a trailing slash"""
+        assert remote_provider.name == 'RemoteDashboard'
+        assert remote_provider.base_url == UPSTREAM_URL
+        assert remote_provider.get_provider_info()['upstream'] == UPSTREAM_URL
+
+    @patch('weather_core.providers.requests.get')
+    def test_fetches_upstream_weather_api(
+        self,
+        mock_get: MagicMock,
+        remote_provider: RemoteDashboardProvider,
+        upstream_weather: dict[str, Any],
+    ) -> None:
+        """Test weather is requested from the upstream /api/weather"""
+        mock_response = MagicMock()
+        mock_response.status_code = 200
+        mock_response.json.return_value = upstream_weather
+        mock_get.return_value = mock_response
+
+        result = remote_provider.get_weather(
+            CHICAGO_LAT, CHICAGO_LON, 'Cabin', 'America/Chicago'
+        )
+
+        mock_get.assert_called_once_with(
+            f'{UPSTREAM_URL}/api/weather',
+            params={
+                'lat': CHICAGO_LAT,
+                'lon': CHICAGO_LON,
+                'naming': 'legacy',
+                'timezone': 'America/Chicago',
+            },
+            timeout=PROVIDER_TIMEOUT,
+        )
+        assert result is not None
+        assert result['current']['temperature'] == UPSTREAM_TEMP
+        assert result['location'] == 'Cabin'
+        assert result['provider'] == 'RemoteDashboard (OpenMeteo)'
+        assert result['upstream'] == UPSTREAM_URL
+
+    @patch('weather_core.http_client.time.sleep')
+    @patch('weather_core.providers.requests.get')
+    def test_upstream_error_returns_none(
+        self,
+        mock_get: MagicMock,
+        mock_sleep: MagicMock,
+        remote_provider: RemoteDashboardProvider,
+    ) -> None:
+        """Test upstream error documents and failed requests yield no data"""
+        mock_response = MagicMock()
+        mock_response.status_code = 500
+        mock_response.json.return_value = {'error': 'Weather data unavailable'}
+        mock_get.return_value = mock_response
+
+        assert remote_provider.get_weather(CHICAGO_LAT, CHICAGO_LON) is None
+
+        mock_get.side_effect = requests.exceptions.ConnectionError('offline')
+        assert remote_provider.get_weather(CHICAGO_LAT, CHICAGO_LON) is None
+
+    @patch('weather_core.http_client.time.sleep')
+    @patch('weather_core.providers.requests.get')
+    def test_manager_falls_back_when_upstream_down(
+        self,
+        mock_get: MagicMock,
+        mock_sleep: MagicMock,
+        remote_provider: RemoteDashboardProvider,
+    ) -> None:
+        """Test a satellite instance falls back to its local providers"""
+        mock_get.side_effect = requests.exceptions.Timeout('upstream timeout')
+        local_provider = MagicMock()
+        local_provider.name = 'OpenMeteo'
+        local_provider.get_weather.return_value = {'provider': 'OpenMeteo'}
+
+        manager = WeatherProviderManager()
+        manager.add_provider(local_provider, is_primary=True)
+        manager.add_provider(remote_provider)
+        manager.set_primary_provider(remote_provider.name)
+
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON, 'Cabin')
+
+        assert result == {'provider': 'OpenMeteo'}
+        assert manager.fallback_providers == ['OpenMeteo']
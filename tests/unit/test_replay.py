@@ -0,0 +1,218 @@
+"""ABOUTME: Test recording and replaying provider HTTP traffic, and golden files of
+ABOUTME: what each provider makes of its recordings - UPDATE_GOLDEN=1 rewrites them"""
+
+import contextlib
+import json
+import os
+from collections.abc import Iterator
+from datetime import datetime
+from pathlib import Path
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+import requests
+
+from weather_core.providers import (
+    HybridWeatherProvider,
+    OpenMeteoProvider,
+    PirateWeatherProvider,
+    RemoteDashboardProvider,
+    WeatherProvider,
+)
+from weather_core.replay import (
+    REDACTED,
+    Cassette,
+    RecordingTransport,
+    ReplayTransport,
+    main,
+    replaying,
+    request_url,
+)
+from weather_core.transport import build_response, get_transport
+
+
+# Test constants
+FIXTURES = Path(__file__).parent.parent / 'fixtures'
+CASSETTES = FIXTURES / 'cassettes'
+GOLDEN = FIXTURES / 'golden'
+URL = 'https://api.example.com/forecast'
+SECRET = 'key/with+symbols'
+UPSTREAM = 'https://weather.example.com'
+
+
+@contextlib.contextmanager
+def frozen_clock(moment: datetime) -> Iterator[None]:
+    """Providers see the time a cassette was recorded as now"""
+
+    class FrozenDatetime(datetime):
+        @classmethod
+        def now(cls, tz: Any = None) -> datetime:  # type: ignore[override]
+            return moment.astimezone(tz) if tz else moment.replace(tzinfo=None)
+
+    with (
+        patch('weather_core.providers.datetime', FrozenDatetime),
+        patch('time.time', return_value=moment.timestamp()),
+    ):
+        yield
+
+
+def replay_weather(provider: WeatherProvider, name: str) -> dict[str, Any] | None:
+    """What a provider makes of a cassette, fetched as it was when recorded"""
+    cassette = Cassette.load(CASSETTES / f'{name}.json')
+    request = cassette.request
+    with replaying(cassette), frozen_clock(cassette.recorded_time):
+        return provider.get_weather(
+            request['lat'], request['lon'], request['location'], request['tz']
+        )
+
+
+def assert_golden(name: str, weather: dict[str, Any] | None) -> None:
+    """Compare with the golden file, or rewrite it when UPDATE_GOLDEN is set"""
+    assert weather, 'provider returned nothing for its recording'
+    path = GOLDEN / f'{name}.json'
+    if os.environ.get('UPDATE_GOLDEN'):
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(
+            json.dumps(weather, indent=2, sort_keys=True, ensure_ascii=False) + '\n',
+            encoding='utf-8',
+        )
+    golden = json.loads(path.read_text(encoding='utf-8'))
+    assert json.loads(json.dumps(weather)) == golden
+
+
+def json_reply(url: str, body: Any, status: int = 200) -> requests.Response:
+    """A JSON response as a transport would return it"""
+    return build_response(
+        url,
+        status,
+        {'Content-Type': 'application/json', 'Date': 'Sat, 01 Jun 2024'},
+        json.dumps(body).encode('utf-8'),
+    )
+
+
+class TestRecordAndReplay:
+    """Test cassettes capture replies and give them back"""
+
+    def test_request_url_ignores_parameter_order(self) -> None:
+        """Test the same query in a different order is the same request"""
+        assert request_url(URL, {'b': 2, 'a': 'x y'}) == f'{URL}?a=x+y&b=2'
+        assert request_url(f'{URL}?b=2&a=x+y') == request_url(URL, {'a': 'x y', 'b': 2})
+
+    def test_round_trip(self, tmp_path: Path) -> None:
+        """Test a recorded reply replays from the saved file"""
+        inner = MagicMock()
+        inner.get.return_value = json_reply(URL, {'temperature': 72})
+        cassette = Cassette(request={'lat': 41.88})
+        RecordingTransport(inner, cassette).get(URL, params={'units': 'us'}, timeout=5)
+        cassette.save(tmp_path / 'cassette.json')
+
+        loaded = Cassette.load(tmp_path / 'cassette.json')
+        response = ReplayTransport(loaded).get(URL, params={'units': 'us'})
+
+        inner.get.assert_called_once_with(URL, params={'units': 'us'}, timeout=5)
+        assert loaded.request == {'lat': 41.88}
+        assert loaded.interactions[0]['headers'] == {'Content-Type': 'application/json'}
+        assert response.status_code == 200  # noqa: PLR2004
+        assert response.json() == {'temperature': 72}
+        assert response.url == f'{URL}?units=us'
+
+    def test_secrets_redacted(self) -> None:
+        """Test API keys never reach the cassette, plain or URL-encoded"""
+        inner = MagicMock()
+        inner.get.return_value = json_reply(URL, {'echo': SECRET})
+        cassette = Cassette()
+
+        RecordingTransport(inner, cassette, [SECRET]).get(
+            f'{URL}/{SECRET}', params={'key': SECRET}
+        )
+
+        recorded = json.dumps(cassette.interactions)
+        assert 'symbols' not in recorded
+        assert cassette.interactions[0]['url'] == f'{URL}/{REDACTED}?key={REDACTED}'
+        assert cassette.interactions[0]['json'] == {'echo': REDACTED}
+
+    def test_repeats_and_misses(self) -> None:
+        """Test repeated requests replay in order and unknown ones fail"""
+        cassette = Cassette(
+            [
+                {'url': URL, 'status': 503, 'text': 'busy'},
+                {'url': URL, 'status': 200, 'json': {}},
+            ]
+        )
+        transport = ReplayTransport(cassette)
+
+        statuses = [transport.get(URL).status_code for _ in range(3)]
+
+        assert statuses == [503, 200, 200]
+        with pytest.raises(requests.ConnectionError, match='No recording'):
+            transport.get(URL, params={'lat': 1})
+
+    def test_not_a_cassette(self, tmp_path: Path) -> None:
+        """Test other JSON files are refused"""
+        path = tmp_path / 'other.json'
+        path.write_text('{"interactions": []}')
+
+        with pytest.raises(ValueError, match='not a version 1 cassette'):
+            Cassette.load(path)
+
+    @patch('weather_core.providers.OpenMeteoProvider.get_weather')
+    def test_record_command(self, mock_weather: MagicMock, tmp_path: Path) -> None:
+        """Test the recorder saves what was asked and puts the transport back"""
+        original = get_transport()
+        mock_weather.return_value = {'current': {'temperature': 72}}
+        out = tmp_path / 'open_meteo.json'
+
+        code = main(
+            [
+                *('--provider', 'OpenMeteo', '--lat', '41.8781', '--lon', '-87.6298'),
+                *('--location', 'Chicago', '--out', str(out)),
+            ],
+            {'PROVIDERS_CONFIG_PATH': str(tmp_path / 'absent.json')},
+        )
+
+        assert code == 0
+        assert get_transport() is original
+        assert Cassette.load(out).request['location'] == 'Chicago'
+
+    def test_record_nothing_saved_on_failure(self, tmp_path: Path) -> None:
+        """Test an unknown provider is refused before anything is fetched"""
+        out = tmp_path / 'nope.json'
+
+        code = main(
+            ['--provider', 'Nope', '--lat', '1', '--lon', '2', '--out', str(out)],
+            {'PROVIDERS_CONFIG_PATH': str(tmp_path / 'absent.json')},
+        )
+
+        assert code == 2  # noqa: PLR2004
+        assert not out.exists()
+
+
+class TestProviderGoldenFiles:
+    """Test each provider's processed weather for a recorded response"""
+
+    def test_open_meteo(self) -> None:
+        """Test Open-Meteo's full forecast"""
+        assert_golden('open_meteo', replay_weather(OpenMeteoProvider(), 'open_meteo'))
+
+    def test_pirate_weather(self) -> None:
+        """Test PirateWeather's current conditions and short hourly strip"""
+        provider = PirateWeatherProvider(REDACTED)
+
+        assert_golden('pirate_weather', replay_weather(provider, 'pirate_weather'))
+
+    def test_hybrid(self) -> None:
+        """Test the hybrid's blend of the two"""
+        provider = HybridWeatherProvider(
+            PirateWeatherProvider(REDACTED), OpenMeteoProvider()
+        )
+
+        assert_golden('hybrid', replay_weather(provider, 'hybrid'))
+
+    def test_remote_dashboard(self) -> None:
+        """Test an upstream dashboard's document passed through"""
+        provider = RemoteDashboardProvider(UPSTREAM)
+
+        assert_golden(
+            'remote_dashboard', replay_weather(provider, 'remote_dashboard')
+        )
@@ -0,0 +1,51 @@
+"""ABOUTME: Test request ids and the JSON access log line
+ABOUTME: Pure functions only; the Flask hooks are covered in test_main"""
+
+import json
+
+from requestlog import access_log_line, request_id
+
+
+# Test constants
+PROXY_ID = 'edge-7f3a9c21'
+HEX_ID_LENGTH = 32
+STATUS_OK = 200
+
+
+class TestRequestId:
+    """Test choosing a request's id"""
+
+    def test_new_ids_are_unique(self) -> None:
+        """Test a request without an id gets a fresh random one"""
+        first, second = request_id(), request_id(None)
+
+        assert len(first) == HEX_ID_LENGTH
+        assert first != second
+
+    def test_proxy_id_kept(self) -> None:
+        """Test a well-formed id from a proxy is carried through"""
+        assert request_id(PROXY_ID) == PROXY_ID
+
+    def test_malformed_ids_replaced(self) -> None:
+        """Test short, long or odd-character ids are not trusted into the logs"""
+        for inbound in ('abc', 'x' * 200, 'id with spaces', '"},{"status":500'):
+            assert request_id(inbound) != inbound
+
+
+class TestAccessLogLine:
+    """Test the structured log line"""
+
+    def test_fields(self) -> None:
+        """Test the line is one JSON object with every field, unknowns null"""
+        line = access_log_line(PROXY_ID, 'GET', '/api/weather', STATUS_OK, 12.345)
+
+        assert '\n' not in line
+        entry = json.loads(line)
+        assert entry['request_id'] == PROXY_ID
+        assert entry['method'] == 'GET'
+        assert entry['path'] == '/api/weather'
+        assert entry['status'] == STATUS_OK
+        assert entry['latency_ms'] == 12.3  # noqa: PLR2004
+        assert entry['provider'] is None
+        assert entry['cache_hit'] is None
+        assert entry['ts'].endswith('+00:00')
@@ -3,7 +3,7 @@ from typing import Any
 
 import pytest
 
-from weather_providers import SolarDataProvider
+from weather_core.providers import SolarDataProvider
 
 
 # Test constants
@@ -0,0 +1,227 @@
+"""ABOUTME: Test personal weather station config, upload parsing in the JSON, Ecowitt
+ABOUTME: and Weather Underground formats, and which station stands in for current"""
+
+import json
+
+import pytest
+
+from station import (
+    STATION_FRESH_SECONDS,
+    STATION_PROVIDER,
+    StationNetwork,
+    overlay_current,
+    parse_stations,
+    reading_from_json,
+    reading_from_upload,
+)
+from tokens import hash_token
+
+
+# Test constants
+NOW = 1_706_710_000.0
+BACKYARD = {
+    'name': 'backyard',
+    'lat': 41.8781,
+    'lon': -87.6298,
+    'key': 'passkey-1',
+    'override_current': True,
+}
+ECOWITT_UPLOAD = {
+    'PASSKEY': 'passkey-1',
+    'stationtype': 'EasyWeatherPro_V5.1.1',
+    'dateutc': '2024-01-31 14:05:00',
+    'tempf': '31.6',
+    'humidity': '78',
+    'windspeedmph': '4.5',
+    'windgustmph': '8.1',
+    'winddir': '225',
+    'baromrelin': '30.012',
+    'baromabsin': '29.401',
+    'rainratein': '0.000',
+    'uv': '1',
+}
+
+
+def network(*entries: dict) -> StationNetwork:
+    """A network with the given station entries"""
+    stations = StationNetwork()
+    stations.configure(parse_stations(json.dumps(list(entries))))
+    return stations
+
+
+class TestParseStations:
+    """Test STATIONS config"""
+
+    def test_entries(self) -> None:
+        """Test keys are hashed and options default sensibly"""
+        station, other = parse_stations(
+            json.dumps(
+                [
+                    BACKYARD,
+                    {
+                        'name': 'roof',
+                        'lat': 40,
+                        'lon': -74,
+                        'key_sha256': 'A' * 64,
+                        'radius_km': 0.5,
+                    },
+                ]
+            )
+        )
+
+        assert station['hash'] == hash_token('passkey-1')
+        assert station['override_current'] is True
+        assert station['radius_km'] == 2.0  # noqa: PLR2004
+        assert other['hash'] == 'a' * 64
+        assert other['override_current'] is False
+        assert parse_stations('  ') == []
+
+    def test_invalid_entries(self) -> None:
+        """Test missing names, keys, positions and duplicates are refused"""
+        for entries, message in (
+            ({'name': 'x'}, 'must be a JSON list'),
+            ([{'lat': 1, 'lon': 1, 'key': 'k'}], 'needs a name'),
+            ([{'name': 'x', 'key': 'k'}], 'needs a lat and lon'),
+            ([{'name': 'x', 'lat': 91, 'lon': 0, 'key': 'k'}], 'out of range'),
+            ([{'name': 'x', 'lat': 1, 'lon': 1}], 'needs a key'),
+            ([BACKYARD, BACKYARD], 'must be unique'),
+        ):
+            with pytest.raises(ValueError, match=message):
+                parse_stations(json.dumps(entries))
+
+
+class TestReadings:
+    """Test uploads become readings in the history's fields"""
+
+    def test_ecowitt_upload(self) -> None:
+        """Test Ecowitt parameters map onto history fields (hPa) and its time"""
+        reading = reading_from_upload(ECOWITT_UPLOAD, NOW)
+
+        assert reading == {
+            'temperature': 31.6,
+            'humidity': 78.0,
+            'wind_speed': 4.5,
+            'wind_gust': 8.1,
+            'wind_direction': 225.0,
+            'pressure': 1016.3,
+            'uv_index': 1.0,
+            'precipitation_rate': 0.0,
+            'timestamp': 1_706_709_900.0,
+        }
+
+    def test_weather_underground_upload(self) -> None:
+        """Test the Weather Underground names, and dateutc=now"""
+        reading = reading_from_upload(
+            {
+                'ID': 'KILCHICA1',
+                'PASSWORD': 'pw',
+                'dateutc': 'now',
+                'tempf': '70',
+                'dewptf': '55',
+                'baromin': '29.92',
+                'UV': '3',
+            },
+            NOW,
+        )
+
+        assert reading['dew_point'] == 55.0  # noqa: PLR2004
+        assert reading['pressure'] == 1013.2  # noqa: PLR2004
+        assert reading['uv_index'] == 3.0  # noqa: PLR2004
+        assert reading['timestamp'] == NOW
+
+    def test_bad_uploads(self) -> None:
+        """Test unreadable numbers, times and empty uploads are refused"""
+        for params, message in (
+            ({'tempf': 'warm'}, 'tempf must be a number'),
+            ({'tempf': 'nan'}, 'tempf must be a number'),
+            ({'tempf': '70', 'dateutc': 'yesterday'}, 'dateutc must look like'),
+            ({'PASSKEY': 'x'}, 'no readings'),
+        ):
+            with pytest.raises(ValueError, match=message):
+                reading_from_upload(params, NOW)
+
+    def test_json_reading(self) -> None:
+        """Test JSON takes history field names and an optional observed_at"""
+        reading = reading_from_json(
+            {'temperature': 68, 'humidity': 40, 'observed_at': '2024-01-31T14:05Z'},
+            NOW,
+        )
+
+        assert reading == {
+            'temperature': 68.0,
+            'humidity': 40.0,
+            'timestamp': 1_706_709_900.0,
+        }
+        assert reading_from_json({'pressure': 30.1}, NOW)['timestamp'] == NOW
+
+    def test_bad_json(self) -> None:
+        """Test non-objects, empty readings and bad times are refused"""
+        for body, message in (
+            ([68], 'JSON object'),
+            ({'station': 'backyard'}, 'at least one of'),
+            ({'temperature': 'hot'}, 'temperature must be a number'),
+            ({'temperature': 68, 'observed_at': 'noon'}, 'observed_at must be'),
+        ):
+            with pytest.raises(ValueError, match=message):
+                reading_from_json(body, NOW)
+
+
+class TestStationNetwork:
+    """Test authentication and which station overrides current conditions"""
+
+    def test_authenticate(self) -> None:
+        """Test a station is found by its key and nothing by any other"""
+        stations = network(BACKYARD)
+
+        station = stations.authenticate('passkey-1')
+
+        assert station is not None
+        assert station['name'] == 'backyard'
+        assert stations.authenticate('wrong') is None
+        assert stations.authenticate(None) is None
+
+    def test_keeps_newest_reading(self) -> None:
+        """Test a late upload of an older reading doesn't replace a newer one"""
+        stations = network(BACKYARD)
+        station = stations.authenticate('passkey-1')
+        assert station is not None
+
+        assert stations.update(station, {'temperature': 70.0, 'timestamp': NOW})
+        older = {'temperature': 60.0, 'timestamp': NOW - 60}
+        assert not stations.update(station, older)
+        assert stations.latest['backyard']['temperature'] == 70.0  # noqa: PLR2004
+
+    def test_current_for_nearby_fresh_reading(self) -> None:
+        """Test only a fresh reading from an overriding station in range counts"""
+        stations = network(
+            BACKYARD,
+            {**BACKYARD, 'name': 'quiet', 'key': 'k2', 'override_current': False},
+        )
+        for name in ('backyard', 'quiet'):
+            stations.latest[name] = {'temperature': 70.0, 'timestamp': NOW}
+
+        found = stations.current_for(41.88, -87.63, NOW + 60)
+
+        assert found is not None
+        assert found[0]['name'] == 'backyard'
+        assert stations.current_for(41.95, -87.63, NOW) is None  # 8 km away
+        stale = NOW + STATION_FRESH_SECONDS + 1
+        assert stations.current_for(41.88, -87.63, stale) is None
+
+    def test_overlay_keeps_sky(self) -> None:
+        """Test readings replace the provider's but its icon and summary stay"""
+        current = overlay_current(
+            {'temperature': 75, 'humidity': 50, 'icon': 'rain', 'summary': 'Rain'},
+            {'name': 'backyard'},
+            {'temperature': 71.2, 'timestamp': NOW},
+        )
+
+        assert current == {
+            'temperature': 71.2,
+            'humidity': 50,
+            'icon': 'rain',
+            'summary': 'Rain',
+            'timestamp': NOW,
+            'provider': STATION_PROVIDER,
+            'station': 'backyard',
+        }
@@ -0,0 +1,63 @@
+"""ABOUTME: Test the SQLite favorites store: validation, ordering and persistence
+ABOUTME: Each test uses a fresh database in a temporary directory"""
+
+from pathlib import Path
+
+import pytest
+
+from storage import FavoritesStore, validate_favorite
+
+
+# Test constants
+CHICAGO = {'name': 'Chicago', 'lat': 41.8781, 'lon': -87.6298}
+TOKYO = {'name': 'Tokyo', 'lat': 35.6762, 'lon': 139.6503}
+
+
+class TestValidateFavorite:
+    """Test checking submitted favorites"""
+
+    def test_normalizes_fields(self) -> None:
+        """Test names are trimmed and coordinates become floats"""
+        assert validate_favorite({'name': ' Home ', 'lat': 41, 'lon': -87}) == {
+            'name': 'Home',
+            'lat': 41.0,
+            'lon': -87.0,
+        }
+        assert validate_favorite({'position': 2}, partial=True) == {'position': 2}
+
+    def test_rejects_bad_favorites(self) -> None:
+        """Test missing names, bad coordinates and non-objects are errors"""
+        with pytest.raises(ValueError, match='name is required'):
+            validate_favorite({'lat': 41, 'lon': -87})
+        with pytest.raises(ValueError, match='out of range'):
+            validate_favorite({'name': 'Nowhere', 'lat': 91, 'lon': 0})
+        with pytest.raises(ValueError, match='lat must be a number'):
+            validate_favorite({'lat': True}, partial=True)
+        with pytest.raises(ValueError, match='JSON object'):
+            validate_favorite([CHICAGO])
+
+
+class TestFavoritesStore:
+    """Test saving, ordering and removing favorites"""
+
+    def test_add_update_delete(self, tmp_path: Path) -> None:
+        """Test favorites list in position order and changes persist"""
+        path = str(tmp_path / 'favorites.db')
+        store = FavoritesStore(path)
+        chicago = store.add(CHICAGO)
+        tokyo = store.add(TOKYO)
+
+        assert [fav['name'] for fav in store.list_favorites()] == ['Chicago', 'Tokyo']
+        moved = store.update(tokyo['id'], {'position': -1, 'name': 'Tokyo Office'})
+        assert moved is not None
+        assert moved['name'] == 'Tokyo Office'
+        assert store.update('missing', {'name': 'Nope'}) is None
+
+        reopened = FavoritesStore(path)
+        assert [fav['id'] for fav in reopened.list_favorites()] == [
+            tokyo['id'],
+            chicago['id'],
+        ]
+        assert reopened.delete(chicago['id'])
+        assert not reopened.delete(chicago['id'])
+        assert reopened.get(chicago['id']) is None
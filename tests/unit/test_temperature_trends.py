@@ -6,7 +6,7 @@ from datetime import datetime, timezone
 from typing import Any
 from unittest.mock import patch
 
-from weather_providers import EnhancedTemperatureTrendProvider
+from weather_core.providers import EnhancedTemperatureTrendProvider
 
 
 # Test constants for temperature calculations
@@ -439,7 +439,7 @@ class TestEnhancedTemperatureTrendProvider:
 
         assert percentile_bands['data_source'] == 'estimated'
 
-    @patch('weather_providers.datetime')
+    @patch('weather_core.providers.datetime')
     def test_process_weather_data_complete(self, mock_datetime: Any) -> None:
         """Test complete weather data processing"""
         # Mock current time
@@ -0,0 +1,34 @@
+"""ABOUTME: Test theme name resolution and the generated theme stylesheet
+ABOUTME: Older theme names map onto the data-theme values components style"""
+
+from themes import THEMES, resolve_theme, theme_attribute, theme_stylesheet
+
+
+class TestThemes:
+    """Test picking themes and rendering their tokens"""
+
+    def test_resolve_theme(self) -> None:
+        """Test names and aliases resolve, and anything else is ignored"""
+        assert resolve_theme('Dark') == 'dark'
+        assert resolve_theme('white') == 'light'
+        assert resolve_theme('dashboard') == 'eink'
+        assert resolve_theme('auto') == 'auto'
+        assert resolve_theme('sepia') is None
+        assert resolve_theme(None) is None
+
+    def test_attributes(self) -> None:
+        """Test themes keep the data-theme values existing styles expect"""
+        assert theme_attribute('light') == 'white'
+        assert theme_attribute('eink') == 'dashboard'
+        assert theme_attribute('high-contrast') == 'high-contrast'
+        assert theme_attribute('auto') is None
+
+    def test_stylesheet(self) -> None:
+        """Test every theme's tokens render, with dark for dark-scheme auto"""
+        css = theme_stylesheet()
+
+        assert css.startswith(':root { --bg-primary: linear-gradient(')
+        assert '@media (prefers-color-scheme: dark) { body:not([data-theme])' in css
+        for theme in THEMES:
+            assert f'[data-theme="{theme_attribute(theme)}"]' in css
+        assert '--card-border: #ffffff;' in css
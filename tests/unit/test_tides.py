@@ -0,0 +1,113 @@
+"""ABOUTME: Test NOAA tide station lookup and high/low prediction parsing
+ABOUTME: NOAA responses are mocked; no network access is needed"""
+
+from datetime import datetime, timezone
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+
+from tides import (
+    distance_km,
+    fetch_tide_predictions,
+    fetch_tide_stations,
+    nearest_station,
+    upcoming_tides,
+)
+
+
+# Test constants
+SF_LAT = 37.8063
+SF_LON = -122.4659
+LA_LAT = 33.7200
+LA_LON = -118.2720
+SF_TO_LA_KM = 591
+NOW = datetime(2026, 10, 16, 12, 0, tzinfo=timezone.utc)
+
+STATIONS: list[dict[str, Any]] = [
+    {
+        'id': '9414290',
+        'name': 'San Francisco',
+        'state': 'CA',
+        'lat': SF_LAT,
+        'lon': SF_LON,
+    },
+    {
+        'id': '9410660',
+        'name': 'Los Angeles',
+        'state': 'CA',
+        'lat': LA_LAT,
+        'lon': LA_LON,
+    },
+]
+
+
+class TestTides:
+    """Test station selection and prediction handling"""
+
+    def test_distance_km(self) -> None:
+        """Test haversine distance between two known stations"""
+        assert round(distance_km(SF_LAT, SF_LON, LA_LAT, LA_LON)) == SF_TO_LA_KM
+        assert distance_km(SF_LAT, SF_LON, SF_LAT, SF_LON) == 0
+
+    def test_nearest_station(self) -> None:
+        """Test the closest station wins and far-away points get none"""
+        station = nearest_station(STATIONS, 37.77, -122.42)
+
+        assert station is not None
+        assert station['id'] == '9414290'
+        assert station['distance_km'] < 10  # noqa: PLR2004
+        assert nearest_station(STATIONS, 41.8781, -87.6298) is None  # Chicago
+
+    @patch('tides.requests.get')
+    def test_fetch_tide_stations(self, mock_get: MagicMock) -> None:
+        """Test station metadata is normalized and unusable rows skipped"""
+        mock_get.return_value.json.return_value = {
+            'stations': [
+                {
+                    'id': 9414290,
+                    'name': 'San Francisco',
+                    'state': 'CA',
+                    'lat': SF_LAT,
+                    'lng': SF_LON,
+                },
+                {'id': 1, 'name': 'No position'},
+            ]
+        }
+
+        stations = fetch_tide_stations()
+
+        assert stations == [STATIONS[0]]
+        assert mock_get.call_args[1]['params'] == {'type': 'tidepredictions'}
+
+    @patch('tides.requests.get')
+    def test_fetch_and_filter_predictions(self, mock_get: MagicMock) -> None:
+        """Test hi/lo predictions parse as UTC and are windowed to 72 hours"""
+        mock_get.return_value.json.return_value = {
+            'predictions': [
+                {'t': '2026-10-16 04:12', 'v': '5.123', 'type': 'H'},
+                {'t': '2026-10-16 13:30', 'v': '-0.41', 'type': 'L'},
+                {'t': '2026-10-19 13:00', 'v': '4.9', 'type': 'H'},
+            ]
+        }
+
+        predictions = fetch_tide_predictions('9414290', NOW)
+        upcoming = upcoming_tides(predictions, NOW)
+
+        assert mock_get.call_args[1]['params']['begin_date'] == '20261016'
+        assert predictions[0] == {
+            'time': '2026-10-16T04:12:00+00:00',
+            'type': 'high',
+            'height_ft': 5.12,
+        }
+        assert [tide['type'] for tide in upcoming] == ['low']
+
+    @patch('tides.requests.get')
+    def test_noaa_error_raises(self, mock_get: MagicMock) -> None:
+        """Test NOAA's in-band error messages surface as ValueError"""
+        mock_get.return_value.json.return_value = {
+            'error': {'message': 'No Predictions data was found'}
+        }
+
+        with pytest.raises(ValueError, match='No Predictions'):
+            fetch_tide_predictions('0000000', NOW)
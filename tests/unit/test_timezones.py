@@ -0,0 +1,59 @@
+"""ABOUTME: Test timezone resolution for forecasts - named zones, fixed offsets and the
+ABOUTME: default - and that naive provider times are read as the location's clock"""
+
+import zoneinfo
+from datetime import datetime, timedelta, timezone
+
+from weather_core.timezones import DEFAULT_TIMEZONE, localize, resolve_timezone
+
+
+# Test constants
+TOKYO = zoneinfo.ZoneInfo('Asia/Tokyo')
+TOKYO_OFFSET_SECONDS = 9 * 3600
+
+
+class TestResolveTimezone:
+    """Test which zone a forecast's times are in"""
+
+    def test_first_known_name(self) -> None:
+        """Test the first valid IANA name wins, skipping missing and unknown ones"""
+        assert resolve_timezone(None, 'Mars/Olympus', 'Asia/Tokyo') == TOKYO
+
+    def test_offset_without_name(self) -> None:
+        """Test a fixed offset stands in when no name is usable"""
+        zone = resolve_timezone('GMT+9ish', utc_offset_seconds=TOKYO_OFFSET_SECONDS)
+
+        assert zone == timezone(timedelta(hours=9))
+
+    def test_default(self) -> None:
+        """Test nothing to go on gives the default zone"""
+        assert resolve_timezone(None) == zoneinfo.ZoneInfo(DEFAULT_TIMEZONE)
+
+
+class TestLocalize:
+    """Test timestamps are placed in the forecast's zone"""
+
+    def test_naive_is_wall_clock(self) -> None:
+        """Test a naive time is the zone's local time, whatever the server's zone"""
+        moment = localize(datetime(2024, 6, 1, 9, 0), TOKYO)
+
+        assert moment.astimezone(timezone.utc) == datetime(
+            2024, 6, 1, 0, 0, tzinfo=timezone.utc
+        )
+
+    def test_aware_is_converted(self) -> None:
+        """Test an aware time keeps its instant"""
+        moment = localize(datetime(2024, 6, 1, 0, 0, tzinfo=timezone.utc), TOKYO)
+
+        assert moment.hour == 9  # noqa: PLR2004
+        assert moment.tzinfo == TOKYO
+
+    def test_dst_follows_zone(self) -> None:
+        """Test a named zone applies the offset in force on each date"""
+        chicago = zoneinfo.ZoneInfo('America/Chicago')
+
+        winter = localize(datetime(2024, 1, 15, 12), chicago)
+        summer = localize(datetime(2024, 7, 15, 12), chicago)
+
+        assert winter.utcoffset() == timedelta(hours=-6)
+        assert summer.utcoffset() == timedelta(hours=-5)
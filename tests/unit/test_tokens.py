@@ -0,0 +1,155 @@
+"""ABOUTME: Test API token storage, expiry, revocation and persistence
+ABOUTME: Covers the TokenStore used for admin and read token rotation"""
+
+import json
+import time
+from pathlib import Path
+
+import pytest
+
+from tokens import TokenStore, hash_token, parse_api_keys
+
+
+# Test constants
+SHORT_EXPIRY = 60
+DASHBOARD_KEY = 'dashboard-secret'
+DEPLOY_KEY = 'deploy-secret'
+
+
+class TestTokenStore:
+    """Test concurrently valid tokens with expiry and revocation"""
+
+    @pytest.fixture
+    def store(self, tmp_path: Path) -> TokenStore:
+        """Create a token store backed by a temporary file"""
+        return TokenStore(str(tmp_path / 'data' / 'tokens.json'))
+
+    def test_create_and_verify(self, store: TokenStore) -> None:
+        """Test a new token verifies for its scope and admin covers read"""
+        admin, admin_secret = store.create('laptop', 'admin')
+        read, read_secret = store.create('kiosk', 'read')
+
+        assert 'hash' not in admin
+        assert store.verify(admin_secret, 'admin') is not None
+        assert store.verify(admin_secret, 'read') is not None
+        assert store.verify(read_secret, 'read')['id'] == read['id']
+        assert store.verify(read_secret, 'admin') is None
+        assert store.verify('not-a-token', 'read') is None
+
+    def test_rotation_keeps_both_tokens_valid(self, store: TokenStore) -> None:
+        """Test old and new tokens overlap until the old one is revoked"""
+        old, old_secret = store.create('kiosk', 'read')
+        _, new_secret = store.create('kiosk (rotated)', 'read')
+
+        assert store.verify(old_secret, 'read') is not None
+        assert store.verify(new_secret, 'read') is not None
+
+        assert store.revoke(old['id'])
+        assert store.verify(old_secret, 'read') is None
+        assert store.verify(new_secret, 'read') is not None
+        assert not store.revoke('missing')
+
+    def test_expired_tokens_are_rejected(self, store: TokenStore) -> None:
+        """Test tokens stop working once they expire"""
+        record, secret = store.create('temporary', 'admin', expires_in=SHORT_EXPIRY)
+        assert record['expires_at'] == pytest.approx(time.time() + SHORT_EXPIRY, abs=5)
+        assert store.has_active('admin')
+
+        store.tokens[record['id']]['expires_at'] = time.time() - 1
+
+        assert store.verify(secret, 'admin') is None
+        assert not store.has_active('admin')
+
+    def test_tokens_persist_without_secrets(self, store: TokenStore) -> None:
+        """Test tokens survive a reload and only hashes reach the disk"""
+        record, secret = store.create('kiosk', 'read')
+
+        saved = Path(store.path).read_text(encoding='utf-8')
+        assert secret not in saved
+        assert json.loads(saved)[0]['id'] == record['id']
+
+        reloaded = TokenStore(store.path)
+        assert reloaded.verify(secret, 'read') is not None
+
+    def test_unknown_scope(self, store: TokenStore) -> None:
+        """Test creating a token with an unknown scope fails"""
+        with pytest.raises(ValueError):
+            store.create('bad', 'superuser')
+
+
+class TestConfiguredKeys:
+    """Test API keys set in config alongside stored tokens"""
+
+    def test_parse_keys_and_hashes(self) -> None:
+        """Test clear keys are hashed and pre-hashed keys kept, scope defaulting"""
+        records = parse_api_keys(
+            json.dumps(
+                [
+                    {'name': 'dashboard', 'key': DASHBOARD_KEY},
+                    {
+                        'name': 'deploy',
+                        'scope': 'admin',
+                        'key_sha256': hash_token(DEPLOY_KEY).upper(),
+                    },
+                ]
+            )
+        )
+
+        assert [(r['id'], r['scope']) for r in records] == [
+            ('config:dashboard', 'read'),
+            ('config:deploy', 'admin'),
+        ]
+        assert records[0]['hash'] == hash_token(DASHBOARD_KEY)
+        assert records[1]['hash'] == hash_token(DEPLOY_KEY)
+        assert parse_api_keys('  ') == []
+
+    def test_invalid_keys(self) -> None:
+        """Test bad scopes, missing secrets, duplicates and non-lists fail"""
+        for raw in (
+            '{"name": "dashboard"}',
+            '[{"key": "x"}]',
+            '[{"name": "dashboard", "key": "x", "scope": "root"}]',
+            '[{"name": "dashboard"}]',
+            '[{"name": "dashboard", "key_sha256": "abc"}]',
+            '[{"name": "a", "key": "x"}, {"name": "a", "key": "y"}]',
+        ):
+            with pytest.raises(ValueError):
+                parse_api_keys(raw)
+
+    def test_configured_keys_verify(self, tmp_path: Path) -> None:
+        """Test configured keys work by scope, are listed but never saved"""
+        store = TokenStore(str(tmp_path / 'tokens.json'))
+        store.configure(
+            parse_api_keys(
+                json.dumps(
+                    [
+                        {'name': 'dashboard', 'key': DASHBOARD_KEY},
+                        {'name': 'deploy', 'scope': 'admin', 'key': DEPLOY_KEY},
+                    ]
+                )
+            )
+        )
+        store.create('kiosk', 'read')
+
+        assert store.verify(DASHBOARD_KEY, 'read')['id'] == 'config:dashboard'
+        assert store.verify(DASHBOARD_KEY, 'admin') is None
+        assert store.verify(DEPLOY_KEY, 'admin') is not None
+        assert store.has_active('admin')
+        assert [t['id'] for t in store.list_tokens()][:2] == [
+            'config:dashboard',
+            'config:deploy',
+        ]
+        assert 'config:' not in Path(store.path).read_text(encoding='utf-8')
+
+        store.configure([])
+        assert store.verify(DEPLOY_KEY, 'admin') is None
+        assert not store.has_active('admin')
+
+    def test_configured_keys_not_revocable(self, tmp_path: Path) -> None:
+        """Test a configured key can only be removed from the config"""
+        store = TokenStore(str(tmp_path / 'tokens.json'))
+        store.configure(parse_api_keys('[{"name": "dashboard", "key": "x"}]'))
+
+        with pytest.raises(ValueError, match='API_KEYS'):
+            store.revoke('config:dashboard')
+        assert store.verify('x', 'read') is not None
@@ -0,0 +1,251 @@
+"""ABOUTME: Test spans, trace context and the OTLP/HTTP JSON exporter
+ABOUTME: The collector is a patched requests.post, so nothing leaves the machine"""
+
+import threading
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+import requests
+
+from weather_core import http_client
+from weather_core.tracing import (
+    SPAN_KIND_CLIENT,
+    STATUS_ERROR,
+    OtlpExporter,
+    Tracer,
+    load_otlp_exporter,
+)
+
+
+# Test constants
+COLLECTOR = 'http://collector:4318/v1/traces'
+TRACE_ID = '4bf92f3577b34da6a3ce929d0e0e4736'
+PARENT_ID = '00f067aa0ba902b7'
+HTTP_OK = 200
+HTTP_SERVICE_UNAVAILABLE = 503
+BATCH_SIZE = 2
+SPAN_COUNT = 3
+
+
+def recording_tracer() -> tuple[Tracer, OtlpExporter]:
+    """A tracer exporting into a buffer that is never sent"""
+    exporter = OtlpExporter(COLLECTOR, batch_size=BATCH_SIZE)
+    return Tracer(exporter), exporter
+
+
+class TestTracer:
+    """Test span nesting and trace context"""
+
+    def test_nested_spans_share_a_trace(self) -> None:
+        """Test a span opened inside another is its child, exported first"""
+        tracer, exporter = recording_tracer()
+
+        with tracer.span('outer') as outer, tracer.span('inner') as inner:
+            assert tracer.current() is inner
+
+        assert tracer.current() is None
+        assert list(exporter.buffer) == [inner, outer]
+        assert inner.trace_id == outer.trace_id
+        assert inner.parent_id == outer.span_id
+        assert outer.parent_id is None
+        assert outer.end_ns is not None
+        assert outer.end_ns >= outer.start_ns
+
+    def test_exception_marks_span_failed(self) -> None:
+        """Test a block that raises leaves an error status and re-raises"""
+        tracer, exporter = recording_tracer()
+
+        with pytest.raises(ValueError), tracer.span('parse'):
+            raise ValueError('bad payload')
+
+        assert exporter.buffer[0].error == 'ValueError: bad payload'
+        assert exporter.buffer[0].to_otlp()['status']['code'] == STATUS_ERROR
+
+    def test_traceparent_joins_callers_trace(self) -> None:
+        """Test a root span continues a well-formed W3C traceparent"""
+        tracer, _ = recording_tracer()
+
+        traceparent = f'00-{TRACE_ID}-{PARENT_ID}-01'
+
+        joined = tracer.start('GET /api/weather', traceparent=traceparent)
+        tracer.finish(joined)
+        fresh = tracer.start('GET /api/weather', traceparent='garbage')
+        tracer.finish(fresh)
+
+        assert (joined.trace_id, joined.parent_id) == (TRACE_ID, PARENT_ID)
+        assert fresh.trace_id != TRACE_ID
+        assert fresh.parent_id is None
+
+    def test_finish_closes_children_left_open(self) -> None:
+        """Test finishing a span drops any of its children still on the stack"""
+        tracer, _ = recording_tracer()
+        request = tracer.start('request')
+        tracer.start('forgotten')
+
+        tracer.finish(request)
+
+        assert tracer.current() is None
+
+    def test_wrap_carries_parent_to_other_threads(self) -> None:
+        """Test work handed to a thread pool stays in the caller's trace"""
+        tracer, exporter = recording_tracer()
+
+        def work() -> None:
+            with tracer.span('provider'):
+                pass
+
+        with tracer.span('request') as request:
+            thread = threading.Thread(target=tracer.wrap(work))
+            thread.start()
+            thread.join()
+
+        provider = exporter.buffer[0]
+        assert provider.name == 'provider'
+        assert provider.parent_id == request.span_id
+        assert provider.trace_id == request.trace_id
+
+    def test_no_exporter_keeps_nothing(self) -> None:
+        """Test spans are only timed while tracing isn't configured"""
+        tracer = Tracer()
+
+        with tracer.span('cache.get') as span:
+            span.set_attribute('cache.hit', True)
+
+        assert tracer.current() is None
+
+
+class TestOtlpExporter:
+    """Test the OTLP JSON payload and batching"""
+
+    def test_payload_shape(self) -> None:
+        """Test spans are grouped under the service resource with typed attributes"""
+        tracer, exporter = recording_tracer()
+        exporter.service_name = 'weather-test'
+        attributes = {'http.route': '/api/weather', 'count': 2, 'hit': False}
+        with tracer.span('GET /api/weather', {**attributes, 'ratio': 0.5}):
+            pass
+
+        payload = exporter.payload(list(exporter.buffer))
+
+        resource_spans = payload['resourceSpans'][0]
+        assert resource_spans['resource']['attributes'] == [
+            {'key': 'service.name', 'value': {'stringValue': 'weather-test'}}
+        ]
+        span = resource_spans['scopeSpans'][0]['spans'][0]
+        assert len(span['traceId']) == len(TRACE_ID)
+        assert len(span['spanId']) == len(PARENT_ID)
+        assert 'parentSpanId' not in span
+        assert 'status' not in span
+        assert span['attributes'] == [
+            {'key': 'http.route', 'value': {'stringValue': '/api/weather'}},
+            {'key': 'count', 'value': {'intValue': '2'}},
+            {'key': 'hit', 'value': {'boolValue': False}},
+            {'key': 'ratio', 'value': {'doubleValue': 0.5}},
+        ]
+
+    @patch('weather_core.tracing.requests.post')
+    def test_flush_in_batches(self, mock_post: MagicMock) -> None:
+        """Test queued spans go out in batches and the buffer empties"""
+        mock_post.return_value = MagicMock(ok=True, status_code=HTTP_OK)
+        tracer, exporter = recording_tracer()
+        exporter.headers = {'Authorization': 'Bearer t'}
+        for index in range(SPAN_COUNT):
+            with tracer.span(f'span {index}'):
+                pass
+
+        assert exporter.flush()
+
+        assert mock_post.call_count == 2  # noqa: PLR2004
+        assert mock_post.call_args.args[0] == COLLECTOR
+        assert mock_post.call_args.kwargs['headers'] == {'Authorization': 'Bearer t'}
+        assert not exporter.buffer
+
+    @patch('weather_core.tracing.requests.post')
+    def test_failed_export_keeps_spans(self, mock_post: MagicMock) -> None:
+        """Test spans stay queued while the collector is down"""
+        tracer, exporter = recording_tracer()
+        with tracer.span('kept'):
+            pass
+
+        mock_post.return_value = MagicMock(
+            ok=False, status_code=HTTP_SERVICE_UNAVAILABLE, text='down'
+        )
+        assert not exporter.flush()
+        mock_post.side_effect = requests.exceptions.ConnectionError('refused')
+        assert not exporter.flush()
+
+        assert len(exporter.buffer) == 1
+
+
+class TestLoadExporter:
+    """Test configuration from the standard OTEL_* variables"""
+
+    def test_off_without_endpoint(self) -> None:
+        """Test tracing stays off unless an endpoint is set, or when disabled"""
+        assert load_otlp_exporter({}) is None
+        assert (
+            load_otlp_exporter(
+                {
+                    'OTEL_EXPORTER_OTLP_ENDPOINT': 'http://collector:4318',
+                    'OTEL_TRACES_EXPORTER': 'none',
+                }
+            )
+            is None
+        )
+
+    def test_base_endpoint_and_headers(self) -> None:
+        """Test the base endpoint gets /v1/traces and headers are URL-decoded"""
+        exporter = load_otlp_exporter(
+            {
+                'OTEL_EXPORTER_OTLP_ENDPOINT': 'http://collector:4318/',
+                'OTEL_EXPORTER_OTLP_HEADERS': (
+                    'x-scope-orgid=home,authorization=Basic%20YQ=='
+                ),
+                'OTEL_SERVICE_NAME': 'weather-kitchen',
+            }
+        )
+
+        assert exporter is not None
+        assert exporter.endpoint == COLLECTOR
+        assert exporter.headers == {
+            'x-scope-orgid': 'home',
+            'authorization': 'Basic YQ==',
+        }
+        assert exporter.service_name == 'weather-kitchen'
+
+    def test_traces_endpoint_used_as_is(self) -> None:
+        """Test the traces-specific endpoint is a full URL and wins"""
+        exporter = load_otlp_exporter(
+            {
+                'OTEL_EXPORTER_OTLP_ENDPOINT': 'http://other:4318',
+                'OTEL_EXPORTER_OTLP_TRACES_ENDPOINT': 'https://tempo.example/otlp',
+            }
+        )
+
+        assert exporter is not None
+        assert exporter.endpoint == 'https://tempo.example/otlp'
+        assert exporter.service_name == 'weather-dashboard'
+
+
+class TestHttpClientSpans:
+    """Test provider HTTP calls are traced without leaking keys"""
+
+    @patch('weather_core.http_client.requests.get')
+    def test_get_records_host_and_status(self, mock_get: MagicMock) -> None:
+        """Test the span names the host only, since keys can be in the path"""
+        mock_get.return_value = MagicMock(status_code=HTTP_OK)
+        tracer, exporter = recording_tracer()
+
+        with patch('weather_core.http_client.tracer', tracer):
+            http_client.get('https://api.pirateweather.net/forecast/SECRET/41,-87')
+
+        span: Any = exporter.buffer[0]
+        assert span.kind == SPAN_KIND_CLIENT
+        assert span.attributes == {
+            'http.request.method': 'GET',
+            'server.address': 'api.pirateweather.net',
+            'http.request.resend_count': 0,
+            'http.response.status_code': HTTP_OK,
+        }
+        assert 'SECRET' not in str(span.to_otlp())
@@ -0,0 +1,178 @@
+"""ABOUTME: Test what lets weather-core run under WebAssembly - the inline executor
+ABOUTME: and the HTTP transports, with a fake js module standing in for Pyodide"""
+
+import sys
+from types import ModuleType, SimpleNamespace
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+import requests
+
+from weather_core import geocoding, http_client
+from weather_core.runtime import InlineExecutor, make_executor
+from weather_core.transport import (
+    RequestsTransport,
+    XhrTransport,
+    get_transport,
+    parse_header_lines,
+    set_transport,
+)
+
+
+# Test constants
+URL = 'https://api.example.com/forecast'
+BODY = '{"temperature": 72}'
+RAW_HEADERS = 'content-type: application/json\r\nx-ratelimit-remaining: 99\r\n'
+
+
+class FakeXhr:
+    """Just enough of XMLHttpRequest for the transport, recording what it's asked"""
+
+    sent: list['FakeXhr'] = []
+
+    def __init__(self, status: int = 200, fail: bool = False) -> None:
+        self.status = status
+        self.statusText = 'OK' if status == 200 else 'Error'  # noqa: N815, PLR2004
+        self.responseText = BODY  # noqa: N815
+        self.fail = fail
+        self.opened: tuple[str, str, bool] | None = None
+        self.headers: dict[str, str] = {}
+
+    def open(self, method: str, url: str, is_async: bool) -> None:
+        self.opened = (method, url, is_async)
+
+    def setRequestHeader(self, name: str, value: str) -> None:  # noqa: N802
+        self.headers[name] = value
+
+    def send(self) -> None:
+        if self.fail:
+            raise RuntimeError('NetworkError: A network error occurred.')
+        FakeXhr.sent.append(self)
+
+    def getAllResponseHeaders(self) -> str:  # noqa: N802
+        return RAW_HEADERS
+
+
+def fake_js(**xhr_kwargs: Any) -> ModuleType:
+    """A js module whose XMLHttpRequest.new() returns a FakeXhr"""
+    module = ModuleType('js')
+    module.XMLHttpRequest = SimpleNamespace(  # type: ignore[attr-defined]
+        new=lambda: FakeXhr(**xhr_kwargs)
+    )
+    return module
+
+
+class TestInlineExecutor:
+    """Test the executor used where threads can't start"""
+
+    def test_runs_on_submit(self) -> None:
+        """Test calls run immediately and their futures are already done"""
+        calls = []
+        executor = InlineExecutor()
+
+        future = executor.submit(lambda x: calls.append(x) or x * 2, 21)
+
+        assert calls == [21]
+        assert future.done()
+        assert future.result() == 42  # noqa: PLR2004
+
+    def test_exceptions_kept_in_future(self) -> None:
+        """Test a failing call raises from result(), not from submit()"""
+
+        def fail() -> None:
+            raise ValueError('no data')
+
+        future = InlineExecutor().submit(fail)
+
+        assert isinstance(future.exception(), ValueError)
+
+    def test_picked_by_runtime(self) -> None:
+        """Test make_executor only falls back where threads are unavailable"""
+        with patch('weather_core.runtime.CAN_THREAD', False):
+            assert isinstance(make_executor(4), InlineExecutor)
+        with make_executor(4) as executor:
+            assert not isinstance(executor, InlineExecutor)
+
+
+class TestXhrTransport:
+    """Test requests made through XMLHttpRequest under Pyodide"""
+
+    def setup_method(self) -> None:
+        """Forget earlier requests"""
+        FakeXhr.sent = []
+
+    def test_builds_response(self) -> None:
+        """Test the reply works like one from requests"""
+        with patch.dict(sys.modules, {'js': fake_js()}):
+            response = XhrTransport().get(
+                URL, params={'lat': 41.88, 'units': 'us'}, headers={'X-Key': 'k'}
+            )
+
+        sent = FakeXhr.sent[0]
+        assert sent.opened == ('GET', f'{URL}?lat=41.88&units=us', False)
+        assert sent.headers == {'X-Key': 'k'}
+        assert response.status_code == 200  # noqa: PLR2004
+        assert response.json() == {'temperature': 72}
+        assert response.headers['Content-Type'] == 'application/json'
+        assert response.url == f'{URL}?lat=41.88&units=us'
+
+    def test_error_status_raises_like_requests(self) -> None:
+        """Test HTTP errors come back as replies for callers to check"""
+        with patch.dict(sys.modules, {'js': fake_js(status=503)}):
+            response = XhrTransport().get(URL)
+
+        with pytest.raises(requests.HTTPError):
+            response.raise_for_status()
+
+    def test_network_failures(self) -> None:
+        """Test failed sends and blocked requests become ConnectionError"""
+        with patch.dict(sys.modules, {'js': fake_js(fail=True)}):
+            with pytest.raises(requests.ConnectionError, match='NetworkError'):
+                XhrTransport().get(URL)
+        with patch.dict(sys.modules, {'js': fake_js(status=0)}):
+            with pytest.raises(requests.ConnectionError, match='CORS'):
+                XhrTransport().get(URL)
+
+    def test_header_lines(self) -> None:
+        """Test header names are case-insensitive and values may hold colons"""
+        headers = parse_header_lines('Date: Tue, 01 Oct 2024 10:00:00 GMT\r\n\r\n')
+
+        assert headers['date'] == 'Tue, 01 Oct 2024 10:00:00 GMT'
+        assert len(headers) == 1
+
+
+class TestSetTransport:
+    """Test provider requests go through the configured transport"""
+
+    def setup_method(self) -> None:
+        """Remember the transport to put back"""
+        self.original = get_transport()
+
+    def teardown_method(self) -> None:
+        """Put the original transport back"""
+        set_transport(self.original)
+
+    def test_default_is_requests(self) -> None:
+        """Test CPython uses requests, so patching requests.get still works"""
+        assert isinstance(self.original, RequestsTransport)
+        with patch('requests.get', return_value=MagicMock(status_code=200)) as get:
+            http_client.get(URL, timeout=5)
+
+        get.assert_called_once_with(URL, timeout=5)
+
+    def test_http_client_and_geocoding_use_it(self) -> None:
+        """Test a replacement transport sees provider and geocoding requests"""
+        transport = MagicMock()
+        transport.get.return_value = MagicMock(
+            status_code=200,
+            json=lambda: {'results': [{'latitude': 1.0, 'longitude': 2.0}]},
+        )
+        set_transport(transport)
+
+        http_client.get(URL)
+        match = geocoding.geocode_place('Transport Test Town')
+
+        assert transport.get.call_count == 2  # noqa: PLR2004
+        assert transport.get.call_args_list[0].args == (URL,)
+        assert match == (1.0, 2.0, 'Transport Test Town')
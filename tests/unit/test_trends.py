@@ -0,0 +1,137 @@
+"""ABOUTME: Test rolling statistics over stored observations - rolling means, rates of
+ABOUTME: change, anomaly detection and the chart-ready shape of the trends response"""
+
+from trends import (
+    TREND_FIELDS,
+    build_trends,
+    field_stats,
+    find_anomalies,
+    rate_per_hour,
+    rolling_mean,
+)
+
+
+# Test constants
+HOUR = 3600
+START = 1_700_006_400  # 2023-11-15T00:00:00Z
+THREE_HOURS = 3 * HOUR
+SPIKE_HOUR = 30
+SPIKE_TEMPERATURE = 95.0
+
+
+def hourly(count: int) -> list[int]:
+    """Hour starts from START"""
+    return [START + hour * HOUR for hour in range(count)]
+
+
+def swing(count: int) -> list[float | None]:
+    """Temperatures rising and falling 10 degrees every twelve hours"""
+    cycle = [0, 2, 4, 6, 8, 10, 10, 8, 6, 4, 2, 0]
+    return [60.0 + cycle[hour % len(cycle)] for hour in range(count)]
+
+
+class TestRollingStatistics:
+    """Test the per-field building blocks"""
+
+    def test_rolling_mean_over_span(self) -> None:
+        """Test each point averages the readings within the span ending at it"""
+        timestamps = hourly(4)
+        values: list[float | None] = [10.0, 20.0, None, 40.0]
+
+        averages = rolling_mean(timestamps, values, THREE_HOURS)
+
+        assert averages == [10.0, 15.0, 15.0, 30.0]
+
+    def test_rate_per_hour_uses_latest_hours(self) -> None:
+        """Test the slope covers only the tendency period, ignoring older readings"""
+        timestamps = hourly(6)
+        values: list[float | None] = [0.0, 50.0, 100.0, 30.0, 29.9, 29.8]
+
+        assert rate_per_hour(timestamps, values) == -0.1
+
+    def test_rate_needs_two_readings(self) -> None:
+        """Test there's no rate from a single reading or none"""
+        assert rate_per_hour([], []) is None
+        assert rate_per_hour(hourly(2), [None, 30.0]) is None
+
+    def test_field_stats(self) -> None:
+        """Test range, mean and change skip missing readings"""
+        stats = field_stats(hourly(4), [None, 50.0, 60.0, 55.0], '°F')
+
+        assert stats['min'] == 50.0  # noqa: PLR2004
+        assert stats['max'] == 60.0  # noqa: PLR2004
+        assert stats['mean'] == 55.0  # noqa: PLR2004
+        assert stats['change'] == 5.0  # noqa: PLR2004
+        assert stats['unit'] == '°F'
+
+    def test_field_stats_without_readings(self) -> None:
+        """Test a field no provider reports has empty statistics"""
+        stats = field_stats(hourly(2), [None, None], 'mph')
+
+        assert stats['min'] is None
+        assert stats['rate_per_hour'] is None
+
+
+class TestAnomalies:
+    """Test sudden jumps stand out from ordinary change"""
+
+    def test_steady_swing_not_flagged(self) -> None:
+        """Test a regular daily cycle has no anomalies"""
+        timestamps = hourly(48)
+        values = swing(48)
+
+        assert find_anomalies(timestamps, values, THREE_HOURS) == []
+
+    def test_spike_flagged(self) -> None:
+        """Test one reading far from those before it is reported"""
+        timestamps = hourly(48)
+        values = swing(48)
+        values[SPIKE_HOUR] = SPIKE_TEMPERATURE
+
+        anomalies = find_anomalies(timestamps, values, THREE_HOURS)
+
+        assert [anomaly['value'] for anomaly in anomalies] == [SPIKE_TEMPERATURE]
+        assert anomalies[0]['time'] == '2023-11-16T06:00:00+00:00'
+        assert anomalies[0]['sigmas'] > 3  # noqa: PLR2004
+
+    def test_flat_readings_have_no_anomalies(self) -> None:
+        """Test identical readings don't divide by zero"""
+        timestamps = hourly(12)
+
+        assert find_anomalies(timestamps, [30.0] * 12, THREE_HOURS) == []
+
+
+class TestBuildTrends:
+    """Test the response shape"""
+
+    def test_chart_ready_series(self) -> None:
+        """Test labels, series and both chart formats line up"""
+        hours = {
+            stamp: {'temperature': 50.0 + index, 'pressure': 1015.0}
+            for index, stamp in enumerate(hourly(5))
+        }
+
+        trends = build_trends(hours, '24h')
+
+        assert trends['window'] == '24h'
+        assert trends['timestamps'] == hourly(5)
+        assert trends['labels'][0] == '2023-11-15T00:00:00+00:00'
+        assert trends['series']['temperature'] == [50.0, 51.0, 52.0, 53.0, 54.0]
+        assert trends['series']['humidity'] == [None] * 5
+        assert trends['stats']['temperature']['rate_per_hour'] == 1.0
+        assert trends['stats']['pressure']['change'] == 0.0
+        assert trends['stats']['pressure']['unit'] == 'hPa'
+        assert len(trends['chartjs']['datasets']) == 2 * len(TREND_FIELDS)
+        assert trends['chartjs']['labels'] == trends['labels']
+        uplot = trends['uplot']
+        assert len(uplot['data']) == len(uplot['series'])
+        assert uplot['data'][0] == hourly(5)
+        assert uplot['data'][1] == trends['series']['temperature']
+
+    def test_empty_window(self) -> None:
+        """Test a location with no stored observations still gets a shape"""
+        trends = build_trends({}, '7d')
+
+        assert trends['labels'] == []
+        assert trends['anomalies']['temperature'] == []
+        assert trends['stats']['temperature']['mean'] is None
@@ -0,0 +1,146 @@
+"""ABOUTME: Test the standalone weather command - place lookup, tables and JSON, units
+ABOUTME: and provider choice. Geocoding and providers are patched, so nothing leaves"""
+
+import json
+from pathlib import Path
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+
+from weather_core.cli import EXIT_FAILED, EXIT_OK, EXIT_USAGE, main, render_table
+
+
+# Test constants
+CHICAGO = (41.8781, -87.6298, 'Chicago')
+WEATHER: dict[str, Any] = {
+    'current': {
+        'temperature': 72,
+        'feels_like': 75,
+        'humidity': 65,
+        'wind_speed': 8,
+        'wind_direction': 225,
+        'uv_index': 6,
+        'pressure': 1013.2,
+        'icon': 'partly-cloudy-day',
+        'summary': 'Partly cloudy',
+    },
+    'hourly': [
+        {'t': f'{hour}pm', 'temp': 70 + hour, 'rain': 10, 'desc': 'Cloudy'}
+        for hour in range(1, 6)
+    ],
+    'daily': [{'d': 'Mon', 'h': 80, 'l': 60, 'rain': 20, 'icon': 'rain'}],
+    'location': 'Chicago',
+    'provider': 'OpenMeteo',
+}
+
+
+def environment(tmp_path: Path) -> dict[str, str]:
+    """An environment with no providers file, so nothing on disk leaks in"""
+    return {'PROVIDERS_CONFIG_PATH': str(tmp_path / 'absent.json')}
+
+
+class TestWeatherCommand:
+    """Test the weather command from arguments to output"""
+
+    @patch('weather_core.cli.geocode_place', return_value=CHICAGO)
+    @patch('weather_core.providers.OpenMeteoProvider.get_weather')
+    def test_city_table(
+        self,
+        mock_weather: MagicMock,
+        mock_geocode: MagicMock,
+        tmp_path: Path,
+        capsys: pytest.CaptureFixture[str],
+    ) -> None:
+        """Test a city is looked up and shown as tables, with progress hidden"""
+        mock_weather.return_value = WEATHER
+
+        code = main(['--city', 'chicago', '--hours', '2'], environment(tmp_path))
+
+        assert code == EXIT_OK
+        mock_geocode.assert_called_once_with('chicago')
+        mock_weather.assert_called_once_with(41.8781, -87.6298, 'Chicago', None)
+        captured = capsys.readouterr()
+        assert captured.out.startswith('Chicago (OpenMeteo)\nNow: 72°F, Partly cloudy')
+        assert 'Wind 8 mph SW' in captured.out
+        assert '2pm   72°F' in captured.out
+        assert '3pm' not in captured.out
+        assert 'Mon  80°F  60°F  20%   rain' in captured.out
+        assert captured.err == ''
+
+    @patch('weather_core.providers.OpenMeteoProvider.get_weather')
+    def test_metric_json(
+        self,
+        mock_weather: MagicMock,
+        tmp_path: Path,
+        capsys: pytest.CaptureFixture[str],
+    ) -> None:
+        """Test JSON output is converted and labelled, progress going to stderr"""
+        mock_weather.return_value = WEATHER
+
+        code = main(
+            [
+                *('--lat', '41.8781', '--lon', '-87.6298'),
+                *('--units', 'metric', '--format', 'json', '--verbose'),
+            ],
+            environment(tmp_path),
+        )
+
+        assert code == EXIT_OK
+        captured = capsys.readouterr()
+        body = json.loads(captured.out)
+        assert body['current']['temperature'] == 22  # noqa: PLR2004
+        assert body['units']['temperature'] == '°C'
+        assert mock_weather.call_args.args[2] == '41.8781,-87.6298'
+        assert 'OpenMeteo' in captured.err
+
+    @patch('weather_core.cli.geocode_place', return_value=None)
+    def test_unknown_city(
+        self,
+        mock_geocode: MagicMock,
+        tmp_path: Path,
+        capsys: pytest.CaptureFixture[str],
+    ) -> None:
+        """Test a place the geocoder doesn't know fails without output"""
+        assert main(['--city', 'Atlantis'], environment(tmp_path)) == EXIT_FAILED
+        captured = capsys.readouterr()
+        assert captured.out == ''
+        assert "No place called 'Atlantis'" in captured.err
+
+    @patch('weather_core.cli.geocode_place', return_value=CHICAGO)
+    def test_unknown_provider(
+        self,
+        mock_geocode: MagicMock,
+        tmp_path: Path,
+        capsys: pytest.CaptureFixture[str],
+    ) -> None:
+        """Test asking for a provider that isn't configured is a usage error"""
+        code = main(['--city', 'Chicago', '--provider', 'Nope'], environment(tmp_path))
+
+        assert code == EXIT_USAGE
+        assert 'have: OpenMeteo' in capsys.readouterr().err
+
+    def test_place_required(self) -> None:
+        """Test a latitude needs a longitude and a city can't have either"""
+        with pytest.raises(SystemExit):
+            main(['--lat', '41.8'], {})
+        with pytest.raises(SystemExit):
+            main(['--city', 'Chicago', '--lon', '-87.6'], {})
+        with pytest.raises(SystemExit):
+            main(['--lat', '91', '--lon', '0'], {})
+
+
+class TestRenderTable:
+    """Test the terminal layout"""
+
+    def test_missing_readings_dashed(self) -> None:
+        """Test readings a provider left out show as dashes"""
+        text = render_table(
+            {'current': {'temperature': 50}, 'location': 'Nowhere'}, 'imperial', 12
+        )
+
+        assert text.splitlines() == [
+            'Nowhere (unknown)',
+            'Now: 50°F, -, feels like -',
+            'Humidity -  Wind -  UV -  Pressure -',
+        ]
@@ -1,14 +1,28 @@
+import time
+import zoneinfo
+from concurrent.futures import ThreadPoolExecutor
+from datetime import datetime, timedelta, timezone
 from typing import Any
 from unittest.mock import MagicMock, patch
 
 import pytest
 import requests
 
-from weather_providers import (
+from weather_core.http_client import ProviderQuota
+from weather_core.providers import (
+    MOCK_SCENARIOS,
+    BlendProvider,
+    HybridWeatherProvider,
+    MockProvider,
     OpenMeteoProvider,
+    PirateWeatherProvider,
     WeatherProvider,
     WeatherProviderManager,
+    build_provider,
+    extract_weather_section,
+    upcoming_hours,
 )
+from weather_core.settings import ProviderSettings
 
 
 # Test constants
@@ -20,6 +34,17 @@ MOCK_UV_INDEX = 6
 PROVIDER_TIMEOUT = 10
 CHICAGO_LAT = 41.8781
 CHICAGO_LON = -87.6298
+SLOW_SECONDS = 0.5
+TEST_BUDGET = 0.3
+TEST_HEDGE_DELAY = 0.05
+DAILY_HIGH = 77
+CONCURRENT_CALLERS = 20
+OPEN_METEO_CACHE_TTL = 900
+PIRATE_WEATHER_CACHE_TTL = 300
+BLEND_MEDIAN_TEMP = 72
+BLEND_MEAN_TEMP = 74
+BLEND_PAIR_TEMP = 75
+BLEND_BEARING = 7
 
 
 class TestWeatherProvider:
@@ -124,6 +149,68 @@ class TestOpenMeteoProvider:
         assert current['icon'] == 'clear-day'
         assert current['summary'] == 'Clear sky'
 
+    def test_precipitation_timing_from_minutely(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test local 15-minute slots give minutes until rain starts and ends"""
+        offset = timedelta(hours=-6)
+        local_now = datetime.now(timezone.utc) + offset
+        slot = local_now.replace(
+            minute=local_now.minute - local_now.minute % 15, second=0, microsecond=0
+        )
+        times = [
+            (slot + timedelta(minutes=15 * index)).strftime('%Y-%m-%dT%H:%M')
+            for index in range(6)
+        ]
+        raw_data = {
+            **mock_open_meteo_response,
+            'utc_offset_seconds': int(offset.total_seconds()),
+            'minutely_15': {
+                'time': times,
+                'precipitation': [0, 0, 0.02, 0.05, 0, 0],
+            },
+        }
+
+        result = OpenMeteoProvider().process_weather_data(raw_data, 'Chicago')
+
+        assert result is not None
+        current = result['current']
+        # Rain starts two slots after the one we're in: 15-30 minutes away
+        assert 15 <= current['precip_starts_in_minutes'] <= 30  # noqa: PLR2004
+        assert (
+            current['precip_ends_in_minutes'] - current['precip_starts_in_minutes']
+            == 30  # noqa: PLR2004
+        )
+
+    def test_hourly_in_location_timezone(
+        self, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test the hourly strip starts at the location's hour, not the server's"""
+        tokyo = zoneinfo.ZoneInfo('Asia/Tokyo')
+        midnight = datetime.now(tokyo).replace(
+            hour=0, minute=0, second=0, microsecond=0
+        )
+        times = [
+            (midnight + timedelta(hours=hour)).strftime('%Y-%m-%dT%H:%M')
+            for hour in range(48)
+        ]
+        raw_data = {
+            **mock_open_meteo_response,
+            'timezone': 'Asia/Tokyo',
+            'utc_offset_seconds': 9 * 3600,
+            'hourly': {
+                'time': times,
+                'temperature_2m': [70.0] * 48,
+                'weather_code': [0] * 48,
+            },
+        }
+
+        result = OpenMeteoProvider().process_weather_data(raw_data, 'Tokyo')
+
+        assert result is not None
+        this_hour = datetime.now(tokyo).strftime('%Y-%m-%dT%H:00')
+        assert result['hourly'][0]['time'] == this_hour
+
     def test_process_weather_data_empty(self) -> None:
         """Test processing with empty data"""
         provider = OpenMeteoProvider()
@@ -167,6 +254,32 @@ class TestOpenMeteoProvider:
         assert provider._get_weather_description(999) == 'Unknown'
 
 
+class TestUpcomingHours:
+    """Test which slots of an hourly series are the forecast from now"""
+
+    def setup_method(self) -> None:
+        """A day of hours from local midnight, as Open-Meteo sends them"""
+        self.tokyo = zoneinfo.ZoneInfo('Asia/Tokyo')
+        midnight = datetime(2024, 6, 1, tzinfo=self.tokyo)
+        self.moments = [midnight + timedelta(hours=hour) for hour in range(24)]
+
+    def test_starts_at_current_hour(self) -> None:
+        """Test at 9:40pm the series starts at 9pm, and stops at its end"""
+        now = datetime(2024, 6, 1, 21, 40, tzinfo=self.tokyo)
+
+        assert upcoming_hours(self.moments, now, 24) == range(21, 24)
+        assert upcoming_hours(self.moments, now, 2) == range(21, 23)
+
+    def test_hour_boundary_is_local(self) -> None:
+        """Test half-hour zones start at their own hour, not UTC's"""
+        kolkata = zoneinfo.ZoneInfo('Asia/Kolkata')
+        midnight = datetime(2024, 6, 1, tzinfo=kolkata)
+        moments = [midnight + timedelta(hours=hour) for hour in range(24)]
+        now = datetime(2024, 6, 1, 6, 15, tzinfo=timezone.utc)  # 11:45 in Kolkata
+
+        assert upcoming_hours(moments, now.astimezone(kolkata), 3) == range(11, 14)
+
+
 class TestWeatherProviderManager:
     """Test the WeatherProviderManager"""
 
@@ -427,3 +540,770 @@ class TestWeatherProviderManager:
 
         assert result is False
         assert manager.primary_provider == provider1.name
+
+
+class DelayedProvider(WeatherProvider):
+    """Test provider that answers after a delay"""
+
+    def __init__(self, name: str, delay: float, result: dict | None) -> None:
+        super().__init__(name)
+        self.delay = delay
+        self.result = result
+        self.calls = 0
+
+    def fetch_weather_data(
+        self,
+        lat: float,  # noqa: ARG002
+        lon: float,  # noqa: ARG002
+        tz_name: str | None = None,  # noqa: ARG002
+    ) -> dict[str, Any] | None:
+        return None
+
+    def process_weather_data(
+        self,
+        raw_data: dict[str, Any],  # noqa: ARG002
+        location_name: str | None = None,  # noqa: ARG002
+        tz_name: str | None = None,  # noqa: ARG002
+    ) -> dict[str, Any] | None:
+        return None
+
+    def get_weather(
+        self,
+        lat: float,  # noqa: ARG002
+        lon: float,  # noqa: ARG002
+        location_name: str | None = None,  # noqa: ARG002
+        tz_name: str | None = None,  # noqa: ARG002
+    ) -> dict[str, Any] | None:
+        self.calls += 1
+        time.sleep(self.delay)
+        return self.result
+
+    def health_check(self) -> bool:
+        return self.result is not None
+
+
+class TestProviderCacheTtl:
+    """Test providers declaring how long their data stays current"""
+
+    def test_providers_declare_ttls(self) -> None:
+        """Test each provider's TTL, with the hybrid as fresh as its faster half"""
+        open_meteo = OpenMeteoProvider()
+        pirate_weather = PirateWeatherProvider('test-key')
+        hybrid = HybridWeatherProvider(pirate_weather, open_meteo)
+
+        assert open_meteo.cache_ttl == OPEN_METEO_CACHE_TTL
+        assert hybrid.cache_ttl == PIRATE_WEATHER_CACHE_TTL
+        assert open_meteo.get_provider_info()['cache_ttl'] == OPEN_METEO_CACHE_TTL
+
+    def test_manager_picks_first_named_ttl(self) -> None:
+        """Test the answering provider's TTL wins, skipping unknown names"""
+        manager = WeatherProviderManager()
+        manager.add_provider(OpenMeteoProvider(), is_primary=True)
+        manager.add_provider(PirateWeatherProvider('test-key'))
+
+        assert manager.cache_ttl('PirateWeather', 'OpenMeteo') == (
+            PIRATE_WEATHER_CACHE_TTL
+        )
+        assert manager.cache_ttl('Unknown', 'OpenMeteo') == OPEN_METEO_CACHE_TTL
+        assert manager.cache_ttl(None, 'Unknown') is None
+
+
+class TestLatencyBudget:
+    """Test latency budget enforcement in the WeatherProviderManager"""
+
+    def make_manager(
+        self, primary: WeatherProvider, fallback: WeatherProvider, hedge: float
+    ) -> WeatherProviderManager:
+        """Build a budgeted manager with one primary and one fallback"""
+        manager = WeatherProviderManager()
+        manager.add_provider(primary, is_primary=True)
+        manager.add_provider(fallback)
+        manager.latency_budget = TEST_BUDGET
+        manager.hedge_delay = hedge
+        return manager
+
+    def test_fast_primary_skips_fallback(self) -> None:
+        """Test a quick primary answer never starts the fallback"""
+        primary = DelayedProvider('Primary', 0, {'source': 'primary'})
+        fallback = DelayedProvider('Fallback', 0, {'source': 'fallback'})
+        manager = self.make_manager(primary, fallback, TEST_HEDGE_DELAY)
+
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result == {'source': 'primary'}
+        assert fallback.calls == 0
+        assert manager.get_latency_metrics()['outcomes']['primary'] == 1
+
+    def test_slow_primary_races_fallback(self) -> None:
+        """Test a slow primary is hedged and the fallback answer wins"""
+        primary = DelayedProvider('Primary', SLOW_SECONDS, {'source': 'primary'})
+        fallback = DelayedProvider('Fallback', 0, {'source': 'fallback'})
+        manager = self.make_manager(primary, fallback, TEST_HEDGE_DELAY)
+
+        start = time.monotonic()
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result == {'source': 'fallback'}
+        assert time.monotonic() - start < SLOW_SECONDS
+        assert manager.get_latency_metrics()['last_outcome'] == 'fallback'
+
+    def test_failed_primary_starts_fallback_immediately(self) -> None:
+        """Test a primary failure doesn't wait for the hedge delay"""
+        primary = DelayedProvider('Primary', 0, None)
+        fallback = DelayedProvider('Fallback', 0, {'source': 'fallback'})
+        manager = self.make_manager(primary, fallback, SLOW_SECONDS)
+
+        start = time.monotonic()
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result == {'source': 'fallback'}
+        assert time.monotonic() - start < TEST_BUDGET
+
+    def test_budget_exceeded(self) -> None:
+        """Test the manager gives up once the budget runs out"""
+        primary = DelayedProvider('Primary', SLOW_SECONDS, {'source': 'primary'})
+        fallback = DelayedProvider('Fallback', SLOW_SECONDS, {'source': 'fallback'})
+        manager = self.make_manager(primary, fallback, TEST_HEDGE_DELAY)
+
+        start = time.monotonic()
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is None
+        assert time.monotonic() - start < SLOW_SECONDS
+        metrics = manager.get_provider_info()['latency']
+        assert metrics['outcomes']['budget_exceeded'] == 1
+        assert metrics['budget_seconds'] == TEST_BUDGET
+
+    def test_without_threads_falls_back_in_turn(self) -> None:
+        """Test a runtime without threads tries the chain one provider at a time"""
+        primary = DelayedProvider('Primary', 0, None)
+        fallback = DelayedProvider('Fallback', 0, {'source': 'fallback'})
+        manager = self.make_manager(primary, fallback, SLOW_SECONDS)
+
+        with patch('weather_core.runtime.CAN_THREAD', False):
+            result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result == {'source': 'fallback'}
+        assert (primary.calls, fallback.calls) == (1, 1)
+        assert manager.get_latency_metrics()['last_outcome'] == 'fallback'
+
+
+class TestProviderHealth:
+    """Test provider health tracking, failover and failback"""
+
+    def make_manager(self) -> tuple[WeatherProviderManager, DelayedProvider]:
+        """A manager whose primary is down and whose fallback answers"""
+        primary = DelayedProvider('Primary', 0, None)
+        manager = WeatherProviderManager()
+        manager.add_provider(primary, is_primary=True)
+        manager.add_provider(DelayedProvider('Fallback', 0, {'source': 'fallback'}))
+        return manager, primary
+
+    def test_failing_primary_fails_over(self) -> None:
+        """Test a primary that keeps failing stops being tried first"""
+        manager, primary = self.make_manager()
+
+        for _ in range(WeatherProviderManager.FAILURE_THRESHOLD + 2):
+            assert manager.get_weather(CHICAGO_LAT, CHICAGO_LON) == {
+                'source': 'fallback'
+            }
+
+        assert primary.calls == WeatherProviderManager.FAILURE_THRESHOLD
+        assert manager.primary_provider == 'Fallback'
+        assert manager.preferred_primary == 'Primary'
+        assert manager.fallback_providers == ['Primary']
+
+    def test_recovered_primary_promoted_back(self) -> None:
+        """Test the configured primary returns after enough passing probes"""
+        manager, primary = self.make_manager()
+        for _ in range(WeatherProviderManager.FAILURE_THRESHOLD):
+            manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        primary.result = {'source': 'primary'}
+        manager.run_health_checks()
+        assert manager.primary_provider == 'Fallback'
+        manager.run_health_checks()
+
+        assert manager.primary_provider == 'Primary'
+        assert manager.fallback_providers == ['Fallback']
+        assert manager.get_weather(CHICAGO_LAT, CHICAGO_LON) == {'source': 'primary'}
+
+    def test_availability_score(self) -> None:
+        """Test provider info reports the share of recent successes"""
+        manager, _ = self.make_manager()
+        manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        health = manager.get_provider_info()['health']
+
+        assert health['Primary']['availability'] == 0
+        assert health['Primary']['healthy'] is True
+        assert health['Fallback']['availability'] == 1
+        assert health['Fallback']['last_success'] is not None
+
+    def test_busy_healthy_provider_not_probed(self) -> None:
+        """Test a provider that just answered a request skips its probe"""
+        manager, _ = self.make_manager()
+        fallback = manager.providers['Fallback']
+        manager.record_outcome('Fallback', True)
+
+        with (
+            patch.object(fallback, 'health_check') as fallback_probe,
+            patch.object(manager.providers['Primary'], 'health_check') as probe,
+        ):
+            manager.run_health_checks()
+
+        fallback_probe.assert_not_called()
+        probe.assert_called_once()
+
+
+def blend_document(
+    temp: int, icon: str, direction: int, hourly_temps: list[int]
+) -> dict[str, Any]:
+    """A member's processed weather document for blending"""
+    return {
+        'current': {
+            'temperature': temp,
+            'icon': icon,
+            'wind_direction': direction,
+            'humidity': None,
+        },
+        'hourly': [
+            {'time': f'2024-01-01T{12 + index}:00', 'temp': hour_temp}
+            for index, hour_temp in enumerate(hourly_temps)
+        ],
+        'daily': [{'date': '2024-01-01', 'h': temp + 5, 'l': temp - 5}],
+        'sun': {'2024-01-01': {'sunrise': '07:00'}},
+        'provider': 'Member',
+    }
+
+
+class TestBlendProvider:
+    """Test the consensus provider blending several members"""
+
+    def make_blend(self, method: str = 'median') -> BlendProvider:
+        """A blend of three members that disagree"""
+        return BlendProvider(
+            [
+                DelayedProvider('A', 0, blend_document(70, 'rain', 350, [70, 71])),
+                DelayedProvider('B', 0, blend_document(72, 'cloudy', 10, [72, 73])),
+                DelayedProvider('C', 0, blend_document(80, 'rain', 20, [74])),
+            ],
+            method,
+        )
+
+    def test_current_conditions_blended(self) -> None:
+        """Test numbers take the median, icons the vote, bearings wrap around"""
+        result = self.make_blend().get_weather(CHICAGO_LAT, CHICAGO_LON, 'Chicago')
+
+        assert result is not None
+        current = result['current']
+        assert current['temperature'] == BLEND_MEDIAN_TEMP
+        assert current['icon'] == 'rain'
+        assert current['wind_direction'] == BLEND_BEARING
+        assert current['humidity'] is None
+        assert result['provider'] == 'Blend (A + B + C)'
+        assert result['blend'] == {'method': 'median', 'members': ['A', 'B', 'C']}
+        assert result['location'] == 'Chicago'
+
+    def test_mean_method(self) -> None:
+        """Test the mean method averages instead"""
+        result = self.make_blend('mean').get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is not None
+        assert result['current']['temperature'] == BLEND_MEAN_TEMP
+
+    def test_forecasts_lined_up_by_time(self) -> None:
+        """Test hourly entries blend only with entries for the same hour"""
+        result = self.make_blend().get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is not None
+        assert [hour['temp'] for hour in result['hourly']] == [72, 72]
+        assert result['daily'][0]['h'] == BLEND_MEDIAN_TEMP + 5
+        # Unblendable sections come from the first member
+        assert result['sun'] == {'2024-01-01': {'sunrise': '07:00'}}
+
+    def test_failed_member_left_out(self) -> None:
+        """Test the blend covers only the members that answered"""
+        blend = self.make_blend()
+        blend.members[1].result = None
+
+        result = blend.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is not None
+        assert result['blend']['members'] == ['A', 'C']
+        assert result['current']['temperature'] == BLEND_PAIR_TEMP
+
+    def test_without_threads(self) -> None:
+        """Test members are fetched in turn where threads aren't available"""
+        with patch('weather_core.runtime.CAN_THREAD', False):
+            result = self.make_blend().get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is not None
+        assert result['current']['temperature'] == BLEND_MEDIAN_TEMP
+
+    def test_all_members_failing(self) -> None:
+        """Test a blend with no answers has no data"""
+        blend = self.make_blend()
+        for member in blend.members:
+            member.result = None
+
+        assert blend.get_weather(CHICAGO_LAT, CHICAGO_LON) is None
+
+    def test_selectable_as_primary(self) -> None:
+        """Test the blend registers and switches in like any provider"""
+        blend = self.make_blend()
+        manager = WeatherProviderManager()
+        for member in blend.members:
+            manager.add_provider(member, is_primary=member.name == 'A')
+        manager.add_provider(blend)
+
+        assert manager.switch_provider('Blend')
+        assert manager.primary_provider == 'Blend'
+        assert manager.get_provider_info()['providers']['Blend']['members'] == [
+            'A',
+            'B',
+            'C',
+        ]
+
+    def test_unknown_method(self) -> None:
+        """Test an unknown blend method is refused"""
+        with pytest.raises(ValueError, match='blend method'):
+            BlendProvider([], 'mode')
+
+
+class TestRequestCoalescing:
+    """Test concurrent misses for one location sharing an upstream fetch"""
+
+    def test_concurrent_callers_share_one_fetch(self) -> None:
+        """Test only one caller reaches the provider and all get their own copy"""
+        provider = DelayedProvider('Slow', TEST_BUDGET, {'location': 'Chicago'})
+        manager = WeatherProviderManager()
+        manager.add_provider(provider, is_primary=True)
+
+        with ThreadPoolExecutor(max_workers=CONCURRENT_CALLERS) as executor:
+            results = list(
+                executor.map(
+                    lambda index: manager.get_weather(
+                        CHICAGO_LAT, CHICAGO_LON, f'Kiosk {index}'
+                    ),
+                    range(CONCURRENT_CALLERS),
+                )
+            )
+
+        assert provider.calls == 1
+        assert manager.coalesced_requests == CONCURRENT_CALLERS - 1
+        assert len({id(result) for result in results}) == CONCURRENT_CALLERS
+        assert manager.in_flight == {}
+        # Followers are relabeled; the leader keeps what its provider returned
+        assert sum(result['location'] == 'Chicago' for result in results) == 1
+
+    def test_later_calls_fetch_again(self) -> None:
+        """Test a finished fetch isn't reused - that's the cache's job"""
+        provider = DelayedProvider('Fast', 0, None)
+        manager = WeatherProviderManager()
+        manager.add_provider(provider, is_primary=True)
+
+        assert manager.get_weather(CHICAGO_LAT, CHICAGO_LON) is None
+        assert manager.get_weather(CHICAGO_LAT, CHICAGO_LON) is None
+        assert provider.calls == 2  # noqa: PLR2004
+
+
+class TestSplitFetch:
+    """Test fetching single sections of the weather document"""
+
+    @patch('weather_core.providers.requests.get')
+    def test_open_meteo_requests_only_needed_blocks(
+        self, mock_get: MagicMock, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test a daily fetch leaves current, minutely and hourly out of the request"""
+        daily_only = {'daily': mock_open_meteo_response['daily']}
+        mock_response = MagicMock()
+        mock_response.json.return_value = daily_only
+        mock_get.return_value = mock_response
+
+        result = OpenMeteoProvider().get_weather_section(
+            CHICAGO_LAT, CHICAGO_LON, 'daily', 'Chicago'
+        )
+
+        params = mock_get.call_args[1]['params']
+        assert 'daily' in params
+        for block in ('current', 'minutely_15', 'hourly'):
+            assert block not in params
+        assert result is not None
+        assert set(result) == {'daily', 'sun', 'location', 'provider'}
+        assert result['daily'][0]['h'] == DAILY_HIGH
+
+    def test_extract_weather_section(self, mock_weather_data: dict[str, Any]) -> None:
+        """Test sections keep their companion fields and the provider tag"""
+        current = extract_weather_section(
+            {**mock_weather_data, 'minutely': {'next_hour': []}}, 'current'
+        )
+
+        assert set(current) == {'current', 'minutely', 'location', 'provider'}
+
+    def test_manager_falls_back_per_section(self) -> None:
+        """Test a failed section fetch moves on to the fallback provider"""
+        primary = MagicMock()
+        primary.name = 'Primary'
+        primary.get_weather_section.return_value = None
+        fallback = MagicMock()
+        fallback.name = 'Fallback'
+        fallback.get_weather_section.return_value = {'hourly': []}
+
+        manager = WeatherProviderManager()
+        manager.add_provider(primary, is_primary=True)
+        manager.add_provider(fallback)
+
+        result = manager.get_weather_section(CHICAGO_LAT, CHICAGO_LON, 'hourly')
+
+        assert result == {'hourly': []}
+        fallback.get_weather_section.assert_called_once_with(
+            CHICAGO_LAT, CHICAGO_LON, 'hourly', None, None
+        )
+
+
+class TestProviderCapabilities:
+    """Test filling fields the answering provider lacks and noting their source"""
+
+    def make_manager(
+        self, primary_result: dict | None = None
+    ) -> tuple[WeatherProviderManager, DelayedProvider, DelayedProvider]:
+        """A current-only primary and a full-forecast fallback"""
+        primary = DelayedProvider(
+            'Nowcast',
+            0,
+            primary_result
+            or {'current': {'temperature': MOCK_TEMP, 'uv_index': None}, 'hourly': []},
+        )
+        primary.capabilities = frozenset({'current'})
+        fallback = DelayedProvider(
+            'Forecast',
+            0,
+            {
+                'current': {'temperature': 60, 'uv_index': MOCK_UV_INDEX},
+                'minutely': {'next_hour': [0.1]},
+                'hourly': [{'time': '12:00', 'temp': 70}],
+                'daily': [{'date': '2024-01-01', 'h': DAILY_HIGH}],
+            },
+        )
+        fallback.capabilities = frozenset(
+            {'current', 'minutely', 'hourly', 'daily', 'uv'}
+        )
+        manager = WeatherProviderManager()
+        manager.add_provider(primary, is_primary=True)
+        manager.add_provider(fallback)
+        return manager, primary, fallback
+
+    def test_missing_fields_filled(self) -> None:
+        """Test gaps are filled from one fetch of the provider declaring them"""
+        manager, _, fallback = self.make_manager()
+
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is not None
+        assert result['current']['temperature'] == MOCK_TEMP
+        assert result['current']['uv_index'] == MOCK_UV_INDEX
+        assert result['minutely'] == {'next_hour': [0.1]}
+        assert result['hourly'] == [{'time': '12:00', 'temp': 70}]
+        assert result['daily'][0]['h'] == DAILY_HIGH
+        assert fallback.calls == 1
+        assert result['provenance'] == {
+            'current': 'Nowcast',
+            'current.uv_index': 'Forecast',
+            'minutely': 'Forecast',
+            'hourly': 'Forecast',
+            'daily': 'Forecast',
+        }
+
+    def test_complete_answer_untouched(self) -> None:
+        """Test no other provider is asked when nothing is missing"""
+        manager, _, fallback = self.make_manager(
+            {
+                'current': {'temperature': MOCK_TEMP, 'uv_index': 3},
+                'minutely': {'next_hour': []},
+                'hourly': [{'time': '12:00', 'temp': 71}],
+                'pressure_trend': {'trend': 'steady'},
+                'daily': [{'date': '2024-01-01', 'h': 75}],
+                'sun': {'2024-01-01': {'sunrise': '07:00'}},
+            }
+        )
+
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is not None
+        assert set(result['provenance'].values()) == {'Nowcast'}
+        assert fallback.calls == 0
+
+    def test_fallback_answer_keeps_its_provenance(self) -> None:
+        """Test a fallback's answer is attributed to it, not the primary"""
+        manager, primary, _ = self.make_manager()
+        primary.result = None
+
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is not None
+        assert set(result['provenance'].values()) == {'Forecast'}
+
+    def test_undeclared_providers_not_asked(self) -> None:
+        """Test providers that declare nothing aren't used to fill gaps"""
+        manager, _, fallback = self.make_manager()
+        fallback.capabilities = frozenset()
+
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result is not None
+        assert result['hourly'] == []
+        assert fallback.calls == 0
+
+    def test_section_fetch_skips_unsupported(self) -> None:
+        """Test a section is only requested from providers declaring it"""
+        manager, primary, fallback = self.make_manager()
+
+        result = manager.get_weather_section(CHICAGO_LAT, CHICAGO_LON, 'daily')
+
+        assert result is not None
+        assert result['daily'][0]['h'] == DAILY_HIGH
+        assert primary.calls == 0
+        assert fallback.calls == 1
+
+    def test_capabilities_in_provider_info(self) -> None:
+        """Test declared capabilities are listed for each provider"""
+        info = OpenMeteoProvider().get_provider_info()
+
+        assert info['capabilities'] == ['current', 'daily', 'hourly', 'minutely', 'uv']
+        assert 'alerts' not in PirateWeatherProvider('test-key').capabilities
+
+
+class TestProviderQuotas:
+    """Test providers out of quota being skipped for the next fallback"""
+
+    def test_exhausted_primary_skipped(self) -> None:
+        """Test a spent primary isn't called and doesn't count as failing"""
+        primary = DelayedProvider('Metered', 0, {'location': 'Metered'})
+        primary.quota = ProviderQuota(per_day=1)
+        primary.quota.acquire()
+        fallback = DelayedProvider('Free', 0, {'location': 'Free'})
+        manager = WeatherProviderManager()
+        manager.add_provider(primary, is_primary=True)
+        manager.add_provider(fallback)
+
+        result = manager.get_weather(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result == {'location': 'Free'}
+        assert primary.calls == 0
+        assert manager.health['Metered'].consecutive_failures == 0
+        quota = manager.get_provider_info()['providers']['Metered']['quota']
+        assert quota['remaining_day'] == 0
+        assert quota['used_today'] == 1
+
+    def test_hybrid_needs_both_halves(self) -> None:
+        """Test the hybrid is out of quota when either half is"""
+        pirate_weather = PirateWeatherProvider('test-key')
+        hybrid = HybridWeatherProvider(pirate_weather, OpenMeteoProvider())
+        assert hybrid.has_quota()
+
+        pirate_weather.quota = ProviderQuota(per_minute=0)
+
+        assert not hybrid.has_quota()
+        assert hybrid.get_provider_info()['quota'] is None
+
+    @patch('weather_core.http_client.requests.get')
+    def test_provider_refuses_calls_over_quota(self, mock_get: MagicMock) -> None:
+        """Test a provider past its quota returns no data without calling out"""
+        provider = OpenMeteoProvider()
+        provider.quota = ProviderQuota(per_minute=0)
+
+        assert provider.get_weather(CHICAGO_LAT, CHICAGO_LON) is None
+        mock_get.assert_not_called()
+        assert provider.quota.refused == 1
+
+
+class TestProviderConfig:
+    """Test building and ordering providers from their configuration"""
+
+    def test_configured_order(self) -> None:
+        """Test the first enabled entry is primary, disabled ones are left out"""
+        manager = WeatherProviderManager()
+        manager.configure(
+            [
+                ProviderSettings('OpenMeteo', enabled=False),
+                ProviderSettings('PirateWeather', api_key='key', timeout=4),
+                ProviderSettings('OpenMeteo', name='Backup', quota='5/minute'),
+            ]
+        )
+
+        assert manager.primary_provider == 'PirateWeather'
+        assert manager.fallback_providers == ['Backup']
+        assert manager.providers['PirateWeather'].timeout == 4  # noqa: PLR2004
+        backup = manager.providers['Backup']
+        assert isinstance(backup, OpenMeteoProvider)
+        assert backup.quota is not None
+        assert backup.quota.per_minute == 5  # noqa: PLR2004
+
+    def test_composites_share_configured_providers(self) -> None:
+        """Test the hybrid and blend reuse providers listed after them"""
+        manager = WeatherProviderManager()
+        manager.configure(
+            [
+                ProviderSettings('Hybrid'),
+                ProviderSettings('OpenMeteo'),
+                ProviderSettings('PirateWeather', api_key='key'),
+                ProviderSettings(
+                    'Blend', options={'members': ['OpenMeteo', 'PirateWeather']}
+                ),
+            ]
+        )
+
+        hybrid = manager.providers['Hybrid']
+        assert isinstance(hybrid, HybridWeatherProvider)
+        assert manager.primary_provider == 'Hybrid'
+        assert hybrid.open_meteo is manager.providers['OpenMeteo']
+        assert hybrid.pirate_weather is manager.providers['PirateWeather']
+        assert manager.fallback_providers == ['OpenMeteo', 'PirateWeather', 'Blend']
+
+    def test_unusable_entries_skipped(self) -> None:
+        """Test entries that can't be built are skipped, not fatal"""
+        manager = WeatherProviderManager()
+        manager.configure(
+            [
+                ProviderSettings('PirateWeather'),
+                ProviderSettings('AccuWeather'),
+                ProviderSettings('Blend', options={'members': ['OpenMeteo']}),
+                ProviderSettings('OpenMeteo'),
+            ]
+        )
+
+        assert list(manager.providers) == ['OpenMeteo']
+        assert manager.primary_provider == 'OpenMeteo'
+
+    def test_reconfigure_swaps_tables(self) -> None:
+        """Test a reload replaces the providers without touching the old tables"""
+        manager = WeatherProviderManager()
+        manager.configure([ProviderSettings('OpenMeteo')])
+        old_providers = manager.providers
+        old_provider = old_providers['OpenMeteo']
+
+        assert manager.reconfigure(
+            [
+                ProviderSettings('PirateWeather', api_key='new-key'),
+                ProviderSettings('OpenMeteo'),
+            ]
+        )
+
+        assert manager.primary_provider == 'PirateWeather'
+        assert manager.preferred_primary == 'PirateWeather'
+        assert manager.fallback_providers == ['OpenMeteo']
+        assert manager.providers['OpenMeteo'] is not old_provider
+        # A request that already looked up its provider finishes with it
+        assert old_providers == {'OpenMeteo': old_provider}
+
+    def test_reconfigure_keeps_providers_when_none_build(self) -> None:
+        """Test a reload leaving nothing usable is refused"""
+        manager = WeatherProviderManager()
+        manager.configure([ProviderSettings('OpenMeteo')])
+
+        assert not manager.reconfigure([ProviderSettings('PirateWeather')])
+        assert manager.primary_provider == 'OpenMeteo'
+
+
+class TestDryRun:
+    """Test running one provider end to end for debugging"""
+
+    @patch('weather_core.http_client.requests.get')
+    def test_raw_and_processed(
+        self, mock_get: MagicMock, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test the upstream payload comes back beside the processed document"""
+        mock_get.return_value.json.return_value = mock_open_meteo_response
+        mock_get.return_value.status_code = 200
+
+        result = OpenMeteoProvider().dry_run(CHICAGO_LAT, CHICAGO_LON, 'Chicago')
+
+        assert result['ok'] is True
+        assert result['error'] is None
+        assert result['raw'] == mock_open_meteo_response
+        assert result['processed']['current']['temperature'] == MOCK_TEMP
+        assert set(result['timing']) == {'fetch_ms', 'process_ms', 'total_ms'}
+
+    @patch('weather_core.http_client.requests.get')
+    def test_unusable_payload_reported(
+        self, mock_get: MagicMock, mock_open_meteo_response: dict[str, Any]
+    ) -> None:
+        """Test a payload that fails mapping is returned with the error"""
+        del mock_open_meteo_response['current']['temperature_2m']
+        mock_get.return_value.json.return_value = mock_open_meteo_response
+        mock_get.return_value.status_code = 200
+
+        result = OpenMeteoProvider().dry_run(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result['ok'] is False
+        assert result['error'] == 'Processing returned no data'
+        assert result['raw'] == mock_open_meteo_response
+        assert result['processed'] is None
+
+    def test_no_upstream_data(self) -> None:
+        """Test a provider that fetched nothing says so"""
+        result = DelayedProvider('Empty', 0, None).dry_run(CHICAGO_LAT, CHICAGO_LON)
+
+        assert result['error'] == 'Upstream returned no data'
+        assert result['raw'] is None
+
+
+class TestMockProvider:
+    """Test simulated weather for development and demos"""
+
+    def test_same_seed_same_weather(self) -> None:
+        """Test a seed repeats its weather and another seed changes it"""
+
+        def temperatures(seed: int) -> list[int]:
+            result = MockProvider(seed).get_weather(CHICAGO_LAT, CHICAGO_LON)
+            assert result is not None
+            return [hour['temp'] for hour in result['hourly']]
+
+        assert temperatures(1) == temperatures(1)
+        assert temperatures(1) != temperatures(2)
+
+    def test_document_shape(self) -> None:
+        """Test the document has every section a real provider fills"""
+        result = MockProvider().get_weather(CHICAGO_LAT, CHICAGO_LON, 'Chicago')
+
+        assert result is not None
+        assert len(result['hourly']) == 24  # noqa: PLR2004
+        assert len(result['daily']) == 7  # noqa: PLR2004
+        assert result['current']['summary']
+        assert result['pressure_trend']['trend']
+        assert set(result['sun']) >= {day['date'] for day in result['daily']}
+        assert (result['location'], result['provider']) == ('Chicago', 'Mock')
+        assert not result['hourly'][0]['t'].startswith('0')
+
+    def test_extreme_scenarios(self) -> None:
+        """Test blizzards are cold, windy and snowing and heat waves are hot"""
+        blizzard = MockProvider(scenario='blizzard').get_weather(
+            CHICAGO_LAT, CHICAGO_LON
+        )
+        heat_wave = MockProvider().with_scenario('heat_wave').get_weather(
+            CHICAGO_LAT, CHICAGO_LON
+        )
+
+        assert blizzard is not None
+        assert heat_wave is not None
+        assert blizzard['scenario'] == 'blizzard'
+        assert max(day['h'] for day in blizzard['daily']) < 32  # noqa: PLR2004
+        assert blizzard['current']['precipitation_type'] == 'snow'
+        assert blizzard['current']['feels_like'] < blizzard['current']['temperature']
+        assert min(day['h'] for day in heat_wave['daily']) > 95  # noqa: PLR2004
+        assert heat_wave['current']['precipitation_rate'] == 0
+
+    def test_built_from_config(self) -> None:
+        """Test the Mock type takes seed and scenario options, checking both"""
+        provider = build_provider(
+            ProviderSettings('Mock', options={'seed': 3, 'scenario': 'rain'}), {}
+        )
+
+        assert isinstance(provider, MockProvider)
+        assert (provider.seed, provider.scenario) == (3, 'rain')
+        with pytest.raises(ValueError, match='Unknown scenario'):
+            build_provider(ProviderSettings('Mock', options={'scenario': 'x'}), {})
+        with pytest.raises(ValueError, match='whole number'):
+            build_provider(ProviderSettings('Mock', options={'seed': '3'}), {})
+        assert set(MOCK_SCENARIOS) >= {'normal', 'blizzard', 'heat_wave'}
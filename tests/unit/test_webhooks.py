@@ -0,0 +1,169 @@
+"""ABOUTME: Test webhook subscriptions for condition changes
+ABOUTME: Covers validation, change detection against baselines, templates and signing"""
+
+import hashlib
+import hmac
+import json
+from pathlib import Path
+from typing import Any
+from unittest.mock import MagicMock, patch
+
+import pytest
+
+from webhooks import (
+    SIGNATURE_HEADER,
+    WebhookSubscriptions,
+    deliver,
+    render_template,
+    validate_subscription,
+)
+
+
+# Test constants
+HOOK_URL = 'https://automation.example.com/hooks/weather'
+TEMPERATURE_DELTA = 5
+COLD = 60
+WARM = 66
+DRIFT = 63
+
+
+def make_subscription(**overrides: Any) -> dict[str, Any]:
+    """A valid subscription request"""
+    return {
+        'name': 'Porch lights',
+        'url': HOOK_URL,
+        'lat': 41.8781,
+        'lon': -87.6298,
+        'location': 'Chicago',
+        'temperature_delta': TEMPERATURE_DELTA,
+        **overrides,
+    }
+
+
+def conditions(temperature: float = COLD, rate: float = 0) -> dict[str, Any]:
+    """A snake_case weather document with just current conditions"""
+    return {
+        'location': 'Chicago',
+        'current': {
+            'temperature': temperature,
+            'precipitation_rate': rate,
+            'precipitation_type': 'rain' if rate else None,
+            'summary': 'Overcast',
+            'icon': 'cloudy',
+        },
+    }
+
+
+class TestValidation:
+    """Test subscription requests are checked and normalized"""
+
+    def test_defaults(self) -> None:
+        """Test precipitation is watched unless turned off"""
+        subscription = validate_subscription(make_subscription())
+
+        assert subscription['temperature_delta'] == TEMPERATURE_DELTA
+        assert subscription['precipitation'] is True
+        assert subscription['template'] is None
+
+    def test_rejects(self) -> None:
+        """Test unusable URLs, deltas, triggers and template fields are refused"""
+        bad = [
+            ({'url': 'ftp://example.com/hook'}, 'http'),
+            ({'temperature_delta': 0}, 'positive'),
+            ({'temperature_delta': None, 'precipitation': False}, 'nothing to watch'),
+            ({'lat': 91}, 'out of range'),
+            ({'template': {'text': '$temprature'}}, 'temprature'),
+            ({'template': 'plain'}, 'object or array'),
+        ]
+        for overrides, message in bad:
+            with pytest.raises(ValueError, match=message):
+                validate_subscription(make_subscription(**overrides))
+
+
+class TestChangeDetection:
+    """Test events fire on changes past the deltas, once each"""
+
+    def test_temperature_swing(self, tmp_path: Path) -> None:
+        """Test the first check sets a baseline and slow drifts add up to a swing"""
+        store = WebhookSubscriptions(str(tmp_path / 'webhooks.json'))
+        subscription = store.create(make_subscription(precipitation=False))
+
+        assert store.check(subscription['id'], conditions(COLD)) == []
+        assert store.check(subscription['id'], conditions(DRIFT)) == []
+        events = store.check(subscription['id'], conditions(WARM))
+
+        assert [event['event'] for event in events] == ['temperature_swing']
+        assert events[0]['previous_temperature'] == COLD
+        assert events[0]['change'] == WARM - COLD
+        assert events[0]['location'] == 'Chicago'
+        assert store.check(subscription['id'], conditions(WARM)) == []
+
+    def test_precipitation_starts_and_stops(self, tmp_path: Path) -> None:
+        """Test precipitation starting and stopping each fire once"""
+        store = WebhookSubscriptions(str(tmp_path / 'webhooks.json'))
+        subscription = store.create(make_subscription(temperature_delta=None))
+
+        store.check(subscription['id'], conditions())
+        started = store.check(subscription['id'], conditions(rate=0.1))
+        still = store.check(subscription['id'], conditions(rate=0.2))
+        stopped = store.check(subscription['id'], conditions())
+
+        assert [event['event'] for event in started] == ['precipitation_started']
+        assert started[0]['precipitation_type'] == 'rain'
+        assert still == []
+        assert [event['event'] for event in stopped] == ['precipitation_stopped']
+
+    def test_persists_without_secrets_in_listing(self, tmp_path: Path) -> None:
+        """Test subscriptions and baselines survive a restart; listings hide secrets"""
+        path = str(tmp_path / 'data' / 'webhooks.json')
+        store = WebhookSubscriptions(path)
+        subscription = store.create(make_subscription())
+        store.check(subscription['id'], conditions(COLD))
+
+        reloaded = WebhookSubscriptions(path)
+        listed = reloaded.list_subscriptions()
+        assert [saved['id'] for saved in listed] == [subscription['id']]
+        assert 'secret' not in listed[0]
+        assert listed[0]['baseline']['temperature'] == COLD
+        assert reloaded.get(subscription['id'])['secret'] == subscription['secret']
+        assert reloaded.delete(subscription['id']) is True
+        assert reloaded.delete(subscription['id']) is False
+
+
+class TestDelivery:
+    """Test payload templates and signed requests"""
+
+    def test_render_template(self) -> None:
+        """Test lone placeholders keep their type and others become text"""
+        event = {'event': 'temperature_swing', 'temperature': 66, 'summary': None}
+        template = {
+            'text': '$event: now ${temperature}°F $summary',
+            'values': ['$temperature', 'literal $$5'],
+        }
+
+        assert render_template(template, event) == {
+            'text': 'temperature_swing: now 66°F ',
+            'values': [66, 'literal $5'],
+        }
+
+    @patch('webhooks.requests.post')
+    def test_signed_post(self, mock_post: MagicMock, tmp_path: Path) -> None:
+        """Test the body is signed with the subscription's secret"""
+        store = WebhookSubscriptions(str(tmp_path / 'webhooks.json'))
+        subscription = store.create(
+            make_subscription(template={'temp': '$temperature'})
+        )
+        mock_post.return_value.status_code = 204
+
+        status = deliver(subscription, {'event': 'test', 'temperature': 66})
+
+        kwargs = mock_post.call_args[1]
+        body = kwargs['data']
+        expected = hmac.new(
+            subscription['secret'].encode(), body, hashlib.sha256
+        ).hexdigest()
+        assert status == 204  # noqa: PLR2004
+        assert mock_post.call_args[0][0] == HOOK_URL
+        assert json.loads(body) == {'temp': 66}
+        assert kwargs['headers'][SIGNATURE_HEADER] == f'sha256={expected}'
+        mock_post.return_value.raise_for_status.assert_called_once()
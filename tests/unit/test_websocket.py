@@ -5,11 +5,24 @@ from unittest.mock import MagicMock, patch
 
 from flask_socketio import SocketIOTestClient
 
-from main import CHICAGO_LAT, CHICAGO_LON, app, socketio
+from main import (
+    CHICAGO_LAT,
+    CHICAGO_LON,
+    REALTIME_NAMESPACE,
+    alerts_cache,
+    app,
+    realtime_subscriptions,
+    socketio,
+    store_weather_data,
+    weather_cache,
+)
 
 
 # Test constants
 EXPECTED_MESSAGE_COUNT = 2
+PATCHED_TEMP = 55
+CHICAGO_KEY = '41.8781,-87.6298'
+CHICAGO_LOCATION = {'lat': 41.8781, 'lon': -87.6298, 'location': 'Chicago'}
 
 
 class TestWebSocketHandlers:
@@ -59,7 +72,10 @@ class TestWebSocketHandlers:
             (r for r in received if r['name'] == 'weather_update'), None
         )
         assert weather_update is not None
-        assert weather_update['args'][0] == mock_weather_data
+        payload = weather_update['args'][0]
+        assert 'server_time' in payload
+        payload.pop('server_time')
+        assert payload == mock_weather_data
 
     @patch('main.weather_manager.get_weather')
     def test_handle_weather_update_request_with_defaults(
@@ -108,3 +124,122 @@ class TestWebSocketHandlers:
         # Should only receive provider_info on connect, no weather_update
         weather_updates = [r for r in received if r['name'] == 'weather_update']
         assert len(weather_updates) == 0
+
+
+class TestRealtimeChannel:
+    """Test the /ws live-update channel"""
+
+    def setup_method(self) -> None:
+        """Clear caches before each test"""
+        weather_cache.clear()
+        alerts_cache.clear()
+
+    def connect(self) -> SocketIOTestClient:
+        """Connect a test client to the live-update namespace"""
+        return SocketIOTestClient(app, socketio, namespace=REALTIME_NAMESPACE)
+
+    @patch('main.weather_manager.get_weather')
+    def test_subscribe_sends_full_snapshot(self, mock_get_weather: MagicMock) -> None:
+        """Test subscribing sends the current document for each location"""
+        mock_get_weather.return_value = {'current': {'temperature': 72}}
+
+        client = self.connect()
+        client.emit('subscribe', {'locations': [CHICAGO_LOCATION]}, namespace='/ws')
+        received = client.get_received(REALTIME_NAMESPACE)
+
+        patch_message = next(r for r in received if r['name'] == 'weather_patch')
+        payload = patch_message['args'][0]
+        assert payload['location'] == CHICAGO_KEY
+        assert payload['patch'] == [
+            {'op': 'replace', 'path': '', 'value': {'current': {'temperature': 72}}}
+        ]
+
+        subscribed = next(r for r in received if r['name'] == 'subscribed')
+        assert subscribed['args'][0]['locations'] == [CHICAGO_KEY]
+        client.disconnect(namespace=REALTIME_NAMESPACE)
+
+    @patch('main.weather_manager.get_weather')
+    def test_updates_arrive_as_patches(self, mock_get_weather: MagicMock) -> None:
+        """Test fresh weather data reaches subscribers as a minimal patch"""
+        mock_get_weather.return_value = {'current': {'temperature': 72}}
+
+        client = self.connect()
+        client.emit('subscribe', {'locations': [CHICAGO_LOCATION]}, namespace='/ws')
+        client.get_received(REALTIME_NAMESPACE)
+
+        store_weather_data(CHICAGO_KEY, {'current': {'temperature': PATCHED_TEMP}})
+        received = client.get_received(REALTIME_NAMESPACE)
+
+        assert len(received) == 1
+        assert received[0]['args'][0]['patch'] == [
+            {'op': 'replace', 'path': '/current/temperature', 'value': PATCHED_TEMP}
+        ]
+        client.disconnect(namespace=REALTIME_NAMESPACE)
+
+    @patch('main.nws_provider.get_weather')
+    @patch('main.weather_manager.get_weather')
+    def test_new_alerts_arrive_as_patches(
+        self, mock_get_weather: MagicMock, mock_get_alerts: MagicMock
+    ) -> None:
+        """Test newly fetched alerts are pushed to subscribers"""
+        mock_get_weather.return_value = {'current': {'temperature': 72}}
+        alert = {'id': 'urn:alert/1', 'type': 'Heat Advisory'}
+        mock_get_alerts.return_value = {
+            'alerts': {'active_count': 1, 'alerts': [alert], 'has_warnings': False}
+        }
+
+        client = self.connect()
+        client.emit('subscribe', {'locations': [CHICAGO_LOCATION]}, namespace='/ws')
+        client.get_received(REALTIME_NAMESPACE)
+
+        app.test_client().get('/api/weather/alerts?lat=41.8781&lon=-87.6298')
+        received = client.get_received(REALTIME_NAMESPACE)
+
+        assert received[0]['args'][0]['patch'] == [
+            {'op': 'add', 'path': '/alerts', 'value': {'urn:alert/1': alert}}
+        ]
+        client.disconnect(namespace=REALTIME_NAMESPACE)
+
+    @patch('main.weather_manager.get_weather')
+    def test_unsubscribe_and_disconnect_clear_subscriptions(
+        self, mock_get_weather: MagicMock
+    ) -> None:
+        """Test subscriptions are dropped on unsubscribe and on disconnect"""
+        mock_get_weather.return_value = {'current': {'temperature': 72}}
+
+        client = self.connect()
+        client.emit('subscribe', {'locations': [CHICAGO_LOCATION]}, namespace='/ws')
+        client.emit('unsubscribe', {'locations': [CHICAGO_LOCATION]}, namespace='/ws')
+        received = client.get_received(REALTIME_NAMESPACE)
+
+        unsubscribed = next(r for r in received if r['name'] == 'unsubscribed')
+        assert unsubscribed['args'][0]['locations'] == []
+
+        client.emit('subscribe', {'locations': [CHICAGO_LOCATION]}, namespace='/ws')
+        assert realtime_subscriptions.subscribers(CHICAGO_KEY)
+        client.disconnect(namespace=REALTIME_NAMESPACE)
+        assert not realtime_subscriptions.subscribers(CHICAGO_KEY)
+
+    def test_invalid_locations_are_ignored(self) -> None:
+        """Test out-of-range or malformed coordinates are skipped"""
+        client = self.connect()
+        client.emit(
+            'subscribe',
+            {'locations': [{'lat': 200, 'lon': 0}, {'lat': 'north'}]},
+            namespace='/ws',
+        )
+        received = client.get_received(REALTIME_NAMESPACE)
+
+        subscribed = next(r for r in received if r['name'] == 'subscribed')
+        assert subscribed['args'][0]['locations'] == []
+        client.disconnect(namespace=REALTIME_NAMESPACE)
+
+    def test_ping_pong(self) -> None:
+        """Test keepalive pings get a pong back"""
+        client = self.connect()
+        client.emit('ping', namespace='/ws')
+        received = client.get_received(REALTIME_NAMESPACE)
+
+        assert received[0]['name'] == 'pong'
+        assert 'timestamp' in received[0]['args'][0]
+        client.disconnect(namespace=REALTIME_NAMESPACE)
@@ -0,0 +1,130 @@
+# ABOUTME: Page themes as CSS custom property tokens rendered into the templates
+# ABOUTME: Chosen by ?theme=, the visitor's saved preference, or prefers-color-scheme
+
+from typing import Any
+
+
+# Tokens every theme defines; components read them as var(--name)
+THEMES: dict[str, dict[str, Any]] = {
+    'default': {
+        'label': 'Blue',
+        'tokens': {
+            'bg-primary': (
+                'linear-gradient(135deg, #1e3a8a 0%, #3b82f6 50%, #60a5fa 100%)'
+            ),
+            'text-primary': 'white',
+            'card-bg': 'rgba(255, 255, 255, 0.1)',
+            'card-border': 'rgba(255, 255, 255, 0.2)',
+            'error-color': '#fca5a5',
+            'connection-bg-connected': '#10b981',
+            'connection-bg-polling': '#f59e0b',
+            'connection-bg-disconnected': '#ef4444',
+        },
+    },
+    'light': {
+        'label': 'Light',
+        # Component styles predate the theme names and still key off these values
+        'attribute': 'white',
+        'tokens': {
+            'bg-primary': '#ffffff',
+            'text-primary': '#000000',
+            'card-bg': 'rgba(0, 0, 0, 0.05)',
+            'card-border': 'rgba(0, 0, 0, 0.1)',
+            'error-color': '#dc2626',
+            'connection-bg-connected': '#10b981',
+            'connection-bg-polling': '#f59e0b',
+            'connection-bg-disconnected': '#ef4444',
+        },
+    },
+    'dark': {
+        'label': 'Dark',
+        'tokens': {
+            'bg-primary': '#111827',
+            'text-primary': '#f9fafb',
+            'card-bg': 'rgba(255, 255, 255, 0.05)',
+            'card-border': 'rgba(255, 255, 255, 0.1)',
+            'error-color': '#f87171',
+            'connection-bg-connected': '#059669',
+            'connection-bg-polling': '#d97706',
+            'connection-bg-disconnected': '#dc2626',
+        },
+    },
+    'high-contrast': {
+        'label': 'High contrast',
+        'tokens': {
+            'bg-primary': '#000000',
+            'text-primary': '#ffffff',
+            'card-bg': '#000000',
+            'card-border': '#ffffff',
+            'error-color': '#ffff00',
+            'connection-bg-connected': '#008000',
+            'connection-bg-polling': '#806000',
+            'connection-bg-disconnected': '#c00000',
+        },
+    },
+    'eink': {
+        'label': 'E-ink',
+        'attribute': 'dashboard',
+        # High contrast, no gradients or translucency for eInk displays
+        'tokens': {
+            'bg-primary': '#eeeeee',
+            'text-primary': '#000000',
+            'card-bg': '#ffffff',
+            'card-border': '#000000',
+            'error-color': '#000000',
+            'connection-bg-connected': '#000000',
+            'connection-bg-polling': '#000000',
+            'connection-bg-disconnected': '#000000',
+        },
+    },
+}
+# 'auto' follows the browser's prefers-color-scheme: the default theme, or dark
+AUTO_THEME = 'auto'
+AUTO_DARK_THEME = 'dark'
+THEME_NAMES = (AUTO_THEME, *THEMES)
+
+# Older and alternate names still accepted in ?theme= and saved preferences
+THEME_ALIASES = {
+    'white': 'light',
+    'dashboard': 'eink',
+    'e-ink': 'eink',
+    'contrast': 'high-contrast',
+}
+
+
+def resolve_theme(name: str | None) -> str | None:
+    """The canonical theme for a name or alias, or None if it isn't one"""
+    if not name:
+        return None
+    name = name.strip().lower()
+    name = THEME_ALIASES.get(name, name)
+    return name if name in THEME_NAMES else None
+
+
+def theme_attribute(theme: str) -> str | None:
+    """The body's data-theme value for a theme (none for auto)"""
+    if theme == AUTO_THEME:
+        return None
+    return str(THEMES[theme].get('attribute', theme))
+
+
+def token_block(selector: str, theme: str) -> str:
+    """A CSS rule setting a theme's tokens as custom properties"""
+    declarations = ' '.join(
+        f'--{name}: {value};' for name, value in THEMES[theme]['tokens'].items()
+    )
+    return f'{selector} {{ {declarations} }}'
+
+
+def theme_stylesheet() -> str:
+    """CSS for every theme, keyed on the body's data-theme attribute"""
+    rules = [token_block(':root', 'default')]
+    rules.append(
+        '@media (prefers-color-scheme: dark) { '
+        f'{token_block("body:not([data-theme])", AUTO_DARK_THEME)} }}'
+    )
+    rules.extend(
+        token_block(f'[data-theme="{theme_attribute(theme)}"]', theme)
+        for theme in THEMES
+    )
+    return '\n'.join(rules)
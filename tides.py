@@ -0,0 +1,122 @@
+# ABOUTME: High/low tide predictions from NOAA CO-OPS for the nearest US tide station
+# ABOUTME: Station metadata from the CO-OPS metadata API, tides from its datagetter
+
+import math
+from datetime import datetime, timedelta, timezone
+from typing import Any
+
+import requests
+
+
+NOAA_STATIONS_URL = (
+    'https://api.tidesandcurrents.noaa.gov/mdapi/prod/webapi/stations.json'
+)
+NOAA_DATAGETTER_URL = 'https://api.tidesandcurrents.noaa.gov/api/prod/datagetter'
+NOAA_TIMEOUT_SECONDS = 15
+TIDE_DATUM = 'MLLW'  # Heights are relative to mean lower low water
+TIDE_PREDICTION_HOURS = 72
+MAX_STATION_DISTANCE_KM = 100  # Further than this, the station's tides don't apply
+EARTH_RADIUS_KM = 6371.0
+TIDE_TYPES = {'H': 'high', 'L': 'low'}
+
+
+def distance_km(lat1: float, lon1: float, lat2: float, lon2: float) -> float:
+    """Great-circle distance between two points (haversine)"""
+    phi1, phi2 = math.radians(lat1), math.radians(lat2)
+    d_phi = phi2 - phi1
+    d_lambda = math.radians(lon2 - lon1)
+    a = (
+        math.sin(d_phi / 2) ** 2
+        + math.cos(phi1) * math.cos(phi2) * math.sin(d_lambda / 2) ** 2
+    )
+    return 2 * EARTH_RADIUS_KM * math.asin(math.sqrt(a))
+
+
+def fetch_tide_stations() -> list[dict[str, Any]]:
+    """Every CO-OPS station that publishes tide predictions"""
+    response = requests.get(
+        NOAA_STATIONS_URL,
+        params={'type': 'tidepredictions'},
+        timeout=NOAA_TIMEOUT_SECONDS,
+    )
+    response.raise_for_status()
+    stations = []
+    for station in response.json().get('stations', []):
+        try:
+            lat, lon = float(station['lat']), float(station['lng'])
+        except (KeyError, TypeError, ValueError):
+            continue
+        stations.append(
+            {
+                'id': str(station['id']),
+                'name': station.get('name'),
+                'state': station.get('state') or None,
+                'lat': lat,
+                'lon': lon,
+            }
+        )
+    return stations
+
+
+def nearest_station(
+    stations: list[dict[str, Any]], lat: float, lon: float
+) -> dict[str, Any] | None:
+    """The closest station within MAX_STATION_DISTANCE_KM, with its distance"""
+    best = None
+    best_distance = MAX_STATION_DISTANCE_KM
+    for station in stations:
+        distance = distance_km(lat, lon, station['lat'], station['lon'])
+        if distance <= best_distance:
+            best, best_distance = station, distance
+    if best is None:
+        return None
+    return {**best, 'distance_km': round(best_distance, 1)}
+
+
+def fetch_tide_predictions(station_id: str, start: datetime) -> list[dict[str, Any]]:
+    """High and low tides (feet above MLLW) from a UTC day through the window"""
+    response = requests.get(
+        NOAA_DATAGETTER_URL,
+        params={
+            'product': 'predictions',
+            'interval': 'hilo',
+            'datum': TIDE_DATUM,
+            'station': station_id,
+            'begin_date': start.strftime('%Y%m%d'),
+            # A day of slack so the window still fits late in the UTC day
+            'range': TIDE_PREDICTION_HOURS + 24,
+            'time_zone': 'gmt',
+            'units': 'english',
+            'format': 'json',
+            'application': 'weather-dashboard',
+        },
+        timeout=NOAA_TIMEOUT_SECONDS,
+    )
+    response.raise_for_status()
+    data = response.json()
+    if 'error' in data:
+        msg = data['error'].get('message', 'NOAA CO-OPS error')
+        raise ValueError(msg)
+
+    return [
+        {
+            'time': datetime.strptime(prediction['t'], '%Y-%m-%d %H:%M')
+            .replace(tzinfo=timezone.utc)
+            .isoformat(),
+            'type': TIDE_TYPES.get(prediction.get('type', ''), 'unknown'),
+            'height_ft': round(float(prediction['v']), 2),
+        }
+        for prediction in data.get('predictions', [])
+    ]
+
+
+def upcoming_tides(
+    predictions: list[dict[str, Any]], now: datetime
+) -> list[dict[str, Any]]:
+    """The predictions falling in the next TIDE_PREDICTION_HOURS"""
+    until = now + timedelta(hours=TIDE_PREDICTION_HOURS)
+    return [
+        prediction
+        for prediction in predictions
+        if now <= datetime.fromisoformat(prediction['time']) <= until
+    ]
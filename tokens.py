@@ -0,0 +1,192 @@
+# ABOUTME: Admin and read API tokens with expiry and revocation, persisted to disk or
+# ABOUTME: set in config; several can be valid at once so leaked ones rotate smoothly
+
+import hashlib
+import json
+import os
+import re
+import secrets
+import threading
+import time
+from typing import Any
+
+
+TOKEN_SCOPES = ('read', 'admin')
+SHA256_HEX = re.compile(r'[0-9a-f]{64}')
+
+
+def hash_token(secret: str) -> str:
+    """Hash a token secret - only hashes are ever written to disk"""
+    return hashlib.sha256(secret.encode()).hexdigest()
+
+
+def parse_api_keys(raw: str) -> list[dict[str, Any]]:
+    """API_KEYS (a JSON list of {name, scope, key or key_sha256}) as token records
+
+    key_sha256 keeps the secret itself out of the config; keys given in the
+    clear are hashed on the way in like any other token.
+    """
+    if not raw.strip():
+        return []
+    entries = json.loads(raw)
+    if not isinstance(entries, list):
+        msg = 'API_KEYS must be a JSON list'
+        raise ValueError(msg)
+    records: dict[str, dict[str, Any]] = {}
+    for entry in entries:
+        name = entry.get('name') if isinstance(entry, dict) else None
+        if not isinstance(name, str) or not name.strip():
+            msg = 'Every API key needs a name'
+            raise ValueError(msg)
+        name = name.strip()
+        scope = entry.get('scope', 'read')
+        if scope not in TOKEN_SCOPES:
+            msg = f'API key {name!r} has unknown scope {scope!r}'
+            raise ValueError(msg)
+        key, key_hash = entry.get('key'), entry.get('key_sha256')
+        if isinstance(key, str) and key:
+            token_hash = hash_token(key)
+        elif isinstance(key_hash, str) and SHA256_HEX.fullmatch(key_hash.lower()):
+            token_hash = key_hash.lower()
+        else:
+            msg = f'API key {name!r} needs a key or a 64-digit hex key_sha256'
+            raise ValueError(msg)
+        if f'config:{name}' in records:
+            msg = f'API key names must be unique: {name!r}'
+            raise ValueError(msg)
+        records[f'config:{name}'] = {
+            'id': f'config:{name}',
+            'name': name,
+            'scope': scope,
+            'hash': token_hash,
+            'created_at': None,
+            'expires_at': None,
+            'revoked_at': None,
+            'source': 'config',
+        }
+    return list(records.values())
+
+
+class TokenStore:
+    """Concurrently valid API tokens, saved as JSON so they survive restarts"""
+
+    def __init__(self, path: str) -> None:
+        self.path = path
+        self.tokens: dict[str, dict[str, Any]] = {}
+        # Keys from API_KEYS: checked like the rest but never saved or revoked
+        self.configured: dict[str, dict[str, Any]] = {}
+        self._lock = threading.Lock()
+        self.load()
+
+    def configure(self, records: list[dict[str, Any]]) -> None:
+        """Replace the keys set in config (see parse_api_keys)"""
+        with self._lock:
+            self.configured = {record['id']: record for record in records}
+
+    def load(self) -> None:
+        """Load saved tokens, starting empty if the file is missing or unreadable"""
+        try:
+            with open(self.path, encoding='utf-8') as f:
+                records = json.load(f)
+        except FileNotFoundError:
+            return
+        except (OSError, ValueError) as e:
+            print(f'❌ Could not read token store {self.path}: {str(e)}')
+            return
+        with self._lock:
+            self.tokens = {record['id']: record for record in records}
+
+    def save(self) -> None:
+        """Write tokens atomically (lock held)"""
+        directory = os.path.dirname(self.path)
+        if directory:
+            os.makedirs(directory, exist_ok=True)
+        temp_path = f'{self.path}.tmp'
+        with open(temp_path, 'w', encoding='utf-8') as f:
+            json.dump(list(self.tokens.values()), f, indent=2)
+        os.replace(temp_path, self.path)
+
+    def create(
+        self, name: str, scope: str, expires_in: float | None = None
+    ) -> tuple[dict[str, Any], str]:
+        """Create a token and return its public record plus the one-time secret"""
+        if scope not in TOKEN_SCOPES:
+            msg = f"Unknown token scope '{scope}'"
+            raise ValueError(msg)
+
+        secret = secrets.token_urlsafe(32)
+        now = time.time()
+        record = {
+            'id': secrets.token_hex(4),
+            'name': name,
+            'scope': scope,
+            'hash': hash_token(secret),
+            'created_at': now,
+            'expires_at': now + expires_in if expires_in else None,
+            'revoked_at': None,
+        }
+        with self._lock:
+            self.tokens[record['id']] = record
+            self.save()
+        return self.public_record(record), secret
+
+    def revoke(self, token_id: str) -> bool:
+        """Revoke a token by id, returning False if it doesn't exist
+
+        Keys set in config can only be removed there; trying raises ValueError.
+        """
+        with self._lock:
+            if token_id in self.configured:
+                msg = f'{token_id} is set in API_KEYS - remove it there'
+                raise ValueError(msg)
+            record = self.tokens.get(token_id)
+            if record is None:
+                return False
+            if record['revoked_at'] is None:
+                record['revoked_at'] = time.time()
+                self.save()
+            return True
+
+    def verify(self, secret: str, scope: str) -> dict[str, Any] | None:
+        """Find the live token for a secret granting the scope (admin covers read)"""
+        token_hash = hash_token(secret)
+        now = time.time()
+        with self._lock:
+            for record in [*self.configured.values(), *self.tokens.values()]:
+                if not secrets.compare_digest(record['hash'], token_hash):
+                    continue
+                if record['revoked_at'] is not None:
+                    return None
+                if record['expires_at'] is not None and record['expires_at'] <= now:
+                    return None
+                if scope == 'admin' and record['scope'] != 'admin':
+                    return None
+                return self.public_record(record)
+        return None
+
+    def has_active(self, scope: str) -> bool:
+        """Check whether any unrevoked, unexpired token has exactly this scope"""
+        now = time.time()
+        with self._lock:
+            return any(
+                record['scope'] == scope
+                and record['revoked_at'] is None
+                and (record['expires_at'] is None or record['expires_at'] > now)
+                for record in [*self.configured.values(), *self.tokens.values()]
+            )
+
+    def list_tokens(self) -> list[dict[str, Any]]:
+        """List every token's public record, configured keys then newest first"""
+        with self._lock:
+            records = sorted(
+                self.tokens.values(), key=lambda r: r['created_at'], reverse=True
+            )
+            return [
+                self.public_record(record)
+                for record in [*self.configured.values(), *records]
+            ]
+
+    @staticmethod
+    def public_record(record: dict[str, Any]) -> dict[str, Any]:
+        """Token metadata that is safe to show - everything but the hash"""
+        return {key: value for key, value in record.items() if key != 'hash'}
@@ -0,0 +1,200 @@
+# ABOUTME: Rolling statistics over stored observations - ranges, rates of change and
+# ABOUTME: sudden jumps - shaped as chart-ready series like the forecast charts
+
+import math
+import statistics
+from datetime import datetime, timezone
+from typing import Any
+
+
+# Window name: (length in seconds, span of the rolling mean in seconds)
+TREND_WINDOWS = {'24h': (24 * 3600, 3 * 3600), '7d': (7 * 86400, 24 * 3600)}
+
+# (field in the hourly history, label, unit)
+TREND_FIELDS = [
+    ('temperature', 'Temperature', '°F'),
+    ('pressure', 'Pressure', 'hPa'),
+    ('humidity', 'Humidity', '%'),
+    ('wind_speed', 'Wind speed', 'mph'),
+]
+
+# Rates of change are over the latest three hours, the usual pressure tendency
+TENDENCY_SECONDS = 3 * 3600
+
+# A reading this many standard deviations off the mean of the readings just
+# before it is an anomaly, once there are enough of them to judge by
+ANOMALY_SIGMAS = 3
+MIN_BASELINE_POINTS = 3
+MAD_TO_SIGMA = 1.4826  # Median absolute deviation to standard deviation
+
+
+def mean(values: list[float]) -> float:
+    """The average of some readings"""
+    return sum(values) / len(values)
+
+
+def rolling_mean(
+    timestamps: list[int], values: list[float | None], span: int
+) -> list[float | None]:
+    """Each point's average over the span of time ending at it"""
+    averages: list[float | None] = []
+    for stamp in timestamps:
+        window = [
+            value
+            for other, value in zip(timestamps, values, strict=True)
+            if stamp - span < other <= stamp and value is not None
+        ]
+        averages.append(round(mean(window), 2) if window else None)
+    return averages
+
+
+def rate_per_hour(timestamps: list[int], values: list[float | None]) -> float | None:
+    """Least-squares slope per hour over the latest TENDENCY_SECONDS"""
+    if not timestamps:
+        return None
+    points = [
+        (stamp / 3600, value)
+        for stamp, value in zip(timestamps, values, strict=True)
+        if value is not None and stamp > timestamps[-1] - TENDENCY_SECONDS
+    ]
+    if len(points) < 2:  # noqa: PLR2004
+        return None
+    mean_x = mean([x for x, _ in points])
+    mean_y = mean([y for _, y in points])
+    spread = sum((x - mean_x) ** 2 for x, _ in points)
+    if spread == 0:
+        return None
+    slope = sum((x - mean_x) * (y - mean_y) for x, y in points) / spread
+    return round(slope, 3)
+
+
+def find_anomalies(
+    timestamps: list[int], values: list[float | None], span: int
+) -> list[dict[str, Any]]:
+    """Readings that jump well away from the ones in the span just before them
+
+    How far is "well away" is judged against the typical jump across the
+    window, so a steady diurnal swing isn't flagged but a sudden one is.
+    """
+    deviations: list[tuple[int, float, float]] = []
+    for index, stamp in enumerate(timestamps):
+        value = values[index]
+        baseline = [
+            earlier
+            for other, earlier in zip(timestamps[:index], values[:index], strict=True)
+            if stamp - span <= other and earlier is not None
+        ]
+        if value is None or len(baseline) < MIN_BASELINE_POINTS:
+            continue
+        expected = mean(baseline)
+        deviations.append((stamp, value, expected))
+    if len(deviations) < MIN_BASELINE_POINTS:
+        return []
+
+    # The median jump resists the very spikes being looked for; a window of
+    # identical readings falls back to the root mean square
+    residuals = [abs(value - expected) for _, value, expected in deviations]
+    sigma = statistics.median(residuals) * MAD_TO_SIGMA or math.sqrt(
+        mean([residual**2 for residual in residuals])
+    )
+    if sigma == 0:
+        return []
+    return [
+        {
+            'time': iso_time(stamp),
+            'value': value,
+            'expected': round(expected, 2),
+            'sigmas': round((value - expected) / sigma, 1),
+        }
+        for stamp, value, expected in deviations
+        if abs(value - expected) > ANOMALY_SIGMAS * sigma
+    ]
+
+
+def iso_time(stamp: int) -> str:
+    """A Unix time as an ISO 8601 UTC string"""
+    return datetime.fromtimestamp(stamp, tz=timezone.utc).isoformat()
+
+
+def field_stats(
+    timestamps: list[int], values: list[float | None], unit: str
+) -> dict[str, Any]:
+    """Range, average and change of one field across the window"""
+    known = [value for value in values if value is not None]
+    if not known:
+        return {
+            'min': None,
+            'max': None,
+            'mean': None,
+            'change': None,
+            'rate_per_hour': None,
+            'unit': unit,
+        }
+    return {
+        'min': min(known),
+        'max': max(known),
+        'mean': round(mean(known), 2),
+        'change': round(known[-1] - known[0], 2),
+        'rate_per_hour': rate_per_hour(timestamps, values),
+        'unit': unit,
+    }
+
+
+def build_trends(hours: dict[int, dict[str, Any]], window: str) -> dict[str, Any]:
+    """Series, statistics and anomalies for hourly points, ready to plot"""
+    _, span = TREND_WINDOWS[window]
+    timestamps = sorted(hours)
+    labels = [iso_time(stamp) for stamp in timestamps]
+    series: dict[str, list[float | None]] = {}
+    stats = {}
+    anomalies = {}
+    for field, _, unit in TREND_FIELDS:
+        values = [hours[stamp].get(field) for stamp in timestamps]
+        series[field] = values
+        series[f'{field}_rolling_mean'] = rolling_mean(timestamps, values, span)
+        stats[field] = field_stats(timestamps, values, unit)
+        anomalies[field] = find_anomalies(timestamps, values, span)
+
+    return {
+        'window': window,
+        'labels': labels,
+        'timestamps': timestamps,
+        'series': series,
+        'stats': stats,
+        'anomalies': anomalies,
+        # new Chart(ctx, {data: chartjs}) - one axis per field
+        'chartjs': {
+            'labels': labels,
+            'datasets': [
+                dataset
+                for field, label, _ in TREND_FIELDS
+                for dataset in (
+                    {'label': label, 'data': series[field], 'yAxisID': field},
+                    {
+                        'label': f'{label} (rolling mean)',
+                        'data': series[f'{field}_rolling_mean'],
+                        'yAxisID': field,
+                    },
+                )
+            ],
+        },
+        # new uPlot({series, ...}, data) - x values are Unix seconds
+        'uplot': {
+            'data': [
+                timestamps,
+                *(
+                    series[key]
+                    for field, _, _ in TREND_FIELDS
+                    for key in (field, f'{field}_rolling_mean')
+                ),
+            ],
+            'series': [
+                {'label': 'Time'},
+                *(
+                    {'label': name, 'scale': field}
+                    for field, label, _ in TREND_FIELDS
+                    for name in (label, f'{label} (rolling mean)')
+                ),
+            ],
+        },
+    }
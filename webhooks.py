@@ -0,0 +1,355 @@
+# ABOUTME: Webhook subscriptions that POST when a tracked location's conditions change
+# ABOUTME: beyond their deltas - temperature swings, precipitation starting or stopping
+
+import hashlib
+import hmac
+import json
+import os
+import re
+import secrets
+import threading
+import time
+from datetime import datetime, timezone
+from string import Template
+from typing import Any
+from urllib.parse import urlsplit
+
+import requests
+
+
+WEBHOOK_EVENTS = ('temperature_swing', 'precipitation_started', 'precipitation_stopped')
+DEFAULT_TEMPERATURE_DELTA = 10  # °F, the same swing the change feeds report
+WEBHOOK_TIMEOUT_SECONDS = 10
+# HMAC-SHA256 of the request body with the subscription's secret
+SIGNATURE_HEADER = 'X-Weather-Signature'
+# What $placeholders in a payload template can name
+TEMPLATE_FIELDS = (
+    'event',
+    'subscription',
+    'subscription_id',
+    'location',
+    'lat',
+    'lon',
+    'temperature',
+    'previous_temperature',
+    'change',
+    'precipitation_type',
+    'precipitation_rate',
+    'summary',
+    'icon',
+    'triggered_at',
+)
+MAX_LATITUDE = 90
+MAX_LONGITUDE = 180
+
+
+def template_placeholders(template: Any) -> set[str]:
+    """Every $name or ${name} in a template's strings, however deeply nested"""
+    if isinstance(template, str):
+        return {
+            match.group('named') or match.group('braced')
+            for match in Template.pattern.finditer(template)
+            if match.group('named') or match.group('braced')
+        }
+    if isinstance(template, dict):
+        values = list(template.values())
+    elif isinstance(template, list):
+        values = template
+    else:
+        return set()
+    return set().union(*(template_placeholders(value) for value in values))
+
+
+def render_template(template: Any, event: dict[str, Any]) -> Any:
+    """A payload template with the event's fields filled in
+
+    A string that is nothing but one placeholder takes the field's value as is,
+    so "$temperature" stays a JSON number; other strings get text, with
+    missing values left empty.
+    """
+    if isinstance(template, dict):
+        return {key: render_template(value, event) for key, value in template.items()}
+    if isinstance(template, list):
+        return [render_template(value, event) for value in template]
+    if not isinstance(template, str):
+        return template
+    whole = re.fullmatch(r'\$(\w+)|\$\{(\w+)\}', template)
+    if whole:
+        return event.get(whole.group(1) or whole.group(2))
+    text = {
+        name: '' if event.get(name) is None else str(event[name])
+        for name in TEMPLATE_FIELDS
+    }
+    return Template(template).safe_substitute(text)
+
+
+def validate_subscription(raw: Any) -> dict[str, Any]:
+    """Check a submitted subscription and normalize it, raising ValueError"""
+    if not isinstance(raw, dict):
+        msg = 'Webhook must be a JSON object'
+        raise ValueError(msg)  # noqa: TRY004
+
+    name = raw.get('name')
+    if not isinstance(name, str) or not name.strip():
+        msg = 'Webhook name is required'
+        raise ValueError(msg)
+    url = raw.get('url')
+    parts = urlsplit(url) if isinstance(url, str) else None
+    if parts is None or parts.scheme not in ('http', 'https') or not parts.netloc:
+        msg = 'url must be an http(s) URL'
+        raise ValueError(msg)
+
+    numbers = {}
+    for field in ('lat', 'lon'):
+        value = raw.get(field)
+        if isinstance(value, bool) or not isinstance(value, int | float):
+            msg = f'{field} must be a number'
+            raise ValueError(msg)
+        numbers[field] = float(value)
+    if abs(numbers['lat']) > MAX_LATITUDE or abs(numbers['lon']) > MAX_LONGITUDE:
+        msg = 'lat/lon are out of range'
+        raise ValueError(msg)
+
+    delta = raw.get('temperature_delta', DEFAULT_TEMPERATURE_DELTA)
+    if delta is not None and (
+        isinstance(delta, bool) or not isinstance(delta, int | float) or delta <= 0
+    ):
+        msg = 'temperature_delta must be a positive number of °F, or null'
+        raise ValueError(msg)
+    precipitation = raw.get('precipitation', True)
+    if not isinstance(precipitation, bool):
+        msg = 'precipitation must be true or false'
+        raise ValueError(msg)  # noqa: TRY004
+    if delta is None and not precipitation:
+        msg = 'Webhook has nothing to watch: set temperature_delta or precipitation'
+        raise ValueError(msg)
+
+    template = raw.get('template')
+    if template is not None:
+        if not isinstance(template, dict | list):
+            msg = 'template must be a JSON object or array'
+            raise ValueError(msg)
+        unknown = sorted(template_placeholders(template) - set(TEMPLATE_FIELDS))
+        if unknown:
+            msg = (
+                f'Unknown template fields: {", ".join(unknown)} '
+                f'(have: {", ".join(TEMPLATE_FIELDS)})'
+            )
+            raise ValueError(msg)
+
+    location = raw.get('location')
+    return {
+        'name': name.strip(),
+        'url': url,
+        'lat': numbers['lat'],
+        'lon': numbers['lon'],
+        'location': location if isinstance(location, str) and location else None,
+        'temperature_delta': float(delta) if delta is not None else None,
+        'precipitation': precipitation,
+        'template': template,
+    }
+
+
+def detect_changes(
+    subscription: dict[str, Any], current: dict[str, Any]
+) -> tuple[list[str], dict[str, Any]]:
+    """The events current conditions set off, and the baseline to keep after
+
+    The first conditions seen only set the baseline. The temperature baseline
+    moves only when a swing is reported, so slow drifts add up to one.
+    """
+    baseline = dict(subscription.get('baseline') or {})
+    events = []
+    temperature = current.get('temperature')
+    previous = baseline.get('temperature')
+    delta = subscription['temperature_delta']
+    if isinstance(temperature, int | float):
+        if previous is None:
+            baseline['temperature'] = temperature
+        elif delta is not None and abs(temperature - previous) >= delta:
+            events.append('temperature_swing')
+            baseline['temperature'] = temperature
+
+    precipitating = bool(current.get('precipitation_rate'))
+    was_precipitating = baseline.get('precipitating')
+    baseline['precipitating'] = precipitating
+    if subscription['precipitation'] and was_precipitating is not None:
+        if precipitating and not was_precipitating:
+            events.append('precipitation_started')
+        elif was_precipitating and not precipitating:
+            events.append('precipitation_stopped')
+    return events, baseline
+
+
+def build_event(
+    event: str,
+    subscription: dict[str, Any],
+    weather_data: dict[str, Any],
+    previous_temperature: float | None = None,
+) -> dict[str, Any]:
+    """The default payload for an event (snake_case weather document)"""
+    current = weather_data.get('current') or {}
+    temperature = current.get('temperature')
+    change = (
+        temperature - previous_temperature
+        if isinstance(temperature, int | float) and previous_temperature is not None
+        else None
+    )
+    return {
+        'event': event,
+        'subscription': subscription['name'],
+        'subscription_id': subscription['id'],
+        'location': subscription['location'] or weather_data.get('location'),
+        'lat': subscription['lat'],
+        'lon': subscription['lon'],
+        'temperature': temperature,
+        'previous_temperature': previous_temperature,
+        'change': change,
+        'precipitation_type': current.get('precipitation_type'),
+        'precipitation_rate': current.get('precipitation_rate'),
+        'summary': current.get('summary'),
+        'icon': current.get('icon'),
+        'triggered_at': datetime.now(timezone.utc).isoformat(),
+    }
+
+
+def sign(body: bytes, secret: str) -> str:
+    """The signature header value for a request body"""
+    digest = hmac.new(secret.encode('utf-8'), body, hashlib.sha256).hexdigest()
+    return f'sha256={digest}'
+
+
+def deliver(subscription: dict[str, Any], event: dict[str, Any]) -> int:
+    """POST an event to the subscription's URL, returning the response status
+
+    The body is the rendered template, or the event itself without one.
+    Raises requests' exceptions on network failures and error statuses.
+    """
+    template = subscription.get('template')
+    payload = render_template(template, event) if template is not None else event
+    body = json.dumps(payload).encode('utf-8')
+    response = requests.post(
+        subscription['url'],
+        data=body,
+        headers={
+            'Content-Type': 'application/json',
+            SIGNATURE_HEADER: sign(body, subscription['secret']),
+        },
+        timeout=WEBHOOK_TIMEOUT_SECONDS,
+    )
+    response.raise_for_status()
+    return response.status_code
+
+
+class WebhookSubscriptions:
+    """Webhook subscriptions with their last-seen conditions, saved as JSON"""
+
+    def __init__(self, path: str) -> None:
+        self.path = path
+        self.subscriptions: dict[str, dict[str, Any]] = {}
+        self._lock = threading.Lock()
+        self.load()
+
+    def load(self) -> None:
+        """Load saved subscriptions, starting empty if the file is missing"""
+        try:
+            with open(self.path, encoding='utf-8') as f:
+                records = json.load(f)
+        except FileNotFoundError:
+            return
+        except (OSError, ValueError) as e:
+            print(f'❌ Could not read webhooks {self.path}: {str(e)}')
+            return
+        with self._lock:
+            self.subscriptions = {record['id']: record for record in records}
+
+    def save(self) -> None:
+        """Write subscriptions atomically (lock held)"""
+        directory = os.path.dirname(self.path)
+        if directory:
+            os.makedirs(directory, exist_ok=True)
+        temp_path = f'{self.path}.tmp'
+        with open(temp_path, 'w', encoding='utf-8') as f:
+            json.dump(list(self.subscriptions.values()), f, indent=2)
+        os.replace(temp_path, self.path)
+
+    def create(self, raw: Any) -> dict[str, Any]:
+        """Validate and store a new subscription, returning it with its secret"""
+        subscription = {
+            'id': secrets.token_hex(4),
+            **validate_subscription(raw),
+            'secret': secrets.token_hex(16),
+            'created_at': time.time(),
+            'baseline': {},
+            'last_delivery': None,
+        }
+        with self._lock:
+            self.subscriptions[subscription['id']] = subscription
+            self.save()
+        return dict(subscription)
+
+    def delete(self, subscription_id: str) -> bool:
+        """Remove a subscription, returning False if it doesn't exist"""
+        with self._lock:
+            if self.subscriptions.pop(subscription_id, None) is None:
+                return False
+            self.save()
+            return True
+
+    def get(self, subscription_id: str) -> dict[str, Any] | None:
+        """One subscription, secret included, for delivering to"""
+        with self._lock:
+            subscription = self.subscriptions.get(subscription_id)
+            return dict(subscription) if subscription else None
+
+    def list_subscriptions(self) -> list[dict[str, Any]]:
+        """Every subscription, oldest first, without secrets"""
+        with self._lock:
+            return sorted(
+                (
+                    {key: value for key, value in item.items() if key != 'secret'}
+                    for item in self.subscriptions.values()
+                ),
+                key=lambda item: item['created_at'],
+            )
+
+    def check(
+        self, subscription_id: str, weather_data: dict[str, Any]
+    ) -> list[dict[str, Any]]:
+        """Events since the conditions last seen, updating the saved baseline"""
+        with self._lock:
+            subscription = self.subscriptions.get(subscription_id)
+            if subscription is None:
+                return []
+            previous = (subscription.get('baseline') or {}).get('temperature')
+            names, baseline = detect_changes(
+                subscription, weather_data.get('current') or {}
+            )
+            if baseline != subscription.get('baseline'):
+                subscription['baseline'] = baseline
+                self.save()
+            snapshot = dict(subscription)
+        return [
+            build_event(
+                name,
+                snapshot,
+                weather_data,
+                previous if name == 'temperature_swing' else None,
+            )
+            for name in names
+        ]
+
+    def record_delivery(
+        self, subscription_id: str, status: int | None, error: str | None = None
+    ) -> None:
+        """Note how the latest delivery went, for the admin listing"""
+        with self._lock:
+            subscription = self.subscriptions.get(subscription_id)
+            if subscription is None:
+                return
+            subscription['last_delivery'] = {
+                'at': time.time(),
+                'status': status,
+                'error': error,
+            }
+            self.save()